@@ -0,0 +1,42 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * Kind of a {@link ContentBlock}.
+ *
+ * @see Ndb#getNoteBlocks(Transaction, long)
+ */
+public enum BlockType {
+
+    /** Plain text. */
+    TEXT(0),
+
+    /** A {@code nostr:} mention (npub, nprofile, note, nevent, naddr), or a legacy {@code #[n]} mention. */
+    MENTION(1),
+
+    /** A {@code #hashtag}. */
+    HASHTAG(2),
+
+    /** A URL. */
+    URL(3),
+
+    /** A lightning invoice. */
+    INVOICE(4);
+
+    private final int code;
+
+    BlockType(int code) {
+        this.code = code;
+    }
+
+    /**
+     * Map a native block type to a type.
+     */
+    static BlockType fromCode(int code) {
+        for (BlockType type : values()) {
+            if (type.code == code) {
+                return type;
+            }
+        }
+        throw new IllegalStateException("Unknown block type: " + code);
+    }
+}
@@ -0,0 +1,90 @@
+package xyz.tcheeric.nostrdb;
+
+import java.io.Closeable;
+
+/**
+ * A token for stopping cancellable queries and file imports early.
+ *
+ * <p>Pass the token to {@link Ndb#queryCancellable} or
+ * {@link Ndb#queryNotesCancellable} and call {@link #cancel()} from any thread,
+ * e.g. when the user navigates away from a view. The query then returns the
+ * results found so far, marked as cancelled. An import with
+ * {@link Ndb#processEventsFromFile(java.nio.file.Path, ImportProgressCallback, CancelToken, long)}
+ * or {@link Ndb#processEventsBytes(byte[], ImportProgressCallback, CancelToken)}
+ * stops before its next line in the same way, reporting the offset to resume
+ * from. A token cannot be reset; use a
+ * new one for each query that should be cancellable on its own.
+ *
+ * <p>Example usage:
+ * <pre>{@code
+ * try (CancelToken token = new CancelToken()) {
+ *     screen.onClose(token::cancel);
+ *     PartialResult<Note> notes = ndb.queryNotesCancellable(txn, filter, 5000, token);
+ *     if (!notes.isCancelled()) {
+ *         show(notes.results());
+ *     }
+ * }
+ * }</pre>
+ *
+ * <p>Close the token only once every query using it has returned;
+ * {@link #cancel()} is safe to call before and after closing.
+ */
+public final class CancelToken implements Closeable {
+
+    private final long ptr;
+    private boolean closed;
+
+    /**
+     * Create a token that has not been cancelled.
+     *
+     * @throws NostrdbException if the native token cannot be created
+     */
+    public CancelToken() {
+        this.ptr = NostrdbNative.cancelTokenNew();
+        if (this.ptr == 0) {
+            throw new NostrdbException("Failed to create cancel token");
+        }
+    }
+
+    /**
+     * Cancel every query using this token.
+     *
+     * <p>Safe to call from any thread and more than once. Does nothing once
+     * the token is closed.
+     */
+    public synchronized void cancel() {
+        if (!closed) {
+            NostrdbNative.cancelTokenCancel(ptr);
+        }
+    }
+
+    /**
+     * Check if this token has been cancelled.
+     *
+     * @return true if {@link #cancel()} was called while the token was open
+     * @throws IllegalStateException if the token is closed
+     */
+    public synchronized boolean isCancelled() {
+        checkOpen();
+        return NostrdbNative.cancelTokenIsCancelled(ptr);
+    }
+
+    synchronized long ptr() {
+        checkOpen();
+        return ptr;
+    }
+
+    private void checkOpen() {
+        if (closed) {
+            throw new IllegalStateException("CancelToken is closed");
+        }
+    }
+
+    @Override
+    public synchronized void close() {
+        if (!closed) {
+            closed = true;
+            NostrdbNative.cancelTokenFree(ptr);
+        }
+    }
+}
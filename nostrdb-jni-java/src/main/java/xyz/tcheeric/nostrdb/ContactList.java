@@ -0,0 +1,74 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
+import java.util.Collections;
+import java.util.List;
+
+/**
+ * The pubkeys an author follows, from their newest contact list (kind 3).
+ *
+ * <p>Each pubkey appears once, in the order the list first names it; tags
+ * whose pubkey is not 64 hex characters are skipped.
+ *
+ * @see Ndb#getContacts(Transaction, byte[], boolean)
+ */
+public final class ContactList {
+
+    private final List<byte[]> pubkeys;
+    private final List<String> relayHints;
+
+    private ContactList(List<byte[]> pubkeys, List<String> relayHints) {
+        this.pubkeys = pubkeys;
+        this.relayHints = relayHints;
+    }
+
+    /**
+     * Get the followed pubkeys, in list order.
+     *
+     * @return Unmodifiable list of 32-byte public keys
+     */
+    public List<byte[]> pubkeys() {
+        return pubkeys;
+    }
+
+    /**
+     * Get the relay hint of each followed pubkey, parallel to {@link #pubkeys()}.
+     *
+     * @return Unmodifiable list of relay URLs, empty strings where a contact has
+     *         none; empty if relay hints were not requested
+     */
+    public List<String> relayHints() {
+        return relayHints;
+    }
+
+    /**
+     * Parse a contact list from native byte array.
+     *
+     * <p>Format: [count:4]([pubkey:32])*, followed if requested by ([len:2][relay:len])*
+     */
+    static ContactList parse(byte[] data, boolean withRelayHints) {
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+        List<byte[]> pubkeys = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            byte[] pubkey = new byte[32];
+            buf.get(pubkey);
+            pubkeys.add(pubkey);
+        }
+        List<String> relayHints = new ArrayList<>(withRelayHints ? count : 0);
+        for (int i = 0; withRelayHints && i < count; i++) {
+            byte[] relay = new byte[Short.toUnsignedInt(buf.getShort())];
+            buf.get(relay);
+            relayHints.add(new String(relay, StandardCharsets.UTF_8));
+        }
+        return new ContactList(Collections.unmodifiableList(pubkeys), Collections.unmodifiableList(relayHints));
+    }
+
+    @Override
+    public String toString() {
+        return "ContactList{pubkeys=" + pubkeys.size() + ", relayHints=" + !relayHints.isEmpty() + '}';
+    }
+}
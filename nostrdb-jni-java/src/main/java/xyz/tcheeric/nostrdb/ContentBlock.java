@@ -0,0 +1,131 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
+import java.util.List;
+import java.util.Optional;
+
+/**
+ * One block of a note's content, as parsed by nostrdb: text, a mention, a
+ * hashtag, a URL, or a lightning invoice.
+ *
+ * <p>A note's blocks cover its content contiguously, in order, so rendering
+ * them one after another reproduces the content. A block includes its marker,
+ * e.g. {@code nostr:} or {@code #}. Offsets are in bytes of the content's UTF-8
+ * encoding, as {@link Ndb#getNoteContent(Transaction, long)} returns it; use
+ * {@link #textIn(String)} to get the block's text from a Java string.
+ *
+ * @see Ndb#getNoteBlocks(Transaction, long)
+ */
+public final class ContentBlock {
+
+    private static final int TARGET_NONE = 0;
+    private static final int TARGET_PUBKEY = 1;
+    private static final int TARGET_EVENT = 2;
+
+    private final BlockType type;
+    private final int offset;
+    private final int length;
+    private final int targetType;
+    private final byte[] target;
+
+    private ContentBlock(BlockType type, int offset, int length, int targetType, byte[] target) {
+        this.type = type;
+        this.offset = offset;
+        this.length = length;
+        this.targetType = targetType;
+        this.target = target;
+    }
+
+    /**
+     * Get the kind of block.
+     *
+     * @return The block type
+     */
+    public BlockType type() {
+        return type;
+    }
+
+    /**
+     * Get where the block starts.
+     *
+     * @return Byte offset into the UTF-8 content
+     */
+    public int offset() {
+        return offset;
+    }
+
+    /**
+     * Get the length of the block.
+     *
+     * @return Length in bytes of the UTF-8 content
+     */
+    public int length() {
+        return length;
+    }
+
+    /**
+     * Get the pubkey a mention refers to, for npub and nprofile mentions.
+     *
+     * @return 32-byte pubkey, or empty if this block mentions no pubkey
+     */
+    public Optional<byte[]> mentionedPubkey() {
+        return targetType == TARGET_PUBKEY ? Optional.of(target.clone()) : Optional.empty();
+    }
+
+    /**
+     * Get the event id a mention refers to, for note and nevent mentions.
+     *
+     * @return 32-byte event id, or empty if this block mentions no event
+     */
+    public Optional<byte[]> mentionedEventId() {
+        return targetType == TARGET_EVENT ? Optional.of(target.clone()) : Optional.empty();
+    }
+
+    /**
+     * Get the block's text from the note's content.
+     *
+     * @param content The content of the note this block belongs to
+     * @return The block's text, as it appears in the content
+     * @throws IndexOutOfBoundsException if the content is too short for the block
+     */
+    public String textIn(String content) {
+        byte[] utf8 = content.getBytes(StandardCharsets.UTF_8);
+        return new String(utf8, offset, length, StandardCharsets.UTF_8);
+    }
+
+    /**
+     * Parse blocks from native byte array.
+     *
+     * <p>Format: [count:4]([type:1][offset:4][length:4][target:1][id:32]?)*,
+     * where the id is present unless target is 0
+     */
+    static List<ContentBlock> parseResults(byte[] data) {
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+
+        List<ContentBlock> blocks = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            BlockType type = BlockType.fromCode(buf.get());
+            int offset = buf.getInt();
+            int length = buf.getInt();
+            int targetType = buf.get();
+            byte[] target = null;
+            if (targetType != TARGET_NONE) {
+                target = new byte[32];
+                buf.get(target);
+            }
+            blocks.add(new ContentBlock(type, offset, length, targetType, target));
+        }
+
+        return blocks;
+    }
+
+    @Override
+    public String toString() {
+        return "ContentBlock{type=" + type + ", offset=" + offset + ", length=" + length
+            + (target == null ? "" : ", target=" + HexUtil.encode(target)) + '}';
+    }
+}
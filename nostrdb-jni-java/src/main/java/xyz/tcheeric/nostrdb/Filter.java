@@ -2,7 +2,10 @@ package xyz.tcheeric.nostrdb;
 
 import java.io.Closeable;
 import java.nio.ByteBuffer;
-import java.nio.ByteOrder;
+import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.List;
+import java.util.OptionalLong;
 import java.util.concurrent.atomic.AtomicBoolean;
 
 /**
@@ -31,6 +34,11 @@ public final class Filter implements Closeable {
      */
     public static final int MAX_LIMIT = 100_000_000;
 
+    /**
+     * Default for how far in the future since/until timestamps may lie (10 years).
+     */
+    public static final long DEFAULT_TIMESTAMP_SLACK_SECONDS = 10L * 365 * 24 * 60 * 60;
+
     private final long ptr;
     private final AtomicBoolean closed = new AtomicBoolean(false);
 
@@ -38,6 +46,19 @@ public final class Filter implements Closeable {
         this.ptr = ptr;
     }
 
+    /**
+     * Set how far past the current time a since/until timestamp may lie before
+     * it is rejected, for all filters built afterwards. Such values are usually
+     * milliseconds passed where seconds are expected.
+     *
+     * @param seconds Allowed distance into the future (default
+     *                {@link #DEFAULT_TIMESTAMP_SLACK_SECONDS})
+     * @throws IllegalArgumentException if seconds is negative
+     */
+    public static void setTimestampSlack(long seconds) {
+        NostrdbNative.filterSetTimestampSlack(seconds);
+    }
+
     /**
      * Create a new filter builder.
      *
@@ -47,6 +68,40 @@ public final class Filter implements Closeable {
         return new Builder();
     }
 
+    /**
+     * Parse a NIP-01 filter object, e.g. the filter of a relay REQ.
+     *
+     * <p>Supports {@code ids}, {@code authors}, {@code kinds}, {@code #<letter>} tags,
+     * {@code since}, {@code until}, {@code limit}, and {@code search}.
+     *
+     * @param json The filter JSON object
+     * @return The built Filter
+     * @throws NostrdbException if the JSON is malformed, contains an unsupported field,
+     *         or describes a filter {@link Builder#build()} would reject
+     */
+    public static Filter fromJson(String json) {
+        return fromJson(json, false);
+    }
+
+    /**
+     * Parse a NIP-01 filter object, optionally rejecting an empty filter.
+     *
+     * @param json The filter JSON object
+     * @param strict If true, {@code {}} (which matches every note) is rejected
+     * @return The built Filter
+     * @throws NostrdbException as for {@link #fromJson(String)}, or if strict and the filter is empty
+     */
+    public static Filter fromJson(String json, boolean strict) {
+        if (json == null) {
+            throw new IllegalArgumentException("Filter JSON must not be null");
+        }
+        long ptr = strict ? NostrdbNative.filterFromJsonStrict(json) : NostrdbNative.filterFromJson(json);
+        if (ptr == 0) {
+            throw new NostrdbException("Failed to parse filter JSON");
+        }
+        return new Filter(ptr);
+    }
+
     /**
      * Get the native pointer (for internal use).
      */
@@ -55,6 +110,145 @@ public final class Filter implements Closeable {
         return ptr;
     }
 
+    /**
+     * Create an independent copy of this filter.
+     *
+     * <p>The copy has its own native handle and must be closed separately; it
+     * stays usable after this filter is closed. Useful to both query the backlog
+     * and subscribe with the same filter.
+     *
+     * @return A new Filter equal to this one
+     */
+    public Filter copy() {
+        checkOpen();
+        long copyPtr = NostrdbNative.filterClone(ptr);
+        if (copyPtr == 0) {
+            throw new NostrdbException("Failed to copy filter");
+        }
+        return new Filter(copyPtr);
+    }
+
+    /**
+     * Serialize this filter to its NIP-01 JSON object, e.g. to forward it to a
+     * relay in a REQ.
+     *
+     * <p>Ids and pubkeys are hex-encoded and tags appear under {@code #x} keys.
+     * {@link #fromJson(String)} parses the result back into an equivalent filter.
+     *
+     * @return The filter JSON
+     */
+    public String toJson() {
+        checkOpen();
+        String json = NostrdbNative.filterToJson(ptr);
+        if (json == null) {
+            throw new NostrdbException("Failed to serialize filter");
+        }
+        return json;
+    }
+
+    /**
+     * Get the kinds this filter matches.
+     *
+     * @return The kinds, or an empty array if the filter does not restrict kinds
+     */
+    public int[] kinds() {
+        checkOpen();
+        int[] kinds = NostrdbNative.filterGetKinds(ptr);
+        return kinds != null ? kinds : new int[0];
+    }
+
+    /**
+     * Get the author public keys this filter matches.
+     *
+     * @return Hex-encoded pubkeys, or an empty list if the filter does not restrict authors
+     */
+    public List<String> authors() {
+        checkOpen();
+        byte[] packed = NostrdbNative.filterGetAuthors(ptr);
+        List<String> authors = new ArrayList<>();
+        if (packed != null) {
+            for (int i = 0; i < packed.length; i += 32) {
+                authors.add(HexUtil.encode(Arrays.copyOfRange(packed, i, i + 32)));
+            }
+        }
+        return authors;
+    }
+
+    /**
+     * Get the since timestamp.
+     *
+     * @return Unix timestamp in seconds, or empty if unset
+     */
+    public OptionalLong since() {
+        checkOpen();
+        return optional(NostrdbNative.filterGetSince(ptr));
+    }
+
+    /**
+     * Get the until timestamp.
+     *
+     * @return Unix timestamp in seconds, or empty if unset
+     */
+    public OptionalLong until() {
+        checkOpen();
+        return optional(NostrdbNative.filterGetUntil(ptr));
+    }
+
+    /**
+     * Get the result limit.
+     *
+     * @return The limit, or empty if unset
+     */
+    public OptionalLong limit() {
+        checkOpen();
+        return optional(NostrdbNative.filterGetLimit(ptr));
+    }
+
+    /**
+     * Check whether a stored note matches this filter, without running a query
+     * (e.g. to classify incoming notes against a mute filter).
+     *
+     * <p>{@code limit} is ignored. A search term matches as a case-insensitive
+     * substring of the content.
+     *
+     * @param ndb The database holding the note
+     * @param txn Active transaction
+     * @param noteKey Internal note key
+     * @return true if the note matches
+     * @throws java.util.NoSuchElementException if no note has this key
+     */
+    public boolean matches(Ndb ndb, Transaction txn, long noteKey) {
+        checkOpen();
+        return NostrdbNative.filterMatches(ptr, ndb.ptr(), txn.ptr(), noteKey);
+    }
+
+    /**
+     * Check whether an event that is not stored matches this filter.
+     *
+     * <p>The event is not validated; only the fields this filter refers to are
+     * read. Matching follows {@link #matches(Ndb, Transaction, long)}.
+     *
+     * @param eventJson The event as a JSON object
+     * @return true if the event matches
+     * @throws IllegalArgumentException if a field the filter refers to is missing or malformed
+     */
+    public boolean matches(String eventJson) {
+        checkOpen();
+        if (eventJson == null) {
+            throw new IllegalArgumentException("Event JSON must not be null");
+        }
+        return NostrdbNative.filterMatchesJson(ptr, eventJson);
+    }
+
+    @Override
+    public String toString() {
+        return closed.get() ? "Filter{closed}" : "Filter" + toJson();
+    }
+
+    private static OptionalLong optional(long value) {
+        return value < 0 ? OptionalLong.empty() : OptionalLong.of(value);
+    }
+
     private void checkOpen() {
         if (closed.get()) {
             throw new IllegalStateException("Filter is closed");
@@ -72,11 +266,15 @@ public final class Filter implements Closeable {
      * Builder for constructing filters.
      */
     public static final class Builder {
-        private long ptr;
+        /** Largest kind range that {@link #kindRange} expands into a kinds list. */
+        public static final int KIND_RANGE_EXPAND_MAX = 256;
+
+        private final long ptr;
         private boolean built = false;
+        private boolean strict = false;
 
         private Builder() {
-            this.ptr = NostrdbNative.filterNew();
+            this.ptr = NostrdbNative.filterBuilderNew();
             if (this.ptr == 0) {
                 throw new NostrdbException("Failed to create filter");
             }
@@ -87,6 +285,7 @@ public final class Filter implements Closeable {
          *
          * @param kinds The kinds to match (e.g., 1 for text notes, 0 for profiles)
          * @return this builder
+         * @throws IllegalArgumentException if a kind is negative
          */
         public Builder kinds(int... kinds) {
             checkNotBuilt();
@@ -94,24 +293,17 @@ public final class Filter implements Closeable {
                 return this;
             }
 
-            ByteBuffer buf = ByteBuffer.allocate(kinds.length * 4)
-                .order(ByteOrder.LITTLE_ENDIAN);
-            for (int kind : kinds) {
-                buf.putInt(kind);
-            }
-
-            ptr = NostrdbNative.filterKinds(ptr, buf.array());
-            if (ptr == 0) {
-                throw new NostrdbException("Failed to add kinds to filter");
-            }
+            NostrdbNative.filterBuilderAddKindsInt(ptr, kinds);
             return this;
         }
 
         /**
          * Add author public keys to match (hex-encoded).
          *
-         * @param pubkeysHex 64-character hex public keys
+         * @param pubkeysHex 64-character hex public keys (either case)
          * @return this builder
+         * @throws IllegalArgumentException if a pubkey is not 64 hex characters;
+         *         the message names its index
          */
         public Builder authors(String... pubkeysHex) {
             checkNotBuilt();
@@ -119,15 +311,7 @@ public final class Filter implements Closeable {
                 return this;
             }
 
-            ByteBuffer buf = ByteBuffer.allocate(pubkeysHex.length * 32);
-            for (String hex : pubkeysHex) {
-                buf.put(HexUtil.decode(hex));
-            }
-
-            ptr = NostrdbNative.filterAuthors(ptr, buf.array());
-            if (ptr == 0) {
-                throw new NostrdbException("Failed to add authors to filter");
-            }
+            NostrdbNative.filterBuilderAddAuthorsHex(ptr, pubkeysHex);
             return this;
         }
 
@@ -151,10 +335,113 @@ public final class Filter implements Closeable {
                 buf.put(pubkey);
             }
 
-            ptr = NostrdbNative.filterAuthors(ptr, buf.array());
-            if (ptr == 0) {
-                throw new NostrdbException("Failed to add authors to filter");
+            NostrdbNative.filterBuilderAddAuthors(ptr, buf.array());
+            return this;
+        }
+
+        /**
+         * Add event IDs to match (hex-encoded).
+         *
+         * @param idsHex 64-character hex event IDs
+         * @return this builder
+         */
+        public Builder ids(String... idsHex) {
+            checkNotBuilt();
+            if (idsHex == null || idsHex.length == 0) {
+                return this;
+            }
+
+            NostrdbNative.filterBuilderAddIds(ptr, packHex(idsHex));
+            return this;
+        }
+
+        /**
+         * Add event IDs to match (raw bytes).
+         *
+         * @param ids 32-byte event IDs
+         * @return this builder
+         */
+        public Builder ids(byte[]... ids) {
+            checkNotBuilt();
+            if (ids == null || ids.length == 0) {
+                return this;
+            }
+
+            NostrdbNative.filterBuilderAddIds(ptr, pack("Event ID", ids));
+            return this;
+        }
+
+        /**
+         * Match events referencing any of the given events in an "e" tag
+         * (hex-encoded).
+         *
+         * <p>Unlike {@link #eTag(String...)}, the ids are validated and passed to
+         * nostrdb as binary.
+         *
+         * @param eventIdsHex 64-character hex event IDs
+         * @return this builder
+         */
+        public Builder events(String... eventIdsHex) {
+            checkNotBuilt();
+            if (eventIdsHex == null || eventIdsHex.length == 0) {
+                return this;
+            }
+
+            NostrdbNative.filterBuilderAddEvents(ptr, packHex(eventIdsHex));
+            return this;
+        }
+
+        /**
+         * Match events referencing any of the given events in an "e" tag
+         * (raw bytes).
+         *
+         * @param eventIds 32-byte event IDs
+         * @return this builder
+         */
+        public Builder events(byte[]... eventIds) {
+            checkNotBuilt();
+            if (eventIds == null || eventIds.length == 0) {
+                return this;
             }
+
+            NostrdbNative.filterBuilderAddEvents(ptr, pack("Event ID", eventIds));
+            return this;
+        }
+
+        /**
+         * Match events referencing any of the given pubkeys in a "p" tag
+         * (hex-encoded), e.g. mentions or DMs addressed to them.
+         *
+         * <p>Unlike {@link #pTag(String...)}, the pubkeys are validated and passed
+         * to nostrdb as binary.
+         *
+         * @param pubkeysHex 64-character hex public keys
+         * @return this builder
+         */
+        public Builder pubkeys(String... pubkeysHex) {
+            checkNotBuilt();
+            if (pubkeysHex == null || pubkeysHex.length == 0) {
+                return this;
+            }
+
+            NostrdbNative.filterBuilderAddPubkeys(ptr, packHex(pubkeysHex));
+            return this;
+        }
+
+        /**
+         * Match events referencing any of the given pubkeys in a "p" tag
+         * (raw bytes).
+         *
+         * @param pubkeys 32-byte public keys
+         * @return this builder
+         */
+        public Builder pubkeys(byte[]... pubkeys) {
+            checkNotBuilt();
+            if (pubkeys == null || pubkeys.length == 0) {
+                return this;
+            }
+
+            NostrdbNative.filterBuilderAddPubkeys(ptr, pack("Pubkey", pubkeys));
             return this;
         }
 
@@ -171,10 +458,102 @@ public final class Filter implements Closeable {
                 return this;
             }
 
-            ptr = NostrdbNative.filterTag(ptr, tagName, values);
-            if (ptr == 0) {
-                throw new NostrdbException("Failed to add tag to filter");
+            NostrdbNative.filterBuilderAddTag(ptr, tagName, values);
+            return this;
+        }
+
+        /**
+         * Match events with any of the given hashtags ("t" tags).
+         *
+         * <p>Values are normalized per NIP-24 the way clients index them: a leading
+         * '#' is removed and they are lowercased, so {@code "#Bitcoin"} matches
+         * {@code ["t","bitcoin"]}. Empty values and duplicates are skipped.
+         *
+         * @param hashtags Hashtags as typed
+         * @return this builder
+         * @throws IllegalArgumentException if every value is empty after normalization
+         */
+        public Builder hashtags(String... hashtags) {
+            checkNotBuilt();
+            if (hashtags == null || hashtags.length == 0) {
+                return this;
+            }
+
+            NostrdbNative.filterBuilderAddHashtags(ptr, hashtags);
+            return this;
+        }
+
+        /**
+         * Add a tag filter whose values are 32-byte ids (e.g. "e", "p", "q").
+         *
+         * <p>Matches the same events as {@link #tag(String, String...)} with the
+         * hex-encoded values, without the hex round trip.
+         *
+         * @param tagName Single-character tag name
+         * @param values 32-byte tag values
+         * @return this builder
+         */
+        public Builder binaryTag(String tagName, byte[]... values) {
+            checkNotBuilt();
+            if (tagName == null || values == null || values.length == 0) {
+                return this;
+            }
+
+            NostrdbNative.filterBuilderAddTagBinary(ptr, tagName, pack("Tag value", values));
+            return this;
+        }
+
+        /**
+         * Match only kinds in an inclusive range, e.g. 30000-39999 for all
+         * parameterized replaceable events.
+         *
+         * <p>Ranges of up to {@link #KIND_RANGE_EXPAND_MAX} kinds are added as an explicit
+         * kinds list. Larger ones are checked natively against query results, since nostrdb
+         * only matches kinds by list; such a filter cannot be used with
+         * {@link Ndb#subscribe(Filter)}.
+         *
+         * @param minKind Lowest kind to match
+         * @param maxKind Highest kind to match
+         * @return this builder
+         * @throws IllegalArgumentException if minKind is negative or greater than maxKind
+         */
+        public Builder kindRange(int minKind, int maxKind) {
+            checkNotBuilt();
+            NostrdbNative.filterBuilderKindRange(ptr, minKind, maxKind);
+            return this;
+        }
+
+        /**
+         * Match one parameterized replaceable event (kind 3xxxx) by kind, author,
+         * and "d" tag, and set limit 1 so a query returns its latest version.
+         *
+         * @param kind Event kind, e.g. 30023
+         * @param authorHex 64-character hex public key of the author
+         * @param dTag The "d" tag value; may be empty
+         * @return this builder
+         * @throws IllegalArgumentException if the kind is negative or the pubkey is not 64 hex characters
+         */
+        public Builder replaceable(int kind, String authorHex, String dTag) {
+            return replaceable(kind, packHex(authorHex), dTag);
+        }
+
+        /**
+         * Match one parameterized replaceable event (kind 3xxxx) by kind, author,
+         * and "d" tag, and set limit 1 so a query returns its latest version.
+         *
+         * @param kind Event kind, e.g. 30023
+         * @param author 32-byte public key of the author
+         * @param dTag The "d" tag value; may be empty
+         * @return this builder
+         * @throws IllegalArgumentException if the kind is negative or the pubkey is not 32 bytes
+         */
+        public Builder replaceable(int kind, byte[] author, String dTag) {
+            checkNotBuilt();
+            if (author == null || dTag == null) {
+                throw new IllegalArgumentException("Author and d tag must not be null");
             }
+
+            NostrdbNative.filterBuilderReplaceable(ptr, kind, author, dTag);
             return this;
         }
 
@@ -213,13 +592,12 @@ public final class Filter implements Closeable {
          *
          * @param since Unix timestamp in seconds
          * @return this builder
+         * @throws IllegalArgumentException if since is negative or too far in the future
+         *         (see {@link Filter#setTimestampSlack(long)})
          */
         public Builder since(long since) {
             checkNotBuilt();
-            ptr = NostrdbNative.filterSince(ptr, since);
-            if (ptr == 0) {
-                throw new NostrdbException("Failed to set since on filter");
-            }
+            NostrdbNative.filterBuilderSetSince(ptr, since);
             return this;
         }
 
@@ -228,13 +606,12 @@ public final class Filter implements Closeable {
          *
          * @param until Unix timestamp in seconds
          * @return this builder
+         * @throws IllegalArgumentException if until is negative or too far in the future
+         *         (see {@link Filter#setTimestampSlack(long)})
          */
         public Builder until(long until) {
             checkNotBuilt();
-            ptr = NostrdbNative.filterUntil(ptr, until);
-            if (ptr == 0) {
-                throw new NostrdbException("Failed to set until on filter");
-            }
+            NostrdbNative.filterBuilderSetUntil(ptr, until);
             return this;
         }
 
@@ -254,10 +631,7 @@ public final class Filter implements Closeable {
                 throw new IllegalArgumentException(
                         "Limit exceeds maximum allowed value of " + MAX_LIMIT + ", got: " + limit);
             }
-            ptr = NostrdbNative.filterLimit(ptr, limit);
-            if (ptr == 0) {
-                throw new NostrdbException("Failed to set limit on filter");
-            }
+            NostrdbNative.filterBuilderSetLimit(ptr, limit);
             return this;
         }
 
@@ -272,27 +646,45 @@ public final class Filter implements Closeable {
             if (search == null || search.isEmpty()) {
                 return this;
             }
-            ptr = NostrdbNative.filterSearch(ptr, search);
-            if (ptr == 0) {
-                throw new NostrdbException("Failed to set search on filter");
-            }
+            NostrdbNative.filterBuilderSetSearch(ptr, search);
+            return this;
+        }
+
+        /**
+         * Reject an empty filter at build time instead of matching every note.
+         *
+         * @return this builder
+         */
+        public Builder strict() {
+            checkNotBuilt();
+            strict = true;
             return this;
         }
 
         /**
          * Build the filter.
          *
+         * <p>The builder cannot be reused afterwards, even if validation fails.
+         *
          * @return The constructed Filter
+         * @throws NostrdbException if since is after until, the filter has more elements
+         *         than nostrdb supports, or it is empty and {@link #strict()} was set
          */
         public Filter build() {
             checkNotBuilt();
             built = true;
 
-            long filterPtr = NostrdbNative.filterBuild(ptr);
-            if (filterPtr == 0) {
-                throw new NostrdbException("Failed to build filter");
+            try {
+                long filterPtr = strict
+                    ? NostrdbNative.filterBuilderBuildStrict(ptr)
+                    : NostrdbNative.filterBuilderBuild(ptr);
+                if (filterPtr == 0) {
+                    throw new NostrdbException("Failed to build filter");
+                }
+                return new Filter(filterPtr);
+            } finally {
+                NostrdbNative.filterBuilderDestroy(ptr);
             }
-            return new Filter(filterPtr);
         }
 
         private void checkNotBuilt() {
@@ -300,5 +692,34 @@ public final class Filter implements Closeable {
                 throw new IllegalStateException("Filter has already been built");
             }
         }
+
+        /**
+         * Concatenate hex-encoded 32-byte values for the native layer.
+         */
+        private static byte[] packHex(String... valuesHex) {
+            ByteBuffer buf = ByteBuffer.allocate(valuesHex.length * 32);
+            for (String hex : valuesHex) {
+                byte[] value = HexUtil.decode(hex);
+                if (value == null || value.length != 32) {
+                    throw new IllegalArgumentException("Expected 64 hex characters, got: " + hex);
+                }
+                buf.put(value);
+            }
+            return buf.array();
+        }
+
+        /**
+         * Concatenate 32-byte values for the native layer.
+         */
+        private static byte[] pack(String what, byte[]... values) {
+            ByteBuffer buf = ByteBuffer.allocate(values.length * 32);
+            for (byte[] value : values) {
+                if (value == null || value.length != 32) {
+                    throw new IllegalArgumentException(what + " must be 32 bytes");
+                }
+                buf.put(value);
+            }
+            return buf.array();
+        }
     }
 }
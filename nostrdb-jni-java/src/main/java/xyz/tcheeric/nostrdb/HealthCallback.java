@@ -0,0 +1,72 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * Receives database health events from a background monitor thread.
+ *
+ * <p>Events are edge-triggered: a condition is reported once when it is entered,
+ * and again only after the matching recovery event. Callbacks run on a native
+ * monitor thread; implementations should return quickly and must be thread-safe.
+ * Exceptions thrown by a callback are logged and otherwise ignored.
+ *
+ * <p>Example usage:
+ * <pre>{@code
+ * ndb.setHealthCallback((code, value) -> {
+ *     if (code == HealthCallback.MAP_NEARLY_FULL) {
+ *         log.warn("nostrdb map is {} bytes, consider a larger map size", value);
+ *     } else if (code == HealthCallback.INGEST_BACKLOG_HIGH) {
+ *         log.warn("nostrdb is {} events behind", value);
+ *     }
+ * }, 5_000);
+ * }</pre>
+ *
+ * @see Ndb#setHealthCallback(HealthCallback, long)
+ */
+@FunctionalInterface
+public interface HealthCallback {
+
+    /**
+     * Map utilization crossed the nearly-full threshold. Value: bytes used.
+     *
+     * <p>Utilization counts the pages LMDB holds data in, so pages it has freed
+     * and will reuse don't count.
+     */
+    int MAP_NEARLY_FULL = 1;
+
+    /**
+     * The map is full and writes will fail. Value: bytes used.
+     *
+     * <p>Reported at 99 percent, or once less than 256 KiB is free.
+     */
+    int MAP_FULL = 2;
+
+    /**
+     * Map utilization dropped back below the nearly-full threshold, for example
+     * after auto-resize grew the map. Value: bytes used.
+     */
+    int MAP_RECOVERED = 3;
+
+    /**
+     * Events accepted for ingestion but not yet stored reached the backlog
+     * threshold. Value: backlog in events.
+     *
+     * <p>The backlog counts events accepted through this {@link Ndb} since the
+     * callback was registered, less the notes stored since. Events nostrdb
+     * drops after accepting them are forgotten once ingestion has been idle for
+     * a second.
+     */
+    int INGEST_BACKLOG_HIGH = 4;
+
+    /** The ingest backlog dropped back below its threshold. Value: backlog in events. */
+    int BACKLOG_RECOVERED = 5;
+
+    /** Backlog threshold used unless {@link Ndb#setHealthThresholds(int, long)} sets one. */
+    long DEFAULT_INGEST_BACKLOG_HIGH = 10_000;
+
+    /**
+     * Called when a health condition is entered or cleared.
+     *
+     * @param code One of the event constants in this interface
+     * @param value Event-specific value (see the constant's documentation)
+     */
+    void onHealthEvent(int code, long value);
+}
@@ -0,0 +1,32 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * Receives progress reports while a file or byte batch is imported.
+ *
+ * <p>Example usage:
+ * <pre>{@code
+ * ImportSummary summary = ndb.processEventsFromFile(export,
+ *     (lines, failed) -> status.setText(lines + " lines read, " + failed + " failed"), null);
+ * }</pre>
+ *
+ * <p>The callback runs synchronously on the importing thread, every
+ * {@link #INTERVAL} lines and once when the import ends. An exception it
+ * throws stops the import and propagates to the caller.
+ *
+ * @see Ndb#processEventsFromFile(java.nio.file.Path, ImportProgressCallback, CancelToken)
+ * @see Ndb#processEventsBytes(byte[], ImportProgressCallback, CancelToken)
+ */
+@FunctionalInterface
+public interface ImportProgressCallback {
+
+    /** Lines read between two progress reports. */
+    int INTERVAL = 1_000;
+
+    /**
+     * Called with the import's progress so far.
+     *
+     * @param lines Lines read, including blank lines
+     * @param failed Lines that could not be ingested
+     */
+    void onProgress(long lines, long failed);
+}
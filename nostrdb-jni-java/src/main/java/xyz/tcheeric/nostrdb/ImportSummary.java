@@ -0,0 +1,88 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * What happened to the lines of an imported file or batch.
+ *
+ * <p>An import that was cancelled can be resumed from {@link #offset()}.
+ *
+ * @see Ndb#processEventsFromFile(java.nio.file.Path, ImportProgressCallback, CancelToken, long)
+ * @see Ndb#processEventsBytes(byte[], ImportProgressCallback, CancelToken)
+ */
+public final class ImportSummary {
+
+    private final long lines;
+    private final long processed;
+    private final long failed;
+    private final boolean cancelled;
+    private final long offset;
+
+    private ImportSummary(long lines, long processed, long failed, boolean cancelled, long offset) {
+        this.lines = lines;
+        this.processed = processed;
+        this.failed = failed;
+        this.cancelled = cancelled;
+        this.offset = offset;
+    }
+
+    /**
+     * Get the number of lines read.
+     *
+     * @return Lines read, including blank lines
+     */
+    public long lines() {
+        return lines;
+    }
+
+    /**
+     * Get the number of events handed to nostrdb.
+     *
+     * @return Events processed
+     */
+    public long processed() {
+        return processed;
+    }
+
+    /**
+     * Get the number of lines that were not valid UTF-8, or whose event
+     * nostrdb refused.
+     *
+     * @return Lines that failed
+     */
+    public long failed() {
+        return failed;
+    }
+
+    /**
+     * Check if the import was cancelled before the end of the file.
+     *
+     * @return true if the token was cancelled and the rest of the file was not read
+     */
+    public boolean isCancelled() {
+        return cancelled;
+    }
+
+    /**
+     * Get the byte offset just past the last line read, from the start of the
+     * file (of the decompressed stream for a gzipped file) or batch. Passing it
+     * as the resume offset of a later import of the same file continues where
+     * this one stopped.
+     *
+     * @return The offset of the first line not read
+     */
+    public long offset() {
+        return offset;
+    }
+
+    /**
+     * Create a summary from the native [lines, processed, failed, cancelled, offset] array.
+     */
+    static ImportSummary fromArray(long[] counts) {
+        return new ImportSummary(counts[0], counts[1], counts[2], counts[3] != 0, counts[4]);
+    }
+
+    @Override
+    public String toString() {
+        return "ImportSummary{lines=" + lines + ", processed=" + processed + ", failed=" + failed
+            + ", cancelled=" + cancelled + ", offset=" + offset + '}';
+    }
+}
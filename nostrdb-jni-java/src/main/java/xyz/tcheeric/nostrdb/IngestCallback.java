@@ -0,0 +1,49 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * Receives the outcome of an event submitted with
+ * {@link Ndb#processEventAsync(String, IngestCallback, long)}.
+ *
+ * <p>Example usage:
+ * <pre>{@code
+ * ndb.processEventAsync(json, (id, noteKey, status) -> {
+ *     if (status == IngestCallback.STORED) {
+ *         timeline.insert(noteKey);
+ *     }
+ * });
+ * }</pre>
+ *
+ * <p>The callback is called exactly once per event, on a native thread;
+ * implementations should return quickly and must be thread-safe. Exceptions
+ * thrown by a callback are logged and otherwise ignored.
+ */
+@FunctionalInterface
+public interface IngestCallback {
+
+    /** The note was stored. */
+    int STORED = 0;
+
+    /** The event is malformed, or its id or signature does not verify. */
+    int INVALID = 1;
+
+    /** A note with the event's id was already stored; its key is passed. */
+    int DUPLICATE = 2;
+
+    /** The note was not stored before the timeout. */
+    int TIMEOUT = 3;
+
+    /** nostrdb refused to queue the event. */
+    int REJECTED = 4;
+
+    /** The database was closed before the note was stored. */
+    int CLOSED = 5;
+
+    /**
+     * Called once the event's outcome is known.
+     *
+     * @param id The event ID (32 bytes), or null if the event is too malformed to have one
+     * @param noteKey The stored note's key for {@link #STORED} and {@link #DUPLICATE}, otherwise 0
+     * @param status One of the status constants in this interface
+     */
+    void onComplete(byte[] id, long noteKey, int status);
+}
@@ -0,0 +1,159 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
+import java.util.Collections;
+import java.util.List;
+
+/**
+ * The outcome of ingesting a batch of newline-delimited events, with the
+ * lines that failed.
+ *
+ * <p>For a JSON array of events, each element counts as a line, numbered
+ * from 1, and there are no blank lines.
+ *
+ * @see Ndb#processEventsDetailed(String, boolean)
+ * @see Ndb#processEventsArray(String)
+ */
+public final class IngestReport {
+
+    private final int succeeded;
+    private final int duplicates;
+    private final int blank;
+    private final int linesRead;
+    private final List<LineError> errors;
+
+    private IngestReport(int succeeded, int duplicates, int blank, int linesRead, List<LineError> errors) {
+        this.succeeded = succeeded;
+        this.duplicates = duplicates;
+        this.blank = blank;
+        this.linesRead = linesRead;
+        this.errors = errors;
+    }
+
+    /**
+     * A line that failed to ingest.
+     */
+    public static final class LineError {
+
+        private final int line;
+        private final String message;
+
+        private LineError(int line, String message) {
+            this.line = line;
+            this.message = message;
+        }
+
+        /**
+         * Get the line number.
+         *
+         * @return The line's number, starting at 1
+         */
+        public int line() {
+            return line;
+        }
+
+        /**
+         * Get what was wrong with the line.
+         *
+         * @return A short error message
+         */
+        public String message() {
+            return message;
+        }
+
+        @Override
+        public String toString() {
+            return "line " + line + ": " + message;
+        }
+    }
+
+    /**
+     * Get the number of new events ingested.
+     *
+     * @return Lines handed to nostrdb
+     */
+    public int succeeded() {
+        return succeeded;
+    }
+
+    /**
+     * Get the number of events that were already stored, or repeated an earlier
+     * line, and were not ingested again.
+     *
+     * @return Duplicate events
+     */
+    public int duplicates() {
+        return duplicates;
+    }
+
+    /**
+     * Get the number of lines that failed.
+     *
+     * @return Failed lines, each listed in {@link #errors()}
+     */
+    public int failed() {
+        return errors.size();
+    }
+
+    /**
+     * Get the number of blank lines, which were skipped.
+     *
+     * @return Blank lines
+     */
+    public int blank() {
+        return blank;
+    }
+
+    /**
+     * Get how far ingestion got: the number of the last line read.
+     *
+     * <p>With stop-on-error this is the first failed line; otherwise it is
+     * the number of lines in the batch.
+     *
+     * @return The last line read, or 0 for an empty batch
+     */
+    public int linesRead() {
+        return linesRead;
+    }
+
+    /**
+     * Get the lines that failed, in line order.
+     *
+     * @return Unmodifiable list of failed lines
+     */
+    public List<LineError> errors() {
+        return errors;
+    }
+
+    /**
+     * Parse a report from native byte array.
+     *
+     * <p>Format: [succeeded:4][duplicates:4][failed:4][blank:4][linesRead:4]
+     * ([line:4][len:2][error:len])*
+     */
+    static IngestReport parse(byte[] data) {
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int succeeded = buf.getInt();
+        int duplicates = buf.getInt();
+        int failed = buf.getInt();
+        int blank = buf.getInt();
+        int linesRead = buf.getInt();
+        List<LineError> errors = new ArrayList<>(failed);
+        for (int i = 0; i < failed; i++) {
+            int line = buf.getInt();
+            byte[] message = new byte[Short.toUnsignedInt(buf.getShort())];
+            buf.get(message);
+            errors.add(new LineError(line, new String(message, StandardCharsets.UTF_8)));
+        }
+        return new IngestReport(succeeded, duplicates, blank, linesRead, Collections.unmodifiableList(errors));
+    }
+
+    @Override
+    public String toString() {
+        return "IngestReport{succeeded=" + succeeded + ", duplicates=" + duplicates + ", failed=" + errors.size()
+            + ", blank=" + blank + ", linesRead=" + linesRead + '}';
+    }
+}
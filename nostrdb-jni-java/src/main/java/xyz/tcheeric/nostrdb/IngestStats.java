@@ -0,0 +1,82 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * Running totals of the events ingested through a database handle.
+ *
+ * @see Ndb#getIngestStats()
+ */
+public final class IngestStats {
+
+    private final long accepted;
+    private final long duplicates;
+    private final long rejected;
+    private final long bytes;
+
+    private IngestStats(long accepted, long duplicates, long rejected, long bytes) {
+        this.accepted = accepted;
+        this.duplicates = duplicates;
+        this.rejected = rejected;
+        this.bytes = bytes;
+    }
+
+    /**
+     * Get the number of events handed to nostrdb.
+     *
+     * @return Events accepted
+     */
+    public long accepted() {
+        return accepted;
+    }
+
+    /**
+     * Get the number of events that were already stored, or repeated an
+     * earlier line of the same batch. Only the methods that look an event up
+     * before queuing it detect duplicates; the others count them as accepted.
+     *
+     * @return Duplicate events
+     */
+    public long duplicates() {
+        return duplicates;
+    }
+
+    /**
+     * Get the number of events that were malformed, failed validation, or
+     * were refused by nostrdb.
+     *
+     * @return Events rejected
+     */
+    public long rejected() {
+        return rejected;
+    }
+
+    /**
+     * Get the size of the accepted events.
+     *
+     * @return UTF-8 bytes of the accepted events
+     */
+    public long bytes() {
+        return bytes;
+    }
+
+    /**
+     * Get the number of events handed in, whatever happened to them.
+     *
+     * @return The sum of accepted, duplicate, and rejected events
+     */
+    public long total() {
+        return accepted + duplicates + rejected;
+    }
+
+    /**
+     * Create totals from the native [accepted, duplicates, rejected, bytes] array.
+     */
+    static IngestStats fromArray(long[] totals) {
+        return new IngestStats(totals[0], totals[1], totals[2], totals[3]);
+    }
+
+    @Override
+    public String toString() {
+        return "IngestStats{accepted=" + accepted + ", duplicates=" + duplicates + ", rejected=" + rejected
+            + ", bytes=" + bytes + '}';
+    }
+}
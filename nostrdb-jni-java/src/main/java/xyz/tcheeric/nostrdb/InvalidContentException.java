@@ -0,0 +1,32 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * Thrown when a stored note's content is not valid UTF-8 and the database was
+ * opened with {@link NdbConfig#strictUtf8(boolean) strict UTF-8} handling.
+ *
+ * <p>nostrdb does not check content encoding, so events ingested without
+ * validation can carry arbitrary bytes. Without strict handling, invalid
+ * sequences are replaced with U+FFFD instead.
+ */
+public class InvalidContentException extends NostrdbException {
+
+    private final long noteKey;
+
+    /**
+     * @param message Description of the failure
+     * @param noteKey Internal key of the offending note, or 0 if unknown
+     */
+    public InvalidContentException(String message, long noteKey) {
+        super(message);
+        this.noteKey = noteKey;
+    }
+
+    /**
+     * Get the internal key of the note whose content is invalid.
+     *
+     * @return The note key, for {@link Ndb#getNoteContentBytes}, or 0 if unknown
+     */
+    public long noteKey() {
+        return noteKey;
+    }
+}
@@ -0,0 +1,88 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.util.ArrayList;
+import java.util.List;
+
+/**
+ * An author's newest note matching a filter.
+ *
+ * @see Ndb#queryLatestPerAuthor(Transaction, Filter, java.util.Collection)
+ */
+public final class LatestNote {
+
+    private final byte[] pubkey;
+    private final long noteKey;
+    private final long createdAt;
+
+    private LatestNote(byte[] pubkey, long noteKey, long createdAt) {
+        this.pubkey = pubkey;
+        this.noteKey = noteKey;
+        this.createdAt = createdAt;
+    }
+
+    /**
+     * Get the author's public key.
+     *
+     * @return 32-byte public key
+     */
+    public byte[] pubkey() {
+        return pubkey.clone();
+    }
+
+    /**
+     * Get the author's public key as hex.
+     *
+     * @return 64-character hex string
+     */
+    public String pubkeyHex() {
+        return HexUtil.encode(pubkey);
+    }
+
+    /**
+     * Get the internal key of the note.
+     *
+     * @return The note key, for {@link Ndb#getNoteByKey(Transaction, long)}
+     */
+    public long noteKey() {
+        return noteKey;
+    }
+
+    /**
+     * Get the note's creation timestamp.
+     *
+     * @return Unix timestamp in seconds
+     */
+    public long createdAt() {
+        return createdAt;
+    }
+
+    /**
+     * Parse results from native byte array.
+     *
+     * <p>Format: [count:4]([pubkey:32][key:8][created_at:8])*
+     */
+    static List<LatestNote> parseResults(byte[] data) {
+        if (data == null || data.length < 4) {
+            return List.of();
+        }
+
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+
+        List<LatestNote> results = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            byte[] pubkey = new byte[32];
+            buf.get(pubkey);
+            results.add(new LatestNote(pubkey, buf.getLong(), buf.getLong()));
+        }
+
+        return results;
+    }
+
+    @Override
+    public String toString() {
+        return "LatestNote{pubkey=" + pubkeyHex() + ", noteKey=" + noteKey + ", createdAt=" + createdAt + '}';
+    }
+}
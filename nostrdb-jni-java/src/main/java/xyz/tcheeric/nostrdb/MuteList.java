@@ -0,0 +1,128 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
+import java.util.Collections;
+import java.util.List;
+
+/**
+ * The public entries of an author's newest mute list (NIP-51, kind 10000).
+ *
+ * <p>Each entry appears once, in list order; ids that are not 64 hex
+ * characters are skipped. Private entries are encrypted to the author in
+ * the list's content and are not read: {@link #hasPrivateEntries()} only
+ * reports that they exist.
+ *
+ * @see Ndb#getMuteList(Transaction, byte[])
+ */
+public final class MuteList {
+
+    private static final int PRIVATE = 1;
+
+    private final List<byte[]> pubkeys;
+    private final List<byte[]> eventIds;
+    private final List<String> hashtags;
+    private final List<String> words;
+    private final boolean hasPrivateEntries;
+
+    private MuteList(List<byte[]> pubkeys, List<byte[]> eventIds, List<String> hashtags, List<String> words,
+                     boolean hasPrivateEntries) {
+        this.pubkeys = pubkeys;
+        this.eventIds = eventIds;
+        this.hashtags = hashtags;
+        this.words = words;
+        this.hasPrivateEntries = hasPrivateEntries;
+    }
+
+    /**
+     * Get the muted authors, from the list's "p" tags.
+     *
+     * @return Unmodifiable list of 32-byte public keys
+     */
+    public List<byte[]> pubkeys() {
+        return pubkeys;
+    }
+
+    /**
+     * Get the muted events, from the list's "e" tags.
+     *
+     * @return Unmodifiable list of 32-byte event ids
+     */
+    public List<byte[]> eventIds() {
+        return eventIds;
+    }
+
+    /**
+     * Get the muted hashtags, from the list's "t" tags.
+     *
+     * @return Unmodifiable list of hashtags, without "#"
+     */
+    public List<String> hashtags() {
+        return hashtags;
+    }
+
+    /**
+     * Get the muted words, from the list's "word" tags.
+     *
+     * @return Unmodifiable list of words
+     */
+    public List<String> words() {
+        return words;
+    }
+
+    /**
+     * Check whether the list also has encrypted private entries, which are not included.
+     *
+     * @return true if the list's content is not empty
+     */
+    public boolean hasPrivateEntries() {
+        return hasPrivateEntries;
+    }
+
+    /**
+     * Parse a mute list from native byte array.
+     *
+     * <p>Format: [flags:1][count:4]([pubkey:32])*[count:4]([id:32])*
+     * [count:4]([len:2][hashtag:len])*[count:4]([len:2][word:len])*
+     */
+    static MuteList parse(byte[] data) {
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        boolean hasPrivateEntries = (buf.get() & PRIVATE) != 0;
+        List<byte[]> pubkeys = readIds(buf);
+        List<byte[]> eventIds = readIds(buf);
+        List<String> hashtags = readStrings(buf);
+        List<String> words = readStrings(buf);
+        return new MuteList(pubkeys, eventIds, hashtags, words, hasPrivateEntries);
+    }
+
+    private static List<byte[]> readIds(ByteBuffer buf) {
+        int count = buf.getInt();
+        List<byte[]> ids = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            byte[] id = new byte[32];
+            buf.get(id);
+            ids.add(id);
+        }
+        return Collections.unmodifiableList(ids);
+    }
+
+    private static List<String> readStrings(ByteBuffer buf) {
+        int count = buf.getInt();
+        List<String> values = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            byte[] value = new byte[Short.toUnsignedInt(buf.getShort())];
+            buf.get(value);
+            values.add(new String(value, StandardCharsets.UTF_8));
+        }
+        return Collections.unmodifiableList(values);
+    }
+
+    @Override
+    public String toString() {
+        return "MuteList{pubkeys=" + pubkeys.size() + ", eventIds=" + eventIds.size()
+            + ", hashtags=" + hashtags + ", words=" + words.size()
+            + ", hasPrivateEntries=" + hasPrivateEntries + '}';
+    }
+}
@@ -3,10 +3,18 @@ package xyz.tcheeric.nostrdb;
 import java.io.Closeable;
 import java.nio.ByteBuffer;
 import java.nio.ByteOrder;
+import java.nio.charset.StandardCharsets;
 import java.nio.file.Path;
 import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.BitSet;
+import java.util.Collection;
+import java.util.LinkedHashMap;
 import java.util.List;
+import java.util.Map;
 import java.util.Optional;
+import java.util.OptionalInt;
+import java.util.OptionalLong;
 import java.util.concurrent.atomic.AtomicBoolean;
 
 /**
@@ -44,235 +52,2563 @@ import java.util.concurrent.atomic.AtomicBoolean;
  */
 public final class Ndb implements Closeable {
 
+    /**
+     * Query limit that defers to the filter's own limit ({@link Filter.Builder#limit(int)}),
+     * returning every match if the filter has none.
+     */
+    public static final int FILTER_LIMIT = -1;
+
+    /**
+     * Profile search flags for nostrdb's index as is: names match by prefix, as stored.
+     */
+    public static final int PROFILE_SEARCH_EXACT = 0;
+
+    /**
+     * Profile search flag that ignores case and accents, so "jose" finds "Jos\u00e9".
+     * Matches come newest profile first.
+     */
+    public static final int PROFILE_SEARCH_FOLD = 1 << 0;
+
+    /**
+     * {@link #processEventWithResult} outcome: the event is malformed, its id or
+     * signature does not verify, or nostrdb refused it.
+     */
+    public static final int PROCESS_REJECTED = 0;
+
+    /**
+     * {@link #processEventWithResult} outcome: the event was queued to be stored.
+     */
+    public static final int PROCESS_STORED = 1;
+
+    /**
+     * {@link #processEventWithResult} outcome: a note with the event's id is already
+     * stored, so the event was not queued again.
+     */
+    public static final int PROCESS_DUPLICATE = 2;
+
+    /**
+     * {@link #processEventReturningKey} outcome: the event is malformed, or its id
+     * or signature does not verify.
+     */
+    public static final long INGEST_INVALID = -1;
+
+    /**
+     * {@link #processEventReturningKey} outcome: a note with the event's id is already stored.
+     */
+    public static final long INGEST_DUPLICATE = -2;
+
+    /**
+     * {@link #processEventReturningKey} outcome: the note was not stored before the timeout.
+     */
+    public static final long INGEST_TIMEOUT = -3;
+
+    /**
+     * {@link #processEventReturningKey} outcome: nostrdb refused to process the event.
+     */
+    public static final long INGEST_REJECTED = -4;
+
+    /**
+     * How long {@link #processEventReturningKey(String)} waits for a note to be stored.
+     */
+    public static final long DEFAULT_INGEST_TIMEOUT_MS = 1_000;
+
     private final long ptr;
+    private final boolean writeProtected;
     private final AtomicBoolean closed = new AtomicBoolean(false);
 
     private Ndb(long ptr) {
+        this(ptr, false);
+    }
+
+    private Ndb(long ptr, boolean writeProtected) {
         this.ptr = ptr;
+        this.writeProtected = writeProtected;
+    }
+
+    /**
+     * Open a database at the specified path with default configuration.
+     *
+     * @param dbPath Path to the database directory (will be created if it doesn't exist)
+     * @return The Ndb instance
+     * @throws NostrdbException if the database cannot be opened
+     */
+    public static Ndb open(Path dbPath) {
+        return open(dbPath.toString());
+    }
+
+    /**
+     * Open a database at the specified path with default configuration.
+     *
+     * @param dbPath Path to the database directory (will be created if it doesn't exist)
+     * @return The Ndb instance
+     * @throws NostrdbException if the database cannot be opened
+     */
+    public static Ndb open(String dbPath) {
+        long ptr = NostrdbNative.ndbOpen(dbPath, 0, true);
+        if (ptr == 0) {
+            throw new NostrdbException("Failed to open database at " + dbPath);
+        }
+        return new Ndb(ptr);
+    }
+
+    /**
+     * Open a database at the specified path with the given configuration.
+     *
+     * @param dbPath Path to the database directory (will be created if it doesn't exist)
+     * @param config Configuration to open with; it is not consumed and may be reused
+     * @return The Ndb instance
+     * @throws NostrdbException if the database cannot be opened
+     */
+    public static Ndb open(Path dbPath, NdbConfig config) {
+        return open(dbPath.toString(), config);
+    }
+
+    /**
+     * Open a database at the specified path with the given configuration.
+     *
+     * @param dbPath Path to the database directory (will be created if it doesn't exist)
+     * @param config Configuration to open with; it is not consumed and may be reused
+     * @return The Ndb instance
+     * @throws NostrdbException if the database cannot be opened
+     */
+    public static Ndb open(String dbPath, NdbConfig config) {
+        return open(dbPath, config, true);
+    }
+
+    /**
+     * Open a database, optionally requiring that the directory already exists.
+     *
+     * <p>If the database cannot be opened, the native layer throws an
+     * {@link java.io.IOException} whose message names the cause: the directory or
+     * one of its parents is missing, the path is not a directory, permission is
+     * denied, or the database is already open in this process.
+     *
+     * @param dbPath Path to the database directory
+     * @param config Configuration to open with, or null for defaults
+     * @param createIfMissing Create the directory (and its parents) if it does not exist
+     * @return The Ndb instance
+     * @throws NostrdbException if the database cannot be opened
+     */
+    public static Ndb open(String dbPath, NdbConfig config, boolean createIfMissing) {
+        long ptr = NostrdbNative.ndbOpen(dbPath, config == null ? 0 : config.ptr(), createIfMissing);
+        if (ptr == 0) {
+            throw new NostrdbException("Failed to open database at " + dbPath);
+        }
+        return new Ndb(ptr);
+    }
+
+    /**
+     * Open an existing database with writes through this handle rejected.
+     *
+     * <p>Reads ({@link #beginTransaction()}, {@link #query}, {@link #getNoteById},
+     * {@link #getProfileByPubkey}, subscriptions) work as usual. Methods that write,
+     * such as {@link #processEvent(String)} or {@link #processEvents(String)}, throw
+     * {@link IllegalStateException}.
+     *
+     * <p>If the directory is already open in this process, the handle shares that
+     * instance and sees its writes as they are committed. Otherwise the database is
+     * opened with migrations disabled.
+     *
+     * <p>This is a write guard in the binding, not a read-only open: nostrdb has no
+     * read-only mode, so LMDB still opens the database read-write (the directory must
+     * be writable) and nostrdb's writer threads run, though nothing reaches them
+     * through this handle. Other processes may open the same directory and write to
+     * it; their commits are visible to transactions begun afterwards.
+     *
+     * @param dbPath Path to an existing database directory
+     * @return The write-protected Ndb instance
+     * @throws NostrdbException if the database cannot be opened (an {@code IOException}
+     *         is raised by the native layer if the directory does not exist)
+     */
+    public static Ndb openWriteProtected(Path dbPath) {
+        return openWriteProtected(dbPath.toString(), null);
+    }
+
+    /**
+     * Open an existing database with writes through this handle rejected, using the
+     * given configuration.
+     *
+     * @param dbPath Path to an existing database directory
+     * @param config Configuration to open with, or null for defaults
+     * @return The write-protected Ndb instance
+     * @throws NostrdbException if the database cannot be opened
+     * @see #openWriteProtected(Path)
+     */
+    public static Ndb openWriteProtected(String dbPath, NdbConfig config) {
+        long ptr = NostrdbNative.ndbOpenWriteProtected(dbPath, config == null ? 0 : config.ptr());
+        if (ptr == 0) {
+            throw new NostrdbException("Failed to open database at " + dbPath);
+        }
+        return new Ndb(ptr, true);
+    }
+
+    /**
+     * Describe the loaded native library, for diagnosing mismatched JARs and
+     * native builds.
+     *
+     * @return e.g. {@code "nostrdb-jni 0.1.2 (nostrdb 0.8.0, git 78151cf0a1b2)"}
+     */
+    public static String nativeVersion() {
+        return NostrdbNative.nativeVersion();
+    }
+
+    /**
+     * Compute the NIP-01 id of an event before it is signed.
+     *
+     * <p>The id is the SHA-256 of {@code [0,pubkey,created_at,kind,tags,content]}
+     * with NIP-01's escaping, computed natively so that field order and string
+     * escaping match what relays and nostrdb expect. Any {@code id} and
+     * {@code sig} fields are ignored.
+     *
+     * @param json The event JSON, with pubkey, created_at, kind, tags, and content
+     * @return 32-byte event id
+     * @throws IllegalArgumentException if json is null or a field is missing or
+     *         mistyped
+     */
+    public static byte[] computeEventId(String json) {
+        if (json == null) {
+            throw new IllegalArgumentException("Event JSON must not be null");
+        }
+        return NostrdbNative.computeEventId(json);
+    }
+
+    /**
+     * Verify an event's id and signature without ingesting it, e.g. for events
+     * received outside of a relay subscription.
+     *
+     * <p>The id is recomputed over the NIP-01 serialization and compared with the
+     * event's {@code id} field; the BIP-340 signature is then checked over it.
+     *
+     * @param json The event JSON
+     * @return The outcome; never throws for an invalid event
+     * @throws IllegalArgumentException if json is null
+     */
+    public static VerifyResult verifyEvent(String json) {
+        if (json == null) {
+            throw new IllegalArgumentException("Event JSON must not be null");
+        }
+        return VerifyResult.fromCode(NostrdbNative.verifyEvent(json, false));
+    }
+
+    /**
+     * Verify an event's id and signature, throwing if it is invalid.
+     *
+     * @param json The event JSON
+     * @throws IllegalArgumentException if json is null or the event is malformed,
+     *         has the wrong id, or has a bad signature; the message says which
+     */
+    public static void verifyEventStrict(String json) {
+        if (json == null) {
+            throw new IllegalArgumentException("Event JSON must not be null");
+        }
+        NostrdbNative.verifyEvent(json, true);
+    }
+
+    /**
+     * Check whether this database was opened with {@link #openWriteProtected}.
+     *
+     * @return true if writes through this instance are rejected
+     */
+    public boolean isWriteProtected() {
+        return writeProtected;
+    }
+
+    /**
+     * Check whether this database has been closed.
+     *
+     * @return true after {@link #close()}
+     */
+    public boolean isClosed() {
+        return closed.get() || !NostrdbNative.ndbIsOpen(ptr);
+    }
+
+    /**
+     * Get the database directory.
+     *
+     * @return The canonical (absolute, symlink-free) database directory
+     * @throws IllegalStateException if the database is closed
+     */
+    public Path path() {
+        checkOpen();
+        return Path.of(NostrdbNative.ndbGetPath(ptr));
+    }
+
+    /**
+     * Get the settings the database is running with: map size, flags, ingester
+     * threads, and the auto-resize limit.
+     *
+     * <p>A write-protected instance sharing a database already open in this process
+     * reports the settings it was first opened with.
+     *
+     * @return The settings
+     * @throws IllegalStateException if the database is closed
+     */
+    public NdbSettings settings() {
+        checkOpen();
+        return NdbSettings.fromArray(NostrdbNative.ndbGetSettings(ptr));
+    }
+
+    /**
+     * Process a single Nostr event JSON.
+     *
+     * <p>The JSON can be in either relay format {@code ["EVENT", "subid", {...}]} or
+     * client format {@code ["EVENT", {...}]} or just the event object {@code {...}}.
+     *
+     * <p>The event is queued as it is, as {@link #processEvents(String)} queues each
+     * line; nostrdb's writer parses and verifies it later and drops it if it is
+     * invalid or already stored. Use {@link #processEventWithResult(String)} to have
+     * it checked first.
+     *
+     * @param json The JSON event string
+     * @throws NostrdbException if the event cannot be processed
+     */
+    public void processEvent(String json) {
+        checkOpen();
+        int result = NostrdbNative.processEvent(ptr, json);
+        if (result == 0) {
+            throw new NostrdbException("Failed to process event");
+        }
+    }
+
+    /**
+     * Process a single Nostr event JSON, reporting whether it was queued.
+     *
+     * <p>The event's id is looked up first, so re-ingesting a stored event returns
+     * {@link #PROCESS_DUPLICATE} instead of queuing it again; an event that is
+     * queued but not yet written is not yet a duplicate. Unless the database was
+     * opened with {@link NdbConfig#skipValidation(boolean)}, the id and signature
+     * are verified before the event is queued, so an event nostrdb's writer would
+     * drop returns {@link #PROCESS_REJECTED}. Events are checked as
+     * {@link #processEventsDetailed(String)} checks each line. Use
+     * {@link #processEventReturningKey(String, long)} to wait until it is written.
+     *
+     * @param json The JSON event string
+     * @return {@link #PROCESS_STORED}, {@link #PROCESS_DUPLICATE} or
+     *         {@link #PROCESS_REJECTED}
+     * @throws NostrdbException if the database cannot be read
+     */
+    public int processEventWithResult(String json) {
+        checkOpen();
+        return NostrdbNative.processEventWithResult(ptr, json);
+    }
+
+    /**
+     * Process a single Nostr event JSON, choosing for this call whether its id and
+     * signature are verified.
+     *
+     * <p>With {@code skipValidation} false, the event is verified before it is
+     * queued, even if the database was opened with
+     * {@link NdbConfig#skipValidation(boolean)}, and one that does not verify
+     * throws rather than being dropped silently. With it true, the event is stored
+     * unverified; nostrdb verifies every event of a database that does not skip
+     * validation, so this is only allowed on one that does. Otherwise behaves like
+     * {@link #processEvent(String)}.
+     *
+     * @param json The JSON event string
+     * @param skipValidation Whether to store the event without verifying it
+     * @throws IllegalStateException if skipValidation is true and the database
+     *         does not skip validation
+     * @throws NostrdbException if the event is malformed or does not verify, or
+     *         nostrdb refuses it
+     */
+    public void processEvent(String json, boolean skipValidation) {
+        checkOpen();
+        int result = NostrdbNative.processEventWithValidation(ptr, json, skipValidation);
+        if (result == 0) {
+            throw new NostrdbException("Failed to process event");
+        }
+    }
+
+    /**
+     * Process a single Nostr event from UTF-8 encoded JSON.
+     *
+     * <p>Behaves like {@link #processEvent(String)}, but the bytes are handed to
+     * nostrdb as they are, without decoding them into a {@code String} first.
+     *
+     * @param json The JSON event, UTF-8 encoded
+     * @throws IllegalArgumentException if the bytes are not valid UTF-8
+     * @throws NostrdbException if the event cannot be processed
+     */
+    public void processEventBytes(byte[] json) {
+        checkOpen();
+        int result = NostrdbNative.processEventBytes(ptr, json);
+        if (result == 0) {
+            throw new NostrdbException("Failed to process event");
+        }
+    }
+
+    /**
+     * Process a single Nostr event JSON and return the stored note's key.
+     *
+     * <p>Waits up to {@link #DEFAULT_INGEST_TIMEOUT_MS} for the note to be stored.
+     *
+     * @param json The JSON event string
+     * @return The note key, or a negative {@code INGEST_*} outcome
+     * @see #processEventReturningKey(String, long)
+     */
+    public long processEventReturningKey(String json) {
+        return processEventReturningKey(json, DEFAULT_INGEST_TIMEOUT_MS);
+    }
+
+    /**
+     * Process a single Nostr event JSON and return the stored note's key, saving
+     * a lookup by id to read it back.
+     *
+     * <p>nostrdb stores events asynchronously, so this polls for the note natively
+     * until it is stored or {@code timeoutMs} has passed. Events nostrdb would drop
+     * silently are reported instead, as negative outcomes:
+     * {@link #INGEST_INVALID} if the id or signature does not verify (unless the
+     * database skips validation), {@link #INGEST_DUPLICATE} if the note is already
+     * stored, in which case it is not processed again, {@link #INGEST_TIMEOUT} if
+     * the note was not stored in time, and {@link #INGEST_REJECTED} if nostrdb
+     * refused the event. Like {@link #processEvent(String)}, the JSON may be
+     * wrapped in a relay or client {@code EVENT} message.
+     *
+     * @param json The JSON event string
+     * @param timeoutMs How long to wait for the note to be stored, in milliseconds
+     * @return The note key, or a negative {@code INGEST_*} outcome
+     * @throws IllegalArgumentException if timeoutMs is negative
+     */
+    public long processEventReturningKey(String json, long timeoutMs) {
+        checkOpen();
+        if (timeoutMs < 0) {
+            throw new IllegalArgumentException("Timeout must not be negative, got " + timeoutMs);
+        }
+        return NostrdbNative.processEventReturningKey(ptr, json, timeoutMs);
+    }
+
+    /**
+     * Process multiple events from newline-delimited JSON.
+     *
+     * @param ldjson Newline-delimited JSON events
+     * @return The number of events successfully processed
+     */
+    public int processEvents(String ldjson) {
+        checkOpen();
+        int result = NostrdbNative.processEvents(ptr, ldjson);
+        if (result < 0) {
+            throw new NostrdbException("Failed to process events");
+        }
+        return result;
+    }
+
+    /**
+     * Process multiple events from newline-delimited JSON, choosing for this call
+     * whether their ids and signatures are verified.
+     *
+     * <p>As {@link #processEvent(String, boolean)}, for each line: with
+     * {@code skipValidation} false, an event that does not verify is skipped and
+     * not counted.
+     *
+     * @param ldjson Newline-delimited JSON events
+     * @param skipValidation Whether to store the events without verifying them
+     * @return The number of events successfully processed
+     * @throws IllegalStateException if skipValidation is true and the database
+     *         does not skip validation
+     */
+    public int processEvents(String ldjson, boolean skipValidation) {
+        checkOpen();
+        int result = NostrdbNative.processEventsWithValidation(ptr, ldjson, skipValidation);
+        if (result < 0) {
+            throw new NostrdbException("Failed to process events");
+        }
+        return result;
+    }
+
+    /**
+     * Process multiple events from UTF-8 encoded newline-delimited JSON.
+     *
+     * <p>Behaves like {@link #processEvents(String)}, but the batch is split on
+     * newlines natively, so an export read with {@code Files.readAllBytes} is
+     * ingested without being decoded into a {@code String}. A line that is not
+     * valid UTF-8 is skipped and not counted.
+     *
+     * @param ldjson Newline-delimited JSON events, UTF-8 encoded
+     * @return The number of events successfully processed
+     */
+    public int processEventsBytes(byte[] ldjson) {
+        checkOpen();
+        int result = NostrdbNative.processEventsBytes(ptr, ldjson);
+        if (result < 0) {
+            throw new NostrdbException("Failed to process events");
+        }
+        return result;
+    }
+
+    /**
+     * Process multiple events from UTF-8 encoded newline-delimited JSON,
+     * reporting progress and stopping early if a token is cancelled.
+     *
+     * <p>Lines are read as by {@link #processEventsFromFile(Path, ImportProgressCallback,
+     * CancelToken, long)}, with a line that is not valid UTF-8 counted as
+     * failed. Once the token is cancelled the import stops between lines; the
+     * rest of the batch starts at {@link ImportSummary#offset()}.
+     *
+     * @param ldjson Newline-delimited JSON events, UTF-8 encoded
+     * @param progress Receives progress reports, or null
+     * @param token Token to cancel the import from another thread, or null
+     * @return What happened to the batch's lines
+     */
+    public ImportSummary processEventsBytes(byte[] ldjson, ImportProgressCallback progress, CancelToken token) {
+        checkOpen();
+        long[] counts = NostrdbNative.processEventsBytesCancellable(ptr, ldjson, progress,
+            token == null ? 0 : token.ptr());
+        return ImportSummary.fromArray(counts);
+    }
+
+    /**
+     * Process multiple events from gzip-compressed newline-delimited JSON, such
+     * as a gzipped relay dump.
+     *
+     * <p>The data is decompressed natively as it is split into lines, so the
+     * uncompressed events are never held in memory at once. Lines are processed
+     * as by {@link #processEventsBytes(byte[])}; concatenated gzip members are
+     * read as one stream.
+     *
+     * @param gzipData Gzip-compressed newline-delimited JSON events
+     * @return The number of events successfully processed
+     * @throws NostrdbException if the data is not gzip or is truncated or
+     *         corrupt; the message gives the number of events ingested before
+     *         the failure, which stay stored
+     */
+    public int processEventsGzip(byte[] gzipData) {
+        checkOpen();
+        int result = NostrdbNative.processEventsGzip(ptr, gzipData);
+        if (result < 0) {
+            throw new NostrdbException("Failed to process events");
+        }
+        return result;
+    }
+
+    /**
+     * Process multiple events from newline-delimited JSON in a direct buffer,
+     * from its position to its limit.
+     *
+     * @param buffer A direct buffer holding UTF-8 newline-delimited JSON
+     * @return The number of events successfully processed
+     * @see #processEventsDirect(ByteBuffer, int, int)
+     */
+    public int processEventsDirect(ByteBuffer buffer) {
+        if (buffer == null || !buffer.isDirect()) {
+            throw new IllegalArgumentException("Buffer must be a direct ByteBuffer");
+        }
+        return processEventsDirect(buffer, buffer.position(), buffer.remaining());
+    }
+
+    /**
+     * Process multiple events from newline-delimited JSON read in place from
+     * a window of a direct buffer.
+     *
+     * <p>Behaves like {@link #processEventsBytes(byte[])}, but nothing is copied:
+     * the events are read straight from the buffer's memory, so a dump mapped
+     * with {@link java.nio.channels.FileChannel#map} can be streamed into the
+     * database window by window. The buffer's position and limit are ignored
+     * and left unchanged; it may be read-only.
+     *
+     * <pre>{@code
+     * try (FileChannel channel = FileChannel.open(dump)) {
+     *     MappedByteBuffer mapped = channel.map(MapMode.READ_ONLY, 0, channel.size());
+     *     int count = ndb.processEventsDirect(mapped, 0, mapped.capacity());
+     * }
+     * }</pre>
+     *
+     * @param buffer A direct buffer holding UTF-8 newline-delimited JSON
+     * @param offset Index of the window's first byte
+     * @param length Length of the window in bytes
+     * @return The number of events successfully processed
+     * @throws IllegalArgumentException if the buffer is not direct or the window is
+     *         outside its capacity
+     */
+    public int processEventsDirect(ByteBuffer buffer, int offset, int length) {
+        checkOpen();
+        if (buffer == null || !buffer.isDirect()) {
+            throw new IllegalArgumentException("Buffer must be a direct ByteBuffer");
+        }
+        if (offset < 0 || length < 0 || offset > buffer.capacity() - length) {
+            throw new IllegalArgumentException("Window [" + offset + ", " + offset + " + " + length
+                + ") is outside the buffer's capacity " + buffer.capacity());
+        }
+        int result = NostrdbNative.processEventsDirect(ptr, buffer, offset, length);
+        if (result < 0) {
+            throw new NostrdbException("Failed to process events");
+        }
+        return result;
+    }
+
+    /**
+     * Import events from a file of newline-delimited JSON.
+     *
+     * @param path The file to import
+     * @return What happened to the file's lines
+     * @see #processEventsFromFile(Path, ImportProgressCallback, CancelToken, long)
+     */
+    public ImportSummary processEventsFromFile(Path path) {
+        return processEventsFromFile(path, null, null, 0);
+    }
+
+    /**
+     * Import events from a file of newline-delimited JSON, reporting progress
+     * and stopping early if a token is cancelled.
+     *
+     * @param path The file to import
+     * @param progress Receives progress reports, or null
+     * @param token Token to cancel the import from another thread, or null
+     * @return What happened to the file's lines
+     * @see #processEventsFromFile(Path, ImportProgressCallback, CancelToken, long)
+     */
+    public ImportSummary processEventsFromFile(Path path, ImportProgressCallback progress, CancelToken token) {
+        return processEventsFromFile(path, progress, token, 0);
+    }
+
+    /**
+     * Import events from a file of newline-delimited JSON, such as a relay
+     * export, reporting progress and stopping early if a token is cancelled,
+     * starting at a byte offset.
+     *
+     * <p>The file is opened and read natively a line at a time, so its size is
+     * not limited by the Java heap. Lines are processed as by
+     * {@link #processEventsBytes(byte[])}. The progress callback is called on
+     * this thread every {@link ImportProgressCallback#INTERVAL} lines and once
+     * at the end. The token is checked before every line; once it is cancelled
+     * the import stops and the summary is marked as cancelled.
+     *
+     * <p>An import that was cancelled can be resumed by passing its
+     * {@link ImportSummary#offset()} as {@code resumeOffset}; the counts of the
+     * resumed import start from zero, while its offset is still from the start
+     * of the file. A gzipped file cannot be sought, so it is decompressed up to
+     * the offset again.
+     *
+     * <pre>{@code
+     * ImportSummary first = ndb.processEventsFromFile(dump, null, token, 0);
+     * // ... later, after the app comes back to the foreground
+     * ImportSummary rest = ndb.processEventsFromFile(dump, null, null, first.offset());
+     * }</pre>
+     *
+     * <p>A file starting with the gzip magic bytes is decompressed as it is
+     * read, as by {@link #processEventsGzip(byte[])}, so a gzipped dump does
+     * not need to be expanded on disk first. A truncated or corrupt gzip file
+     * throws {@link NostrdbException} giving the number of events ingested
+     * before the failure.
+     *
+     * <p>If the file cannot be opened, the native layer throws an
+     * {@link java.io.IOException} whose message names the path.
+     *
+     * @param path The file to import
+     * @param progress Receives progress reports, or null
+     * @param token Token to cancel the import from another thread, or null
+     * @param resumeOffset Byte offset to start at: 0, or the offset of an earlier
+     *        import of the same file
+     * @return What happened to the file's lines
+     * @throws IllegalArgumentException if the offset is negative
+     */
+    public ImportSummary processEventsFromFile(Path path, ImportProgressCallback progress, CancelToken token,
+                                               long resumeOffset) {
+        checkOpen();
+        long[] counts = NostrdbNative.processEventsFromFile(ptr, path.toString(), progress,
+            token == null ? 0 : token.ptr(), resumeOffset);
+        return ImportSummary.fromArray(counts);
+    }
+
+    /**
+     * Process a single Nostr event JSON, calling back once it has been written.
+     *
+     * <p>Waits up to {@link #DEFAULT_INGEST_TIMEOUT_MS} for the note to be stored.
+     *
+     * @param json The JSON event string
+     * @param callback Receives the outcome
+     * @see #processEventAsync(String, IngestCallback, long)
+     */
+    public void processEventAsync(String json, IngestCallback callback) {
+        processEventAsync(json, callback, DEFAULT_INGEST_TIMEOUT_MS);
+    }
+
+    /**
+     * Process a single Nostr event JSON, calling back once it has been written,
+     * instead of polling for it.
+     *
+     * <p>The event is checked as by {@link #processEventReturningKey(String, long)}
+     * and a one-shot subscription on its id is set up before it is queued, so
+     * this returns without waiting. The callback is then called exactly once on
+     * a native thread: with {@link IngestCallback#STORED} and the note key when
+     * the note lands, with {@link IngestCallback#TIMEOUT} once {@code timeoutMs}
+     * has passed, or straight away with {@link IngestCallback#INVALID},
+     * {@link IngestCallback#DUPLICATE}, or {@link IngestCallback#REJECTED}.
+     *
+     * <p>Each call waits on its own native thread; for batches, use
+     * {@link #processEvents(String)} and {@link #waitForIngestionCount(long, long)}.
+     *
+     * @param json The JSON event string
+     * @param callback Receives the outcome
+     * @param timeoutMs How long to wait for the note to be stored, in milliseconds
+     * @throws IllegalArgumentException if callback is null or timeoutMs is negative
+     */
+    public void processEventAsync(String json, IngestCallback callback, long timeoutMs) {
+        checkOpen();
+        if (callback == null) {
+            throw new IllegalArgumentException("Callback must not be null");
+        }
+        if (timeoutMs < 0) {
+            throw new IllegalArgumentException("Timeout must not be negative, got " + timeoutMs);
+        }
+        NostrdbNative.processEventAsync(ptr, json, callback, timeoutMs);
+    }
+
+    /**
+     * Wait until an event is stored.
+     *
+     * <p>nostrdb stores events on background writer threads, so a note is not
+     * visible to new transactions as soon as {@link #processEvent(String)}
+     * returns. Call this between writing and reading the note back:
+     *
+     * <pre>{@code
+     * ndb.processEvent(json);
+     * if (ndb.waitForIngestion(eventId, 1_000)) {
+     *     try (Transaction txn = ndb.beginTransaction()) {
+     *         Note note = ndb.getNoteById(txn, eventId).orElseThrow();
+     *     }
+     * }
+     * }</pre>
+     *
+     * <p>An event that nostrdb drops, e.g. because its signature does not verify,
+     * is never stored, so the wait runs out.
+     *
+     * @param eventId The event ID (32 bytes)
+     * @param timeoutMs How long to wait in milliseconds
+     * @return true once the note is stored, or false if it was not stored in time
+     * @throws IllegalArgumentException if eventId is not 32 bytes or timeoutMs is negative
+     */
+    public boolean waitForIngestion(byte[] eventId, long timeoutMs) {
+        checkOpen();
+        if (eventId == null || eventId.length != 32) {
+            throw new IllegalArgumentException("Event ID must be 32 bytes");
+        }
+        if (timeoutMs < 0) {
+            throw new IllegalArgumentException("Timeout must not be negative, got " + timeoutMs);
+        }
+        return NostrdbNative.waitForIngestion(ptr, eventId, timeoutMs);
+    }
+
+    /**
+     * Wait until the database holds at least a number of notes, e.g. after a
+     * batch import with {@link #processEvents(String)}.
+     *
+     * <p>nostrdb numbers notes from 1 in the order they are stored, so this
+     * waits for the note with key {@code expectedCount}; it is as cheap as one
+     * lookup per poll, however large the database. The count is of all notes,
+     * so include those stored before the import.
+     *
+     * @param expectedCount Number of notes to wait for; 0 or less returns true at once
+     * @param timeoutMs How long to wait in milliseconds
+     * @return true once the notes are stored, or false if they were not stored in time
+     * @throws IllegalArgumentException if timeoutMs is negative
+     */
+    public boolean waitForIngestionCount(long expectedCount, long timeoutMs) {
+        checkOpen();
+        if (timeoutMs < 0) {
+            throw new IllegalArgumentException("Timeout must not be negative, got " + timeoutMs);
+        }
+        return NostrdbNative.waitForIngestionCount(ptr, expectedCount, timeoutMs);
+    }
+
+    /**
+     * Process multiple events from newline-delimited JSON, reporting each line that failed.
+     *
+     * @param ldjson Newline-delimited JSON events
+     * @return Totals and the failed lines
+     * @see #processEventsDetailed(String, boolean)
+     */
+    public IngestReport processEventsDetailed(String ldjson) {
+        return processEventsDetailed(ldjson, false);
+    }
+
+    /**
+     * Process multiple events from newline-delimited JSON, reporting each line
+     * that failed with its line number and what was wrong.
+     *
+     * <p>nostrdb drops events that do not parse or verify on its writer
+     * threads without reporting them, so each line is checked natively before
+     * it is queued: the JSON must be an event, and unless the database skips
+     * validation, its id and signature must verify. An event that is already
+     * stored, or repeats an earlier line, is counted as a duplicate and not
+     * queued again. Successful and duplicate lines are only counted.
+     *
+     * @param ldjson Newline-delimited JSON events
+     * @param stopOnError Whether to stop at the first failed line
+     * @return Totals and the failed lines; {@link IngestReport#linesRead()} tells
+     *         how far ingestion got
+     */
+    public IngestReport processEventsDetailed(String ldjson, boolean stopOnError) {
+        checkOpen();
+        return IngestReport.parse(NostrdbNative.processEventsDetailed(ptr, ldjson, stopOnError));
+    }
+
+    /**
+     * Process a JSON array of events, such as {@code [{...}, {...}]} from a REST
+     * endpoint or fixture, reporting each element that failed.
+     *
+     * <p>The array is read natively an element at a time, so a large array is
+     * never parsed into a whole document. Each element is checked and queued as
+     * a line is by {@link #processEventsDetailed(String, boolean)}; in the
+     * report, elements are numbered from 1 in place of lines.
+     *
+     * @param json JSON array of events
+     * @return Totals and the failed elements
+     * @throws NostrdbException if the JSON is not an array, or is malformed
+     *         partway through; the elements before the error have been processed
+     */
+    public IngestReport processEventsArray(String json) {
+        checkOpen();
+        return IngestReport.parse(NostrdbNative.processEventsArray(ptr, json));
+    }
+
+    /**
+     * Process a single Nostr event JSON received from a relay.
+     *
+     * <p>The relay is recorded as one of the note's origins, also when the note
+     * is already stored, so {@link #getNoteRelays} can tell which relays have
+     * it. Like {@link #processEvent(String)}, the note is ingested
+     * asynchronously, and so is the relay.
+     *
+     * @param json The JSON event string
+     * @param relayUrl URL of the relay the event came from
+     * @throws IllegalArgumentException if relayUrl is null or empty
+     * @throws NostrdbException if the event cannot be processed
+     */
+    public void processEventFromRelay(String json, String relayUrl) {
+        checkOpen();
+        if (relayUrl == null) {
+            throw new IllegalArgumentException("Relay URL must not be null");
+        }
+        int result = NostrdbNative.processEventFromRelay(ptr, json, relayUrl);
+        if (result == 0) {
+            throw new NostrdbException("Failed to process event");
+        }
+    }
+
+    /**
+     * Process multiple events received from a relay, from newline-delimited JSON.
+     *
+     * <p>Each event is recorded as seen on the relay, as by
+     * {@link #processEventFromRelay(String, String)}, including events that are
+     * already stored.
+     *
+     * @param ldjson Newline-delimited JSON events
+     * @param relayUrl URL of the relay the events came from
+     * @return The number of events successfully processed
+     * @throws IllegalArgumentException if relayUrl is null or empty
+     */
+    public int processEventsFromRelay(String ldjson, String relayUrl) {
+        checkOpen();
+        if (relayUrl == null) {
+            throw new IllegalArgumentException("Relay URL must not be null");
+        }
+        int result = NostrdbNative.processEventsFromRelay(ptr, ldjson, relayUrl);
+        if (result < 0) {
+            throw new NostrdbException("Failed to process events");
+        }
+        return result;
+    }
+
+    /**
+     * Process a client message {@code ["EVENT", {...}]}, as a client sends it to
+     * a relay, through nostrdb's client ingestion path.
+     *
+     * <p>The envelope is checked natively, so it need not be stripped in Java.
+     * A bare event object is refused; see {@link #processClientEvent(String, boolean)}.
+     *
+     * @param json The client message JSON
+     * @throws NostrdbException if the message is not an EVENT envelope, naming what
+     *         is wrong, or the event cannot be processed
+     */
+    public void processClientEvent(String json) {
+        processClientEvent(json, false);
+    }
+
+    /**
+     * Process a client message {@code ["EVENT", {...}]}, as a client sends it to
+     * a relay, through nostrdb's client ingestion path, optionally accepting a
+     * bare event object as well.
+     *
+     * @param json The client message JSON
+     * @param acceptBareEvent Whether to process a bare event object as if it were wrapped
+     * @throws NostrdbException if the message is not an EVENT envelope, naming what
+     *         is wrong, or the event cannot be processed
+     */
+    public void processClientEvent(String json, boolean acceptBareEvent) {
+        checkOpen();
+        int result = NostrdbNative.processClientEvent(ptr, json, acceptBareEvent);
+        if (result == 0) {
+            throw new NostrdbException("Failed to process event");
+        }
+    }
+
+    /**
+     * Process a message received from a relay websocket, ingesting it if it is
+     * {@code ["EVENT", subscriptionId, {...}]}.
+     *
+     * <p>The message is handed to nostrdb whole, without unwrapping it in Java;
+     * the subscription id is ignored. Other relay messages, such as
+     * {@code EOSE}, {@code NOTICE}, and {@code OK}, are skipped after reading
+     * only their type.
+     *
+     * @param json The relay message JSON
+     * @return true if an event was processed, false if the message was skipped
+     * @throws NostrdbException if the JSON is not a relay message or the event
+     *         cannot be processed
+     */
+    public boolean processRelayEvent(String json) {
+        checkOpen();
+        return NostrdbNative.processRelayEvent(ptr, json) == 1;
+    }
+
+    /**
+     * Process newline-delimited messages received from relay websockets.
+     *
+     * <p>Each line is handled as by {@link #processRelayEvent(String)}, but
+     * lines that fail are counted instead of throwing.
+     *
+     * @param ldjson Newline-delimited relay messages
+     * @return How many lines were processed as events, skipped, or failed
+     */
+    public RelayMessageCounts processRelayEvents(String ldjson) {
+        checkOpen();
+        return RelayMessageCounts.fromArray(NostrdbNative.processRelayEvents(ptr, ldjson));
+    }
+
+    /**
+     * Get the running totals of the events ingested through this handle.
+     *
+     * <p>Every ingestion method, including the batch, file, relay, and
+     * asynchronous variants and {@link NoteBuilder#sign(byte[], Ndb)}, records
+     * each event it is handed, from any thread, since the database was opened
+     * or {@link #resetIngestStats()} was last called. Blank lines and relay
+     * messages other than {@code EVENT} are not counted.
+     *
+     * <p>Each total is exact, but a snapshot taken while other threads ingest
+     * may count an event in one total and not yet in another.
+     *
+     * @return The ingestion totals
+     */
+    public IngestStats getIngestStats() {
+        checkOpen();
+        return IngestStats.fromArray(NostrdbNative.getIngestStats(ptr));
+    }
+
+    /**
+     * Set the ingestion totals back to zero.
+     *
+     * @see #getIngestStats()
+     */
+    public void resetIngestStats() {
+        checkOpen();
+        NostrdbNative.resetIngestStats(ptr);
+    }
+
+    /**
+     * Begin a read transaction.
+     *
+     * <p><b>IMPORTANT:</b> Only one transaction per thread is allowed (LMDB constraint).
+     * Always use try-with-resources to ensure proper cleanup.
+     *
+     * @return A new Transaction
+     * @throws NostrdbException if the transaction cannot be started
+     */
+    public Transaction beginTransaction() {
+        checkOpen();
+        long txnPtr = NostrdbNative.beginTransaction(ptr);
+        if (txnPtr == 0) {
+            throw new NostrdbException("Failed to begin transaction");
+        }
+        return new Transaction(this, txnPtr);
+    }
+
+    /**
+     * Get a note by its 32-byte event ID.
+     *
+     * @param txn The transaction
+     * @param eventId 32-byte event ID
+     * @return The note, or empty if not found
+     */
+    public Optional<Note> getNoteById(Transaction txn, byte[] eventId) {
+        checkOpen();
+        if (eventId == null || eventId.length != 32) {
+            throw new IllegalArgumentException("Event ID must be 32 bytes");
+        }
+        byte[] data = NostrdbNative.getNoteById(ptr, txn.ptr(), eventId);
+        return Optional.ofNullable(data).map(Note::fromBytes);
+    }
+
+    /**
+     * Get a note by its hex-encoded event ID.
+     *
+     * @param txn The transaction
+     * @param eventIdHex 64-character hex event ID
+     * @return The note, or empty if not found
+     */
+    public Optional<Note> getNoteById(Transaction txn, String eventIdHex) {
+        return getNoteById(txn, HexUtil.decode(eventIdHex));
+    }
+
+    /**
+     * Find the replies in a thread, split into direct and nested replies.
+     *
+     * <p>Fetches the newest text notes (kind 1) whose {@code e} tags reference
+     * the root, and classifies each by NIP-10 markers, or by tag position for
+     * notes without markers.
+     *
+     * @param txn The transaction
+     * @param rootId 32-byte id of the thread's root event
+     * @param limit Maximum number of replies in total (must be positive and at most {@link Filter#MAX_LIMIT})
+     * @return The replies, grouped
+     * @throws IllegalArgumentException if the root id is not 32 bytes or limit is out of range
+     */
+    public ThreadReplies queryThread(Transaction txn, byte[] rootId, int limit) {
+        checkOpen();
+        if (rootId == null || rootId.length != 32) {
+            throw new IllegalArgumentException("Event ID must be 32 bytes");
+        }
+        validateLimit(limit);
+        byte[] data = NostrdbNative.queryThread(ptr, txn.ptr(), rootId, limit);
+        return ThreadReplies.parse(data);
+    }
+
+    /**
+     * Find the replies in a thread by its hex-encoded root id.
+     *
+     * @param txn The transaction
+     * @param rootIdHex 64-character hex id of the thread's root event
+     * @param limit Maximum number of replies in total
+     * @return The replies, grouped
+     * @see #queryThread(Transaction, byte[], int)
+     */
+    public ThreadReplies queryThread(Transaction txn, String rootIdHex, int limit) {
+        return queryThread(txn, HexUtil.decode(rootIdHex), limit);
+    }
+
+    /**
+     * Total the zaps a note has received.
+     *
+     * <p>Every zap receipt (kind 9735) referencing the note is read natively; only
+     * the total crosses into Java.
+     *
+     * @param txn The transaction
+     * @param noteId 32-byte id of the zapped note
+     * @return The total, which is zero if the note has no receipts
+     * @throws IllegalArgumentException if the note id is not 32 bytes
+     */
+    public ZapTotal sumZaps(Transaction txn, byte[] noteId) {
+        checkOpen();
+        if (noteId == null || noteId.length != 32) {
+            throw new IllegalArgumentException("Event ID must be 32 bytes");
+        }
+        return ZapTotal.parse(NostrdbNative.sumZaps(ptr, txn.ptr(), noteId));
+    }
+
+    /**
+     * Total the zaps a note has received, by its hex-encoded id.
+     *
+     * @param txn The transaction
+     * @param noteIdHex 64-character hex id of the zapped note
+     * @return The total
+     * @see #sumZaps(Transaction, byte[])
+     */
+    public ZapTotal sumZaps(Transaction txn, String noteIdHex) {
+        return sumZaps(txn, HexUtil.decode(noteIdHex));
+    }
+
+    /**
+     * Count the reposts and quotes of a note.
+     *
+     * <p>Quotes are told apart from replies by their tags natively; replies are
+     * not counted.
+     *
+     * @param txn The transaction
+     * @param noteId 32-byte id of the note
+     * @return The counts
+     * @throws IllegalArgumentException if the note id is not 32 bytes
+     */
+    public RepostCount countReposts(Transaction txn, byte[] noteId) {
+        checkOpen();
+        if (noteId == null || noteId.length != 32) {
+            throw new IllegalArgumentException("Event ID must be 32 bytes");
+        }
+        return RepostCount.parse(NostrdbNative.countReposts(ptr, txn.ptr(), noteId));
+    }
+
+    /**
+     * Count the reposts and quotes of a note, by its hex-encoded id.
+     *
+     * @param txn The transaction
+     * @param noteIdHex 64-character hex id of the note
+     * @return The counts
+     * @see #countReposts(Transaction, byte[])
+     */
+    public RepostCount countReposts(Transaction txn, String noteIdHex) {
+        return countReposts(txn, HexUtil.decode(noteIdHex));
+    }
+
+    /**
+     * Get a note by its internal key (faster for repeated lookups).
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return The note, or empty if not found
+     */
+    public Optional<Note> getNoteByKey(Transaction txn, long noteKey) {
+        checkOpen();
+        byte[] data = NostrdbNative.getNoteByKey(ptr, txn.ptr(), noteKey);
+        return Optional.ofNullable(data).map(Note::fromBytes);
+    }
+
+    /**
+     * Get a note by its 32-byte event ID, as CBOR.
+     *
+     * <p>The note is a CBOR map with the same keys as its JSON ({@code content},
+     * {@code created_at}, {@code id}, {@code kind}, {@code pubkey}, {@code sig},
+     * {@code tags}), but {@code id}, {@code pubkey}, and {@code sig} are byte
+     * strings rather than hex. For callers that decode CBOR anyway, this skips
+     * encoding and parsing JSON.
+     *
+     * @param txn The transaction
+     * @param eventId 32-byte event ID
+     * @return The note as CBOR, or empty if not found
+     * @throws IllegalArgumentException if the event ID is not 32 bytes
+     */
+    public Optional<byte[]> getNoteByIdCbor(Transaction txn, byte[] eventId) {
+        checkOpen();
+        if (eventId == null || eventId.length != 32) {
+            throw new IllegalArgumentException("Event ID must be 32 bytes");
+        }
+        return Optional.ofNullable(NostrdbNative.getNoteByIdCbor(ptr, txn.ptr(), eventId));
+    }
+
+    /**
+     * Get a note by its internal key, as CBOR.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return The note as CBOR (see {@link #getNoteByIdCbor}), or empty if not found
+     */
+    public Optional<byte[]> getNoteByKeyCbor(Transaction txn, long noteKey) {
+        checkOpen();
+        return Optional.ofNullable(NostrdbNative.getNoteByKeyCbor(ptr, txn.ptr(), noteKey));
+    }
+
+    /**
+     * Get a note by its 32-byte event ID, as JSON.
+     *
+     * <p>The JSON is the one {@link #getNoteById} parses, handed over as a Java
+     * string rather than UTF-8 bytes. nostrdb can store content that is not
+     * valid UTF-8, e.g. from an unpaired surrogate escape in an event ingested
+     * without validation; such bytes come back as U+FFFD rather than failing
+     * the call, or throw {@link InvalidContentException} in strict mode.
+     *
+     * @param txn The transaction
+     * @param eventId 32-byte event ID
+     * @return The note's JSON, or empty if not found
+     * @throws IllegalArgumentException if the event ID is not 32 bytes
+     */
+    public Optional<String> getNoteByIdString(Transaction txn, byte[] eventId) {
+        checkOpen();
+        if (eventId == null || eventId.length != 32) {
+            throw new IllegalArgumentException("Event ID must be 32 bytes");
+        }
+        return Optional.ofNullable(NostrdbNative.getNoteByIdString(ptr, txn.ptr(), eventId));
+    }
+
+    /**
+     * Get a note by its hex-encoded event ID, as JSON.
+     *
+     * @param txn The transaction
+     * @param eventIdHex 64-character hex event ID
+     * @return The note's JSON, or empty if not found
+     * @see #getNoteByIdString(Transaction, byte[])
+     */
+    public Optional<String> getNoteByIdString(Transaction txn, String eventIdHex) {
+        return getNoteByIdString(txn, HexUtil.decode(eventIdHex));
+    }
+
+    /**
+     * Get a note by its internal key, as JSON.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return The note's JSON, or empty if not found
+     * @see #getNoteByIdString(Transaction, byte[])
+     */
+    public Optional<String> getNoteByKeyString(Transaction txn, long noteKey) {
+        checkOpen();
+        return Optional.ofNullable(NostrdbNative.getNoteByKeyString(ptr, txn.ptr(), noteKey));
+    }
+
+    /**
+     * Get many notes by their internal keys in one call.
+     *
+     * <p>Fetching a timeline's notes this way costs one native call instead of one
+     * per note. Keys that no longer resolve to a note are skipped.
+     *
+     * @param txn The transaction
+     * @param noteKeys Internal note keys
+     * @return The notes found, in the order of {@code noteKeys}
+     * @throws IllegalArgumentException if noteKeys is null
+     */
+    public NoteBatch getNotesByKeys(Transaction txn, long[] noteKeys) {
+        checkOpen();
+        if (noteKeys == null) {
+            throw new IllegalArgumentException("Note keys must not be null");
+        }
+        return NoteBatch.parseByKeys(NostrdbNative.getNotesByKeys(ptr, txn.ptr(), noteKeys), noteKeys);
+    }
+
+    /**
+     * Get many notes by their event IDs in one call.
+     *
+     * <p>IDs that aren't stored are skipped; {@link NoteBatch#isPresent(int)} tells
+     * which, e.g. to find the events a relay sent that still need fetching.
+     *
+     * @param txn The transaction
+     * @param eventIds 32-byte event IDs
+     * @return The notes found, in the order of {@code eventIds}
+     * @throws IllegalArgumentException if eventIds is null or an ID is not 32 bytes
+     */
+    public NoteBatch getNotesByIds(Transaction txn, List<byte[]> eventIds) {
+        checkOpen();
+        if (eventIds == null) {
+            throw new IllegalArgumentException("Event IDs must not be null");
+        }
+        ByteBuffer ids = ByteBuffer.allocate(eventIds.size() * 32);
+        for (int i = 0; i < eventIds.size(); i++) {
+            byte[] id = eventIds.get(i);
+            if (id == null || id.length != 32) {
+                throw new IllegalArgumentException("Event ID at index " + i + " must be 32 bytes");
+            }
+            ids.put(id);
+        }
+        byte[] data = NostrdbNative.getNotesByIds(ptr, txn.ptr(), ids.array());
+        return NoteBatch.parseByIds(data, eventIds.size());
+    }
+
+    /**
+     * Get a note's raw bytes by its internal key, without converting it to JSON.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return The note in nostrdb's internal layout (see {@link RawNote}), or empty if not found
+     */
+    public Optional<byte[]> getNoteRawByKey(Transaction txn, long noteKey) {
+        checkOpen();
+        return Optional.ofNullable(NostrdbNative.getNoteRawByKey(ptr, txn.ptr(), noteKey));
+    }
+
+    /**
+     * Get a note's JSON as nostrdb stores it, by its internal key.
+     *
+     * <p>{@link #getNoteByKey} parses the note into a {@link Note}; this returns
+     * the event JSON nostrdb itself produces, unchanged, e.g. for relaying stored
+     * events to other clients.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return The event JSON, or empty if not found
+     */
+    public Optional<String> getNoteJsonByKey(Transaction txn, long noteKey) {
+        checkOpen();
+        byte[] data = NostrdbNative.getNoteJsonByKey(ptr, txn.ptr(), noteKey);
+        return Optional.ofNullable(data).map(json -> new String(json, StandardCharsets.UTF_8));
+    }
+
+    /**
+     * Get a note's JSON as nostrdb stores it, by its 32-byte event ID.
+     *
+     * @param txn The transaction
+     * @param eventId 32-byte event ID
+     * @return The event JSON, or empty if not found
+     * @see #getNoteJsonByKey(Transaction, long)
+     */
+    public Optional<String> getNoteJsonById(Transaction txn, byte[] eventId) {
+        checkOpen();
+        if (eventId == null || eventId.length != 32) {
+            throw new IllegalArgumentException("Event ID must be 32 bytes");
+        }
+        byte[] data = NostrdbNative.getNoteJsonById(ptr, txn.ptr(), eventId);
+        return Optional.ofNullable(data).map(json -> new String(json, StandardCharsets.UTF_8));
+    }
+
+    /**
+     * Get a note's JSON as nostrdb stores it, by its hex-encoded event ID.
+     *
+     * @param txn The transaction
+     * @param eventIdHex 64-character hex event ID
+     * @return The event JSON, or empty if not found
+     */
+    public Optional<String> getNoteJsonById(Transaction txn, String eventIdHex) {
+        return getNoteJsonById(txn, HexUtil.decode(eventIdHex));
+    }
+
+    /**
+     * Verify a stored note's id and signature, e.g. after ingesting with
+     * validation skipped.
+     *
+     * @param txn The transaction
+     * @param eventId 32-byte event ID
+     * @return The outcome, or empty if not found
+     * @see #verifyEvent(String)
+     */
+    public Optional<VerifyResult> verifyNoteById(Transaction txn, byte[] eventId) {
+        checkOpen();
+        if (eventId == null || eventId.length != 32) {
+            throw new IllegalArgumentException("Event ID must be 32 bytes");
+        }
+        int code = NostrdbNative.verifyNoteById(ptr, txn.ptr(), eventId);
+        return code < 0 ? Optional.empty() : Optional.of(VerifyResult.fromCode(code));
+    }
+
+    /**
+     * Verify a stored note's id and signature, by its hex-encoded event ID.
+     *
+     * @param txn The transaction
+     * @param eventIdHex 64-character hex event ID
+     * @return The outcome, or empty if not found
+     */
+    public Optional<VerifyResult> verifyNoteById(Transaction txn, String eventIdHex) {
+        return verifyNoteById(txn, HexUtil.decode(eventIdHex));
+    }
+
+    /**
+     * Get a note's event ID by its internal key, without serializing the note.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return 32-byte event ID, or empty if not found
+     */
+    public Optional<byte[]> getNoteIdByKey(Transaction txn, long noteKey) {
+        checkOpen();
+        return Optional.ofNullable(NostrdbNative.getNoteIdByKey(ptr, txn.ptr(), noteKey));
+    }
+
+    /**
+     * Get a note's internal key by its event ID, without serializing the note.
+     *
+     * @param txn The transaction
+     * @param eventId 32-byte event ID
+     * @return The note key, or empty if not found
+     * @throws IllegalArgumentException if the event ID is not 32 bytes
+     */
+    public OptionalLong getNoteKeyById(Transaction txn, byte[] eventId) {
+        checkOpen();
+        if (eventId == null || eventId.length != 32) {
+            throw new IllegalArgumentException("Event ID must be 32 bytes");
+        }
+        long key = NostrdbNative.getNoteKeyById(ptr, txn.ptr(), eventId);
+        return key < 0 ? OptionalLong.empty() : OptionalLong.of(key);
+    }
+
+    /**
+     * Get a note's internal key by its hex-encoded event ID.
+     *
+     * @param txn The transaction
+     * @param eventIdHex 64-character hex event ID
+     * @return The note key, or empty if not found
+     */
+    public OptionalLong getNoteKeyById(Transaction txn, String eventIdHex) {
+        return getNoteKeyById(txn, HexUtil.decode(eventIdHex));
+    }
+
+    /**
+     * Check whether a note is stored, by its event ID.
+     *
+     * <p>Only the id index is read, so this is cheaper than
+     * {@link #getNoteById(Transaction, byte[])} when a yes or no is enough, e.g. to
+     * decide whether to request an event from a relay.
+     *
+     * @param txn The transaction
+     * @param eventId 32-byte event ID
+     * @return true if the note is stored
+     * @throws IllegalArgumentException if the event ID is not 32 bytes
+     */
+    public boolean noteExists(Transaction txn, byte[] eventId) {
+        checkOpen();
+        if (eventId == null || eventId.length != 32) {
+            throw new IllegalArgumentException("Event ID must be 32 bytes");
+        }
+        return NostrdbNative.noteExists(ptr, txn.ptr(), eventId);
+    }
+
+    /**
+     * Check whether a note is stored, by its hex-encoded event ID.
+     *
+     * @param txn The transaction
+     * @param eventIdHex 64-character hex event ID
+     * @return true if the note is stored
+     */
+    public boolean noteExists(Transaction txn, String eventIdHex) {
+        return noteExists(txn, HexUtil.decode(eventIdHex));
+    }
+
+    /**
+     * Check which of many event IDs are stored, in one call.
+     *
+     * <p>As {@link #noteExists(Transaction, byte[])} for each ID, e.g. to find which
+     * of the ids a relay offers still need fetching.
+     *
+     * @param txn The transaction
+     * @param eventIds 32-byte event IDs
+     * @return Bit {@code i} set if {@code eventIds.get(i)} is stored
+     * @throws IllegalArgumentException if eventIds is null or an ID is not 32 bytes
+     */
+    public BitSet notesExist(Transaction txn, List<byte[]> eventIds) {
+        checkOpen();
+        if (eventIds == null) {
+            throw new IllegalArgumentException("Event IDs must not be null");
+        }
+        ByteBuffer ids = ByteBuffer.allocate(eventIds.size() * 32);
+        for (int i = 0; i < eventIds.size(); i++) {
+            byte[] id = eventIds.get(i);
+            if (id == null || id.length != 32) {
+                throw new IllegalArgumentException("Event ID at index " + i + " must be 32 bytes");
+            }
+            ids.put(id);
+        }
+        return BitSet.valueOf(NostrdbNative.notesExist(ptr, txn.ptr(), ids.array()));
+    }
+
+    /**
+     * Get a note's kind by its internal key.
+     *
+     * <p>This and the other field accessors read one field natively, without
+     * serializing the whole note as {@link #getNoteByKey} does; e.g. for list
+     * views that show only a few fields.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return The kind, or empty if not found
+     */
+    public OptionalInt getNoteKind(Transaction txn, long noteKey) {
+        checkOpen();
+        long kind = NostrdbNative.getNoteKind(ptr, txn.ptr(), noteKey);
+        return kind < 0 ? OptionalInt.empty() : OptionalInt.of((int) kind);
+    }
+
+    /**
+     * Get a note's creation timestamp by its internal key.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return Unix timestamp in seconds, or empty if not found
+     */
+    public OptionalLong getNoteCreatedAt(Transaction txn, long noteKey) {
+        checkOpen();
+        long createdAt = NostrdbNative.getNoteCreatedAt(ptr, txn.ptr(), noteKey);
+        return createdAt < 0 ? OptionalLong.empty() : OptionalLong.of(createdAt);
+    }
+
+    /**
+     * Get a note's author public key by its internal key.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return 32-byte public key, or empty if not found
+     */
+    public Optional<byte[]> getNotePubkey(Transaction txn, long noteKey) {
+        checkOpen();
+        return Optional.ofNullable(NostrdbNative.getNotePubkey(ptr, txn.ptr(), noteKey));
+    }
+
+    /**
+     * Get a note's content by its internal key.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return The content, or empty if not found
+     */
+    public Optional<String> getNoteContent(Transaction txn, long noteKey) {
+        checkOpen();
+        byte[] data = NostrdbNative.getNoteContent(ptr, txn.ptr(), noteKey);
+        return Optional.ofNullable(data).map(content -> new String(content, StandardCharsets.UTF_8));
+    }
+
+    /**
+     * Get a note's content as UTF-8 bytes, by its internal key.
+     *
+     * <p>The bytes are copied straight from the stored note, skipping both the
+     * JSON serialization of {@link #getNoteByKey} and the decoding of
+     * {@link #getNoteContent}, e.g. for writing long-form content to a stream.
+     * Content that is not valid UTF-8 is repaired as for every other accessor
+     * (see {@link NdbConfig#strictUtf8}).
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return The content as UTF-8, or empty if not found
+     */
+    public Optional<byte[]> getNoteContentBytes(Transaction txn, long noteKey) {
+        checkOpen();
+        return Optional.ofNullable(NostrdbNative.getNoteContent(ptr, txn.ptr(), noteKey));
+    }
+
+    /**
+     * Get the length of a note's content by its internal key, e.g. to size a
+     * buffer before fetching it.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return Length in UTF-8 bytes, or empty if not found
+     */
+    public OptionalInt getNoteContentLength(Transaction txn, long noteKey) {
+        checkOpen();
+        int length = NostrdbNative.getNoteContentLength(ptr, txn.ptr(), noteKey);
+        return length < 0 ? OptionalInt.empty() : OptionalInt.of(length);
+    }
+
+    /**
+     * Get a note's signature by its internal key.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return 64-byte signature, or empty if not found
+     */
+    public Optional<byte[]> getNoteSig(Transaction txn, long noteKey) {
+        checkOpen();
+        return Optional.ofNullable(NostrdbNative.getNoteSig(ptr, txn.ptr(), noteKey));
+    }
+
+    /**
+     * Get a note's stored size by its internal key.
+     *
+     * <p>This is the size of nostrdb's own note buffer, as returned by
+     * {@link #getNoteRawByKey}, e.g. for budgeting a cache or spotting oversized
+     * events; it is not the length of the note's JSON.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return Size in bytes, or empty if not found
+     */
+    public OptionalLong getNoteSize(Transaction txn, long noteKey) {
+        checkOpen();
+        long size = NostrdbNative.getNoteSize(ptr, txn.ptr(), noteKey);
+        return size < 0 ? OptionalLong.empty() : OptionalLong.of(size);
+    }
+
+    /**
+     * Get the number of tags on a note by its internal key.
+     *
+     * <p>With {@link #getNoteTagLength} and {@link #getNoteTag}, this walks a
+     * note's tags natively, without deserializing the whole note.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return The tag count, or empty if not found
+     */
+    public OptionalInt getNoteTagCount(Transaction txn, long noteKey) {
+        checkOpen();
+        int count = NostrdbNative.noteTagCount(ptr, txn.ptr(), noteKey);
+        return count < 0 ? OptionalInt.empty() : OptionalInt.of(count);
+    }
+
+    /**
+     * Get the number of elements in one of a note's tags.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @param tagIndex Index of the tag
+     * @return The element count
+     * @throws java.util.NoSuchElementException if the note is not found
+     * @throws IndexOutOfBoundsException if the tag index is out of range
+     */
+    public int getNoteTagLength(Transaction txn, long noteKey, int tagIndex) {
+        checkOpen();
+        return NostrdbNative.noteTagLen(ptr, txn.ptr(), noteKey, tagIndex);
+    }
+
+    /**
+     * Get one element of one of a note's tags as a string.
+     *
+     * <p>nostrdb stores 64-character hex elements (event ids, pubkeys) as 32
+     * bytes; these come back hex-encoded, as in the note's JSON.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @param tagIndex Index of the tag
+     * @param elemIndex Index of the element in the tag
+     * @return The element
+     * @throws java.util.NoSuchElementException if the note is not found
+     * @throws IndexOutOfBoundsException if an index is out of range
+     */
+    public String getNoteTag(Transaction txn, long noteKey, int tagIndex, int elemIndex) {
+        return new String(getNoteTag(txn, noteKey, tagIndex, elemIndex, false), StandardCharsets.UTF_8);
+    }
+
+    /**
+     * Get one element of one of a note's tags as bytes.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @param tagIndex Index of the tag
+     * @param elemIndex Index of the element in the tag
+     * @param rawIds Whether to return elements stored as ids as their 32 bytes
+     *               rather than as UTF-8 hex
+     * @return The element's UTF-8 bytes, or 32 id bytes if {@code rawIds} is set
+     *         and the element is stored as an id
+     * @throws java.util.NoSuchElementException if the note is not found
+     * @throws IndexOutOfBoundsException if an index is out of range
+     */
+    public byte[] getNoteTag(Transaction txn, long noteKey, int tagIndex, int elemIndex, boolean rawIds) {
+        checkOpen();
+        return NostrdbNative.noteTagGet(ptr, txn.ptr(), noteKey, tagIndex, elemIndex, rawIds);
+    }
+
+    /**
+     * Get the event ids or pubkeys a note's {@code e}, {@code p}, or {@code q}
+     * tags reference, without parsing its tags in Java.
+     *
+     * <pre>{@code
+     * List<byte[]> mentioned = ndb.getNoteTagIds(txn, noteKey, "p").orElseThrow().ids();
+     * }</pre>
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @param tagName "e", "p", or "q"
+     * @return The 32-byte values in tag order, with the number of malformed
+     *         ones skipped, or empty if the note is not found
+     * @throws IllegalArgumentException if tagName is not "e", "p", or "q"
+     */
+    public Optional<TagIds> getNoteTagIds(Transaction txn, long noteKey, String tagName) {
+        checkOpen();
+        if (tagName == null) {
+            throw new IllegalArgumentException("Tag name must not be null");
+        }
+        byte[] data = NostrdbNative.getNoteTagIds(ptr, txn.ptr(), noteKey, tagName);
+        return Optional.ofNullable(data).map(TagIds::parse);
+    }
+
+    /**
+     * Open a handle on a note by its internal key, for reading several of its
+     * fields without deserializing the whole note.
+     *
+     * <p>The handle reads from {@code txn}; close it before the transaction.
+     * Once the transaction is closed, its accessors throw.
+     *
+     * @param txn The transaction to read from
+     * @param noteKey Internal note key
+     * @return A handle on the note, or empty if not found
+     */
+    public Optional<NoteHandle> openNote(Transaction txn, long noteKey) {
+        checkOpen();
+        long notePtr = NostrdbNative.noteOpen(ptr, txn.ptr(), noteKey);
+        return notePtr == 0 ? Optional.empty() : Optional.of(new NoteHandle(notePtr));
+    }
+
+    /**
+     * Open a handle on a note by its 32-byte event ID.
+     *
+     * @param txn The transaction to read from
+     * @param eventId 32-byte event ID
+     * @return A handle on the note, or empty if not found
+     * @see #openNote(Transaction, long)
+     */
+    public Optional<NoteHandle> openNote(Transaction txn, byte[] eventId) {
+        checkOpen();
+        if (eventId == null || eventId.length != 32) {
+            throw new IllegalArgumentException("Event ID must be 32 bytes");
+        }
+        long notePtr = NostrdbNative.noteOpenById(ptr, txn.ptr(), eventId);
+        return notePtr == 0 ? Optional.empty() : Optional.of(new NoteHandle(notePtr));
+    }
+
+    /**
+     * Get the relays a note was seen on, by its internal key.
+     *
+     * <p>Relays are recorded by {@link #processEventFromRelay}; a note only ever
+     * ingested without one has none.
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return Relay URLs, or empty if not found
+     */
+    public Optional<List<String>> getNoteRelays(Transaction txn, long noteKey) {
+        checkOpen();
+        String[] relays = NostrdbNative.getNoteRelays(ptr, txn.ptr(), noteKey);
+        return Optional.ofNullable(relays).map(List::of);
+    }
+
+    /**
+     * Get a note's content split into blocks, by its internal key.
+     *
+     * <p>nostrdb parses a note's content into text, {@code nostr:} mentions,
+     * hashtags, URLs, and lightning invoices when it ingests the note, so
+     * rendering it needs no parsing in Java. The blocks cover the content
+     * contiguously; mentions of a pubkey or event carry its id. Content
+     * nostrdb did not parse, as for kinds it doesn't parse, is one text block.
+     *
+     * <pre>{@code
+     * String content = ndb.getNoteContent(txn, key).orElseThrow();
+     * for (ContentBlock block : ndb.getNoteBlocks(txn, key).orElseThrow()) {
+     *     String text = block.textIn(content);
+     *     switch (block.type()) {
+     *         case MENTION -> block.mentionedPubkey().ifPresent(pubkey -> renderProfileLink(pubkey, text));
+     *         case HASHTAG -> renderTag(text);
+     *         default -> renderText(text);
+     *     }
+     * }
+     * }</pre>
+     *
+     * @param txn The transaction
+     * @param noteKey Internal note key
+     * @return The blocks in content order, or empty if not found
+     */
+    public Optional<List<ContentBlock>> getNoteBlocks(Transaction txn, long noteKey) {
+        checkOpen();
+        byte[] data = NostrdbNative.getNoteBlocks(ptr, txn.ptr(), noteKey);
+        return Optional.ofNullable(data).map(ContentBlock::parseResults);
+    }
+
+    /**
+     * Query for notes matching a filter, at most 100 (or the filter's own
+     * limit, if smaller).
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @return List of query results (note keys)
+     */
+    public List<QueryResult> query(Transaction txn, Filter filter) {
+        return query(txn, filter, 100);
+    }
+
+    /**
+     * Query for notes matching a filter with an explicit limit.
+     *
+     * <p>If the filter has its own limit, the smaller of the two applies. Pass
+     * {@link #FILTER_LIMIT} to use only the filter's limit.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of results (positive and at most {@link Filter#MAX_LIMIT}),
+     *              or {@link #FILTER_LIMIT}
+     * @return List of query results (note keys)
+     * @throws IllegalArgumentException if limit is invalid
+     */
+    public List<QueryResult> query(Transaction txn, Filter filter, int limit) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        long[] keys = NostrdbNative.queryKeys(ptr, txn.ptr(), filter.ptr(), limit);
+        return QueryResult.fromKeys(keys);
+    }
+
+    /**
+     * Query for notes matching a filter, writing the note keys into a reusable
+     * direct buffer instead of allocating a new array per query.
+     *
+     * <p>The results are written from index 0, regardless of the buffer's
+     * position and limit, as a little-endian {@code int} count followed by
+     * that many {@code long} note keys. If the buffer's capacity is too small,
+     * nothing is written and the negated required size is returned, so the
+     * caller can allocate a larger buffer and retry:
+     *
+     * <pre>{@code
+     * int n = ndb.queryInto(txn, filter, 500, buffer);
+     * if (n < 0) {
+     *     buffer = ByteBuffer.allocateDirect(-n);
+     *     n = ndb.queryInto(txn, filter, 500, buffer);
+     * }
+     * buffer.order(ByteOrder.LITTLE_ENDIAN);
+     * int count = buffer.getInt(0);
+     * }</pre>
+     *
+     * <p>The limit combines with the filter's own limit as in
+     * {@link #query(Transaction, Filter, int)}.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of results (positive and at most {@link Filter#MAX_LIMIT}),
+     *              or {@link #FILTER_LIMIT}
+     * @param buffer A writable direct buffer
+     * @return Number of bytes written, or the negated required size if the buffer is too small
+     * @throws IllegalArgumentException if limit is invalid or the buffer is not a writable direct buffer
+     */
+    public int queryInto(Transaction txn, Filter filter, int limit, ByteBuffer buffer) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        if (buffer == null || !buffer.isDirect()) {
+            throw new IllegalArgumentException("Buffer must be a direct ByteBuffer");
+        }
+        if (buffer.isReadOnly()) {
+            throw new IllegalArgumentException("Buffer must be writable");
+        }
+        return NostrdbNative.queryInto(ptr, txn.ptr(), filter.ptr(), limit, buffer);
+    }
+
+    /**
+     * Count the notes matching a filter.
+     *
+     * <p>Matches are counted natively without returning their keys, so there
+     * is no need to pass a large limit. If the filter has its own limit, the
+     * count is capped by it.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @return The number of matching notes
+     */
+    public long queryCount(Transaction txn, Filter filter) {
+        checkOpen();
+        return NostrdbNative.queryCount(ptr, txn.ptr(), filter.ptr());
+    }
+
+    /**
+     * Query for notes matching a filter, one page at a time.
+     *
+     * <p>Pages are ordered newest first, with notes that share a
+     * {@code created_at} ordered consistently, so pages never overlap or skip
+     * notes. Unlike {@link #queryCursor}, the position is kept in the returned
+     * cursor rather than native state, so the next page can be fetched in a
+     * later transaction. The filter's own limit is ignored.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param pageSize Maximum number of results in the page (must be positive and
+     *                 at most {@link Filter#MAX_LIMIT})
+     * @param cursor {@link QueryPage#nextCursor()} of the previous page, or null for
+     *               the first page
+     * @return The page and the cursor for the next one
+     * @throws IllegalArgumentException if pageSize is out of range or the cursor is malformed
+     */
+    public QueryPage queryPage(Transaction txn, Filter filter, int pageSize, byte[] cursor) {
+        checkOpen();
+        validateLimit(pageSize);
+        return QueryPage.parse(NostrdbNative.queryPage(ptr, txn.ptr(), filter.ptr(), pageSize, cursor));
+    }
+
+    /**
+     * Open a cursor paging through every note matching a filter.
+     *
+     * <p>Use this instead of a single large query for exports and other
+     * result sets that should not be materialized at once. Pages are read
+     * from {@code txn}, which must stay open while the cursor is used. The
+     * filter's own limit, if any, caps the total number of results.
+     *
+     * @param txn The transaction to read from
+     * @param filter The query filter (copied; it may be closed afterwards)
+     * @return A cursor, to be closed before the transaction
+     */
+    public QueryCursor queryCursor(Transaction txn, Filter filter) {
+        checkOpen();
+        return new QueryCursor(NostrdbNative.queryBegin(ptr, txn.ptr(), filter.ptr()));
+    }
+
+    /**
+     * Query for notes matching a filter, in a chosen order.
+     *
+     * <p>{@link QueryOrder#NEWEST_REVERSED} returns the same notes as
+     * {@link #query(Transaction, Filter, int)} in ascending order, while
+     * {@link QueryOrder#OLDEST_FIRST} returns the oldest {@code limit} notes.
+     * Results are reordered natively.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of results (positive and at most {@link Filter#MAX_LIMIT}),
+     *              or {@link #FILTER_LIMIT}
+     * @param order Which matches to return, and in what order
+     * @return List of query results (note keys)
+     * @throws IllegalArgumentException if limit is invalid or order is null
+     */
+    public List<QueryResult> queryOrdered(Transaction txn, Filter filter, int limit, QueryOrder order) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        if (order == null) {
+            throw new IllegalArgumentException("Order must not be null");
+        }
+        long[] keys = NostrdbNative.queryOrdered(ptr, txn.ptr(), filter.ptr(), limit, order.code());
+        return QueryResult.fromKeys(keys);
+    }
+
+    /**
+     * Query for notes matching a filter and a Java predicate.
+     *
+     * <p>Candidates matching the filter are passed to the predicate as JSON,
+     * newest first, until {@code limit} notes are accepted or no candidates are
+     * left. Use this for conditions NIP-01 filters can't express; the filter
+     * should still narrow the candidates as far as possible.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of accepted results (must be positive and at most {@link Filter#MAX_LIMIT})
+     * @param predicate Decides which candidates to keep; an exception it throws aborts the query
+     * @return List of query results (note keys)
+     * @throws IllegalArgumentException if limit is not positive or exceeds MAX_LIMIT
+     */
+    public List<QueryResult> query(Transaction txn, Filter filter, int limit, NotePredicate predicate) {
+        checkOpen();
+        validateLimit(limit);
+        if (predicate == null) {
+            throw new IllegalArgumentException("Predicate must not be null");
+        }
+        byte[] resultData = NostrdbNative.queryWithPredicate(ptr, txn.ptr(), filter.ptr(), limit, predicate);
+        return QueryResult.parseResults(resultData);
+    }
+
+    /**
+     * Query for notes matching a filter, leaving out muted authors and events.
+     *
+     * <p>Muted notes are skipped natively while results are collected, so the
+     * limit counts only visible notes: a timeline query still returns
+     * {@code limit} notes if enough unmuted ones exist.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of visible results (positive and at most
+     *              {@link Filter#MAX_LIMIT}), or {@link #FILTER_LIMIT}
+     * @param mutedAuthors 32-byte public keys whose notes are excluded
+     * @param mutedIds 32-byte ids of events to exclude
+     * @param muteThreads Also exclude notes whose "p" tags reference a muted author
+     *                    or whose "e" tags reference a muted event
+     * @return List of query results (note keys)
+     * @throws IllegalArgumentException if limit is invalid or a muted value is not 32 bytes
+     */
+    public List<QueryResult> queryExcluding(Transaction txn, Filter filter, int limit,
+                                            Collection<byte[]> mutedAuthors, Collection<byte[]> mutedIds,
+                                            boolean muteThreads) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        byte[] resultData = NostrdbNative.queryExcluding(ptr, txn.ptr(), filter.ptr(), limit,
+            pack("Muted author", mutedAuthors), pack("Muted id", mutedIds), muteThreads);
+        return QueryResult.parseResults(resultData);
+    }
+
+    /**
+     * Query for notes matching a filter, leaving out notes hidden by an
+     * author's stored mute list.
+     *
+     * <p>The author's newest mute list (NIP-51, kind 10000) is read natively:
+     * notes by its muted authors, its muted events, notes tagged with its
+     * muted hashtags, and notes whose content contains one of its muted
+     * words, ignoring case, are skipped while results are collected. Its
+     * encrypted private entries are not applied. Without a stored mute list,
+     * nothing is left out.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of visible results (positive and at most
+     *              {@link Filter#MAX_LIMIT}), or {@link #FILTER_LIMIT}
+     * @param pubkey 32-byte public key of the mute list's author
+     * @return List of query results (note keys)
+     * @throws IllegalArgumentException if limit is invalid or pubkey is not 32 bytes
+     * @see #getMuteList(Transaction, byte[])
+     */
+    public List<QueryResult> queryExcludingMuteList(Transaction txn, Filter filter, int limit, byte[] pubkey) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        if (pubkey == null || pubkey.length != 32) {
+            throw new IllegalArgumentException("Pubkey must be 32 bytes");
+        }
+        byte[] resultData = NostrdbNative.queryExcludingMuteList(ptr, txn.ptr(), filter.ptr(), limit, pubkey);
+        return QueryResult.parseResults(resultData);
+    }
+
+    /**
+     * Query for notes matching a filter, leaving out notes that have expired.
+     *
+     * <p>A note expires at the timestamp in its NIP-40 {@code expiration} tag.
+     * Expired notes are skipped natively while results are collected, so the limit
+     * counts only live notes. Notes whose expiration value is not a non-negative
+     * integer are treated as never expiring.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of live results (positive and at most
+     *              {@link Filter#MAX_LIMIT}), or {@link #FILTER_LIMIT}
+     * @param nowSeconds The current time as a Unix timestamp in seconds, e.g.
+     *                   {@code Instant.now().getEpochSecond()}
+     * @return List of query results (note keys)
+     * @throws IllegalArgumentException if limit is invalid or nowSeconds is negative
+     */
+    public List<QueryResult> queryUnexpired(Transaction txn, Filter filter, int limit, long nowSeconds) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        byte[] resultData = NostrdbNative.queryUnexpired(ptr, txn.ptr(), filter.ptr(), limit, nowSeconds);
+        return QueryResult.parseResults(resultData);
+    }
+
+    /**
+     * Query for notes that have not expired, fetching full note objects.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of live results (positive and at most
+     *              {@link Filter#MAX_LIMIT}), or {@link #FILTER_LIMIT}
+     * @param nowSeconds The current time as a Unix timestamp in seconds
+     * @return List of notes
+     * @throws IllegalArgumentException if limit is invalid or nowSeconds is negative
+     * @see #queryUnexpired(Transaction, Filter, int, long)
+     */
+    public List<Note> queryNotesUnexpired(Transaction txn, Filter filter, int limit, long nowSeconds) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        byte[] data = NostrdbNative.queryNotesUnexpired(ptr, txn.ptr(), filter.ptr(), limit, nowSeconds);
+        return Note.parseNotes(data);
+    }
+
+    /**
+     * Query for notes matching any of several filters, like the filters of a
+     * single REQ.
+     *
+     * <p>Results are merged in one native query, and the limit applies to the
+     * merged set.
+     *
+     * @param txn The transaction
+     * @param filters The query filters (at least one)
+     * @param limit Maximum number of results (must be positive and at most {@link Filter#MAX_LIMIT})
+     * @return List of query results (note keys)
+     * @throws IllegalArgumentException if filters is empty or limit is out of range
+     */
+    public List<QueryResult> query(Transaction txn, List<Filter> filters, int limit) {
+        checkOpen();
+        validateLimit(limit);
+        byte[] resultData = NostrdbNative.queryMulti(ptr, txn.ptr(), filterPtrs(filters), limit);
+        return QueryResult.parseResults(resultData);
+    }
+
+    /**
+     * Query several filters separately and merge their results.
+     *
+     * <p>Use this for filters that overlap, e.g. notes by followed authors and
+     * notes tagged with a followed hashtag: a note matching several filters is
+     * returned once. Each filter honours its own limit, and the merged results
+     * are ordered by {@code created_at}, newest first, before the limit is
+     * applied. Notes sharing a {@code created_at} are ordered by note key.
+     *
+     * @param txn The transaction
+     * @param filters The query filters (at least one)
+     * @param limit Maximum number of merged results (positive and at most
+     *              {@link Filter#MAX_LIMIT}), or {@link #FILTER_LIMIT} for no
+     *              limit beyond the filters' own
+     * @return List of query results (note keys), newest first
+     * @throws IllegalArgumentException if filters is empty or limit is invalid
+     */
+    public List<QueryResult> queryMergedUnique(Transaction txn, List<Filter> filters, int limit) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        byte[] resultData = NostrdbNative.queryMergedUnique(ptr, txn.ptr(), filterPtrs(filters), limit);
+        return QueryResult.parseResults(resultData);
+    }
+
+    /**
+     * Query for the event ids of notes matching a filter.
+     *
+     * <p>Use this instead of fetching notes when only ids are needed, e.g. to
+     * check which events a relay has that the database doesn't. The limit is
+     * combined with the filter's own limit as in {@link #query(Transaction, Filter, int)}.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of results (positive and at most {@link Filter#MAX_LIMIT}),
+     *              or {@link #FILTER_LIMIT}
+     * @return 32-byte event ids, in query order
+     * @throws IllegalArgumentException if limit is invalid
+     */
+    public List<byte[]> queryIds(Transaction txn, Filter filter, int limit) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        return unpack(NostrdbNative.queryIds(ptr, txn.ptr(), filter.ptr(), limit));
+    }
+
+    /**
+     * Find each author's newest note matching a filter.
+     *
+     * <p>Every author is looked up natively within the one transaction, e.g.
+     * for a "who posted recently" view over a follow list. Authors without a
+     * matching note are left out, and an author listed twice is reported once.
+     * The filter's own limit is ignored.
+     *
+     * @param txn The transaction
+     * @param filter The query filter, typically kinds only
+     * @param authors 32-byte public keys
+     * @return One entry per author with a match, newest first
+     * @throws IllegalArgumentException if an author is not 32 bytes
+     */
+    public List<LatestNote> queryLatestPerAuthor(Transaction txn, Filter filter, Collection<byte[]> authors) {
+        checkOpen();
+        byte[] resultData = NostrdbNative.queryLatestPerAuthor(ptr, txn.ptr(), filter.ptr(), pack("Author", authors));
+        return LatestNote.parseResults(resultData);
+    }
+
+    /**
+     * Query for notes and fetch full note objects.
+     *
+     * <p>The notes are serialized natively in the same call as the query, so
+     * this is much cheaper than calling {@link #getNoteByKey} for each result.
+     * The limit is combined with the filter's own limit as in
+     * {@link #query(Transaction, Filter, int)}.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of results (must be positive and at most {@link Filter#MAX_LIMIT})
+     * @return List of notes
+     * @throws IllegalArgumentException if limit is not positive or exceeds MAX_LIMIT
+     */
+    public List<Note> queryNotes(Transaction txn, Filter filter, int limit) {
+        checkOpen();
+        validateLimit(limit);
+        byte[] data = NostrdbNative.queryNotes(ptr, txn.ptr(), filter.ptr(), limit);
+        return Note.parseNotes(data);
+    }
+
+    /**
+     * Query for notes, returning their raw bytes instead of parsed notes.
+     *
+     * <p>As {@link #queryNotes}, but each note is copied in nostrdb's internal
+     * layout (see {@link RawNote}) rather than serialized to JSON.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of results (positive and at most {@link Filter#MAX_LIMIT}),
+     *              or {@link #FILTER_LIMIT}
+     * @return Raw notes, in query order
+     * @throws IllegalArgumentException if limit is invalid
+     */
+    public List<byte[]> queryNotesRaw(Transaction txn, Filter filter, int limit) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        return RawNote.parseList(NostrdbNative.queryNotesRaw(ptr, txn.ptr(), filter.ptr(), limit));
+    }
+
+    /**
+     * Query for notes, returning each as CBOR.
+     *
+     * <p>As {@link #queryNotes}, but each note is serialized as in
+     * {@link #getNoteByIdCbor} rather than as JSON.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of results (positive and at most {@link Filter#MAX_LIMIT}),
+     *              or {@link #FILTER_LIMIT}
+     * @return CBOR notes, in query order
+     * @throws IllegalArgumentException if limit is invalid
+     */
+    public List<byte[]> queryNotesCbor(Transaction txn, Filter filter, int limit) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        return RawNote.parseList(NostrdbNative.queryNotesCbor(ptr, txn.ptr(), filter.ptr(), limit));
+    }
+
+    /**
+     * Query for notes matching a filter, stopping early if a token is cancelled.
+     *
+     * <p>Matches are fetched natively in pages, newest first, and the token is
+     * checked between pages and between results, so a broad query returns
+     * shortly after {@link CancelToken#cancel()} is called from another thread.
+     * Notes sharing a {@code created_at} may be ordered differently than by
+     * {@link #query(Transaction, Filter, int)}. The limit is combined with the
+     * filter's own limit in the same way.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of results (positive and at most {@link Filter#MAX_LIMIT}),
+     *              or {@link #FILTER_LIMIT}
+     * @param token The cancellation token
+     * @return The results, marked as cancelled if the query stopped early
+     * @throws IllegalArgumentException if limit is invalid
+     */
+    public PartialResult<QueryResult> queryCancellable(Transaction txn, Filter filter, int limit, CancelToken token) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        byte[] data = NostrdbNative.queryCancellable(ptr, txn.ptr(), filter.ptr(), limit, token.ptr());
+        return PartialResult.of(QueryResult.parseResults(data), data);
+    }
+
+    /**
+     * Query for full notes matching a filter, stopping early if a token is
+     * cancelled.
+     *
+     * <p>As {@link #queryCancellable}, with the token also checked between
+     * serialized notes.
+     *
+     * @param txn The transaction
+     * @param filter The query filter
+     * @param limit Maximum number of results (positive and at most {@link Filter#MAX_LIMIT}),
+     *              or {@link #FILTER_LIMIT}
+     * @param token The cancellation token
+     * @return The notes, marked as cancelled if the query stopped early
+     * @throws IllegalArgumentException if limit is invalid
+     */
+    public PartialResult<Note> queryNotesCancellable(Transaction txn, Filter filter, int limit, CancelToken token) {
+        checkOpen();
+        if (limit != FILTER_LIMIT) {
+            validateLimit(limit);
+        }
+        byte[] data = NostrdbNative.queryNotesCancellable(ptr, txn.ptr(), filter.ptr(), limit, token.ptr());
+        return PartialResult.of(Note.parseNotes(data), data);
     }
 
     /**
-     * Open a database at the specified path with default configuration.
+     * Get a profile by its 32-byte public key.
      *
-     * @param dbPath Path to the database directory (will be created if it doesn't exist)
-     * @return The Ndb instance
-     * @throws NostrdbException if the database cannot be opened
+     * @param txn The transaction
+     * @param pubkey 32-byte public key
+     * @return The profile, or empty if not found
      */
-    public static Ndb open(Path dbPath) {
-        return open(dbPath.toString());
+    public Optional<Profile> getProfileByPubkey(Transaction txn, byte[] pubkey) {
+        checkOpen();
+        if (pubkey == null || pubkey.length != 32) {
+            throw new IllegalArgumentException("Pubkey must be 32 bytes");
+        }
+        byte[] data = NostrdbNative.getProfileByPubkey(ptr, txn.ptr(), pubkey);
+        return Optional.ofNullable(data).map(Profile::fromBytes);
     }
 
     /**
-     * Open a database at the specified path with default configuration.
+     * Get a profile by its hex-encoded public key.
      *
-     * @param dbPath Path to the database directory (will be created if it doesn't exist)
-     * @return The Ndb instance
-     * @throws NostrdbException if the database cannot be opened
+     * @param txn The transaction
+     * @param pubkeyHex 64-character hex public key
+     * @return The profile, or empty if not found
      */
-    public static Ndb open(String dbPath) {
-        long ptr = NostrdbNative.ndbOpen(dbPath, 0);
-        if (ptr == 0) {
-            throw new NostrdbException("Failed to open database at " + dbPath);
-        }
-        return new Ndb(ptr);
+    public Optional<Profile> getProfileByPubkey(Transaction txn, String pubkeyHex) {
+        return getProfileByPubkey(txn, HexUtil.decode(pubkeyHex));
     }
 
     /**
-     * Process a single Nostr event JSON.
+     * Get one string field of a profile by its 32-byte public key.
      *
-     * <p>The JSON can be in either relay format {@code ["EVENT", "subid", {...}]} or
-     * client format {@code ["EVENT", {...}]} or just the event object {@code {...}}.
+     * <p>Cheaper than {@link #getProfileByPubkey} when only a field or two is
+     * needed, e.g. for mention autocompletion, since no JSON is built or parsed.
      *
-     * @param json The JSON event string
-     * @throws NostrdbException if the event cannot be processed
+     * <pre>{@code
+     * String name = ndb.getProfileField(txn, pubkey, ProfileField.DISPLAY_NAME).orElse("");
+     * }</pre>
+     *
+     * @param txn The transaction
+     * @param pubkey 32-byte public key
+     * @param field The field to read
+     * @return The field's value, or empty if there is no profile or it lacks the field
      */
-    public void processEvent(String json) {
+    public Optional<String> getProfileField(Transaction txn, byte[] pubkey, ProfileField field) {
         checkOpen();
-        int result = NostrdbNative.processEvent(ptr, json);
-        if (result == 0) {
-            throw new NostrdbException("Failed to process event");
+        if (pubkey == null || pubkey.length != 32) {
+            throw new IllegalArgumentException("Pubkey must be 32 bytes");
+        }
+        if (field == null) {
+            throw new IllegalArgumentException("Profile field must not be null");
         }
+        return Optional.ofNullable(NostrdbNative.getProfileField(ptr, txn.ptr(), pubkey, field.code()));
     }
 
     /**
-     * Process multiple events from newline-delimited JSON.
+     * Get the signed kind-0 event a pubkey's profile was read from.
      *
-     * @param ldjson Newline-delimited JSON events
-     * @return The number of events successfully processed
+     * <p>Where {@link #getProfileByPubkey} returns the parsed profile, this
+     * returns the event JSON nostrdb itself produces, as
+     * {@link #getNoteJsonByKey} does, e.g. to rebroadcast a profile to
+     * another relay.
+     *
+     * @param txn The transaction
+     * @param pubkey 32-byte public key
+     * @return The event JSON, or empty if the pubkey has no stored profile
      */
-    public int processEvents(String ldjson) {
+    public Optional<String> getProfileNote(Transaction txn, byte[] pubkey) {
         checkOpen();
-        int result = NostrdbNative.processEvents(ptr, ldjson);
-        if (result < 0) {
-            throw new NostrdbException("Failed to process events");
+        if (pubkey == null || pubkey.length != 32) {
+            throw new IllegalArgumentException("Pubkey must be 32 bytes");
         }
-        return result;
+        byte[] data = NostrdbNative.getProfileNote(ptr, txn.ptr(), pubkey);
+        return Optional.ofNullable(data).map(json -> new String(json, StandardCharsets.UTF_8));
     }
 
     /**
-     * Begin a read transaction.
+     * Get the signed kind-0 event a pubkey's profile was read from, by
+     * hex-encoded public key.
      *
-     * <p><b>IMPORTANT:</b> Only one transaction per thread is allowed (LMDB constraint).
-     * Always use try-with-resources to ensure proper cleanup.
+     * @param txn The transaction
+     * @param pubkeyHex 64-character hex public key
+     * @return The event JSON, or empty if the pubkey has no stored profile
+     */
+    public Optional<String> getProfileNote(Transaction txn, String pubkeyHex) {
+        return getProfileNote(txn, HexUtil.decode(pubkeyHex));
+    }
+
+    /**
+     * Get a profile's raw bytes by public key, without converting it to JSON.
      *
-     * @return A new Transaction
-     * @throws NostrdbException if the transaction cannot be started
+     * <p>The bytes are the profile record as nostrdb stores it: a flatbuffer
+     * whose root table is an {@code NdbProfileRecord} from nostrdb's
+     * {@code profile.fbs} schema, for readers that already embed it. The
+     * schema belongs to nostrdb and is covered by the native ABI version,
+     * like {@link #getNoteRawByKey}.
+     *
+     * @param txn The transaction
+     * @param pubkey 32-byte public key
+     * @return The raw profile record, or empty if the pubkey has no stored profile
      */
-    public Transaction beginTransaction() {
+    public Optional<byte[]> getProfileRawByPubkey(Transaction txn, byte[] pubkey) {
         checkOpen();
-        long txnPtr = NostrdbNative.beginTransaction(ptr);
-        if (txnPtr == 0) {
-            throw new NostrdbException("Failed to begin transaction");
+        if (pubkey == null || pubkey.length != 32) {
+            throw new IllegalArgumentException("Pubkey must be 32 bytes");
         }
-        return new Transaction(this, txnPtr);
+        return Optional.ofNullable(NostrdbNative.getProfileRawByPubkey(ptr, txn.ptr(), pubkey));
     }
 
     /**
-     * Get a note by its 32-byte event ID.
+     * Get the pubkeys an author follows, from their newest contact list (kind 3).
      *
      * @param txn The transaction
-     * @param eventId 32-byte event ID
-     * @return The note, or empty if not found
+     * @param pubkey 32-byte public key of the author
+     * @return The followed pubkeys, or empty if the author has no contact list
+     * @see #getContacts(Transaction, byte[], boolean)
      */
-    public Optional<Note> getNoteById(Transaction txn, byte[] eventId) {
-        checkOpen();
-        if (eventId == null || eventId.length != 32) {
-            throw new IllegalArgumentException("Event ID must be 32 bytes");
-        }
-        byte[] data = NostrdbNative.getNoteById(ptr, txn.ptr(), eventId);
-        return Optional.ofNullable(data).map(Note::fromBytes);
+    public Optional<ContactList> getContacts(Transaction txn, byte[] pubkey) {
+        return getContacts(txn, pubkey, false);
     }
 
     /**
-     * Get a note by its hex-encoded event ID.
+     * Get the pubkeys an author follows, from their newest contact list (kind 3),
+     * optionally with each contact's relay hint.
+     *
+     * <p>The list's {@code p} tags are parsed natively: each pubkey is returned
+     * once, in the order first listed, and tags whose pubkey is not 64 hex
+     * characters are skipped.
      *
      * @param txn The transaction
-     * @param eventIdHex 64-character hex event ID
-     * @return The note, or empty if not found
+     * @param pubkey 32-byte public key of the author
+     * @param relayHints Whether to return relay hints as well
+     * @return The followed pubkeys, or empty if the author has no contact list
      */
-    public Optional<Note> getNoteById(Transaction txn, String eventIdHex) {
-        return getNoteById(txn, HexUtil.decode(eventIdHex));
+    public Optional<ContactList> getContacts(Transaction txn, byte[] pubkey, boolean relayHints) {
+        checkOpen();
+        if (pubkey == null || pubkey.length != 32) {
+            throw new IllegalArgumentException("Pubkey must be 32 bytes");
+        }
+        byte[] data = NostrdbNative.getContacts(ptr, txn.ptr(), pubkey, relayHints);
+        return Optional.ofNullable(data).map(contacts -> ContactList.parse(contacts, relayHints));
     }
 
     /**
-     * Get a note by its internal key (faster for repeated lookups).
+     * Get the relays an author reads from and writes to, from their newest
+     * relay list (NIP-65, kind 10002).
+     *
+     * <p>Only the newest relay list counts. Relays listed without a
+     * {@code read} or {@code write} marker are both.
      *
      * @param txn The transaction
-     * @param noteKey Internal note key
-     * @return The note, or empty if not found
+     * @param pubkey 32-byte public key of the author
+     * @return The author's relays, or empty if the author has no relay list
      */
-    public Optional<Note> getNoteByKey(Transaction txn, long noteKey) {
+    public Optional<RelayList> getRelayList(Transaction txn, byte[] pubkey) {
         checkOpen();
-        byte[] data = NostrdbNative.getNoteByKey(ptr, txn.ptr(), noteKey);
-        return Optional.ofNullable(data).map(Note::fromBytes);
+        if (pubkey == null || pubkey.length != 32) {
+            throw new IllegalArgumentException("Pubkey must be 32 bytes");
+        }
+        return Optional.ofNullable(NostrdbNative.getRelayList(ptr, txn.ptr(), pubkey)).map(RelayList::parse);
     }
 
     /**
-     * Query for notes matching a filter.
+     * Get the public entries of an author's newest mute list (NIP-51, kind 10000).
+     *
+     * <p>Private entries, encrypted in the list's content, are skipped;
+     * {@link MuteList#hasPrivateEntries()} reports whether there are any.
      *
      * @param txn The transaction
-     * @param filter The query filter
-     * @return List of query results (note keys)
+     * @param pubkey 32-byte public key of the author
+     * @return The mute list, or empty if the author has no mute list
+     * @see #queryExcludingMuteList(Transaction, Filter, int, byte[])
      */
-    public List<QueryResult> query(Transaction txn, Filter filter) {
-        return query(txn, filter, 100);
+    public Optional<MuteList> getMuteList(Transaction txn, byte[] pubkey) {
+        checkOpen();
+        if (pubkey == null || pubkey.length != 32) {
+            throw new IllegalArgumentException("Pubkey must be 32 bytes");
+        }
+        return Optional.ofNullable(NostrdbNative.getMuteList(ptr, txn.ptr(), pubkey)).map(MuteList::parse);
     }
 
     /**
-     * Query for notes matching a filter with an explicit limit.
+     * Get the profiles of several pubkeys in one native call, e.g. the
+     * authors of a timeline.
      *
      * @param txn The transaction
-     * @param filter The query filter
-     * @param limit Maximum number of results (must be positive and at most {@link Filter#MAX_LIMIT})
-     * @return List of query results (note keys)
-     * @throws IllegalArgumentException if limit is not positive or exceeds MAX_LIMIT
+     * @param pubkeys 32-byte public keys
+     * @return One entry per pubkey, in order, empty for pubkeys without a profile
+     * @throws IllegalArgumentException if a pubkey is not 32 bytes
      */
-    public List<QueryResult> query(Transaction txn, Filter filter, int limit) {
+    public List<Optional<Profile>> getProfilesByPubkeys(Transaction txn, List<byte[]> pubkeys) {
         checkOpen();
-        validateLimit(limit);
-        byte[] resultData = NostrdbNative.query(ptr, txn.ptr(), filter.ptr(), limit);
-        return QueryResult.parseResults(resultData);
+        if (pubkeys == null) {
+            throw new IllegalArgumentException("Pubkeys must not be null");
+        }
+        ByteBuffer packed = ByteBuffer.allocate(pubkeys.size() * 32);
+        for (int i = 0; i < pubkeys.size(); i++) {
+            byte[] pubkey = pubkeys.get(i);
+            if (pubkey == null || pubkey.length != 32) {
+                throw new IllegalArgumentException("Pubkey at index " + i + " must be 32 bytes");
+            }
+            packed.put(pubkey);
+        }
+
+        byte[] data = NostrdbNative.getProfilesByPubkeys(ptr, txn.ptr(), packed.array());
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+        List<Optional<Profile>> profiles = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            buf.position(buf.position() + 32);
+            byte[] json = new byte[buf.getInt()];
+            buf.get(json);
+            profiles.add(json.length == 0 ? Optional.empty() : Optional.of(Profile.fromBytes(json)));
+        }
+        return profiles;
     }
 
     /**
-     * Query for notes and fetch full note objects.
+     * Get the profiles of the authors of the notes matching a filter.
      *
-     * <p>This is a convenience method that queries and then fetches each note.
+     * <p>Runs the query, collects its distinct authors, and looks up each profile
+     * in the same native call, e.g. for "everyone who posted in this thread". The
+     * limit bounds the notes queried, as in {@link #query(Transaction, Filter, int)},
+     * not the number of profiles.
      *
      * @param txn The transaction
      * @param filter The query filter
-     * @param limit Maximum number of results (must be positive and at most {@link Filter#MAX_LIMIT})
-     * @return List of notes
+     * @param limit Maximum number of notes (must be positive and at most {@link Filter#MAX_LIMIT})
+     * @param includeMissing Whether to include authors without a stored profile, mapped to
+     *        a profile with every field null
+     * @return Profiles keyed by hex public key, in the order of each author's newest matching note
      * @throws IllegalArgumentException if limit is not positive or exceeds MAX_LIMIT
      */
-    public List<Note> queryNotes(Transaction txn, Filter filter, int limit) {
+    public Map<String, Profile> queryProfiles(Transaction txn, Filter filter, int limit, boolean includeMissing) {
+        checkOpen();
         validateLimit(limit);
-        List<QueryResult> results = query(txn, filter, limit);
-        List<Note> notes = new ArrayList<>(results.size());
+        byte[] data = NostrdbNative.queryProfiles(ptr, txn.ptr(), filter.ptr(), limit, includeMissing);
+        if (data == null || data.length < 4) {
+            return Map.of();
+        }
 
-        for (QueryResult result : results) {
-            getNoteByKey(txn, result.noteKey()).ifPresent(notes::add);
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+        Map<String, Profile> profiles = new LinkedHashMap<>(count * 2);
+        for (int i = 0; i < count; i++) {
+            byte[] pubkey = new byte[32];
+            buf.get(pubkey);
+            byte[] json = new byte[buf.getInt()];
+            buf.get(json);
+            profiles.put(HexUtil.encode(pubkey), Profile.fromBytes(json));
         }
+        return profiles;
+    }
 
-        return notes;
+    /**
+     * Search for profiles by name.
+     *
+     * @param txn The transaction
+     * @param query Search query (matches name/display_name)
+     * @param limit Maximum number of results (must be positive and at most {@link Filter#MAX_LIMIT})
+     * @return List of matching public keys
+     * @throws IllegalArgumentException if limit is not positive or exceeds MAX_LIMIT
+     */
+    public List<byte[]> searchProfiles(Transaction txn, String query, int limit) {
+        return searchProfiles(txn, query, limit, PROFILE_SEARCH_EXACT);
     }
 
     /**
-     * Get a profile by its 32-byte public key.
+     * Search for profiles by name, optionally ignoring case and accents.
+     *
+     * <p>With {@link #PROFILE_SEARCH_FOLD}, the query and names are folded
+     * natively (compatibility-decomposed, lowercased, and stripped of
+     * combining marks; Turkish {@code \u0130} and {@code \u0131} fold to
+     * {@code i}) and a profile matches if its name or display name starts with
+     * the query. This scans the stored profiles rather than nostrdb's index.
      *
      * @param txn The transaction
-     * @param pubkey 32-byte public key
-     * @return The profile, or empty if not found
+     * @param query Search query (matches name/display_name)
+     * @param limit Maximum number of results (must be positive and at most {@link Filter#MAX_LIMIT})
+     * @param flags {@link #PROFILE_SEARCH_EXACT} or {@link #PROFILE_SEARCH_FOLD}
+     * @return List of matching public keys
+     * @throws IllegalArgumentException if limit is out of range, the flags are unknown,
+     *         or a folded query is blank
      */
-    public Optional<Profile> getProfileByPubkey(Transaction txn, byte[] pubkey) {
+    public List<byte[]> searchProfiles(Transaction txn, String query, int limit, int flags) {
         checkOpen();
-        if (pubkey == null || pubkey.length != 32) {
-            throw new IllegalArgumentException("Pubkey must be 32 bytes");
-        }
-        byte[] data = NostrdbNative.getProfileByPubkey(ptr, txn.ptr(), pubkey);
-        return Optional.ofNullable(data).map(Profile::fromBytes);
+        validateLimit(limit);
+        byte[] resultData = NostrdbNative.searchProfiles(ptr, txn.ptr(), query, limit, flags);
+        return unpack(resultData);
     }
 
     /**
-     * Get a profile by its hex-encoded public key.
+     * Search for profiles by name, with each match's names.
+     *
+     * <p>Unlike {@link #searchProfiles}, each match carries the names to show,
+     * e.g. in an autocomplete list, without a profile lookup per match.
      *
      * @param txn The transaction
-     * @param pubkeyHex 64-character hex public key
-     * @return The profile, or empty if not found
+     * @param query Search query (matches name/display_name)
+     * @param limit Maximum number of results (must be positive and at most {@link Filter#MAX_LIMIT})
+     * @return Matching profiles, in nostrdb's order
+     * @throws IllegalArgumentException if limit is not positive or exceeds MAX_LIMIT
      */
-    public Optional<Profile> getProfileByPubkey(Transaction txn, String pubkeyHex) {
-        return getProfileByPubkey(txn, HexUtil.decode(pubkeyHex));
+    public List<ProfileMatch> searchProfileNames(Transaction txn, String query, int limit) {
+        return searchProfileNames(txn, query, limit, PROFILE_SEARCH_EXACT);
     }
 
     /**
-     * Search for profiles by name.
+     * Search for profiles by name, with each match's names, optionally
+     * ignoring case and accents as {@link #searchProfiles(Transaction, String, int, int)} does.
      *
      * @param txn The transaction
      * @param query Search query (matches name/display_name)
      * @param limit Maximum number of results (must be positive and at most {@link Filter#MAX_LIMIT})
-     * @return List of matching public keys
-     * @throws IllegalArgumentException if limit is not positive or exceeds MAX_LIMIT
+     * @param flags {@link #PROFILE_SEARCH_EXACT} or {@link #PROFILE_SEARCH_FOLD}
+     * @return Matching profiles
+     * @throws IllegalArgumentException if limit is out of range, the flags are unknown,
+     *         or a folded query is blank
      */
-    public List<byte[]> searchProfiles(Transaction txn, String query, int limit) {
+    public List<ProfileMatch> searchProfileNames(Transaction txn, String query, int limit, int flags) {
         checkOpen();
         validateLimit(limit);
-        byte[] resultData = NostrdbNative.searchProfiles(ptr, txn.ptr(), query, limit);
-
-        if (resultData == null || resultData.length < 4) {
-            return List.of();
-        }
-
-        ByteBuffer buf = ByteBuffer.wrap(resultData).order(ByteOrder.LITTLE_ENDIAN);
-        int count = buf.getInt();
+        return ProfileMatch.parseResults(NostrdbNative.searchProfilesNamed(ptr, txn.ptr(), query, limit, flags));
+    }
 
-        List<byte[]> pubkeys = new ArrayList<>(count);
-        for (int i = 0; i < count; i++) {
-            byte[] pubkey = new byte[32];
-            buf.get(pubkey);
-            pubkeys.add(pubkey);
+    /**
+     * Search note contents, reporting where each hit matched.
+     *
+     * <p>Hits come from nostrdb's full-text index; each carries the byte
+     * offset and length of the match in the note's UTF-8 content, so callers
+     * can highlight it without searching again. Notes the index returns
+     * whose content doesn't contain the query or any of its words are left
+     * out.
+     *
+     * @param txn The transaction
+     * @param query Search text
+     * @param limit Maximum number of hits (must be positive and at most {@link Filter#MAX_LIMIT})
+     * @param order How to rank the hits
+     * @return Hits, in the requested order
+     * @throws IllegalArgumentException if the query is blank, the limit is out of range,
+     *         or the order is null
+     */
+    public List<SearchHit> searchNotes(Transaction txn, String query, int limit, SearchOrder order) {
+        checkOpen();
+        validateLimit(limit);
+        if (order == null) {
+            throw new IllegalArgumentException("Order must not be null");
         }
-
-        return pubkeys;
+        byte[] resultData = NostrdbNative.searchNotes(ptr, txn.ptr(), query, limit, order.code());
+        return SearchHit.parseResults(resultData);
     }
 
     /**
@@ -301,21 +2637,8 @@ public final class Ndb implements Closeable {
     public List<Long> pollForNotes(Subscription subscription, int maxNotes) {
         checkOpen();
         validateLimit(maxNotes);
-        byte[] resultData = NostrdbNative.pollForNotes(ptr, subscription.id(), maxNotes);
-
-        if (resultData == null || resultData.length < 4) {
-            return List.of();
-        }
-
-        ByteBuffer buf = ByteBuffer.wrap(resultData).order(ByteOrder.LITTLE_ENDIAN);
-        int count = buf.getInt();
-
-        List<Long> noteKeys = new ArrayList<>(count);
-        for (int i = 0; i < count; i++) {
-            noteKeys.add(buf.getLong());
-        }
-
-        return noteKeys;
+        long[] keys = NostrdbNative.pollForNoteKeys(ptr, subscription.id(), maxNotes);
+        return keys == null ? List.of() : Arrays.stream(keys).boxed().toList();
     }
 
     /**
@@ -328,6 +2651,55 @@ public final class Ndb implements Closeable {
         NostrdbNative.unsubscribe(ptr, subscription.rawId());
     }
 
+    /**
+     * Register a callback for database health events.
+     *
+     * <p>A background native thread checks the database every {@code checkIntervalMs}
+     * milliseconds and reports threshold crossings (see {@link HealthCallback}).
+     * Registering a new callback replaces the previous one; passing null stops
+     * monitoring. No callbacks are delivered after {@link #close()} returns.
+     *
+     * @param callback Callback to notify, or null to stop monitoring
+     * @param checkIntervalMs Milliseconds between checks (ignored when callback is null)
+     * @throws IllegalArgumentException if checkIntervalMs is not positive
+     */
+    public void setHealthCallback(HealthCallback callback, long checkIntervalMs) {
+        checkOpen();
+        NostrdbNative.setHealthCallback(ptr, callback, checkIntervalMs);
+    }
+
+    /**
+     * Set the map threshold used by the health monitor, keeping the default
+     * backlog threshold of {@value HealthCallback#DEFAULT_INGEST_BACKLOG_HIGH} events.
+     *
+     * @param mapNearlyFullPercent Map utilization percentage (0-100) that triggers
+     *                             {@link HealthCallback#MAP_NEARLY_FULL}
+     * @throws IllegalArgumentException if the percentage is out of range
+     * @see #setHealthThresholds(int, long)
+     */
+    public void setHealthThresholds(int mapNearlyFullPercent) {
+        setHealthThresholds(mapNearlyFullPercent, HealthCallback.DEFAULT_INGEST_BACKLOG_HIGH);
+    }
+
+    /**
+     * Set the thresholds used by the health monitor.
+     *
+     * <p>Thresholds take effect the next time {@link #setHealthCallback} is called.
+     * The defaults are 90 percent and
+     * {@value HealthCallback#DEFAULT_INGEST_BACKLOG_HIGH} events.
+     *
+     * @param mapNearlyFullPercent Map utilization percentage (0-100) that triggers
+     *                             {@link HealthCallback#MAP_NEARLY_FULL}
+     * @param ingestBacklogHigh Events accepted but not yet stored that trigger
+     *                          {@link HealthCallback#INGEST_BACKLOG_HIGH}
+     * @throws IllegalArgumentException if the percentage is out of range or the
+     *                                  backlog threshold is not positive
+     */
+    public void setHealthThresholds(int mapNearlyFullPercent, long ingestBacklogHigh) {
+        checkOpen();
+        NostrdbNative.setHealthThresholds(ptr, mapNearlyFullPercent, ingestBacklogHigh);
+    }
+
     /**
      * Get the native pointer (for internal use).
      */
@@ -358,10 +2730,77 @@ public final class Ndb implements Closeable {
         }
     }
 
+    /**
+     * Native pointers of a non-empty list of filters.
+     */
+    private static long[] filterPtrs(List<Filter> filters) {
+        if (filters == null || filters.isEmpty()) {
+            throw new IllegalArgumentException("At least one filter is required");
+        }
+        long[] filterPtrs = new long[filters.size()];
+        for (int i = 0; i < filterPtrs.length; i++) {
+            filterPtrs[i] = filters.get(i).ptr();
+        }
+        return filterPtrs;
+    }
+
+    /**
+     * Concatenate 32-byte values for the native layer; null means none.
+     */
+    private static byte[] pack(String what, Collection<byte[]> values) {
+        if (values == null) {
+            return new byte[0];
+        }
+        ByteBuffer buf = ByteBuffer.allocate(values.size() * 32);
+        for (byte[] value : values) {
+            if (value == null || value.length != 32) {
+                throw new IllegalArgumentException(what + " must be 32 bytes");
+            }
+            buf.put(value);
+        }
+        return buf.array();
+    }
+
+    /**
+     * Split native [count:4][value1:32][value2:32]... data into 32-byte values.
+     */
+    private static List<byte[]> unpack(byte[] data) {
+        if (data == null || data.length < 4) {
+            return List.of();
+        }
+
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+
+        List<byte[]> values = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            byte[] value = new byte[32];
+            buf.get(value);
+            values.add(value);
+        }
+
+        return values;
+    }
+
     @Override
     public void close() {
         if (closed.compareAndSet(false, true)) {
             NostrdbNative.ndbClose(ptr);
         }
     }
+
+    @Override
+    public String toString() {
+        if (isClosed()) {
+            return "Ndb{closed}";
+        }
+        NdbSettings settings = NdbSettings.fromArray(NostrdbNative.ndbGetSettings(ptr));
+        return "Ndb{" +
+            "path='" + NostrdbNative.ndbGetPath(ptr) + '\'' +
+            ", writeProtected=" + writeProtected +
+            ", mapSize=" + settings.mapSize() +
+            ", flags=0x" + Integer.toHexString(settings.flags()) +
+            ", ingesterThreads=" + settings.ingesterThreads() +
+            '}';
+    }
 }
@@ -0,0 +1,180 @@
+package xyz.tcheeric.nostrdb;
+
+import java.io.Closeable;
+import java.util.concurrent.atomic.AtomicBoolean;
+
+/**
+ * Configuration used when opening a database.
+ *
+ * <p>A config is a native object; close it when it is no longer needed. Opening
+ * a database does not consume the config, so one instance can be reused.
+ *
+ * <p>Example usage:
+ * <pre>{@code
+ * try (NdbConfig config = new NdbConfig().mapSize(64L * 1024 * 1024 * 1024);
+ *      Ndb ndb = Ndb.open(dbPath, config)) {
+ *     ndb.processEvent(eventJson);
+ * }
+ * }</pre>
+ */
+public final class NdbConfig implements Closeable {
+
+    /** Don't run database migrations on open. */
+    public static final int NO_MIGRATE = 1 << 0;
+
+    /** Skip event id and signature verification (see {@link #skipValidation(boolean)}). */
+    public static final int SKIP_NOTE_VERIFY = 1 << 1;
+
+    /** Don't build the fulltext search index; search filters then match nothing. */
+    public static final int NO_FULLTEXT = 1 << 2;
+
+    /** Don't parse and store content blocks (mentions, hashtags, URLs). */
+    public static final int NO_NOTE_BLOCKS = 1 << 3;
+
+    /** Don't collect database statistics. */
+    public static final int NO_STATS = 1 << 4;
+
+    private final long ptr;
+    private final AtomicBoolean closed = new AtomicBoolean(false);
+
+    /**
+     * Create a configuration with nostrdb's defaults.
+     *
+     * @throws NostrdbException if the native config cannot be created
+     */
+    public NdbConfig() {
+        this.ptr = NostrdbNative.configNew();
+        if (this.ptr == 0) {
+            throw new NostrdbException("Failed to create config");
+        }
+    }
+
+    /**
+     * Set the LMDB map size.
+     *
+     * <p>The map size is the maximum size the database can grow to; once it is
+     * reached, new events are silently dropped by the writer. The default is
+     * 32 GiB. A database can be reopened with a larger map size to resume
+     * ingestion.
+     *
+     * @param sizeBytes Map size in bytes
+     * @return this config
+     * @throws IllegalArgumentException if sizeBytes is not positive
+     */
+    public NdbConfig mapSize(long sizeBytes) {
+        NostrdbNative.configSetMapSize(ptr(), sizeBytes);
+        return this;
+    }
+
+    /**
+     * Set the number of threads that parse and verify incoming events.
+     *
+     * <p>More threads speed up bulk ingestion on multi-core machines; writes to
+     * the database are still performed by a single writer thread.
+     *
+     * @param count Number of ingester threads
+     * @return this config
+     * @throws IllegalArgumentException if count is not positive
+     */
+    public NdbConfig ingesterThreads(int count) {
+        NostrdbNative.configSetIngesterThreads(ptr(), count);
+        return this;
+    }
+
+    /**
+     * Skip event id and signature verification during ingestion.
+     *
+     * <p>This makes bulk imports much faster, but events are stored as-is, including
+     * forged or corrupted ones. Only enable it for input you trust, such as a dump
+     * of your own relay.
+     *
+     * @param skip true to skip verification
+     * @return this config
+     */
+    public NdbConfig skipValidation(boolean skip) {
+        NostrdbNative.configSkipValidation(ptr(), skip);
+        return this;
+    }
+
+    /**
+     * Replace all nostrdb flags.
+     *
+     * <p>This overwrites every flag, including {@link #SKIP_NOTE_VERIFY}, so call
+     * {@link #skipValidation(boolean)} afterwards or include the bit in {@code flags}.
+     *
+     * <pre>{@code
+     * NdbConfig config = new NdbConfig().flags(NdbConfig.NO_FULLTEXT | NdbConfig.NO_STATS);
+     * }</pre>
+     *
+     * @param flags Bitmask of the flag constants in this class
+     * @return this config
+     * @throws IllegalArgumentException if flags contains unknown bits
+     */
+    public NdbConfig flags(int flags) {
+        NostrdbNative.configSetFlags(ptr(), flags);
+        return this;
+    }
+
+    /**
+     * Grow the map automatically as the database fills up.
+     *
+     * <p>A background thread checks how much of the map LMDB has used. Once it is
+     * full, the database is closed and reopened with twice the map size, up to
+     * {@code maxSizeBytes}. Up to 64 MiB of the events queued while the map was
+     * nearly full are kept, and those nostrdb's writer dropped for lack of space are
+     * queued again after the resize. Subscriptions carry over to the reopened
+     * database with the notes they had matched. If the database can't be reopened
+     * with the larger map, it is reopened with the current one.
+     *
+     * <p>The resize needs every transaction and operation on the database to end
+     * first. While any stays open, writes are held back and queued once the resize
+     * has happened, so long-lived transactions delay ingestion. Once 64 MiB of
+     * events are held back, further writes throw {@link IllegalStateException}.
+     *
+     * @param maxSizeBytes Largest map size to grow to, or 0 to disable auto-resize
+     * @return this config
+     * @throws IllegalArgumentException if maxSizeBytes is negative
+     */
+    public NdbConfig autoResize(long maxSizeBytes) {
+        NostrdbNative.configSetAutoResize(ptr(), maxSizeBytes);
+        return this;
+    }
+
+    /**
+     * Fail on stored content that is not valid UTF-8 instead of repairing it.
+     *
+     * <p>nostrdb does not check content encoding, and a few events in the wild
+     * carry invalid UTF-8. By default, such content is returned with the invalid
+     * sequences replaced by U+FFFD. In strict mode, any call that returns the
+     * note's content, as JSON or otherwise, throws an
+     * {@link InvalidContentException} carrying the note's key.
+     *
+     * @param strict true to throw rather than repair the content
+     * @return this config
+     */
+    public NdbConfig strictUtf8(boolean strict) {
+        NostrdbNative.configSetStrictUtf8(ptr(), strict);
+        return this;
+    }
+
+    /**
+     * Get the native pointer (for internal use).
+     */
+    long ptr() {
+        checkOpen();
+        return ptr;
+    }
+
+    private void checkOpen() {
+        if (closed.get()) {
+            throw new IllegalStateException("NdbConfig is closed");
+        }
+    }
+
+    @Override
+    public void close() {
+        if (closed.compareAndSet(false, true)) {
+            NostrdbNative.configDestroy(ptr);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * The settings an open database is running with, as given by its
+ * {@link NdbConfig}.
+ *
+ * @see Ndb#settings()
+ */
+public final class NdbSettings {
+
+    private final long mapSize;
+    private final int flags;
+    private final int ingesterThreads;
+    private final long autoResizeMax;
+
+    private NdbSettings(long mapSize, int flags, int ingesterThreads, long autoResizeMax) {
+        this.mapSize = mapSize;
+        this.flags = flags;
+        this.ingesterThreads = ingesterThreads;
+        this.autoResizeMax = autoResizeMax;
+    }
+
+    /**
+     * Get the LMDB map size, including any growth from auto-resize.
+     *
+     * @return Map size in bytes
+     */
+    public long mapSize() {
+        return mapSize;
+    }
+
+    /**
+     * Get the nostrdb flags, e.g. {@link NdbConfig#NO_FULLTEXT}.
+     *
+     * <p>A database opened with {@link Ndb#openWriteProtected} on its own also has
+     * {@link NdbConfig#NO_MIGRATE}.
+     *
+     * @return Bitwise OR of the {@link NdbConfig} flag constants
+     */
+    public int flags() {
+        return flags;
+    }
+
+    /**
+     * Get the number of ingester threads.
+     *
+     * @return The count set with {@link NdbConfig#ingesterThreads(int)}, or 0 for
+     *         nostrdb's default of one per CPU core
+     */
+    public int ingesterThreads() {
+        return ingesterThreads;
+    }
+
+    /**
+     * Get the limit automatic map growth stops at.
+     *
+     * @return Maximum map size in bytes, or 0 if auto-resize is off
+     */
+    public long autoResizeMax() {
+        return autoResizeMax;
+    }
+
+    /**
+     * Create settings from the native [mapsize, flags, ingester_threads,
+     * auto_resize_max] array.
+     */
+    static NdbSettings fromArray(long[] settings) {
+        return new NdbSettings(settings[0], (int) settings[1], (int) settings[2], settings[3]);
+    }
+
+    @Override
+    public String toString() {
+        return "NdbSettings{mapSize=" + mapSize + ", flags=0x" + Integer.toHexString(flags)
+            + ", ingesterThreads=" + ingesterThreads + ", autoResizeMax=" + autoResizeMax + '}';
+    }
+}
@@ -1,5 +1,7 @@
 package xyz.tcheeric.nostrdb;
 
+import java.nio.ByteBuffer;
+
 /**
  * Native method declarations for nostrdb JNI bindings.
  * This class loads the native library and declares all JNI methods.
@@ -12,6 +14,14 @@ package xyz.tcheeric.nostrdb;
  */
 final class NostrdbNative {
 
+    /**
+     * ABI version this wrapper was written against.
+     *
+     * <p>Must match {@code ABI_VERSION} in the native library; it changes whenever
+     * the serialized formats exchanged over JNI change.
+     */
+    static final int EXPECTED_ABI_VERSION = 7;
+
     private static volatile boolean loaded = false;
     private static volatile Throwable loadError = null;
 
@@ -25,17 +35,41 @@ final class NostrdbNative {
         try {
             // Try loading from java.library.path first
             System.loadLibrary("nostrdb_jni");
-            loaded = true;
         } catch (UnsatisfiedLinkError e) {
             try {
                 // Fall back to loading from JAR resources
                 NativeLoader.loadFromJar("nostrdb_jni");
-                loaded = true;
             } catch (Exception ex) {
                 loadError = ex;
                 throw new RuntimeException("Failed to load nostrdb native library", ex);
             }
         }
+
+        try {
+            checkAbiVersion();
+        } catch (RuntimeException e) {
+            loadError = e;
+            throw e;
+        }
+        loaded = true;
+    }
+
+    /**
+     * Fail fast if the loaded native library does not match this wrapper.
+     */
+    private static void checkAbiVersion() {
+        int actual;
+        try {
+            actual = nativeAbiVersion();
+        } catch (UnsatisfiedLinkError e) {
+            throw new IllegalStateException(
+                "nostrdb native library is too old for this JAR (no ABI version)", e);
+        }
+        if (actual != EXPECTED_ABI_VERSION) {
+            throw new IllegalStateException("nostrdb native library ABI version " + actual
+                + " does not match expected version " + EXPECTED_ABI_VERSION
+                + " (loaded " + nativeVersion() + ")");
+        }
     }
 
     /**
@@ -63,9 +97,19 @@ final class NostrdbNative {
      *
      * @param dbPath Path to the database directory
      * @param configPtr Pointer to configuration (0 for defaults)
+     * @param createIfMissing Create the directory if it does not exist
      * @return Pointer to the Ndb instance, or 0 on error
      */
-    static native long ndbOpen(String dbPath, long configPtr);
+    static native long ndbOpen(String dbPath, long configPtr, boolean createIfMissing);
+
+    /**
+     * Open an existing nostrdb database, rejecting writes through the handle.
+     *
+     * @param dbPath Path to the database directory
+     * @param configPtr Pointer to configuration (0 for defaults)
+     * @return Pointer to the Ndb instance, or 0 on error
+     */
+    static native long ndbOpenWriteProtected(String dbPath, long configPtr);
 
     /**
      * Close a nostrdb database.
@@ -74,6 +118,99 @@ final class NostrdbNative {
      */
     static native void ndbClose(long ndbPtr);
 
+    /**
+     * Check whether a database handle is open.
+     *
+     * <p>Safe to call with a pointer that was already closed.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @return true if the handle is open
+     */
+    static native boolean ndbIsOpen(long ndbPtr);
+
+    /**
+     * Get the canonical directory of an open database.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @return The database directory
+     */
+    static native String ndbGetPath(long ndbPtr);
+
+    /**
+     * Get the settings an open database is running with.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @return [mapsize, flags, ingester_threads, auto_resize_max]; ingester_threads
+     *         is 0 for nostrdb's default
+     */
+    static native long[] ndbGetSettings(long ndbPtr);
+
+    // ========================================================================
+    // Configuration
+    // ========================================================================
+
+    /**
+     * Create a database configuration with nostrdb's defaults.
+     *
+     * @return Pointer to the Config, or 0 on error
+     */
+    static native long configNew();
+
+    /**
+     * Destroy a database configuration.
+     *
+     * @param configPtr Pointer to the Config
+     */
+    static native void configDestroy(long configPtr);
+
+    /**
+     * Set the LMDB map size, the maximum size the database can grow to.
+     *
+     * @param configPtr Pointer to the Config
+     * @param sizeBytes Map size in bytes (positive)
+     */
+    static native void configSetMapSize(long configPtr, long sizeBytes);
+
+    /**
+     * Set the number of ingester threads.
+     *
+     * @param configPtr Pointer to the Config
+     * @param count Number of threads (positive)
+     */
+    static native void configSetIngesterThreads(long configPtr, int count);
+
+    /**
+     * Enable or disable skip-validation mode.
+     *
+     * @param configPtr Pointer to the Config
+     * @param skip true to store events without verifying ids and signatures
+     */
+    static native void configSkipValidation(long configPtr, boolean skip);
+
+    /**
+     * Replace the nostrdb flags bitmask.
+     *
+     * @param configPtr Pointer to the Config
+     * @param flags Bitmask of {@link NdbConfig} flag constants
+     */
+    static native void configSetFlags(long configPtr, int flags);
+
+    /**
+     * Enable automatic map growth.
+     *
+     * @param configPtr Pointer to the Config
+     * @param maxSizeBytes Largest map size to grow to, or 0 to disable
+     */
+    static native void configSetAutoResize(long configPtr, long maxSizeBytes);
+
+    /**
+     * Throw instead of repairing stored content that is not valid UTF-8.
+     *
+     * @param configPtr Pointer to the Config
+     * @param strict Whether to throw InvalidContentException
+     */
+    static native void configSetStrictUtf8(long configPtr, boolean strict);
+
     // ========================================================================
     // Event Ingestion
     // ========================================================================
@@ -87,6 +224,45 @@ final class NostrdbNative {
      */
     static native int processEvent(long ndbPtr, String json);
 
+    /**
+     * Process a single JSON event, checking its id first.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param json JSON string of the event
+     * @return 1 if queued, 2 if already stored, 0 if malformed, invalid or refused
+     */
+    static native int processEventWithResult(long ndbPtr, String json);
+
+    /**
+     * Process a single JSON event, validating it or not for this call only.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param json JSON string of the event
+     * @param skipValidation Whether to skip id and signature verification
+     * @return 1 on success, 0 if invalid or refused
+     */
+    static native int processEventWithValidation(long ndbPtr, String json, boolean skipValidation);
+
+    /**
+     * Process a single JSON event from UTF-8 bytes.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param json JSON event, UTF-8 encoded
+     * @return 1 on success, 0 on failure
+     */
+    static native int processEventBytes(long ndbPtr, byte[] json);
+
+    /**
+     * Process a single JSON event and wait for the stored note's key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param json JSON string of the event
+     * @param timeoutMs How long to wait for the note to be stored (not negative)
+     * @return The note key, or -1 if invalid, -2 if a duplicate, -3 if not stored
+     *         in time, -4 if rejected by nostrdb
+     */
+    static native long processEventReturningKey(long ndbPtr, String json, long timeoutMs);
+
     /**
      * Process multiple newline-delimited JSON events.
      *
@@ -96,208 +272,1765 @@ final class NostrdbNative {
      */
     static native int processEvents(long ndbPtr, String ldjson);
 
-    // ========================================================================
-    // Transaction
-    // ========================================================================
-
     /**
-     * Begin a read transaction.
-     * IMPORTANT: Only one transaction per thread!
+     * Process multiple newline-delimited JSON events, validating them or not
+     * for this call only.
      *
      * @param ndbPtr Pointer to the Ndb instance
-     * @return Pointer to the Transaction, or 0 on error
+     * @param ldjson Newline-delimited JSON events
+     * @param skipValidation Whether to skip id and signature verification
+     * @return Number of events processed, or -1 on error
      */
-    static native long beginTransaction(long ndbPtr);
+    static native int processEventsWithValidation(long ndbPtr, String ldjson, boolean skipValidation);
 
     /**
-     * End a transaction.
+     * Process multiple newline-delimited JSON events from UTF-8 bytes.
      *
-     * @param txnPtr Pointer to the Transaction
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param ldjson Newline-delimited JSON events, UTF-8 encoded
+     * @return Number of events processed, or -1 on error
      */
-    static native void endTransaction(long txnPtr);
+    static native int processEventsBytes(long ndbPtr, byte[] ldjson);
 
-    // ========================================================================
-    // Note Retrieval
-    // ========================================================================
+    /**
+     * Process multiple newline-delimited JSON events from gzip-compressed bytes,
+     * decompressed natively as they are read.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param gzipData Gzip-compressed newline-delimited JSON events
+     * @return Number of events processed, or -1 on error
+     */
+    static native int processEventsGzip(long ndbPtr, byte[] gzipData);
 
     /**
-     * Get a note by its 32-byte event ID.
+     * Process multiple newline-delimited JSON events read in place from a direct buffer.
      *
      * @param ndbPtr Pointer to the Ndb instance
-     * @param txnPtr Pointer to the Transaction
-     * @param eventId 32-byte event ID
-     * @return Serialized note as JSON bytes, or null if not found
+     * @param buffer Direct buffer holding UTF-8 newline-delimited JSON
+     * @param offset Index of the first byte to read
+     * @param length Number of bytes to read
+     * @return Number of events processed, or -1 on error
      */
-    static native byte[] getNoteById(long ndbPtr, long txnPtr, byte[] eventId);
+    static native int processEventsDirect(long ndbPtr, ByteBuffer buffer, int offset, int length);
 
     /**
-     * Get a note by its internal key (faster for repeated lookups).
+     * Import newline-delimited JSON events from a file, read natively a line at a time.
      *
      * @param ndbPtr Pointer to the Ndb instance
-     * @param txnPtr Pointer to the Transaction
-     * @param noteKey Internal note key
-     * @return Serialized note as JSON bytes, or null if not found
+     * @param path Path of the file to import
+     * @param progress Progress callback, or null
+     * @param tokenPtr Pointer to a cancellation token, or 0
+     * @param resumeOffset Byte offset to start at, 0 or the offset of an earlier import
+     * @return Counts as [lines, processed, failed, cancelled (1 or 0), offset]
      */
-    static native byte[] getNoteByKey(long ndbPtr, long txnPtr, long noteKey);
+    static native long[] processEventsFromFile(long ndbPtr, String path, ImportProgressCallback progress,
+                                               long tokenPtr, long resumeOffset);
 
-    // ========================================================================
-    // Query
-    // ========================================================================
+    /**
+     * Process newline-delimited JSON events from UTF-8 bytes, reporting progress
+     * and stopping once a token is cancelled.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param ldjson Newline-delimited JSON events, UTF-8 encoded
+     * @param progress Progress callback, or null
+     * @param tokenPtr Pointer to a cancellation token, or 0
+     * @return Counts as [lines, processed, failed, cancelled (1 or 0), offset]
+     */
+    static native long[] processEventsBytesCancellable(long ndbPtr, byte[] ldjson, ImportProgressCallback progress,
+                                                       long tokenPtr);
 
     /**
-     * Execute a query with a filter.
+     * Process a single JSON event, calling back from a native thread once it has been written.
      *
      * @param ndbPtr Pointer to the Ndb instance
-     * @param txnPtr Pointer to the Transaction
-     * @param filterPtr Pointer to the Filter
-     * @param limit Maximum number of results
-     * @return Serialized results: [count:4][key1:8][key2:8]...
+     * @param json JSON event string
+     * @param callback Receives the outcome exactly once
+     * @param timeoutMs How long to wait for the note to be stored, not negative
      */
-    static native byte[] query(long ndbPtr, long txnPtr, long filterPtr, int limit);
+    static native void processEventAsync(long ndbPtr, String json, IngestCallback callback, long timeoutMs);
 
-    // ========================================================================
-    // Filter Building
-    // ========================================================================
+    /**
+     * Wait for an event to be stored.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param eventId Event ID (32 bytes)
+     * @param timeoutMs How long to wait, not negative
+     * @return true if the note is stored, false if the wait ran out
+     */
+    static native boolean waitForIngestion(long ndbPtr, byte[] eventId, long timeoutMs);
 
     /**
-     * Create a new filter builder.
+     * Wait for the database to hold at least a number of notes.
      *
-     * @return Pointer to the FilterBuilder
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param expectedCount Number of notes to wait for
+     * @param timeoutMs How long to wait, not negative
+     * @return true if the notes are stored, false if the wait ran out
      */
-    static native long filterNew();
+    static native boolean waitForIngestionCount(long ndbPtr, long expectedCount, long timeoutMs);
 
     /**
-     * Add kinds to the filter.
+     * Process multiple newline-delimited JSON events, reporting the lines that failed.
      *
-     * @param filterPtr Pointer to the FilterBuilder
-     * @param kinds Serialized kinds: [kind1:4][kind2:4]...
-     * @return New filter pointer (old one is consumed)
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param ldjson Newline-delimited JSON events
+     * @param stopOnError Stop at the first failed line
+     * @return Serialized report: [succeeded:4][duplicates:4][failed:4][blank:4][linesRead:4]
+     *         ([line:4][len:2][error:len])*
      */
-    static native long filterKinds(long filterPtr, byte[] kinds);
+    static native byte[] processEventsDetailed(long ndbPtr, String ldjson, boolean stopOnError);
 
     /**
-     * Add authors to the filter.
+     * Process a JSON array of events, reporting the elements that failed.
      *
-     * @param filterPtr Pointer to the FilterBuilder
-     * @param authors Serialized authors: [pubkey1:32][pubkey2:32]...
-     * @return New filter pointer (old one is consumed)
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param json JSON array of events
+     * @return Serialized report, as for {@link #processEventsDetailed}, numbering elements from 1
+     * @throws NostrdbException if the JSON is not an array
      */
-    static native long filterAuthors(long filterPtr, byte[] authors);
+    static native byte[] processEventsArray(long ndbPtr, String json);
 
     /**
-     * Add a tag filter.
+     * Process a single JSON event received from a relay, recording the relay
+     * as one of the note's origins.
      *
-     * @param filterPtr Pointer to the FilterBuilder
-     * @param tagName Single-character tag name (e.g., "d", "p", "e")
-     * @param tagValues Array of tag values
-     * @return New filter pointer (old one is consumed)
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param json JSON string of the event
+     * @param relayUrl URL of the relay the event came from
+     * @return 1 on success, 0 on failure
+     * @throws IllegalArgumentException if relayUrl is empty
      */
-    static native long filterTag(long filterPtr, String tagName, String[] tagValues);
+    static native int processEventFromRelay(long ndbPtr, String json, String relayUrl);
 
     /**
-     * Set the since timestamp.
+     * Process multiple newline-delimited JSON events received from a relay,
+     * recording the relay as one of each note's origins.
      *
-     * @param filterPtr Pointer to the FilterBuilder
-     * @param since Unix timestamp
-     * @return New filter pointer (old one is consumed)
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param ldjson Newline-delimited JSON events
+     * @param relayUrl URL of the relay the events came from
+     * @return Number of events processed, or -1 on error
      */
-    static native long filterSince(long filterPtr, long since);
+    static native int processEventsFromRelay(long ndbPtr, String ldjson, String relayUrl);
 
     /**
-     * Set the until timestamp.
+     * Process a client message {@code ["EVENT", {...}]} through nostrdb's client path.
      *
-     * @param filterPtr Pointer to the FilterBuilder
-     * @param until Unix timestamp
-     * @return New filter pointer (old one is consumed)
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param json JSON of the client message
+     * @param acceptBare Accept a bare event object as if it were wrapped
+     * @return 1 on success, 0 on failure
+     * @throws NostrdbException if the message is not an EVENT envelope
      */
-    static native long filterUntil(long filterPtr, long until);
+    static native int processClientEvent(long ndbPtr, String json, boolean acceptBare);
 
     /**
-     * Set the result limit.
+     * Process a relay message, ingesting it if it is {@code ["EVENT", subid, {...}]}.
      *
-     * @param filterPtr Pointer to the FilterBuilder
-     * @param limit Maximum number of results
-     * @return New filter pointer (old one is consumed)
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param json JSON of the relay message
+     * @return 1 if an event was processed, 0 if the message was skipped
+     * @throws NostrdbException if the JSON is not a relay message or the event cannot be processed
      */
-    static native long filterLimit(long filterPtr, long limit);
+    static native int processRelayEvent(long ndbPtr, String json);
 
     /**
-     * Set full-text search query.
+     * Process newline-delimited relay messages, skipping all but EVENT messages.
      *
-     * @param filterPtr Pointer to the FilterBuilder
-     * @param search Search query
-     * @return New filter pointer (old one is consumed)
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param ldjson Newline-delimited relay messages
+     * @return Counts [events, skipped, failed]
      */
-    static native long filterSearch(long filterPtr, String search);
+    static native int[] processRelayEvents(long ndbPtr, String ldjson);
 
     /**
-     * Build the filter (finalize).
+     * Get the running ingestion totals of a database handle.
      *
-     * @param filterPtr Pointer to the FilterBuilder
-     * @return Pointer to the built Filter
+     * @param ndbPtr Pointer to the Ndb instance
+     * @return Totals as [accepted, duplicates, rejected, bytes]
      */
-    static native long filterBuild(long filterPtr);
+    static native long[] getIngestStats(long ndbPtr);
 
     /**
-     * Destroy a filter.
+     * Set the ingestion totals of a database handle back to zero.
      *
-     * @param filterPtr Pointer to the Filter
+     * @param ndbPtr Pointer to the Ndb instance
      */
-    static native void filterDestroy(long filterPtr);
+    static native void resetIngestStats(long ndbPtr);
 
     // ========================================================================
-    // Profile
+    // Transaction
     // ========================================================================
 
     /**
-     * Get a profile by pubkey.
+     * Begin a read transaction.
+     * IMPORTANT: Only one transaction per thread!
      *
      * @param ndbPtr Pointer to the Ndb instance
-     * @param txnPtr Pointer to the Transaction
-     * @param pubkey 32-byte public key
-     * @return Serialized profile as JSON bytes, or null if not found
+     * @return Pointer to the Transaction, or 0 on error
      */
-    static native byte[] getProfileByPubkey(long ndbPtr, long txnPtr, byte[] pubkey);
+    static native long beginTransaction(long ndbPtr);
 
     /**
-     * Search profiles by name.
+     * End a transaction.
      *
-     * @param ndbPtr Pointer to the Ndb instance
      * @param txnPtr Pointer to the Transaction
-     * @param query Search query
-     * @param limit Maximum number of results
-     * @return Serialized results: [count:4][pubkey1:32][pubkey2:32]...
      */
-    static native byte[] searchProfiles(long ndbPtr, long txnPtr, String query, int limit);
+    static native void endTransaction(long txnPtr);
 
     // ========================================================================
-    // Subscription
+    // Note Retrieval
     // ========================================================================
 
     /**
-     * Subscribe to events matching a filter.
+     * Get a note by its 32-byte event ID.
      *
      * @param ndbPtr Pointer to the Ndb instance
-     * @param filterPtr Pointer to the Filter
-     * @return Subscription ID
+     * @param txnPtr Pointer to the Transaction
+     * @param eventId 32-byte event ID
+     * @return Serialized note as JSON bytes, or null if not found
      */
-    static native long subscribe(long ndbPtr, long filterPtr);
+    static native byte[] getNoteById(long ndbPtr, long txnPtr, byte[] eventId);
 
     /**
-     * Poll for new notes on a subscription.
+     * Get a note by its internal key (faster for repeated lookups).
      *
      * @param ndbPtr Pointer to the Ndb instance
-     * @param subId Subscription ID
-     * @param maxNotes Maximum notes to return
-     * @return Serialized results: [count:4][key1:8][key2:8]...
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return Serialized note as JSON bytes, or null if not found
      */
-    static native byte[] pollForNotes(long ndbPtr, long subId, int maxNotes);
+    static native byte[] getNoteByKey(long ndbPtr, long txnPtr, long noteKey);
 
     /**
-     * Unsubscribe from a subscription.
+     * Get a note by its 32-byte event ID, as CBOR.
      *
      * @param ndbPtr Pointer to the Ndb instance
-     * @param subId Subscription ID
+     * @param txnPtr Pointer to the Transaction
+     * @param eventId 32-byte event ID
+     * @return Serialized note as CBOR bytes, or null if not found
+     */
+    static native byte[] getNoteByIdCbor(long ndbPtr, long txnPtr, byte[] eventId);
+
+    /**
+     * Get a note by its internal key, as CBOR.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return Serialized note as CBOR bytes, or null if not found
+     */
+    static native byte[] getNoteByKeyCbor(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get a note by its 32-byte event ID, as a JSON string. Invalid UTF-8 in
+     * the stored note is replaced with U+FFFD.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param eventId 32-byte event ID
+     * @return The note's JSON, or null if not found
+     */
+    static native String getNoteByIdString(long ndbPtr, long txnPtr, byte[] eventId);
+
+    /**
+     * Get a note by its internal key, as a JSON string. Invalid UTF-8 in the
+     * stored note is replaced with U+FFFD.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return The note's JSON, or null if not found
+     */
+    static native String getNoteByKeyString(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get notes by internal key, skipping keys that don't resolve.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKeys Internal note keys
+     * @return Serialized notes: [count:4][skipped:4]([key:8][size:4][len:4][noteJson:len])*
+     */
+    static native byte[] getNotesByKeys(long ndbPtr, long txnPtr, long[] noteKeys);
+
+    /**
+     * Get notes by event ID, skipping IDs that aren't stored.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param eventIds Event IDs [id1:32][id2:32]...
+     * @return Serialized notes: [count:4][skipped:4][found:(n+7)/8]([key:8][size:4][len:4][noteJson:len])*,
+     *         bit i of the found bitmap (least significant first) set if ID i was found
+     */
+    static native byte[] getNotesByIds(long ndbPtr, long txnPtr, byte[] eventIds);
+
+    /**
+     * Get a note's raw bytes by its internal key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return The note in nostrdb's internal layout (see {@link RawNote}), or null if not found
+     */
+    static native byte[] getNoteRawByKey(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get a note's JSON as nostrdb writes it, by internal key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return The event JSON as UTF-8 bytes, or null if not found
+     */
+    static native byte[] getNoteJsonByKey(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get a note's JSON as nostrdb writes it, by event ID.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param eventId 32-byte event ID
+     * @return The event JSON as UTF-8 bytes, or null if not found
+     */
+    static native byte[] getNoteJsonById(long ndbPtr, long txnPtr, byte[] eventId);
+
+    /**
+     * Get a note's event ID by internal key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return 32-byte event ID, or null if not found
+     */
+    static native byte[] getNoteIdByKey(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get a note's internal key by event ID.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param eventId 32-byte event ID
+     * @return The note key, or -1 if not found
+     */
+    static native long getNoteKeyById(long ndbPtr, long txnPtr, byte[] eventId);
+
+    /**
+     * Check whether a note is stored, by event ID, without reading the note.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param eventId 32-byte event ID
+     * @return true if the note is stored
+     */
+    static native boolean noteExists(long ndbPtr, long txnPtr, byte[] eventId);
+
+    /**
+     * Check which of many event IDs are stored, without reading the notes.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param eventIds Event IDs [id1:32][id2:32]...
+     * @return Bitmap [found:(n+7)/8], bit i (least significant first) set if ID i is stored
+     */
+    static native byte[] notesExist(long ndbPtr, long txnPtr, byte[] eventIds);
+
+    // ========================================================================
+    // Note Fields
+    // ========================================================================
+
+    /**
+     * Get a note's kind by internal key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return The kind, or -1 if not found
+     */
+    static native long getNoteKind(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get a note's creation timestamp by internal key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return Unix timestamp in seconds, or -1 if not found
+     */
+    static native long getNoteCreatedAt(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get a note's author public key by internal key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return 32-byte public key, or null if not found
+     */
+    static native byte[] getNotePubkey(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get a note's content by internal key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return Content as UTF-8 bytes, or null if not found
+     */
+    static native byte[] getNoteContent(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get the length of a note's content by internal key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return Length in UTF-8 bytes, or -1 if not found
+     */
+    static native int getNoteContentLength(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get a note's signature by internal key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return 64-byte signature, or null if not found
+     */
+    static native byte[] getNoteSig(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get a note's stored size by internal key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return Size of the note buffer in bytes, or -1 if not found
+     */
+    static native long getNoteSize(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get the number of tags on a note by internal key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return Tag count, or -1 if not found
+     */
+    static native int noteTagCount(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get the number of elements in one of a note's tags.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @param tagIndex Index of the tag
+     * @return Element count
+     * @throws java.util.NoSuchElementException if the note is not found
+     * @throws IndexOutOfBoundsException if the tag index is out of range
+     */
+    static native int noteTagLen(long ndbPtr, long txnPtr, long noteKey, int tagIndex);
+
+    /**
+     * Get one element of one of a note's tags.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @param tagIndex Index of the tag
+     * @param elemIndex Index of the element in the tag
+     * @param rawIds Whether to return id elements as 32 bytes instead of hex
+     * @return Element as UTF-8 bytes, or 32 id bytes if rawIds is set
+     * @throws java.util.NoSuchElementException if the note is not found
+     * @throws IndexOutOfBoundsException if an index is out of range
+     */
+    static native byte[] noteTagGet(long ndbPtr, long txnPtr, long noteKey, int tagIndex, int elemIndex,
+                                    boolean rawIds);
+
+    /**
+     * Get the 32-byte values of a note's {@code e}, {@code p}, or {@code q} tags.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @param tagName "e", "p", or "q"
+     * @return [count:4]([id:32])*[skipped:4], or null if the note is not found
+     * @throws IllegalArgumentException for any other tag name
+     */
+    static native byte[] getNoteTagIds(long ndbPtr, long txnPtr, long noteKey, String tagName);
+
+    /**
+     * Open a handle on a note by internal key.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction the handle reads from
+     * @param noteKey Internal note key
+     * @return Pointer to the handle, or 0 if not found; free it with {@link #noteClose}
+     */
+    static native long noteOpen(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Open a handle on a note by event ID.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction the handle reads from
+     * @param eventId 32-byte event ID
+     * @return Pointer to the handle, or 0 if not found; free it with {@link #noteClose}
+     */
+    static native long noteOpenById(long ndbPtr, long txnPtr, byte[] eventId);
+
+    /**
+     * Get the internal key of a handle's note.
+     *
+     * @param notePtr Pointer to the note handle
+     * @return Internal note key
+     * @throws IllegalStateException if the handle's transaction has ended
+     */
+    static native long noteHandleKey(long notePtr);
+
+    /**
+     * Get the event ID of a handle's note.
+     *
+     * @param notePtr Pointer to the note handle
+     * @return 32-byte event ID
+     * @throws IllegalStateException if the handle's transaction has ended
+     */
+    static native byte[] noteHandleId(long notePtr);
+
+    /**
+     * Get the author public key of a handle's note.
+     *
+     * @param notePtr Pointer to the note handle
+     * @return 32-byte public key
+     * @throws IllegalStateException if the handle's transaction has ended
+     */
+    static native byte[] noteHandlePubkey(long notePtr);
+
+    /**
+     * Get the signature of a handle's note.
+     *
+     * @param notePtr Pointer to the note handle
+     * @return 64-byte signature
+     * @throws IllegalStateException if the handle's transaction has ended
+     */
+    static native byte[] noteHandleSig(long notePtr);
+
+    /**
+     * Get the kind of a handle's note.
+     *
+     * @param notePtr Pointer to the note handle
+     * @return Event kind
+     * @throws IllegalStateException if the handle's transaction has ended
+     */
+    static native long noteHandleKind(long notePtr);
+
+    /**
+     * Get the creation timestamp of a handle's note.
+     *
+     * @param notePtr Pointer to the note handle
+     * @return Unix timestamp in seconds
+     * @throws IllegalStateException if the handle's transaction has ended
+     */
+    static native long noteHandleCreatedAt(long notePtr);
+
+    /**
+     * Get the content of a handle's note.
+     *
+     * @param notePtr Pointer to the note handle
+     * @return Content as UTF-8 bytes
+     * @throws IllegalStateException if the handle's transaction has ended
+     */
+    static native byte[] noteHandleContent(long notePtr);
+
+    /**
+     * Get the number of tags on a handle's note.
+     *
+     * @param notePtr Pointer to the note handle
+     * @return Tag count
+     * @throws IllegalStateException if the handle's transaction has ended
+     */
+    static native int noteHandleTagCount(long notePtr);
+
+    /**
+     * Get the number of elements in one of a handle's note's tags.
+     *
+     * @param notePtr Pointer to the note handle
+     * @param tagIndex Index of the tag
+     * @return Element count
+     * @throws IndexOutOfBoundsException if the tag index is out of range
+     * @throws IllegalStateException if the handle's transaction has ended
+     */
+    static native int noteHandleTagLen(long notePtr, int tagIndex);
+
+    /**
+     * Get one element of one of a handle's note's tags.
+     *
+     * @param notePtr Pointer to the note handle
+     * @param tagIndex Index of the tag
+     * @param elemIndex Index of the element in the tag
+     * @param rawIds Whether to return id elements as 32 bytes instead of hex
+     * @return Element as UTF-8 bytes, or 32 id bytes if rawIds is set
+     * @throws IndexOutOfBoundsException if an index is out of range
+     * @throws IllegalStateException if the handle's transaction has ended
+     */
+    static native byte[] noteHandleTagGet(long notePtr, int tagIndex, int elemIndex, boolean rawIds);
+
+    /**
+     * Free a note handle. Safe to call after its transaction has ended.
+     *
+     * @param notePtr Pointer to the note handle
+     */
+    static native void noteClose(long notePtr);
+
+    /**
+     * Get the relays a note was seen on.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return Relay URLs, empty if none were recorded, or null if not found
+     */
+    static native String[] getNoteRelays(long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Get a note's content blocks.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return Serialized blocks: [count:4]([type:1][offset:4][length:4][target:1][id:32]?)*,
+     *         or null if not found
+     */
+    static native byte[] getNoteBlocks(long ndbPtr, long txnPtr, long noteKey);
+
+    // ========================================================================
+    // Query
+    // ========================================================================
+
+    /**
+     * Execute a query with a filter. The filter's own limit caps {@code limit}.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of results, or negative to use only the filter's limit
+     *              (every match if it has none)
+     * @return Serialized results: [count:4][key1:8][key2:8]...
+     */
+    static native byte[] query(long ndbPtr, long txnPtr, long filterPtr, int limit);
+
+    /**
+     * Count the notes matching a filter, capped by the filter's own limit.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @return The number of matching notes
+     */
+    static native long queryCount(long ndbPtr, long txnPtr, long filterPtr);
+
+    /**
+     * Execute a query one page at a time.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param pageSize Maximum number of results in the page (must be positive)
+     * @param cursor Cursor returned with the previous page, or null for the first page
+     * @return Serialized page: [count:4][key1:8][key2:8]...[cursor:16], without
+     *         the cursor after the last page
+     */
+    static native byte[] queryPage(long ndbPtr, long txnPtr, long filterPtr, int pageSize, byte[] cursor);
+
+    /**
+     * Start a cursor over the results of a query.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction the cursor reads from
+     * @param filterPtr Pointer to the Filter (copied)
+     * @return Pointer to the cursor; free it with {@link #queryEnd}
+     */
+    static native long queryBegin(long ndbPtr, long txnPtr, long filterPtr);
+
+    /**
+     * Fetch the next page of a cursor's results.
+     *
+     * @param cursorPtr Pointer to the cursor
+     * @param batchSize Maximum number of results (must be positive)
+     * @return Serialized results: [count:4][key1:8][key2:8]..., count 0 at the end
+     */
+    static native byte[] queryNext(long cursorPtr, int batchSize);
+
+    /**
+     * Free a cursor.
+     *
+     * @param cursorPtr Pointer to the cursor
+     */
+    static native void queryEnd(long cursorPtr);
+
+    /**
+     * Create a cancellation token.
+     *
+     * @return Pointer to the token; free it with {@link #cancelTokenFree}
+     */
+    static native long cancelTokenNew();
+
+    /**
+     * Cancel every query using a token. Safe to call from any thread.
+     *
+     * @param tokenPtr Pointer to the token
+     */
+    static native void cancelTokenCancel(long tokenPtr);
+
+    /**
+     * Check if a token has been cancelled.
+     *
+     * @param tokenPtr Pointer to the token
+     * @return true if cancelled
+     */
+    static native boolean cancelTokenIsCancelled(long tokenPtr);
+
+    /**
+     * Free a cancellation token.
+     *
+     * @param tokenPtr Pointer to the token
+     */
+    static native void cancelTokenFree(long tokenPtr);
+
+    /**
+     * Execute a query, stopping early if a token is cancelled.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of results, or negative for the filter's limit
+     * @param tokenPtr Pointer to the cancellation token
+     * @return Serialized results: [count:4][key1:8][key2:8]...[cancelled:1]
+     */
+    static native byte[] queryCancellable(long ndbPtr, long txnPtr, long filterPtr, int limit, long tokenPtr);
+
+    /**
+     * Execute a query returning full notes, stopping early if a token is cancelled.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of results, or negative for the filter's limit
+     * @param tokenPtr Pointer to the cancellation token
+     * @return Serialized notes: [count:4]([len:4][noteJson:len])*[cancelled:1]
+     */
+    static native byte[] queryNotesCancellable(long ndbPtr, long txnPtr, long filterPtr, int limit, long tokenPtr);
+
+    /**
+     * Execute one query with several filters (OR'd together).
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtrs Pointers to the Filters
+     * @param limit Maximum number of results across all filters
+     * @return Serialized results: [count:4][key1:8][key2:8]...
+     */
+    static native byte[] queryMulti(long ndbPtr, long txnPtr, long[] filterPtrs, int limit);
+
+    /**
+     * Execute several filters, merging their results without duplicates.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtrs Pointers to the Filters
+     * @param limit Maximum number of merged results, or negative for no limit
+     *              beyond the filters' own
+     * @return Serialized results, newest first: [count:4][key1:8][key2:8]...
+     */
+    static native byte[] queryMergedUnique(long ndbPtr, long txnPtr, long[] filterPtrs, int limit);
+
+    /**
+     * Execute a query, returning the note keys as an array.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of results, or negative for the filter's limit
+     * @return Note keys, in the same order as {@link #query}
+     */
+    static native long[] queryKeys(long ndbPtr, long txnPtr, long filterPtr, int limit);
+
+    /**
+     * Execute a query, writing the results into a direct buffer.
+     *
+     * <p>The results are written from index 0 regardless of the buffer's
+     * position; nothing is written if the buffer is too small.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of results, or negative for the filter's limit
+     * @param buffer Direct buffer to write [count:4][key1:8][key2:8]... into
+     * @return Number of bytes written, or the negated required size if the
+     *         buffer's capacity is too small
+     */
+    static native int queryInto(long ndbPtr, long txnPtr, long filterPtr, int limit, ByteBuffer buffer);
+
+    /**
+     * Execute a query, returning the note keys in a chosen order.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of results, or negative for the filter's limit
+     * @param order A {@link QueryOrder} code
+     * @return Note keys in the requested order
+     */
+    static native long[] queryOrdered(long ndbPtr, long txnPtr, long filterPtr, int limit, int order);
+
+    /**
+     * Execute a query, returning event ids instead of note keys.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of results, or negative for the filter's limit
+     * @return Serialized ids: [count:4][id1:32][id2:32]...
+     */
+    static native byte[] queryIds(long ndbPtr, long txnPtr, long filterPtr, int limit);
+
+    /**
+     * Execute a query, returning the raw bytes of the matching notes.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of results, or negative for the filter's limit
+     * @return Serialized notes: [count:4]([len:4][rawNote:len])*
+     */
+    static native byte[] queryNotesRaw(long ndbPtr, long txnPtr, long filterPtr, int limit);
+
+    /**
+     * Execute a query, returning the matching notes as CBOR.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of results, or negative for the filter's limit
+     * @return Serialized notes: [count:4]([len:4][noteCbor:len])*
+     */
+    static native byte[] queryNotesCbor(long ndbPtr, long txnPtr, long filterPtr, int limit);
+
+    /**
+     * Find the newest note matching a filter for each author.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param authors Concatenated 32-byte author pubkeys
+     * @return Serialized results, newest first: [count:4]([pubkey:32][key:8][created_at:8])*
+     */
+    static native byte[] queryLatestPerAuthor(long ndbPtr, long txnPtr, long filterPtr, byte[] authors);
+
+    /**
+     * Execute a query and serialize every matching note in the same call.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of results, or negative for the filter's limit
+     * @return Serialized notes: [count:4]([len:4][noteJson:len])*
+     */
+    static native byte[] queryNotes(long ndbPtr, long txnPtr, long filterPtr, int limit);
+
+    /**
+     * Execute a query, keeping only notes accepted by a Java predicate.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of accepted results
+     * @param predicate Called once per candidate note
+     * @return Serialized accepted results: [count:4][key1:8][key2:8]...
+     */
+    static native byte[] queryWithPredicate(long ndbPtr, long txnPtr, long filterPtr, int limit,
+                                            NotePredicate predicate);
+
+    /**
+     * Execute a query, excluding muted authors and events natively. The limit
+     * counts only visible notes.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of visible results, or negative for the filter's limit
+     * @param mutedAuthors Muted pubkeys [pubkey1:32][pubkey2:32]..., or null
+     * @param mutedIds Muted event ids [id1:32][id2:32]..., or null
+     * @param muteThreads Also exclude notes whose p / e tags reference a muted author / event
+     * @return Serialized results: [count:4][key1:8][key2:8]...
+     */
+    static native byte[] queryExcluding(long ndbPtr, long txnPtr, long filterPtr, int limit,
+                                        byte[] mutedAuthors, byte[] mutedIds, boolean muteThreads);
+
+    /**
+     * Execute a query, excluding notes hidden by an author's stored mute list
+     * natively. The limit counts only visible notes.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of visible results, or negative for the filter's limit
+     * @param pubkey 32-byte public key of the mute list's author
+     * @return Serialized results: [count:4][key1:8][key2:8]...
+     */
+    static native byte[] queryExcludingMuteList(long ndbPtr, long txnPtr, long filterPtr, int limit, byte[] pubkey);
+
+    /**
+     * Execute a query, excluding notes whose NIP-40 expiration has passed. The
+     * limit counts only live notes.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of live results, or negative for the filter's limit
+     * @param now Current time in Unix seconds
+     * @return Serialized results: [count:4][key1:8][key2:8]...
+     */
+    static native byte[] queryUnexpired(long ndbPtr, long txnPtr, long filterPtr, int limit, long now);
+
+    /**
+     * Execute a query, returning the notes whose NIP-40 expiration has not passed.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of live results, or negative for the filter's limit
+     * @param now Current time in Unix seconds
+     * @return Serialized notes: [count:4]([len:4][noteJson:len])*
+     */
+    static native byte[] queryNotesUnexpired(long ndbPtr, long txnPtr, long filterPtr, int limit, long now);
+
+    /**
+     * Find the replies in a thread, split by NIP-10 into direct and nested replies.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param rootId 32-byte id of the root event
+     * @param limit Maximum number of replies in total
+     * @return Serialized keys: [directCount:4][key:8]*[otherCount:4][key:8]*
+     */
+    static native byte[] queryThread(long ndbPtr, long txnPtr, byte[] rootId, int limit);
+
+    /**
+     * Total the zap receipts referencing a note.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteId 32-byte id of the zapped note
+     * @return Serialized total: [millisats:8][count:4][unparsable:4]
+     */
+    static native byte[] sumZaps(long ndbPtr, long txnPtr, byte[] noteId);
+
+    /**
+     * Count the reposts and quotes referencing a note.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteId 32-byte id of the note
+     * @return Serialized counts: [reposts:4][quotes:4]
+     */
+    static native byte[] countReposts(long ndbPtr, long txnPtr, byte[] noteId);
+
+    // ========================================================================
+    // Filter Building
+    // ========================================================================
+
+    /**
+     * Create a new filter builder.
+     *
+     * @return Pointer to the FilterBuilder
+     */
+    static native long filterNew();
+
+    /**
+     * Add kinds to the filter.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param kinds Serialized kinds: [kind1:4][kind2:4]...
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterKinds(long filterPtr, byte[] kinds);
+
+    /**
+     * Add kinds to the filter.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param kinds The kinds to match; negative kinds are rejected
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterKindsInt(long filterPtr, int[] kinds);
+
+    /**
+     * Add authors to the filter.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param authors Serialized authors: [pubkey1:32][pubkey2:32]...
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterAuthors(long filterPtr, byte[] authors);
+
+    /**
+     * Add authors to the filter from hex-encoded pubkeys.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param hexPubkeys 64-character hex pubkeys (either case)
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterAuthorsHex(long filterPtr, String[] hexPubkeys);
+
+    /**
+     * Add event IDs to the filter.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param ids Serialized ids: [id1:32][id2:32]...
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterIds(long filterPtr, byte[] ids);
+
+    /**
+     * Add referenced event IDs ({@code #e} tag) to the filter.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param ids Serialized ids: [id1:32][id2:32]...
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterEvents(long filterPtr, byte[] ids);
+
+    /**
+     * Add referenced pubkeys ({@code #p} tag) to the filter.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param pubkeys Serialized pubkeys: [pubkey1:32][pubkey2:32]...
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterPubkeys(long filterPtr, byte[] pubkeys);
+
+    /**
+     * Add a tag filter.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param tagName Single-character tag name (e.g., "d", "p", "e")
+     * @param tagValues Array of tag values
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterTag(long filterPtr, String tagName, String[] tagValues);
+
+    /**
+     * Add hashtags ({@code #t} tag) to the filter, normalized per NIP-24: a
+     * leading '#' is removed, values are lowercased, and empty values and
+     * duplicates are skipped.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param tags Hashtags as typed, e.g. "#Bitcoin"
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterHashtags(long filterPtr, String[] tags);
+
+    /**
+     * Match one parameterized replaceable event by kind, author, and
+     * {@code d} tag, with limit 1 so a query returns the latest version.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param kind Event kind, e.g. 30023
+     * @param author 32-byte author pubkey
+     * @param dTag The {@code d} tag value; may be empty
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterReplaceable(long filterPtr, int kind, byte[] author, String dTag);
+
+    /**
+     * Add a tag filter with 32-byte binary values.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param tagName Single-character tag name
+     * @param values Serialized values: [value1:32][value2:32]...
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterTagBinary(long filterPtr, String tagName, byte[] values);
+
+    /**
+     * Set the since timestamp.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param since Unix timestamp
+     * @return New filter pointer (old one is consumed). Throws
+     *         IllegalArgumentException for a negative or far-future value, in
+     *         which case the old pointer stays valid.
+     */
+    static native long filterSince(long filterPtr, long since);
+
+    /**
+     * Set the until timestamp.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param until Unix timestamp
+     * @return New filter pointer (old one is consumed). Throws
+     *         IllegalArgumentException for a negative or far-future value, in
+     *         which case the old pointer stays valid.
+     */
+    static native long filterUntil(long filterPtr, long until);
+
+    /**
+     * Set the result limit.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param limit Maximum number of results
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterLimit(long filterPtr, long limit);
+
+    /**
+     * Set how far in the future since/until timestamps may lie.
+     *
+     * @param slackSeconds Seconds past the current time (non-negative)
+     */
+    static native void filterSetTimestampSlack(long slackSeconds);
+
+    /**
+     * Set full-text search query.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param search Search query
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterSearch(long filterPtr, String search);
+
+    /**
+     * Match only kinds in an inclusive range. Ranges of up to 256 kinds become
+     * a kinds list; larger ones are checked against query results natively and
+     * cannot be subscribed to.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @param minKind Lowest kind to match
+     * @param maxKind Highest kind to match
+     * @return New filter pointer (old one is consumed)
+     */
+    static native long filterKindRange(long filterPtr, int minKind, int maxKind);
+
+    /**
+     * Build the filter (finalize).
+     *
+     * <p>Throws NostrdbException if since is after until or the filter has too
+     * many elements. The builder is consumed either way.
+     *
+     * @param filterPtr Pointer to the FilterBuilder
+     * @return Pointer to the built Filter
+     */
+    static native long filterBuild(long filterPtr);
+
+    /**
+     * Like {@link #filterBuild}, but also rejects an empty filter.
+     */
+    static native long filterBuildStrict(long filterPtr);
+
+    /**
+     * Parse a NIP-01 filter JSON object into a built filter.
+     *
+     * @param json The filter object
+     * @return Pointer to the Filter (same as {@link #filterBuild})
+     */
+    static native long filterFromJson(String json);
+
+    /**
+     * Like {@link #filterFromJson}, but also rejects an empty filter.
+     */
+    static native long filterFromJsonStrict(String json);
+
+    /**
+     * Serialize a built filter to NIP-01 JSON.
+     *
+     * @param filterPtr Pointer to the Filter
+     * @return The filter JSON object
+     */
+    static native String filterToJson(long filterPtr);
+
+    /**
+     * Check whether a stored note matches a filter.
+     *
+     * @param filterPtr Pointer to the Filter
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param noteKey Internal note key
+     * @return true if the note matches
+     */
+    static native boolean filterMatches(long filterPtr, long ndbPtr, long txnPtr, long noteKey);
+
+    /**
+     * Check whether an event that is not stored matches a filter.
+     *
+     * @param filterPtr Pointer to the Filter
+     * @param eventJson The event JSON object
+     * @return true if the event matches
+     */
+    static native boolean filterMatchesJson(long filterPtr, String eventJson);
+
+    /**
+     * Get the kinds of a built filter.
+     *
+     * @param filterPtr Pointer to the Filter
+     * @return The kinds, or null if the filter has none
+     */
+    static native int[] filterGetKinds(long filterPtr);
+
+    /**
+     * Get the authors of a built filter.
+     *
+     * @param filterPtr Pointer to the Filter
+     * @return Serialized pubkeys [pubkey1:32][pubkey2:32]..., or null if the filter has none
+     */
+    static native byte[] filterGetAuthors(long filterPtr);
+
+    /**
+     * Get the since timestamp of a built filter.
+     *
+     * @param filterPtr Pointer to the Filter
+     * @return The timestamp, or -1 if unset
+     */
+    static native long filterGetSince(long filterPtr);
+
+    /**
+     * Get the until timestamp of a built filter.
+     *
+     * @param filterPtr Pointer to the Filter
+     * @return The timestamp, or -1 if unset
+     */
+    static native long filterGetUntil(long filterPtr);
+
+    /**
+     * Get the limit of a built filter.
+     *
+     * @param filterPtr Pointer to the Filter
+     * @return The limit, or -1 if unset
+     */
+    static native long filterGetLimit(long filterPtr);
+
+    /**
+     * Clone a built filter.
+     *
+     * @param filterPtr Pointer to the Filter
+     * @return Pointer to a new, independently owned Filter
+     */
+    static native long filterClone(long filterPtr);
+
+    /**
+     * Destroy a filter.
+     *
+     * @param filterPtr Pointer to the Filter
+     */
+    static native void filterDestroy(long filterPtr);
+
+    // ========================================================================
+    // Filter Building (stable pointer)
+    // ========================================================================
+    //
+    // Alternative to the consuming filter* functions above: the builder pointer
+    // stays valid across calls and is mutated in place. It must be released with
+    // filterBuilderDestroy, also after filterBuilderBuild.
+
+    /**
+     * Create a new filter builder whose pointer stays stable.
+     *
+     * @return Pointer to the builder
+     */
+    static native long filterBuilderNew();
+
+    /**
+     * Add kinds to the builder.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param kinds Serialized kinds: [kind1:4][kind2:4]...
+     */
+    static native void filterBuilderAddKinds(long builderPtr, byte[] kinds);
+
+    /**
+     * Add kinds to the builder.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param kinds The kinds to match; negative kinds are rejected
+     */
+    static native void filterBuilderAddKindsInt(long builderPtr, int[] kinds);
+
+    /**
+     * Add authors to the builder.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param authors Serialized authors: [pubkey1:32][pubkey2:32]...
+     */
+    static native void filterBuilderAddAuthors(long builderPtr, byte[] authors);
+
+    /**
+     * Add authors to the builder from hex-encoded pubkeys.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param hexPubkeys 64-character hex pubkeys (either case)
+     */
+    static native void filterBuilderAddAuthorsHex(long builderPtr, String[] hexPubkeys);
+
+    /**
+     * Add event IDs to the builder.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param ids Serialized ids: [id1:32][id2:32]...
+     */
+    static native void filterBuilderAddIds(long builderPtr, byte[] ids);
+
+    /**
+     * Add referenced event IDs ({@code #e} tag) to the builder.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param ids Serialized ids: [id1:32][id2:32]...
+     */
+    static native void filterBuilderAddEvents(long builderPtr, byte[] ids);
+
+    /**
+     * Add referenced pubkeys ({@code #p} tag) to the builder.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param pubkeys Serialized pubkeys: [pubkey1:32][pubkey2:32]...
+     */
+    static native void filterBuilderAddPubkeys(long builderPtr, byte[] pubkeys);
+
+    /**
+     * Add a tag filter to the builder.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param tagName Single-character tag name (e.g., "d", "p", "e")
+     * @param tagValues Array of tag values
+     */
+    static native void filterBuilderAddTag(long builderPtr, String tagName, String[] tagValues);
+
+    /**
+     * Add hashtags ({@code #t} tag) to the builder, normalized as in
+     * {@link #filterHashtags}.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param tags Hashtags as typed, e.g. "#Bitcoin"
+     * @return The number of distinct hashtags applied
+     */
+    static native int filterBuilderAddHashtags(long builderPtr, String[] tags);
+
+    /**
+     * Match one parameterized replaceable event, as in {@link #filterReplaceable}.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param kind Event kind, e.g. 30023
+     * @param author 32-byte author pubkey
+     * @param dTag The {@code d} tag value; may be empty
+     */
+    static native void filterBuilderReplaceable(long builderPtr, int kind, byte[] author, String dTag);
+
+    /**
+     * Add a tag filter with 32-byte binary values to the builder.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param tagName Single-character tag name
+     * @param values Serialized values: [value1:32][value2:32]...
+     */
+    static native void filterBuilderAddTagBinary(long builderPtr, String tagName, byte[] values);
+
+    /**
+     * Set the since timestamp on the builder.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param since Unix timestamp
+     */
+    static native void filterBuilderSetSince(long builderPtr, long since);
+
+    /**
+     * Set the until timestamp on the builder.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param until Unix timestamp
+     */
+    static native void filterBuilderSetUntil(long builderPtr, long until);
+
+    /**
+     * Set the result limit on the builder.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param limit Maximum number of results
+     */
+    static native void filterBuilderSetLimit(long builderPtr, long limit);
+
+    /**
+     * Set the full-text search query on the builder.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @param search Search query
+     */
+    static native void filterBuilderSetSearch(long builderPtr, String search);
+
+    /**
+     * Match only kinds in an inclusive range (see {@link #filterKindRange}).
+     */
+    static native void filterBuilderKindRange(long builderPtr, int minKind, int maxKind);
+
+    /**
+     * Build the filter. The builder pointer stays valid but can no longer be
+     * modified or built.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     * @return Pointer to the built Filter
+     */
+    static native long filterBuilderBuild(long builderPtr);
+
+    /**
+     * Like {@link #filterBuilderBuild}, but also rejects an empty filter.
+     */
+    static native long filterBuilderBuildStrict(long builderPtr);
+
+    /**
+     * Destroy a builder, built or not.
+     *
+     * @param builderPtr Pointer from {@link #filterBuilderNew}
+     */
+    static native void filterBuilderDestroy(long builderPtr);
+
+    // ========================================================================
+    // Note Building
+    // ========================================================================
+    //
+    // The builder pointer stays valid across calls and is mutated in place.
+    // It must be released with noteBuilderFree, also after noteBuilderSign.
+
+    /**
+     * Create a builder for an empty kind 1 note.
+     *
+     * @return Pointer to the builder
+     */
+    static native long noteBuilderNew();
+
+    /**
+     * Set the kind of the note being built.
+     *
+     * @param builderPtr Pointer from {@link #noteBuilderNew}
+     * @param kind Event kind
+     * @throws IllegalArgumentException if kind is negative
+     */
+    static native void noteBuilderKind(long builderPtr, int kind);
+
+    /**
+     * Set the content of the note being built.
+     *
+     * @param builderPtr Pointer from {@link #noteBuilderNew}
+     * @param content Content as UTF-8 bytes
+     */
+    static native void noteBuilderContent(long builderPtr, byte[] content);
+
+    /**
+     * Set the creation timestamp of the note being built; without one, the
+     * note is stamped when it is signed.
+     *
+     * @param builderPtr Pointer from {@link #noteBuilderNew}
+     * @param createdAt Unix timestamp in seconds
+     * @throws IllegalArgumentException if createdAt is negative
+     */
+    static native void noteBuilderCreatedAt(long builderPtr, long createdAt);
+
+    /**
+     * Start a new tag; elements are added to the tag started last.
+     *
+     * @param builderPtr Pointer from {@link #noteBuilderNew}
+     */
+    static native void noteBuilderStartTag(long builderPtr);
+
+    /**
+     * Add a string element to the current tag.
+     *
+     * @param builderPtr Pointer from {@link #noteBuilderNew}
+     * @param value Element as UTF-8 bytes
+     * @throws IllegalStateException if no tag has been started
+     */
+    static native void noteBuilderTagStr(long builderPtr, byte[] value);
+
+    /**
+     * Add a 32-byte id element to the current tag.
+     *
+     * @param builderPtr Pointer from {@link #noteBuilderNew}
+     * @param id 32-byte event id or pubkey
+     * @throws IllegalStateException if no tag has been started
+     */
+    static native void noteBuilderTagId(long builderPtr, byte[] id);
+
+    /**
+     * Finalize and sign the note, optionally ingesting it.
+     *
+     * @param builderPtr Pointer from {@link #noteBuilderNew}
+     * @param ndbPtr Pointer to the Ndb instance to ingest into, or 0
+     * @param seckey 32-byte secret key; the native copy is wiped after use
+     * @return Signed event as JSON bytes
+     * @throws IllegalStateException if the note has already been signed
+     */
+    static native byte[] noteBuilderSign(long builderPtr, long ndbPtr, byte[] seckey);
+
+    /**
+     * Free a builder, signed or not.
+     *
+     * @param builderPtr Pointer from {@link #noteBuilderNew}
+     */
+    static native void noteBuilderFree(long builderPtr);
+
+    // ========================================================================
+    // Verification
+    // ========================================================================
+
+    /**
+     * Verify an event's id and signature without ingesting it.
+     *
+     * @param json The event JSON
+     * @param strict Throw IllegalArgumentException describing a failure instead
+     *               of returning its code
+     * @return 0 if valid, 1 for a bad id, 2 for a bad signature, 3 if malformed
+     */
+    static native int verifyEvent(String json, boolean strict);
+
+    /**
+     * Compute the NIP-01 id of an event, ignoring any id and sig fields.
+     *
+     * @param json The event JSON
+     * @return 32-byte event id
+     * @throws IllegalArgumentException if a field is missing or mistyped
+     */
+    static native byte[] computeEventId(String json);
+
+    /**
+     * Verify a stored note's id and signature.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param eventId 32-byte event ID
+     * @return The code as for {@link #verifyEvent}, or -1 if not found
+     */
+    static native int verifyNoteById(long ndbPtr, long txnPtr, byte[] eventId);
+
+    // ========================================================================
+    // Profile
+    // ========================================================================
+
+    /**
+     * Get a profile by pubkey.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param pubkey 32-byte public key
+     * @return Serialized profile as JSON bytes, or null if not found
+     */
+    static native byte[] getProfileByPubkey(long ndbPtr, long txnPtr, byte[] pubkey);
+
+    /**
+     * Get one string field of a profile by pubkey.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param pubkey 32-byte public key
+     * @param fieldId {@link ProfileField} code
+     * @return The field's value, or null if there is no profile or it lacks the field
+     * @throws IllegalArgumentException for an unknown field id
+     */
+    static native String getProfileField(long ndbPtr, long txnPtr, byte[] pubkey, int fieldId);
+
+    /**
+     * Get the kind-0 event behind a pubkey's profile, as nostrdb writes it.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param pubkey 32-byte public key
+     * @return The event JSON, or null if there is no stored profile
+     */
+    static native byte[] getProfileNote(long ndbPtr, long txnPtr, byte[] pubkey);
+
+    /**
+     * Get a profile's raw bytes by public key: nostrdb's stored flatbuffer,
+     * rooted at an {@code NdbProfileRecord}.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param pubkey 32-byte public key
+     * @return The raw profile record, or null if not found
+     */
+    static native byte[] getProfileRawByPubkey(long ndbPtr, long txnPtr, byte[] pubkey);
+
+    /**
+     * Get profiles by pubkey, in one call.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param pubkeys Public keys [pubkey1:32][pubkey2:32]...
+     * @return Serialized profiles: [count:4]([pubkey:32][len:4][profileJson:len])*, in
+     *         input order, with len 0 for pubkeys without a profile
+     */
+    static native byte[] getProfilesByPubkeys(long ndbPtr, long txnPtr, byte[] pubkeys);
+
+    /**
+     * Get the pubkeys an author follows, from their newest contact list.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param pubkey 32-byte public key of the author
+     * @param relayHints Whether to append each contact's relay hint
+     * @return Serialized contacts: [count:4]([pubkey:32])*, followed with relayHints by
+     *         ([len:2][relay:len])*, or null if the author has no contact list
+     */
+    static native byte[] getContacts(long ndbPtr, long txnPtr, byte[] pubkey, boolean relayHints);
+
+    /**
+     * Get the relays an author reads from and writes to, from their newest relay list.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param pubkey 32-byte public key of the author
+     * @return Serialized relays: [count:4]([mode:1][len:2][url:len])*, where mode bit 0 is
+     *         read and bit 1 is write; or null if the author has no relay list
+     */
+    static native byte[] getRelayList(long ndbPtr, long txnPtr, byte[] pubkey);
+
+    /**
+     * Get the public entries of an author's newest mute list.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param pubkey 32-byte public key of the author
+     * @return Serialized mute list: [flags:1][count:4]([pubkey:32])*[count:4]([id:32])*
+     *         [count:4]([len:2][hashtag:len])*[count:4]([len:2][word:len])*, where flag bit 0
+     *         marks skipped private entries; or null if the author has no mute list
+     */
+    static native byte[] getMuteList(long ndbPtr, long txnPtr, byte[] pubkey);
+
+    /**
+     * Get the profiles of the authors of the notes matching a filter.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param filterPtr Pointer to the Filter
+     * @param limit Maximum number of notes
+     * @param includeMissing Include authors without a profile, as an empty JSON object
+     * @return Serialized profiles: [count:4]([pubkey:32][len:4][profileJson:len])*
+     */
+    static native byte[] queryProfiles(long ndbPtr, long txnPtr, long filterPtr, int limit, boolean includeMissing);
+
+    /**
+     * Search note contents.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param query Search text
+     * @param limit Maximum number of hits
+     * @param order {@link SearchOrder} code
+     * @return Serialized hits: [count:4]([key:8][created_at:8][offset:4][length:4])*
+     */
+    static native byte[] searchNotes(long ndbPtr, long txnPtr, String query, int limit, int order);
+
+    /**
+     * Search profiles by name.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param query Search query
+     * @param limit Maximum number of results
+     * @param flags 0, or {@link Ndb#PROFILE_SEARCH_FOLD} to ignore case and accents
+     * @return Serialized results: [count:4][pubkey1:32][pubkey2:32]...
+     */
+    static native byte[] searchProfiles(long ndbPtr, long txnPtr, String query, int limit, int flags);
+
+    /**
+     * Search profiles by name, with each hit's name and display name.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param txnPtr Pointer to the Transaction
+     * @param query Search query
+     * @param limit Maximum number of results
+     * @param flags As in {@link #searchProfiles}
+     * @return Serialized hits: [count:4]([pubkey:32][nameLen:2][name][displayNameLen:2][displayName])*,
+     *         with empty names where the profile has none
+     */
+    static native byte[] searchProfilesNamed(long ndbPtr, long txnPtr, String query, int limit, int flags);
+
+    // ========================================================================
+    // Subscription
+    // ========================================================================
+
+    /**
+     * Subscribe to events matching a filter.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param filterPtr Pointer to the Filter
+     * @return Subscription ID
+     */
+    static native long subscribe(long ndbPtr, long filterPtr);
+
+    /**
+     * Poll for new notes on a subscription.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param subId Subscription ID
+     * @param maxNotes Maximum notes to return
+     * @return Serialized results: [count:4][key1:8][key2:8]...
+     */
+    static native byte[] pollForNotes(long ndbPtr, long subId, int maxNotes);
+
+    /**
+     * Poll for new notes on a subscription, returning the keys as an array.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param subId Subscription ID
+     * @param maxNotes Maximum notes to return
+     * @return Note keys, in the same order as {@link #pollForNotes}
+     */
+    static native long[] pollForNoteKeys(long ndbPtr, long subId, int maxNotes);
+
+    /**
+     * Unsubscribe from a subscription.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param subId Subscription ID
      */
     static native void unsubscribe(long ndbPtr, long subId);
+
+    // ========================================================================
+    // Health Monitoring
+    // ========================================================================
+
+    /**
+     * Start or replace the health monitor for a database.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param callback Callback to notify, or null to stop monitoring
+     * @param checkIntervalMs Milliseconds between checks (positive)
+     */
+    static native void setHealthCallback(long ndbPtr, HealthCallback callback, long checkIntervalMs);
+
+    /**
+     * Set health monitor thresholds, applied when a callback is next registered.
+     *
+     * @param ndbPtr Pointer to the Ndb instance
+     * @param mapNearlyFullPercent Map utilization (0-100) that triggers MAP_NEARLY_FULL
+     * @param ingestBacklogHigh Events accepted but not yet stored (positive) that trigger
+     *                          INGEST_BACKLOG_HIGH
+     */
+    static native void setHealthThresholds(long ndbPtr, int mapNearlyFullPercent, long ingestBacklogHigh);
+
+    // ========================================================================
+    // Version Information
+    // ========================================================================
+
+    /**
+     * Describe the loaded native library.
+     *
+     * @return The nostrdb-jni version, nostrdb version, and git commit
+     */
+    static native String nativeVersion();
+
+    /**
+     * Get the ABI version of the loaded native library.
+     *
+     * @return The native ABI version
+     * @see #EXPECTED_ABI_VERSION
+     */
+    static native int nativeAbiVersion();
 }
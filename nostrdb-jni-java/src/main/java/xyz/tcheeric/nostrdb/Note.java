@@ -6,7 +6,11 @@ import com.fasterxml.jackson.annotation.JsonProperty;
 import com.fasterxml.jackson.core.JsonProcessingException;
 import com.fasterxml.jackson.databind.ObjectMapper;
 
+import java.io.IOException;
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
 import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
 import java.util.List;
 
 /**
@@ -66,6 +70,33 @@ public final class Note {
         }
     }
 
+    /**
+     * Parse notes from a native byte array.
+     *
+     * <p>Format: [count:4]([len:4][noteJson:len])*
+     */
+    static List<Note> parseNotes(byte[] data) {
+        if (data == null || data.length < 4) {
+            return List.of();
+        }
+
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+
+        List<Note> notes = new ArrayList<>(count);
+        try {
+            for (int i = 0; i < count; i++) {
+                int len = buf.getInt();
+                notes.add(MAPPER.readValue(data, buf.position(), len, Note.class));
+                buf.position(buf.position() + len);
+            }
+        } catch (IOException e) {
+            throw new NostrdbException("Failed to parse note JSON", e);
+        }
+
+        return notes;
+    }
+
     /**
      * Parse a note from a JSON string.
      */
@@ -0,0 +1,138 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.util.ArrayList;
+import java.util.BitSet;
+import java.util.HashSet;
+import java.util.List;
+import java.util.Set;
+
+/**
+ * Notes fetched in one batch, in the order they were requested.
+ *
+ * <p>Requested notes that aren't stored are left out; {@link #skippedCount()}
+ * says how many, and {@link #isPresent(int)} which.
+ *
+ * @see Ndb#getNotesByKeys(Transaction, long[])
+ * @see Ndb#getNotesByIds(Transaction, List)
+ */
+public final class NoteBatch {
+
+    private final long[] keys;
+    private final long[] sizes;
+    private final List<Note> notes;
+    private final int skippedCount;
+    private final BitSet present;
+
+    private NoteBatch(long[] keys, long[] sizes, List<Note> notes, int skippedCount, BitSet present) {
+        this.keys = keys;
+        this.sizes = sizes;
+        this.notes = notes;
+        this.skippedCount = skippedCount;
+        this.present = present;
+    }
+
+    /**
+     * Get the notes found.
+     *
+     * @return Notes, in request order
+     */
+    public List<Note> notes() {
+        return notes;
+    }
+
+    /**
+     * Get the internal keys of the notes found.
+     *
+     * @return Note keys, parallel to {@link #notes()}
+     */
+    public long[] keys() {
+        return keys.clone();
+    }
+
+    /**
+     * Get the stored sizes of the notes found.
+     *
+     * @return Sizes in bytes, as {@link Ndb#getNoteSize}, parallel to {@link #notes()}
+     */
+    public long[] sizes() {
+        return sizes.clone();
+    }
+
+    /**
+     * Get the number of requested notes that weren't found.
+     *
+     * @return Skipped count
+     */
+    public int skippedCount() {
+        return skippedCount;
+    }
+
+    /**
+     * Check whether a requested note was found.
+     *
+     * @param index Position in the requested keys or ids
+     * @return true if the note is in {@link #notes()}
+     */
+    public boolean isPresent(int index) {
+        return present.get(index);
+    }
+
+    /**
+     * Parse a batch of notes requested by key from native byte array.
+     *
+     * <p>Format: [count:4][skipped:4]([key:8][size:4][len:4][noteJson:len])*
+     */
+    static NoteBatch parseByKeys(byte[] data, long[] requested) {
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+        int skipped = buf.getInt();
+        NoteBatch batch = readNotes(buf, count, skipped, new BitSet());
+
+        Set<Long> found = new HashSet<>();
+        for (long key : batch.keys) {
+            found.add(key);
+        }
+        for (int i = 0; i < requested.length; i++) {
+            if (found.contains(requested[i])) {
+                batch.present.set(i);
+            }
+        }
+        return batch;
+    }
+
+    /**
+     * Parse a batch of notes requested by id from native byte array.
+     *
+     * <p>Format: [count:4][skipped:4][found:(n+7)/8]([key:8][size:4][len:4][noteJson:len])*
+     */
+    static NoteBatch parseByIds(byte[] data, int requested) {
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+        int skipped = buf.getInt();
+        byte[] bitmap = new byte[(requested + 7) / 8];
+        buf.get(bitmap);
+        return readNotes(buf, count, skipped, BitSet.valueOf(bitmap));
+    }
+
+    private static NoteBatch readNotes(ByteBuffer buf, int count, int skipped, BitSet present) {
+        long[] keys = new long[count];
+        long[] sizes = new long[count];
+        List<Note> notes = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            keys[i] = buf.getLong();
+            sizes[i] = Integer.toUnsignedLong(buf.getInt());
+            byte[] json = new byte[buf.getInt()];
+            buf.get(json);
+            notes.add(Note.fromBytes(json));
+        }
+
+        return new NoteBatch(keys, sizes, notes, skipped, present);
+    }
+
+    @Override
+    public String toString() {
+        return "NoteBatch{count=" + notes.size() + ", skippedCount=" + skippedCount + '}';
+    }
+}
@@ -0,0 +1,213 @@
+package xyz.tcheeric.nostrdb;
+
+import java.io.Closeable;
+import java.nio.charset.StandardCharsets;
+
+/**
+ * Builds and signs a Nostr event in the native layer.
+ *
+ * <p>nostrdb computes the id, derives the pubkey from the secret key, and
+ * writes the BIP-340 signature, so no separate signing library is needed.
+ * The secret key is copied to native memory only while {@link #sign} runs and
+ * is wiped before it returns; clearing the caller's own array is up to the
+ * caller.
+ *
+ * <p>Example usage:
+ * <pre>{@code
+ * try (NoteBuilder builder = new NoteBuilder()) {
+ *     String json = builder.kind(1)
+ *         .content("hello")
+ *         .tag("t", "nostr")
+ *         .sign(secretKey, ndb);
+ *     relay.publish(json);
+ * }
+ * }</pre>
+ *
+ * <p>A builder signs one note. It is not thread-safe.
+ */
+public final class NoteBuilder implements Closeable {
+
+    private final long ptr;
+    private boolean signed;
+    private boolean closed;
+
+    /**
+     * Create a builder for an empty kind 1 note, stamped with the time it is
+     * signed unless {@link #createdAt} is set.
+     *
+     * @throws NostrdbException if the native builder cannot be created
+     */
+    public NoteBuilder() {
+        this.ptr = NostrdbNative.noteBuilderNew();
+        if (this.ptr == 0) {
+            throw new NostrdbException("Failed to create note builder");
+        }
+    }
+
+    /**
+     * Set the event kind.
+     *
+     * @param kind Event kind
+     * @return this builder
+     * @throws IllegalArgumentException if kind is negative
+     */
+    public NoteBuilder kind(int kind) {
+        checkNotSigned();
+        NostrdbNative.noteBuilderKind(ptr, kind);
+        return this;
+    }
+
+    /**
+     * Set the content.
+     *
+     * @param content Event content
+     * @return this builder
+     * @throws IllegalArgumentException if content is null
+     */
+    public NoteBuilder content(String content) {
+        checkNotSigned();
+        if (content == null) {
+            throw new IllegalArgumentException("Content must not be null");
+        }
+        NostrdbNative.noteBuilderContent(ptr, content.getBytes(StandardCharsets.UTF_8));
+        return this;
+    }
+
+    /**
+     * Set the creation timestamp.
+     *
+     * @param createdAt Unix timestamp in seconds
+     * @return this builder
+     * @throws IllegalArgumentException if createdAt is negative
+     */
+    public NoteBuilder createdAt(long createdAt) {
+        checkNotSigned();
+        NostrdbNative.noteBuilderCreatedAt(ptr, createdAt);
+        return this;
+    }
+
+    /**
+     * Add a tag of string elements.
+     *
+     * @param elements The tag's elements, e.g. {@code "t", "nostr"}
+     * @return this builder
+     * @throws IllegalArgumentException if an element is null
+     */
+    public NoteBuilder tag(String... elements) {
+        startTag();
+        for (String element : elements) {
+            tagStr(element);
+        }
+        return this;
+    }
+
+    /**
+     * Start a new, empty tag. {@link #tagStr} and {@link #tagId} add elements
+     * to the tag started last.
+     *
+     * @return this builder
+     */
+    public NoteBuilder startTag() {
+        checkNotSigned();
+        NostrdbNative.noteBuilderStartTag(ptr);
+        return this;
+    }
+
+    /**
+     * Add a string element to the current tag.
+     *
+     * @param value The element
+     * @return this builder
+     * @throws IllegalArgumentException if value is null
+     * @throws IllegalStateException if no tag has been started
+     */
+    public NoteBuilder tagStr(String value) {
+        checkNotSigned();
+        if (value == null) {
+            throw new IllegalArgumentException("Tag element must not be null");
+        }
+        NostrdbNative.noteBuilderTagStr(ptr, value.getBytes(StandardCharsets.UTF_8));
+        return this;
+    }
+
+    /**
+     * Add an event id or pubkey element to the current tag; it appears in the
+     * event as 64 hex characters.
+     *
+     * @param id 32-byte event id or pubkey
+     * @return this builder
+     * @throws IllegalArgumentException if id is not 32 bytes
+     * @throws IllegalStateException if no tag has been started
+     */
+    public NoteBuilder tagId(byte[] id) {
+        checkNotSigned();
+        if (id == null || id.length != 32) {
+            throw new IllegalArgumentException("ID must be 32 bytes");
+        }
+        NostrdbNative.noteBuilderTagId(ptr, id);
+        return this;
+    }
+
+    /**
+     * Finalize and sign the note.
+     *
+     * @param secretKey 32-byte secret key
+     * @return The signed event as JSON
+     * @throws IllegalArgumentException if the key is not 32 bytes or is not a
+     *         valid secp256k1 secret key
+     * @throws IllegalStateException if the note has already been signed
+     */
+    public String sign(byte[] secretKey) {
+        return sign(secretKey, null);
+    }
+
+    /**
+     * Finalize and sign the note, then ingest it.
+     *
+     * <p>Ingestion is asynchronous, as for {@link Ndb#processEvent}: the note
+     * becomes visible to queries shortly after this returns.
+     *
+     * @param secretKey 32-byte secret key
+     * @param ndb Database to ingest the note into, or null not to ingest it
+     * @return The signed event as JSON
+     * @throws IllegalArgumentException if the key is not 32 bytes or is not a
+     *         valid secp256k1 secret key
+     * @throws IllegalStateException if the note has already been signed, or
+     *         ndb is closed or write-protected
+     */
+    public String sign(byte[] secretKey, Ndb ndb) {
+        checkNotSigned();
+        if (secretKey == null || secretKey.length != 32) {
+            throw new IllegalArgumentException("Secret key must be 32 bytes");
+        }
+        long ndbPtr = ndb == null ? 0 : ndb.ptr();
+        signed = true;
+        try {
+            byte[] json = NostrdbNative.noteBuilderSign(ptr, ndbPtr, secretKey);
+            return new String(json, StandardCharsets.UTF_8);
+        } finally {
+            close();
+        }
+    }
+
+    private void checkNotSigned() {
+        if (signed) {
+            throw new IllegalStateException("Note has already been signed");
+        }
+        if (closed) {
+            throw new IllegalStateException("NoteBuilder is closed");
+        }
+    }
+
+    /**
+     * Free the builder. Signing frees it as well; closing it again does
+     * nothing.
+     */
+    @Override
+    public void close() {
+        if (!closed) {
+            closed = true;
+            NostrdbNative.noteBuilderFree(ptr);
+        }
+    }
+}
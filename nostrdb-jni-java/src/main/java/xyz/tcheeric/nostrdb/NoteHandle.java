@@ -0,0 +1,203 @@
+package xyz.tcheeric.nostrdb;
+
+import java.io.Closeable;
+import java.nio.charset.StandardCharsets;
+import java.util.concurrent.atomic.AtomicBoolean;
+
+/**
+ * A handle on a stored note, reading each field natively when asked for it.
+ *
+ * <p>Where {@link Ndb#getNoteByKey} deserializes the whole note, a handle
+ * fetches it once and copies out only the fields that are read, which suits
+ * code that looks at a note's kind or a few tags before deciding whether to
+ * read more. Handles read from the transaction they were opened with; close
+ * the handle before the transaction. Once the transaction is closed, every
+ * accessor throws {@link IllegalStateException}.
+ *
+ * <p>Example usage:
+ * <pre>{@code
+ * try (Transaction txn = ndb.beginTransaction();
+ *      NoteHandle note = ndb.openNote(txn, noteKey).orElseThrow()) {
+ *     if (note.kind() == 1) {
+ *         render(note.pubkey(), note.content());
+ *     }
+ * }
+ * }</pre>
+ */
+public final class NoteHandle implements Closeable {
+
+    private final long ptr;
+    private final AtomicBoolean closed = new AtomicBoolean(false);
+
+    NoteHandle(long ptr) {
+        this.ptr = ptr;
+    }
+
+    /**
+     * Get the note's internal key.
+     *
+     * @return Internal note key
+     * @throws IllegalStateException if the handle or its transaction is closed
+     */
+    public long key() {
+        checkOpen();
+        return NostrdbNative.noteHandleKey(ptr);
+    }
+
+    /**
+     * Get the note's event ID.
+     *
+     * @return 32-byte event ID
+     * @throws IllegalStateException if the handle or its transaction is closed
+     */
+    public byte[] id() {
+        checkOpen();
+        return NostrdbNative.noteHandleId(ptr);
+    }
+
+    /**
+     * Get the public key of the note's author.
+     *
+     * @return 32-byte public key
+     * @throws IllegalStateException if the handle or its transaction is closed
+     */
+    public byte[] pubkey() {
+        checkOpen();
+        return NostrdbNative.noteHandlePubkey(ptr);
+    }
+
+    /**
+     * Get the note's signature.
+     *
+     * @return 64-byte signature
+     * @throws IllegalStateException if the handle or its transaction is closed
+     */
+    public byte[] sig() {
+        checkOpen();
+        return NostrdbNative.noteHandleSig(ptr);
+    }
+
+    /**
+     * Get the note's kind.
+     *
+     * @return Event kind
+     * @throws IllegalStateException if the handle or its transaction is closed
+     */
+    public int kind() {
+        checkOpen();
+        return (int) NostrdbNative.noteHandleKind(ptr);
+    }
+
+    /**
+     * Get the note's creation timestamp.
+     *
+     * @return Unix timestamp in seconds
+     * @throws IllegalStateException if the handle or its transaction is closed
+     */
+    public long createdAt() {
+        checkOpen();
+        return NostrdbNative.noteHandleCreatedAt(ptr);
+    }
+
+    /**
+     * Get the note's content.
+     *
+     * @return The content
+     * @throws IllegalStateException if the handle or its transaction is closed
+     * @throws InvalidContentException if the content is not valid UTF-8 and
+     *         the database was opened with {@link NdbConfig#strictUtf8}
+     */
+    public String content() {
+        return new String(contentBytes(), StandardCharsets.UTF_8);
+    }
+
+    /**
+     * Get the note's content as UTF-8 bytes.
+     *
+     * @return The content's bytes
+     * @throws IllegalStateException if the handle or its transaction is closed
+     * @throws InvalidContentException if the content is not valid UTF-8 and
+     *         the database was opened with {@link NdbConfig#strictUtf8}
+     */
+    public byte[] contentBytes() {
+        checkOpen();
+        return NostrdbNative.noteHandleContent(ptr);
+    }
+
+    /**
+     * Get the number of tags on the note.
+     *
+     * @return The tag count
+     * @throws IllegalStateException if the handle or its transaction is closed
+     */
+    public int tagCount() {
+        checkOpen();
+        return NostrdbNative.noteHandleTagCount(ptr);
+    }
+
+    /**
+     * Get the number of elements in one of the note's tags.
+     *
+     * @param tagIndex Index of the tag
+     * @return The element count
+     * @throws IndexOutOfBoundsException if the tag index is out of range
+     * @throws IllegalStateException if the handle or its transaction is closed
+     */
+    public int tagLength(int tagIndex) {
+        checkOpen();
+        return NostrdbNative.noteHandleTagLen(ptr, tagIndex);
+    }
+
+    /**
+     * Get one element of one of the note's tags as a string, with id
+     * elements hex-encoded as in {@link Ndb#getNoteTag(Transaction, long, int, int)}.
+     *
+     * @param tagIndex Index of the tag
+     * @param elemIndex Index of the element in the tag
+     * @return The element
+     * @throws IndexOutOfBoundsException if an index is out of range
+     * @throws IllegalStateException if the handle or its transaction is closed
+     */
+    public String tag(int tagIndex, int elemIndex) {
+        return new String(tag(tagIndex, elemIndex, false), StandardCharsets.UTF_8);
+    }
+
+    /**
+     * Get one element of one of the note's tags as bytes.
+     *
+     * @param tagIndex Index of the tag
+     * @param elemIndex Index of the element in the tag
+     * @param rawIds Whether to return elements stored as ids as their 32 bytes
+     *               rather than as UTF-8 hex
+     * @return The element's UTF-8 bytes, or 32 id bytes if {@code rawIds} is set
+     *         and the element is stored as an id
+     * @throws IndexOutOfBoundsException if an index is out of range
+     * @throws IllegalStateException if the handle or its transaction is closed
+     */
+    public byte[] tag(int tagIndex, int elemIndex, boolean rawIds) {
+        checkOpen();
+        return NostrdbNative.noteHandleTagGet(ptr, tagIndex, elemIndex, rawIds);
+    }
+
+    /**
+     * Check if this handle is still open.
+     *
+     * @return true if open, false if closed
+     */
+    public boolean isOpen() {
+        return !closed.get();
+    }
+
+    private void checkOpen() {
+        if (closed.get()) {
+            throw new IllegalStateException("Note handle is closed");
+        }
+    }
+
+    @Override
+    public void close() {
+        if (closed.compareAndSet(false, true)) {
+            NostrdbNative.noteClose(ptr);
+        }
+    }
+}
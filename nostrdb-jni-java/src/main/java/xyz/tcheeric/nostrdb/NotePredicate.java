@@ -0,0 +1,29 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * Decides whether a candidate note belongs in a query result, for filtering
+ * that NIP-01 filters can't express (e.g. content regexes or proof-of-work).
+ *
+ * <p>Example usage:
+ * <pre>{@code
+ * Pattern invoice = Pattern.compile("lnbc[0-9a-z]+");
+ * List<QueryResult> results = ndb.query(txn, filter, 50, noteJson ->
+ *     invoice.matcher(new String(noteJson, StandardCharsets.UTF_8)).find());
+ * }</pre>
+ *
+ * <p>The predicate runs synchronously on the querying thread. An exception it
+ * throws aborts the query and propagates to the caller.
+ *
+ * @see Ndb#query(Transaction, Filter, int, NotePredicate)
+ */
+@FunctionalInterface
+public interface NotePredicate {
+
+    /**
+     * Test one candidate note.
+     *
+     * @param noteJson The note as UTF-8 JSON, in the format returned by {@link Ndb#getNoteByKey}
+     * @return true to include the note in the results
+     */
+    boolean test(byte[] noteJson);
+}
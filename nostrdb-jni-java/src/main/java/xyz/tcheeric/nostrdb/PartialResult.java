@@ -0,0 +1,54 @@
+package xyz.tcheeric.nostrdb;
+
+import java.util.List;
+
+/**
+ * Results of a cancellable query.
+ *
+ * <p>If the query was cancelled, {@link #results()} holds the results found
+ * before it stopped: the newest matches, in order, but possibly not all of them.
+ *
+ * @param <T> The result type
+ * @see CancelToken
+ */
+public final class PartialResult<T> {
+
+    private final List<T> results;
+    private final boolean cancelled;
+
+    private PartialResult(List<T> results, boolean cancelled) {
+        this.results = results;
+        this.cancelled = cancelled;
+    }
+
+    /**
+     * The results found, newest first.
+     *
+     * @return Every result, or a prefix of them if cancelled
+     */
+    public List<T> results() {
+        return results;
+    }
+
+    /**
+     * Check if the query stopped early because its token was cancelled.
+     *
+     * @return true if the results may be incomplete
+     */
+    public boolean isCancelled() {
+        return cancelled;
+    }
+
+    /**
+     * Wrap results parsed from native data ending in a cancelled marker byte.
+     */
+    static <T> PartialResult<T> of(List<T> results, byte[] data) {
+        boolean cancelled = data != null && data.length > 0 && data[data.length - 1] != 0;
+        return new PartialResult<>(results, cancelled);
+    }
+
+    @Override
+    public String toString() {
+        return "PartialResult{results=" + results.size() + ", cancelled=" + cancelled + '}';
+    }
+}
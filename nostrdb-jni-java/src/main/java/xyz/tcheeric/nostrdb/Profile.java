@@ -7,6 +7,7 @@ import com.fasterxml.jackson.core.JsonProcessingException;
 import com.fasterxml.jackson.databind.ObjectMapper;
 
 import java.nio.charset.StandardCharsets;
+import java.util.OptionalLong;
 
 /**
  * A Nostr profile (kind 0 event content).
@@ -22,6 +23,10 @@ import java.nio.charset.StandardCharsets;
  *   <li>lud16 - Lightning address</li>
  *   <li>website - Website URL</li>
  * </ul>
+ *
+ * <p>Profiles read from the database also carry what nostrdb recorded about
+ * them: when the profile was received, the key of the kind-0 note it came
+ * from, and the LNURL nostrdb resolved.
  */
 @JsonIgnoreProperties(ignoreUnknown = true)
 public final class Profile {
@@ -37,9 +42,26 @@ public final class Profile {
     private final String lud16;
     private final String lud06;
     private final String website;
+    private final Boolean reactions;
+    private final Long receivedAt;
+    private final Long noteKey;
+    private final String lnurl;
 
-    @JsonCreator
     public Profile(
+            String name,
+            String displayName,
+            String about,
+            String picture,
+            String banner,
+            String nip05,
+            String lud16,
+            String lud06,
+            String website) {
+        this(name, displayName, about, picture, banner, nip05, lud16, lud06, website, null, null, null, null);
+    }
+
+    @JsonCreator
+    private Profile(
             @JsonProperty("name") String name,
             @JsonProperty("display_name") String displayName,
             @JsonProperty("about") String about,
@@ -48,7 +70,11 @@ public final class Profile {
             @JsonProperty("nip05") String nip05,
             @JsonProperty("lud16") String lud16,
             @JsonProperty("lud06") String lud06,
-            @JsonProperty("website") String website) {
+            @JsonProperty("website") String website,
+            @JsonProperty("reactions") Boolean reactions,
+            @JsonProperty("received_at") Long receivedAt,
+            @JsonProperty("note_key") Long noteKey,
+            @JsonProperty("lnurl") String lnurl) {
         this.name = name;
         this.displayName = displayName;
         this.about = about;
@@ -58,6 +84,10 @@ public final class Profile {
         this.lud16 = lud16;
         this.lud06 = lud06;
         this.website = website;
+        this.reactions = reactions;
+        this.receivedAt = receivedAt;
+        this.noteKey = noteKey;
+        this.lnurl = lnurl;
     }
 
     /**
@@ -170,6 +200,44 @@ public final class Profile {
         return website;
     }
 
+    /**
+     * Get whether the user wants to see reactions (a damus extension).
+     *
+     * <p>Returns true unless the profile turns them off.
+     */
+    public boolean reactions() {
+        return reactions == null || reactions;
+    }
+
+    /**
+     * Get when nostrdb received this profile.
+     *
+     * @return Unix timestamp in seconds, or empty if not read from the database
+     */
+    public OptionalLong receivedAt() {
+        return receivedAt == null ? OptionalLong.empty() : OptionalLong.of(receivedAt);
+    }
+
+    /**
+     * Get the internal key of the kind-0 note this profile was read from.
+     *
+     * <p>Pass it to {@link Ndb#getNoteByKey} to fetch the original event.
+     *
+     * @return The note key, or empty if not read from the database
+     */
+    public OptionalLong noteKey() {
+        return noteKey == null ? OptionalLong.empty() : OptionalLong.of(noteKey);
+    }
+
+    /**
+     * Get the LNURL nostrdb resolved for this profile's Lightning address.
+     *
+     * @return The LNURL, or null if none was recorded
+     */
+    public String lnurl() {
+        return lnurl;
+    }
+
     /**
      * Convert to JSON string.
      */
@@ -187,6 +255,7 @@ public final class Profile {
             "name='" + name + '\'' +
             ", displayName='" + displayName + '\'' +
             ", nip05='" + nip05 + '\'' +
+            ", noteKey=" + noteKey +
             '}';
     }
 }
@@ -0,0 +1,48 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * A single string field of a profile, for
+ * {@link Ndb#getProfileField(Transaction, byte[], ProfileField)}.
+ */
+public enum ProfileField {
+
+    /** The {@code name} field. */
+    NAME(0),
+
+    /** The {@code display_name} field. */
+    DISPLAY_NAME(1),
+
+    /** The {@code about} field. */
+    ABOUT(2),
+
+    /** The {@code picture} field (avatar URL). */
+    PICTURE(3),
+
+    /** The {@code banner} field (banner image URL). */
+    BANNER(4),
+
+    /** The {@code nip05} field (NIP-05 identifier). */
+    NIP05(5),
+
+    /** The {@code lud06} field (LNURL). */
+    LUD06(6),
+
+    /** The {@code lud16} field (Lightning address). */
+    LUD16(7),
+
+    /** The {@code website} field. */
+    WEBSITE(8);
+
+    private final int code;
+
+    ProfileField(int code) {
+        this.code = code;
+    }
+
+    /**
+     * The value passed to the native layer.
+     */
+    int code() {
+        return code;
+    }
+}
@@ -0,0 +1,96 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
+import java.util.List;
+
+/**
+ * A profile matched by a name search, with the names to show for it.
+ *
+ * @see Ndb#searchProfileNames(Transaction, String, int)
+ */
+public final class ProfileMatch {
+
+    private final byte[] pubkey;
+    private final String name;
+    private final String displayName;
+
+    private ProfileMatch(byte[] pubkey, String name, String displayName) {
+        this.pubkey = pubkey;
+        this.name = name;
+        this.displayName = displayName;
+    }
+
+    /**
+     * Get the profile's public key.
+     *
+     * @return 32-byte public key
+     */
+    public byte[] pubkey() {
+        return pubkey;
+    }
+
+    /**
+     * Get the profile's name.
+     *
+     * @return The name, or an empty string if the profile has none
+     */
+    public String name() {
+        return name;
+    }
+
+    /**
+     * Get the profile's display name.
+     *
+     * @return The display name, or an empty string if the profile has none
+     */
+    public String displayName() {
+        return displayName;
+    }
+
+    /**
+     * Get the best name to show.
+     *
+     * <p>Returns the display name if set, otherwise the name.
+     */
+    public String bestDisplayName() {
+        return displayName.isBlank() ? name : displayName;
+    }
+
+    /**
+     * Parse matches from native byte array.
+     *
+     * <p>Format: [count:4]([pubkey:32][nameLen:2][name][displayNameLen:2][displayName])*
+     */
+    static List<ProfileMatch> parseResults(byte[] data) {
+        if (data == null || data.length < 4) {
+            return List.of();
+        }
+
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+
+        List<ProfileMatch> results = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            byte[] pubkey = new byte[32];
+            buf.get(pubkey);
+            results.add(new ProfileMatch(pubkey, shortString(buf), shortString(buf)));
+        }
+
+        return results;
+    }
+
+    private static String shortString(ByteBuffer buf) {
+        byte[] utf8 = new byte[Short.toUnsignedInt(buf.getShort())];
+        buf.get(utf8);
+        return new String(utf8, StandardCharsets.UTF_8);
+    }
+
+    @Override
+    public String toString() {
+        return "ProfileMatch{pubkey=" + HexUtil.encode(pubkey) + ", name='" + name
+            + "', displayName='" + displayName + "'}";
+    }
+}
@@ -0,0 +1,72 @@
+package xyz.tcheeric.nostrdb;
+
+import java.io.Closeable;
+import java.util.List;
+import java.util.concurrent.atomic.AtomicBoolean;
+
+/**
+ * A cursor paging through the results of a query.
+ *
+ * <p>Cursors read from the transaction they were opened with, so every page
+ * comes from the same consistent snapshot. They are meant for result sets too
+ * large to fetch at once, such as exports. Close the cursor before the
+ * transaction; once the transaction is closed, {@link #next(int)} throws.
+ *
+ * <p>Example usage:
+ * <pre>{@code
+ * try (Transaction txn = ndb.beginTransaction();
+ *      QueryCursor cursor = ndb.queryCursor(txn, filter)) {
+ *     List<QueryResult> page;
+ *     while (!(page = cursor.next(500)).isEmpty()) {
+ *         export(page);
+ *     }
+ * }
+ * }</pre>
+ */
+public final class QueryCursor implements Closeable {
+
+    private final long ptr;
+    private final AtomicBoolean closed = new AtomicBoolean(false);
+
+    QueryCursor(long ptr) {
+        this.ptr = ptr;
+    }
+
+    /**
+     * Fetch the next page of results, newest first.
+     *
+     * @param batchSize Maximum number of results to return (must be positive)
+     * @return The next results, or an empty list once every result was returned
+     * @throws IllegalArgumentException if batchSize is not positive
+     * @throws IllegalStateException if the cursor or its transaction is closed
+     */
+    public List<QueryResult> next(int batchSize) {
+        checkOpen();
+        if (batchSize <= 0) {
+            throw new IllegalArgumentException("Batch size must be positive, got " + batchSize);
+        }
+        return QueryResult.parseResults(NostrdbNative.queryNext(ptr, batchSize));
+    }
+
+    /**
+     * Check if this cursor is still open.
+     *
+     * @return true if open, false if closed
+     */
+    public boolean isOpen() {
+        return !closed.get();
+    }
+
+    private void checkOpen() {
+        if (closed.get()) {
+            throw new IllegalStateException("Cursor is closed");
+        }
+    }
+
+    @Override
+    public void close() {
+        if (closed.compareAndSet(false, true)) {
+            NostrdbNative.queryEnd(ptr);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * Which matches an ordered query returns, and in what order.
+ *
+ * <p>Orders are applied natively after the limit, so "the newest N, oldest
+ * first" and "the oldest N" are distinct requests. Notes with the same
+ * {@code created_at} keep a consistent relative order.
+ *
+ * @see Ndb#queryOrdered(Transaction, Filter, int, QueryOrder)
+ */
+public enum QueryOrder {
+
+    /** The newest matches, newest first (nostrdb's natural order). */
+    NEWEST_FIRST(0),
+
+    /** The newest matches, oldest first; e.g. the latest page of a chat view. */
+    NEWEST_REVERSED(1),
+
+    /** The oldest matches, oldest first; e.g. a chronological export. */
+    OLDEST_FIRST(2);
+
+    private final int code;
+
+    QueryOrder(int code) {
+        this.code = code;
+    }
+
+    /**
+     * The value passed to the native layer.
+     */
+    int code() {
+        return code;
+    }
+}
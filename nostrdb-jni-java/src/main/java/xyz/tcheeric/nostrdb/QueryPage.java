@@ -0,0 +1,81 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.util.List;
+
+/**
+ * One page of results from {@link Ndb#queryPage(Transaction, Filter, int, byte[])}.
+ *
+ * <p>Pass {@link #nextCursor()} to the next call to get the following page.
+ * The cursor is opaque but plain data: it may be stored and used with a later
+ * transaction, e.g. for a "load older" button.
+ */
+public final class QueryPage {
+
+    private final List<QueryResult> results;
+    private final byte[] nextCursor;
+
+    private QueryPage(List<QueryResult> results, byte[] nextCursor) {
+        this.results = results;
+        this.nextCursor = nextCursor;
+    }
+
+    /**
+     * The results on this page, newest first.
+     *
+     * @return List of query results (note keys)
+     */
+    public List<QueryResult> results() {
+        return results;
+    }
+
+    /**
+     * The cursor for the following page.
+     *
+     * @return The cursor, or null if this is the last page
+     */
+    public byte[] nextCursor() {
+        return nextCursor == null ? null : nextCursor.clone();
+    }
+
+    /**
+     * Check if there may be more results after this page.
+     *
+     * <p>The page after a full page can turn out to be empty.
+     *
+     * @return true if {@link #nextCursor()} is not null
+     */
+    public boolean hasMore() {
+        return nextCursor != null;
+    }
+
+    /**
+     * Parse a page from native byte array.
+     *
+     * <p>Format: [count:4][key1:8][key2:8]...[cursor:16], without the cursor
+     * after the last page
+     */
+    static QueryPage parse(byte[] data) {
+        if (data == null || data.length < 4) {
+            return new QueryPage(List.of(), null);
+        }
+
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+
+        long[] keys = new long[count];
+        for (int i = 0; i < count; i++) {
+            keys[i] = buf.getLong();
+        }
+        List<QueryResult> results = QueryResult.fromKeys(keys);
+
+        byte[] nextCursor = null;
+        if (buf.hasRemaining()) {
+            nextCursor = new byte[buf.remaining()];
+            buf.get(nextCursor);
+        }
+
+        return new QueryPage(results, nextCursor);
+    }
+}
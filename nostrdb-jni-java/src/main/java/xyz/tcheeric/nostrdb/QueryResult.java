@@ -52,6 +52,22 @@ public final class QueryResult {
         return results;
     }
 
+    /**
+     * Wrap note keys returned by the native layer.
+     */
+    static List<QueryResult> fromKeys(long[] keys) {
+        if (keys == null) {
+            return List.of();
+        }
+
+        List<QueryResult> results = new ArrayList<>(keys.length);
+        for (long key : keys) {
+            results.add(new QueryResult(key));
+        }
+
+        return results;
+    }
+
     @Override
     public String toString() {
         return "QueryResult{noteKey=" + noteKey + '}';
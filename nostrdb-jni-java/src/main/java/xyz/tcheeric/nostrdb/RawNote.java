@@ -0,0 +1,114 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.List;
+
+/**
+ * Accessors for raw notes, as returned by {@link Ndb#getNoteRawByKey} and
+ * {@link Ndb#queryNotesRaw}.
+ *
+ * <p>A raw note is nostrdb's internal {@code struct ndb_note}, copied as
+ * stored, for callers that parse notes themselves (e.g. with a flatbuffer-style
+ * reader) instead of paying for JSON. The fixed header is:
+ *
+ * <pre>
+ * offset  size  field
+ *      0     1  version
+ *      1     3  padding
+ *      4    32  id
+ *     36    32  pubkey
+ *     68    64  sig
+ *    132     8  created_at
+ *    140     4  kind
+ * </pre>
+ *
+ * <p>followed by the packed content and tags. Numbers are in native byte order
+ * (little-endian on every supported platform). The layout belongs to nostrdb,
+ * not this library; it is covered by the native ABI version, so a JAR and
+ * native library that disagree on it fail at load time.
+ */
+public final class RawNote {
+
+    /** Offset of the 32-byte event id. */
+    public static final int ID_OFFSET = 4;
+
+    /** Offset of the 32-byte author public key. */
+    public static final int PUBKEY_OFFSET = 36;
+
+    /** Offset of the 64-byte signature. */
+    public static final int SIG_OFFSET = 68;
+
+    /** Offset of the 8-byte creation timestamp. */
+    public static final int CREATED_AT_OFFSET = 132;
+
+    /** Offset of the 4-byte kind. */
+    public static final int KIND_OFFSET = 140;
+
+    private RawNote() {
+    }
+
+    /**
+     * Get the event id of a raw note.
+     *
+     * @param raw The raw note
+     * @return 32-byte event id
+     */
+    public static byte[] id(byte[] raw) {
+        return Arrays.copyOfRange(raw, ID_OFFSET, ID_OFFSET + 32);
+    }
+
+    /**
+     * Get the author public key of a raw note.
+     *
+     * @param raw The raw note
+     * @return 32-byte public key
+     */
+    public static byte[] pubkey(byte[] raw) {
+        return Arrays.copyOfRange(raw, PUBKEY_OFFSET, PUBKEY_OFFSET + 32);
+    }
+
+    /**
+     * Get the creation timestamp of a raw note.
+     *
+     * @param raw The raw note
+     * @return Unix timestamp in seconds
+     */
+    public static long createdAt(byte[] raw) {
+        return ByteBuffer.wrap(raw).order(ByteOrder.nativeOrder()).getLong(CREATED_AT_OFFSET);
+    }
+
+    /**
+     * Get the kind of a raw note.
+     *
+     * @param raw The raw note
+     * @return Event kind
+     */
+    public static int kind(byte[] raw) {
+        return ByteBuffer.wrap(raw).order(ByteOrder.nativeOrder()).getInt(KIND_OFFSET);
+    }
+
+    /**
+     * Split native [count:4]([len:4][note:len])* data into the notes' bytes,
+     * raw notes or CBOR notes.
+     */
+    static List<byte[]> parseList(byte[] data) {
+        if (data == null || data.length < 4) {
+            return List.of();
+        }
+
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+
+        List<byte[]> notes = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            byte[] note = new byte[buf.getInt()];
+            buf.get(note);
+            notes.add(note);
+        }
+
+        return notes;
+    }
+}
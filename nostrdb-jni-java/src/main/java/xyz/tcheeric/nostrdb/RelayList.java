@@ -0,0 +1,128 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
+import java.util.Collections;
+import java.util.List;
+
+/**
+ * The relays an author reads from and writes to, from their newest relay
+ * list (NIP-65, kind 10002).
+ *
+ * <p>A relay listed without a marker is both read and write. A URL listed
+ * more than once appears once, with the union of its markers; entries with
+ * an empty URL or an unknown marker are skipped.
+ *
+ * @see Ndb#getRelayList(Transaction, byte[])
+ */
+public final class RelayList {
+
+    private static final int READ = 1;
+    private static final int WRITE = 1 << 1;
+
+    private final List<Relay> relays;
+
+    private RelayList(List<Relay> relays) {
+        this.relays = relays;
+    }
+
+    /**
+     * A relay of a relay list.
+     */
+    public static final class Relay {
+
+        private final String url;
+        private final boolean read;
+        private final boolean write;
+
+        private Relay(String url, boolean read, boolean write) {
+            this.url = url;
+            this.read = read;
+            this.write = write;
+        }
+
+        /**
+         * Get the relay URL.
+         *
+         * @return The URL as listed
+         */
+        public String url() {
+            return url;
+        }
+
+        /**
+         * Check whether the author reads from this relay (their inbox).
+         *
+         * @return true if marked read, or unmarked
+         */
+        public boolean read() {
+            return read;
+        }
+
+        /**
+         * Check whether the author writes to this relay (their outbox).
+         *
+         * @return true if marked write, or unmarked
+         */
+        public boolean write() {
+            return write;
+        }
+
+        @Override
+        public String toString() {
+            return url + (read ? write ? " (read, write)" : " (read)" : " (write)");
+        }
+    }
+
+    /**
+     * Get every relay of the list, in list order.
+     *
+     * @return Unmodifiable list of relays
+     */
+    public List<Relay> relays() {
+        return relays;
+    }
+
+    /**
+     * Get the URLs of the relays the author reads from.
+     *
+     * @return Unmodifiable list of relay URLs, in list order
+     */
+    public List<String> readRelays() {
+        return relays.stream().filter(Relay::read).map(Relay::url).toList();
+    }
+
+    /**
+     * Get the URLs of the relays the author writes to.
+     *
+     * @return Unmodifiable list of relay URLs, in list order
+     */
+    public List<String> writeRelays() {
+        return relays.stream().filter(Relay::write).map(Relay::url).toList();
+    }
+
+    /**
+     * Parse a relay list from native byte array.
+     *
+     * <p>Format: [count:4]([mode:1][len:2][url:len])*, where mode bit 0 is read and bit 1 is write
+     */
+    static RelayList parse(byte[] data) {
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+        List<Relay> relays = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            int mode = buf.get();
+            byte[] url = new byte[Short.toUnsignedInt(buf.getShort())];
+            buf.get(url);
+            relays.add(new Relay(new String(url, StandardCharsets.UTF_8), (mode & READ) != 0, (mode & WRITE) != 0));
+        }
+        return new RelayList(Collections.unmodifiableList(relays));
+    }
+
+    @Override
+    public String toString() {
+        return "RelayList{relays=" + relays + '}';
+    }
+}
@@ -0,0 +1,60 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * What happened to the lines of a batch of relay messages.
+ *
+ * @see Ndb#processRelayEvents(String)
+ */
+public final class RelayMessageCounts {
+
+    private final int events;
+    private final int skipped;
+    private final int failed;
+
+    private RelayMessageCounts(int events, int skipped, int failed) {
+        this.events = events;
+        this.skipped = skipped;
+        this.failed = failed;
+    }
+
+    /**
+     * Get the number of {@code EVENT} messages processed.
+     *
+     * @return Events handed to nostrdb
+     */
+    public int events() {
+        return events;
+    }
+
+    /**
+     * Get the number of other relay messages, such as {@code EOSE}, {@code NOTICE},
+     * and {@code OK}, which were skipped.
+     *
+     * @return Messages skipped
+     */
+    public int skipped() {
+        return skipped;
+    }
+
+    /**
+     * Get the number of lines that were not relay messages, or whose event
+     * nostrdb refused.
+     *
+     * @return Lines that failed
+     */
+    public int failed() {
+        return failed;
+    }
+
+    /**
+     * Create counts from the native [events, skipped, failed] array.
+     */
+    static RelayMessageCounts fromArray(int[] counts) {
+        return new RelayMessageCounts(counts[0], counts[1], counts[2]);
+    }
+
+    @Override
+    public String toString() {
+        return "RelayMessageCounts{events=" + events + ", skipped=" + skipped + ", failed=" + failed + '}';
+    }
+}
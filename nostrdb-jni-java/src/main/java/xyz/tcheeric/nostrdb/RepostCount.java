@@ -0,0 +1,54 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+
+/**
+ * How often a note has been reposted and quoted.
+ *
+ * @see Ndb#countReposts(Transaction, byte[])
+ */
+public final class RepostCount {
+
+    private final int reposts;
+    private final int quotes;
+
+    private RepostCount(int reposts, int quotes) {
+        this.reposts = reposts;
+        this.quotes = quotes;
+    }
+
+    /**
+     * Get the number of reposts (kind 6) of the note.
+     *
+     * @return Repost count
+     */
+    public int reposts() {
+        return reposts;
+    }
+
+    /**
+     * Get the number of text notes quoting the note, with a {@code q} tag or an
+     * {@code e} tag marked "mention".
+     *
+     * @return Quote count
+     */
+    public int quotes() {
+        return quotes;
+    }
+
+    /**
+     * Parse counts from native byte array.
+     *
+     * <p>Format: [reposts:4][quotes:4]
+     */
+    static RepostCount parse(byte[] data) {
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        return new RepostCount(buf.getInt(), buf.getInt());
+    }
+
+    @Override
+    public String toString() {
+        return "RepostCount{reposts=" + reposts + ", quotes=" + quotes + '}';
+    }
+}
@@ -0,0 +1,107 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
+import java.util.List;
+
+/**
+ * A note matched by a full-text search, with where the query matched.
+ *
+ * <p>The match is the whole query if the content contains it (ignoring case),
+ * otherwise the earliest of its words. Offsets are in bytes of the content's
+ * UTF-8 encoding, as {@link Ndb#getNoteContent(Transaction, long)} returns it;
+ * use {@link #matchIn(String)} to get the matched text from a Java string.
+ *
+ * @see Ndb#searchNotes(Transaction, String, int, SearchOrder)
+ */
+public final class SearchHit {
+
+    private final long noteKey;
+    private final long createdAt;
+    private final int matchOffset;
+    private final int matchLength;
+
+    private SearchHit(long noteKey, long createdAt, int matchOffset, int matchLength) {
+        this.noteKey = noteKey;
+        this.createdAt = createdAt;
+        this.matchOffset = matchOffset;
+        this.matchLength = matchLength;
+    }
+
+    /**
+     * Get the internal key of the note.
+     *
+     * @return The note key, for {@link Ndb#getNoteByKey(Transaction, long)}
+     */
+    public long noteKey() {
+        return noteKey;
+    }
+
+    /**
+     * Get the note's creation timestamp.
+     *
+     * @return Unix timestamp in seconds
+     */
+    public long createdAt() {
+        return createdAt;
+    }
+
+    /**
+     * Get where the match starts.
+     *
+     * @return Byte offset into the UTF-8 content
+     */
+    public int matchOffset() {
+        return matchOffset;
+    }
+
+    /**
+     * Get the length of the match.
+     *
+     * @return Length in bytes of the UTF-8 content
+     */
+    public int matchLength() {
+        return matchLength;
+    }
+
+    /**
+     * Get the matched text from the note's content.
+     *
+     * @param content The content of the note this hit refers to
+     * @return The matched text, as it appears in the content
+     * @throws IndexOutOfBoundsException if the content is too short for the match
+     */
+    public String matchIn(String content) {
+        byte[] utf8 = content.getBytes(StandardCharsets.UTF_8);
+        return new String(utf8, matchOffset, matchLength, StandardCharsets.UTF_8);
+    }
+
+    /**
+     * Parse hits from native byte array.
+     *
+     * <p>Format: [count:4]([key:8][created_at:8][offset:4][length:4])*
+     */
+    static List<SearchHit> parseResults(byte[] data) {
+        if (data == null || data.length < 4) {
+            return List.of();
+        }
+
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+
+        List<SearchHit> results = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            results.add(new SearchHit(buf.getLong(), buf.getLong(), buf.getInt(), buf.getInt()));
+        }
+
+        return results;
+    }
+
+    @Override
+    public String toString() {
+        return "SearchHit{noteKey=" + noteKey + ", createdAt=" + createdAt
+            + ", matchOffset=" + matchOffset + ", matchLength=" + matchLength + '}';
+    }
+}
@@ -0,0 +1,29 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * How {@link Ndb#searchNotes(Transaction, String, int, SearchOrder)} ranks its hits.
+ */
+public enum SearchOrder {
+
+    /** The newest hits, newest first. */
+    NEWEST_FIRST(0),
+
+    /**
+     * The hits whose content mentions the query's words most often, ties newest
+     * first. Every match has to be ranked, so this is slower for common terms.
+     */
+    RELEVANCE(1);
+
+    private final int code;
+
+    SearchOrder(int code) {
+        this.code = code;
+    }
+
+    /**
+     * The value passed to the native layer.
+     */
+    int code() {
+        return code;
+    }
+}
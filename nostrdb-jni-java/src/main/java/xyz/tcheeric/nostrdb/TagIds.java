@@ -0,0 +1,66 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.util.ArrayList;
+import java.util.Collections;
+import java.util.List;
+
+/**
+ * The 32-byte values of one kind of a note's tags, such as the pubkeys its
+ * {@code p} tags mention.
+ *
+ * @see Ndb#getNoteTagIds(Transaction, long, String)
+ */
+public final class TagIds {
+
+    private final List<byte[]> ids;
+    private final int skipped;
+
+    private TagIds(List<byte[]> ids, int skipped) {
+        this.ids = ids;
+        this.skipped = skipped;
+    }
+
+    /**
+     * Get the referenced event ids or pubkeys, in tag order.
+     *
+     * @return Unmodifiable list of 32-byte values
+     */
+    public List<byte[]> ids() {
+        return ids;
+    }
+
+    /**
+     * Get the number of matching tags whose value is not a 32-byte id, e.g.
+     * a truncated hex pubkey, a {@code q} tag naming an address, or a tag with
+     * no value.
+     *
+     * @return Number of tags skipped
+     */
+    public int skipped() {
+        return skipped;
+    }
+
+    /**
+     * Parse ids from native byte array.
+     *
+     * <p>Format: [count:4]([id:32])*[skipped:4]
+     */
+    static TagIds parse(byte[] data) {
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        int count = buf.getInt();
+        List<byte[]> ids = new ArrayList<>(count);
+        for (int i = 0; i < count; i++) {
+            byte[] id = new byte[32];
+            buf.get(id);
+            ids.add(id);
+        }
+        return new TagIds(Collections.unmodifiableList(ids), buf.getInt());
+    }
+
+    @Override
+    public String toString() {
+        return "TagIds{ids=" + ids.size() + ", skipped=" + skipped + '}';
+    }
+}
@@ -0,0 +1,73 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.util.List;
+
+/**
+ * The replies in a thread, split by the event each one replies to (NIP-10).
+ *
+ * <p>A reply with marked {@code e} tags replies to its "reply" tag, or to its
+ * "root" tag if it has none. A reply with only unmarked tags follows the older
+ * positional convention and replies to its last {@code e} tag.
+ *
+ * @see Ndb#queryThread(Transaction, byte[], int)
+ */
+public final class ThreadReplies {
+
+    private final List<QueryResult> direct;
+    private final List<QueryResult> nested;
+
+    private ThreadReplies(List<QueryResult> direct, List<QueryResult> nested) {
+        this.direct = direct;
+        this.nested = nested;
+    }
+
+    /**
+     * The replies to the root itself.
+     *
+     * @return Note keys, newest first
+     */
+    public List<QueryResult> direct() {
+        return direct;
+    }
+
+    /**
+     * The other notes referencing the root: replies to replies, and notes
+     * that only mention it.
+     *
+     * @return Note keys, newest first
+     */
+    public List<QueryResult> nested() {
+        return nested;
+    }
+
+    /**
+     * Parse replies from native byte array.
+     *
+     * <p>Format: [directCount:4][key:8]*[otherCount:4][key:8]*
+     */
+    static ThreadReplies parse(byte[] data) {
+        if (data == null || data.length < 8) {
+            return new ThreadReplies(List.of(), List.of());
+        }
+
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        List<QueryResult> direct = readKeys(buf);
+        List<QueryResult> nested = readKeys(buf);
+        return new ThreadReplies(direct, nested);
+    }
+
+    private static List<QueryResult> readKeys(ByteBuffer buf) {
+        long[] keys = new long[buf.getInt()];
+        for (int i = 0; i < keys.length; i++) {
+            keys[i] = buf.getLong();
+        }
+        return QueryResult.fromKeys(keys);
+    }
+
+    @Override
+    public String toString() {
+        return "ThreadReplies{direct=" + direct.size() + ", nested=" + nested.size() + '}';
+    }
+}
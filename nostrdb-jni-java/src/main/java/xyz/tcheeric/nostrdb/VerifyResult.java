@@ -0,0 +1,49 @@
+package xyz.tcheeric.nostrdb;
+
+/**
+ * Outcome of verifying an event's id and signature.
+ *
+ * @see Ndb#verifyEvent(String)
+ * @see Ndb#verifyNoteById(Transaction, byte[])
+ */
+public enum VerifyResult {
+
+    /** The id matches the event and the signature verifies. */
+    OK(0),
+
+    /** The {@code id} field is not the hash of the event's content. */
+    BAD_ID(1),
+
+    /** The signature does not verify for the id and pubkey. */
+    BAD_SIG(2),
+
+    /** The event is not a well-formed NIP-01 event. */
+    MALFORMED(3);
+
+    private final int code;
+
+    VerifyResult(int code) {
+        this.code = code;
+    }
+
+    /**
+     * Check if the event is valid.
+     *
+     * @return true for {@link #OK}
+     */
+    public boolean isValid() {
+        return this == OK;
+    }
+
+    /**
+     * Map a native result code to a result.
+     */
+    static VerifyResult fromCode(int code) {
+        for (VerifyResult result : values()) {
+            if (result.code == code) {
+                return result;
+            }
+        }
+        throw new IllegalStateException("Unknown verification result: " + code);
+    }
+}
@@ -0,0 +1,79 @@
+package xyz.tcheeric.nostrdb;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+
+/**
+ * The zaps a note has received, totalled from its NIP-57 zap receipts.
+ *
+ * <p>A receipt's amount is the {@code amount} tag of the zap request in its
+ * {@code description} tag or, failing that, the amount of its {@code bolt11}
+ * invoice. Receipts with neither are not in the total, and are counted in
+ * {@link #unparsableCount()} instead.
+ *
+ * @see Ndb#sumZaps(Transaction, byte[])
+ */
+public final class ZapTotal {
+
+    private final long totalMillisats;
+    private final int count;
+    private final int unparsableCount;
+
+    private ZapTotal(long totalMillisats, int count, int unparsableCount) {
+        this.totalMillisats = totalMillisats;
+        this.count = count;
+        this.unparsableCount = unparsableCount;
+    }
+
+    /**
+     * Get the total amount zapped.
+     *
+     * @return Millisats, summed over the counted receipts
+     */
+    public long totalMillisats() {
+        return totalMillisats;
+    }
+
+    /**
+     * Get the total amount zapped in whole sats.
+     *
+     * @return Sats, rounded down
+     */
+    public long totalSats() {
+        return totalMillisats / 1000;
+    }
+
+    /**
+     * Get the number of receipts in the total.
+     *
+     * @return Receipt count
+     */
+    public int count() {
+        return count;
+    }
+
+    /**
+     * Get the number of receipts whose amount couldn't be read.
+     *
+     * @return Receipt count
+     */
+    public int unparsableCount() {
+        return unparsableCount;
+    }
+
+    /**
+     * Parse a total from native byte array.
+     *
+     * <p>Format: [millisats:8][count:4][unparsable:4]
+     */
+    static ZapTotal parse(byte[] data) {
+        ByteBuffer buf = ByteBuffer.wrap(data).order(ByteOrder.LITTLE_ENDIAN);
+        return new ZapTotal(buf.getLong(), buf.getInt(), buf.getInt());
+    }
+
+    @Override
+    public String toString() {
+        return "ZapTotal{totalMillisats=" + totalMillisats + ", count=" + count
+            + ", unparsableCount=" + unparsableCount + '}';
+    }
+}
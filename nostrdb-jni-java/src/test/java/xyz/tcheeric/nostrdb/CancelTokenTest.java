@@ -0,0 +1,152 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.HashSet;
+import java.util.List;
+import java.util.concurrent.CompletableFuture;
+import java.util.concurrent.TimeUnit;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for cancellable queries.
+ */
+class CancelTokenTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static final int NOTES = 20_000;
+
+    /**
+     * An unsigned note with a unique id, stored with validation skipped so the
+     * large fixture set is quick to build.
+     */
+    private String unsignedNote(int i) {
+        return "{\"id\":\"" + String.format("%064x", i + 1) + "\",\"pubkey\":\"" + alice.pubkeyHex()
+            + "\",\"created_at\":" + (1_700_000_000L + i / 3) + ",\"kind\":1,\"tags\":[],"
+            + "\"content\":\"note " + i + " " + "x".repeat(200) + "\",\"sig\":\"" + "0".repeat(128) + "\"}";
+    }
+
+    private Ndb openWithNotes(NdbConfig config, Filter notes) throws Exception {
+        Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+        for (int i = 0; i < NOTES; i++) {
+            ndb.processEvent(unsignedNote(i));
+        }
+        assertEquals(NOTES, TestEvents.awaitCount(ndb, notes, NOTES, 60_000));
+        return ndb;
+    }
+
+    @Test
+    @DisplayName("Should return every match when the token is never cancelled")
+    void testComplete() throws Exception {
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter limited = Filter.builder().kinds(1).limit(300).build();
+             Ndb ndb = openWithNotes(config, notes);
+             Transaction txn = ndb.beginTransaction();
+             CancelToken token = new CancelToken()) {
+            PartialResult<QueryResult> all = ndb.queryCancellable(txn, notes, Ndb.FILTER_LIMIT, token);
+            assertFalse(all.isCancelled());
+            assertEquals(NOTES, all.results().size());
+            assertEquals(new HashSet<>(ndb.query(txn, notes, Ndb.FILTER_LIMIT)), new HashSet<>(all.results()));
+
+            // Limits combine as for query, and the newest matches come first; 999 ends
+            // on a timestamp boundary, so tie order can't change which notes are in
+            List<QueryResult> newest = ndb.query(txn, notes, 999);
+            assertEquals(new HashSet<>(newest), new HashSet<>(ndb.queryCancellable(txn, notes, 999, token).results()));
+            assertEquals(300, ndb.queryCancellable(txn, limited, Ndb.FILTER_LIMIT, token).results().size());
+            assertEquals(300, ndb.queryCancellable(txn, limited, 500, token).results().size());
+
+            PartialResult<Note> some = ndb.queryNotesCancellable(txn, notes, 700, token);
+            assertFalse(some.isCancelled());
+            assertEquals(700, some.results().size());
+            for (int i = 1; i < some.results().size(); i++) {
+                assertTrue(some.results().get(i - 1).createdAt() >= some.results().get(i).createdAt());
+            }
+            assertFalse(token.isCancelled());
+        }
+    }
+
+    @Test
+    @DisplayName("A cancelled token should stop queries before they fetch anything")
+    void testAlreadyCancelled() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            for (int i = 0; i < 5; i++) {
+                ndb.processEvent(alice.textNote(1_700_000_000L + i, "note " + i));
+            }
+            assertEquals(5, TestEvents.awaitCount(ndb, notes, 5, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction();
+                 CancelToken token = new CancelToken()) {
+                token.cancel();
+                token.cancel();
+                assertTrue(token.isCancelled());
+
+                PartialResult<QueryResult> keys = ndb.queryCancellable(txn, notes, 10, token);
+                assertTrue(keys.isCancelled());
+                assertTrue(keys.results().isEmpty());
+                PartialResult<Note> full = ndb.queryNotesCancellable(txn, notes, 10, token);
+                assertTrue(full.isCancelled());
+                assertTrue(full.results().isEmpty());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Cancelling from another thread should make a large query return promptly")
+    void testCancelMidIteration() throws Exception {
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Filter notes = Filter.builder().kinds(1).build();
+             Ndb ndb = openWithNotes(config, notes);
+             Transaction txn = ndb.beginTransaction()) {
+            for (long delayMs : new long[] {1, 5, 20}) {
+                try (CancelToken token = new CancelToken()) {
+                    CompletableFuture<PartialResult<Note>> query = CompletableFuture.supplyAsync(() ->
+                        ndb.queryNotesCancellable(txn, notes, Ndb.FILTER_LIMIT, token));
+                    Thread.sleep(delayMs);
+                    token.cancel();
+                    long cancelledAt = System.nanoTime();
+
+                    PartialResult<Note> result = query.get(10, TimeUnit.SECONDS);
+                    long returnedMs = TimeUnit.NANOSECONDS.toMillis(System.nanoTime() - cancelledAt);
+                    if (result.isCancelled()) {
+                        // One page of work at most after the flag is set
+                        assertTrue(returnedMs < 1000, "returned " + returnedMs + "ms after cancel");
+                    } else {
+                        // The query beat the cancel; it must then be complete
+                        assertEquals(NOTES, result.results().size());
+                    }
+                    assertEquals(result.results().size(),
+                        result.results().stream().map(Note::id).distinct().count());
+                }
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Closed tokens should reject use but tolerate cancel")
+    void testClosed() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Transaction txn = ndb.beginTransaction()) {
+            CancelToken token = new CancelToken();
+            token.close();
+            token.close();
+            token.cancel();
+            assertThrows(IllegalStateException.class, token::isCancelled);
+            assertThrows(IllegalStateException.class, () -> ndb.queryCancellable(txn, notes, 10, token));
+            assertThrows(IllegalArgumentException.class, () -> {
+                try (CancelToken open = new CancelToken()) {
+                    ndb.queryCancellable(txn, notes, 0, open);
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,70 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for ingesting client EVENT messages.
+ */
+class ClientEventTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("A client EVENT message should be ingested")
+    void testClientMessage() throws Exception {
+        String event = alice.textNote(T, "from a client");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processClientEvent("[\"EVENT\"," + event + "]");
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals("from a client", ndb.getNoteById(txn, TestEvents.idOf(event)).orElseThrow().content());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A bare event should be refused unless bare events are accepted")
+    void testBareEvent() throws Exception {
+        String event = alice.textNote(T, "bare");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            NostrdbException e = assertThrows(NostrdbException.class, () -> ndb.processClientEvent(event));
+            assertTrue(e.getMessage().contains("bare event"), e.getMessage());
+
+            ndb.processClientEvent(event, true);
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+        }
+    }
+
+    @Test
+    @DisplayName("Malformed envelopes should throw naming what is wrong")
+    void testGarbage() {
+        String event = alice.textNote(T, "hello");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            assertMessage(ndb, "not json", "not valid JSON");
+            assertMessage(ndb, "42", "JSON array");
+            assertMessage(ndb, "[]", "empty");
+            assertMessage(ndb, "[1, {}]", "string");
+            assertMessage(ndb, "[\"REQ\", \"sub\", {}]", "REQ");
+            assertMessage(ndb, "[\"EVENT\", \"sub\", " + event + "]", "2 elements");
+            assertMessage(ndb, "[\"EVENT\", \"event\"]", "JSON object");
+        }
+    }
+
+    private static void assertMessage(Ndb ndb, String json, String expected) {
+        NostrdbException e = assertThrows(NostrdbException.class, () -> ndb.processClientEvent(json, true));
+        assertTrue(e.getMessage().contains(expected), e.getMessage());
+    }
+}
@@ -0,0 +1,63 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for computing event ids natively.
+ */
+class ComputeEventIdTest {
+
+    private static final String PUBKEY = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("Ids should match known vectors, including unicode, quotes, newlines, and control characters")
+    void testVectors() {
+        String empty = "{\"pubkey\":\"" + PUBKEY + "\",\"created_at\":1700000000,\"kind\":1,\"tags\":[],\"content\":\"\"}";
+        assertEquals("1868e8ad4ca66b7a9bb6ddaaecde6e5cc5d11682e87abb650a6ce8853854ef05",
+            HexUtil.encode(Ndb.computeEventId(empty)));
+
+        // Content with an accented letter, an astral emoji, quotes, a newline, a tab, a backslash,
+        // a control character, and an escaped slash
+        String expected = "47ec08a5d639f44c0360012f3f882382b9841a8fafd342c2c15528481eddd445";
+        String tags = "[[\"t\",\"nostr\"],[\"e\",\"" + "ff".repeat(32) + "\",\"\",\"root\"]]";
+        String escaped = "{\"pubkey\":\"" + PUBKEY + "\",\"created_at\":1700000000,\"kind\":1,\"tags\":" + tags
+            + ",\"content\":\"caf\\u00e9 \\ud83d\\ude80 \\\"quoted\\\"\\nline\\ttab \\\\ back\\u0001slash <\\/script>\"}";
+        assertEquals(expected, HexUtil.encode(Ndb.computeEventId(escaped)));
+
+        // Raw UTF-8, other field order, and id/sig fields give the same id
+        String raw = "{\"content\":\"caf\u00e9 \ud83d\ude80 \\\"quoted\\\"\\nline\\ttab \\\\ back\\u0001slash </script>\","
+            + "\"tags\":" + tags + ",\"kind\":1,\"created_at\":1700000000,\"pubkey\":\"" + PUBKEY + "\","
+            + "\"id\":\"" + "00".repeat(32) + "\",\"sig\":\"bogus\"}";
+        assertEquals(expected, HexUtil.encode(Ndb.computeEventId(raw)));
+    }
+
+    @Test
+    @DisplayName("Ids should match those of signed events")
+    void testSignedEvents() {
+        String[] events = {
+            alice.textNote(1_700_000_000L, "plain"),
+            alice.event(1, 1_700_000_001L, "line\nbreak \"quote\" \\ caf\u00e9 \ud83d\ude80 \u0007",
+                new String[] {"t", "x"}, new String[] {"p", alice.pubkeyHex(), "wss://relay.example/"}),
+            alice.event(30023, 1_700_000_002L, "", new String[] {"d", ""}),
+        };
+        for (String event : events) {
+            assertEquals(TestEvents.idOf(event), HexUtil.encode(Ndb.computeEventId(event)));
+        }
+    }
+
+    @Test
+    @DisplayName("Missing or mistyped fields should throw")
+    void testInvalid() {
+        assertThrows(IllegalArgumentException.class, () -> Ndb.computeEventId(null));
+        assertThrows(IllegalArgumentException.class, () -> Ndb.computeEventId("not json"));
+        assertThrows(IllegalArgumentException.class, () -> Ndb.computeEventId("{}"));
+        assertThrows(IllegalArgumentException.class, () -> Ndb.computeEventId(
+            "{\"pubkey\":\"" + PUBKEY + "\",\"created_at\":-1,\"kind\":1,\"tags\":[],\"content\":\"\"}"));
+        assertThrows(IllegalArgumentException.class, () -> Ndb.computeEventId(
+            "{\"pubkey\":\"abcd\",\"created_at\":1,\"kind\":1,\"tags\":[],\"content\":\"\"}"));
+    }
+}
@@ -0,0 +1,401 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.condition.EnabledOnOs;
+import org.junit.jupiter.api.condition.OS;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.io.IOException;
+import java.nio.file.Files;
+import java.nio.file.Path;
+import java.util.HashSet;
+import java.util.List;
+import java.util.Set;
+import java.util.concurrent.CopyOnWriteArrayList;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for opening databases with an explicit {@link NdbConfig}.
+ */
+class ConfigTest {
+
+    @TempDir
+    Path tempDir;
+
+    @Test
+    @DisplayName("Should open a database with a config handle")
+    void testOpenWithConfig() {
+        try (NdbConfig config = new NdbConfig();
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config)) {
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertTrue(txn.isOpen());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A non-default config should take effect and be what the database reports running with")
+    void testNonDefaultConfigReported() throws InterruptedException {
+        TestEvents author = TestEvents.forSeed(4);
+        String broken = brokenSignatureNote(author, 1_700_000_000L);
+        String padding = " lorem ipsum dolor sit amet".repeat(150);
+        int attempted = 400;
+        long mapSize = 1024 * 1024;
+        int flags = NdbConfig.NO_FULLTEXT | NdbConfig.NO_STATS;
+        try (NdbConfig config = new NdbConfig().mapSize(mapSize).flags(flags).ingesterThreads(2)
+                 .skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            // nostrdb stores an event that does not verify only if it skips validation
+            ndb.processEvent(broken);
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            // LMDB enforces the map size: a 1 MiB map cannot hold 400 notes of 4 KiB
+            for (int i = 1; i <= attempted; i++) {
+                try {
+                    ndb.processEvent(author.textNote(1_700_000_000L + i, "note " + i + padding));
+                } catch (NostrdbException e) {
+                    // Rejections once the map is full are expected
+                }
+            }
+            int stored = TestEvents.awaitStable(ndb, notes, 1_000, 30_000);
+            assertTrue(stored < attempted, "Stored " + stored + " notes in a 1 MiB map");
+
+            NdbSettings settings = ndb.settings();
+            assertEquals(mapSize, settings.mapSize());
+            assertEquals(flags | NdbConfig.SKIP_NOTE_VERIFY, settings.flags());
+            assertEquals(2, settings.ingesterThreads());
+            assertEquals(0, settings.autoResizeMax());
+
+            String description = ndb.toString();
+            assertTrue(description.contains("mapSize=" + mapSize), description);
+            assertTrue(description.contains("flags=0x" + Integer.toHexString(settings.flags())), description);
+            assertTrue(description.contains("ingesterThreads=2"), description);
+        }
+
+        // Defaults, for contrast
+        try (Ndb ndb = Ndb.open(tempDir.resolve("default"))) {
+            NdbSettings settings = ndb.settings();
+            assertEquals(32L * 1024 * 1024 * 1024, settings.mapSize());
+            assertEquals(0, settings.flags());
+            assertEquals(0, settings.ingesterThreads());
+        }
+    }
+
+    @Test
+    @DisplayName("A config should be reusable across opens")
+    void testConfigReuse() {
+        try (NdbConfig config = new NdbConfig()) {
+            try (Ndb first = Ndb.open(tempDir.resolve("first"), config)) {
+                assertNotNull(first);
+            }
+            try (Ndb second = Ndb.open(tempDir.resolve("second"), config)) {
+                assertNotNull(second);
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A closed config should be rejected")
+    void testClosedConfig() {
+        NdbConfig config = new NdbConfig();
+        config.close();
+        config.close(); // idempotent
+
+        assertThrows(IllegalStateException.class, () -> Ndb.open(tempDir.resolve("db"), config));
+    }
+
+    @Test
+    @DisplayName("Should reject non-positive map sizes")
+    void testInvalidMapSize() {
+        try (NdbConfig config = new NdbConfig()) {
+            assertThrows(IllegalArgumentException.class, () -> config.mapSize(0));
+            assertThrows(IllegalArgumentException.class, () -> config.mapSize(-1));
+        }
+    }
+
+    @Test
+    @DisplayName("A full map should stop ingestion until reopened with a larger map")
+    void testMapSizeLimitAndResize() throws InterruptedException {
+        TestEvents author = TestEvents.forSeed(1);
+        String padding = " lorem ipsum dolor sit amet".repeat(150);
+        Path dbPath = tempDir.resolve("db");
+        int attempted = 400;
+        int stored;
+
+        try (NdbConfig config = new NdbConfig().mapSize(1024 * 1024);
+             Ndb ndb = Ndb.open(dbPath, config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            for (int i = 0; i < attempted; i++) {
+                try {
+                    ndb.processEvent(author.textNote(1_700_000_000L + i, "note " + i + padding));
+                } catch (NostrdbException e) {
+                    // Rejections once the map is full are expected
+                }
+            }
+            stored = TestEvents.awaitStable(ndb, notes, 1_000, 30_000);
+            assertTrue(stored < attempted, "A 1 MiB map cannot hold " + attempted + " notes of 4 KiB");
+        }
+
+        try (NdbConfig config = new NdbConfig().mapSize(64L * 1024 * 1024);
+             Ndb ndb = Ndb.open(dbPath, config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(author.textNote(1_800_000_000L, "after resize"));
+            assertEquals(stored + 1, TestEvents.awaitCount(ndb, notes, stored + 1, 10_000),
+                "Ingestion should resume after reopening with a larger map");
+        }
+    }
+
+    @Test
+    @DisplayName("Should reject non-positive ingester thread counts")
+    void testInvalidIngesterThreads() {
+        try (NdbConfig config = new NdbConfig()) {
+            assertThrows(IllegalArgumentException.class, () -> config.ingesterThreads(0));
+            assertThrows(IllegalArgumentException.class, () -> config.ingesterThreads(-4));
+        }
+    }
+
+    @Test
+    @DisplayName("Bulk ingestion should complete with 1 and 4 ingester threads")
+    void testIngesterThreads() throws InterruptedException {
+        TestEvents author = TestEvents.forSeed(2);
+        int eventCount = 3_000;
+        StringBuilder ldjson = new StringBuilder();
+        for (int i = 0; i < eventCount; i++) {
+            ldjson.append(author.textNote(1_700_000_000L + i, "bulk note " + i)).append('\n');
+        }
+
+        for (int threads : new int[] {1, 4}) {
+            try (NdbConfig config = new NdbConfig().ingesterThreads(threads);
+                 Ndb ndb = Ndb.open(tempDir.resolve("db-" + threads), config);
+                 Filter notes = Filter.builder().kinds(1).build()) {
+                assertEquals(threads, ndb.settings().ingesterThreads());
+                ndb.processEvents(ldjson.toString());
+                assertEquals(eventCount, TestEvents.awaitCount(ndb, notes, eventCount, 60_000));
+            }
+        }
+    }
+
+    /** The number of threads in this process, from /proc. */
+    private static int processThreads() throws IOException {
+        for (String line : Files.readAllLines(Path.of("/proc/self/status"))) {
+            if (line.startsWith("Threads:")) {
+                return Integer.parseInt(line.substring("Threads:".length()).trim());
+            }
+        }
+        throw new IllegalStateException("No thread count in /proc/self/status");
+    }
+
+    /** How many threads opening a database with the given ingester thread count starts. */
+    private static int threadsStarted(Path dbPath, int ingesterThreads) throws IOException {
+        try (NdbConfig config = new NdbConfig().ingesterThreads(ingesterThreads)) {
+            int before = processThreads();
+            try (Ndb ndb = Ndb.open(dbPath, config)) {
+                int started = processThreads() - before;
+                assertEquals(ingesterThreads, ndb.settings().ingesterThreads());
+                return started;
+            }
+        }
+    }
+
+    @Test
+    @EnabledOnOs(OS.LINUX)
+    @DisplayName("The ingester thread count should set how many threads nostrdb starts")
+    void testIngesterThreadsStarted() throws IOException {
+        // JVM threads may start or stop meanwhile, so allow a few tries
+        int one = 0;
+        int four = 0;
+        for (int attempt = 0; attempt < 5 && four - one != 3; attempt++) {
+            one = threadsStarted(tempDir.resolve("one-" + attempt), 1);
+            four = threadsStarted(tempDir.resolve("four-" + attempt), 4);
+        }
+        assertEquals(3, four - one, "Threads started with 1 ingester: " + one + ", with 4: " + four);
+    }
+
+    /** A signed note whose signature has been corrupted. */
+    private static String brokenSignatureNote(TestEvents author, long createdAt) {
+        String json = author.textNote(createdAt, "tampered");
+        int sig = json.indexOf("\"sig\":\"") + 7;
+        char flipped = json.charAt(sig) == '0' ? '1' : '0';
+        return json.substring(0, sig) + flipped + json.substring(sig + 1);
+    }
+
+    @Test
+    @DisplayName("Events with a broken signature should be rejected by default")
+    void testValidationEnabled() throws InterruptedException {
+        TestEvents author = TestEvents.forSeed(3);
+        String broken = brokenSignatureNote(author, 1_700_000_000L);
+        String valid = author.textNote(1_700_000_001L, "valid");
+
+        try (NdbConfig config = new NdbConfig().ingesterThreads(1);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(broken);
+            ndb.processEvent(valid);
+
+            // Once the later valid event is visible, the broken one has been processed
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertTrue(ndb.getNoteById(txn, TestEvents.idOf(broken)).isEmpty());
+                assertTrue(ndb.getNoteById(txn, TestEvents.idOf(valid)).isPresent());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Events with a broken signature should be stored when validation is skipped")
+    void testSkipValidation() throws InterruptedException {
+        TestEvents author = TestEvents.forSeed(3);
+        String broken = brokenSignatureNote(author, 1_700_000_000L);
+
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(broken);
+
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertTrue(ndb.getNoteById(txn, TestEvents.idOf(broken)).isPresent());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Should reject unknown config flags")
+    void testUnknownFlags() {
+        try (NdbConfig config = new NdbConfig()) {
+            assertThrows(IllegalArgumentException.class, () -> config.flags(1 << 10));
+            assertThrows(IllegalArgumentException.class, () -> config.flags(NdbConfig.NO_FULLTEXT | 1 << 31));
+
+            config.flags(NdbConfig.NO_MIGRATE | NdbConfig.SKIP_NOTE_VERIFY | NdbConfig.NO_FULLTEXT
+                | NdbConfig.NO_NOTE_BLOCKS | NdbConfig.NO_STATS);
+            config.flags(0);
+        }
+    }
+
+    @Test
+    @DisplayName("Search should find nothing, without crashing, when fulltext is disabled")
+    void testNoFulltext() throws InterruptedException {
+        TestEvents author = TestEvents.forSeed(4);
+        String note = author.textNote(1_700_000_000L, "the quick brown fox");
+
+        for (boolean fulltext : new boolean[] {true, false}) {
+            try (NdbConfig config = new NdbConfig().flags(fulltext ? 0 : NdbConfig.NO_FULLTEXT);
+                 Ndb ndb = Ndb.open(tempDir.resolve("db-" + fulltext), config);
+                 Filter notes = Filter.builder().kinds(1).build();
+                 Filter search = Filter.builder().search("brown").build()) {
+                ndb.processEvent(note);
+                assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+                List<QueryResult> results;
+                try (Transaction txn = ndb.beginTransaction()) {
+                    results = ndb.query(txn, search, 10);
+                } catch (NostrdbException e) {
+                    results = List.of();
+                }
+                assertEquals(fulltext ? 1 : 0, results.size(),
+                    fulltext ? "Fulltext search should find the note" : "Search without an index should be empty");
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Auto-resize should grow a tiny map so ingestion and subscriptions continue")
+    void testAutoResize() throws Exception {
+        TestEvents author = TestEvents.forSeed(6);
+        Path dbPath = tempDir.resolve("db");
+        long initialMap = 1024 * 1024;
+        int batches = 30;
+        int batchSize = 20;
+
+        try (NdbConfig config = new NdbConfig().mapSize(initialMap).autoResize(64L * 1024 * 1024);
+             Ndb ndb = Ndb.open(dbPath, config);
+             Filter notes = Filter.builder().kinds(1).build();
+             Subscription sub = ndb.subscribe(notes)) {
+            Set<Long> matched = new HashSet<>();
+            int total = 0;
+            for (int b = 0; b < batches; b++) {
+                StringBuilder batch = new StringBuilder();
+                for (int i = 0; i < batchSize; i++) {
+                    int n = b * batchSize + i;
+                    batch.append(author.textNote(1_700_000_000L + n, "note " + n + " " + "x".repeat(2000)))
+                        .append('\n');
+                }
+                ndb.processEvents(batch.toString());
+                total += batchSize;
+                assertEquals(total, TestEvents.awaitCount(ndb, notes, total, 10_000),
+                    "Batch " + b + " should be stored");
+                matched.addAll(sub.poll(batchSize));
+            }
+
+            long deadline = System.currentTimeMillis() + 10_000;
+            while (matched.size() < total && System.currentTimeMillis() < deadline) {
+                matched.addAll(sub.poll(100));
+                Thread.sleep(10);
+            }
+            assertEquals(total, matched.size(), "The subscription should match every note across resizes");
+            assertTrue(ndb.settings().mapSize() > initialMap, "The reported map size should have grown");
+        }
+
+        assertTrue(Files.size(dbPath.resolve("data.mdb")) > initialMap, "The map should have grown");
+    }
+
+    @Test
+    @DisplayName("An open transaction should hold writes back on a full map until it ends, losing no events")
+    void testAutoResizeStalledByTransaction() throws Exception {
+        TestEvents author = TestEvents.forSeed(7);
+        long initialMap = 1024 * 1024;
+        List<Integer> mapEvents = new CopyOnWriteArrayList<>();
+
+        try (NdbConfig config = new NdbConfig().mapSize(initialMap).autoResize(64L * 1024 * 1024);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.setHealthCallback((code, value) -> {
+                if (code <= HealthCallback.MAP_RECOVERED) {
+                    mapEvents.add(code);
+                }
+            }, 10);
+
+            int written = 0;
+            try (Transaction txn = ndb.beginTransaction()) {
+                // Keep writing for a few seconds past the full map, well beyond the
+                // time the resize waits before holding writes back
+                int afterFull = 0;
+                for (int n = 0; n < 1000 && afterFull < 150; n++) {
+                    ndb.processEvent(author.textNote(1_700_000_000L + n, "x".repeat(16 * 1024)));
+                    written++;
+                    if (mapEvents.contains(HealthCallback.MAP_FULL)) {
+                        afterFull++;
+                    }
+                    Thread.sleep(20);
+                }
+                assertTrue(mapEvents.contains(HealthCallback.MAP_FULL), "Health should report the full map: " + mapEvents);
+                assertEquals(initialMap, ndb.settings().mapSize(), "The transaction should hold up the resize");
+                assertTrue(txn.isOpen());
+            }
+
+            assertEquals(written, TestEvents.awaitCount(ndb, notes, written, 30_000),
+                "Every event written, including those held back, should be stored once the map has grown");
+            assertTrue(ndb.settings().mapSize() > initialMap, "The map should have grown");
+            ndb.processEvent(author.textNote(1_600_000_000L, "after the resize"));
+            assertEquals(written + 1, TestEvents.awaitCount(ndb, notes, written + 1, 10_000));
+
+            long deadline = System.currentTimeMillis() + 5_000;
+            while (!mapEvents.contains(HealthCallback.MAP_RECOVERED) && System.currentTimeMillis() < deadline) {
+                Thread.sleep(10);
+            }
+            assertEquals(HealthCallback.MAP_RECOVERED, (int) mapEvents.get(mapEvents.size() - 1),
+                "Health should report recovery after the resize: " + mapEvents);
+        }
+    }
+
+    @Test
+    @DisplayName("Should reject a negative auto-resize maximum")
+    void testInvalidAutoResize() {
+        try (NdbConfig config = new NdbConfig()) {
+            assertThrows(IllegalArgumentException.class, () -> config.autoResize(-1));
+            config.autoResize(0);
+        }
+    }
+}
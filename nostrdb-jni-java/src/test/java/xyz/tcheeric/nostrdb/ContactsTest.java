@@ -0,0 +1,61 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for reading an author's contact list.
+ */
+class ContactsTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+    private final TestEvents carol = TestEvents.forSeed(13);
+    private final TestEvents dave = TestEvents.forSeed(14);
+
+    @Test
+    @DisplayName("The newest contact list should be parsed in order, without duplicates or malformed pubkeys")
+    void testContacts() throws Exception {
+        String older = alice.event(3, T, "", new String[] {"p", dave.pubkeyHex()});
+        String newest = alice.event(3, T + 10, "",
+            new String[] {"p", bob.pubkeyHex(), "wss://bob.example.com", "bob"},
+            new String[] {"t", "ignored"},
+            new String[] {"p", carol.pubkeyHex()},
+            new String[] {"p", bob.pubkeyHex(), "wss://other.example.com"},
+            new String[] {"p", dave.pubkeyHex().substring(2)},
+            new String[] {"p", "not hex"},
+            new String[] {"p"});
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter lists = Filter.builder().kinds(3).build()) {
+            ndb.processEvent(older);
+            ndb.processEvent(newest);
+            assertEquals(2, TestEvents.awaitCount(ndb, lists, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                byte[] author = HexUtil.decode(alice.pubkeyHex());
+
+                ContactList contacts = ndb.getContacts(txn, author).orElseThrow();
+                assertEquals(List.of(bob.pubkeyHex(), carol.pubkeyHex()),
+                    contacts.pubkeys().stream().map(HexUtil::encode).toList());
+                assertTrue(contacts.relayHints().isEmpty());
+
+                ContactList withHints = ndb.getContacts(txn, author, true).orElseThrow();
+                assertEquals(contacts.pubkeys().size(), withHints.pubkeys().size());
+                assertEquals(List.of("wss://bob.example.com", ""), withHints.relayHints());
+
+                assertTrue(ndb.getContacts(txn, HexUtil.decode(bob.pubkeyHex())).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.getContacts(txn, new byte[31]));
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for nostrdb's parse of note content into blocks.
+ */
+class ContentBlocksTest {
+
+    private static final String NPUB = "npub1sg6plzptd64u62a878hep2kev88swjh3tw00gjsfl8f237lmu63q0uf63m";
+    private static final String NPUB_HEX = "82341f882b6eabcd2ba7f1ef90aad961cf074af15b9ef44a09f9d2a8fbfbe6a2";
+    private static final String NOTE = "note14w46h2at4w46h2at4w46h2at4w46h2at4w46h2at4w46h2at4w4sfreljc";
+    private static final String NOTE_HEX = "ab".repeat(32);
+    private static final String INVOICE = "lnbc1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpl2pkx2ctnv5"
+        + "sxxmmwwd5kgetjypeh2ursdae8g6twvus8g6rfwvs8qun0dfjkxaq8rkx3yf5tcsyz3d73gafnh3cax9rn449d9p5uxz9ezhhypd0elx87"
+        + "sjle52x86fux2ypatgddc6k63n7erqz25le42c4u4ecky03ylcqca784w";
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private List<ContentBlock> blocksOf(Ndb ndb, String event) throws Exception {
+        try (Filter byId = Filter.builder().ids(TestEvents.idOf(event)).build()) {
+            assertEquals(1, TestEvents.awaitCount(ndb, byId, 1, 10_000));
+        }
+        try (Transaction txn = ndb.beginTransaction()) {
+            long key = ndb.getNoteKeyById(txn, TestEvents.idOf(event)).getAsLong();
+            return ndb.getNoteBlocks(txn, key).orElseThrow();
+        }
+    }
+
+    /** Blocks must tile the content: each starts where the last ended, up to the end. */
+    private static void assertContiguous(String content, List<ContentBlock> blocks) {
+        int end = 0;
+        StringBuilder joined = new StringBuilder();
+        for (ContentBlock block : blocks) {
+            assertEquals(end, block.offset(), "gap or overlap before " + block);
+            end += block.length();
+            joined.append(block.textIn(content));
+        }
+        assertEquals(content.getBytes(StandardCharsets.UTF_8).length, end);
+        assertEquals(content, joined.toString());
+    }
+
+    @Test
+    @DisplayName("A note should split into text, mention, hashtag, URL, and invoice blocks")
+    void testBlockTypes() throws Exception {
+        String content = "gm nostr:" + NPUB + " check #nostr at https://example.com/x?a=1 zap " + INVOICE
+            + " and nostr:" + NOTE + " caf\u00e9 \ud83d\ude80";
+        String event = alice.textNote(1_700_000_000L, content);
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            List<ContentBlock> blocks = blocksOf(ndb, event);
+            assertContiguous(content, blocks);
+
+            assertEquals(List.of(BlockType.TEXT, BlockType.MENTION, BlockType.TEXT, BlockType.HASHTAG,
+                    BlockType.TEXT, BlockType.URL, BlockType.TEXT, BlockType.INVOICE, BlockType.TEXT,
+                    BlockType.MENTION, BlockType.TEXT),
+                blocks.stream().map(ContentBlock::type).toList());
+            assertEquals("gm ", blocks.get(0).textIn(content));
+            assertEquals("nostr:" + NPUB, blocks.get(1).textIn(content));
+            assertEquals("#nostr", blocks.get(3).textIn(content));
+            assertEquals("https://example.com/x?a=1", blocks.get(5).textIn(content));
+            assertEquals(INVOICE, blocks.get(7).textIn(content));
+            assertEquals("nostr:" + NOTE, blocks.get(9).textIn(content));
+            assertEquals(" caf\u00e9 \ud83d\ude80", blocks.get(10).textIn(content));
+
+            assertEquals(NPUB_HEX, HexUtil.encode(blocks.get(1).mentionedPubkey().orElseThrow()));
+            assertTrue(blocks.get(1).mentionedEventId().isEmpty());
+            assertEquals(NOTE_HEX, HexUtil.encode(blocks.get(9).mentionedEventId().orElseThrow()));
+            assertTrue(blocks.get(9).mentionedPubkey().isEmpty());
+            assertTrue(blocks.get(3).mentionedPubkey().isEmpty());
+            assertTrue(blocks.get(3).mentionedEventId().isEmpty());
+        }
+    }
+
+    @Test
+    @DisplayName("Plain content should be a single text block, and empty content none")
+    void testPlainContent() throws Exception {
+        String plain = alice.textNote(1_700_000_000L, "just words");
+        String empty = alice.textNote(1_700_000_001L, "");
+        String reaction = alice.event(7, 1_700_000_002L, "+");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            List<ContentBlock> blocks = blocksOf(ndb, plain);
+            assertEquals(1, blocks.size());
+            assertEquals(BlockType.TEXT, blocks.get(0).type());
+            assertContiguous("just words", blocks);
+
+            assertEquals(List.of(), blocksOf(ndb, empty));
+
+            List<ContentBlock> reactionBlocks = blocksOf(ndb, reaction);
+            assertEquals(List.of(BlockType.TEXT), reactionBlocks.stream().map(ContentBlock::type).toList());
+            assertContiguous("+", reactionBlocks);
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertTrue(ndb.getNoteBlocks(txn, 999_999).isEmpty());
+            }
+        }
+    }
+}
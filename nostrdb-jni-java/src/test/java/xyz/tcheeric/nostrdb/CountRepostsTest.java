@@ -0,0 +1,58 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for counting reposts and quotes.
+ */
+class CountRepostsTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+    private final TestEvents carol = TestEvents.forSeed(13);
+
+    private static final long T = 1_700_000_000L;
+
+    @Test
+    @DisplayName("Should count reposts and quotes, but not replies")
+    void testCountReposts() throws Exception {
+        String note = alice.textNote(T, "worth sharing");
+        String id = TestEvents.idOf(note);
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1, 6, 7).build()) {
+            ndb.processEvent(note);
+            ndb.processEvent(bob.event(6, T + 1, note, new String[] {"e", id}, new String[] {"p", alice.pubkeyHex()}));
+            ndb.processEvent(carol.event(6, T + 2, "", new String[] {"e", id}));
+            ndb.processEvent(bob.event(1, T + 3, "look at this nostr:note", new String[] {"q", id}));
+            ndb.processEvent(carol.event(1, T + 4, "as mentioned", new String[] {"e", id, "", "mention"}));
+            // Both quote tags: still one quote
+            ndb.processEvent(carol.event(1, T + 5, "quoted twice",
+                new String[] {"q", id}, new String[] {"e", id, "", "mention"}));
+            // Replies and reactions are neither
+            ndb.processEvent(bob.event(1, T + 6, "marked reply", new String[] {"e", id, "", "root"}));
+            ndb.processEvent(carol.event(1, T + 7, "positional reply", new String[] {"e", id}));
+            ndb.processEvent(bob.event(7, T + 8, "+", new String[] {"e", id}));
+            assertEquals(9, TestEvents.awaitCount(ndb, all, 9, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                RepostCount count = ndb.countReposts(txn, id);
+                assertEquals(2, count.reposts());
+                assertEquals(3, count.quotes());
+
+                RepostCount none = ndb.countReposts(txn, new byte[32]);
+                assertEquals(0, none.reposts());
+                assertEquals(0, none.quotes());
+
+                assertThrows(IllegalArgumentException.class, () -> ndb.countReposts(txn, new byte[31]));
+            }
+        }
+    }
+}
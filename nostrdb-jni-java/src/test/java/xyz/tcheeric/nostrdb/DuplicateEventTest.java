@@ -0,0 +1,90 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for telling new, duplicate, and rejected events apart.
+ */
+class DuplicateEventTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("processEventWithResult should report a stored event as a duplicate and reject a malformed one")
+    void testProcessEventWithResult() throws Exception {
+        String note = alice.textNote(T, "hello");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            assertEquals(Ndb.PROCESS_STORED, ndb.processEventWithResult(note));
+            assertTrue(ndb.waitForIngestion(HexUtil.decode(TestEvents.idOf(note)), 10_000));
+
+            assertEquals(Ndb.PROCESS_DUPLICATE, ndb.processEventWithResult(note));
+            assertEquals(Ndb.PROCESS_DUPLICATE,
+                ndb.processEventWithResult("[\"EVENT\",\"sub\"," + note + "]"));
+            assertEquals(Ndb.PROCESS_REJECTED, ndb.processEventWithResult("not json"));
+            assertEquals(Ndb.PROCESS_REJECTED, ndb.processEventWithResult("{\"kind\":1}"));
+        }
+    }
+
+    @Test
+    @DisplayName("processEvent should queue a duplicate without throwing")
+    void testProcessEventDuplicate() throws Exception {
+        String note = alice.textNote(T, "hello");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(note);
+            assertTrue(ndb.waitForIngestion(HexUtil.decode(TestEvents.idOf(note)), 10_000));
+
+            assertDoesNotThrow(() -> ndb.processEvent(note));
+            assertDoesNotThrow(() -> ndb.processEventBytes(note.getBytes(StandardCharsets.UTF_8)));
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+        }
+    }
+
+    @Test
+    @DisplayName("processEvent and processEvents should accept or refuse a bad event alike")
+    void testSingleAndBatchAgree() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            for (String bad : new String[] {"not json", "{\"kind\":1}", "{\"id\": 42}"}) {
+                boolean accepted;
+                try {
+                    ndb.processEvent(bad);
+                    accepted = true;
+                } catch (NostrdbException e) {
+                    accepted = false;
+                }
+                assertEquals(accepted ? 1 : 0, ndb.processEvents(bad), bad);
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A batch should count new, duplicate, and failed events separately")
+    void testBatchTotals() throws Exception {
+        String note = alice.textNote(T, "hello");
+        String batch = String.join("\n", note, note, "{\"id\": 42}");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            IngestReport report = ndb.processEventsDetailed(batch);
+            assertEquals(1, report.succeeded());
+            assertEquals(1, report.duplicates());
+            assertEquals(1, report.failed());
+            assertEquals(3, report.errors().get(0).line());
+
+            assertTrue(ndb.waitForIngestion(HexUtil.decode(TestEvents.idOf(note)), 10_000));
+            IngestReport again = ndb.processEventsDetailed(note);
+            assertEquals(0, again.succeeded());
+            assertEquals(1, again.duplicates());
+            assertEquals(0, again.failed());
+        }
+    }
+}
@@ -0,0 +1,61 @@
+package xyz.tcheeric.nostrdb;
+
+import java.io.IOException;
+import java.nio.file.Files;
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.List;
+import java.util.concurrent.TimeUnit;
+
+/**
+ * Writes to a database from a separate JVM, for tests of what other processes
+ * see.
+ *
+ * <p>Usage: {@code ExternalWriter <db dir> <ldjson file>}. Ingests every line of
+ * the file into a database that holds no notes yet, waits until all of them are
+ * stored, and exits with status 0, or 1 if they were not stored in time.
+ */
+final class ExternalWriter {
+
+    private ExternalWriter() {
+    }
+
+    public static void main(String[] args) {
+        boolean stored;
+        try (Ndb ndb = Ndb.open(Path.of(args[0]))) {
+            long events = ndb.processEventsFromFile(Path.of(args[1])).processed();
+            stored = ndb.waitForIngestionCount(events, 30_000);
+        }
+        System.exit(stored ? 0 : 1);
+    }
+
+    /**
+     * Run the writer in a new JVM with the test classpath and wait for it.
+     *
+     * @param dbPath Database directory
+     * @param events Events to write, one JSON object each
+     * @throws IOException if the writer can't be started, fails, or times out
+     */
+    static void run(Path dbPath, List<String> events) throws IOException, InterruptedException {
+        Path ldjson = Files.createTempFile(dbPath.getParent(), "events", ".jsonl");
+        Path log = Files.createTempFile(dbPath.getParent(), "writer", ".log");
+        Files.write(ldjson, events);
+
+        List<String> command = new ArrayList<>();
+        command.add(Path.of(System.getProperty("java.home"), "bin", "java").toString());
+        command.add("-cp");
+        command.add(System.getProperty("java.class.path"));
+        command.add(ExternalWriter.class.getName());
+        command.add(dbPath.toString());
+        command.add(ldjson.toString());
+        Process process = new ProcessBuilder(command)
+            .redirectErrorStream(true)
+            .redirectOutput(log.toFile())
+            .start();
+
+        if (!process.waitFor(60, TimeUnit.SECONDS) || process.exitValue() != 0) {
+            process.destroyForcibly();
+            throw new IOException("External writer failed:\n" + Files.readString(log));
+        }
+    }
+}
@@ -0,0 +1,953 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+import java.util.Set;
+import java.util.stream.Collectors;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for filter builder fields, run against real signed events.
+ */
+class FilterQueryTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static Set<String> queryIds(Ndb ndb, Filter filter) {
+        try (Transaction txn = ndb.beginTransaction()) {
+            return ndb.queryNotes(txn, filter, 100).stream()
+                .map(Note::id)
+                .collect(Collectors.toSet());
+        }
+    }
+
+    private static long keyOf(Ndb ndb, Transaction txn, String eventId) {
+        try (Filter byId = Filter.builder().ids(eventId).build()) {
+            return ndb.query(txn, byId, 1).get(0).noteKey();
+        }
+    }
+
+    @Test
+    @DisplayName("Should match events by id")
+    void testIds() throws Exception {
+        String first = alice.textNote(1_700_000_001L, "first");
+        String second = alice.textNote(1_700_000_002L, "second");
+        String third = alice.textNote(1_700_000_003L, "third");
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1).build()) {
+            for (String event : List.of(first, second, third)) {
+                ndb.processEvent(event);
+            }
+            assertEquals(3, TestEvents.awaitCount(ndb, all, 3, 10_000));
+
+            try (Filter byHex = Filter.builder()
+                     .ids(TestEvents.idOf(first), TestEvents.idOf(third))
+                     .build();
+                 Filter byBytes = Filter.builder()
+                     .ids(HexUtil.decode(TestEvents.idOf(second)))
+                     .build();
+                 Transaction txn = ndb.beginTransaction()) {
+                assertEquals(2, ndb.query(txn, byHex, 100).size());
+                assertEquals(Set.of(TestEvents.idOf(first), TestEvents.idOf(third)), queryIds(ndb, byHex));
+                assertEquals(Set.of(TestEvents.idOf(second)), queryIds(ndb, byBytes));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Should match replies by referenced event id")
+    void testEvents() throws Exception {
+        TestEvents bob = TestEvents.forSeed(12);
+        String root = alice.textNote(1_700_000_000L, "root");
+        String rootId = TestEvents.idOf(root);
+        String[] eTag = {"e", rootId};
+        String reply1 = bob.event(1, 1_700_000_010L, "reply 1", eTag);
+        String reply2 = alice.event(1, 1_700_000_020L, "reply 2", eTag);
+        String reaction = bob.event(7, 1_700_000_030L, "+", eTag);
+        String oldReply = bob.event(1, 1_600_000_000L, "old reply", eTag);
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1, 7).build()) {
+            for (String event : List.of(root, reply1, reply2, reaction, oldReply)) {
+                ndb.processEvent(event);
+            }
+            assertEquals(5, TestEvents.awaitCount(ndb, all, 5, 10_000));
+
+            try (Filter replies = Filter.builder()
+                     .kinds(1)
+                     .events(HexUtil.decode(rootId))
+                     .since(1_700_000_000L)
+                     .build();
+                 Filter anyReference = Filter.builder().events(rootId).build()) {
+                assertEquals(Set.of(TestEvents.idOf(reply1), TestEvents.idOf(reply2)), queryIds(ndb, replies));
+                assertEquals(4, queryIds(ndb, anyReference).size());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Should find DMs addressed to a pubkey")
+    void testPubkeys() throws Exception {
+        TestEvents bob = TestEvents.forSeed(12);
+        TestEvents carol = TestEvents.forSeed(13);
+        String toBob1 = alice.event(4, 1_700_000_001L, "ciphertext-1?iv=aaaa", new String[] {"p", bob.pubkeyHex()});
+        String toBob2 = carol.event(4, 1_700_000_002L, "ciphertext-2?iv=bbbb", new String[] {"p", bob.pubkeyHex()});
+        String toCarol = alice.event(4, 1_700_000_003L, "ciphertext-3?iv=cccc", new String[] {"p", carol.pubkeyHex()});
+        String mention = alice.event(1, 1_700_000_004L, "hi bob", new String[] {"p", bob.pubkeyHex()});
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1, 4).build()) {
+            for (String event : List.of(toBob1, toBob2, toCarol, mention)) {
+                ndb.processEvent(event);
+            }
+            assertEquals(4, TestEvents.awaitCount(ndb, all, 4, 10_000));
+
+            try (Filter dmsToBob = Filter.builder().kinds(4).pubkeys(bob.pubkey()).build();
+                 Filter toBobOrCarol = Filter.builder().kinds(4).pubkeys(bob.pubkeyHex(), carol.pubkeyHex()).build()) {
+                assertEquals(Set.of(TestEvents.idOf(toBob1), TestEvents.idOf(toBob2)), queryIds(ndb, dmsToBob));
+                assertEquals(3, queryIds(ndb, toBobOrCarol).size());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Binary tag values should match tags stored from hex JSON")
+    void testBinaryTag() throws Exception {
+        String quoted = alice.textNote(1_700_000_000L, "quoted");
+        String quotedId = TestEvents.idOf(quoted);
+        String quote = alice.event(1, 1_700_000_010L, "look at this", new String[] {"q", quotedId});
+        String other = alice.event(1, 1_700_000_020L, "unrelated", new String[] {"q", "00".repeat(32)});
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1).build()) {
+            for (String event : List.of(quoted, quote, other)) {
+                ndb.processEvent(event);
+            }
+            assertEquals(3, TestEvents.awaitCount(ndb, all, 3, 10_000));
+
+            try (Filter binary = Filter.builder().binaryTag("q", HexUtil.decode(quotedId)).build();
+                 Filter hex = Filter.builder().tag("q", quotedId).build();
+                 Filter noMatch = Filter.builder().binaryTag("q", new byte[32]).binaryTag("e", HexUtil.decode(quotedId)).build()) {
+                assertEquals(Set.of(TestEvents.idOf(quote)), queryIds(ndb, binary));
+                assertEquals(queryIds(ndb, hex), queryIds(ndb, binary));
+                assertTrue(queryIds(ndb, noMatch).isEmpty());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Binary tags should reject malformed lengths and tag names")
+    void testBinaryTagValidation() {
+        long ptr = NostrdbNative.filterNew();
+        assertThrows(IllegalArgumentException.class, () -> NostrdbNative.filterTagBinary(ptr, "e", new byte[40]));
+        assertThrows(IllegalArgumentException.class, () -> NostrdbNative.filterTagBinary(ptr, "", new byte[32]));
+        assertThrows(IllegalArgumentException.class, () -> NostrdbNative.filterTagBinary(ptr, "ee", new byte[32]));
+        NostrdbNative.filterDestroy(NostrdbNative.filterBuild(ptr));
+
+        assertThrows(IllegalArgumentException.class, () -> Filter.builder().binaryTag("p", new byte[31]));
+    }
+
+    @Test
+    @DisplayName("A filter parsed from JSON should match like a built one")
+    void testFromJson() throws Exception {
+        TestEvents bob = TestEvents.forSeed(12);
+        String root = alice.textNote(1_700_000_000L, "root");
+        String rootId = TestEvents.idOf(root);
+        String[] eTag = {"e", rootId};
+        String reply = bob.event(1, 1_700_000_010L, "reply", eTag, new String[] {"t", "nostr"});
+        String lateReply = bob.event(1, 1_700_000_500L, "late reply", eTag, new String[] {"t", "nostr"});
+        String otherTopic = bob.event(1, 1_700_000_020L, "other", eTag, new String[] {"t", "bitcoin"});
+        String byAlice = alice.event(1, 1_700_000_030L, "self reply", eTag, new String[] {"t", "nostr"});
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1).build()) {
+            for (String event : List.of(root, reply, lateReply, otherTopic, byAlice)) {
+                ndb.processEvent(event);
+            }
+            assertEquals(5, TestEvents.awaitCount(ndb, all, 5, 10_000));
+
+            String json = "{\"kinds\":[1,7],\"authors\":[\"" + bob.pubkeyHex() + "\"],"
+                + "\"#e\":[\"" + rootId + "\"],\"#t\":[\"nostr\"],"
+                + "\"since\":1700000000,\"until\":1700000100,\"limit\":10}";
+            try (Filter parsed = Filter.fromJson(json);
+                 Filter built = Filter.builder()
+                     .kinds(1, 7)
+                     .authors(bob.pubkey())
+                     .events(rootId)
+                     .tag("t", "nostr")
+                     .since(1_700_000_000L)
+                     .until(1_700_000_100L)
+                     .limit(10)
+                     .build();
+                 Filter byIds = Filter.fromJson("{\"ids\":[\"" + rootId + "\"]}")) {
+                assertEquals(Set.of(TestEvents.idOf(reply)), queryIds(ndb, parsed));
+                assertEquals(queryIds(ndb, built), queryIds(ndb, parsed));
+                assertEquals(Set.of(rootId), queryIds(ndb, byIds));
+
+                // Usable for subscriptions like a built filter
+                Subscription sub = ndb.subscribe(parsed);
+                ndb.unsubscribe(sub);
+            }
+
+            Filter.fromJson("{\"search\":\"hello\",\"kinds\":[1]}").close();
+        }
+    }
+
+    @Test
+    @DisplayName("A filter serialized to JSON and parsed back should match the same events")
+    void testToJsonRoundTrip() throws Exception {
+        TestEvents bob = TestEvents.forSeed(12);
+        String root = alice.textNote(1_700_000_000L, "root");
+        String rootId = TestEvents.idOf(root);
+        String reply = bob.event(1, 1_700_000_010L, "reply", new String[] {"e", rootId}, new String[] {"t", "nostr"});
+        String mention = bob.event(1, 1_700_000_020L, "mention", new String[] {"p", alice.pubkeyHex()}, new String[] {"t", "nostr"});
+        String reaction = bob.event(7, 1_700_000_030L, "+", new String[] {"e", rootId});
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1, 7).build()) {
+            for (String event : List.of(root, reply, mention, reaction)) {
+                ndb.processEvent(event);
+            }
+            assertEquals(4, TestEvents.awaitCount(ndb, all, 4, 10_000));
+
+            try (Filter original = Filter.builder()
+                     .kinds(1, 7)
+                     .authors(bob.pubkey())
+                     .events(rootId)
+                     .since(1_700_000_000L)
+                     .until(1_800_000_000L)
+                     .limit(50)
+                     .build();
+                 Filter byTag = Filter.builder()
+                     .ids(TestEvents.idOf(mention), TestEvents.idOf(reply))
+                     .pubkeys(alice.pubkey())
+                     .tag("t", "nostr")
+                     .build()) {
+                String json = original.toJson();
+                assertTrue(json.contains("\"kinds\":[1,7]"), json);
+                assertTrue(json.contains("\"#e\":[\"" + rootId + "\"]"), json);
+                assertTrue(json.contains("\"authors\":[\"" + bob.pubkeyHex() + "\"]"), json);
+                assertTrue(json.contains("\"since\":1700000000"), json);
+
+                try (Filter parsed = Filter.fromJson(json);
+                     Filter parsedByTag = Filter.fromJson(byTag.toJson())) {
+                    assertEquals(Set.of(TestEvents.idOf(reply), TestEvents.idOf(reaction)), queryIds(ndb, parsed));
+                    assertEquals(queryIds(ndb, original), queryIds(ndb, parsed));
+                    assertEquals(Set.of(TestEvents.idOf(mention)), queryIds(ndb, parsedByTag));
+                    assertEquals(queryIds(ndb, byTag), queryIds(ndb, parsedByTag));
+                }
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Malformed filter JSON should throw NostrdbException")
+    void testFromJsonInvalid() {
+        assertThrows(NostrdbException.class, () -> Filter.fromJson("{\"kinds\":[1"));
+        assertThrows(NostrdbException.class, () -> Filter.fromJson("[]"));
+        assertThrows(NostrdbException.class, () -> Filter.fromJson("{\"kinds\":\"1\"}"));
+        assertThrows(NostrdbException.class, () -> Filter.fromJson("{\"ids\":[\"abcd\"]}"));
+        assertThrows(NostrdbException.class, () -> Filter.fromJson("{\"since\":-5}"));
+        assertThrows(NostrdbException.class, () -> Filter.fromJson("{\"unknown\":1}"));
+        assertThrows(NostrdbException.class, () -> Filter.fromJson("{\"#tag\":[\"x\"]}"));
+    }
+
+    @Test
+    @DisplayName("Should evaluate a filter against single stored and unstored notes")
+    void testMatches() throws Exception {
+        TestEvents bob = TestEvents.forSeed(12);
+        String note = alice.event(1, 1_700_000_100L, "GM Nostr", new String[] {"t", "gm"});
+        String other = bob.event(7, 1_700_000_200L, "+", new String[] {"t", "gm"});
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1, 7).build()) {
+            ndb.processEvent(note);
+            ndb.processEvent(other);
+            assertEquals(2, TestEvents.awaitCount(ndb, all, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction();
+                 Filter kind = Filter.builder().kinds(1).build();
+                 Filter author = Filter.builder().authors(alice.pubkeyHex()).build();
+                 Filter tag = Filter.builder().tag("t", "gm").build();
+                 Filter window = Filter.builder().since(1_700_000_100L).until(1_700_000_150L).build();
+                 Filter search = Filter.builder().search("gm nostr").build();
+                 Filter mismatch = Filter.builder().kinds(1).authors(bob.pubkey()).build()) {
+                long noteKey = keyOf(ndb, txn, TestEvents.idOf(note));
+                long otherKey = keyOf(ndb, txn, TestEvents.idOf(other));
+
+                for (Filter f : List.of(kind, author, tag, window, search)) {
+                    assertTrue(f.matches(ndb, txn, noteKey), f.toString());
+                    assertTrue(f.matches(note), f.toString());
+                }
+                assertTrue(tag.matches(ndb, txn, otherKey));
+                assertTrue(tag.matches(other));
+                for (Filter f : List.of(kind, author, window, search, mismatch)) {
+                    assertFalse(f.matches(ndb, txn, otherKey), f.toString());
+                    assertFalse(f.matches(other), f.toString());
+                }
+                assertFalse(mismatch.matches(ndb, txn, noteKey));
+                assertFalse(mismatch.matches(note));
+
+                assertThrows(java.util.NoSuchElementException.class, () -> kind.matches(ndb, txn, 999_999));
+                assertThrows(IllegalArgumentException.class, () -> kind.matches("{\"content\":\"no kind\"}"));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A copied filter should outlive the original")
+    void testCopy() throws Exception {
+        TestEvents bob = TestEvents.forSeed(12);
+        String fromAlice = alice.textNote(1_700_000_001L, "from alice");
+        String fromBob = bob.textNote(1_700_000_002L, "from bob");
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(fromAlice);
+            ndb.processEvent(fromBob);
+            assertEquals(2, TestEvents.awaitCount(ndb, all, 2, 10_000));
+
+            Filter original = Filter.builder().kinds(1).authors(alice.pubkey()).build();
+            String json = original.toJson();
+            try (Filter copy = original.copy()) {
+                original.close();
+                assertThrows(IllegalStateException.class, original::copy);
+
+                assertEquals(json, copy.toJson());
+                assertEquals(Set.of(TestEvents.idOf(fromAlice)), queryIds(ndb, copy));
+
+                Subscription sub = ndb.subscribe(copy);
+                ndb.unsubscribe(sub);
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A multi-filter query should return the union, limited as a whole")
+    void testQueryMulti() throws Exception {
+        TestEvents bob = TestEvents.forSeed(12);
+        String note1 = alice.textNote(1_700_000_001L, "note 1");
+        String note2 = alice.textNote(1_700_000_002L, "note 2");
+        String reaction = bob.event(7, 1_700_000_003L, "+", new String[] {"e", TestEvents.idOf(note1)});
+        String profile = bob.event(0, 1_700_000_004L, "{\"name\":\"bob\"}");
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(0, 1, 7).build()) {
+            for (String event : List.of(note1, note2, reaction, profile)) {
+                ndb.processEvent(event);
+            }
+            assertEquals(4, TestEvents.awaitCount(ndb, all, 4, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction();
+                 Filter notes = Filter.builder().kinds(1).build();
+                 Filter reactions = Filter.builder().kinds(7).build()) {
+                List<QueryResult> union = ndb.query(txn, List.of(notes, reactions), 100);
+                assertEquals(3, union.size());
+                Set<Long> expected = new java.util.HashSet<>();
+                for (QueryResult r : ndb.query(txn, notes, 100)) {
+                    expected.add(r.noteKey());
+                }
+                expected.add(ndb.query(txn, reactions, 100).get(0).noteKey());
+                assertEquals(expected, union.stream().map(QueryResult::noteKey).collect(Collectors.toSet()));
+
+                assertEquals(2, ndb.query(txn, List.of(notes, reactions), 2).size());
+                assertThrows(IllegalArgumentException.class, () -> ndb.query(txn, List.of(), 10));
+                assertThrows(IllegalArgumentException.class, () ->
+                    NostrdbNative.queryMulti(ndb.ptr(), txn.ptr(), new long[0], 10));
+                assertThrows(NullPointerException.class, () ->
+                    NostrdbNative.queryMulti(ndb.ptr(), txn.ptr(), new long[] {notes.ptr(), 0}, 10));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Filter builder handles should stay valid after a native error")
+    void testBuilderSurvivesErrors() throws Exception {
+        String note = alice.textNote(1_700_000_001L, "still here");
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(note);
+            assertEquals(1, TestEvents.awaitCount(ndb, all, 1, 10_000));
+
+            long ptr = NostrdbNative.filterNew();
+            assertThrows(IllegalArgumentException.class, () -> NostrdbNative.filterKinds(ptr, new byte[3]));
+            assertThrows(IllegalArgumentException.class, () -> NostrdbNative.filterAuthors(ptr, new byte[33]));
+            assertThrows(NostrdbException.class, () -> NostrdbNative.filterTag(ptr, "", new String[] {"x"}));
+            assertThrows(Exception.class, () -> NostrdbNative.filterTag(ptr, "t", new String[] {null}));
+            assertThrows(Exception.class, () -> NostrdbNative.filterSearch(ptr, null));
+
+            // The original pointer is still the live builder
+            long next = NostrdbNative.filterKinds(ptr, new byte[] {1, 0, 0, 0});
+            long built = NostrdbNative.filterBuild(next);
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(1, QueryResult.parseResults(NostrdbNative.query(ndb.ptr(), txn.ptr(), built, 10)).size());
+            } finally {
+                NostrdbNative.filterDestroy(built);
+            }
+
+            // Same through the Java builder, which keeps its pointer on error
+            Filter.Builder builder = Filter.builder().kinds(1);
+            assertThrows(IllegalArgumentException.class, () -> builder.authors(new byte[31]));
+            try (Filter filter = builder.build()) {
+                assertEquals(Set.of(TestEvents.idOf(note)), queryIds(ndb, filter));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A stable builder pointer should match the consuming builder API")
+    void testStableBuilder() throws Exception {
+        TestEvents bob = TestEvents.forSeed(12);
+        List<String> events = List.of(
+            alice.textNote(1_700_000_001L, "too early"),
+            alice.textNote(1_700_000_002L, "in range"),
+            alice.event(7, 1_700_000_003L, "+"),
+            alice.textNote(1_700_000_004L, "also in range"),
+            bob.textNote(1_700_000_003L, "other author"),
+            alice.textNote(1_700_000_009L, "too late"));
+        byte[] kinds = {1, 0, 0, 0};
+        byte[] authors = alice.pubkey();
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1, 7).build()) {
+            for (String event : events) {
+                ndb.processEvent(event);
+            }
+            assertEquals(6, TestEvents.awaitCount(ndb, all, 6, 10_000));
+
+            long consumed = NostrdbNative.filterNew();
+            consumed = NostrdbNative.filterKinds(consumed, kinds);
+            consumed = NostrdbNative.filterAuthors(consumed, authors);
+            consumed = NostrdbNative.filterSince(consumed, 1_700_000_002L);
+            consumed = NostrdbNative.filterUntil(consumed, 1_700_000_005L);
+            consumed = NostrdbNative.filterLimit(consumed, 10);
+            long oldFilter = NostrdbNative.filterBuild(consumed);
+
+            long builder = NostrdbNative.filterBuilderNew();
+            NostrdbNative.filterBuilderAddKinds(builder, kinds);
+            NostrdbNative.filterBuilderAddAuthors(builder, authors);
+            NostrdbNative.filterBuilderSetSince(builder, 1_700_000_002L);
+            NostrdbNative.filterBuilderSetUntil(builder, 1_700_000_005L);
+            NostrdbNative.filterBuilderSetLimit(builder, 10);
+            long newFilter = NostrdbNative.filterBuilderBuild(builder);
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> expected = QueryResult.parseResults(
+                    NostrdbNative.query(ndb.ptr(), txn.ptr(), oldFilter, 100));
+                List<QueryResult> actual = QueryResult.parseResults(
+                    NostrdbNative.query(ndb.ptr(), txn.ptr(), newFilter, 100));
+                assertEquals(2, expected.size());
+                assertEquals(
+                    expected.stream().map(QueryResult::noteKey).collect(Collectors.toSet()),
+                    actual.stream().map(QueryResult::noteKey).collect(Collectors.toSet()));
+                assertEquals(NostrdbNative.filterToJson(oldFilter), NostrdbNative.filterToJson(newFilter));
+            } finally {
+                NostrdbNative.filterDestroy(oldFilter);
+                NostrdbNative.filterDestroy(newFilter);
+            }
+
+            // A built builder stays a valid handle but can't be reused
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.filterBuilderSetLimit(builder, 5));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.filterBuilderBuild(builder));
+            NostrdbNative.filterBuilderDestroy(builder);
+        }
+    }
+
+    @Test
+    @DisplayName("A stable builder should keep its state after an error and reject the consuming API")
+    void testStableBuilderErrors() {
+        long builder = NostrdbNative.filterBuilderNew();
+        try {
+            NostrdbNative.filterBuilderAddKinds(builder, new byte[] {1, 0, 0, 0});
+            assertThrows(IllegalArgumentException.class, () -> NostrdbNative.filterBuilderAddKinds(builder, new byte[3]));
+            assertThrows(IllegalArgumentException.class, () ->
+                NostrdbNative.filterBuilderAddTagBinary(builder, "ab", new byte[32]));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.filterKinds(builder, new byte[4]));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.filterBuild(builder));
+
+            long filter = NostrdbNative.filterBuilderBuild(builder);
+            try {
+                assertTrue(NostrdbNative.filterToJson(filter).contains("\"kinds\":[1]"));
+            } finally {
+                NostrdbNative.filterDestroy(filter);
+            }
+        } finally {
+            NostrdbNative.filterBuilderDestroy(builder);
+        }
+    }
+
+    @Test
+    @DisplayName("Kinds passed as int[] should match kinds packed into byte[]")
+    void testKindsInt() throws Exception {
+        List<String> events = List.of(
+            alice.textNote(1_700_000_001L, "note"),
+            alice.event(7, 1_700_000_002L, "+"),
+            alice.event(30023, 1_700_000_003L, "article", new String[] {"d", "a"}),
+            alice.event(3, 1_700_000_004L, ""));
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1, 3, 7, 30023).build()) {
+            for (String event : events) {
+                ndb.processEvent(event);
+            }
+            assertEquals(4, TestEvents.awaitCount(ndb, all, 4, 10_000));
+
+            byte[] packed = {1, 0, 0, 0, 0x47, 0x75, 0, 0};
+            long fromBytes = NostrdbNative.filterBuild(NostrdbNative.filterKinds(NostrdbNative.filterNew(), packed));
+            long fromInts = NostrdbNative.filterBuild(
+                NostrdbNative.filterKindsInt(NostrdbNative.filterNew(), new int[] {1, 30023}));
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> expected = QueryResult.parseResults(
+                    NostrdbNative.query(ndb.ptr(), txn.ptr(), fromBytes, 100));
+                List<QueryResult> actual = QueryResult.parseResults(
+                    NostrdbNative.query(ndb.ptr(), txn.ptr(), fromInts, 100));
+                assertEquals(2, expected.size());
+                assertEquals(
+                    expected.stream().map(QueryResult::noteKey).collect(Collectors.toSet()),
+                    actual.stream().map(QueryResult::noteKey).collect(Collectors.toSet()));
+            } finally {
+                NostrdbNative.filterDestroy(fromBytes);
+                NostrdbNative.filterDestroy(fromInts);
+            }
+
+            try (Filter viaBuilder = Filter.builder().kinds(1, 30023).build()) {
+                assertEquals(2, queryIds(ndb, viaBuilder).size());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Negative kinds should be rejected")
+    void testNegativeKinds() {
+        assertThrows(IllegalArgumentException.class, () -> Filter.builder().kinds(1, -1));
+
+        // The consuming API keeps the builder on error
+        long ptr = NostrdbNative.filterNew();
+        assertThrows(IllegalArgumentException.class, () -> NostrdbNative.filterKindsInt(ptr, new int[] {-7}));
+        NostrdbNative.filterDestroy(NostrdbNative.filterBuild(NostrdbNative.filterKindsInt(ptr, new int[] {1})));
+    }
+
+    @Test
+    @DisplayName("Should match authors given as hex in either case")
+    void testAuthorsHex() throws Exception {
+        TestEvents bob = TestEvents.forSeed(12);
+        String fromAlice = alice.textNote(1_700_000_001L, "alice");
+        String fromBob = bob.textNote(1_700_000_002L, "bob");
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(fromAlice);
+            ndb.processEvent(fromBob);
+            assertEquals(2, TestEvents.awaitCount(ndb, all, 2, 10_000));
+
+            String upper = alice.pubkeyHex().toUpperCase();
+            String mixed = upper.substring(0, 32) + alice.pubkeyHex().substring(32);
+            long fromHex = NostrdbNative.filterBuild(
+                NostrdbNative.filterAuthorsHex(NostrdbNative.filterNew(), new String[] {mixed}));
+            long fromBytes = NostrdbNative.filterBuild(
+                NostrdbNative.filterAuthors(NostrdbNative.filterNew(), alice.pubkey()));
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> expected = QueryResult.parseResults(
+                    NostrdbNative.query(ndb.ptr(), txn.ptr(), fromBytes, 100));
+                List<QueryResult> actual = QueryResult.parseResults(
+                    NostrdbNative.query(ndb.ptr(), txn.ptr(), fromHex, 100));
+                assertEquals(1, actual.size());
+                assertEquals(expected.get(0).noteKey(), actual.get(0).noteKey());
+            } finally {
+                NostrdbNative.filterDestroy(fromHex);
+                NostrdbNative.filterDestroy(fromBytes);
+            }
+
+            try (Filter both = Filter.builder().authors(upper, bob.pubkeyHex()).build()) {
+                assertEquals(Set.of(TestEvents.idOf(fromAlice), TestEvents.idOf(fromBob)), queryIds(ndb, both));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Malformed hex authors should name the offending index")
+    void testAuthorsHexInvalid() {
+        String valid = alice.pubkeyHex();
+        Filter.Builder builder = Filter.builder();
+        IllegalArgumentException e = assertThrows(IllegalArgumentException.class, () ->
+            builder.authors(valid, valid.substring(0, 62) + "zz"));
+        assertTrue(e.getMessage().contains("index 1"), e.getMessage());
+        e = assertThrows(IllegalArgumentException.class, () -> builder.authors(valid.substring(2)));
+        assertTrue(e.getMessage().contains("index 0"), e.getMessage());
+        e = assertThrows(IllegalArgumentException.class, () -> builder.authors(valid, valid, null));
+        assertTrue(e.getMessage().contains("index 2"), e.getMessage());
+        builder.authors(valid).build().close();
+
+        long ptr = NostrdbNative.filterNew();
+        assertThrows(IllegalArgumentException.class, () ->
+            NostrdbNative.filterAuthorsHex(ptr, new String[] {valid + "00"}));
+        NostrdbNative.filterDestroy(NostrdbNative.filterBuild(ptr));
+    }
+
+    @Test
+    @DisplayName("Should read back every element of a built filter")
+    void testIntrospection() {
+        TestEvents bob = TestEvents.forSeed(12);
+        try (Filter filter = Filter.builder()
+                 .kinds(1, 30023)
+                 .authors(alice.pubkeyHex(), bob.pubkeyHex())
+                 .since(1_700_000_000L)
+                 .until(1_700_000_100L)
+                 .limit(25)
+                 .build()) {
+            assertArrayEquals(new int[] {1, 30023}, filter.kinds());
+            assertEquals(List.of(alice.pubkeyHex(), bob.pubkeyHex()), filter.authors());
+            assertEquals(1_700_000_000L, filter.since().getAsLong());
+            assertEquals(1_700_000_100L, filter.until().getAsLong());
+            assertEquals(25, filter.limit().getAsLong());
+
+            byte[] packed = NostrdbNative.filterGetAuthors(filter.ptr());
+            assertEquals(64, packed.length);
+            assertArrayEquals(alice.pubkey(), java.util.Arrays.copyOfRange(packed, 0, 32));
+        }
+
+        // Missing elements read back as null / -1 natively and empty in Java
+        try (Filter filter = Filter.builder().ids(new byte[32]).build()) {
+            assertNull(NostrdbNative.filterGetKinds(filter.ptr()));
+            assertNull(NostrdbNative.filterGetAuthors(filter.ptr()));
+            assertEquals(-1, NostrdbNative.filterGetSince(filter.ptr()));
+            assertEquals(-1, NostrdbNative.filterGetUntil(filter.ptr()));
+            assertEquals(-1, NostrdbNative.filterGetLimit(filter.ptr()));
+
+            assertEquals(0, filter.kinds().length);
+            assertTrue(filter.authors().isEmpty());
+            assertTrue(filter.since().isEmpty());
+            assertTrue(filter.until().isEmpty());
+            assertTrue(filter.limit().isEmpty());
+        }
+
+        try (Filter parsed = Filter.fromJson("{\"kinds\":[7],\"since\":5}")) {
+            assertArrayEquals(new int[] {7}, parsed.kinds());
+            assertEquals(5, parsed.since().getAsLong());
+            assertTrue(parsed.limit().isEmpty());
+        }
+    }
+
+    @Test
+    @DisplayName("Hashtags should be normalized to match lowercase t tags")
+    void testHashtags() throws Exception {
+        String bitcoin = alice.event(1, 1_700_000_001L, "stack sats", new String[] {"t", "bitcoin"});
+        String nostr = alice.event(1, 1_700_000_002L, "gm", new String[] {"t", "nostr"});
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(bitcoin);
+            ndb.processEvent(nostr);
+            assertEquals(2, TestEvents.awaitCount(ndb, all, 2, 10_000));
+
+            try (Filter typed = Filter.builder().hashtags("#Bitcoin").build();
+                 Filter raw = Filter.builder().tag("t", "#Bitcoin").build()) {
+                assertEquals(Set.of(TestEvents.idOf(bitcoin)), queryIds(ndb, typed));
+                assertTrue(queryIds(ndb, raw).isEmpty(), "Unnormalized tag values should not match");
+            }
+
+            long builder = NostrdbNative.filterBuilderNew();
+            try {
+                assertEquals(2, NostrdbNative.filterBuilderAddHashtags(builder,
+                    new String[] {"#Bitcoin", "", "bitcoin", " #NOSTR ", "#"}));
+                long filter = NostrdbNative.filterBuilderBuild(builder);
+                try (Transaction txn = ndb.beginTransaction()) {
+                    assertEquals(2, QueryResult.parseResults(
+                        NostrdbNative.query(ndb.ptr(), txn.ptr(), filter, 10)).size());
+                } finally {
+                    NostrdbNative.filterDestroy(filter);
+                }
+            } finally {
+                NostrdbNative.filterBuilderDestroy(builder);
+            }
+
+            long consumed = NostrdbNative.filterHashtags(NostrdbNative.filterNew(), new String[] {"#BITCOIN"});
+            long filter = NostrdbNative.filterBuild(consumed);
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(1, QueryResult.parseResults(
+                    NostrdbNative.query(ndb.ptr(), txn.ptr(), filter, 10)).size());
+            } finally {
+                NostrdbNative.filterDestroy(filter);
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Hashtags that are all empty after normalization should be rejected")
+    void testHashtagsAllEmpty() {
+        Filter.Builder builder = Filter.builder();
+        assertThrows(IllegalArgumentException.class, () -> builder.hashtags("#", "", "  "));
+        builder.hashtags("#ok").build().close();
+
+        long ptr = NostrdbNative.filterNew();
+        assertThrows(IllegalArgumentException.class, () -> NostrdbNative.filterHashtags(ptr, new String[] {"#"}));
+        NostrdbNative.filterDestroy(NostrdbNative.filterBuild(ptr));
+    }
+
+    @Test
+    @DisplayName("A replaceable filter should find the latest version of an article")
+    void testReplaceable() throws Exception {
+        TestEvents bob = TestEvents.forSeed(12);
+        String v1 = alice.event(30023, 1_700_000_001L, "draft", new String[] {"d", "my-article"});
+        String v2 = alice.event(30023, 1_700_000_050L, "final", new String[] {"d", "my-article"});
+        String otherArticle = alice.event(30023, 1_700_000_060L, "other", new String[] {"d", "other"});
+        String otherAuthor = bob.event(30023, 1_700_000_070L, "bob's", new String[] {"d", "my-article"});
+        String emptyD = alice.event(30023, 1_700_000_080L, "no slug", new String[] {"d", ""});
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(30023).build()) {
+            for (String event : List.of(v1, v2, otherArticle, otherAuthor, emptyD)) {
+                ndb.processEvent(event);
+            }
+            assertEquals(5, TestEvents.awaitCount(ndb, all, 5, 10_000));
+
+            try (Filter latest = Filter.builder().replaceable(30023, alice.pubkeyHex(), "my-article").build()) {
+                assertEquals(1, latest.limit().getAsLong());
+                assertEquals(Set.of(TestEvents.idOf(v2)), queryIds(ndb, latest));
+            }
+            try (Filter empty = Filter.builder().replaceable(30023, alice.pubkey(), "").build()) {
+                assertEquals(Set.of(TestEvents.idOf(emptyD)), queryIds(ndb, empty));
+            }
+
+            long consumed = NostrdbNative.filterReplaceable(NostrdbNative.filterNew(), 30023, alice.pubkey(), "my-article");
+            long filter = NostrdbNative.filterBuild(consumed);
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> results = QueryResult.parseResults(
+                    NostrdbNative.query(ndb.ptr(), txn.ptr(), filter, 10));
+                assertEquals(1, results.size());
+                assertEquals(keyOf(ndb, txn, TestEvents.idOf(v2)), results.get(0).noteKey());
+            } finally {
+                NostrdbNative.filterDestroy(filter);
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A replaceable filter should validate the kind and pubkey")
+    void testReplaceableValidation() {
+        Filter.Builder builder = Filter.builder();
+        assertThrows(IllegalArgumentException.class, () -> builder.replaceable(30023, new byte[31], "a"));
+        assertThrows(IllegalArgumentException.class, () -> builder.replaceable(-1, new byte[32], "a"));
+        assertThrows(IllegalArgumentException.class, () -> builder.replaceable(30023, "abcd", "a"));
+        builder.replaceable(30023, new byte[32], "a").build().close();
+
+        long ptr = NostrdbNative.filterNew();
+        assertThrows(IllegalArgumentException.class, () ->
+            NostrdbNative.filterReplaceable(ptr, 30023, new byte[33], "a"));
+        NostrdbNative.filterDestroy(NostrdbNative.filterBuild(ptr));
+    }
+
+    @Test
+    @DisplayName("The query limit and the filter's own limit should combine predictably")
+    void testLimitSemantics() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1).build()) {
+            for (int i = 0; i < 12; i++) {
+                ndb.processEvent(alice.textNote(1_700_000_000L + i, "note " + i));
+            }
+            assertEquals(12, TestEvents.awaitCount(ndb, all, 12, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction();
+                 Filter limited = Filter.builder().kinds(1).limit(5).build()) {
+                // Filter limit only
+                assertEquals(5, ndb.query(txn, limited, Ndb.FILTER_LIMIT).size());
+                // Argument limit only
+                assertEquals(7, ndb.query(txn, all, 7).size());
+                // Both: the smaller one wins
+                assertEquals(3, ndb.query(txn, limited, 3).size());
+                assertEquals(5, ndb.query(txn, limited, 10).size());
+                // Neither: every match
+                assertEquals(12, ndb.query(txn, all, Ndb.FILTER_LIMIT).size());
+
+                assertEquals(12, QueryResult.parseResults(
+                    NostrdbNative.query(ndb.ptr(), txn.ptr(), all.ptr(), -5)).size());
+                assertThrows(IllegalArgumentException.class, () -> ndb.query(txn, all, -2));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A large kind range should only return notes in the range")
+    void testKindRange() throws Exception {
+        String note = alice.textNote(1_700_000_001L, "note");
+        String relays = alice.event(10002, 1_700_000_002L, "");
+        String below = alice.event(29999, 1_700_000_003L, "below", new String[] {"d", "a"});
+        String article = alice.event(30023, 1_700_000_004L, "article", new String[] {"d", "a"});
+        String appData = alice.event(30078, 1_700_000_005L, "app", new String[] {"d", "a"});
+        String last = alice.event(39999, 1_700_000_006L, "last", new String[] {"d", "a"});
+        String above = alice.event(40000, 1_700_000_007L, "above", new String[] {"d", "a"});
+        Set<String> inRange = Set.of(TestEvents.idOf(article), TestEvents.idOf(appData), TestEvents.idOf(last));
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().authors(alice.pubkey()).build();
+             Filter range = Filter.builder().kindRange(30000, 39999).build()) {
+            for (String event : List.of(note, relays, below, article, appData, last, above)) {
+                ndb.processEvent(event);
+            }
+            assertEquals(7, TestEvents.awaitCount(ndb, all, 7, 10_000));
+
+            assertEquals(inRange, queryIds(ndb, range));
+            try (Transaction txn = ndb.beginTransaction()) {
+                // The limit counts notes in the range, not candidates
+                assertEquals(2, ndb.query(txn, range, 2).size());
+                assertEquals(3, ndb.query(txn, range, Ndb.FILTER_LIMIT).size());
+                assertTrue(range.matches(ndb, txn, keyOf(ndb, txn, TestEvents.idOf(article))));
+                assertFalse(range.matches(ndb, txn, keyOf(ndb, txn, TestEvents.idOf(above))));
+
+                try (Filter notes = Filter.builder().kinds(1).build()) {
+                    assertEquals(4, ndb.query(txn, List.of(range, notes), 10).size());
+                }
+            }
+            assertTrue(range.matches(article));
+            assertFalse(range.matches(below));
+            assertEquals(10_000, range.kinds().length);
+            assertThrows(IllegalArgumentException.class, () -> ndb.subscribe(range));
+
+            // The consuming API behaves the same
+            long filter = NostrdbNative.filterBuild(NostrdbNative.filterKindRange(NostrdbNative.filterNew(), 30000, 39999));
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(3, QueryResult.parseResults(
+                    NostrdbNative.query(ndb.ptr(), txn.ptr(), filter, 10)).size());
+            } finally {
+                NostrdbNative.filterDestroy(filter);
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A small kind range should expand into a kinds list")
+    void testKindRangeExpanded() {
+        try (Filter small = Filter.builder().kindRange(5, 7).build();
+             Filter single = Filter.builder().kindRange(1, 1).build()) {
+            assertArrayEquals(new int[]{5, 6, 7}, small.kinds());
+            assertArrayEquals(new int[]{1}, single.kinds());
+        }
+
+        Filter.Builder builder = Filter.builder();
+        assertThrows(IllegalArgumentException.class, () -> builder.kindRange(10, 9));
+        assertThrows(IllegalArgumentException.class, () -> builder.kindRange(-1, 9));
+        // A rejected range leaves the builder usable
+        builder.kindRange(0, Filter.Builder.KIND_RANGE_EXPAND_MAX - 1).build().close();
+
+        long ptr = NostrdbNative.filterNew();
+        assertThrows(IllegalArgumentException.class, () -> NostrdbNative.filterKindRange(ptr, 40000, 30000));
+        NostrdbNative.filterDestroy(NostrdbNative.filterBuild(ptr));
+    }
+
+    @Test
+    @DisplayName("Should reject negative and far-future since/until values")
+    void testTimestampValidation() {
+        long now = System.currentTimeMillis() / 1000;
+
+        Filter.Builder builder = Filter.builder();
+        assertThrows(IllegalArgumentException.class, () -> builder.since(-1));
+        assertThrows(IllegalArgumentException.class, () -> builder.until(-1));
+        // Milliseconds instead of seconds
+        assertThrows(IllegalArgumentException.class, () -> builder.until(System.currentTimeMillis()));
+        try (Filter filter = builder.since(0).until(now).build()) {
+            assertEquals(0, filter.since().getAsLong());
+            assertEquals(now, filter.until().getAsLong());
+        }
+
+        // A rejected value leaves the consumed-style pointer valid
+        long ptr = NostrdbNative.filterSince(NostrdbNative.filterNew(), 1_700_000_000L);
+        assertThrows(IllegalArgumentException.class, () -> NostrdbNative.filterUntil(ptr, -1));
+        long filter = NostrdbNative.filterBuild(ptr);
+        try {
+            assertEquals(1_700_000_000L, NostrdbNative.filterGetSince(filter));
+            assertEquals(-1, NostrdbNative.filterGetUntil(filter));
+        } finally {
+            NostrdbNative.filterDestroy(filter);
+        }
+    }
+
+    @Test
+    @DisplayName("The allowed distance into the future should be configurable")
+    void testTimestampSlack() {
+        long inAnHour = System.currentTimeMillis() / 1000 + 3600;
+        try {
+            Filter.setTimestampSlack(60);
+            assertThrows(IllegalArgumentException.class, () -> Filter.builder().until(inAnHour));
+            Filter.setTimestampSlack(7200);
+            Filter.builder().until(inAnHour).build().close();
+            assertThrows(IllegalArgumentException.class, () -> Filter.setTimestampSlack(-1));
+        } finally {
+            Filter.setTimestampSlack(Filter.DEFAULT_TIMESTAMP_SLACK_SECONDS);
+        }
+    }
+
+    @Test
+    @DisplayName("Should reject since after until at build time")
+    void testSinceAfterUntilRejected() {
+        Filter.Builder builder = Filter.builder().kinds(1).since(200).until(100);
+        NostrdbException e = assertThrows(NostrdbException.class, builder::build);
+        assertTrue(e.getMessage().contains("since (200) is after until (100)"), e.getMessage());
+        assertThrows(IllegalStateException.class, builder::build, "A failed build still consumes the builder");
+
+        assertThrows(NostrdbException.class, () -> Filter.fromJson("{\"since\":200,\"until\":100}"));
+
+        long ptr = NostrdbNative.filterUntil(NostrdbNative.filterSince(NostrdbNative.filterNew(), 200), 100);
+        assertThrows(NostrdbException.class, () -> NostrdbNative.filterBuild(ptr));
+
+        // Equal bounds are a valid one-second window
+        Filter.builder().since(100).until(100).build().close();
+    }
+
+    @Test
+    @DisplayName("Should reject empty filters only in strict mode")
+    void testStrictEmptyFilter() {
+        Filter.builder().build().close();
+        Filter.fromJson("{}").close();
+
+        NostrdbException e = assertThrows(NostrdbException.class, () -> Filter.builder().strict().build());
+        assertTrue(e.getMessage().contains("empty"), e.getMessage());
+        assertThrows(NostrdbException.class, () -> Filter.fromJson("{}", true));
+
+        long ptr = NostrdbNative.filterNew();
+        assertThrows(NostrdbException.class, () -> NostrdbNative.filterBuildStrict(ptr));
+
+        try (Filter filter = Filter.builder().strict().kinds(1).since(100).until(200).build();
+             Filter parsed = Filter.fromJson("{\"kinds\":[1]}", true)) {
+            assertArrayEquals(new int[]{1}, filter.kinds());
+            assertArrayEquals(new int[]{1}, parsed.kinds());
+        }
+    }
+
+    @Test
+    @DisplayName("Should reject filters with more elements than nostrdb supports")
+    void testTooManyElements() {
+        String id = "00".repeat(32);
+        String json = "{\"ids\":[\"" + id + "\"],\"authors\":[\"" + id + "\"],\"kinds\":[1],"
+            + "\"#t\":[\"nostr\"],\"since\":1,\"until\":2,\"limit\":3,\"search\":\"gm\"}";
+        NostrdbException e = assertThrows(NostrdbException.class, () -> Filter.fromJson(json));
+        assertTrue(e.getMessage().contains("8 elements"), e.getMessage());
+
+        // Seven elements is the limit
+        Filter.fromJson(json.replace(",\"search\":\"gm\"", "")).close();
+    }
+
+    @Test
+    @DisplayName("Should reject ids that are not 32 bytes")
+    void testInvalidIdLength() {
+        Filter.Builder builder = Filter.builder();
+        assertThrows(IllegalArgumentException.class, () -> builder.ids(new byte[31]));
+        assertThrows(IllegalArgumentException.class, () -> builder.ids("abcd"));
+        assertThrows(IllegalArgumentException.class, () -> builder.events(new byte[64], new byte[1]));
+        assertThrows(IllegalArgumentException.class, () -> builder.pubkeys(new byte[16]));
+        builder.ids(new byte[32]).build().close();
+
+        // The native layer validates packed arrays and keeps the builder on error
+        long ptr = NostrdbNative.filterNew();
+        assertThrows(IllegalArgumentException.class, () -> NostrdbNative.filterIds(ptr, new byte[33]));
+        NostrdbNative.filterDestroy(NostrdbNative.filterBuild(ptr));
+    }
+}
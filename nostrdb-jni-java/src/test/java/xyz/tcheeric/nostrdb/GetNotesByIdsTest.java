@@ -0,0 +1,72 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for fetching notes by event id in one batch.
+ */
+class GetNotesByIdsTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("Should return the stored notes in request order and mark which ids were found")
+    void testBatch() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            List<byte[]> ids = new ArrayList<>();
+            List<String> storedIds = new ArrayList<>();
+            for (int i = 0; i < 12; i++) {
+                String event = alice.textNote(1_700_000_000L + i, "note " + i);
+                String id = TestEvents.idOf(event);
+                if (i % 3 == 0) {
+                    // Never stored
+                    ids.add(HexUtil.decode(id));
+                    continue;
+                }
+                ndb.processEvent(event);
+                ids.add(HexUtil.decode(id));
+                storedIds.add(id);
+            }
+            assertEquals(8, TestEvents.awaitCount(ndb, notes, 8, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                NoteBatch batch = ndb.getNotesByIds(txn, ids);
+                assertEquals(8, batch.notes().size());
+                assertEquals(4, batch.skippedCount());
+                assertEquals(storedIds, batch.notes().stream().map(Note::id).toList());
+                for (int i = 0; i < ids.size(); i++) {
+                    assertEquals(i % 3 != 0, batch.isPresent(i), "id " + i);
+                }
+                for (int i = 0; i < batch.notes().size(); i++) {
+                    assertEquals(batch.keys()[i], ndb.getNoteKeyById(txn, batch.notes().get(i).id()).getAsLong());
+                }
+
+                // Every id missing: the bitmap is all clear
+                NoteBatch none = ndb.getNotesByIds(txn, List.of(new byte[32], new byte[32]));
+                assertTrue(none.notes().isEmpty());
+                assertEquals(2, none.skippedCount());
+                assertFalse(none.isPresent(0));
+                assertFalse(none.isPresent(1));
+
+                assertTrue(ndb.getNotesByIds(txn, List.of()).notes().isEmpty());
+
+                List<byte[]> bad = new ArrayList<>(ids);
+                bad.set(5, new byte[31]);
+                IllegalArgumentException e = assertThrows(IllegalArgumentException.class,
+                    () -> ndb.getNotesByIds(txn, bad));
+                assertTrue(e.getMessage().contains("index 5"), e.getMessage());
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.Collections;
+import java.util.List;
+import java.util.Random;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for fetching notes by key in one batch.
+ */
+class GetNotesByKeysTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static final int NOTES = 200;
+
+    @Test
+    @DisplayName("Should return notes in request order, skipping keys that don't resolve")
+    void testBatch() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            for (int i = 0; i < NOTES; i++) {
+                ndb.processEvent(alice.textNote(1_700_000_000L + i, "note " + i));
+            }
+            assertEquals(NOTES, TestEvents.awaitCount(ndb, notes, NOTES, 30_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<Long> stored = new ArrayList<>();
+                for (QueryResult result : ndb.query(txn, notes, NOTES)) {
+                    stored.add(result.noteKey());
+                }
+
+                // 1000 keys: every stored key (some repeated) in random order, plus bogus ones
+                List<Long> requested = new ArrayList<>();
+                for (int i = 0; i < 900; i++) {
+                    requested.add(stored.get(i % NOTES));
+                }
+                for (int i = 0; i < 100; i++) {
+                    requested.add(10_000_000L + i);
+                }
+                Collections.shuffle(requested, new Random(42));
+                long[] keys = requested.stream().mapToLong(Long::longValue).toArray();
+
+                NoteBatch batch = ndb.getNotesByKeys(txn, keys);
+                assertEquals(100, batch.skippedCount());
+                assertEquals(900, batch.notes().size());
+
+                long[] expected = requested.stream().filter(stored::contains).mapToLong(Long::longValue).toArray();
+                assertArrayEquals(expected, batch.keys());
+                for (int i = 0; i < keys.length; i++) {
+                    assertEquals(stored.contains(keys[i]), batch.isPresent(i));
+                }
+                for (int i = 0; i < expected.length; i++) {
+                    assertEquals(ndb.getNoteByKey(txn, expected[i]).orElseThrow().id(), batch.notes().get(i).id());
+                }
+
+                NoteBatch empty = ndb.getNotesByKeys(txn, new long[0]);
+                assertTrue(empty.notes().isEmpty());
+                assertEquals(0, empty.skippedCount());
+                assertThrows(IllegalArgumentException.class, () -> ndb.getNotesByKeys(txn, null));
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+import java.util.Optional;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for looking up several profiles in one call.
+ */
+class GetProfilesByPubkeysTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+    private final TestEvents carol = TestEvents.forSeed(13);
+
+    @Test
+    @DisplayName("Should return one entry per pubkey in input order, empty for unknown pubkeys")
+    void testBatch() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter profiles = Filter.builder().kinds(0).build()) {
+            ndb.processEvent(alice.event(0, T, "{\"name\":\"alice\"}"));
+            ndb.processEvent(bob.event(0, T, "{\"name\":\"bob\"}"));
+            assertEquals(2, TestEvents.awaitCount(ndb, profiles, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                byte[] a = HexUtil.decode(alice.pubkeyHex());
+                byte[] b = HexUtil.decode(bob.pubkeyHex());
+                byte[] c = HexUtil.decode(carol.pubkeyHex());
+
+                List<Optional<Profile>> found = ndb.getProfilesByPubkeys(txn, List.of(c, b, a, c, b));
+                assertEquals(5, found.size());
+                assertTrue(found.get(0).isEmpty());
+                assertEquals("bob", found.get(1).orElseThrow().name());
+                assertEquals("alice", found.get(2).orElseThrow().name());
+                assertTrue(found.get(3).isEmpty());
+                assertEquals("bob", found.get(4).orElseThrow().name());
+                assertEquals(ndb.getProfileByPubkey(txn, a).orElseThrow().noteKey(),
+                    found.get(2).orElseThrow().noteKey());
+
+                assertTrue(ndb.getProfilesByPubkeys(txn, List.of()).isEmpty());
+
+                IllegalArgumentException e = assertThrows(IllegalArgumentException.class,
+                    () -> ndb.getProfilesByPubkeys(txn, List.of(a, b, new byte[31])));
+                assertTrue(e.getMessage().contains("index 2"), e.getMessage());
+
+                // The native names the trailing partial pubkey too
+                e = assertThrows(IllegalArgumentException.class,
+                    () -> NostrdbNative.getProfilesByPubkeys(ndb.ptr(), txn.ptr(), new byte[70]));
+                assertTrue(e.getMessage().contains("index 2"), e.getMessage());
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests that native handles of one kind are rejected where another is expected.
+ */
+class HandleKindTest {
+
+    @TempDir
+    Path tempDir;
+
+    @Test
+    @DisplayName("Filter and FilterBuilder handles should not be interchangeable")
+    void testFilterHandles() {
+        long builder = NostrdbNative.filterNew();
+        long built = NostrdbNative.filterBuild(NostrdbNative.filterKinds(NostrdbNative.filterNew(), new byte[] {1, 0, 0, 0}));
+        try {
+            IllegalStateException e = assertThrows(IllegalStateException.class,
+                () -> NostrdbNative.filterKinds(built, new byte[] {1, 0, 0, 0}));
+            assertTrue(e.getMessage().contains("FilterBuilder"), e.getMessage());
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.filterSince(built, 1));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.filterBuild(built));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.filterDestroy(builder));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.filterToJson(builder));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.filterClone(builder));
+
+            // Both handles are still intact
+            assertTrue(NostrdbNative.filterToJson(built).contains("kinds"));
+        } finally {
+            NostrdbNative.filterDestroy(built);
+            NostrdbNative.filterDestroy(NostrdbNative.filterBuild(builder));
+        }
+    }
+
+    @Test
+    @DisplayName("Ndb, Transaction, Filter, and config handles should not be interchangeable")
+    void testDatabaseHandles() {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Transaction txn = ndb.beginTransaction();
+             Filter filter = Filter.builder().kinds(1).build();
+             NdbConfig config = new NdbConfig()) {
+            long ndbPtr = ndb.ptr();
+            long txnPtr = txn.ptr();
+            long filterPtr = filter.ptr();
+
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.beginTransaction(txnPtr));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.query(ndbPtr, ndbPtr, filterPtr, 10));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.query(ndbPtr, filterPtr, txnPtr, 10));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.query(txnPtr, txnPtr, filterPtr, 10));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.getNoteByKey(filterPtr, txnPtr, 1));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.endTransaction(filterPtr));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.filterDestroy(txnPtr));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.configSetMapSize(ndbPtr, 1 << 20));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.configDestroy(filterPtr));
+            assertThrows(IllegalStateException.class, () ->
+                NostrdbNative.ndbOpen(tempDir.resolve("other").toString(), txnPtr, true));
+            assertThrows(IllegalStateException.class, () ->
+                NostrdbNative.ndbOpen(tempDir.resolve("other").toString(), filterPtr, true));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.filterToJson(config.ptr()));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.queryNext(txnPtr, 10));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.queryEnd(filterPtr));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.queryBegin(ndbPtr, filterPtr, filterPtr));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.cancelTokenCancel(filterPtr));
+            assertThrows(IllegalStateException.class, () -> NostrdbNative.cancelTokenFree(txnPtr));
+            assertThrows(IllegalStateException.class, () ->
+                NostrdbNative.queryCancellable(ndbPtr, txnPtr, filterPtr, 10, filterPtr));
+
+            // A non-Ndb handle is not an open database, and closing it does nothing
+            assertFalse(NostrdbNative.ndbIsOpen(txnPtr));
+            NostrdbNative.ndbClose(filterPtr);
+
+            // Everything still works after the rejected calls
+            assertTrue(ndb.query(txn, filter, 10).isEmpty());
+        }
+    }
+}
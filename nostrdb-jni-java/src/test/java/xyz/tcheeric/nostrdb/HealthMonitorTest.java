@@ -0,0 +1,93 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+import java.util.concurrent.CopyOnWriteArrayList;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for health monitoring callbacks.
+ */
+class HealthMonitorTest {
+
+    @TempDir
+    Path tempDir;
+
+    @Test
+    @DisplayName("Filling a tiny map should report nearly full, full, and a stalled backlog once each")
+    void testTinyMapFillsUp() throws InterruptedException {
+        TestEvents author = TestEvents.forSeed(1);
+        String padding = "x".repeat(16_000);
+        List<Integer> mapEvents = new CopyOnWriteArrayList<>();
+        List<Integer> backlogEvents = new CopyOnWriteArrayList<>();
+        HealthCallback callback = (code, value) ->
+            (code <= HealthCallback.MAP_RECOVERED ? mapEvents : backlogEvents).add(code);
+
+        try (NdbConfig config = new NdbConfig().mapSize(4 * 1024 * 1024);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config)) {
+            ndb.setHealthThresholds(90, 50);
+            ndb.setHealthCallback(callback, 10);
+
+            // One note at a time, so the map fills a page run at a time
+            long deadline = System.currentTimeMillis() + 60_000;
+            int n = 0;
+            while (!mapEvents.contains(HealthCallback.MAP_FULL) && System.currentTimeMillis() < deadline) {
+                String note = author.textNote(1_700_000_000L + n, "note " + n + " " + padding);
+                ndb.processEvent(note);
+                ndb.waitForIngestion(HexUtil.decode(TestEvents.idOf(note)), 1_000);
+                n++;
+            }
+            assertEquals(List.of(HealthCallback.MAP_NEARLY_FULL, HealthCallback.MAP_FULL), mapEvents);
+
+            // The full map stalls the writer: accepted events are never stored
+            StringBuilder stalled = new StringBuilder();
+            for (int i = 0; i < 100; i++) {
+                stalled.append(author.textNote(1_800_000_000L + i, "stalled " + i + " " + padding)).append('\n');
+            }
+            ndb.processEvents(stalled.toString());
+
+            // Dropped events stop counting once ingestion has been idle for a second
+            deadline = System.currentTimeMillis() + 10_000;
+            while (backlogEvents.size() < 2 && System.currentTimeMillis() < deadline) {
+                Thread.sleep(10);
+            }
+            // Let several more checks run
+            Thread.sleep(200);
+        }
+
+        assertEquals(List.of(HealthCallback.MAP_NEARLY_FULL, HealthCallback.MAP_FULL), mapEvents);
+        assertEquals(List.of(HealthCallback.INGEST_BACKLOG_HIGH, HealthCallback.BACKLOG_RECOVERED), backlogEvents);
+    }
+
+    @Test
+    @DisplayName("No callbacks should be delivered after close")
+    void testNoCallbacksAfterClose() throws InterruptedException {
+        List<Integer> events = new CopyOnWriteArrayList<>();
+        Ndb ndb = Ndb.open(tempDir.resolve("db"));
+        ndb.setHealthThresholds(0);
+        ndb.setHealthCallback((code, value) -> events.add(code), 10);
+        ndb.close();
+
+        int afterClose = events.size();
+        Thread.sleep(100);
+        assertEquals(afterClose, events.size());
+    }
+
+    @Test
+    @DisplayName("Should validate health monitor arguments")
+    void testInvalidArguments() {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            assertThrows(IllegalArgumentException.class, () -> ndb.setHealthThresholds(101));
+            assertThrows(IllegalArgumentException.class, () -> ndb.setHealthThresholds(-1));
+            assertThrows(IllegalArgumentException.class, () -> ndb.setHealthThresholds(90, 0));
+            assertThrows(IllegalArgumentException.class, () -> ndb.setHealthCallback((c, v) -> {}, 0));
+
+            // Clearing a callback that was never set is a no-op
+            ndb.setHealthCallback(null, 0);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.io.ByteArrayOutputStream;
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.List;
+import java.util.concurrent.ExecutorService;
+import java.util.concurrent.Executors;
+import java.util.concurrent.Future;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for the running ingestion totals of a database handle.
+ */
+class IngestStatsTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /** An unsigned note with a unique id, stored with validation skipped. */
+    private String unsignedNote(int i) {
+        return "{\"id\":\"" + String.format("%064x", i + 1) + "\",\"pubkey\":\"" + alice.pubkeyHex()
+            + "\",\"created_at\":" + (T + i) + ",\"kind\":1,\"tags\":[],"
+            + "\"content\":\"note " + i + "\",\"sig\":\"" + "0".repeat(128) + "\"}";
+    }
+
+    private static long utf8Length(String json) {
+        return json.getBytes(StandardCharsets.UTF_8).length;
+    }
+
+    @Test
+    @DisplayName("Every ingestion method should add to the totals")
+    void testMixedIngestion() throws Exception {
+        String first = alice.textNote(T, "first \u00e9");
+        String second = alice.textNote(T + 1, "second");
+        String third = alice.textNote(T + 2, "third");
+        String relayed = "[\"EVENT\",\"sub1\"," + alice.textNote(T + 3, "relayed") + "]";
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            IngestStats empty = ndb.getIngestStats();
+            assertEquals(0, empty.total());
+            assertEquals(0, empty.bytes());
+
+            // 1 accepted
+            ndb.processEvent(first);
+            assertTrue(ndb.waitForIngestion(HexUtil.decode(TestEvents.idOf(first)), 10_000));
+            // 1 duplicate, 1 rejected
+            assertEquals(Ndb.PROCESS_DUPLICATE, ndb.processEventWithResult(first));
+            assertEquals(Ndb.PROCESS_REJECTED, ndb.processEventWithResult("not json"));
+
+            // 1 accepted, 1 rejected (not UTF-8); the blank line is not counted
+            ByteArrayOutputStream batch = new ByteArrayOutputStream();
+            batch.write((second + "\n\n").getBytes(StandardCharsets.UTF_8));
+            batch.write(new byte[] {'{', (byte) 0xff, '}', '\n'});
+            assertEquals(1, ndb.processEventsBytes(batch.toByteArray()));
+
+            // 1 accepted, 1 duplicate (repeated line), 1 rejected (checked natively)
+            IngestReport report = ndb.processEventsDetailed(String.join("\n", third, third, "{\"id\": 42}"));
+            assertEquals(1, report.succeeded());
+
+            // 1 accepted; EOSE is not an event and is not counted
+            RelayMessageCounts counts = ndb.processRelayEvents(relayed + "\n[\"EOSE\",\"sub1\"]");
+            assertEquals(1, counts.events());
+
+            IngestStats stats = ndb.getIngestStats();
+            assertEquals(4, stats.accepted(), stats.toString());
+            assertEquals(2, stats.duplicates(), stats.toString());
+            assertEquals(3, stats.rejected(), stats.toString());
+            assertEquals(9, stats.total());
+            assertEquals(utf8Length(first) + utf8Length(second) + utf8Length(third) + utf8Length(relayed),
+                stats.bytes());
+        }
+    }
+
+    @Test
+    @DisplayName("Totals should stay exact when several threads ingest at once")
+    void testConcurrentIngestion() throws Exception {
+        int threads = 4;
+        int perThread = 500;
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config)) {
+            ExecutorService pool = Executors.newFixedThreadPool(threads);
+            long expectedBytes = 0;
+            List<Future<?>> done = new ArrayList<>();
+            try {
+                for (int t = 0; t < threads; t++) {
+                    int first = t * perThread;
+                    for (int i = first; i < first + perThread; i++) {
+                        expectedBytes += utf8Length(unsignedNote(i));
+                    }
+                    done.add(pool.submit(() -> {
+                        for (int i = first; i < first + perThread; i++) {
+                            ndb.processEventsBytes((unsignedNote(i) + "\n").getBytes(StandardCharsets.UTF_8));
+                        }
+                    }));
+                }
+                for (Future<?> f : done) {
+                    f.get();
+                }
+            } finally {
+                pool.shutdown();
+            }
+
+            IngestStats stats = ndb.getIngestStats();
+            assertEquals((long) threads * perThread, stats.accepted());
+            assertEquals(0, stats.duplicates());
+            assertEquals(0, stats.rejected());
+            assertEquals(expectedBytes, stats.bytes());
+        }
+    }
+
+    @Test
+    @DisplayName("resetIngestStats should set every total back to zero")
+    void testReset() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            ndb.processEvent(alice.textNote(T, "hello"));
+            assertEquals(Ndb.PROCESS_REJECTED, ndb.processEventWithResult("not json"));
+            assertEquals(2, ndb.getIngestStats().total());
+
+            ndb.resetIngestStats();
+            IngestStats stats = ndb.getIngestStats();
+            assertEquals(0, stats.total());
+            assertEquals(0, stats.bytes());
+
+            ndb.processEvent(alice.textNote(T + 1, "again"));
+            assertEquals(1, ndb.getIngestStats().accepted());
+        }
+    }
+}
@@ -0,0 +1,136 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for notes whose stored content is not valid UTF-8.
+ */
+class InvalidContentTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /**
+     * A note whose content nostrdb stores as invalid UTF-8.
+     *
+     * <p>Events reach nostrdb as Java strings, which can't carry raw bytes such
+     * as 0xFF; an unpaired surrogate escape is stored as the bytes it encodes
+     * to, which aren't valid UTF-8 either. The id no longer matches, so the
+     * database must skip validation.
+     */
+    private String brokenNote() {
+        return alice.textNote(T + 1, "before BROKEN after").replace("BROKEN", "\\udfff");
+    }
+
+    @Test
+    @DisplayName("Queries over a note with invalid UTF-8 should return it with replacement characters")
+    void testLossy() throws Exception {
+        String valid = alice.textNote(T, "valid");
+        String broken = brokenNote();
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(valid);
+            ndb.processEvent(broken);
+            assertEquals(2, TestEvents.awaitCount(ndb, notes, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<Note> all = ndb.queryNotes(txn, notes, 10);
+                assertEquals(2, all.size());
+                assertEquals(2, ndb.queryNotesCbor(txn, notes, 10).size());
+
+                Note note = ndb.getNoteById(txn, TestEvents.idOf(broken)).orElseThrow();
+                assertTrue(note.content().startsWith("before "), note.content());
+                assertTrue(note.content().endsWith(" after"), note.content());
+                assertTrue(note.content().contains("\ufffd"), note.content());
+                assertTrue(all.stream().anyMatch(n -> n.content().equals(note.content())));
+
+                long key = ndb.getNoteKeyById(txn, TestEvents.idOf(broken)).orElseThrow();
+                byte[] content = ndb.getNoteContentBytes(txn, key).orElseThrow();
+                assertEquals(note.content(), new String(content, StandardCharsets.UTF_8));
+                assertEquals(content.length, ndb.getNoteContentLength(txn, key).orElseThrow());
+                assertEquals(note.content(), ndb.getNoteContent(txn, key).orElseThrow());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Search and content blocks should work on the repaired content")
+    void testSearchAndBlocksLossy() throws Exception {
+        String broken = alice.textNote(T + 2, "before BROKEN after #tagged").replace("BROKEN", "\\udfff");
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter search = Filter.builder().kinds(1).search("after").build()) {
+            ndb.processEvent(broken);
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                long key = ndb.getNoteKeyById(txn, TestEvents.idOf(broken)).orElseThrow();
+                String content = ndb.getNoteContent(txn, key).orElseThrow();
+                assertTrue(content.contains("\ufffd"), content);
+
+                assertTrue(search.matches(ndb, txn, key));
+                List<SearchHit> hits = ndb.searchNotes(txn, "after", 10, SearchOrder.NEWEST_FIRST);
+                assertEquals(1, hits.size());
+                assertEquals("after", hits.get(0).matchIn(content));
+
+                // Offsets are into the repaired content, so the blocks still cover it
+                List<ContentBlock> blocks = ndb.getNoteBlocks(txn, key).orElseThrow();
+                StringBuilder rendered = new StringBuilder();
+                blocks.forEach(block -> rendered.append(block.textIn(content)));
+                assertEquals(content, rendered.toString());
+                ContentBlock last = blocks.get(blocks.size() - 1);
+                assertEquals(BlockType.HASHTAG, last.type());
+                assertEquals("#tagged", last.textIn(content));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Strict mode should report the note with invalid UTF-8 by key")
+    void testStrict() throws Exception {
+        String valid = alice.textNote(T, "valid");
+        String broken = brokenNote();
+        try (NdbConfig config = new NdbConfig().skipValidation(true).strictUtf8(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(valid);
+            ndb.processEvent(broken);
+            assertEquals(2, TestEvents.awaitCount(ndb, notes, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                long key = ndb.getNoteKeyById(txn, TestEvents.idOf(broken)).orElseThrow();
+
+                InvalidContentException e = assertThrows(InvalidContentException.class,
+                    () -> ndb.queryNotes(txn, notes, 10));
+                assertEquals(key, e.noteKey());
+                assertTrue(e.getMessage().contains(Long.toString(key)), e.getMessage());
+
+                assertEquals(key, assertThrows(InvalidContentException.class,
+                    () -> ndb.getNoteByKey(txn, key)).noteKey());
+                assertEquals(key, assertThrows(InvalidContentException.class,
+                    () -> ndb.getNoteContentBytes(txn, key)).noteKey());
+                assertEquals(key, assertThrows(InvalidContentException.class,
+                    () -> ndb.getNoteBlocks(txn, key)).noteKey());
+                try (Filter search = Filter.builder().kinds(1).search("after").build()) {
+                    assertEquals(key, assertThrows(InvalidContentException.class,
+                        () -> search.matches(ndb, txn, key)).noteKey());
+                }
+
+                assertEquals("valid", ndb.getNoteById(txn, TestEvents.idOf(valid)).orElseThrow().content());
+            }
+        }
+    }
+}
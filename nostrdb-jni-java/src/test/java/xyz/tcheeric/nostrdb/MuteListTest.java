@@ -0,0 +1,111 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+import java.util.Set;
+import java.util.stream.Collectors;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for reading stored mute lists and querying with them.
+ */
+class MuteListTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+    private final TestEvents carol = TestEvents.forSeed(13);
+    private final TestEvents dave = TestEvents.forSeed(14);
+
+    private static Set<String> ids(Ndb ndb, Transaction txn, List<QueryResult> results) {
+        return results.stream()
+            .map(result -> ndb.getNoteByKey(txn, result.noteKey()).orElseThrow().id())
+            .collect(Collectors.toSet());
+    }
+
+    @Test
+    @DisplayName("Notes muted by the newest mute list should vanish from the query")
+    void testMuteList() throws Exception {
+        String fromBob = bob.textNote(T, "hello from bob");
+        String fromCarol = carol.textNote(T + 1, "hello from carol");
+        String mutedEvent = dave.textNote(T + 2, "muted event");
+        String tagged = dave.event(1, T + 3, "tagged", new String[] {"t", "spam"});
+        String wordy = dave.textNote(T + 4, "Buy CRYPTO now");
+        String fromDave = dave.textNote(T + 5, "hello from dave");
+
+        String older = alice.event(10000, T, "", new String[] {"p", carol.pubkeyHex()});
+        String newest = alice.event(10000, T + 10, "encrypted?iv=private",
+            new String[] {"p", bob.pubkeyHex()},
+            new String[] {"e", TestEvents.idOf(mutedEvent)},
+            new String[] {"t", "Spam"},
+            new String[] {"word", "crypto"},
+            new String[] {"p", bob.pubkeyHex()},
+            new String[] {"p", "not hex"},
+            new String[] {"word", ""});
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter lists = Filter.builder().kinds(10000).build()) {
+            for (String event : List.of(fromBob, fromCarol, mutedEvent, tagged, wordy, fromDave, older, newest)) {
+                ndb.processEvent(event);
+            }
+            assertEquals(6, TestEvents.awaitCount(ndb, notes, 6, 10_000));
+            assertEquals(2, TestEvents.awaitCount(ndb, lists, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                byte[] author = HexUtil.decode(alice.pubkeyHex());
+
+                MuteList mutes = ndb.getMuteList(txn, author).orElseThrow();
+                assertEquals(List.of(bob.pubkeyHex()), mutes.pubkeys().stream().map(HexUtil::encode).toList());
+                assertEquals(List.of(TestEvents.idOf(mutedEvent)),
+                    mutes.eventIds().stream().map(HexUtil::encode).toList());
+                assertEquals(List.of("Spam"), mutes.hashtags());
+                assertEquals(List.of("crypto"), mutes.words());
+                assertTrue(mutes.hasPrivateEntries());
+
+                List<QueryResult> visible = ndb.queryExcludingMuteList(txn, notes, 50, author);
+                assertEquals(Set.of(TestEvents.idOf(fromCarol), TestEvents.idOf(fromDave)), ids(ndb, txn, visible));
+                assertEquals(1, ndb.queryExcludingMuteList(txn, notes, 1, author).size());
+
+                byte[] unmuted = HexUtil.decode(dave.pubkeyHex());
+                assertTrue(ndb.getMuteList(txn, unmuted).isEmpty());
+                assertEquals(6, ndb.queryExcludingMuteList(txn, notes, 50, unmuted).size());
+                assertThrows(IllegalArgumentException.class,
+                    () -> ndb.queryExcludingMuteList(txn, notes, 50, new byte[31]));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A muted word should hide a note whose content is not valid UTF-8, even in strict mode")
+    void testMutedWordInvalidContent() throws Exception {
+        // An unpaired surrogate escape is stored as invalid UTF-8; see InvalidContentTest
+        String broken = dave.textNote(T, "Buy BROKEN CRYPTO now").replace("BROKEN", "\\udfff");
+        String clean = dave.textNote(T + 1, "hello from dave");
+        String list = alice.event(10000, T + 10, "", new String[] {"word", "crypto"});
+
+        try (NdbConfig config = new NdbConfig().skipValidation(true).strictUtf8(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter lists = Filter.builder().kinds(10000).build()) {
+            for (String event : List.of(broken, clean, list)) {
+                ndb.processEvent(event);
+            }
+            assertEquals(2, TestEvents.awaitCount(ndb, notes, 2, 10_000));
+            assertEquals(1, TestEvents.awaitCount(ndb, lists, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> visible = ndb.queryExcludingMuteList(txn, notes, 50, HexUtil.decode(alice.pubkeyHex()));
+                assertEquals(Set.of(TestEvents.idOf(clean)), ids(ndb, txn, visible));
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.List;
+import java.util.Set;
+import java.util.stream.Collectors;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for queries that exclude muted authors and events natively.
+ */
+class MuteQueryTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+    private final TestEvents carol = TestEvents.forSeed(13);
+
+    private static Set<String> authors(Ndb ndb, Transaction txn, List<QueryResult> results) {
+        return results.stream()
+            .map(result -> ndb.getNoteByKey(txn, result.noteKey()).orElseThrow().pubkey())
+            .collect(Collectors.toSet());
+    }
+
+    private static byte[] id(String eventJson) {
+        return HexUtil.decode(TestEvents.idOf(eventJson));
+    }
+
+    @Test
+    @DisplayName("A muted author's notes should never appear and the limit should still be filled")
+    void testMutedAuthorTimeline() throws Exception {
+        List<String> events = new ArrayList<>();
+        // The newest 20 notes are all from the muted author
+        for (int i = 0; i < 20; i++) {
+            events.add(bob.textNote(1_700_000_100L + i, "bob " + i));
+        }
+        for (int i = 0; i < 10; i++) {
+            events.add(alice.textNote(1_700_000_000L + i, "alice " + i));
+            events.add(carol.textNote(1_700_000_050L + i, "carol " + i));
+        }
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter timeline = Filter.builder().kinds(1).build()) {
+            for (String event : events) {
+                ndb.processEvent(event);
+            }
+            assertEquals(40, TestEvents.awaitCount(ndb, timeline, 40, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> visible = ndb.queryExcluding(txn, timeline, 15, List.of(bob.pubkey()), null, false);
+                assertEquals(15, visible.size());
+                assertFalse(authors(ndb, txn, visible).contains(bob.pubkeyHex()));
+
+                List<QueryResult> all = ndb.queryExcluding(txn, timeline, Ndb.FILTER_LIMIT,
+                    List.of(bob.pubkey()), List.of(), false);
+                assertEquals(20, all.size());
+                assertEquals(Set.of(alice.pubkeyHex(), carol.pubkeyHex()), authors(ndb, txn, all));
+
+                // Nothing muted behaves like a plain query
+                assertEquals(ndb.query(txn, timeline, 25), ndb.queryExcluding(txn, timeline, 25, null, null, false));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Should exclude muted events and, with thread muting, notes referencing them")
+    void testMutedIdsAndThreads() throws Exception {
+        String root = alice.textNote(1_700_000_001L, "root");
+        String reply = carol.event(1, 1_700_000_002L, "reply", new String[] {"e", TestEvents.idOf(root)});
+        String mention = carol.event(1, 1_700_000_003L, "hi bob", new String[] {"p", bob.pubkeyHex()});
+        String unrelated = carol.textNote(1_700_000_004L, "unrelated");
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            for (String event : List.of(root, reply, mention, unrelated)) {
+                ndb.processEvent(event);
+            }
+            assertEquals(4, TestEvents.awaitCount(ndb, notes, 4, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<byte[]> mutedIds = List.of(id(root));
+                List<byte[]> mutedAuthors = List.of(bob.pubkey());
+
+                assertEquals(3, ndb.queryExcluding(txn, notes, 10, mutedAuthors, mutedIds, false).size(),
+                    "Without thread muting only the muted event itself is excluded");
+
+                List<QueryResult> threads = ndb.queryExcluding(txn, notes, 10, mutedAuthors, mutedIds, true);
+                assertEquals(1, threads.size());
+                assertEquals("unrelated", ndb.getNoteByKey(txn, threads.get(0).noteKey()).orElseThrow().content());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Should validate muted values and the limit")
+    void testValidation() {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Transaction txn = ndb.beginTransaction()) {
+            assertThrows(IllegalArgumentException.class, () ->
+                ndb.queryExcluding(txn, notes, 10, List.of(new byte[31]), null, false));
+            assertThrows(IllegalArgumentException.class, () ->
+                ndb.queryExcluding(txn, notes, 0, null, null, false));
+            assertThrows(IllegalArgumentException.class, () ->
+                NostrdbNative.queryExcluding(ndb.ptr(), txn.ptr(), notes.ptr(), 10, new byte[33], null, false));
+            assertTrue(ndb.queryExcluding(txn, notes, 10, null, null, true).isEmpty());
+        }
+    }
+}
@@ -0,0 +1,63 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Files;
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for database handle introspection.
+ */
+class NdbLifecycleTest {
+
+    @TempDir
+    Path tempDir;
+
+    @Test
+    @DisplayName("Should report open state and path until closed")
+    void testOpenStateAndPath() throws Exception {
+        Path dbPath = tempDir.resolve("db");
+        Ndb ndb = Ndb.open(dbPath);
+
+        assertFalse(ndb.isClosed());
+        assertEquals(dbPath.toRealPath(), ndb.path());
+        assertTrue(ndb.toString().contains(dbPath.toRealPath().toString()));
+
+        ndb.close();
+        assertTrue(ndb.isClosed());
+        assertThrows(IllegalStateException.class, ndb::path);
+        assertEquals("Ndb{closed}", ndb.toString());
+
+        // A second close is a no-op
+        ndb.close();
+        assertTrue(ndb.isClosed());
+    }
+
+    @Test
+    @DisplayName("Should resolve the path to its canonical form")
+    void testCanonicalPath() throws Exception {
+        Path real = Files.createDirectories(tempDir.resolve("real"));
+        Path link = Files.createSymbolicLink(tempDir.resolve("link"), real);
+
+        try (Ndb ndb = Ndb.open(link.resolve("db"))) {
+            assertEquals(real.toRealPath().resolve("db"), ndb.path());
+        }
+    }
+
+    @Test
+    @DisplayName("Stale native pointers should report closed instead of crashing")
+    void testStalePointer() {
+        Ndb ndb = Ndb.open(tempDir.resolve("db"));
+        long ptr = ndb.ptr();
+        assertTrue(NostrdbNative.ndbIsOpen(ptr));
+
+        ndb.close();
+        assertFalse(NostrdbNative.ndbIsOpen(ptr));
+        assertFalse(NostrdbNative.ndbIsOpen(0));
+        // Closing a stale pointer again must not double-free
+        NostrdbNative.ndbClose(ptr);
+    }
+}
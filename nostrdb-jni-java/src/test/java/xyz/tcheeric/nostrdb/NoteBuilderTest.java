@@ -0,0 +1,104 @@
+package xyz.tcheeric.nostrdb;
+
+import com.fasterxml.jackson.databind.JsonNode;
+import com.fasterxml.jackson.databind.ObjectMapper;
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for building and signing notes natively.
+ */
+class NoteBuilderTest {
+
+    private static final ObjectMapper MAPPER = new ObjectMapper();
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static final long T = 1_700_000_000L;
+
+    @Test
+    @DisplayName("A signed note should verify, match the fixture's id, and be ingested")
+    void testSignAndIngest() throws Exception {
+        String rootId = TestEvents.idOf(alice.textNote(T, "root"));
+        String content = "caf\u00e9 \ud83d\ude80 \"quoted\"\nline";
+        String[][] tags = {{"t", "nostr"}, {"e", rootId, "", "root"}, {"p", alice.pubkeyHex()}};
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             NoteBuilder builder = new NoteBuilder()) {
+            String json = builder.kind(1)
+                .content(content)
+                .createdAt(T + 1)
+                .tag("t", "nostr")
+                .startTag().tagStr("e").tagId(HexUtil.decode(rootId)).tagStr("").tagStr("root")
+                .tag("p", alice.pubkeyHex())
+                .sign(alice.secretKey(), ndb);
+
+            JsonNode event = MAPPER.readTree(json);
+            String id = event.get("id").asText();
+            assertEquals(alice.pubkeyHex(), event.get("pubkey").asText());
+            assertEquals(T + 1, event.get("created_at").asLong());
+            assertEquals(content, event.get("content").asText());
+            assertEquals(MAPPER.readTree(MAPPER.writeValueAsString(tags)), event.get("tags"));
+
+            // The same event as the fixture signs, apart from the signature's nonce
+            assertEquals(TestEvents.idOf(alice.event(1, T + 1, content, tags)), id);
+            assertTrue(TestEvents.verify(alice.pubkey(), HexUtil.decode(id),
+                HexUtil.decode(event.get("sig").asText())));
+
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+            try (Transaction txn = ndb.beginTransaction()) {
+                Note stored = ndb.getNoteById(txn, id).orElseThrow();
+                assertEquals(content, stored.content());
+                assertEquals(event.get("sig").asText(), stored.sig());
+            }
+
+            assertThrows(IllegalStateException.class, () -> builder.content("again"));
+            assertThrows(IllegalStateException.class, () -> builder.sign(alice.secretKey()));
+        }
+    }
+
+    @Test
+    @DisplayName("Defaults should give a kind 1 note stamped when signed")
+    void testDefaults() throws Exception {
+        long before = System.currentTimeMillis() / 1000;
+        JsonNode event;
+        try (NoteBuilder builder = new NoteBuilder()) {
+            event = MAPPER.readTree(builder.sign(alice.secretKey()));
+        }
+        long after = System.currentTimeMillis() / 1000;
+
+        assertEquals(1, event.get("kind").asInt());
+        assertEquals("", event.get("content").asText());
+        assertEquals(0, event.get("tags").size());
+        long createdAt = event.get("created_at").asLong();
+        assertTrue(createdAt >= before && createdAt <= after, "created_at " + createdAt);
+        assertTrue(TestEvents.verify(alice.pubkey(), HexUtil.decode(event.get("id").asText()),
+            HexUtil.decode(event.get("sig").asText())));
+    }
+
+    @Test
+    @DisplayName("Invalid input should be rejected")
+    void testInvalid() {
+        try (NoteBuilder builder = new NoteBuilder()) {
+            assertThrows(IllegalStateException.class, () -> builder.tagStr("t"));
+            assertThrows(IllegalArgumentException.class, () -> builder.kind(-1));
+            assertThrows(IllegalArgumentException.class, () -> builder.createdAt(-1));
+            assertThrows(IllegalArgumentException.class, () -> builder.startTag().tagId(new byte[31]));
+            assertThrows(IllegalArgumentException.class, () -> builder.sign(new byte[31]));
+        }
+
+        // A zero key is not a valid secret key; the builder is used up either way
+        try (NoteBuilder builder = new NoteBuilder()) {
+            assertThrows(IllegalArgumentException.class, () -> builder.sign(new byte[32]));
+            assertThrows(IllegalStateException.class, () -> builder.sign(alice.secretKey()));
+        }
+    }
+}
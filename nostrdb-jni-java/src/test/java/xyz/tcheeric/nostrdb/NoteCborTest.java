@@ -0,0 +1,180 @@
+package xyz.tcheeric.nostrdb;
+
+import com.fasterxml.jackson.databind.JsonNode;
+import com.fasterxml.jackson.databind.ObjectMapper;
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.io.ByteArrayInputStream;
+import java.io.EOFException;
+import java.io.IOException;
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.HashSet;
+import java.util.Iterator;
+import java.util.LinkedHashMap;
+import java.util.List;
+import java.util.Map;
+import java.util.Set;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for fetching notes as CBOR.
+ */
+class NoteCborTest {
+
+    private static final ObjectMapper MAPPER = new ObjectMapper();
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /**
+     * Decode one CBOR data item: maps, arrays (definite or indefinite), byte
+     * and text strings, and unsigned integers, which is all a note uses.
+     */
+    private static Object decodeCbor(byte[] data) throws IOException {
+        ByteArrayInputStream in = new ByteArrayInputStream(data);
+        Object value = readItem(in);
+        assertEquals(0, in.available(), "trailing bytes after CBOR item");
+        return value;
+    }
+
+    private static final Object BREAK = new Object();
+
+    private static Object readItem(ByteArrayInputStream in) throws IOException {
+        int initial = readByte(in);
+        if (initial == 0xff) {
+            return BREAK;
+        }
+        int major = initial >> 5;
+        int info = initial & 0x1f;
+        boolean indefinite = info == 31;
+        long length = indefinite ? -1 : readArgument(in, info);
+        switch (major) {
+            case 0:
+                return length;
+            case 2:
+                return in.readNBytes((int) length);
+            case 3:
+                return new String(in.readNBytes((int) length), StandardCharsets.UTF_8);
+            case 4: {
+                List<Object> list = new ArrayList<>();
+                for (long i = 0; indefinite || i < length; i++) {
+                    Object item = readItem(in);
+                    if (item == BREAK) {
+                        break;
+                    }
+                    list.add(item);
+                }
+                return list;
+            }
+            case 5: {
+                Map<Object, Object> map = new LinkedHashMap<>();
+                for (long i = 0; indefinite || i < length; i++) {
+                    Object key = readItem(in);
+                    if (key == BREAK) {
+                        break;
+                    }
+                    map.put(key, readItem(in));
+                }
+                return map;
+            }
+            default:
+                throw new IOException("Unexpected CBOR major type " + major);
+        }
+    }
+
+    private static long readArgument(ByteArrayInputStream in, int info) throws IOException {
+        if (info < 24) {
+            return info;
+        }
+        int bytes = switch (info) {
+            case 24 -> 1;
+            case 25 -> 2;
+            case 26 -> 4;
+            case 27 -> 8;
+            default -> throw new IOException("Unexpected CBOR additional info " + info);
+        };
+        long value = 0;
+        for (int i = 0; i < bytes; i++) {
+            value = (value << 8) | readByte(in);
+        }
+        return value;
+    }
+
+    private static int readByte(ByteArrayInputStream in) throws IOException {
+        int b = in.read();
+        if (b < 0) {
+            throw new EOFException("Truncated CBOR");
+        }
+        return b;
+    }
+
+    /** Check a CBOR note against the JSON of the same note, field by field. */
+    @SuppressWarnings("unchecked")
+    private static void assertSameNote(String json, byte[] cbor) throws IOException {
+        JsonNode expected = MAPPER.readTree(json);
+        Map<Object, Object> note = (Map<Object, Object>) decodeCbor(cbor);
+
+        Set<Object> jsonKeys = new HashSet<>();
+        for (Iterator<String> names = expected.fieldNames(); names.hasNext(); ) {
+            jsonKeys.add(names.next());
+        }
+        assertEquals(jsonKeys, note.keySet());
+
+        assertEquals(expected.get("content").asText(), note.get("content"));
+        assertEquals(expected.get("created_at").asLong(), note.get("created_at"));
+        assertEquals(expected.get("kind").asLong(), note.get("kind"));
+        assertArrayEquals(HexUtil.decode(expected.get("id").asText()), (byte[]) note.get("id"));
+        assertArrayEquals(HexUtil.decode(expected.get("pubkey").asText()), (byte[]) note.get("pubkey"));
+        assertArrayEquals(HexUtil.decode(expected.get("sig").asText()), (byte[]) note.get("sig"));
+        assertEquals(MAPPER.readValue(expected.get("tags").toString(), List.class), note.get("tags"));
+    }
+
+    @Test
+    @DisplayName("CBOR notes should match their JSON field by field")
+    void testMatchesJson() throws Exception {
+        String root = alice.textNote(1_700_000_000L, "root");
+        String[] events = {
+            root,
+            alice.event(1, 1_700_000_001L, "caf\u00e9 \ud83d\ude80 \"quoted\"\n" + "x".repeat(300),
+                new String[] {"e", TestEvents.idOf(root), "", "root"}, new String[] {"p", alice.pubkeyHex()},
+                new String[] {"t", "cbor"}),
+            alice.event(30023, 1_700_000_002L, "", new String[] {"d", ""}),
+        };
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1, 30023).build();
+             Filter none = Filter.builder().kinds(7).build()) {
+            for (String event : events) {
+                ndb.processEvent(event);
+            }
+            assertEquals(3, TestEvents.awaitCount(ndb, all, 3, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> results = ndb.query(txn, all, 10);
+                List<byte[]> batch = ndb.queryNotesCbor(txn, all, 10);
+                assertEquals(results.size(), batch.size());
+
+                for (int i = 0; i < results.size(); i++) {
+                    long key = results.get(i).noteKey();
+                    String json = ndb.getNoteJsonByKey(txn, key).orElseThrow();
+                    byte[] cbor = ndb.getNoteByKeyCbor(txn, key).orElseThrow();
+                    assertSameNote(json, cbor);
+                    assertArrayEquals(cbor, batch.get(i));
+
+                    byte[] id = ndb.getNoteIdByKey(txn, key).orElseThrow();
+                    assertArrayEquals(cbor, ndb.getNoteByIdCbor(txn, id).orElseThrow());
+                }
+
+                assertTrue(ndb.getNoteByKeyCbor(txn, 999_999).isEmpty());
+                assertTrue(ndb.getNoteByIdCbor(txn, new byte[32]).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.getNoteByIdCbor(txn, new byte[8]));
+                assertTrue(ndb.queryNotesCbor(txn, none, 10).isEmpty());
+            }
+        }
+    }
+}
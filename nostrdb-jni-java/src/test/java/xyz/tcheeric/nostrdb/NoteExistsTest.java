@@ -0,0 +1,77 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.BitSet;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for checking whether notes are stored by event id.
+ */
+class NoteExistsTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("noteExists should report a stored note and not an unknown id")
+    void testNoteExists() throws Exception {
+        String event = alice.textNote(T, "hello");
+        byte[] id = HexUtil.decode(TestEvents.idOf(event));
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(event);
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertTrue(ndb.noteExists(txn, id));
+                assertTrue(ndb.noteExists(txn, TestEvents.idOf(event)));
+                assertFalse(ndb.noteExists(txn, new byte[32]));
+                assertThrows(IllegalArgumentException.class, () -> ndb.noteExists(txn, new byte[31]));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("notesExist should mark exactly the stored ids of a 1000-id batch")
+    void testBatch() throws Exception {
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            List<byte[]> ids = new ArrayList<>();
+            StringBuilder batch = new StringBuilder();
+            for (int i = 0; i < 1000; i++) {
+                String id = String.format("%064x", i + 1);
+                ids.add(HexUtil.decode(id));
+                if (i % 3 == 0) {
+                    batch.append("{\"id\":\"").append(id).append("\",\"pubkey\":\"").append(alice.pubkeyHex())
+                        .append("\",\"created_at\":").append(T + i).append(",\"kind\":1,\"tags\":[],")
+                        .append("\"content\":\"note ").append(i).append("\",\"sig\":\"")
+                        .append("0".repeat(128)).append("\"}\n");
+                }
+            }
+            assertEquals(334, ndb.processEvents(batch.toString()));
+            assertEquals(334, TestEvents.awaitCount(ndb, notes, 334, 30_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                BitSet stored = ndb.notesExist(txn, ids);
+                assertEquals(334, stored.cardinality());
+                for (int i = 0; i < ids.size(); i++) {
+                    assertEquals(i % 3 == 0, stored.get(i), "id " + i);
+                }
+                assertTrue(ndb.notesExist(txn, List.of()).isEmpty());
+                assertThrows(IllegalArgumentException.class,
+                    () -> ndb.notesExist(txn, List.of(new byte[32], new byte[16])));
+            }
+        }
+    }
+}
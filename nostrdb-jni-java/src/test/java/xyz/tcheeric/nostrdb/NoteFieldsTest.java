@@ -0,0 +1,79 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for reading single note fields.
+ */
+class NoteFieldsTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+
+    @Test
+    @DisplayName("Each field accessor should match the field parsed from the full JSON")
+    void testFields() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1, 7, 30023).build()) {
+            ndb.processEvent(alice.textNote(1_700_000_000L, "plain text"));
+            ndb.processEvent(bob.event(7, 1_700_000_001L, "+", new String[] {"t", "x"}));
+            ndb.processEvent(alice.event(30023, 1_700_000_002L, "caf\u00e9 \ud83d\ude80 \"quoted\"\nline",
+                new String[] {"d", "article"}));
+            ndb.processEvent(bob.textNote(1_700_000_003L, ""));
+            assertEquals(4, TestEvents.awaitCount(ndb, all, 4, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                for (QueryResult result : ndb.query(txn, all, 10)) {
+                    long key = result.noteKey();
+                    Note note = ndb.getNoteByKey(txn, key).orElseThrow();
+                    assertEquals(note.kind(), ndb.getNoteKind(txn, key).getAsInt());
+                    assertEquals(note.createdAt(), ndb.getNoteCreatedAt(txn, key).getAsLong());
+                    assertArrayEquals(note.pubkeyBytes(), ndb.getNotePubkey(txn, key).orElseThrow());
+                    assertEquals(note.content(), ndb.getNoteContent(txn, key).orElseThrow());
+                    assertEquals(note.sig(), HexUtil.encode(ndb.getNoteSig(txn, key).orElseThrow()));
+                }
+
+                long missing = 999_999;
+                assertTrue(ndb.getNoteKind(txn, missing).isEmpty());
+                assertTrue(ndb.getNoteCreatedAt(txn, missing).isEmpty());
+                assertTrue(ndb.getNotePubkey(txn, missing).isEmpty());
+                assertTrue(ndb.getNoteContent(txn, missing).isEmpty());
+                assertTrue(ndb.getNoteSig(txn, missing).isEmpty());
+                assertTrue(ndb.getNoteContentBytes(txn, missing).isEmpty());
+                assertTrue(ndb.getNoteContentLength(txn, missing).isEmpty());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Content bytes should equal the UTF-8 of the content parsed from the JSON")
+    void testContentBytes() throws Exception {
+        String paragraph = "## Caf\u00e9 \ud83d\ude80 \u65e5\u672c\u8a9e \"quoted\" \\ back\tslash\n\u00a0 end\n\n";
+        String article = paragraph.repeat(200);
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter articles = Filter.builder().kinds(30023).build()) {
+            ndb.processEvent(alice.event(30023, 1_700_000_000L, article, new String[] {"d", "long-form"}));
+            assertEquals(1, TestEvents.awaitCount(ndb, articles, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                long key = ndb.query(txn, articles, 1).get(0).noteKey();
+                byte[] fromJson = ndb.getNoteByKey(txn, key).orElseThrow().content().getBytes(StandardCharsets.UTF_8);
+                byte[] bytes = ndb.getNoteContentBytes(txn, key).orElseThrow();
+
+                assertTrue(bytes.length > 8_000, "content is " + bytes.length + " bytes");
+                assertArrayEquals(fromJson, bytes);
+                assertArrayEquals(article.getBytes(StandardCharsets.UTF_8), bytes);
+                assertEquals(bytes.length, ndb.getNoteContentLength(txn, key).getAsInt());
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for reading note fields through a note handle.
+ */
+class NoteHandleTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+
+    @Test
+    @DisplayName("A handle should read the same fields as the deserialized note")
+    void testFields() throws Exception {
+        String tagged = alice.event(1, T, "caf\u00e9", new String[] {"p", bob.pubkeyHex()},
+            new String[] {"t", "nostr"});
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(tagged);
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                Note expected = ndb.getNoteById(txn, TestEvents.idOf(tagged)).orElseThrow();
+                long key = ndb.getNoteKeyById(txn, TestEvents.idOf(tagged)).getAsLong();
+
+                try (NoteHandle note = ndb.openNote(txn, key).orElseThrow()) {
+                    assertEquals(key, note.key());
+                    assertEquals(TestEvents.idOf(tagged), HexUtil.encode(note.id()));
+                    assertEquals(alice.pubkeyHex(), HexUtil.encode(note.pubkey()));
+                    assertEquals(64, note.sig().length);
+                    assertEquals(1, note.kind());
+                    assertEquals(T, note.createdAt());
+                    assertEquals(expected.content(), note.content());
+                    assertArrayEquals("caf\u00e9".getBytes(StandardCharsets.UTF_8), note.contentBytes());
+
+                    assertEquals(2, note.tagCount());
+                    assertEquals(2, note.tagLength(0));
+                    assertEquals(bob.pubkeyHex(), note.tag(0, 1));
+                    assertArrayEquals(HexUtil.decode(bob.pubkeyHex()), note.tag(0, 1, true));
+                    assertEquals("nostr", note.tag(1, 1));
+                    assertThrows(IndexOutOfBoundsException.class, () -> note.tagLength(2));
+                    assertThrows(IndexOutOfBoundsException.class, () -> note.tag(1, 2));
+                }
+
+                try (NoteHandle note = ndb.openNote(txn, HexUtil.decode(TestEvents.idOf(tagged))).orElseThrow()) {
+                    assertEquals(key, note.key());
+                }
+
+                assertTrue(ndb.openNote(txn, key + 1000).isEmpty());
+                assertTrue(ndb.openNote(txn, new byte[32]).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.openNote(txn, new byte[31]));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A handle should throw once its transaction has ended, and still close")
+    void testTransactionEnded() throws Exception {
+        String text = alice.textNote(T, "hello");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(text);
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            Transaction txn = ndb.beginTransaction();
+            NoteHandle note = ndb.openNote(txn, HexUtil.decode(TestEvents.idOf(text))).orElseThrow();
+            assertEquals("hello", note.content());
+            txn.close();
+
+            IllegalStateException e = assertThrows(IllegalStateException.class, note::content);
+            assertTrue(e.getMessage().contains("transaction"), e.getMessage());
+            assertThrows(IllegalStateException.class, note::kind);
+            assertThrows(IllegalStateException.class, note::pubkey);
+            assertThrows(IllegalStateException.class, note::tagCount);
+            assertTrue(note.isOpen());
+
+            note.close();
+            note.close();
+            assertFalse(note.isOpen());
+            e = assertThrows(IllegalStateException.class, note::kind);
+            assertEquals("Note handle is closed", e.getMessage());
+        }
+    }
+}
@@ -0,0 +1,62 @@
+package xyz.tcheeric.nostrdb;
+
+import com.fasterxml.jackson.databind.JsonNode;
+import com.fasterxml.jackson.databind.ObjectMapper;
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+import java.security.MessageDigest;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for fetching notes as nostrdb's own JSON.
+ */
+class NoteJsonTest {
+
+    private static final ObjectMapper MAPPER = new ObjectMapper();
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /** Recompute an event's id from its JSON, per NIP-01. */
+    private static String computeId(JsonNode event) throws Exception {
+        String serialized = MAPPER.writeValueAsString(List.of(0, event.get("pubkey"),
+            event.get("created_at"), event.get("kind"), event.get("tags"), event.get("content")));
+        byte[] id = MessageDigest.getInstance("SHA-256").digest(serialized.getBytes(StandardCharsets.UTF_8));
+        return HexUtil.encode(id);
+    }
+
+    @Test
+    @DisplayName("Stored JSON should hash back to the event's id")
+    void testIdRoundTrip() throws Exception {
+        String event = alice.event(1, 1_700_000_000L, "quotes \" and \\ backslash\nnew line, tab\t, caf\u00e9 \ud83d\ude80",
+            new String[] {"t", "json"}, new String[] {"r", "https://example.com/?a=1&b=</script>"});
+        String id = TestEvents.idOf(event);
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(event);
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                long key = ndb.query(txn, notes, 1).get(0).noteKey();
+                String byKey = ndb.getNoteJsonByKey(txn, key).orElseThrow();
+                assertEquals(byKey, ndb.getNoteJsonById(txn, id).orElseThrow());
+
+                JsonNode stored = MAPPER.readTree(byKey);
+                assertEquals(id, stored.get("id").asText());
+                assertEquals(id, computeId(stored));
+                assertEquals(MAPPER.readTree(event), stored);
+
+                assertTrue(ndb.getNoteJsonByKey(txn, 999_999).isEmpty());
+                assertTrue(ndb.getNoteJsonById(txn, new byte[32]).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.getNoteJsonById(txn, new byte[8]));
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.HashSet;
+import java.util.Set;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for mapping between note keys and event ids.
+ */
+class NoteKeyLookupTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("Keys and ids should round-trip, with empty results for unknown notes")
+    void testRoundTrip() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            Set<String> ids = new HashSet<>();
+            for (int i = 0; i < 5; i++) {
+                String event = alice.textNote(1_700_000_000L + i, "note " + i);
+                ids.add(TestEvents.idOf(event));
+                ndb.processEvent(event);
+            }
+            assertEquals(5, TestEvents.awaitCount(ndb, notes, 5, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                Set<String> found = new HashSet<>();
+                for (QueryResult result : ndb.query(txn, notes, 10)) {
+                    byte[] id = ndb.getNoteIdByKey(txn, result.noteKey()).orElseThrow();
+                    assertEquals(ndb.getNoteByKey(txn, result.noteKey()).orElseThrow().id(), HexUtil.encode(id));
+                    assertEquals(result.noteKey(), ndb.getNoteKeyById(txn, id).getAsLong());
+                    assertEquals(result.noteKey(), ndb.getNoteKeyById(txn, HexUtil.encode(id)).getAsLong());
+                    found.add(HexUtil.encode(id));
+                }
+                assertEquals(ids, found);
+
+                assertTrue(ndb.getNoteIdByKey(txn, 999_999).isEmpty());
+                assertTrue(ndb.getNoteKeyById(txn, new byte[32]).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.getNoteKeyById(txn, new byte[31]));
+            }
+        }
+    }
+}
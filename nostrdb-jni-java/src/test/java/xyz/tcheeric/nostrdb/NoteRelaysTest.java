@@ -0,0 +1,97 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+import java.util.Set;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for recording which relays a note was seen on.
+ */
+class NoteRelaysTest {
+
+    private static final String DAMUS = "wss://relay.damus.io";
+    private static final String NOS = "wss://nos.lol";
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /** The note's relays, once the writer has recorded at least {@code expected} of them. */
+    private static List<String> awaitRelays(Ndb ndb, String id, int expected) throws InterruptedException {
+        long deadline = System.currentTimeMillis() + 10_000;
+        while (true) {
+            try (Transaction txn = ndb.beginTransaction()) {
+                long key = ndb.getNoteKeyById(txn, id).getAsLong();
+                List<String> relays = ndb.getNoteRelays(txn, key).orElseThrow();
+                if (relays.size() >= expected || System.currentTimeMillis() > deadline) {
+                    return relays;
+                }
+            }
+            Thread.sleep(20);
+        }
+    }
+
+    @Test
+    @DisplayName("A note seen on two relays should list both")
+    void testTwoRelays() throws Exception {
+        String event = alice.textNote(1_700_000_000L, "seen twice");
+        String id = TestEvents.idOf(event);
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEventFromRelay(event, DAMUS);
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+            assertEquals(List.of(DAMUS), awaitRelays(ndb, id, 1));
+
+            // The duplicate adds its relay to the stored note
+            ndb.processEventFromRelay(event, NOS);
+            assertEquals(Set.of(DAMUS, NOS), Set.copyOf(awaitRelays(ndb, id, 2)));
+            assertEquals(1, TestEvents.awaitStable(ndb, notes, 200, 10_000));
+        }
+    }
+
+    @Test
+    @DisplayName("A batch seen on two relays should list both on each note")
+    void testBatchFromTwoRelays() throws Exception {
+        String first = alice.textNote(1_700_000_000L, "first");
+        String second = alice.textNote(1_700_000_001L, "second");
+        String batch = first + "\n" + second + "\n";
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            assertEquals(2, ndb.processEventsFromRelay(batch, DAMUS));
+            assertEquals(2, TestEvents.awaitCount(ndb, notes, 2, 10_000));
+            assertEquals(2, ndb.processEventsFromRelay(batch, NOS));
+
+            for (String event : List.of(first, second)) {
+                assertEquals(Set.of(DAMUS, NOS), Set.copyOf(awaitRelays(ndb, TestEvents.idOf(event), 2)));
+            }
+            assertEquals(2, TestEvents.awaitStable(ndb, notes, 200, 10_000));
+            assertThrows(IllegalArgumentException.class, () -> ndb.processEventsFromRelay(batch, ""));
+        }
+    }
+
+    @Test
+    @DisplayName("A note ingested without a relay should have none")
+    void testNoRelays() throws Exception {
+        String event = alice.textNote(1_700_000_000L, "local");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(event);
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                long key = ndb.getNoteKeyById(txn, TestEvents.idOf(event)).getAsLong();
+                assertEquals(List.of(), ndb.getNoteRelays(txn, key).orElseThrow());
+                assertTrue(ndb.getNoteRelays(txn, 999_999).isEmpty());
+            }
+
+            assertThrows(IllegalArgumentException.class, () -> ndb.processEventFromRelay(event, ""));
+            assertThrows(IllegalArgumentException.class, () -> ndb.processEventFromRelay(event, null));
+        }
+    }
+}
@@ -0,0 +1,56 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for stored note sizes.
+ */
+class NoteSizeTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("A large note's stored size should exceed a small one's by about its extra content")
+    void testSize() throws Exception {
+        String small = alice.textNote(1_700_000_000L, "hi");
+        String large = alice.textNote(1_700_000_001L, "x".repeat(20_000));
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(small);
+            ndb.processEvent(large);
+            assertEquals(2, TestEvents.awaitCount(ndb, notes, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                long smallKey = ndb.getNoteKeyById(txn, TestEvents.idOf(small)).getAsLong();
+                long largeKey = ndb.getNoteKeyById(txn, TestEvents.idOf(large)).getAsLong();
+                long smallSize = ndb.getNoteSize(txn, smallKey).getAsLong();
+                long largeSize = ndb.getNoteSize(txn, largeKey).getAsLong();
+
+                // At least the fixed header, and the size of the raw note
+                assertTrue(smallSize > RawNote.KIND_OFFSET + 4, "small note is " + smallSize + " bytes");
+                assertEquals(ndb.getNoteRawByKey(txn, smallKey).orElseThrow().length, smallSize);
+                assertTrue(largeSize - smallSize >= 20_000, "large note is " + largeSize + " bytes");
+                assertTrue(largeSize < 25_000, "large note is " + largeSize + " bytes");
+
+                // Batch lookups carry the same sizes
+                NoteBatch batch = ndb.getNotesByKeys(txn, new long[] {largeKey, 999_999, smallKey});
+                assertArrayEquals(new long[] {largeKey, smallKey}, batch.keys());
+                assertArrayEquals(new long[] {largeSize, smallSize}, batch.sizes());
+                NoteBatch byIds = ndb.getNotesByIds(txn, List.of(
+                    HexUtil.decode(TestEvents.idOf(small)), HexUtil.decode(TestEvents.idOf(large))));
+                assertArrayEquals(new long[] {smallSize, largeSize}, byIds.sizes());
+
+                assertTrue(ndb.getNoteSize(txn, 999_999).isEmpty());
+            }
+        }
+    }
+}
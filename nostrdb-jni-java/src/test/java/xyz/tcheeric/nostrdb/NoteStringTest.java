@@ -0,0 +1,85 @@
+package xyz.tcheeric.nostrdb;
+
+import com.fasterxml.jackson.databind.JsonNode;
+import com.fasterxml.jackson.databind.ObjectMapper;
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for fetching notes as JSON strings.
+ */
+class NoteStringTest {
+
+    private static final ObjectMapper MAPPER = new ObjectMapper();
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("String variants should match the JSON of the byte variants")
+    void testEmoji() throws Exception {
+        String content = "gm \ud83c\udf05 caf\u00e9 \ud83d\ude80\ud83d\ude80 \u4f60\u597d";
+        String event = alice.textNote(1_700_000_000L, content);
+        String id = TestEvents.idOf(event);
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(event);
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                long key = ndb.query(txn, notes, 1).get(0).noteKey();
+                String byId = ndb.getNoteByIdString(txn, id).orElseThrow();
+                assertEquals(byId, ndb.getNoteByKeyString(txn, key).orElseThrow());
+
+                JsonNode json = MAPPER.readTree(byId);
+                assertEquals(content, json.get("content").asText());
+                assertEquals(id, json.get("id").asText());
+                assertEquals(ndb.getNoteById(txn, id).orElseThrow().content(), json.get("content").asText());
+
+                assertTrue(ndb.getNoteByKeyString(txn, 999_999).isEmpty());
+                assertTrue(ndb.getNoteByIdString(txn, new byte[32]).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.getNoteByIdString(txn, new byte[8]));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Invalid UTF-8 in stored content should come back as replacement characters")
+    void testBrokenUtf8() throws Exception {
+        // nostrdb stores an unpaired surrogate escape as bytes that aren't
+        // valid UTF-8; the id no longer matches, so validation is skipped
+        String event = alice.textNote(1_700_000_000L, "before BROKEN after \ud83d\ude80")
+            .replace("BROKEN", "\\ud800");
+        String id = TestEvents.idOf(event);
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(event);
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                long key = ndb.query(txn, notes, 1).get(0).noteKey();
+                String json = ndb.getNoteByKeyString(txn, key).orElseThrow();
+                assertEquals(json, ndb.getNoteByIdString(txn, id).orElseThrow());
+
+                String content = MAPPER.readTree(json).get("content").asText();
+                assertTrue(content.startsWith("before "), content);
+                assertTrue(content.endsWith(" after \ud83d\ude80"), content);
+                assertTrue(content.contains("\ufffd"), content);
+                // No unpaired surrogate made it into the string
+                assertEquals(content, new String(content.getBytes(StandardCharsets.UTF_8), StandardCharsets.UTF_8));
+
+                // The stored content itself is not valid UTF-8
+                byte[] raw = ndb.getNoteContentBytes(txn, key).orElseThrow();
+                assertTrue(new String(raw, StandardCharsets.UTF_8).contains("\ufffd"));
+            }
+        }
+    }
+}
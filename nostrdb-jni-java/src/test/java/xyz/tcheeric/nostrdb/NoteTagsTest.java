@@ -0,0 +1,142 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+import java.util.NoSuchElementException;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for iterating note tags natively.
+ */
+class NoteTagsTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+
+    private static final long T = 1_700_000_000L;
+
+    @Test
+    @DisplayName("Tags should iterate element by element, with ids hex-encoded or raw on request")
+    void testIterate() throws Exception {
+        String root = alice.textNote(T, "root");
+        String rootId = TestEvents.idOf(root);
+        String[][] tags = {
+            {"t", "nostr"},
+            {"e", rootId, "", "root"},
+            {"p", bob.pubkeyHex()},
+            {"subject", "caf\u00e9"},
+        };
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            String tagged = alice.event(1, T + 1, "tagged", tags);
+            ndb.processEvent(root);
+            ndb.processEvent(tagged);
+            assertEquals(2, TestEvents.awaitCount(ndb, notes, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                long key = ndb.getNoteKeyById(txn, TestEvents.idOf(tagged)).getAsLong();
+                assertEquals(tags.length, ndb.getNoteTagCount(txn, key).getAsInt());
+                for (int i = 0; i < tags.length; i++) {
+                    assertEquals(tags[i].length, ndb.getNoteTagLength(txn, key, i));
+                    for (int j = 0; j < tags[i].length; j++) {
+                        assertEquals(tags[i][j], ndb.getNoteTag(txn, key, i, j));
+                    }
+                }
+
+                // Id elements come back as their 32 bytes when asked; strings stay UTF-8
+                assertArrayEquals(HexUtil.decode(rootId), ndb.getNoteTag(txn, key, 1, 1, true));
+                assertArrayEquals(HexUtil.decode(bob.pubkeyHex()), ndb.getNoteTag(txn, key, 2, 1, true));
+                assertArrayEquals(new byte[0], ndb.getNoteTag(txn, key, 1, 2, true));
+                assertArrayEquals("caf\u00e9".getBytes(StandardCharsets.UTF_8), ndb.getNoteTag(txn, key, 3, 1, true));
+
+                // A note without tags
+                long rootKey = ndb.getNoteKeyById(txn, rootId).getAsLong();
+                assertEquals(0, ndb.getNoteTagCount(txn, rootKey).getAsInt());
+                assertThrows(IndexOutOfBoundsException.class, () -> ndb.getNoteTagLength(txn, rootKey, 0));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Tag ids should be extracted in order, skipping malformed values")
+    void testTagIds() throws Exception {
+        TestEvents carol = TestEvents.forSeed(13);
+        String root = alice.textNote(T, "root");
+        String rootId = TestEvents.idOf(root);
+        String[][] tags = {
+            {"p", bob.pubkeyHex()},
+            {"e", rootId, "", "root"},
+            // Upper-case hex may not be packed as an id; it decodes all the same
+            {"p", carol.pubkeyHex().toUpperCase()},
+            {"p", bob.pubkeyHex().substring(1)},
+            {"q", "30023:" + bob.pubkeyHex() + ":article"},
+        };
+        // If nostrdb packs the upper-case value as an id, the note no longer
+        // hashes to its id, so validation is skipped
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            String tagged = alice.event(1, T + 1, "tagged", tags);
+            ndb.processEvent(root);
+            ndb.processEvent(tagged);
+            assertEquals(2, TestEvents.awaitCount(ndb, notes, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                long key = ndb.getNoteKeyById(txn, TestEvents.idOf(tagged)).getAsLong();
+
+                TagIds pubkeys = ndb.getNoteTagIds(txn, key, "p").orElseThrow();
+                assertEquals(2, pubkeys.ids().size());
+                assertArrayEquals(HexUtil.decode(bob.pubkeyHex()), pubkeys.ids().get(0));
+                assertArrayEquals(HexUtil.decode(carol.pubkeyHex()), pubkeys.ids().get(1));
+                assertEquals(1, pubkeys.skipped());
+
+                TagIds events = ndb.getNoteTagIds(txn, key, "e").orElseThrow();
+                assertEquals(1, events.ids().size());
+                assertArrayEquals(HexUtil.decode(rootId), events.ids().get(0));
+                assertEquals(0, events.skipped());
+
+                TagIds quotes = ndb.getNoteTagIds(txn, key, "q").orElseThrow();
+                assertTrue(quotes.ids().isEmpty());
+                assertEquals(1, quotes.skipped());
+
+                long rootKey = ndb.getNoteKeyById(txn, rootId).getAsLong();
+                assertTrue(ndb.getNoteTagIds(txn, rootKey, "p").orElseThrow().ids().isEmpty());
+                assertTrue(ndb.getNoteTagIds(txn, 999_999, "p").isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.getNoteTagIds(txn, key, "t"));
+                assertThrows(IllegalArgumentException.class, () -> ndb.getNoteTagIds(txn, key, null));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Out-of-range indices should throw IndexOutOfBoundsException")
+    void testOutOfRange() throws Exception {
+        String note = alice.event(1, T, "tagged", new String[] {"t", "nostr"});
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(note);
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                long key = ndb.getNoteKeyById(txn, TestEvents.idOf(note)).getAsLong();
+                assertThrows(IndexOutOfBoundsException.class, () -> ndb.getNoteTagLength(txn, key, 1));
+                assertThrows(IndexOutOfBoundsException.class, () -> ndb.getNoteTagLength(txn, key, -1));
+                assertThrows(IndexOutOfBoundsException.class, () -> ndb.getNoteTag(txn, key, 1, 0));
+                assertThrows(IndexOutOfBoundsException.class, () -> ndb.getNoteTag(txn, key, 0, 2));
+                assertThrows(IndexOutOfBoundsException.class, () -> ndb.getNoteTag(txn, key, 0, -1));
+
+                // A missing note has no count, and its tags can't be read
+                assertTrue(ndb.getNoteTagCount(txn, 999_999).isEmpty());
+                assertThrows(NoSuchElementException.class, () -> ndb.getNoteTagLength(txn, 999_999, 0));
+                assertThrows(NoSuchElementException.class, () -> ndb.getNoteTag(txn, 999_999, 0, 0));
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.io.IOException;
+import java.nio.file.Files;
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for directory handling and error messages when opening a database.
+ */
+class OpenDiagnosticsTest {
+
+    @TempDir
+    Path tempDir;
+
+    @Test
+    @DisplayName("A missing directory should be created when createIfMissing is set")
+    void testCreateIfMissing() {
+        Path dbPath = tempDir.resolve("a").resolve("b").resolve("db");
+        try (Ndb ndb = Ndb.open(dbPath.toString(), null, true)) {
+            assertNotNull(ndb);
+        }
+        assertTrue(Files.isDirectory(dbPath));
+    }
+
+    @Test
+    @DisplayName("A missing directory should be reported when createIfMissing is not set")
+    void testMissingDirectory() {
+        Path dbPath = tempDir.resolve("db");
+        IOException e = assertThrows(IOException.class, () -> Ndb.open(dbPath.toString(), null, false));
+        assertTrue(e.getMessage().contains("directory does not exist"), e.getMessage());
+        assertFalse(Files.exists(dbPath), "Nothing should be created");
+    }
+
+    @Test
+    @DisplayName("A missing parent directory should be named in the error")
+    void testMissingParent() {
+        Path parent = tempDir.resolve("missing-parent");
+        IOException e = assertThrows(IOException.class, () ->
+            Ndb.open(parent.resolve("db").toString(), null, false));
+        assertTrue(e.getMessage().contains("parent " + parent), e.getMessage());
+    }
+
+    @Test
+    @DisplayName("A path pointing at a regular file should be reported")
+    void testRegularFile() throws IOException {
+        Path file = Files.writeString(tempDir.resolve("not-a-dir"), "hello");
+        for (boolean create : new boolean[] {true, false}) {
+            IOException e = assertThrows(IOException.class, () -> Ndb.open(file.toString(), null, create));
+            assertTrue(e.getMessage().contains("not a directory"), e.getMessage());
+        }
+    }
+
+    @Test
+    @DisplayName("Opening a database twice in one process should be reported")
+    void testAlreadyOpen() {
+        Path dbPath = tempDir.resolve("db");
+        try (Ndb ndb = Ndb.open(dbPath)) {
+            assertFalse(ndb.isWriteProtected());
+            IOException e = assertThrows(IOException.class, () -> Ndb.open(dbPath));
+            assertTrue(e.getMessage().contains("already open"), e.getMessage());
+        }
+
+        // Once closed it can be opened again
+        try (Ndb ndb = Ndb.open(dbPath)) {
+            assertNotNull(ndb);
+        }
+    }
+}
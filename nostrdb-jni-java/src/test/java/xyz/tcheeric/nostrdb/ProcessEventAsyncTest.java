@@ -0,0 +1,98 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.concurrent.CompletableFuture;
+import java.util.concurrent.CountDownLatch;
+import java.util.concurrent.TimeUnit;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for ingestion with a completion callback.
+ */
+class ProcessEventAsyncTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /** One callback's arguments. */
+    private static final class Completion {
+        final byte[] id;
+        final long noteKey;
+        final int status;
+
+        Completion(byte[] id, long noteKey, int status) {
+            this.id = id;
+            this.noteKey = noteKey;
+            this.status = status;
+        }
+    }
+
+    private static Completion submit(Ndb ndb, String json, long timeoutMs) throws Exception {
+        CompletableFuture<Completion> done = new CompletableFuture<>();
+        ndb.processEventAsync(json, (id, noteKey, status) -> done.complete(new Completion(id, noteKey, status)),
+            timeoutMs);
+        return done.get(10, TimeUnit.SECONDS);
+    }
+
+    @Test
+    @DisplayName("A good event should call back with its note key")
+    void testStored() throws Exception {
+        String note = alice.textNote(T, "hello");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            Completion completion = submit(ndb, note, 5_000);
+            assertEquals(IngestCallback.STORED, completion.status);
+            assertEquals(TestEvents.idOf(note), HexUtil.encode(completion.id));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(completion.noteKey,
+                    ndb.getNoteKeyById(txn, HexUtil.decode(TestEvents.idOf(note))).orElseThrow());
+            }
+
+            Completion again = submit(ndb, note, 5_000);
+            assertEquals(IngestCallback.DUPLICATE, again.status);
+            assertEquals(completion.noteKey, again.noteKey);
+        }
+    }
+
+    @Test
+    @DisplayName("A bad event should call back with a failure status")
+    void testInvalid() throws Exception {
+        String tampered = alice.textNote(T, "original").replace("original", "tampered");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            Completion completion = submit(ndb, tampered, 5_000);
+            assertEquals(IngestCallback.INVALID, completion.status);
+            assertEquals(0, completion.noteKey);
+            assertEquals(TestEvents.idOf(tampered), HexUtil.encode(completion.id));
+
+            Completion garbage = submit(ndb, "not json", 5_000);
+            assertEquals(IngestCallback.INVALID, garbage.status);
+            assertNull(garbage.id);
+        }
+    }
+
+    @Test
+    @DisplayName("A callback exception should not stop later callbacks")
+    void testCallbackException() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            CountDownLatch thrown = new CountDownLatch(1);
+            ndb.processEventAsync(alice.textNote(T, "first"), (id, noteKey, status) -> {
+                thrown.countDown();
+                throw new IllegalStateException("callback failure");
+            });
+            assertTrue(thrown.await(10, TimeUnit.SECONDS));
+
+            assertEquals(IngestCallback.STORED, submit(ndb, alice.textNote(T + 1, "second"), 5_000).status);
+            assertThrows(IllegalArgumentException.class, () -> ndb.processEventAsync("{}", null));
+            assertThrows(IllegalArgumentException.class,
+                () -> ndb.processEventAsync("{}", (id, noteKey, status) -> { }, -1));
+        }
+    }
+}
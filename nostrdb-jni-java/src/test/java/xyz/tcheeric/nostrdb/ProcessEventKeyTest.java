@@ -0,0 +1,83 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for ingesting events and getting the stored note's key back.
+ */
+class ProcessEventKeyTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static String sigOf(String eventJson) {
+        return Note.fromJson(eventJson).sig();
+    }
+
+    @Test
+    @DisplayName("A fresh event should return the key of its stored note")
+    void testFresh() {
+        String event = alice.textNote(T, "hello");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            long key = ndb.processEventReturningKey(event, 10_000);
+            assertTrue(key > 0, Long.toString(key));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(TestEvents.idOf(event), ndb.getNoteByKey(txn, key).orElseThrow().id());
+            }
+
+            // Wrapped in a client message, as processEvent accepts
+            String wrapped = "[\"EVENT\"," + alice.textNote(T + 1, "wrapped") + "]";
+            assertTrue(ndb.processEventReturningKey(wrapped, 10_000) > 0);
+        }
+    }
+
+    @Test
+    @DisplayName("A duplicate should be reported without being processed again")
+    void testDuplicate() {
+        String event = alice.textNote(T, "hello");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            assertTrue(ndb.processEventReturningKey(event, 10_000) > 0);
+            assertEquals(Ndb.INGEST_DUPLICATE, ndb.processEventReturningKey(event));
+        }
+    }
+
+    @Test
+    @DisplayName("Events with a bad signature or id, or malformed JSON, should be reported as invalid")
+    void testInvalid() throws Exception {
+        String event = alice.textNote(T, "hello");
+        String forged = event.replace(sigOf(event), sigOf(alice.textNote(T, "other")));
+        String tampered = event.replace("hello", "goodbye");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            assertEquals(Ndb.INGEST_INVALID, ndb.processEventReturningKey(forged));
+            assertEquals(Ndb.INGEST_INVALID, ndb.processEventReturningKey(tampered));
+            assertEquals(Ndb.INGEST_INVALID, ndb.processEventReturningKey("{\"kind\":1}"));
+            assertEquals(Ndb.INGEST_INVALID, ndb.processEventReturningKey("not json"));
+            assertThrows(IllegalArgumentException.class, () -> ndb.processEventReturningKey(event, -1));
+
+            assertEquals(0, TestEvents.awaitStable(ndb, notes, 200, 10_000));
+        }
+    }
+
+    @Test
+    @DisplayName("With validation skipped, an event with a bad signature should be stored")
+    void testSkipValidation() {
+        String event = alice.textNote(T, "hello");
+        String forged = event.replace(sigOf(event), sigOf(alice.textNote(T, "other")));
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config)) {
+            long key = ndb.processEventReturningKey(forged, 10_000);
+            assertTrue(key > 0, Long.toString(key));
+        }
+    }
+}
@@ -0,0 +1,77 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for choosing per call whether ingested events are verified.
+ */
+class ProcessEventValidationTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /** The event with its signature zeroed, so that only the signature fails to verify. */
+    private static String badSignature(String json) {
+        return json.replaceAll("\"sig\":\"[0-9a-f]{128}\"", "\"sig\":\"" + "0".repeat(128) + "\"");
+    }
+
+    @Test
+    @DisplayName("A bad signature should be rejected without the flag and stored with it")
+    void testBadSignature() throws Exception {
+        String bad = badSignature(alice.textNote(T, "hello"));
+        assertEquals(VerifyResult.BAD_SIG, Ndb.verifyEvent(bad));
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config)) {
+            assertThrows(NostrdbException.class, () -> ndb.processEvent(bad, false));
+            assertEquals(1, ndb.getIngestStats().rejected());
+
+            ndb.processEvent(bad, true);
+            assertTrue(ndb.waitForIngestion(HexUtil.decode(TestEvents.idOf(bad)), 10_000));
+        }
+    }
+
+    @Test
+    @DisplayName("processEvents should skip events that do not verify only without the flag")
+    void testBatch() throws Exception {
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            String checked = String.join("\n",
+                alice.textNote(T, "one"), badSignature(alice.textNote(T + 1, "two")));
+            assertEquals(1, ndb.processEvents(checked, false));
+
+            String unchecked = String.join("\n",
+                alice.textNote(T + 2, "three"), badSignature(alice.textNote(T + 3, "four")));
+            assertEquals(2, ndb.processEvents(unchecked, true));
+            assertEquals(3, TestEvents.awaitCount(ndb, notes, 3, 10_000));
+        }
+    }
+
+    @Test
+    @DisplayName("Skipping validation should be refused on a database that validates")
+    void testValidatingDatabase() throws Exception {
+        String good = alice.textNote(T, "hello");
+        String bad = badSignature(alice.textNote(T + 1, "bye"));
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            assertThrows(IllegalStateException.class, () -> ndb.processEvent(bad, true));
+            assertThrows(IllegalStateException.class, () -> ndb.processEvents(bad, true));
+            assertThrows(NostrdbException.class, () -> ndb.processEvent(bad, false));
+            // Without the flag, the database's own validation applies before queuing
+            assertEquals(Ndb.PROCESS_REJECTED, ndb.processEventWithResult(bad));
+            assertEquals(0, ndb.getIngestStats().accepted());
+            assertEquals(2, ndb.getIngestStats().rejected());
+
+            ndb.processEvent(good, false);
+            assertTrue(ndb.waitForIngestion(HexUtil.decode(TestEvents.idOf(good)), 10_000));
+        }
+    }
+}
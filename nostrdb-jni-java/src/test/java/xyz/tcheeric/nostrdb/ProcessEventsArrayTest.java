@@ -0,0 +1,80 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for ingesting a JSON array of events.
+ */
+class ProcessEventsArrayTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("Each element should be ingested, with an invalid event reported by element number")
+    void testArray() throws Exception {
+        String reply = alice.event(1, T + 1, "reply",
+            new String[] {"e", "0".repeat(64), "", "root"}, new String[] {"t", "nostr"});
+        String json = "[\n  " + String.join(",\n  ",
+            alice.textNote(T, "one"),
+            reply,
+            alice.textNote(T + 2, "three").replace("three", "tampered"),
+            alice.textNote(T + 3, "four")) + "\n]";
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            IngestReport report = ndb.processEventsArray(json);
+            assertEquals(3, report.succeeded());
+            assertEquals(0, report.duplicates());
+            assertEquals(1, report.failed());
+            assertEquals(0, report.blank());
+            assertEquals(4, report.linesRead());
+            assertEquals(3, report.errors().get(0).line());
+            assertTrue(report.errors().get(0).message().contains("does not match"), report.errors().get(0).message());
+
+            assertEquals(3, TestEvents.awaitCount(ndb, notes, 3, 10_000));
+        }
+    }
+
+    @Test
+    @DisplayName("A nested array should be one failed element, not flattened into events")
+    void testNestedArray() throws Exception {
+        String one = alice.textNote(T, "one");
+        String two = alice.textNote(T + 1, "two");
+        String json = "[" + one + ",[" + two + "]," + one + "]";
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            IngestReport report = ndb.processEventsArray(json);
+            assertEquals(1, report.succeeded());
+            assertEquals(1, report.duplicates());
+            assertEquals(1, report.failed());
+            assertEquals(3, report.linesRead());
+            assertEquals(List.of(2), report.errors().stream().map(IngestReport.LineError::line).toList());
+
+            IngestReport empty = ndb.processEventsArray(" [ ] ");
+            assertEquals(0, empty.linesRead());
+        }
+    }
+
+    @Test
+    @DisplayName("Input that is not an array, or breaks off, should throw")
+    void testNotArray() throws Exception {
+        String note = alice.textNote(T, "one");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            NostrdbException e = assertThrows(NostrdbException.class, () -> ndb.processEventsArray(note));
+            assertTrue(e.getMessage().contains("expected a JSON array of events"), e.getMessage());
+
+            assertThrows(NostrdbException.class, () -> ndb.processEventsArray(note + "\n" + note));
+            assertThrows(NostrdbException.class, () -> ndb.processEventsArray("[" + note + ","));
+            assertThrows(NostrdbException.class, () -> ndb.processEventsArray("[" + note + "] trailing"));
+        }
+    }
+}
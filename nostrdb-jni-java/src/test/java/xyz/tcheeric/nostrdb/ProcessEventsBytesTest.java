@@ -0,0 +1,109 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.io.ByteArrayOutputStream;
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for ingesting UTF-8 bytes without decoding them into strings.
+ */
+class ProcessEventsBytesTest {
+
+    private static final long T = 1_700_000_000L;
+    private static final int COUNT = 4_000;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /**
+     * An unsigned note of about 1 KB with multi-byte content; signing thousands
+     * of notes would make the test needlessly slow, so these are stored with
+     * validation skipped.
+     */
+    private String unsignedNote(int i) {
+        return "{\"id\":\"" + String.format("%064x", i + 1) + "\",\"pubkey\":\"" + alice.pubkeyHex()
+            + "\",\"created_at\":" + (T + i) + ",\"kind\":1,\"tags\":[],"
+            + "\"content\":\"note " + i + " " + "caf\u00e9 \u4e2d\u6587 ".repeat(80) + "\",\"sig\":\""
+            + "0".repeat(128) + "\"}";
+    }
+
+    /** A batch of several megabytes, with blank lines and some CRLF line endings. */
+    private String batch() {
+        StringBuilder ldjson = new StringBuilder();
+        for (int i = 0; i < COUNT; i++) {
+            ldjson.append(unsignedNote(i)).append(i % 3 == 0 ? "\r\n" : "\n");
+            if (i % 500 == 0) {
+                ldjson.append("\n   \n");
+            }
+        }
+        return ldjson.toString();
+    }
+
+    @Test
+    @DisplayName("A multi-megabyte byte batch should match the String path")
+    void testMatchesStringPath() throws Exception {
+        String ldjson = batch();
+        byte[] bytes = ldjson.getBytes(StandardCharsets.UTF_8);
+        assertTrue(bytes.length > 4_000_000, "batch is " + bytes.length + " bytes");
+
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb fromString = Ndb.open(tempDir.resolve("string"), config);
+             Ndb fromBytes = Ndb.open(tempDir.resolve("bytes"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            int stringCount = fromString.processEvents(ldjson);
+            int bytesCount = fromBytes.processEventsBytes(bytes);
+            assertEquals(COUNT, stringCount);
+            assertEquals(stringCount, bytesCount);
+
+            assertEquals(COUNT, TestEvents.awaitCount(fromString, notes, COUNT, 60_000));
+            assertEquals(COUNT, TestEvents.awaitCount(fromBytes, notes, COUNT, 60_000));
+
+            byte[] id = HexUtil.decode(String.format("%064x", COUNT));
+            try (Transaction a = fromString.beginTransaction();
+                 Transaction b = fromBytes.beginTransaction()) {
+                assertEquals(fromString.getNoteById(a, id).orElseThrow().content(),
+                    fromBytes.getNoteById(b, id).orElseThrow().content());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A line that is not valid UTF-8 should be skipped and not counted")
+    void testInvalidLine() throws Exception {
+        ByteArrayOutputStream ldjson = new ByteArrayOutputStream();
+        ldjson.writeBytes(alice.textNote(T, "first").getBytes(StandardCharsets.UTF_8));
+        ldjson.writeBytes(new byte[] {'\n', '{', (byte) 0xff, (byte) 0xfe, '}', '\n'});
+        ldjson.writeBytes(alice.textNote(T + 1, "second").getBytes(StandardCharsets.UTF_8));
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            assertEquals(2, ndb.processEventsBytes(ldjson.toByteArray()));
+            assertEquals(2, TestEvents.awaitCount(ndb, notes, 2, 10_000));
+        }
+    }
+
+    @Test
+    @DisplayName("A single event should be ingested from bytes")
+    void testSingleEvent() throws Exception {
+        String note = alice.textNote(T, "na\u00efve");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEventBytes(note.getBytes(StandardCharsets.UTF_8));
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals("na\u00efve", ndb.getNoteById(txn, TestEvents.idOf(note)).orElseThrow().content());
+            }
+
+            assertThrows(IllegalArgumentException.class,
+                () -> ndb.processEventBytes(new byte[] {'{', (byte) 0xc3, '}'}));
+        }
+    }
+}
@@ -0,0 +1,67 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for batch ingestion that reports failed lines.
+ */
+class ProcessEventsDetailedTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /** Five events, with a tampered event on line 3 and a truncated one on line 6. */
+    private String batch() {
+        return String.join("\n",
+            alice.textNote(T, "one"),
+            alice.textNote(T + 1, "two"),
+            alice.textNote(T + 2, "three").replace("three", "tampered"),
+            "",
+            alice.textNote(T + 3, "four"),
+            alice.textNote(T + 4, "five").substring(0, 40),
+            alice.textNote(T + 5, "six"));
+    }
+
+    @Test
+    @DisplayName("Both bad lines should be reported by line number")
+    void testBadLines() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            IngestReport report = ndb.processEventsDetailed(batch());
+            assertEquals(4, report.succeeded());
+            assertEquals(2, report.failed());
+            assertEquals(1, report.blank());
+            assertEquals(7, report.linesRead());
+            assertEquals(List.of(3, 6), report.errors().stream().map(IngestReport.LineError::line).toList());
+            assertTrue(report.errors().get(0).message().contains("does not match"), report.errors().get(0).message());
+            assertFalse(report.errors().get(1).message().isEmpty());
+
+            assertEquals(4, TestEvents.awaitCount(ndb, notes, 4, 10_000));
+        }
+    }
+
+    @Test
+    @DisplayName("Stopping on error should report how far ingestion got")
+    void testStopOnError() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            IngestReport report = ndb.processEventsDetailed(batch(), true);
+            assertEquals(2, report.succeeded());
+            assertEquals(1, report.failed());
+            assertEquals(3, report.linesRead());
+            assertEquals(3, report.errors().get(0).line());
+
+            assertEquals(2, TestEvents.awaitStable(ndb, notes, 200, 10_000));
+        }
+    }
+}
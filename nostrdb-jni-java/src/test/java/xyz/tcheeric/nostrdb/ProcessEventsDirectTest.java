@@ -0,0 +1,75 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.ByteBuffer;
+import java.nio.MappedByteBuffer;
+import java.nio.channels.FileChannel;
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Files;
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for ingesting events read in place from direct buffers.
+ */
+class ProcessEventsDirectTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("Events should be ingested from a window of a memory-mapped file")
+    void testMappedFile() throws Exception {
+        String first = alice.textNote(T, "first") + "\n";
+        String rest = alice.textNote(T + 1, "second") + "\n\n" + alice.textNote(T + 2, "third") + "\n";
+        Path dump = tempDir.resolve("dump.jsonl");
+        Files.writeString(dump, first + rest);
+        int skip = first.getBytes(StandardCharsets.UTF_8).length;
+
+        try (FileChannel channel = FileChannel.open(dump);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            MappedByteBuffer mapped = channel.map(FileChannel.MapMode.READ_ONLY, 0, channel.size());
+            assertEquals(2, ndb.processEventsDirect(mapped, skip, mapped.capacity() - skip));
+            assertEquals(0, mapped.position(), "Position should be left unchanged");
+            assertEquals(2, TestEvents.awaitCount(ndb, notes, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertTrue(ndb.getNoteById(txn, TestEvents.idOf(first.trim())).isEmpty());
+            }
+
+            mapped.limit(skip);
+            assertEquals(1, ndb.processEventsDirect(mapped));
+            assertEquals(3, TestEvents.awaitCount(ndb, notes, 3, 10_000));
+        }
+    }
+
+    @Test
+    @DisplayName("Heap buffers and windows outside the buffer should be rejected")
+    void testInvalidArguments() throws Exception {
+        byte[] note = alice.textNote(T, "note").getBytes(StandardCharsets.UTF_8);
+        ByteBuffer direct = ByteBuffer.allocateDirect(note.length).put(note).flip();
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            assertThrows(IllegalArgumentException.class,
+                () -> ndb.processEventsDirect(ByteBuffer.wrap(note), 0, note.length));
+            assertThrows(IllegalArgumentException.class,
+                () -> ndb.processEventsDirect(ByteBuffer.wrap(note)));
+            assertThrows(IllegalArgumentException.class, () -> ndb.processEventsDirect(null, 0, 0));
+            assertThrows(IllegalArgumentException.class, () -> ndb.processEventsDirect(direct, -1, 1));
+            assertThrows(IllegalArgumentException.class, () -> ndb.processEventsDirect(direct, 0, -1));
+            assertThrows(IllegalArgumentException.class, () -> ndb.processEventsDirect(direct, 1, note.length));
+            assertThrows(IllegalArgumentException.class,
+                () -> ndb.processEventsDirect(direct, Integer.MAX_VALUE, 1));
+
+            assertEquals(0, ndb.processEventsDirect(direct, note.length, 0));
+            assertEquals(1, ndb.processEventsDirect(direct, 0, note.length));
+        }
+    }
+}
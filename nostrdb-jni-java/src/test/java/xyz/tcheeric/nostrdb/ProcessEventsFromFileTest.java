@@ -0,0 +1,172 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.io.IOException;
+import java.io.OutputStream;
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Files;
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for importing events from a file natively.
+ */
+class ProcessEventsFromFileTest {
+
+    private static final int COUNT = 5_000;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /**
+     * An unsigned note with a unique id; signing thousands of notes would make
+     * the test needlessly slow, so these are stored with validation skipped.
+     */
+    private String unsignedNote(int i) {
+        return "{\"id\":\"" + String.format("%064x", i + 1) + "\",\"pubkey\":\"" + alice.pubkeyHex()
+            + "\",\"created_at\":" + (1_700_000_000L + i) + ",\"kind\":1,\"tags\":[],"
+            + "\"content\":\"note " + i + "\",\"sig\":\"" + "0".repeat(128) + "\"}";
+    }
+
+    /** An export of COUNT notes, then a blank line and a line that is not valid UTF-8. */
+    private Path export() throws IOException {
+        Path file = tempDir.resolve("export.jsonl");
+        try (OutputStream out = Files.newOutputStream(file)) {
+            for (int i = 0; i < COUNT; i++) {
+                out.write((unsignedNote(i) + "\n").getBytes(StandardCharsets.UTF_8));
+            }
+            out.write(new byte[] {'\n', '{', (byte) 0xff, '}', '\n'});
+        }
+        return file;
+    }
+
+    @Test
+    @DisplayName("Importing a file should count every line and report progress")
+    void testImport() throws Exception {
+        Path file = export();
+        List<long[]> reports = new ArrayList<>();
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ImportSummary summary = ndb.processEventsFromFile(file,
+                (lines, failed) -> reports.add(new long[] {lines, failed}), null);
+
+            assertEquals(COUNT + 2, summary.lines());
+            assertEquals(COUNT, summary.processed());
+            assertEquals(1, summary.failed());
+            assertFalse(summary.isCancelled());
+            assertEquals(COUNT, TestEvents.awaitCount(ndb, notes, COUNT, 60_000));
+
+            assertEquals(COUNT / ImportProgressCallback.INTERVAL + 1, reports.size());
+            assertEquals(ImportProgressCallback.INTERVAL, reports.get(0)[0]);
+            assertArrayEquals(new long[] {COUNT + 2, 1}, reports.get(reports.size() - 1));
+        }
+    }
+
+    @Test
+    @DisplayName("Importing without a callback or token should give the same counts")
+    void testWithoutCallback() throws Exception {
+        Path file = export();
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config)) {
+            ImportSummary summary = ndb.processEventsFromFile(file);
+            assertEquals(COUNT + 2, summary.lines());
+            assertEquals(COUNT, summary.processed());
+            assertEquals(1, summary.failed());
+        }
+    }
+
+    @Test
+    @DisplayName("Cancelling the token should stop the import before the next line")
+    void testCancel() throws Exception {
+        Path file = export();
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             CancelToken token = new CancelToken()) {
+            ImportSummary summary = ndb.processEventsFromFile(file, (lines, failed) -> token.cancel(), token);
+            assertTrue(summary.isCancelled());
+            assertEquals(ImportProgressCallback.INTERVAL, summary.lines());
+            assertEquals(ImportProgressCallback.INTERVAL, summary.processed());
+        }
+    }
+
+    @Test
+    @DisplayName("Resuming a cancelled import from its offset should give the totals of an uninterrupted run")
+    void testResume() throws Exception {
+        Path file = export();
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             CancelToken token = new CancelToken();
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ImportSummary first = ndb.processEventsFromFile(file, (lines, failed) -> {
+                if (lines == 2 * ImportProgressCallback.INTERVAL) {
+                    token.cancel();
+                }
+            }, token, 0);
+            assertTrue(first.isCancelled());
+            assertEquals(2 * ImportProgressCallback.INTERVAL, first.lines());
+            long expectedOffset = 0;
+            for (int i = 0; i < first.lines(); i++) {
+                expectedOffset += (unsignedNote(i) + "\n").getBytes(StandardCharsets.UTF_8).length;
+            }
+            assertEquals(expectedOffset, first.offset());
+
+            ImportSummary rest = ndb.processEventsFromFile(file, null, null, first.offset());
+            assertFalse(rest.isCancelled());
+            assertEquals(COUNT + 2, first.lines() + rest.lines());
+            assertEquals(COUNT, first.processed() + rest.processed());
+            assertEquals(1, first.failed() + rest.failed());
+            assertEquals(Files.size(file), rest.offset());
+            assertEquals(COUNT, TestEvents.awaitCount(ndb, notes, COUNT, 60_000));
+
+            assertThrows(IllegalArgumentException.class, () -> ndb.processEventsFromFile(file, null, null, -1));
+        }
+    }
+
+    @Test
+    @DisplayName("A cancelled byte batch should report the offset of the rest of the batch")
+    void testCancelBytes() throws Exception {
+        byte[] batch = Files.readAllBytes(export());
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             CancelToken token = new CancelToken()) {
+            ImportSummary first = ndb.processEventsBytes(batch, (lines, failed) -> token.cancel(), token);
+            assertTrue(first.isCancelled());
+            assertEquals(ImportProgressCallback.INTERVAL, first.lines());
+            assertEquals('\n', batch[(int) first.offset() - 1]);
+
+            byte[] remaining = Arrays.copyOfRange(batch, (int) first.offset(), batch.length);
+            ImportSummary rest = ndb.processEventsBytes(remaining, null, null);
+            assertEquals(COUNT + 2, first.lines() + rest.lines());
+            assertEquals(COUNT, first.processed() + rest.processed());
+            assertEquals(1, rest.failed());
+            assertEquals(remaining.length, rest.offset());
+        }
+    }
+
+    @Test
+    @DisplayName("A callback exception or a missing file should propagate")
+    void testErrors() throws Exception {
+        Path file = export();
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config)) {
+            IllegalStateException stop = new IllegalStateException("stop");
+            assertSame(stop, assertThrows(IllegalStateException.class,
+                () -> ndb.processEventsFromFile(file, (lines, failed) -> {
+                    throw stop;
+                }, null)));
+
+            Path missing = tempDir.resolve("missing.jsonl");
+            IOException e = assertThrows(IOException.class, () -> ndb.processEventsFromFile(missing));
+            assertTrue(e.getMessage().contains(missing.toString()), e.getMessage());
+        }
+    }
+}
@@ -0,0 +1,152 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.io.ByteArrayOutputStream;
+import java.io.IOException;
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Files;
+import java.nio.file.Path;
+import java.util.Arrays;
+import java.util.regex.Matcher;
+import java.util.regex.Pattern;
+import java.util.zip.GZIPOutputStream;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for ingesting gzip-compressed newline-delimited JSON.
+ */
+class ProcessEventsGzipTest {
+
+    private static final int COUNT = 2_000;
+
+    private static final Pattern INGESTED = Pattern.compile("after (\\d+) events were ingested");
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /** An unsigned note with a unique id, stored with validation skipped. */
+    private String unsignedNote(int i) {
+        return "{\"id\":\"" + String.format("%064x", i + 1) + "\",\"pubkey\":\"" + alice.pubkeyHex()
+            + "\",\"created_at\":" + (1_700_000_000L + i) + ",\"kind\":1,\"tags\":[],"
+            + "\"content\":\"note " + i + "\",\"sig\":\"" + "0".repeat(128) + "\"}";
+    }
+
+    /** A gzipped export of the notes numbered from {@code first} up to {@code end}. */
+    private byte[] gzipped(int first, int end) throws IOException {
+        ByteArrayOutputStream bytes = new ByteArrayOutputStream();
+        try (GZIPOutputStream out = new GZIPOutputStream(bytes)) {
+            for (int i = first; i < end; i++) {
+                out.write((unsignedNote(i) + "\n").getBytes(StandardCharsets.UTF_8));
+            }
+        }
+        return bytes.toByteArray();
+    }
+
+    @Test
+    @DisplayName("processEventsGzip should ingest every event of a gzipped batch")
+    void testGzipBytes() throws Exception {
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            assertEquals(COUNT, ndb.processEventsGzip(gzipped(0, COUNT)));
+            assertEquals(COUNT, TestEvents.awaitCount(ndb, notes, COUNT, 60_000));
+        }
+    }
+
+    @Test
+    @DisplayName("Concatenated gzip members should be read as one stream")
+    void testConcatenatedMembers() throws Exception {
+        ByteArrayOutputStream data = new ByteArrayOutputStream();
+        data.write(gzipped(0, COUNT / 2));
+        data.write(gzipped(COUNT / 2, COUNT));
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config)) {
+            assertEquals(COUNT, ndb.processEventsGzip(data.toByteArray()));
+        }
+    }
+
+    @Test
+    @DisplayName("A truncated stream should throw with the number of events ingested before it")
+    void testTruncatedBytes() throws Exception {
+        byte[] data = gzipped(0, COUNT);
+        byte[] truncated = Arrays.copyOf(data, data.length / 2);
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            NostrdbException e = assertThrows(NostrdbException.class, () -> ndb.processEventsGzip(truncated));
+            int ingested = ingested(e);
+            assertTrue(ingested > 0 && ingested < COUNT, e.getMessage());
+            assertEquals(ingested, TestEvents.awaitStable(ndb, notes, 500, 60_000));
+        }
+    }
+
+    @Test
+    @DisplayName("Data that is not gzip should throw without ingesting anything")
+    void testNotGzip() throws Exception {
+        byte[] plain = (unsignedNote(0) + "\n").getBytes(StandardCharsets.UTF_8);
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            NostrdbException e = assertThrows(NostrdbException.class, () -> ndb.processEventsGzip(plain));
+            assertEquals(0, ingested(e));
+        }
+    }
+
+    @Test
+    @DisplayName("processEventsFromFile should detect and decompress a gzipped file")
+    void testGzipFile() throws Exception {
+        Path file = tempDir.resolve("export.jsonl.gz");
+        Files.write(file, gzipped(0, COUNT));
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ImportSummary summary = ndb.processEventsFromFile(file);
+            assertEquals(COUNT, summary.lines());
+            assertEquals(COUNT, summary.processed());
+            assertEquals(0, summary.failed());
+            assertEquals(COUNT, TestEvents.awaitCount(ndb, notes, COUNT, 60_000));
+        }
+    }
+
+    @Test
+    @DisplayName("A cancelled gzipped import should resume from its offset in the decompressed stream")
+    void testResumeGzipFile() throws Exception {
+        Path file = tempDir.resolve("export.jsonl.gz");
+        Files.write(file, gzipped(0, COUNT));
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             CancelToken token = new CancelToken();
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ImportSummary first = ndb.processEventsFromFile(file, (lines, failed) -> token.cancel(), token, 0);
+            assertTrue(first.isCancelled());
+            assertEquals(ImportProgressCallback.INTERVAL, first.lines());
+
+            ImportSummary rest = ndb.processEventsFromFile(file, null, null, first.offset());
+            assertEquals(COUNT, first.processed() + rest.processed());
+            assertEquals(COUNT, TestEvents.awaitCount(ndb, notes, COUNT, 60_000));
+        }
+    }
+
+    @Test
+    @DisplayName("A truncated gzipped file should throw with the number of events ingested before it")
+    void testTruncatedFile() throws Exception {
+        byte[] data = gzipped(0, COUNT);
+        Path file = tempDir.resolve("export.jsonl.gz");
+        Files.write(file, Arrays.copyOf(data, data.length / 2));
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config)) {
+            NostrdbException e = assertThrows(NostrdbException.class, () -> ndb.processEventsFromFile(file));
+            int ingested = ingested(e);
+            assertTrue(ingested > 0 && ingested < COUNT, e.getMessage());
+        }
+    }
+
+    private static int ingested(NostrdbException e) {
+        Matcher m = INGESTED.matcher(e.getMessage());
+        assertTrue(m.find(), e.getMessage());
+        return Integer.parseInt(m.group(1));
+    }
+}
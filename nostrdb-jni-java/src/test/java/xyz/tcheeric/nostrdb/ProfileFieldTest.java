@@ -0,0 +1,69 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.Map;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for reading single profile fields.
+ */
+class ProfileFieldTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+
+    @Test
+    @DisplayName("Each field should read back from a stored profile, and be empty for a missing one")
+    void testFields() throws Exception {
+        Map<ProfileField, String> values = Map.of(
+            ProfileField.NAME, "alice",
+            ProfileField.DISPLAY_NAME, "Alice",
+            ProfileField.ABOUT, "about alice",
+            ProfileField.PICTURE, "https://example.com/alice.png",
+            ProfileField.BANNER, "https://example.com/banner.png",
+            ProfileField.NIP05, "alice@example.com",
+            ProfileField.LUD06, "lnurl1alice",
+            ProfileField.LUD16, "alice@wallet.example.com",
+            ProfileField.WEBSITE, "https://alice.example.com");
+        String content = "{\"name\":\"alice\",\"display_name\":\"Alice\",\"about\":\"about alice\","
+            + "\"picture\":\"https://example.com/alice.png\",\"banner\":\"https://example.com/banner.png\","
+            + "\"nip05\":\"alice@example.com\",\"lud06\":\"lnurl1alice\",\"lud16\":\"alice@wallet.example.com\","
+            + "\"website\":\"https://alice.example.com\"}";
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter profiles = Filter.builder().kinds(0).build()) {
+            ndb.processEvent(alice.event(0, T, content));
+            ndb.processEvent(bob.event(0, T, "{\"name\":\"bob\"}"));
+            assertEquals(2, TestEvents.awaitCount(ndb, profiles, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                byte[] a = HexUtil.decode(alice.pubkeyHex());
+                byte[] b = HexUtil.decode(bob.pubkeyHex());
+                byte[] missing = HexUtil.decode(TestEvents.forSeed(13).pubkeyHex());
+
+                for (ProfileField field : ProfileField.values()) {
+                    assertEquals(values.get(field), ndb.getProfileField(txn, a, field).orElseThrow(), field.name());
+                    assertTrue(ndb.getProfileField(txn, missing, field).isEmpty(), field.name());
+                }
+                assertEquals("bob", ndb.getProfileField(txn, b, ProfileField.NAME).orElseThrow());
+                assertTrue(ndb.getProfileField(txn, b, ProfileField.PICTURE).isEmpty());
+
+                assertThrows(IllegalArgumentException.class,
+                    () -> NostrdbNative.getProfileField(ndb.ptr(), txn.ptr(), a, 9));
+                assertThrows(IllegalArgumentException.class,
+                    () -> NostrdbNative.getProfileField(ndb.ptr(), txn.ptr(), a, -1));
+                assertThrows(IllegalArgumentException.class, () -> ndb.getProfileField(txn, a, null));
+                assertThrows(IllegalArgumentException.class,
+                    () -> ndb.getProfileField(txn, new byte[31], ProfileField.NAME));
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for the record fields nostrdb keeps alongside a profile.
+ */
+class ProfileRecordTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+
+    /**
+     * Position of a table's field in a flatbuffer, or -1 if it is absent.
+     */
+    private static int field(ByteBuffer buf, int table, int index) {
+        int vtable = table - buf.getInt(table);
+        int entry = 4 + 2 * index;
+        if (entry >= buf.getShort(vtable)) {
+            return -1;
+        }
+        int offset = buf.getShort(vtable + entry);
+        return offset == 0 ? -1 : table + offset;
+    }
+
+    /**
+     * Follow the offset stored at a position to the table or string it points to.
+     */
+    private static int deref(ByteBuffer buf, int pos) {
+        return pos + buf.getInt(pos);
+    }
+
+    private static String string(ByteBuffer buf, int pos) {
+        byte[] bytes = new byte[buf.getInt(pos)];
+        buf.get(pos + 4, bytes);
+        return new String(bytes, StandardCharsets.UTF_8);
+    }
+
+    @Test
+    @DisplayName("A stored profile should carry its receipt time and the key of its kind-0 note")
+    void testRecordFields() throws Exception {
+        String metadata = alice.event(0, T, "{\"name\":\"alice\",\"reactions\":false}");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(0, 1).build();
+             Filter notes = Filter.builder().kinds(1).build()) {
+            long before = System.currentTimeMillis() / 1000;
+            ndb.processEvent(metadata);
+            ndb.processEvent(alice.textNote(T + 1, "hello"));
+            ndb.processEvent(bob.textNote(T + 2, "no profile"));
+            assertEquals(3, TestEvents.awaitCount(ndb, all, 3, 10_000));
+            long after = System.currentTimeMillis() / 1000;
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                Profile profile = ndb.getProfileByPubkey(txn, alice.pubkeyHex()).orElseThrow();
+                assertEquals("alice", profile.name());
+                assertFalse(profile.reactions());
+
+                long receivedAt = profile.receivedAt().orElseThrow();
+                assertTrue(receivedAt >= before && receivedAt <= after, Long.toString(receivedAt));
+
+                long noteKey = profile.noteKey().orElseThrow();
+                assertEquals(ndb.getNoteKeyById(txn, TestEvents.idOf(metadata)).getAsLong(), noteKey);
+                Note note = ndb.getNoteByKey(txn, noteKey).orElseThrow();
+                assertEquals(0, note.kind());
+                assertEquals(TestEvents.idOf(metadata), note.id());
+
+                // The same fields come with profiles fetched in bulk; missing ones have none
+                var profiles = ndb.queryProfiles(txn, notes, 10, true);
+                assertEquals(noteKey, profiles.get(alice.pubkeyHex()).noteKey().orElseThrow());
+                Profile missing = profiles.get(bob.pubkeyHex());
+                assertTrue(missing.noteKey().isEmpty());
+                assertTrue(missing.receivedAt().isEmpty());
+                assertTrue(missing.reactions());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("The profile's kind-0 event should come back signed and unchanged")
+    void testProfileNote() throws Exception {
+        String metadata = alice.event(0, T, "{\"name\":\"alice\"}");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter profiles = Filter.builder().kinds(0).build()) {
+            ndb.processEvent(metadata);
+            assertEquals(1, TestEvents.awaitCount(ndb, profiles, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                Note original = Note.fromJson(metadata);
+                String json = ndb.getProfileNote(txn, alice.pubkeyHex()).orElseThrow();
+                Note note = Note.fromJson(json);
+                assertEquals(original.id(), note.id());
+                assertEquals(original.sig(), note.sig());
+                assertEquals(original.content(), note.content());
+                assertEquals(VerifyResult.OK, Ndb.verifyEvent(json));
+
+                assertTrue(ndb.getProfileNote(txn, bob.pubkeyHex()).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.getProfileNote(txn, new byte[31]));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A raw profile should be nostrdb's flatbuffer with the same fields as the JSON path")
+    void testRawProfile() throws Exception {
+        String metadata = alice.event(0, T, "{\"name\":\"alice\",\"about\":\"raw\"}");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter profiles = Filter.builder().kinds(0).build()) {
+            ndb.processEvent(metadata);
+            assertEquals(1, TestEvents.awaitCount(ndb, profiles, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                Profile profile = ndb.getProfileByPubkey(txn, alice.pubkeyHex()).orElseThrow();
+                byte[] raw = ndb.getProfileRawByPubkey(txn, HexUtil.decode(alice.pubkeyHex())).orElseThrow();
+
+                // NdbProfileRecord { profile, received_at, note_key, lnurl }
+                ByteBuffer buf = ByteBuffer.wrap(raw).order(ByteOrder.LITTLE_ENDIAN);
+                int record = deref(buf, 0);
+                assertEquals(profile.receivedAt().orElseThrow(), buf.getLong(field(buf, record, 1)));
+                assertEquals(profile.noteKey().orElseThrow(), buf.getLong(field(buf, record, 2)));
+
+                // NdbProfile { name, website, about, ... }
+                int fields = deref(buf, field(buf, record, 0));
+                assertEquals(profile.name(), string(buf, deref(buf, field(buf, fields, 0))));
+                assertEquals(-1, field(buf, fields, 1));
+                assertEquals("raw", string(buf, deref(buf, field(buf, fields, 2))));
+
+                assertTrue(ndb.getProfileRawByPubkey(txn, HexUtil.decode(bob.pubkeyHex())).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.getProfileRawByPubkey(txn, new byte[31]));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Profiles parsed from kind-0 JSON should have no record fields")
+    void testFromJson() {
+        Profile profile = Profile.fromJson("{\"name\":\"alice\"}");
+        assertTrue(profile.noteKey().isEmpty());
+        assertTrue(profile.receivedAt().isEmpty());
+        assertNull(profile.lnurl());
+        assertTrue(profile.reactions());
+    }
+}
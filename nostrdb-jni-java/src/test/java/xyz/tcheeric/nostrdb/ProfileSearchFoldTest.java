@@ -0,0 +1,74 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for profile search that ignores case and accents.
+ */
+class ProfileSearchFoldTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents jack = TestEvents.forSeed(11);
+    private final TestEvents jose = TestEvents.forSeed(12);
+    private final TestEvents isik = TestEvents.forSeed(13);
+    private final TestEvents isa = TestEvents.forSeed(14);
+    private final TestEvents renee = TestEvents.forSeed(15);
+
+    private List<String> fold(Ndb ndb, Transaction txn, String query) {
+        return ndb.searchProfiles(txn, query, 10, Ndb.PROFILE_SEARCH_FOLD).stream().map(HexUtil::encode).toList();
+    }
+
+    @Test
+    @DisplayName("Folded search should match regardless of case, accents, and Turkish i")
+    void testFold() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter profiles = Filter.builder().kinds(0).build()) {
+            ndb.processEvent(jack.event(0, T, "{\"name\":\"Jack\"}"));
+            ndb.processEvent(jose.event(0, T, "{\"name\":\"jm\",\"display_name\":\"Jos\u00e9 Mar\u00eda\"}"));
+            ndb.processEvent(isik.event(0, T, "{\"name\":\"I\u015f\u0131k\"}"));
+            ndb.processEvent(isa.event(0, T, "{\"name\":\"\u0130sa\"}"));
+            // Stored decomposed: e followed by a combining acute accent
+            ndb.processEvent(renee.event(0, T, "{\"name\":\"Rene\u0301e\"}"));
+            assertEquals(5, TestEvents.awaitCount(ndb, profiles, 5, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(List.of(jack.pubkeyHex()), fold(ndb, txn, "jack"));
+                assertEquals(List.of(jack.pubkeyHex()), fold(ndb, txn, "JA"));
+
+                assertEquals(List.of(jose.pubkeyHex()), fold(ndb, txn, "jose"));
+                assertEquals(List.of(jose.pubkeyHex()), fold(ndb, txn, "JOS\u00c9 mar"));
+                assertEquals(List.of(jose.pubkeyHex()), fold(ndb, txn, "jm"));
+
+                assertEquals(List.of(isik.pubkeyHex()), fold(ndb, txn, "isik"));
+                assertEquals(List.of(isik.pubkeyHex()), fold(ndb, txn, "I\u015eIK"));
+                assertEquals(List.of(isa.pubkeyHex()), fold(ndb, txn, "isa"));
+                assertEquals(List.of(isa.pubkeyHex()), fold(ndb, txn, "\u0131sa"));
+
+                assertEquals(List.of(renee.pubkeyHex()), fold(ndb, txn, "ren\u00e9e"));
+                assertEquals(List.of(renee.pubkeyHex()), fold(ndb, txn, "renee"));
+
+                assertTrue(fold(ndb, txn, "nobody").isEmpty());
+                assertEquals(1, ndb.searchProfiles(txn, "j", 1, Ndb.PROFILE_SEARCH_FOLD).size());
+
+                // Names come back as stored
+                List<ProfileMatch> matches = ndb.searchProfileNames(txn, "jose", 10, Ndb.PROFILE_SEARCH_FOLD);
+                assertEquals(1, matches.size());
+                assertEquals("Jos\u00e9 Mar\u00eda", matches.get(0).displayName());
+
+                assertThrows(IllegalArgumentException.class,
+                    () -> ndb.searchProfiles(txn, "  ", 10, Ndb.PROFILE_SEARCH_FOLD));
+                assertThrows(IllegalArgumentException.class, () -> ndb.searchProfiles(txn, "jack", 10, 1 << 5));
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for counting matching notes natively.
+ */
+class QueryCountTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("Should count zero and many matches without a limit")
+    void testCount() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter articles = Filter.builder().kinds(30023).build()) {
+            // More than one native query batch
+            for (int i = 0; i < 1500; i++) {
+                ndb.processEvent(alice.textNote(1_700_000_000L + i, "note " + i));
+            }
+            assertEquals(1500, TestEvents.awaitCount(ndb, notes, 1500, 30_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(1500, ndb.queryCount(txn, notes));
+                assertEquals(0, ndb.queryCount(txn, articles));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("The filter's own limit should cap the count")
+    void testFilterLimit() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter limited = Filter.builder().kinds(1).limit(5).build();
+             Filter generous = Filter.builder().kinds(1).limit(50).build()) {
+            for (int i = 0; i < 20; i++) {
+                ndb.processEvent(alice.textNote(1_700_000_000L + i, "note " + i));
+            }
+            assertEquals(20, TestEvents.awaitCount(ndb, notes, 20, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(5, ndb.queryCount(txn, limited));
+                assertEquals(20, ndb.queryCount(txn, generous));
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.HashSet;
+import java.util.List;
+import java.util.Set;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for paging through query results with a cursor.
+ */
+class QueryCursorTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /**
+     * An unsigned note with a unique id; signing 10k notes would make the test
+     * needlessly slow, so these are stored with validation skipped.
+     */
+    private String unsignedNote(int i) {
+        return "{\"id\":\"" + String.format("%064x", i + 1) + "\",\"pubkey\":\"" + alice.pubkeyHex()
+            + "\",\"created_at\":" + (1_700_000_000L + i / 4) + ",\"kind\":1,\"tags\":[],"
+            + "\"content\":\"note " + i + "\",\"sig\":\"" + "0".repeat(128) + "\"}";
+    }
+
+    @Test
+    @DisplayName("Should page through 10k notes returning every key exactly once")
+    void testPaging() throws Exception {
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            for (int i = 0; i < 10_000; i++) {
+                ndb.processEvent(unsignedNote(i));
+            }
+            assertEquals(10_000, TestEvents.awaitCount(ndb, notes, 10_000, 60_000));
+
+            try (Transaction txn = ndb.beginTransaction();
+                 QueryCursor cursor = ndb.queryCursor(txn, notes)) {
+                List<Long> paged = new ArrayList<>();
+                List<QueryResult> page;
+                while (!(page = cursor.next(100)).isEmpty()) {
+                    assertTrue(page.size() <= 100);
+                    page.forEach(result -> paged.add(result.noteKey()));
+                }
+
+                assertEquals(10_000, paged.size());
+                Set<Long> unique = new HashSet<>(paged);
+                assertEquals(10_000, unique.size(), "Every key should be returned exactly once");
+                assertEquals(ndb.query(txn, notes, Ndb.FILTER_LIMIT).stream().map(QueryResult::noteKey).toList(),
+                    paged, "Pages should follow query order");
+                assertTrue(cursor.next(100).isEmpty());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("The filter's own limit should cap the total")
+    void testFilterLimit() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter limited = Filter.builder().kinds(1).limit(7).build()) {
+            for (int i = 0; i < 10; i++) {
+                ndb.processEvent(alice.textNote(1_700_000_000L + i, "note " + i));
+            }
+            assertEquals(10, TestEvents.awaitCount(ndb, notes, 10, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction();
+                 QueryCursor cursor = ndb.queryCursor(txn, limited)) {
+                assertEquals(5, cursor.next(5).size());
+                assertEquals(2, cursor.next(5).size());
+                assertTrue(cursor.next(5).isEmpty());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A cursor should fail cleanly once its transaction has ended")
+    void testTransactionEnded() {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            Transaction txn = ndb.beginTransaction();
+            QueryCursor cursor = ndb.queryCursor(txn, notes);
+            assertTrue(cursor.next(10).isEmpty());
+            assertThrows(IllegalArgumentException.class, () -> cursor.next(0));
+
+            txn.close();
+            IllegalStateException e = assertThrows(IllegalStateException.class, () -> cursor.next(10));
+            assertTrue(e.getMessage().contains("transaction"), e.getMessage());
+
+            // Freeing the cursor after the transaction is fine
+            cursor.close();
+            assertFalse(cursor.isOpen());
+            assertThrows(IllegalStateException.class, () -> cursor.next(10));
+        }
+    }
+}
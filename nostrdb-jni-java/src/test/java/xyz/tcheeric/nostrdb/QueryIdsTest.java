@@ -0,0 +1,53 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for queries returning event ids.
+ */
+class QueryIdsTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("Ids should match the ids of the individually fetched notes, in query order")
+    void testIdsMatchNotes() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter limited = Filter.builder().kinds(1).limit(4).build();
+             Filter none = Filter.builder().kinds(30023).build()) {
+            for (int i = 0; i < 25; i++) {
+                ndb.processEvent(alice.textNote(1_700_000_000L + i, "note " + i));
+            }
+            assertEquals(25, TestEvents.awaitCount(ndb, notes, 25, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> results = ndb.query(txn, notes, 20);
+                List<String> expected = results.stream()
+                    .map(result -> ndb.getNoteByKey(txn, result.noteKey()).orElseThrow().id())
+                    .toList();
+                List<String> ids = ndb.queryIds(txn, notes, 20).stream().map(HexUtil::encode).toList();
+                assertEquals(expected, ids);
+
+                assertEquals(4, ndb.queryIds(txn, limited, Ndb.FILTER_LIMIT).size());
+                assertTrue(ndb.queryIds(txn, none, 10).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.queryIds(txn, notes, 0));
+
+                // The ids can be fed straight back into an ids filter
+                byte[][] some = ndb.queryIds(txn, notes, 3).toArray(byte[][]::new);
+                try (Filter byId = Filter.builder().ids(some).build()) {
+                    assertEquals(3, ndb.query(txn, byId, 10).size());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for queries writing into a caller-provided direct buffer.
+ */
+class QueryIntoTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static final int NOTES = 5;
+    private static final int SIZE = 4 + NOTES * 8;
+
+    private static List<QueryResult> read(ByteBuffer buffer) {
+        ByteBuffer view = buffer.duplicate().order(ByteOrder.LITTLE_ENDIAN);
+        long[] keys = new long[view.getInt(0)];
+        for (int i = 0; i < keys.length; i++) {
+            keys[i] = view.getLong(4 + i * 8);
+        }
+        return QueryResult.fromKeys(keys);
+    }
+
+    private Ndb openWithNotes(Filter notes) throws Exception {
+        Ndb ndb = Ndb.open(tempDir.resolve("db"));
+        for (int i = 0; i < NOTES; i++) {
+            ndb.processEvent(alice.textNote(1_700_000_000L + i, "note " + i));
+        }
+        assertEquals(NOTES, TestEvents.awaitCount(ndb, notes, NOTES, 10_000));
+        return ndb;
+    }
+
+    @Test
+    @DisplayName("Should fill exact-fit and oversized buffers with the query framing")
+    void testFits() throws Exception {
+        try (Filter notes = Filter.builder().kinds(1).build();
+             Ndb ndb = openWithNotes(notes);
+             Transaction txn = ndb.beginTransaction()) {
+            List<QueryResult> expected = ndb.query(txn, notes, 10);
+
+            ByteBuffer exact = ByteBuffer.allocateDirect(SIZE);
+            assertEquals(SIZE, ndb.queryInto(txn, notes, 10, exact));
+            assertEquals(expected, read(exact));
+
+            ByteBuffer oversized = ByteBuffer.allocateDirect(4096);
+            for (int i = 0; i < oversized.capacity(); i++) {
+                oversized.put(i, (byte) 0x7f);
+            }
+            assertEquals(SIZE, ndb.queryInto(txn, notes, 10, oversized));
+            assertEquals(expected, read(oversized));
+            assertEquals((byte) 0x7f, oversized.get(SIZE));
+
+            // Writes start at index 0 whatever the position, and the buffer can be reused
+            oversized.position(100);
+            assertEquals(4 + 2 * 8, ndb.queryInto(txn, notes, 2, oversized));
+            assertEquals(expected.subList(0, 2), read(oversized));
+            assertEquals(100, oversized.position());
+        }
+    }
+
+    @Test
+    @DisplayName("Should return the negated required size and leave a too-small buffer untouched")
+    void testTooSmall() throws Exception {
+        try (Filter notes = Filter.builder().kinds(1).build();
+             Filter none = Filter.builder().kinds(30023).build();
+             Ndb ndb = openWithNotes(notes);
+             Transaction txn = ndb.beginTransaction()) {
+            ByteBuffer small = ByteBuffer.allocateDirect(SIZE - 1);
+            assertEquals(-SIZE, ndb.queryInto(txn, notes, 10, small));
+            for (int i = 0; i < small.capacity(); i++) {
+                assertEquals(0, small.get(i));
+            }
+
+            // Retry with the reported size
+            ByteBuffer retry = ByteBuffer.allocateDirect(-ndb.queryInto(txn, notes, 10, small));
+            assertEquals(SIZE, ndb.queryInto(txn, notes, 10, retry));
+            assertEquals(ndb.query(txn, notes, 10), read(retry));
+
+            assertEquals(-4, ndb.queryInto(txn, none, 10, ByteBuffer.allocateDirect(0)));
+            ByteBuffer empty = ByteBuffer.allocateDirect(4);
+            assertEquals(4, ndb.queryInto(txn, none, 10, empty));
+            assertTrue(read(empty).isEmpty());
+        }
+    }
+
+    @Test
+    @DisplayName("Should reject heap, read-only, and null buffers")
+    void testInvalidBuffers() throws Exception {
+        try (Filter notes = Filter.builder().kinds(1).build();
+             Ndb ndb = openWithNotes(notes);
+             Transaction txn = ndb.beginTransaction()) {
+            ByteBuffer direct = ByteBuffer.allocateDirect(SIZE);
+            assertThrows(IllegalArgumentException.class, () -> ndb.queryInto(txn, notes, 10, ByteBuffer.allocate(SIZE)));
+            assertThrows(IllegalArgumentException.class, () -> ndb.queryInto(txn, notes, 10, direct.asReadOnlyBuffer()));
+            assertThrows(IllegalArgumentException.class, () -> ndb.queryInto(txn, notes, 10, null));
+            assertThrows(IllegalArgumentException.class, () -> ndb.queryInto(txn, notes, 0, direct));
+
+            // The native check doesn't rely on the wrapper
+            assertThrows(IllegalArgumentException.class, () ->
+                NostrdbNative.queryInto(ndb.ptr(), txn.ptr(), notes.ptr(), 10, ByteBuffer.allocate(SIZE)));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.List;
+import java.util.function.IntFunction;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests that the long[] query and poll natives match the byte-framed ones.
+ */
+class QueryKeysTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static List<Long> keys(List<QueryResult> results) {
+        return results.stream().map(QueryResult::noteKey).toList();
+    }
+
+    private static List<Long> boxed(long[] keys) {
+        List<Long> list = new ArrayList<>(keys.length);
+        for (long key : keys) {
+            list.add(key);
+        }
+        return list;
+    }
+
+    /** Poll until {@code count} keys arrive or ten seconds pass. */
+    private static List<Long> pollAll(IntFunction<List<Long>> poll, int count) throws InterruptedException {
+        List<Long> keys = new ArrayList<>();
+        long deadline = System.currentTimeMillis() + 10_000;
+        while (keys.size() < count && System.currentTimeMillis() < deadline) {
+            List<Long> batch = poll.apply(count);
+            if (batch.isEmpty()) {
+                Thread.sleep(20);
+            }
+            keys.addAll(batch);
+        }
+        return keys;
+    }
+
+    @Test
+    @DisplayName("queryKeys should return the same keys in the same order as query")
+    void testQueryKeys() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter limited = Filter.builder().kinds(1).limit(7).build();
+             Filter none = Filter.builder().kinds(30023).build()) {
+            for (int i = 0; i < 50; i++) {
+                // Some notes share a timestamp
+                ndb.processEvent(alice.textNote(1_700_000_000L + i / 3, "note " + i));
+            }
+            assertEquals(50, TestEvents.awaitCount(ndb, notes, 50, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                for (Filter filter : List.of(notes, limited, none)) {
+                    for (int limit : new int[] {1, 20, 100, Ndb.FILTER_LIMIT}) {
+                        List<QueryResult> framed = QueryResult.parseResults(
+                            NostrdbNative.query(ndb.ptr(), txn.ptr(), filter.ptr(), limit));
+                        long[] array = NostrdbNative.queryKeys(ndb.ptr(), txn.ptr(), filter.ptr(), limit);
+                        assertEquals(keys(framed), boxed(array));
+                    }
+                }
+                assertEquals(50, ndb.query(txn, notes, 100).size());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("pollForNoteKeys should return the same keys in the same order as pollForNotes")
+    void testPollForNoteKeys() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Subscription framedSub = ndb.subscribe(notes);
+             Subscription arraySub = ndb.subscribe(notes)) {
+            for (int i = 0; i < 30; i++) {
+                ndb.processEvent(alice.textNote(1_700_000_000L + i, "note " + i));
+            }
+
+            List<Long> framed = pollAll(max -> keys(QueryResult.parseResults(
+                NostrdbNative.pollForNotes(ndb.ptr(), framedSub.id(), max))), 30);
+            List<Long> array = pollAll(max -> boxed(
+                NostrdbNative.pollForNoteKeys(ndb.ptr(), arraySub.id(), max)), 30);
+
+            assertEquals(30, framed.size());
+            assertEquals(framed, array);
+            assertTrue(ndb.pollForNotes(arraySub, 10).isEmpty());
+        }
+    }
+}
@@ -0,0 +1,78 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for the newest-note-per-author query.
+ */
+class QueryLatestPerAuthorTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+    private final TestEvents carol = TestEvents.forSeed(13);
+
+    private static final long T = 1_700_000_000L;
+
+    @Test
+    @DisplayName("Should return each author's newest match, newest first, omitting authors without one")
+    void testLatestPerAuthor() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1, 7).build();
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter limited = Filter.builder().kinds(1).limit(1).build();
+             Filter onlyBob = Filter.builder().kinds(1).authors(bob.pubkey()).build()) {
+            for (int i = 0; i < 3; i++) {
+                ndb.processEvent(alice.textNote(T + i, "alice " + i));
+                ndb.processEvent(bob.textNote(T + 10 + i, "bob " + i));
+            }
+            // Alice's newest event overall is a reaction, which the filter excludes
+            ndb.processEvent(alice.event(7, T + 50, "+"));
+            // Carol has no text notes at all
+            ndb.processEvent(carol.event(7, T + 60, "+"));
+            assertEquals(8, TestEvents.awaitCount(ndb, all, 8, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<LatestNote> latest = ndb.queryLatestPerAuthor(txn, notes,
+                    List.of(alice.pubkey(), bob.pubkey(), carol.pubkey()));
+                assertEquals(2, latest.size());
+
+                assertArrayEquals(bob.pubkey(), latest.get(0).pubkey());
+                assertEquals(T + 12, latest.get(0).createdAt());
+                assertEquals("bob 2", ndb.getNoteByKey(txn, latest.get(0).noteKey()).orElseThrow().content());
+
+                assertEquals(alice.pubkeyHex(), latest.get(1).pubkeyHex());
+                assertEquals(T + 2, latest.get(1).createdAt());
+                assertEquals("alice 2", ndb.getNoteByKey(txn, latest.get(1).noteKey()).orElseThrow().content());
+
+                // Without the kind restriction, reactions count
+                List<LatestNote> anyKind = ndb.queryLatestPerAuthor(txn, all,
+                    List.of(alice.pubkey(), bob.pubkey(), carol.pubkey()));
+                assertEquals(List.of(carol.pubkeyHex(), alice.pubkeyHex(), bob.pubkeyHex()),
+                    anyKind.stream().map(LatestNote::pubkeyHex).toList());
+
+                // The filter's own limit doesn't cap the number of authors
+                assertEquals(2, ndb.queryLatestPerAuthor(txn, limited,
+                    List.of(alice.pubkey(), bob.pubkey())).size());
+
+                // The filter's own authors still apply, and repeats are reported once
+                List<LatestNote> restricted = ndb.queryLatestPerAuthor(txn, onlyBob,
+                    List.of(alice.pubkey(), bob.pubkey(), bob.pubkey()));
+                assertEquals(1, restricted.size());
+                assertEquals(bob.pubkeyHex(), restricted.get(0).pubkeyHex());
+
+                assertTrue(ndb.queryLatestPerAuthor(txn, notes, List.of()).isEmpty());
+                assertThrows(IllegalArgumentException.class, () ->
+                    ndb.queryLatestPerAuthor(txn, notes, List.of(new byte[31])));
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for merging the results of overlapping filters.
+ */
+class QueryMergedUniqueTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+
+    private static final long T = 1_700_000_000L;
+
+    private static List<String> contents(Ndb ndb, Transaction txn, List<QueryResult> results) {
+        return results.stream()
+            .map(result -> ndb.getNoteByKey(txn, result.noteKey()).orElseThrow().content())
+            .toList();
+    }
+
+    @Test
+    @DisplayName("A note matched by an author filter and a tag filter should appear once, in time order")
+    void testOverlap() throws Exception {
+        String[] nostr = {"t", "nostr"};
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter byAlice = Filter.builder().kinds(1).authors(alice.pubkey()).build();
+             Filter tagged = Filter.builder().kinds(1).hashtags("nostr").build();
+             Filter tagLimited = Filter.builder().kinds(1).hashtags("nostr").limit(1).build()) {
+            ndb.processEvent(alice.event(1, T + 10, "alice 10"));
+            ndb.processEvent(bob.event(1, T + 20, "bob 20", nostr));
+            ndb.processEvent(alice.event(1, T + 30, "alice 30", nostr));
+            ndb.processEvent(bob.event(1, T + 40, "bob 40"));
+            ndb.processEvent(bob.event(1, T + 50, "bob 50", nostr));
+            ndb.processEvent(alice.event(1, T + 60, "alice 60"));
+            assertEquals(6, TestEvents.awaitCount(ndb, notes, 6, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> merged = ndb.queryMergedUnique(txn, List.of(byAlice, tagged), 10);
+                assertEquals(List.of("alice 60", "bob 50", "alice 30", "bob 20", "alice 10"),
+                    contents(ndb, txn, merged));
+
+                // The order of the filters doesn't matter
+                assertEquals(merged, ndb.queryMergedUnique(txn, List.of(tagged, byAlice), Ndb.FILTER_LIMIT));
+
+                // The limit applies after merging
+                assertEquals(List.of("alice 60", "bob 50", "alice 30"),
+                    contents(ndb, txn, ndb.queryMergedUnique(txn, List.of(byAlice, tagged), 3)));
+
+                // Each filter keeps its own limit
+                assertEquals(List.of("alice 60", "bob 50", "alice 30", "alice 10"),
+                    contents(ndb, txn, ndb.queryMergedUnique(txn, List.of(byAlice, tagLimited), 10)));
+
+                // Identical filters collapse to one
+                assertEquals(ndb.query(txn, tagged, 10), ndb.queryMergedUnique(txn, List.of(tagged, tagged), 10));
+
+                assertThrows(IllegalArgumentException.class, () -> ndb.queryMergedUnique(txn, List.of(), 10));
+                assertThrows(IllegalArgumentException.class, () ->
+                    ndb.queryMergedUnique(txn, List.of(byAlice), 0));
+            }
+        }
+    }
+}
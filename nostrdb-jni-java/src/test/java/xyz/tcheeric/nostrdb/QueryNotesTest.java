@@ -0,0 +1,92 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for queries that return full notes in a single native call.
+ */
+class QueryNotesTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    @Test
+    @DisplayName("Notes returned by queryNotes should match getNoteByKey")
+    void testMatchesGetNoteByKey() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            for (int i = 0; i < 500; i++) {
+                ndb.processEvent(alice.event(1, 1_700_000_000L + i, "note \"" + i + "\"\n",
+                    new String[] {"t", "tag" + i}, new String[] {"p", alice.pubkeyHex()}));
+            }
+            assertEquals(500, TestEvents.awaitCount(ndb, notes, 500, 30_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> results = ndb.query(txn, notes, 500);
+                List<Note> fetched = ndb.queryNotes(txn, notes, 500);
+                assertEquals(500, fetched.size());
+
+                for (int i = 0; i < results.size(); i++) {
+                    Note expected = ndb.getNoteByKey(txn, results.get(i).noteKey()).orElseThrow();
+                    assertEquals(expected.toJson(), fetched.get(i).toJson());
+                }
+                assertEquals(List.of("t", "tag499"), fetched.get(0).tags().get(0));
+                assertEquals("note \"499\"\n", fetched.get(0).content());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Id elements of reply tags should round-trip as 64-char hex")
+    void testReplyTagIds() throws Exception {
+        String root = alice.textNote(1_700_000_000L, "root");
+        String rootId = TestEvents.idOf(root);
+        String reply = alice.event(1, 1_700_000_001L, "reply",
+            new String[] {"e", rootId, "", "root"}, new String[] {"p", alice.pubkeyHex()});
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter replies = Filter.builder().kinds(1).events(rootId).build()) {
+            ndb.processEvent(root);
+            ndb.processEvent(reply);
+            assertEquals(1, TestEvents.awaitCount(ndb, replies, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                long key = ndb.query(txn, replies, 1).get(0).noteKey();
+                List<List<String>> expected = List.of(List.of("e", rootId, "", "root"), List.of("p", alice.pubkeyHex()));
+                Note byKey = ndb.getNoteByKey(txn, key).orElseThrow();
+                assertEquals(expected, byKey.tags());
+                assertEquals(rootId, byKey.getTagValue("e"));
+                assertEquals(expected, ndb.queryNotes(txn, replies, 1).get(0).tags());
+                assertEquals(expected, ndb.getNoteById(txn, TestEvents.idOf(reply)).orElseThrow().tags());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Should apply the limit and the filter's own limit")
+    void testLimits() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter limited = Filter.builder().kinds(1).limit(3).build();
+             Filter none = Filter.builder().kinds(30023).build()) {
+            for (int i = 0; i < 10; i++) {
+                ndb.processEvent(alice.textNote(1_700_000_000L + i, "note " + i));
+            }
+            assertEquals(10, TestEvents.awaitCount(ndb, notes, 10, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(5, ndb.queryNotes(txn, notes, 5).size());
+                assertEquals(3, ndb.queryNotes(txn, limited, 5).size());
+                assertTrue(ndb.queryNotes(txn, none, 5).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.queryNotes(txn, notes, 0));
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.Collections;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for ascending and descending query orders.
+ */
+class QueryOrderTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static final long T = 1_700_000_000L;
+
+    private static List<Long> timestamps(Ndb ndb, Transaction txn, List<QueryResult> results) {
+        return results.stream()
+            .map(result -> ndb.getNoteByKey(txn, result.noteKey()).orElseThrow().createdAt())
+            .toList();
+    }
+
+    private static <E> List<E> reversed(List<E> list) {
+        List<E> copy = new ArrayList<>(list);
+        Collections.reverse(copy);
+        return copy;
+    }
+
+    @Test
+    @DisplayName("Should return the newest or oldest matches in either order, with ties kept consistent")
+    void testOrders() throws Exception {
+        long[] times = {T + 100, T + 200, T + 200, T + 300, T + 400, T + 400};
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            for (int i = 0; i < times.length; i++) {
+                ndb.processEvent(alice.textNote(times[i], "note " + i));
+            }
+            assertEquals(6, TestEvents.awaitCount(ndb, notes, 6, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> newest = ndb.queryOrdered(txn, notes, 3, QueryOrder.NEWEST_FIRST);
+                assertEquals(List.of(T + 400, T + 400, T + 300), timestamps(ndb, txn, newest));
+                assertEquals(ndb.query(txn, notes, 3), newest);
+
+                List<QueryResult> newestReversed = ndb.queryOrdered(txn, notes, 3, QueryOrder.NEWEST_REVERSED);
+                assertEquals(List.of(T + 300, T + 400, T + 400), timestamps(ndb, txn, newestReversed));
+                assertEquals(reversed(newest), newestReversed);
+
+                List<QueryResult> oldest = ndb.queryOrdered(txn, notes, 3, QueryOrder.OLDEST_FIRST);
+                assertEquals(List.of(T + 100, T + 200, T + 200), timestamps(ndb, txn, oldest));
+
+                // Ascending order is the exact reverse of descending order, ties included
+                List<QueryResult> all = ndb.queryOrdered(txn, notes, Ndb.FILTER_LIMIT, QueryOrder.OLDEST_FIRST);
+                assertEquals(reversed(ndb.query(txn, notes, 10)), all);
+                assertEquals(all.subList(0, 3), oldest);
+                assertEquals(all.subList(3, 6), newestReversed);
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Should combine the limit with the filter's own limit")
+    void testFilterLimit() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter limited = Filter.builder().kinds(1).limit(2).build()) {
+            for (int i = 0; i < 5; i++) {
+                ndb.processEvent(alice.textNote(T + i, "note " + i));
+            }
+            assertEquals(5, TestEvents.awaitCount(ndb, notes, 5, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(List.of(T, T + 1),
+                    timestamps(ndb, txn, ndb.queryOrdered(txn, notes, 2, QueryOrder.OLDEST_FIRST)));
+                assertEquals(List.of(T + 3, T + 4),
+                    timestamps(ndb, txn, ndb.queryOrdered(txn, limited, 10, QueryOrder.NEWEST_REVERSED)));
+                assertEquals(List.of(T + 3, T + 4),
+                    timestamps(ndb, txn, ndb.queryOrdered(txn, limited, Ndb.FILTER_LIMIT, QueryOrder.NEWEST_REVERSED)));
+                assertThrows(IllegalArgumentException.class, () -> ndb.queryOrdered(txn, notes, 10, null));
+                assertThrows(IllegalArgumentException.class, () ->
+                    NostrdbNative.queryOrdered(ndb.ptr(), txn.ptr(), notes.ptr(), 10, 3));
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.HashSet;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for keyset pagination.
+ */
+class QueryPageTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static final long T = 1_700_000_000L;
+
+    @Test
+    @DisplayName("Pages should cover every match exactly once when ties straddle a page boundary")
+    void testTiesAcrossPages() throws Exception {
+        // Five notes share T + 50, so every page size below splits them
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            for (int i = 0; i < 12; i++) {
+                long createdAt = i >= 4 && i < 9 ? T + 50 : T + i * 10;
+                ndb.processEvent(alice.textNote(createdAt, "note " + i));
+            }
+            assertEquals(12, TestEvents.awaitCount(ndb, notes, 12, 10_000));
+
+            for (int pageSize : new int[] {1, 2, 3, 4, 7, 12, 20}) {
+                List<QueryResult> all = new ArrayList<>();
+                List<Long> timestamps = new ArrayList<>();
+                byte[] cursor = null;
+                int pages = 0;
+                do {
+                    // A fresh transaction per page: the cursor carries no native state
+                    try (Transaction txn = ndb.beginTransaction()) {
+                        QueryPage page = ndb.queryPage(txn, notes, pageSize, cursor);
+                        assertTrue(page.results().size() <= pageSize);
+                        for (QueryResult result : page.results()) {
+                            all.add(result);
+                            timestamps.add(ndb.getNoteByKey(txn, result.noteKey()).orElseThrow().createdAt());
+                        }
+                        cursor = page.nextCursor();
+                        assertEquals(cursor != null, page.hasMore());
+                    }
+                    assertTrue(++pages <= 13, "paging did not terminate");
+                } while (cursor != null);
+
+                assertEquals(12, all.size(), "page size " + pageSize);
+                assertEquals(12, new HashSet<>(all).size(), "page size " + pageSize);
+                for (int i = 1; i < timestamps.size(); i++) {
+                    assertTrue(timestamps.get(i - 1) >= timestamps.get(i));
+                }
+                try (Transaction txn = ndb.beginTransaction()) {
+                    assertEquals(new HashSet<>(ndb.query(txn, notes, 100)), new HashSet<>(all));
+                }
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Should respect the filter's bounds and ignore its limit")
+    void testFilterBounds() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Filter bounded = Filter.builder().kinds(1).since(T + 2).until(T + 6).limit(2).build();
+             Filter none = Filter.builder().kinds(30023).build()) {
+            for (int i = 0; i < 10; i++) {
+                ndb.processEvent(alice.textNote(T + i, "note " + i));
+            }
+            assertEquals(10, TestEvents.awaitCount(ndb, notes, 10, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                QueryPage first = ndb.queryPage(txn, bounded, 3, null);
+                assertEquals(3, first.results().size());
+                assertTrue(first.hasMore());
+
+                QueryPage second = ndb.queryPage(txn, bounded, 3, first.nextCursor());
+                assertEquals(2, second.results().size());
+                assertFalse(second.hasMore());
+                assertNull(second.nextCursor());
+
+                QueryPage empty = ndb.queryPage(txn, none, 3, null);
+                assertTrue(empty.results().isEmpty());
+                assertFalse(empty.hasMore());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Should reject bad page sizes and malformed cursors")
+    void testInvalidArguments() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build();
+             Transaction txn = ndb.beginTransaction()) {
+            assertThrows(IllegalArgumentException.class, () -> ndb.queryPage(txn, notes, 0, null));
+            assertThrows(IllegalArgumentException.class, () -> ndb.queryPage(txn, notes, -1, null));
+            assertThrows(IllegalArgumentException.class, () -> ndb.queryPage(txn, notes, 10, new byte[8]));
+            assertThrows(IllegalArgumentException.class, () -> ndb.queryPage(txn, notes, 10, new byte[0]));
+            assertThrows(IllegalArgumentException.class, () ->
+                NostrdbNative.queryPage(ndb.ptr(), txn.ptr(), notes.ptr(), 0, null));
+        }
+    }
+}
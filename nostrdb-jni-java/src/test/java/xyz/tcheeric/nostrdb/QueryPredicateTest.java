@@ -0,0 +1,113 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Path;
+import java.util.ArrayList;
+import java.util.List;
+import java.util.Set;
+import java.util.concurrent.atomic.AtomicInteger;
+import java.util.stream.Collectors;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for queries filtered by a Java predicate.
+ */
+class QueryPredicateTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static Set<Long> keys(List<QueryResult> results) {
+        return results.stream().map(QueryResult::noteKey).collect(Collectors.toSet());
+    }
+
+    private static NotePredicate contentContains(String needle) {
+        return noteJson -> new String(noteJson, StandardCharsets.UTF_8).contains(needle);
+    }
+
+    @Test
+    @DisplayName("Should keep only notes accepted by the predicate")
+    void testContentSubstring() throws Exception {
+        List<String> events = new ArrayList<>();
+        for (int i = 0; i < 30; i++) {
+            String content = i % 3 == 0 ? "zap me " + i : "plain note " + i;
+            events.add(alice.textNote(1_700_000_000L + i, content));
+        }
+
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            for (String event : events) {
+                ndb.processEvent(event);
+            }
+            assertEquals(30, TestEvents.awaitCount(ndb, notes, 30, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> all = ndb.query(txn, notes, 100, contentContains("zap me"));
+                assertEquals(10, all.size());
+                for (QueryResult result : all) {
+                    assertTrue(ndb.getNoteByKey(txn, result.noteKey()).orElseThrow().content().startsWith("zap me"));
+                }
+
+                // A limit smaller than the first batch still finds accepted notes
+                // beyond it, and never returns more than the limit
+                List<QueryResult> some = ndb.query(txn, notes, 4, contentContains("zap me"));
+                assertEquals(4, some.size());
+                assertTrue(keys(all).containsAll(keys(some)));
+
+                List<QueryResult> none = ndb.query(txn, notes, 10, noteJson -> false);
+                assertTrue(none.isEmpty());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("Each candidate should be evaluated at most once")
+    void testEvaluatesCandidatesOnce() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            for (int i = 0; i < 20; i++) {
+                ndb.processEvent(alice.textNote(1_700_000_000L + i, "note " + i));
+            }
+            assertEquals(20, TestEvents.awaitCount(ndb, notes, 20, 10_000));
+
+            AtomicInteger calls = new AtomicInteger();
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> results = ndb.query(txn, notes, 3, noteJson -> {
+                    calls.incrementAndGet();
+                    return new String(noteJson, StandardCharsets.UTF_8).contains("\"note 1\"");
+                });
+                assertEquals(1, results.size());
+                assertEquals(20, calls.get(), "Every candidate should be tested exactly once");
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("An exception thrown by the predicate should abort the query")
+    void testPredicateException() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(alice.textNote(1_700_000_001L, "boom"));
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                IllegalStateException e = assertThrows(IllegalStateException.class, () ->
+                    ndb.query(txn, notes, 10, noteJson -> {
+                        throw new IllegalStateException("predicate failed");
+                    }));
+                assertEquals("predicate failed", e.getMessage());
+
+                // The database and transaction remain usable
+                assertEquals(1, ndb.query(txn, notes, 10, noteJson -> true).size());
+                assertThrows(IllegalArgumentException.class, () -> ndb.query(txn, notes, 10, null));
+                assertThrows(IllegalArgumentException.class, () -> ndb.query(txn, notes, 0, noteJson -> true));
+            }
+        }
+    }
+}
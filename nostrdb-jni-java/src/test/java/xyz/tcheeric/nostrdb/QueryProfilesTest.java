@@ -0,0 +1,67 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+import java.util.Map;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for fetching the profiles of a query's authors.
+ */
+class QueryProfilesTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+    private final TestEvents carol = TestEvents.forSeed(13);
+
+    private static final long T = 1_700_000_000L;
+
+    @Test
+    @DisplayName("Should return each author's profile once, optionally including authors without one")
+    void testQueryProfiles() throws Exception {
+        String[] thread = {"t", "thread"};
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(0, 1).build();
+             Filter replies = Filter.builder().kinds(1).hashtags("thread").build()) {
+            ndb.processEvent(alice.event(0, T, "{\"name\":\"alice\"}"));
+            ndb.processEvent(bob.event(0, T, "{\"name\":\"bob\"}"));
+            ndb.processEvent(alice.event(1, T + 10, "first", thread));
+            ndb.processEvent(carol.event(1, T + 20, "second", thread));
+            ndb.processEvent(bob.event(1, T + 30, "third", thread));
+            ndb.processEvent(alice.event(1, T + 40, "fourth", thread));
+            ndb.processEvent(bob.event(1, T + 50, "off topic"));
+            assertEquals(7, TestEvents.awaitCount(ndb, all, 7, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                // Authors come in the order of their newest reply
+                Map<String, Profile> stored = ndb.queryProfiles(txn, replies, 10, false);
+                assertEquals(List.of(alice.pubkeyHex(), bob.pubkeyHex()), List.copyOf(stored.keySet()));
+                assertEquals("alice", stored.get(alice.pubkeyHex()).name());
+                assertEquals("bob", stored.get(bob.pubkeyHex()).name());
+
+                Map<String, Profile> everyone = ndb.queryProfiles(txn, replies, 10, true);
+                assertEquals(List.of(alice.pubkeyHex(), bob.pubkeyHex(), carol.pubkeyHex()),
+                    List.copyOf(everyone.keySet()));
+                Profile missing = everyone.get(carol.pubkeyHex());
+                assertNull(missing.name());
+                assertNull(missing.about());
+
+                // The limit bounds the notes queried, not the profiles
+                assertEquals(List.of(alice.pubkeyHex(), bob.pubkeyHex()),
+                    List.copyOf(ndb.queryProfiles(txn, replies, 2, true).keySet()));
+
+                try (Filter none = Filter.builder().kinds(1).hashtags("nobody").build()) {
+                    assertTrue(ndb.queryProfiles(txn, none, 10, true).isEmpty());
+                }
+                assertThrows(IllegalArgumentException.class, () -> ndb.queryProfiles(txn, replies, 0, true));
+            }
+        }
+    }
+}
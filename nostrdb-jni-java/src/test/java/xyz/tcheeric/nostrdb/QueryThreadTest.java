@@ -0,0 +1,75 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for NIP-10 thread queries.
+ */
+class QueryThreadTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+
+    private static final long T = 1_700_000_000L;
+
+    private static List<String> contents(Ndb ndb, Transaction txn, List<QueryResult> results) {
+        return results.stream()
+            .map(result -> ndb.getNoteByKey(txn, result.noteKey()).orElseThrow().content())
+            .toList();
+    }
+
+    @Test
+    @DisplayName("Replies should be split into direct and nested by markers or tag position")
+    void testThread() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter all = Filter.builder().kinds(1, 7).build()) {
+            String rootEvent = alice.textNote(T, "root");
+            String root = TestEvents.idOf(rootEvent);
+            String markedReply = bob.event(1, T + 1, "marked reply", new String[] {"e", root, "", "root"});
+            String marked = TestEvents.idOf(markedReply);
+
+            ndb.processEvent(rootEvent);
+            ndb.processEvent(markedReply);
+            ndb.processEvent(alice.event(1, T + 2, "marked nested",
+                new String[] {"e", root, "", "root"}, new String[] {"e", marked, "", "reply"}));
+            ndb.processEvent(alice.event(1, T + 3, "positional reply", new String[] {"e", root}));
+            ndb.processEvent(bob.event(1, T + 4, "positional nested",
+                new String[] {"e", root}, new String[] {"e", marked}));
+            ndb.processEvent(bob.event(1, T + 5, "mention", new String[] {"e", root, "", "mention"}));
+            ndb.processEvent(bob.event(1, T + 6, "explicit reply",
+                new String[] {"e", root, "", "root"}, new String[] {"e", root, "", "reply"}));
+            ndb.processEvent(bob.event(7, T + 7, "+", new String[] {"e", root}));
+            assertEquals(8, TestEvents.awaitCount(ndb, all, 8, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                ThreadReplies thread = ndb.queryThread(txn, root, 10);
+                assertEquals(List.of("explicit reply", "positional reply", "marked reply"),
+                    contents(ndb, txn, thread.direct()));
+                assertEquals(List.of("mention", "positional nested", "marked nested"),
+                    contents(ndb, txn, thread.nested()));
+
+                // The limit applies to both groups together, newest first
+                ThreadReplies newest = ndb.queryThread(txn, root, 2);
+                assertEquals(List.of("explicit reply"), contents(ndb, txn, newest.direct()));
+                assertEquals(List.of("mention"), contents(ndb, txn, newest.nested()));
+
+                // A reply's own thread holds the notes replying to it
+                ThreadReplies sub = ndb.queryThread(txn, marked, 10);
+                assertEquals(List.of("positional nested", "marked nested"), contents(ndb, txn, sub.direct()));
+                assertTrue(sub.nested().isEmpty());
+
+                assertThrows(IllegalArgumentException.class, () -> ndb.queryThread(txn, new byte[16], 10));
+                assertThrows(IllegalArgumentException.class, () -> ndb.queryThread(txn, root, 0));
+            }
+        }
+    }
+}
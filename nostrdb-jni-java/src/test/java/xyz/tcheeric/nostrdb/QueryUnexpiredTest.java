@@ -0,0 +1,64 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for queries that skip NIP-40 expired notes.
+ */
+class QueryUnexpiredTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static final long T = 1_700_000_000L;
+
+    private static String[] expiration(String value) {
+        return new String[] {"expiration", value};
+    }
+
+    private static List<String> contents(List<Note> notes) {
+        return notes.stream().map(Note::content).toList();
+    }
+
+    @Test
+    @DisplayName("Expired notes should be left out before the limit, and malformed expirations ignored")
+    void testExpiration() throws Exception {
+        long now = T + 1000;
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(alice.event(1, T + 1, "plain"));
+            ndb.processEvent(alice.event(1, T + 2, "future", expiration(String.valueOf(now + 60))));
+            ndb.processEvent(alice.event(1, T + 3, "malformed", expiration("soon")));
+            ndb.processEvent(alice.event(1, T + 4, "negative", expiration("-5")));
+            ndb.processEvent(alice.event(1, T + 5, "expires now", expiration(String.valueOf(now))));
+            ndb.processEvent(alice.event(1, T + 6, "expired", expiration(String.valueOf(T + 500))));
+            assertEquals(6, TestEvents.awaitCount(ndb, notes, 6, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(List.of("negative", "malformed", "future", "plain"),
+                    contents(ndb.queryNotesUnexpired(txn, notes, 10, now)));
+
+                // The two newest notes are expired, so the limit reaches past them
+                List<QueryResult> keys = ndb.queryUnexpired(txn, notes, 2, now);
+                assertEquals(List.of("negative", "malformed"), keys.stream()
+                    .map(result -> ndb.getNoteByKey(txn, result.noteKey()).orElseThrow().content())
+                    .toList());
+
+                // Before its expiration a note is live; once it passes, it's gone
+                assertEquals(6, ndb.queryUnexpired(txn, notes, Ndb.FILTER_LIMIT, T + 500 - 1).size());
+                assertEquals(3, ndb.queryUnexpired(txn, notes, Ndb.FILTER_LIMIT, now + 60).size());
+
+                assertThrows(IllegalArgumentException.class, () -> ndb.queryUnexpired(txn, notes, 10, -1));
+                assertThrows(IllegalArgumentException.class, () -> ndb.queryNotesUnexpired(txn, notes, 0, now));
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for raw note bytes.
+ */
+class RawNoteTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static final long T = 1_700_000_000L;
+
+    @Test
+    @DisplayName("Raw notes should carry the id, pubkey, timestamp, and kind at their documented offsets")
+    void testRoundTrip() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1, 7).build()) {
+            for (int i = 0; i < 5; i++) {
+                ndb.processEvent(alice.textNote(T + i, "note " + i));
+            }
+            ndb.processEvent(alice.event(7, T + 10, "+", new String[] {"t", "raw"}));
+            assertEquals(6, TestEvents.awaitCount(ndb, notes, 6, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<QueryResult> results = ndb.query(txn, notes, 10);
+                List<byte[]> raw = ndb.queryNotesRaw(txn, notes, 10);
+                assertEquals(results.size(), raw.size());
+
+                for (int i = 0; i < results.size(); i++) {
+                    long key = results.get(i).noteKey();
+                    Note note = ndb.getNoteByKey(txn, key).orElseThrow();
+                    byte[] single = ndb.getNoteRawByKey(txn, key).orElseThrow();
+                    assertArrayEquals(single, raw.get(i));
+
+                    assertEquals(note.id(), HexUtil.encode(RawNote.id(single)));
+                    assertEquals(note.pubkey(), HexUtil.encode(RawNote.pubkey(single)));
+                    assertEquals(note.createdAt(), RawNote.createdAt(single));
+                    assertEquals(note.kind(), RawNote.kind(single));
+                    assertTrue(single.length > RawNote.KIND_OFFSET + 4);
+
+                    // The id addresses the same note
+                    assertEquals(note.id(), ndb.getNoteById(txn, RawNote.id(single)).orElseThrow().id());
+                }
+
+                assertTrue(ndb.getNoteRawByKey(txn, 999_999).isEmpty());
+                assertEquals(2, ndb.queryNotesRaw(txn, notes, 2).size());
+                assertThrows(IllegalArgumentException.class, () -> ndb.queryNotesRaw(txn, notes, 0));
+            }
+        }
+    }
+}
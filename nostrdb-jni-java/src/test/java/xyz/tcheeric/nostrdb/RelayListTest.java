@@ -0,0 +1,59 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for reading an author's NIP-65 relay list.
+ */
+class RelayListTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+
+    @Test
+    @DisplayName("The newest relay list should be split into read and write relays")
+    void testRelayList() throws Exception {
+        String older = alice.event(10002, T, "", new String[] {"r", "wss://old.example.com"});
+        String newest = alice.event(10002, T + 10, "",
+            new String[] {"r", "wss://both.example.com"},
+            new String[] {"r", "wss://inbox.example.com", "read"},
+            new String[] {"r", "wss://outbox.example.com", "write"},
+            new String[] {"p", bob.pubkeyHex()},
+            new String[] {"r", "wss://unknown.example.com", "sometimes"},
+            new String[] {"r", ""},
+            new String[] {"r", "wss://inbox.example.com", "write"});
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter lists = Filter.builder().kinds(10002).build()) {
+            ndb.processEvent(older);
+            ndb.processEvent(newest);
+            assertEquals(2, TestEvents.awaitCount(ndb, lists, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                RelayList relays = ndb.getRelayList(txn, HexUtil.decode(alice.pubkeyHex())).orElseThrow();
+                assertEquals(List.of("wss://both.example.com", "wss://inbox.example.com", "wss://outbox.example.com"),
+                    relays.relays().stream().map(RelayList.Relay::url).toList());
+                assertEquals(List.of("wss://both.example.com", "wss://inbox.example.com"), relays.readRelays());
+                assertEquals(List.of("wss://both.example.com", "wss://inbox.example.com", "wss://outbox.example.com"),
+                    relays.writeRelays());
+
+                RelayList.Relay outbox = relays.relays().get(2);
+                assertFalse(outbox.read());
+                assertTrue(outbox.write());
+
+                assertTrue(ndb.getRelayList(txn, HexUtil.decode(bob.pubkeyHex())).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.getRelayList(txn, new byte[31]));
+            }
+        }
+    }
+}
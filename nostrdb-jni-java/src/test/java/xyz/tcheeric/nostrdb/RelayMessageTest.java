@@ -0,0 +1,64 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for ingesting messages as relays send them.
+ */
+class RelayMessageTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static String eventMessage(String subscriptionId, String event) {
+        return "[\"EVENT\",\"" + subscriptionId + "\"," + event + "]";
+    }
+
+    @Test
+    @DisplayName("A relay EVENT message should be ingested and other messages skipped")
+    void testRelayMessage() throws Exception {
+        String event = alice.textNote(T, "from a relay");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            assertTrue(ndb.processRelayEvent(eventMessage("sub1", event)));
+            assertFalse(ndb.processRelayEvent("[\"EOSE\",\"sub1\"]"));
+            assertFalse(ndb.processRelayEvent(" [ \"NOTICE\", \"slow down\"]"));
+            assertThrows(NostrdbException.class, () -> ndb.processRelayEvent("{\"kind\":1}"));
+            assertThrows(NostrdbException.class, () -> ndb.processRelayEvent("[1]"));
+            assertEquals(1, TestEvents.awaitCount(ndb, notes, 1, 10_000));
+        }
+    }
+
+    @Test
+    @DisplayName("A mixed stream should ingest its events and count what was skipped or failed")
+    void testMixedStream() throws Exception {
+        String first = alice.textNote(T, "first");
+        String second = alice.textNote(T + 1, "second");
+        String stream = String.join("\n",
+            eventMessage("sub1", first),
+            "[\"EOSE\",\"sub1\"]",
+            eventMessage("sub2", second),
+            "",
+            "[\"OK\",\"" + TestEvents.idOf(first) + "\",true,\"\"]",
+            "[\"NOTICE\",\"hello\"]",
+            "[\"EOSE\",\"sub2\"]",
+            "garbage");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            RelayMessageCounts counts = ndb.processRelayEvents(stream);
+            assertEquals(2, counts.events());
+            assertEquals(4, counts.skipped());
+            assertEquals(1, counts.failed());
+            assertEquals(2, TestEvents.awaitCount(ndb, notes, 2, 10_000));
+        }
+    }
+}
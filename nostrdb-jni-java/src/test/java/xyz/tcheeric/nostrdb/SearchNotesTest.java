@@ -0,0 +1,69 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for full-text note search.
+ */
+class SearchNotesTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static final long T = 1_700_000_000L;
+
+    private static String content(Ndb ndb, Transaction txn, SearchHit hit) {
+        return ndb.getNoteByKey(txn, hit.noteKey()).orElseThrow().content();
+    }
+
+    @Test
+    @DisplayName("Hits should report where the query matched in each note's content")
+    void testMatchOffsets() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(alice.textNote(T + 1, "zorbulate the widgets"));
+            ndb.processEvent(alice.textNote(T + 2, "Time to ZORBULATE everything"));
+            ndb.processEvent(alice.textNote(T + 3, "caf\u00e9 cr\u00e8me, then Zorbulate zorbulate"));
+            ndb.processEvent(alice.textNote(T + 4, "nothing to see here"));
+            assertEquals(4, TestEvents.awaitCount(ndb, notes, 4, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<SearchHit> newest = ndb.searchNotes(txn, "zorbulate", 10, SearchOrder.NEWEST_FIRST);
+                assertEquals(3, newest.size());
+                assertEquals(List.of(T + 3, T + 2, T + 1), newest.stream().map(SearchHit::createdAt).toList());
+                for (SearchHit hit : newest) {
+                    String matched = hit.matchIn(content(ndb, txn, hit));
+                    assertTrue(matched.equalsIgnoreCase("zorbulate"), matched);
+                    assertEquals(9, hit.matchLength());
+                }
+                // Offsets count UTF-8 bytes: the two accented letters take two each
+                assertEquals(19, newest.get(0).matchOffset());
+                assertEquals(8, newest.get(1).matchOffset());
+                assertEquals(0, newest.get(2).matchOffset());
+
+                // The note mentioning the word twice ranks first
+                List<SearchHit> relevant = ndb.searchNotes(txn, "zorbulate", 10, SearchOrder.RELEVANCE);
+                assertEquals(newest.get(0).noteKey(), relevant.get(0).noteKey());
+                assertEquals(List.of(T + 3, T + 2, T + 1), relevant.stream().map(SearchHit::createdAt).toList());
+
+                assertEquals(1, ndb.searchNotes(txn, "zorbulate", 1, SearchOrder.NEWEST_FIRST).size());
+                assertTrue(ndb.searchNotes(txn, "quuxified", 10, SearchOrder.NEWEST_FIRST).isEmpty());
+
+                assertThrows(IllegalArgumentException.class, () ->
+                    ndb.searchNotes(txn, "  ", 10, SearchOrder.NEWEST_FIRST));
+                assertThrows(IllegalArgumentException.class, () ->
+                    ndb.searchNotes(txn, "zorbulate", 0, SearchOrder.NEWEST_FIRST));
+                assertThrows(IllegalArgumentException.class, () ->
+                    ndb.searchNotes(txn, "zorbulate", 10, null));
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+import java.util.List;
+import java.util.Map;
+import java.util.function.Function;
+import java.util.stream.Collectors;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for profile search results that carry names.
+ */
+class SearchProfileNamesTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+
+    @Test
+    @DisplayName("Matches should carry each profile's name and display name")
+    void testNames() throws Exception {
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter profiles = Filter.builder().kinds(0).build()) {
+            ndb.processEvent(alice.event(0, T, "{\"name\":\"willa\",\"display_name\":\"Alice W\"}"));
+            ndb.processEvent(bob.event(0, T, "{\"name\":\"william\"}"));
+            assertEquals(2, TestEvents.awaitCount(ndb, profiles, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                List<ProfileMatch> matches = ndb.searchProfileNames(txn, "wil", 10);
+                Map<String, ProfileMatch> byPubkey = matches.stream()
+                    .collect(Collectors.toMap(m -> HexUtil.encode(m.pubkey()), Function.identity(), (a, b) -> a));
+                assertEquals(2, byPubkey.size());
+
+                ProfileMatch willa = byPubkey.get(alice.pubkeyHex());
+                assertEquals("willa", willa.name());
+                assertEquals("Alice W", willa.displayName());
+                assertEquals("Alice W", willa.bestDisplayName());
+
+                ProfileMatch william = byPubkey.get(bob.pubkeyHex());
+                assertEquals("william", william.name());
+                assertEquals("", william.displayName());
+                assertEquals("william", william.bestDisplayName());
+
+                // Same hits as the pubkey-only search
+                assertEquals(ndb.searchProfiles(txn, "wil", 10).stream().map(HexUtil::encode).toList(),
+                    matches.stream().map(m -> HexUtil.encode(m.pubkey())).toList());
+                assertTrue(ndb.searchProfileNames(txn, "nobody", 10).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.searchProfileNames(txn, "wil", 0));
+            }
+        }
+    }
+}
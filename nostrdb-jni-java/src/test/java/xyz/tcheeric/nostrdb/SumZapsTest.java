@@ -0,0 +1,82 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for totalling zap receipts.
+ */
+class SumZapsTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+    private final TestEvents bob = TestEvents.forSeed(12);
+    private final TestEvents wallet = TestEvents.forSeed(20);
+
+    private static final long T = 1_700_000_000L;
+
+    /** The tail of an invoice after its amount; bech32 data never contains a '1'. */
+    private static final String DATA = "1pj9qxzsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3";
+
+    private String zapRequest(String noteId, String millisats) {
+        return bob.event(9734, T, "", new String[] {"e", noteId}, new String[] {"amount", millisats});
+    }
+
+    private String receipt(long createdAt, String noteId, String[]... tags) {
+        String[][] all = new String[tags.length + 1][];
+        all[0] = new String[] {"e", noteId};
+        System.arraycopy(tags, 0, all, 1, tags.length);
+        return wallet.event(9735, createdAt, "", all);
+    }
+
+    @Test
+    @DisplayName("Should total receipt amounts from zap requests, falling back to the invoice")
+    void testSumZaps() throws Exception {
+        String note = alice.textNote(T, "zap me");
+        String id = TestEvents.idOf(note);
+        String other = TestEvents.idOf(alice.textNote(T + 1, "not me"));
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"));
+             Filter receipts = Filter.builder().kinds(9735).build()) {
+            ndb.processEvent(note);
+            // Amount from the zap request: 21 sats
+            ndb.processEvent(receipt(T + 1, id, new String[] {"bolt11", "lnbc210n" + DATA},
+                new String[] {"description", zapRequest(id, "21000")}));
+            // No zap request: 2500 micro-BTC, i.e. 250,000 sats
+            ndb.processEvent(receipt(T + 2, id, new String[] {"bolt11", "LNBC2500U" + DATA.toUpperCase()}));
+            // Malformed zap request: 1 milli-BTC, i.e. 100,000 sats
+            ndb.processEvent(receipt(T + 3, id, new String[] {"bolt11", "lnbc1m" + DATA},
+                new String[] {"description", "{not json"}));
+            // 10 pico-BTC is 1 millisat
+            ndb.processEvent(receipt(T + 4, id, new String[] {"bolt11", "lightning:lnbc10p" + DATA}));
+            // Neither a requested nor an invoice amount
+            ndb.processEvent(receipt(T + 5, id, new String[] {"bolt11", "lnbc" + DATA},
+                new String[] {"description", bob.event(9734, T, "", new String[] {"e", id})}));
+            // A zap for another note
+            ndb.processEvent(receipt(T + 6, other, new String[] {"bolt11", "lnbc5m" + DATA}));
+            assertEquals(6, TestEvents.awaitCount(ndb, receipts, 6, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                ZapTotal total = ndb.sumZaps(txn, id);
+                assertEquals(21_000L + 250_000_000L + 100_000_000L + 1L, total.totalMillisats());
+                assertEquals(350_021L, total.totalSats());
+                assertEquals(4, total.count());
+                assertEquals(1, total.unparsableCount());
+
+                assertEquals(500_000_000L, ndb.sumZaps(txn, other).totalMillisats());
+
+                ZapTotal none = ndb.sumZaps(txn, new byte[32]);
+                assertEquals(0, none.totalMillisats());
+                assertEquals(0, none.count());
+                assertEquals(0, none.unparsableCount());
+
+                assertThrows(IllegalArgumentException.class, () -> ndb.sumZaps(txn, new byte[16]));
+            }
+        }
+    }
+}
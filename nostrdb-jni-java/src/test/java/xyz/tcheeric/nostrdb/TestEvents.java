@@ -0,0 +1,373 @@
+package xyz.tcheeric.nostrdb;
+
+import java.math.BigInteger;
+import java.nio.charset.StandardCharsets;
+import java.security.MessageDigest;
+import java.security.NoSuchAlgorithmException;
+import java.util.Arrays;
+
+/**
+ * Test fixture that produces correctly signed Nostr events.
+ *
+ * <p>nostrdb verifies event ids and BIP-340 signatures, so tests that need
+ * events to actually be stored cannot use hand-written JSON. This class
+ * implements just enough secp256k1 arithmetic to sign events and verify
+ * signatures; it is slow and not constant-time, and must never be used
+ * outside of tests.
+ */
+final class TestEvents {
+
+    private static final BigInteger P = new BigInteger(
+        "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F", 16);
+    private static final BigInteger N = new BigInteger(
+        "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141", 16);
+    private static final BigInteger[] G = {
+        new BigInteger("79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798", 16),
+        new BigInteger("483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8", 16)
+    };
+
+    private final BigInteger secret;
+    private final byte[] pubkey;
+
+    private TestEvents(BigInteger secret) {
+        BigInteger[] point = mulG(secret);
+        this.secret = point[1].testBit(0) ? N.subtract(secret) : secret;
+        this.pubkey = bytes32(point[0]);
+    }
+
+    /**
+     * Create a signer with a deterministic key derived from a seed.
+     *
+     * @param seed Any positive number; different seeds give different authors
+     */
+    static TestEvents forSeed(long seed) {
+        BigInteger secret = new BigInteger(1, sha256(("nostrdb-jni-test-" + seed).getBytes(StandardCharsets.UTF_8)))
+            .mod(N.subtract(BigInteger.ONE)).add(BigInteger.ONE);
+        return new TestEvents(secret);
+    }
+
+    /** The author's public key (32 bytes, x-only). */
+    byte[] pubkey() {
+        return pubkey.clone();
+    }
+
+    /** The author's public key as hex. */
+    String pubkeyHex() {
+        return HexUtil.encode(pubkey);
+    }
+
+    /** The author's secret key (32 bytes), for signing outside this class. */
+    byte[] secretKey() {
+        return bytes32(secret);
+    }
+
+    /**
+     * Create a signed event.
+     *
+     * @param kind Event kind
+     * @param createdAt Unix timestamp
+     * @param content Event content
+     * @param tags Tags, each an array of strings
+     * @return The event as a JSON object string
+     */
+    String event(int kind, long createdAt, String content, String[]... tags) {
+        StringBuilder tagsJson = new StringBuilder("[");
+        for (int i = 0; i < tags.length; i++) {
+            if (i > 0) {
+                tagsJson.append(',');
+            }
+            tagsJson.append('[');
+            for (int j = 0; j < tags[i].length; j++) {
+                if (j > 0) {
+                    tagsJson.append(',');
+                }
+                tagsJson.append(quote(tags[i][j]));
+            }
+            tagsJson.append(']');
+        }
+        tagsJson.append(']');
+
+        String pubkeyHex = pubkeyHex();
+        String serialized = "[0," + quote(pubkeyHex) + "," + createdAt + "," + kind + ","
+            + tagsJson + "," + quote(content) + "]";
+        byte[] id = sha256(serialized.getBytes(StandardCharsets.UTF_8));
+
+        return "{\"id\":" + quote(HexUtil.encode(id))
+            + ",\"pubkey\":" + quote(pubkeyHex)
+            + ",\"created_at\":" + createdAt
+            + ",\"kind\":" + kind
+            + ",\"tags\":" + tagsJson
+            + ",\"content\":" + quote(content)
+            + ",\"sig\":" + quote(HexUtil.encode(sign(id))) + "}";
+    }
+
+    /**
+     * Create a signed kind 1 text note.
+     */
+    String textNote(long createdAt, String content) {
+        return event(1, createdAt, content);
+    }
+
+    /**
+     * Extract the id from an event created by this fixture.
+     */
+    static String idOf(String eventJson) {
+        int start = eventJson.indexOf("\"id\":\"") + 6;
+        return eventJson.substring(start, start + 64);
+    }
+
+    /**
+     * Wait until a query returns at least {@code expected} results.
+     *
+     * <p>nostrdb ingests events on background threads, so they become visible
+     * to queries shortly after {@code processEvent} returns.
+     *
+     * @return The number of results when waiting stopped
+     */
+    static int awaitCount(Ndb ndb, Filter filter, int expected, long timeoutMs) throws InterruptedException {
+        long deadline = System.currentTimeMillis() + timeoutMs;
+        int count = count(ndb, filter);
+        while (count < expected && System.currentTimeMillis() < deadline) {
+            Thread.sleep(10);
+            count = count(ndb, filter);
+        }
+        return count;
+    }
+
+    /**
+     * Wait until the number of query results stops changing.
+     *
+     * @param quietMs How long the count must stay the same
+     * @return The settled number of results
+     */
+    static int awaitStable(Ndb ndb, Filter filter, long quietMs, long timeoutMs) throws InterruptedException {
+        long deadline = System.currentTimeMillis() + timeoutMs;
+        int count = count(ndb, filter);
+        long changedAt = System.currentTimeMillis();
+        while (System.currentTimeMillis() - changedAt < quietMs && System.currentTimeMillis() < deadline) {
+            Thread.sleep(10);
+            int next = count(ndb, filter);
+            if (next != count) {
+                count = next;
+                changedAt = System.currentTimeMillis();
+            }
+        }
+        return count;
+    }
+
+    private static int count(Ndb ndb, Filter filter) {
+        try (Transaction txn = ndb.beginTransaction()) {
+            return ndb.query(txn, filter, 1_000_000).size();
+        }
+    }
+
+    /**
+     * Create a BIP-340 signature over a 32-byte message (zero auxiliary randomness).
+     */
+    byte[] sign(byte[] message) {
+        byte[] d = bytes32(secret);
+        byte[] t = xor(d, taggedHash("BIP0340/aux", new byte[32]));
+        BigInteger k = new BigInteger(1, taggedHash("BIP0340/nonce", t, pubkey, message)).mod(N);
+        BigInteger[] r = mulG(k);
+        if (r[1].testBit(0)) {
+            k = N.subtract(k);
+        }
+        byte[] rx = bytes32(r[0]);
+        BigInteger e = new BigInteger(1, taggedHash("BIP0340/challenge", rx, pubkey, message)).mod(N);
+
+        byte[] sig = new byte[64];
+        System.arraycopy(rx, 0, sig, 0, 32);
+        System.arraycopy(bytes32(k.add(e.multiply(secret)).mod(N)), 0, sig, 32, 32);
+        return sig;
+    }
+
+    /**
+     * Verify a BIP-340 signature over a 32-byte message, independently of the
+     * native library.
+     *
+     * @param pubkey 32-byte x-only public key
+     * @param message 32-byte message (an event id)
+     * @param sig 64-byte signature
+     * @return true if the signature is valid
+     */
+    static boolean verify(byte[] pubkey, byte[] message, byte[] sig) {
+        BigInteger[] point = liftX(new BigInteger(1, pubkey));
+        BigInteger r = new BigInteger(1, Arrays.copyOfRange(sig, 0, 32));
+        BigInteger s = new BigInteger(1, Arrays.copyOfRange(sig, 32, 64));
+        if (point == null || r.compareTo(P) >= 0 || s.compareTo(N) >= 0) {
+            return false;
+        }
+        BigInteger e = new BigInteger(1, taggedHash("BIP0340/challenge", bytes32(r), pubkey, message)).mod(N);
+        BigInteger[] rPoint = add(mulG(s), mul(point, N.subtract(e)));
+        return rPoint != null && !rPoint[1].testBit(0) && rPoint[0].equals(r);
+    }
+
+    /** Quote a string as a NIP-01 JSON string. */
+    private static String quote(String s) {
+        StringBuilder out = new StringBuilder("\"");
+        for (char c : s.toCharArray()) {
+            switch (c) {
+                case '"' -> out.append("\\\"");
+                case '\\' -> out.append("\\\\");
+                case '\n' -> out.append("\\n");
+                case '\r' -> out.append("\\r");
+                case '\t' -> out.append("\\t");
+                case '\b' -> out.append("\\b");
+                case '\f' -> out.append("\\f");
+                default -> {
+                    if (c < 0x20) {
+                        out.append(String.format("\\u%04x", (int) c));
+                    } else {
+                        out.append(c);
+                    }
+                }
+            }
+        }
+        return out.append('"').toString();
+    }
+
+    // ------------------------------------------------------------------------
+    // secp256k1 (null is the point at infinity)
+    // ------------------------------------------------------------------------
+
+    /** 2^i * G in affine coordinates, so that k * G needs no doublings. */
+    private static final BigInteger[][] G_POWERS = new BigInteger[256][];
+
+    static {
+        G_POWERS[0] = G;
+        for (int i = 1; i < 256; i++) {
+            G_POWERS[i] = add(G_POWERS[i - 1], G_POWERS[i - 1]);
+        }
+    }
+
+    /** Affine point addition. */
+    private static BigInteger[] add(BigInteger[] a, BigInteger[] b) {
+        if (a == null) {
+            return b;
+        }
+        if (b == null) {
+            return a;
+        }
+        BigInteger lambda;
+        if (a[0].equals(b[0])) {
+            if (!a[1].equals(b[1]) || a[1].signum() == 0) {
+                return null;
+            }
+            lambda = a[0].pow(2).multiply(BigInteger.valueOf(3))
+                .multiply(a[1].shiftLeft(1).modInverse(P)).mod(P);
+        } else {
+            lambda = b[1].subtract(a[1]).multiply(b[0].subtract(a[0]).modInverse(P)).mod(P);
+        }
+        BigInteger x = lambda.pow(2).subtract(a[0]).subtract(b[0]).mod(P);
+        BigInteger y = lambda.multiply(a[0].subtract(x)).subtract(a[1]).mod(P);
+        return new BigInteger[] {x, y};
+    }
+
+    /** k * G, accumulated in Jacobian coordinates with a single final inversion. */
+    private static BigInteger[] mulG(BigInteger k) {
+        BigInteger[] acc = null; // {X, Y, Z}
+        for (int i = 0; i < k.bitLength(); i++) {
+            if (k.testBit(i)) {
+                acc = addMixed(acc, G_POWERS[i]);
+            }
+        }
+        return toAffine(acc);
+    }
+
+    /** k * point by double-and-add; slow, but only used to verify. */
+    private static BigInteger[] mul(BigInteger[] point, BigInteger k) {
+        BigInteger[] acc = null;
+        BigInteger[] power = point;
+        for (int i = 0; i < k.bitLength(); i++) {
+            if (k.testBit(i)) {
+                acc = add(acc, power);
+            }
+            power = add(power, power);
+        }
+        return acc;
+    }
+
+    /** The point with x coordinate x and even y, or null if there is none. */
+    private static BigInteger[] liftX(BigInteger x) {
+        if (x.compareTo(P) >= 0) {
+            return null;
+        }
+        BigInteger c = x.pow(3).add(BigInteger.valueOf(7)).mod(P);
+        BigInteger y = c.modPow(P.add(BigInteger.ONE).shiftRight(2), P);
+        if (!y.multiply(y).mod(P).equals(c)) {
+            return null;
+        }
+        return new BigInteger[] {x, y.testBit(0) ? P.subtract(y) : y};
+    }
+
+    /** Jacobian + affine point addition. */
+    private static BigInteger[] addMixed(BigInteger[] a, BigInteger[] b) {
+        if (a == null) {
+            return new BigInteger[] {b[0], b[1], BigInteger.ONE};
+        }
+        BigInteger zz = a[2].multiply(a[2]).mod(P);
+        BigInteger u2 = b[0].multiply(zz).mod(P);
+        BigInteger s2 = b[1].multiply(a[2]).multiply(zz).mod(P);
+        BigInteger h = u2.subtract(a[0]).mod(P);
+        BigInteger r = s2.subtract(a[1]).mod(P);
+        if (h.signum() == 0) {
+            // Same x: fall back to the affine formulas for doubling/infinity
+            BigInteger[] sum = add(toAffine(a), b);
+            return sum == null ? null : new BigInteger[] {sum[0], sum[1], BigInteger.ONE};
+        }
+        BigInteger hh = h.multiply(h).mod(P);
+        BigInteger hhh = h.multiply(hh).mod(P);
+        BigInteger v = a[0].multiply(hh).mod(P);
+        BigInteger x = r.multiply(r).subtract(hhh).subtract(v.shiftLeft(1)).mod(P);
+        BigInteger y = r.multiply(v.subtract(x)).subtract(a[1].multiply(hhh)).mod(P);
+        return new BigInteger[] {x, y, a[2].multiply(h).mod(P)};
+    }
+
+    private static BigInteger[] toAffine(BigInteger[] p) {
+        if (p == null) {
+            return null;
+        }
+        BigInteger zInv = p[2].modInverse(P);
+        BigInteger zInv2 = zInv.multiply(zInv).mod(P);
+        return new BigInteger[] {p[0].multiply(zInv2).mod(P), p[1].multiply(zInv2).multiply(zInv).mod(P)};
+    }
+
+    private static byte[] bytes32(BigInteger value) {
+        byte[] raw = value.toByteArray();
+        byte[] out = new byte[32];
+        int copy = Math.min(raw.length, 32);
+        System.arraycopy(raw, raw.length - copy, out, 32 - copy, copy);
+        return out;
+    }
+
+    private static byte[] xor(byte[] a, byte[] b) {
+        byte[] out = new byte[a.length];
+        for (int i = 0; i < a.length; i++) {
+            out[i] = (byte) (a[i] ^ b[i]);
+        }
+        return out;
+    }
+
+    private static byte[] taggedHash(String tag, byte[]... parts) {
+        byte[] tagHash = sha256(tag.getBytes(StandardCharsets.UTF_8));
+        MessageDigest digest = sha256Digest();
+        digest.update(tagHash);
+        digest.update(tagHash);
+        for (byte[] part : parts) {
+            digest.update(part);
+        }
+        return digest.digest();
+    }
+
+    private static byte[] sha256(byte[] data) {
+        return sha256Digest().digest(data);
+    }
+
+    private static MessageDigest sha256Digest() {
+        try {
+            return MessageDigest.getInstance("SHA-256");
+        } catch (NoSuchAlgorithmException e) {
+            throw new IllegalStateException(e);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for verifying events without ingesting them.
+ */
+class VerifyEventTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    private static final long T = 1_700_000_000L;
+
+    private static String sigOf(String event) {
+        int start = event.indexOf("\"sig\":\"") + 7;
+        return event.substring(start, start + 128);
+    }
+
+    @Test
+    @DisplayName("A valid event should verify, tampered content should fail the id, and a forged sig the signature")
+    void testVerify() {
+        String event = alice.event(1, T, "hello \"world\"\ncaf\u00e9", new String[] {"t", "nostr"});
+        assertEquals(VerifyResult.OK, Ndb.verifyEvent(event));
+        assertTrue(Ndb.verifyEvent(event).isValid());
+        Ndb.verifyEventStrict(event);
+
+        // Changed content no longer hashes to the id
+        String tampered = event.replace("hello", "hellO");
+        assertEquals(VerifyResult.BAD_ID, Ndb.verifyEvent(tampered));
+        IllegalArgumentException badId = assertThrows(IllegalArgumentException.class,
+            () -> Ndb.verifyEventStrict(tampered));
+        assertTrue(badId.getMessage().contains("does not match"), badId.getMessage());
+
+        // The id is right, but the signature belongs to another event
+        String forged = event.replace(sigOf(event), sigOf(alice.textNote(T, "other")));
+        assertEquals(VerifyResult.BAD_SIG, Ndb.verifyEvent(forged));
+        assertEquals(VerifyResult.BAD_SIG, Ndb.verifyEvent(event.replace(sigOf(event), "0".repeat(128))));
+        IllegalArgumentException badSig = assertThrows(IllegalArgumentException.class,
+            () -> Ndb.verifyEventStrict(forged));
+        assertTrue(badSig.getMessage().contains("Signature"), badSig.getMessage());
+    }
+
+    @Test
+    @DisplayName("Events that aren't well-formed NIP-01 events should be reported as malformed")
+    void testMalformed() {
+        String event = alice.textNote(T, "hello");
+        assertEquals(VerifyResult.MALFORMED, Ndb.verifyEvent("not json"));
+        assertEquals(VerifyResult.MALFORMED, Ndb.verifyEvent("[]"));
+        assertEquals(VerifyResult.MALFORMED, Ndb.verifyEvent("{}"));
+        assertEquals(VerifyResult.MALFORMED, Ndb.verifyEvent(event.replace("\"kind\":1", "\"kind\":\"1\"")));
+        assertEquals(VerifyResult.MALFORMED, Ndb.verifyEvent(event.replace("\"tags\":[]", "\"tags\":[[1]]")));
+        assertEquals(VerifyResult.MALFORMED, Ndb.verifyEvent(event.replace(sigOf(event), "abcd")));
+        assertThrows(IllegalArgumentException.class, () -> Ndb.verifyEventStrict("{}"));
+        assertThrows(IllegalArgumentException.class, () -> Ndb.verifyEvent(null));
+    }
+
+    @Test
+    @DisplayName("Stored notes should be verified again, catching events ingested without validation")
+    void testStored() throws Exception {
+        String valid = alice.textNote(T, "valid");
+        String signed = alice.textNote(T + 1, "unsigned");
+        String unsigned = signed.replace(sigOf(signed), "0".repeat(128));
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ndb.processEvent(valid);
+            ndb.processEvent(unsigned);
+            assertEquals(2, TestEvents.awaitCount(ndb, notes, 2, 10_000));
+
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(VerifyResult.OK, ndb.verifyNoteById(txn, TestEvents.idOf(valid)).orElseThrow());
+                assertEquals(VerifyResult.BAD_SIG, ndb.verifyNoteById(txn, TestEvents.idOf(unsigned)).orElseThrow());
+                assertTrue(ndb.verifyNoteById(txn, new byte[32]).isEmpty());
+                assertThrows(IllegalArgumentException.class, () -> ndb.verifyNoteById(txn, new byte[31]));
+            }
+        }
+    }
+}
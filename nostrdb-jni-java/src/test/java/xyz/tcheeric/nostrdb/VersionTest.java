@@ -0,0 +1,30 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for native library version information.
+ */
+class VersionTest {
+
+    @Test
+    @DisplayName("Should describe the loaded native library")
+    void testNativeVersion() {
+        String version = Ndb.nativeVersion();
+        assertNotNull(version);
+        assertFalse(version.isBlank());
+        assertTrue(version.startsWith("nostrdb-jni "), version);
+        assertTrue(version.contains("nostrdb "), version);
+        assertTrue(version.contains("git "), version);
+    }
+
+    @Test
+    @DisplayName("Native ABI version should match the wrapper")
+    void testAbiVersion() {
+        assertTrue(NostrdbNative.isLoaded());
+        assertEquals(7, NostrdbNative.EXPECTED_ABI_VERSION);
+        assertEquals(NostrdbNative.EXPECTED_ABI_VERSION, NostrdbNative.nativeAbiVersion());
+    }
+}
@@ -0,0 +1,87 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.nio.file.Path;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for waiting until written events are stored.
+ */
+class WaitForIngestionTest {
+
+    private static final long T = 1_700_000_000L;
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents alice = TestEvents.forSeed(11);
+
+    /**
+     * An unsigned note with a unique id; signing hundreds of notes would make
+     * the test needlessly slow, so these are stored with validation skipped.
+     */
+    private String unsignedNote(int i) {
+        return "{\"id\":\"" + String.format("%064x", i + 1) + "\",\"pubkey\":\"" + alice.pubkeyHex()
+            + "\",\"created_at\":" + (T + i) + ",\"kind\":1,\"tags\":[],"
+            + "\"content\":\"note " + i + "\",\"sig\":\"" + "0".repeat(128) + "\"}";
+    }
+
+    @Test
+    @DisplayName("A note should be readable right after waiting for it")
+    void testReadAfterWrite() throws Exception {
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config)) {
+            for (int i = 0; i < 100; i++) {
+                String note = unsignedNote(i);
+                byte[] id = HexUtil.decode(TestEvents.idOf(note));
+                ndb.processEvent(note);
+                assertTrue(ndb.waitForIngestion(id, 10_000));
+                try (Transaction txn = ndb.beginTransaction()) {
+                    assertTrue(ndb.getNoteById(txn, id).isPresent(), "note " + i);
+                }
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A batch should be fully readable after waiting for its count")
+    void testCount() throws Exception {
+        StringBuilder ldjson = new StringBuilder();
+        for (int i = 0; i < 500; i++) {
+            ldjson.append(unsignedNote(i)).append('\n');
+        }
+        try (NdbConfig config = new NdbConfig().skipValidation(true);
+             Ndb ndb = Ndb.open(tempDir.resolve("db"), config);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            assertTrue(ndb.waitForIngestionCount(0, 0));
+            assertEquals(500, ndb.processEvents(ldjson.toString()));
+            assertTrue(ndb.waitForIngestionCount(500, 30_000));
+            try (Transaction txn = ndb.beginTransaction()) {
+                assertEquals(500, ndb.queryCount(txn, notes));
+            }
+
+            assertFalse(ndb.waitForIngestionCount(501, 50));
+        }
+    }
+
+    @Test
+    @DisplayName("Waiting for an event that is never stored should time out with false")
+    void testTimeout() throws Exception {
+        String tampered = alice.textNote(T, "original").replace("original", "tampered");
+        try (Ndb ndb = Ndb.open(tempDir.resolve("db"))) {
+            // A batch is queued unchecked; nostrdb's writer drops the event
+            assertEquals(1, ndb.processEvents(tampered));
+            long start = System.nanoTime();
+            assertFalse(ndb.waitForIngestion(HexUtil.decode(TestEvents.idOf(tampered)), 100));
+            assertTrue(System.nanoTime() - start >= 100_000_000L);
+            assertFalse(ndb.waitForIngestion(new byte[32], 0));
+
+            assertThrows(IllegalArgumentException.class, () -> ndb.waitForIngestion(new byte[31], 10));
+            assertThrows(IllegalArgumentException.class, () -> ndb.waitForIngestion(new byte[32], -1));
+            assertThrows(IllegalArgumentException.class, () -> ndb.waitForIngestionCount(1, -1));
+        }
+    }
+}
@@ -0,0 +1,137 @@
+package xyz.tcheeric.nostrdb;
+
+import org.junit.jupiter.api.*;
+import org.junit.jupiter.api.io.TempDir;
+
+import java.io.IOException;
+import java.nio.file.Path;
+import java.util.List;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+/**
+ * Tests for write-protected database handles.
+ */
+class WriteProtectedTest {
+
+    @TempDir
+    Path tempDir;
+
+    private final TestEvents author = TestEvents.forSeed(5);
+
+    @Test
+    @DisplayName("Writes through a write-protected handle should throw IllegalStateException")
+    void testWritesRejected() {
+        Path dbPath = tempDir.resolve("db");
+        try (Ndb writer = Ndb.open(dbPath)) {
+            assertFalse(writer.isWriteProtected());
+        }
+
+        try (Ndb reader = Ndb.openWriteProtected(dbPath)) {
+            assertTrue(reader.isWriteProtected());
+            String note = author.textNote(1_700_000_000L, "rejected");
+
+            assertThrows(IllegalStateException.class, () -> reader.processEvent(note));
+            assertThrows(IllegalStateException.class, () -> reader.processEvents(note + "\n"));
+        }
+    }
+
+    @Test
+    @DisplayName("A write-protected handle should see data written by a writer handle")
+    void testReadsSeeWriterData() throws InterruptedException {
+        Path dbPath = tempDir.resolve("db");
+        String note = author.textNote(1_700_000_000L, "hello from the writer");
+        String profile = author.event(0, 1_700_000_000L, "{\"name\":\"alice\"}");
+
+        try (Ndb writer = Ndb.open(dbPath);
+             Ndb reader = Ndb.openWriteProtected(dbPath);
+             Filter notes = Filter.builder().kinds(0, 1).build()) {
+            writer.processEvent(note);
+            writer.processEvent(profile);
+
+            assertEquals(2, TestEvents.awaitCount(reader, notes, 2, 10_000));
+            try (Transaction txn = reader.beginTransaction()) {
+                assertEquals("hello from the writer",
+                    reader.getNoteById(txn, TestEvents.idOf(note)).orElseThrow().content());
+                assertEquals("alice",
+                    reader.getProfileByPubkey(txn, author.pubkeyHex()).orElseThrow().name());
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A write-protected handle should see events written by another process")
+    void testWriterInAnotherProcess() throws Exception {
+        Path dbPath = tempDir.resolve("db");
+        try (Ndb created = Ndb.open(dbPath)) {
+            assertFalse(created.isWriteProtected());
+        }
+        List<String> events = List.of(
+            author.textNote(1_700_000_000L, "first from another process"),
+            author.textNote(1_700_000_001L, "second from another process"));
+
+        // The guard is per handle: LMDB lets the other process write meanwhile
+        try (Ndb reader = Ndb.openWriteProtected(dbPath);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            ExternalWriter.run(dbPath, events);
+
+            assertEquals(2, TestEvents.awaitCount(reader, notes, 2, 10_000));
+            try (Transaction txn = reader.beginTransaction()) {
+                assertEquals("second from another process",
+                    reader.getNoteById(txn, TestEvents.idOf(events.get(1))).orElseThrow().content());
+            }
+            assertThrows(IllegalStateException.class, () -> reader.processEvent(events.get(0)));
+        }
+    }
+
+    @Test
+    @DisplayName("A write-protected handle should read a database after the writer has closed")
+    void testReadAfterWriterClosed() throws InterruptedException {
+        Path dbPath = tempDir.resolve("db");
+        String note = author.textNote(1_700_000_000L, "persisted");
+
+        try (Ndb writer = Ndb.open(dbPath);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            writer.processEvent(note);
+            assertEquals(1, TestEvents.awaitCount(writer, notes, 1, 10_000));
+        }
+
+        try (Ndb reader = Ndb.openWriteProtected(dbPath);
+             Transaction txn = reader.beginTransaction()) {
+            assertTrue(reader.getNoteById(txn, TestEvents.idOf(note)).isPresent());
+        }
+    }
+
+    @Test
+    @DisplayName("Subscriptions should be removable while a write-protected handle shares the database")
+    void testUnsubscribeWhileShared() {
+        Path dbPath = tempDir.resolve("db");
+        try (Ndb writer = Ndb.open(dbPath);
+             Filter notes = Filter.builder().kinds(1).build()) {
+            writer.unsubscribe(writer.subscribe(notes));
+            try (Ndb reader = Ndb.openWriteProtected(dbPath)) {
+                writer.unsubscribe(writer.subscribe(notes));
+                reader.unsubscribe(reader.subscribe(notes));
+            }
+        }
+    }
+
+    @Test
+    @DisplayName("A second read-write open of a database open in this process should fail")
+    void testSecondWriterRejected() {
+        Path dbPath = tempDir.resolve("db");
+        try (Ndb writer = Ndb.open(dbPath)) {
+            assertThrows(IOException.class, () -> Ndb.open(dbPath));
+            assertFalse(writer.isWriteProtected());
+        }
+        try (Ndb reopened = Ndb.open(dbPath)) {
+            assertFalse(reopened.isWriteProtected());
+        }
+    }
+
+    @Test
+    @DisplayName("Opening a missing directory write-protected should fail")
+    void testMissingDirectory() {
+        assertThrows(Exception.class, () -> Ndb.openWriteProtected(tempDir.resolve("missing")));
+    }
+}
@@ -0,0 +1,56 @@
+//! Build script for nostrdb-jni
+//!
+//! Bakes version information into the library so `nativeVersion()` can
+//! report exactly what was loaded: the git commit of this repository and
+//! the nostrdb crate version resolved in Cargo.lock.
+
+use std::fs;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    // HEAD only changes on checkout; a new commit moves the branch ref
+    if let Some(branch) = fs::read_to_string("../.git/HEAD")
+        .ok()
+        .and_then(|head| head.trim().strip_prefix("ref: ").map(str::to_string))
+    {
+        println!("cargo:rerun-if-changed=../.git/{}", branch);
+    }
+    println!("cargo:rerun-if-env-changed=NOSTRDB_JNI_GIT_HASH");
+
+    let git_hash = std::env::var("NOSTRDB_JNI_GIT_HASH")
+        .ok()
+        .or_else(git_hash)
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=NOSTRDB_JNI_GIT_HASH={}", git_hash);
+
+    let nostrdb_version = locked_version("nostrdb").unwrap_or_else(|| "unknown".to_string());
+    println!(
+        "cargo:rustc-env=NOSTRDB_JNI_NOSTRDB_VERSION={}",
+        nostrdb_version
+    );
+}
+
+/// Short hash of the current commit, if built from a git checkout
+fn git_hash() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let hash = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!hash.is_empty()).then_some(hash)
+}
+
+/// Version of a dependency as resolved in Cargo.lock
+fn locked_version(name: &str) -> Option<String> {
+    let lock = fs::read_to_string("Cargo.lock").ok()?;
+    let mut lines = lock.lines();
+    let header = format!("name = \"{}\"", name);
+    lines.find(|line| line.trim() == header)?;
+    let version = lines.next()?.trim().strip_prefix("version = \"")?;
+    Some(version.trim_end_matches('"').to_string())
+}
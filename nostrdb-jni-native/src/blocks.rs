@@ -0,0 +1,147 @@
+//! Content blocks for nostrdb-jni
+//!
+//! Splits a note's content into the blocks nostrdb parsed when it ingested
+//! the note (text, `nostr:` mentions, hashtags, URLs, and lightning
+//! invoices), so Java can render a note without parsing its content again.
+//!
+//! nostrdb's block strings leave out markers such as `nostr:` and `#`.
+//! Each span reported here starts where the previous one ended, so it
+//! takes in its marker and the spans cover the content end to end.
+
+use std::borrow::Cow;
+
+use nostrdb::{Block, BlockType, Mention, Ndb, Note, NoteKey, Transaction};
+
+use crate::error::{Error, Result};
+use crate::json::{content_bytes, note_content, Utf8Mode};
+
+/// Plain text
+pub const BLOCK_TEXT: u8 = 0;
+/// A `nostr:` mention, or a legacy `#[n]` mention
+pub const BLOCK_MENTION: u8 = 1;
+/// A `#hashtag`
+pub const BLOCK_HASHTAG: u8 = 2;
+/// A URL
+pub const BLOCK_URL: u8 = 3;
+/// A lightning invoice
+pub const BLOCK_INVOICE: u8 = 4;
+
+/// The block mentions nothing, or nothing that decodes to an id
+pub const TARGET_NONE: u8 = 0;
+/// The block mentions a pubkey (npub or nprofile)
+pub const TARGET_PUBKEY: u8 = 1;
+/// The block mentions an event (note or nevent)
+pub const TARGET_EVENT: u8 = 2;
+
+/// One block of a note's content
+pub struct Span {
+    kind: u8,
+    /// Byte offset into the content
+    offset: u32,
+    /// Length in bytes
+    len: u32,
+    /// Target kind and the 32-byte pubkey or event id it decodes to
+    target: Option<(u8, [u8; 32])>,
+}
+
+impl Span {
+    /// Serialize as [type:1][offset:4][length:4][target:1], followed by
+    /// [id:32] unless target is TARGET_NONE
+    pub fn write(&self, buf: &mut Vec<u8>) {
+        buf.push(self.kind);
+        buf.extend_from_slice(&self.offset.to_le_bytes());
+        buf.extend_from_slice(&self.len.to_le_bytes());
+        match &self.target {
+            Some((target, id)) => {
+                buf.push(*target);
+                buf.extend_from_slice(id);
+            }
+            None => buf.push(TARGET_NONE),
+        }
+    }
+}
+
+/// The blocks of `note`, covering its content contiguously
+///
+/// Content nostrdb didn't parse into blocks (it only does so for some
+/// kinds) comes back as a single text block. Offsets are into the content
+/// as [`note_content`] returns it under `utf8`, so they stay in step with
+/// the replacement characters of repaired content.
+pub fn note_blocks(
+    ndb: &Ndb,
+    txn: &Transaction,
+    note: &Note,
+    key: NoteKey,
+    utf8: Utf8Mode,
+) -> Result<Vec<Span>> {
+    let raw = content_bytes(note);
+    let base = raw.as_ptr() as usize;
+    let repaired = note_content(note, utf8)?;
+    // Where a byte offset into the stored content lands in the repaired
+    // content; repairing replaces each invalid sequence with U+FFFD
+    let at = |pos: usize| match &repaired {
+        Cow::Borrowed(_) => pos,
+        Cow::Owned(_) => String::from_utf8_lossy(&raw[..pos]).len(),
+    };
+    let mut spans = Vec::new();
+    let mut end = 0;
+
+    let blocks = match ndb.get_blocks_by_key(txn, key) {
+        Ok(blocks) => Some(blocks),
+        Err(nostrdb::Error::NotFound) => None,
+        Err(e) => return Err(e.into()),
+    };
+    for block in blocks.iter().flat_map(|blocks| blocks.iter(note)) {
+        // Block strings point into the note's content
+        let text = block.as_str();
+        let start = (text.as_ptr() as usize).wrapping_sub(base);
+        let block_end = start.wrapping_add(text.len());
+        if start < end || block_end > raw.len() {
+            return Err(Error::InvalidState(
+                "Content block lies outside the note's content".to_string(),
+            ));
+        }
+        spans.push(Span {
+            kind: kind(&block),
+            offset: at(end) as u32,
+            len: (at(block_end) - at(end)) as u32,
+            target: target(&block),
+        });
+        end = block_end;
+    }
+
+    if end < raw.len() {
+        spans.push(Span {
+            kind: BLOCK_TEXT,
+            offset: at(end) as u32,
+            len: (repaired.len() - at(end)) as u32,
+            target: None,
+        });
+    }
+    Ok(spans)
+}
+
+fn kind(block: &Block) -> u8 {
+    match block.blocktype() {
+        BlockType::Text => BLOCK_TEXT,
+        BlockType::MentionIndex | BlockType::MentionBech32 => BLOCK_MENTION,
+        BlockType::Hashtag => BLOCK_HASHTAG,
+        BlockType::Url => BLOCK_URL,
+        BlockType::Invoice => BLOCK_INVOICE,
+    }
+}
+
+/// The pubkey or event id a mention decodes to; secret keys are never
+/// passed on
+fn target(block: &Block) -> Option<(u8, [u8; 32])> {
+    if block.blocktype() != BlockType::MentionBech32 {
+        return None;
+    }
+    match block.as_mention()? {
+        Mention::Pubkey(npub) => Some((TARGET_PUBKEY, *npub.pubkey())),
+        Mention::Profile(nprofile) => Some((TARGET_PUBKEY, *nprofile.pubkey())),
+        Mention::Note(note) => Some((TARGET_EVENT, *note.id())),
+        Mention::Event(nevent) => Some((TARGET_EVENT, *nevent.id())),
+        Mention::Relay(_) | Mention::Addr(_) | Mention::Secret(_) => None,
+    }
+}
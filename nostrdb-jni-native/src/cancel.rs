@@ -0,0 +1,115 @@
+//! Cooperative cancellation for nostrdb-jni
+//!
+//! A [`CancelToken`] is a flag Java can set from any thread while a
+//! cancellable query runs on another. nostrdb can't interrupt a query, so
+//! cancellable queries fetch their results in pages with [`query_page`]
+//! and check the flag between pages and between serialized notes,
+//! returning what they have so far once it is set.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use jni::sys::jint;
+use nostrdb::{Ndb, NoteKey, Transaction};
+
+use crate::error::Result;
+use crate::filter::FilterHandle;
+use crate::json::{write_note, Utf8Mode};
+use crate::page::query_page;
+use crate::util::{Handle, HandleKind};
+
+/// Results fetched between two checks of the flag
+const PAGE_SIZE: usize = 256;
+
+/// A flag that stops cancellable queries
+#[derive(Default)]
+pub struct CancelToken {
+    cancelled: AtomicBool,
+}
+
+impl Handle for CancelToken {
+    const KIND: HandleKind = HandleKind::CancelToken;
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop every query using this token; there is no way to reset it
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Visit the keys of up to `limit` matches (every match if None), newest
+/// first, until done or cancelled
+///
+/// Notes sharing a `created_at` are visited in key order, which may differ
+/// from the order of an uncancellable query. Returns true if the token was
+/// cancelled before every match was visited.
+pub fn for_each_key(
+    ndb: &Ndb,
+    txn: &Transaction,
+    filter: &FilterHandle,
+    limit: Option<usize>,
+    token: &CancelToken,
+    mut visit: impl FnMut(u64) -> Result<()>,
+) -> Result<bool> {
+    let mut visited = 0;
+    let mut after = None;
+    loop {
+        let remaining = limit.map_or(PAGE_SIZE, |limit| limit - visited);
+        if remaining == 0 {
+            return Ok(false);
+        }
+        if token.is_cancelled() {
+            return Ok(true);
+        }
+        let page = query_page(ndb, txn, filter, remaining.min(PAGE_SIZE) as jint, after)?;
+        for key in page.keys {
+            if token.is_cancelled() {
+                return Ok(true);
+            }
+            visit(key)?;
+            visited += 1;
+        }
+        match page.next {
+            Some(next) => after = Some(next),
+            None => return Ok(false),
+        }
+    }
+}
+
+/// Write the notes of up to `limit` matches as
+/// [count:4]([len:4][noteJson:len])*, until done or cancelled
+///
+/// Returns true if cancelled, as for [`for_each_key`].
+pub fn write_notes(
+    ndb: &Ndb,
+    txn: &Transaction,
+    filter: &FilterHandle,
+    limit: Option<usize>,
+    token: &CancelToken,
+    utf8: Utf8Mode,
+    buf: &mut Vec<u8>,
+) -> Result<bool> {
+    let header = buf.len();
+    buf.extend_from_slice(&[0; 4]);
+    let mut count = 0u32;
+    let cancelled = for_each_key(ndb, txn, filter, limit, token, |key| {
+        let note = ndb.get_note_by_key(txn, NoteKey::new(key))?;
+        let start = buf.len();
+        buf.extend_from_slice(&[0; 4]);
+        write_note(&note, utf8, buf)?;
+        let len = (buf.len() - start - 4) as u32;
+        buf[start..start + 4].copy_from_slice(&len.to_le_bytes());
+        count += 1;
+        Ok(())
+    })?;
+    buf[header..header + 4].copy_from_slice(&count.to_le_bytes());
+    Ok(cancelled)
+}
@@ -0,0 +1,142 @@
+//! Ingestion completion callbacks for nostrdb-jni
+//!
+//! `processEventAsync` tells Java when an event has been written instead
+//! of making it poll. The event is checked and a one-shot subscription on
+//! its id is set up before it is queued; a native thread then polls the
+//! subscription until the note lands or the timeout runs out, and calls
+//! the Java `IngestCallback` exactly once. Outcomes known before queuing
+//! (invalid, duplicate, rejected) are reported from such a thread too, so
+//! callers see one threading model.
+//!
+//! The thread holds the database weakly: if it is closed first, the
+//! callback reports [`STATUS_CLOSED`] instead of keeping it open.
+
+use jni::objects::{GlobalRef, JObject, JValue};
+use jni::JavaVM;
+use nostrdb::{Subscription, Transaction};
+use std::thread;
+use std::time::Instant;
+
+use crate::error::{Error, Result};
+use crate::handle::DbSource;
+use crate::ingest::POLL_INTERVAL;
+
+/// The note was stored
+pub const STATUS_STORED: i32 = 0;
+/// The event is malformed, or its id or signature doesn't verify
+pub const STATUS_INVALID: i32 = 1;
+/// A note with the event's id was already stored
+pub const STATUS_DUPLICATE: i32 = 2;
+/// The note wasn't stored before the timeout
+pub const STATUS_TIMEOUT: i32 = 3;
+/// nostrdb refused to queue the event
+pub const STATUS_REJECTED: i32 = 4;
+/// The database was closed before the note was stored
+pub const STATUS_CLOSED: i32 = 5;
+
+/// What a completion thread reports
+pub enum Outcome {
+    /// Known already: the event's id if it could be read, a status, and
+    /// the note key (0 if there is none)
+    Done(Option<[u8; 32]>, i32, u64),
+    /// Wait for the subscription on the event's id until the deadline
+    Waiting([u8; 32], Subscription, Instant),
+}
+
+/// Report `outcome` to `callback` from a new thread
+///
+/// # Arguments
+/// * `vm` - The JavaVM used to attach the thread
+/// * `callback` - Global reference to an `IngestCallback` object
+/// * `source` - The database the event was queued to
+/// * `outcome` - The outcome, or the subscription to wait on
+pub fn spawn(vm: JavaVM, callback: GlobalRef, source: DbSource, outcome: Outcome) -> Result<()> {
+    thread::Builder::new()
+        .name("nostrdb-ingest".to_string())
+        .spawn(move || {
+            let (id, status, key) = match outcome {
+                Outcome::Done(id, status, key) => (id, status, key),
+                Outcome::Waiting(id, sub, deadline) => {
+                    let (status, key) = await_note(&source, &id, sub, deadline);
+                    (Some(id), status, key)
+                }
+            };
+            // Release the database before calling into Java
+            drop(source);
+
+            let mut env = match vm.attach_current_thread_as_daemon() {
+                Ok(env) => env,
+                Err(e) => {
+                    tracing::error!("Ingest callback thread failed to attach to JVM: {}", e);
+                    return;
+                }
+            };
+            let id = match id {
+                Some(id) => match env.byte_array_from_slice(&id) {
+                    Ok(id) => JObject::from(id),
+                    Err(e) => {
+                        tracing::error!("Failed to pass event id to ingest callback: {}", e);
+                        return;
+                    }
+                },
+                None => JObject::null(),
+            };
+            let args = [
+                JValue::Object(&id),
+                JValue::Long(key as i64),
+                JValue::Int(status),
+            ];
+            if env
+                .call_method(&callback, "onComplete", "([BJI)V", &args)
+                .is_err()
+            {
+                // Never let a callback exception escape into native code
+                if env.exception_check().unwrap_or(false) {
+                    let _ = env.exception_describe();
+                    let _ = env.exception_clear();
+                }
+                tracing::error!("Ingest callback threw for status {}", status);
+            }
+        })
+        .map_err(Error::Io)?;
+    Ok(())
+}
+
+/// Poll `sub` until the note lands, the deadline passes, or the database
+/// closes
+fn await_note(
+    source: &DbSource,
+    id: &[u8; 32],
+    sub: Subscription,
+    deadline: Instant,
+) -> (i32, u64) {
+    loop {
+        let Some(polled) = source.poll(sub, 1) else {
+            return (STATUS_CLOSED, 0);
+        };
+        if let Ok(Some(key)) = polled.as_deref().map(|keys| keys.first()) {
+            unsubscribe(source, sub);
+            return (STATUS_STORED, key.as_u64());
+        }
+        if Instant::now() >= deadline {
+            // A subscription lost in a resize no longer reports the note,
+            // so look the id up before giving up
+            let key = source.ndb().and_then(|ndb| {
+                let txn = Transaction::new(&ndb).ok()?;
+                ndb.get_notekey_by_id(&txn, id).ok()
+            });
+            unsubscribe(source, sub);
+            return match key {
+                Some(key) => (STATUS_STORED, key.as_u64()),
+                None => (STATUS_TIMEOUT, 0),
+            };
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn unsubscribe(source: &DbSource, sub: Subscription) {
+    if let Err(e) = source.unsubscribe(sub) {
+        tracing::debug!("Failed to end ingest subscription: {}", e);
+    }
+}
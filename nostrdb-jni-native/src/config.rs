@@ -0,0 +1,146 @@
+//! Database configuration handle for nostrdb-jni
+//!
+//! The jlong handed to Java for a configuration points at an [`NdbConfig`].
+//! nostrdb's `Config` cannot be read back once set, so the handle also
+//! records the settings the binding itself needs after the database is
+//! open (such as the map size used for health monitoring), and reports
+//! them through `ndbGetSettings`.
+
+use nostrdb::Config;
+
+use crate::json::Utf8Mode;
+use crate::util::{Handle, HandleKind};
+
+/// nostrdb's default LMDB map size (32 GiB)
+pub const DEFAULT_MAPSIZE: usize = 1024 * 1024 * 1024 * 32;
+
+/// Don't run database migrations on open
+pub const FLAG_NO_MIGRATE: i32 = 1 << 0;
+/// Skip id and signature verification during ingestion
+pub const FLAG_SKIP_NOTE_VERIFY: i32 = 1 << 1;
+/// Don't build the fulltext search index
+pub const FLAG_NO_FULLTEXT: i32 = 1 << 2;
+/// Don't parse and store content blocks
+pub const FLAG_NO_NOTE_BLOCKS: i32 = 1 << 3;
+/// Don't collect database statistics
+pub const FLAG_NO_STATS: i32 = 1 << 4;
+
+/// All flags understood by nostrdb
+pub const KNOWN_FLAGS: i32 = FLAG_NO_MIGRATE
+    | FLAG_SKIP_NOTE_VERIFY
+    | FLAG_NO_FULLTEXT
+    | FLAG_NO_NOTE_BLOCKS
+    | FLAG_NO_STATS;
+
+/// A nostrdb `Config` plus the settings recorded alongside it
+#[derive(Clone)]
+pub struct NdbConfig {
+    config: Config,
+    mapsize: usize,
+    flags: i32,
+    /// None for nostrdb's default of one thread per CPU core
+    ingester_threads: Option<i32>,
+    auto_resize_max: usize,
+    utf8: Utf8Mode,
+}
+
+impl NdbConfig {
+    /// Create a configuration with nostrdb's defaults
+    pub fn new() -> Self {
+        Self {
+            config: Config::new(),
+            mapsize: DEFAULT_MAPSIZE,
+            flags: 0,
+            ingester_threads: None,
+            auto_resize_max: 0,
+            utf8: Utf8Mode::Lossy,
+        }
+    }
+
+    /// The nostrdb configuration to open with
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The LMDB map size in bytes
+    pub fn mapsize(&self) -> usize {
+        self.mapsize
+    }
+
+    /// The nostrdb flags bitmask
+    pub fn flags(&self) -> i32 {
+        self.flags
+    }
+
+    /// Set the LMDB map size in bytes
+    pub fn set_mapsize(&mut self, bytes: usize) {
+        self.update(|config| config.set_mapsize(bytes));
+        self.mapsize = bytes;
+    }
+
+    /// Upper bound for automatic map growth (0 when auto-resize is off)
+    pub fn auto_resize_max(&self) -> usize {
+        self.auto_resize_max
+    }
+
+    /// Enable automatic map growth up to `max_bytes` (0 disables it)
+    pub fn set_auto_resize_max(&mut self, max_bytes: usize) {
+        self.auto_resize_max = max_bytes;
+    }
+
+    /// How notes with content that isn't valid UTF-8 are serialized
+    pub fn utf8_mode(&self) -> Utf8Mode {
+        self.utf8
+    }
+
+    /// Fail on content that isn't valid UTF-8 instead of repairing it
+    pub fn set_strict_utf8(&mut self, strict: bool) {
+        self.utf8 = if strict {
+            Utf8Mode::Strict
+        } else {
+            Utf8Mode::Lossy
+        };
+    }
+
+    /// The number of ingester threads, or None for nostrdb's default
+    pub fn ingester_threads(&self) -> Option<i32> {
+        self.ingester_threads
+    }
+
+    /// Set the number of ingester threads
+    pub fn set_ingester_threads(&mut self, threads: i32) {
+        self.update(|config| config.set_ingester_threads(threads));
+        self.ingester_threads = Some(threads);
+    }
+
+    /// Skip id and signature verification during ingestion
+    pub fn set_skip_validation(&mut self, skip: bool) {
+        self.update(|config| config.skip_validation(skip));
+        if skip {
+            self.flags |= FLAG_SKIP_NOTE_VERIFY;
+        } else {
+            self.flags &= !FLAG_SKIP_NOTE_VERIFY;
+        }
+    }
+
+    /// Replace all nostrdb flags (see the `FLAG_*` constants)
+    pub fn set_flags(&mut self, flags: i32) {
+        self.update(|config| config.set_flags(flags));
+        self.flags = flags;
+    }
+
+    /// Apply one of nostrdb's consuming `Config` setters in place
+    fn update(&mut self, f: impl FnOnce(Config) -> Config) {
+        self.config = f(std::mem::take(&mut self.config));
+    }
+}
+
+impl Default for NdbConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Handle for NdbConfig {
+    const KIND: HandleKind = HandleKind::Config;
+}
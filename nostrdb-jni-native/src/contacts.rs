@@ -0,0 +1,55 @@
+//! Contact lists for nostrdb-jni
+//!
+//! `getContacts` reads an author's newest contact list (kind 3) and
+//! extracts the pubkeys it follows from its `p` tags, so every caller
+//! gets the same follow list: each pubkey once, in the order first
+//! listed, with the relay hint of that first tag. Petnames are ignored,
+//! and tags whose pubkey isn't 64 hex characters are skipped.
+
+use nostrdb::{Filter, Note};
+use std::collections::HashSet;
+
+use crate::mute::tag_id;
+
+/// Kind of a contact list
+pub const CONTACT_LIST_KIND: u64 = 3;
+
+/// Filter for an author's newest contact list
+pub fn contact_list_filter(pubkey: &[u8; 32]) -> Filter {
+    Filter::new()
+        .authors([pubkey])
+        .kinds([CONTACT_LIST_KIND])
+        .limit(1)
+        .build()
+}
+
+/// The pubkeys a contact list follows
+pub struct Contacts<'a> {
+    pub pubkeys: Vec<[u8; 32]>,
+    /// Relay hint for each pubkey, empty where the tag has none
+    pub relays: Vec<&'a str>,
+}
+
+impl<'a> Contacts<'a> {
+    /// Read the `p` tags of a contact list
+    pub fn parse(note: &Note<'a>) -> Self {
+        let mut seen = HashSet::new();
+        let mut contacts = Contacts {
+            pubkeys: Vec::new(),
+            relays: Vec::new(),
+        };
+        for tag in note.tags().iter() {
+            if tag.get_str(0) != Some("p") {
+                continue;
+            }
+            let Some(pubkey) = tag_id(&tag, 1) else {
+                continue;
+            };
+            if seen.insert(pubkey) {
+                contacts.pubkeys.push(pubkey);
+                contacts.relays.push(tag.get_str(2).unwrap_or(""));
+            }
+        }
+        contacts
+    }
+}
@@ -0,0 +1,98 @@
+//! Streaming query cursors for nostrdb-jni
+//!
+//! A [`QueryCursor`] pages through the results of one filter inside one
+//! read transaction. nostrdb has no native cursor, so matches are fetched
+//! in windows that double in size and keys already handed out are skipped;
+//! within one transaction every window repeats the previous one in the
+//! same order, so each key is returned exactly once.
+//!
+//! A cursor borrows its transaction rather than owning it, and stops
+//! working (with IllegalStateException) once the transaction has ended.
+//! Each call keeps the transaction open until it returns, so ending it on
+//! another thread waits for the call.
+
+use jni::sys::{jint, jlong};
+use std::collections::VecDeque;
+
+use crate::error::Result;
+use crate::filter::FilterHandle;
+use crate::handle::{TxnHandle, TxnLiveness};
+use crate::util::{self, Handle, HandleKind};
+
+/// Size of the first window fetched from nostrdb
+const FIRST_WINDOW: jint = 1024;
+
+/// Position in the results of a query
+pub struct QueryCursor {
+    txn_ptr: jlong,
+    txn_alive: TxnLiveness,
+    filter: FilterHandle,
+    /// Results still to be returned under the filter's own limit, if it has one
+    remaining: Option<usize>,
+    /// Keys fetched but not yet returned
+    pending: VecDeque<u64>,
+    /// Matches fetched so far, accepted or not
+    fetched: usize,
+    window: jint,
+    exhausted: bool,
+}
+
+impl Handle for QueryCursor {
+    const KIND: HandleKind = HandleKind::QueryCursor;
+}
+
+impl QueryCursor {
+    /// Start a cursor over the matches of `filter` in the transaction behind `txn_ptr`
+    pub fn new(txn_ptr: jlong, txn: &TxnHandle, filter: &FilterHandle) -> Self {
+        Self {
+            txn_ptr,
+            txn_alive: txn.liveness(),
+            filter: filter.clone(),
+            remaining: filter.limit().map(|limit| limit as usize),
+            pending: VecDeque::new(),
+            fetched: 0,
+            window: FIRST_WINDOW,
+            exhausted: false,
+        }
+    }
+
+    /// The next `batch_size` keys at most; empty once every match was returned
+    pub fn next(&mut self, batch_size: usize) -> Result<Vec<u64>> {
+        let alive = self.txn_alive.clone();
+        let _open = alive.guard("cursor")?;
+        // The transaction is open until `_open` is dropped, so its handle
+        // has not been freed
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(self.txn_ptr, "transaction")? };
+
+        let batch_size = self.remaining.map_or(batch_size, |r| r.min(batch_size));
+        while self.pending.len() < batch_size && !self.exhausted {
+            self.fetch(txn)?;
+        }
+        let batch: Vec<u64> = self
+            .pending
+            .drain(..batch_size.min(self.pending.len()))
+            .collect();
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= batch.len();
+        }
+        Ok(batch)
+    }
+
+    /// Fetch the next window of matches
+    fn fetch(&mut self, txn: &TxnHandle) -> Result<()> {
+        let filters = std::slice::from_ref(self.filter.filter());
+        let results = txn.ndb().query(txn, filters, self.window)?;
+        let fetched = results.len();
+        self.pending.extend(
+            results
+                .into_iter()
+                .skip(self.fetched)
+                .filter(|result| self.filter.accepts_kind(result.note.kind() as u64))
+                .map(|result| result.note_key.as_u64()),
+        );
+        self.exhausted = fetched < self.window as usize || self.window == jint::MAX;
+        self.fetched = fetched;
+        self.window = self.window.saturating_mul(2);
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+//! Building and signing notes for nostrdb-jni
+//!
+//! A [`NoteDraft`] collects a note's fields across `noteBuilder*` calls.
+//! nostrdb's own builder borrows everything it is given until it builds,
+//! so it can't live across JNI calls; the draft owns the fields instead
+//! and replays them into a nostrdb builder when the note is signed.
+//!
+//! Secret keys are only held for the duration of a signing call, and are
+//! overwritten with [`wipe`] before it returns.
+
+use std::sync::atomic::{compiler_fence, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use nostrdb::NoteBuilder;
+
+use crate::error::{Error, Result};
+use crate::util::{Handle, HandleKind};
+
+/// One tag element
+enum TagElem {
+    Str(String),
+    /// A 32-byte id (event id or pubkey), written to JSON as hex
+    Id([u8; 32]),
+}
+
+/// A note being built, mutated in place by the `noteBuilder*` natives
+pub struct NoteDraft {
+    kind: u32,
+    content: String,
+    /// None to stamp the note with the time it is signed
+    created_at: Option<u64>,
+    tags: Vec<Vec<TagElem>>,
+}
+
+/// A draft behind a stable pointer; `None` once it has been signed
+impl Handle for Option<NoteDraft> {
+    const KIND: HandleKind = HandleKind::NoteBuilder;
+}
+
+impl NoteDraft {
+    /// An empty kind 1 note
+    pub fn new() -> Self {
+        Self {
+            kind: 1,
+            content: String::new(),
+            created_at: None,
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn set_kind(&mut self, kind: u32) {
+        self.kind = kind;
+    }
+
+    pub fn set_content(&mut self, content: String) {
+        self.content = content;
+    }
+
+    pub fn set_created_at(&mut self, created_at: u64) {
+        self.created_at = Some(created_at);
+    }
+
+    /// Start a new, empty tag; elements are added to the latest tag
+    pub fn start_tag(&mut self) {
+        self.tags.push(Vec::new());
+    }
+
+    pub fn push_str(&mut self, value: String) -> Result<()> {
+        self.current_tag()?.push(TagElem::Str(value));
+        Ok(())
+    }
+
+    pub fn push_id(&mut self, id: [u8; 32]) -> Result<()> {
+        self.current_tag()?.push(TagElem::Id(id));
+        Ok(())
+    }
+
+    fn current_tag(&mut self) -> Result<&mut Vec<TagElem>> {
+        self.tags.last_mut().ok_or_else(|| {
+            Error::InvalidState("No tag started; call noteBuilderStartTag first".to_string())
+        })
+    }
+
+    /// Finalize the note, sign it with `seckey`, and return its JSON
+    ///
+    /// The id is computed and the pubkey derived from `seckey` by nostrdb.
+    /// Throws IllegalArgumentException if nostrdb rejects the key (zero or
+    /// not below the curve order) or the note doesn't fit its buffer.
+    pub fn sign(&self, seckey: &[u8; 32]) -> Result<String> {
+        let created_at = self.created_at.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs())
+        });
+        let mut builder = NoteBuilder::new()
+            .kind(self.kind)
+            .content(&self.content)
+            .created_at(created_at);
+        for tag in &self.tags {
+            builder = builder.start_tag();
+            for elem in tag {
+                builder = match elem {
+                    TagElem::Str(value) => builder.tag_str(value),
+                    TagElem::Id(id) => builder.tag_id(id),
+                };
+            }
+        }
+
+        let note = builder.sign(seckey).build().ok_or_else(|| {
+            Error::InvalidArgument(
+                "Failed to sign note: invalid secret key, or note too large".to_string(),
+            )
+        })?;
+        Ok(note.json()?)
+    }
+}
+
+/// Overwrite secret bytes so they don't linger in memory once freed
+pub fn wipe<T: Copy + Default>(bytes: &mut [T]) {
+    for byte in bytes.iter_mut() {
+        // SAFETY: `byte` is a valid, aligned, exclusive reference; the
+        // volatile write keeps the compiler from eliding a dead store
+        unsafe { std::ptr::write_volatile(byte, T::default()) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
@@ -19,6 +19,10 @@ pub enum Error {
     #[error("Nostrdb error: {0}")]
     Nostrdb(#[from] nostrdb::Error),
 
+    /// Opening the database failed, with the diagnosed cause
+    #[error("Failed to open database at {path}: {reason}")]
+    DbOpenFailed { path: String, reason: String },
+
     /// Invalid byte array length (expected 32 bytes for IDs/pubkeys)
     #[error("Invalid length: expected 32 bytes, got {0}")]
     InvalidIdLength(usize),
@@ -35,14 +39,46 @@ pub enum Error {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    /// A stored note's content is not valid UTF-8 (strict mode only)
+    #[error("Content of note {key} is not valid UTF-8")]
+    InvalidContent { key: u64 },
+
+    /// CBOR serialization failed
+    #[error("CBOR error: {0}")]
+    Cbor(#[from] ciborium::ser::Error<std::io::Error>),
+
     /// Filter building failed
     #[error("Filter error: {0}")]
     Filter(String),
 
+    /// A client message is not an `["EVENT", {...}]` envelope
+    #[error("Invalid client message: {0}")]
+    ClientMessage(String),
+
+    /// A relay message does not start with its type, as in `["EVENT", ...]`
+    #[error("Invalid relay message: {0}")]
+    RelayMessage(String),
+
     /// Invalid state
     #[error("Invalid state: {0}")]
     InvalidState(String),
 
+    /// Invalid argument passed from Java
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    /// Index outside the bounds of a native sequence
+    #[error("Index out of bounds: {0}")]
+    IndexOutOfBounds(String),
+
+    /// Filesystem operation failed
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A gzip-compressed import is truncated or corrupt
+    #[error("Corrupt gzip stream after {processed} events were ingested: {reason}")]
+    Gzip { processed: u64, reason: String },
+
     /// Panic occurred in native code
     #[error("Native panic: {0}")]
     Panic(String),
@@ -58,13 +94,31 @@ impl Error {
                 nostrdb::Error::DbOpenFailed => "java/io/IOException",
                 _ => "xyz/tcheeric/nostrdb/NostrdbException",
             },
+            Error::DbOpenFailed { .. } => "java/io/IOException",
             Error::InvalidIdLength(_) => "java/lang/IllegalArgumentException",
             Error::NullPointer(_) => "java/lang/NullPointerException",
             Error::InvalidUtf8(_) => "java/lang/IllegalArgumentException",
             Error::Json(_) => "xyz/tcheeric/nostrdb/NostrdbException",
+            Error::InvalidContent { .. } => "xyz/tcheeric/nostrdb/InvalidContentException",
+            Error::Cbor(_) => "xyz/tcheeric/nostrdb/NostrdbException",
             Error::Filter(_) => "xyz/tcheeric/nostrdb/NostrdbException",
+            Error::ClientMessage(_) => "xyz/tcheeric/nostrdb/NostrdbException",
+            Error::RelayMessage(_) => "xyz/tcheeric/nostrdb/NostrdbException",
             Error::InvalidState(_) => "java/lang/IllegalStateException",
+            Error::InvalidArgument(_) => "java/lang/IllegalArgumentException",
+            Error::IndexOutOfBounds(_) => "java/lang/IndexOutOfBoundsException",
+            Error::Io(_) => "java/io/IOException",
+            Error::Gzip { .. } => "xyz/tcheeric/nostrdb/NostrdbException",
             Error::Panic(_) => "java/lang/RuntimeException",
         }
     }
+
+    /// The key of the note the error is about, passed to the exception's
+    /// `(String, long)` constructor
+    pub fn note_key(&self) -> Option<u64> {
+        match self {
+            Error::InvalidContent { key } => Some(*key),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,27 @@
+//! NIP-40 expiration for nostrdb-jni
+//!
+//! `queryUnexpired` and `queryNotesUnexpired` drop expired notes while they
+//! collect query results, so the query limit counts only live notes. The
+//! caller supplies "now", which keeps results reproducible.
+
+use nostrdb::Note;
+
+/// The note's `expiration` timestamp, if it has a well-formed one
+///
+/// Only the first `expiration` tag counts. A value that isn't a
+/// non-negative integer of seconds is malformed, and the note is treated
+/// as never expiring.
+pub fn expiration(note: &Note) -> Option<u64> {
+    let tag = note
+        .tags()
+        .iter()
+        .find(|tag| tag.get_str(0) == Some("expiration"))?;
+    tag.get_str(1)?.parse().ok()
+}
+
+/// Whether a note has expired at `now` (Unix seconds)
+///
+/// A note expires at its expiration timestamp, not a second after it.
+pub fn is_expired(note: &Note, now: u64) -> bool {
+    expiration(note).is_some_and(|expiration| expiration <= now)
+}
@@ -0,0 +1,536 @@
+//! NIP-01 filter JSON support for nostrdb-jni
+//!
+//! Parses a REQ filter object into a built [`Filter`] with the same
+//! FilterBuilder calls the element-by-element natives use, so the result
+//! behaves exactly like a filter produced by `filterBuild`.
+//!
+//! It also evaluates filters against single notes. nostrdb's own matcher
+//! ignores search terms, so those are checked here as a case-insensitive
+//! substring of the content; notes that are not stored are matched
+//! against the filter's JSON form.
+//!
+//! The JSON form also backs the element accessors used to inspect a built
+//! filter, since its handle is otherwise opaque.
+//!
+//! Builders and filters are handed to Java as a [`BuilderHandle`] and a
+//! [`FilterHandle`], which carry a kind range next to the nostrdb object
+//! when it is too large to express as a kinds list.
+
+use nostrdb::{Filter, FilterBuilder, Note};
+use serde_json::{Map, Value};
+use std::ops::{Deref, RangeInclusive};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{Error, Result};
+use crate::json::{note_content, Utf8Mode};
+use crate::util::{Handle, HandleKind};
+
+/// Maximum number of elements in one nostrdb filter (`NDB_NUM_FILTERS`)
+pub const MAX_ELEMENTS: i32 = 7;
+
+/// Default for how far past the current time since/until may lie (10 years)
+pub const DEFAULT_TIMESTAMP_SLACK_SECS: u64 = 10 * 365 * 24 * 60 * 60;
+
+/// How far past the current time since/until may lie, in seconds
+static TIMESTAMP_SLACK_SECS: AtomicU64 = AtomicU64::new(DEFAULT_TIMESTAMP_SLACK_SECS);
+
+/// Kind ranges of at most this many kinds are expanded into a kinds list;
+/// larger ones are checked against query results instead
+pub const KIND_RANGE_EXPAND_MAX: u64 = 256;
+
+/// A filter builder, as handed to Java
+pub struct BuilderHandle {
+    builder: FilterBuilder,
+    /// Kind range too large to add to the builder
+    kind_range: Option<RangeInclusive<u64>>,
+}
+
+/// A built filter, as handed to Java
+///
+/// Dereferences to the nostrdb filter, which matches everything the
+/// handle matches except for the kind range.
+#[derive(Clone)]
+pub struct FilterHandle {
+    filter: Filter,
+    kind_range: Option<RangeInclusive<u64>>,
+}
+
+impl Handle for BuilderHandle {
+    const KIND: HandleKind = HandleKind::FilterBuilder;
+}
+
+/// A builder mutated in place by the `filterBuilder*` natives; `None`
+/// once it has been built
+impl Handle for Option<BuilderHandle> {
+    const KIND: HandleKind = HandleKind::StableFilterBuilder;
+}
+
+impl Handle for FilterHandle {
+    const KIND: HandleKind = HandleKind::Filter;
+}
+
+impl BuilderHandle {
+    pub fn new() -> Self {
+        Self {
+            builder: Filter::new(),
+            kind_range: None,
+        }
+    }
+
+    /// Apply a FilterBuilder call
+    pub fn map(self, f: impl FnOnce(FilterBuilder) -> FilterBuilder) -> Self {
+        Self {
+            builder: f(self.builder),
+            ..self
+        }
+    }
+
+    /// Match only kinds in `range`
+    ///
+    /// Ranges of up to [`KIND_RANGE_EXPAND_MAX`] kinds become a kinds
+    /// element; larger ones are kept on the handle and applied to results.
+    pub fn kind_range(self, range: RangeInclusive<u64>) -> Self {
+        if range.end() - range.start() < KIND_RANGE_EXPAND_MAX {
+            return self.map(|builder| builder.kinds(range));
+        }
+        let kind_range = match self.kind_range {
+            // A note has to be in both ranges
+            Some(old) => *old.start().max(range.start())..=*old.end().min(range.end()),
+            None => range,
+        };
+        Self {
+            kind_range: Some(kind_range),
+            ..self
+        }
+    }
+
+    /// Build and validate the filter (see [`validate`])
+    ///
+    /// A filter with only a kind range is not considered empty.
+    pub fn build(mut self, strict: bool) -> Result<FilterHandle> {
+        let filter = self.builder.build();
+        validate(&filter, strict && self.kind_range.is_none())?;
+        Ok(FilterHandle {
+            filter,
+            kind_range: self.kind_range,
+        })
+    }
+}
+
+impl FilterHandle {
+    /// The nostrdb filter, without the kind range
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+
+    /// Kind range that query results still have to be checked against
+    pub fn kind_range(&self) -> Option<&RangeInclusive<u64>> {
+        self.kind_range.as_ref()
+    }
+
+    /// Whether a note of this kind passes the kind range, if any
+    pub fn accepts_kind(&self, kind: u64) -> bool {
+        match &self.kind_range {
+            Some(range) => range.contains(&kind),
+            None => true,
+        }
+    }
+
+    /// NIP-01 JSON form, with the kind range written out as kinds
+    pub fn to_json(&self) -> Result<String> {
+        if self.kind_range.is_none() {
+            return Ok(self.filter.json()?);
+        }
+        let mut fields = filter_fields(&self.filter)?;
+        let kinds = kinds(self)?.unwrap_or_default();
+        fields.insert("kinds".to_string(), kinds.into());
+        Ok(serde_json::to_string(&fields)?)
+    }
+
+    /// The filter for one page of paged results
+    ///
+    /// The filter's own limit is dropped, since pages have their own size,
+    /// and `until` is lowered to `before` if given. Returns None if no note
+    /// can match any more because `before` is earlier than `since`.
+    pub fn for_page(&self, before: Option<u64>) -> Result<Option<FilterHandle>> {
+        if before.is_none() && self.filter.limit().is_none() {
+            return Ok(Some(self.clone()));
+        }
+        if let (Some(before), Some(since)) = (before, self.filter.since()) {
+            if before < since {
+                return Ok(None);
+            }
+        }
+        let mut fields = filter_fields(&self.filter)?;
+        fields.remove("limit");
+        if let Some(before) = before {
+            let until = self
+                .filter
+                .until()
+                .map_or(before, |until| until.min(before));
+            fields.insert("until".to_string(), until.into());
+        }
+        Ok(Some(FilterHandle {
+            filter: build_fields(&fields)?,
+            kind_range: self.kind_range.clone(),
+        }))
+    }
+
+    /// The filter restricted to notes by one author
+    ///
+    /// The filter's own limit is dropped. Returns None if the filter lists
+    /// authors and `pubkey` is not one of them.
+    pub fn for_author(&self, pubkey: &[u8; 32]) -> Result<Option<FilterHandle>> {
+        let author = Value::String(hex::encode(pubkey));
+        let mut fields = filter_fields(&self.filter)?;
+        if let Some(authors) = fields.get("authors") {
+            if !contains(authors, &author) {
+                return Ok(None);
+            }
+        }
+        fields.remove("limit");
+        fields.insert("authors".to_string(), Value::Array(vec![author]));
+        Ok(Some(FilterHandle {
+            filter: build_fields(&fields)?,
+            kind_range: self.kind_range.clone(),
+        }))
+    }
+}
+
+impl From<Filter> for FilterHandle {
+    fn from(filter: Filter) -> Self {
+        Self {
+            filter,
+            kind_range: None,
+        }
+    }
+}
+
+impl Deref for FilterHandle {
+    type Target = Filter;
+
+    fn deref(&self) -> &Filter {
+        &self.filter
+    }
+}
+
+/// Parse a NIP-01 filter object
+///
+/// Supports `ids`, `authors`, `kinds`, `#<letter>` tags, `since`, `until`,
+/// `limit`, and `search`. Unknown fields are rejected rather than ignored,
+/// since silently dropping one would widen the filter. The result is
+/// checked with [`validate`].
+pub fn from_json(json: &str, strict: bool) -> Result<Filter> {
+    let value: Value = serde_json::from_str(json)?;
+    let fields = value
+        .as_object()
+        .ok_or_else(|| Error::Filter("Filter JSON must be an object".to_string()))?;
+
+    // Checked up front too, as nostrdb cannot add fields past its limit
+    if fields.len() > MAX_ELEMENTS as usize {
+        return Err(too_many_elements(fields.len()));
+    }
+
+    let filter = build_fields(fields)?;
+    validate(&filter, strict)?;
+    Ok(filter)
+}
+
+/// Build a filter from the fields of a NIP-01 filter object
+fn build_fields(fields: &Map<String, Value>) -> Result<Filter> {
+    let mut builder = Filter::new();
+    for (key, value) in fields {
+        builder = add_field(builder, key, value)?;
+    }
+    Ok(builder.build())
+}
+
+/// Reject filters that could never match as intended
+///
+/// Always rejects since after until and more elements than nostrdb
+/// supports. In strict mode an empty filter, which matches every note, is
+/// rejected as well.
+pub fn validate(filter: &Filter, strict: bool) -> Result<()> {
+    if let (Some(since), Some(until)) = (filter.since(), filter.until()) {
+        if since > until {
+            return Err(Error::Filter(format!(
+                "since ({}) is after until ({}); the filter can never match",
+                since, until
+            )));
+        }
+    }
+    let elements = filter.num_elements();
+    if elements > MAX_ELEMENTS {
+        return Err(too_many_elements(elements as usize));
+    }
+    if strict && elements == 0 {
+        return Err(Error::Filter(
+            "Filter is empty and would match every note".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn too_many_elements(elements: usize) -> Error {
+    Error::Filter(format!(
+        "Filter has {} elements; nostrdb supports at most {}",
+        elements, MAX_ELEMENTS
+    ))
+}
+
+/// Set how far in the future a since/until timestamp may lie
+pub fn set_timestamp_slack(secs: u64) {
+    TIMESTAMP_SLACK_SECS.store(secs, Ordering::Relaxed);
+}
+
+/// Validate a since/until timestamp passed from Java
+///
+/// A negative value would wrap to a huge u64 and one far in the future is
+/// almost certainly in milliseconds; both are rejected rather than
+/// producing a filter that matches everything or nothing.
+pub fn timestamp(name: &str, ts: i64) -> Result<u64> {
+    let ts = u64::try_from(ts).map_err(|_| {
+        Error::InvalidArgument(format!("{} must not be negative, got {}", name, ts))
+    })?;
+    let slack = TIMESTAMP_SLACK_SECS.load(Ordering::Relaxed);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    if ts > now.saturating_add(slack) {
+        return Err(Error::InvalidArgument(format!(
+            "{} {} is more than {} seconds in the future; is it in milliseconds?",
+            name, ts, slack
+        )));
+    }
+    Ok(ts)
+}
+
+/// Whether a stored note matches a filter, including its search term
+///
+/// The search term is matched against the content as [`note_content`]
+/// returns it under `utf8`.
+pub fn matches_note(filter: &FilterHandle, note: &Note, utf8: Utf8Mode) -> Result<bool> {
+    if !filter.matches(note) || !filter.accepts_kind(note.kind() as u64) {
+        return Ok(false);
+    }
+    Ok(match filter_fields(filter)?.get("search") {
+        Some(search) => search_matches(search, &note_content(note, utf8)?),
+        None => true,
+    })
+}
+
+/// Whether a note given as an event JSON object matches a filter
+///
+/// The event is not validated or stored; only the fields the filter
+/// refers to are read.
+pub fn matches_event_json(filter: &FilterHandle, event_json: &str) -> Result<bool> {
+    let event: Value = serde_json::from_str(event_json)?;
+    let event = event
+        .as_object()
+        .ok_or_else(|| Error::InvalidArgument("Event JSON must be an object".to_string()))?;
+    if filter.kind_range().is_some() && !filter.accepts_kind(event_number(event, "kind")?) {
+        return Ok(false);
+    }
+
+    for (key, expected) in &filter_fields(filter)? {
+        let matched = match key.as_str() {
+            "ids" => contains(expected, event_field(event, "id")?),
+            "authors" => contains(expected, event_field(event, "pubkey")?),
+            "kinds" => contains(expected, event_field(event, "kind")?),
+            "since" => number(key, expected)? <= event_number(event, "created_at")?,
+            "until" => number(key, expected)? >= event_number(event, "created_at")?,
+            "limit" => true,
+            "search" => search_matches(expected, event_str(event, "content")?),
+            _ => {
+                let tag = tag_name(key)?.to_string();
+                let tags = event_field(event, "tags")?
+                    .as_array()
+                    .ok_or_else(|| invalid_event("tags"))?;
+                tags.iter().any(|t| {
+                    t.get(0).and_then(Value::as_str) == Some(tag.as_str())
+                        && t.get(1).is_some_and(|value| contains(expected, value))
+                })
+            }
+        };
+        if !matched {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Normalize hashtags for a `#t` filter (NIP-24)
+///
+/// Strips surrounding whitespace and one leading '#', lowercases, and drops
+/// empty values and duplicates, keeping the first occurrence's order.
+/// Fails if no values are left, since an empty tag filter matches nothing.
+pub fn normalize_hashtags(tags: &[String]) -> Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim();
+        let tag = tag.strip_prefix('#').unwrap_or(tag).to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    if normalized.is_empty() {
+        return Err(Error::InvalidArgument(
+            "No hashtags left after normalization".to_string(),
+        ));
+    }
+    Ok(normalized)
+}
+
+/// Kinds matched by a built filter, or None if it does not restrict kinds
+///
+/// A kind range is written out in full, intersected with any kinds element.
+pub fn kinds(filter: &FilterHandle) -> Result<Option<Vec<u64>>> {
+    let listed = filter_fields(filter)?
+        .get("kinds")
+        .map(|kinds| {
+            array("kinds", kinds)?
+                .iter()
+                .map(|kind| number("kinds", kind))
+                .collect::<Result<Vec<_>>>()
+        })
+        .transpose()?;
+    Ok(match (listed, filter.kind_range()) {
+        (Some(listed), Some(_)) => Some(
+            listed
+                .into_iter()
+                .filter(|&kind| filter.accepts_kind(kind))
+                .collect(),
+        ),
+        (None, Some(range)) => Some(range.clone().collect()),
+        (listed, None) => listed,
+    })
+}
+
+/// Authors in a built filter, or None if it has no authors element
+pub fn authors(filter: &Filter) -> Result<Option<Vec<[u8; 32]>>> {
+    filter_fields(filter)?
+        .get("authors")
+        .map(|authors| hex_ids("authors", authors))
+        .transpose()
+}
+
+/// The fields of a built filter, from its JSON form
+fn filter_fields(filter: &Filter) -> Result<Map<String, Value>> {
+    match serde_json::from_str(&filter.json()?)? {
+        Value::Object(fields) => Ok(fields),
+        _ => Err(Error::Filter("Filter JSON is not an object".to_string())),
+    }
+}
+
+fn search_matches(search: &Value, content: &str) -> bool {
+    let search = search.as_str().unwrap_or_default().to_lowercase();
+    content.to_lowercase().contains(&search)
+}
+
+/// Whether a filter array contains a value
+fn contains(expected: &Value, value: &Value) -> bool {
+    expected
+        .as_array()
+        .is_some_and(|values| values.contains(value))
+}
+
+fn event_field<'a>(event: &'a Map<String, Value>, name: &str) -> Result<&'a Value> {
+    event.get(name).ok_or_else(|| invalid_event(name))
+}
+
+fn event_number(event: &Map<String, Value>, name: &str) -> Result<u64> {
+    event_field(event, name)?
+        .as_u64()
+        .ok_or_else(|| invalid_event(name))
+}
+
+fn event_str<'a>(event: &'a Map<String, Value>, name: &str) -> Result<&'a str> {
+    event_field(event, name)?
+        .as_str()
+        .ok_or_else(|| invalid_event(name))
+}
+
+fn invalid_event(field: &str) -> Error {
+    Error::InvalidArgument(format!("Event JSON has a missing or invalid {:?}", field))
+}
+
+/// Apply one filter field to the builder
+fn add_field(builder: FilterBuilder, key: &str, value: &Value) -> Result<FilterBuilder> {
+    Ok(match key {
+        "ids" => builder.ids(hex_ids(key, value)?.iter()),
+        "authors" => builder.authors(hex_ids(key, value)?.iter()),
+        "kinds" => builder.kinds(
+            array(key, value)?
+                .iter()
+                .map(|kind| number(key, kind))
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        "since" => builder.since(number(key, value)?),
+        "until" => builder.until(number(key, value)?),
+        "limit" => builder.limit(number(key, value)?),
+        "search" => builder.search(
+            value
+                .as_str()
+                .ok_or_else(|| invalid(key, "expected a string"))?,
+        ),
+        _ => {
+            let tag = tag_name(key)?;
+            let values = array(key, value)?
+                .iter()
+                .map(|v| v.as_str().ok_or_else(|| invalid(key, "expected strings")))
+                .collect::<Result<Vec<_>>>()?;
+
+            // nostrdb stores 32-byte hex tag values (e.g. #e, #p) in binary,
+            // so they have to be matched as ids
+            let ids: Option<Vec<[u8; 32]>> = values.iter().map(|v| decode_id(v)).collect();
+            match ids {
+                Some(ids) => builder.id_tags(tag, ids.iter()),
+                None => builder.tags(values, tag),
+            }
+        }
+    })
+}
+
+/// Single-character tag name from a `#x` key
+fn tag_name(key: &str) -> Result<char> {
+    let mut chars = key.strip_prefix('#').unwrap_or("").chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(Error::Filter(format!("Unsupported filter field {:?}", key))),
+    }
+}
+
+fn array<'a>(key: &str, value: &'a Value) -> Result<&'a Vec<Value>> {
+    value
+        .as_array()
+        .ok_or_else(|| invalid(key, "expected an array"))
+}
+
+fn number(key: &str, value: &Value) -> Result<u64> {
+    value
+        .as_u64()
+        .ok_or_else(|| invalid(key, "expected a non-negative integer"))
+}
+
+/// Decode an array of 64-character hex strings
+fn hex_ids(key: &str, value: &Value) -> Result<Vec<[u8; 32]>> {
+    array(key, value)?
+        .iter()
+        .map(|v| {
+            v.as_str()
+                .and_then(decode_id)
+                .ok_or_else(|| invalid(key, "expected 64-character hex strings"))
+        })
+        .collect()
+}
+
+fn decode_id(hex_str: &str) -> Option<[u8; 32]> {
+    let mut id = [0u8; 32];
+    hex::decode_to_slice(hex_str, &mut id).ok()?;
+    Some(id)
+}
+
+fn invalid(key: &str, reason: &str) -> Error {
+    Error::Filter(format!("Invalid filter field {:?}: {}", key, reason))
+}
@@ -0,0 +1,1113 @@
+//! Native handle for an open nostrdb database
+//!
+//! The jlong handed to Java for an open database points at an
+//! [`NdbHandle`], which owns the nostrdb instance together with the
+//! binding-level state that lives alongside it.
+//!
+//! LMDB must not open the same environment twice in one process, so the
+//! nostrdb instance of a directory lives in a [`SharedDb`] recorded in a
+//! registry keyed by canonical path. A write-protected open of a directory
+//! that is already open shares the existing state.
+//!
+//! Live handle pointers are tracked as well, so that `ndbIsOpen` and
+//! `ndbClose` can recognize a pointer that was already closed instead of
+//! dereferencing freed memory.
+//!
+//! The instance sits behind a `RwLock` so that it can be closed and
+//! reopened with a larger map when auto-resize is enabled. nostrdb's
+//! writer drops events it can't store once the map is full without
+//! reporting it, so a [`Resizer`] thread measures the map through a read
+//! transaction on the instance instead (see [`lmdb`]), and grows it once
+//! it is full:
+//!
+//! - While the map is nearly full, [`Writer`] keeps a copy of each event it
+//!   queues, up to [`RETAIN_BYTES`]; after the map has grown, those the old
+//!   instance didn't store are queued again.
+//! - Operations and transactions hold an `Arc` clone of the instance while
+//!   they use it, and the instance is only closed once no clone is left.
+//!   If one stays open too long, [`Writer`] holds events back instead of
+//!   handing them to the full instance, and queues them once the map has
+//!   grown. Writes only fail once [`DEFER_BYTES`] are held back.
+//! - Subscriptions are made through the handle, which records their
+//!   filters and re-creates them on the new instance under the same id,
+//!   together with the notes the old instance had matched but that weren't
+//!   polled yet. One that can't be re-created fails when it is polled.
+//! - If the instance can't be reopened with the larger map, it is reopened
+//!   with the old one. Only if that fails too is the database unavailable.
+
+use jni::sys::jlong;
+use nostrdb::{Filter, IngestMetadata, Ndb, NoteKey, Subscription, Transaction};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{
+    Arc, Mutex, MutexGuard, OnceLock, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak,
+};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::config::{self, NdbConfig};
+use crate::error::{Error, Result};
+use crate::filter;
+use crate::health::{self, HealthMonitor, Thresholds};
+use crate::ingest;
+use crate::json::Utf8Mode;
+use crate::lmdb;
+use crate::stats::IngestStats;
+use crate::util::{box_to_ptr, ptr_to_ref, take_ptr, Handle, HandleKind};
+use crate::verify;
+
+/// How often auto-resize measures the map
+const RESIZE_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a resize waits for operations and transactions on the
+/// instance to end
+const RESIZE_WAIT: Duration = Duration::from_secs(1);
+
+/// Utilization (in percent) from which queued events are kept
+const RETAIN_PERCENT: u64 = 90;
+
+/// How long a queued event is kept while the map isn't full
+const RETAIN_TIME: Duration = Duration::from_secs(2);
+
+/// Most bytes of queued events kept at once; the oldest go first
+const RETAIN_BYTES: usize = 64 * 1024 * 1024;
+
+/// Most bytes of events held back while a resize waits; writes beyond it
+/// fail
+const DEFER_BYTES: usize = 64 * 1024 * 1024;
+
+/// Notes taken from a subscription at a time when a resize drains it
+const DRAIN_BATCH: u32 = 1024;
+
+/// An open database and its associated binding state
+pub struct NdbHandle {
+    // Declared first so the monitor threads stop before the database closes
+    health: Mutex<Option<HealthMonitor>>,
+    /// Only held, to stop the thread on close
+    _resizer: Option<Resizer>,
+    thresholds: Mutex<Thresholds>,
+    db: Arc<SharedDb>,
+    /// Canonical database directory
+    path: PathBuf,
+    write_protected: bool,
+    /// From the configuration this handle was opened with
+    utf8: Utf8Mode,
+    /// Totals of the events ingested through this handle, shared with the
+    /// health monitor
+    stats: Arc<IngestStats>,
+    closed: AtomicBool,
+}
+
+/// State shared by all handles open on one directory
+struct SharedDb {
+    /// None only if reopening after a failed resize also failed
+    ndb: RwLock<Option<Arc<Ndb>>>,
+    /// Canonical database directory
+    path: PathBuf,
+    /// The configuration the instance is currently open with
+    config: Mutex<NdbConfig>,
+    subs: Mutex<Subscriptions>,
+    /// Whether writers keep copies of the events they queue
+    retaining: AtomicBool,
+    /// Copies of the events queued while the map was nearly full
+    retained: Mutex<Events>,
+    /// The map is full and can't grow while transactions are open
+    stalled: AtomicBool,
+    /// Events held back while stalled, queued once the map has grown
+    deferred: Mutex<Events>,
+}
+
+/// Events kept by the binding, oldest first, with their total size
+#[derive(Default)]
+struct Events {
+    queue: VecDeque<(Instant, Queued)>,
+    bytes: usize,
+}
+
+/// Subscriptions made through the handle, by the id given to Java
+#[derive(Default)]
+struct Subscriptions {
+    last_id: u64,
+    live: HashMap<u64, Sub>,
+}
+
+/// A subscription, as needed to re-create it on a new instance
+struct Sub {
+    /// Filter JSON, to build the filters from again
+    filters: Vec<String>,
+    /// The subscription on the current instance; None if it couldn't be
+    /// re-created after a resize
+    current: Option<Subscription>,
+    /// Notes matched on the previous instance that haven't been polled
+    pending: VecDeque<NoteKey>,
+}
+
+/// An event handed to nostrdb, kept so that it can be queued again
+enum Queued {
+    Event(String),
+    Relay(String, String),
+    Client(String),
+}
+
+/// The nostrdb instance for a write
+///
+/// Derefs to the instance. Its `process_*` methods shadow the instance's:
+/// they keep a copy of each event they queue while the map is nearly full,
+/// and hold events back while a resize waits.
+pub struct Writer {
+    ndb: Arc<Ndb>,
+    db: Arc<SharedDb>,
+}
+
+/// A database looked up each time it is used, for threads that outlive a
+/// call, without keeping it open
+pub struct DbSource(Weak<SharedDb>);
+
+/// Background thread growing a database's map once it is full
+///
+/// Dropping it stops the thread and waits for it to exit.
+struct Resizer {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// A read transaction together with the instance it reads from
+///
+/// Holding the instance keeps it from being resized while the transaction
+/// is open.
+pub struct TxnHandle {
+    // Declared first so the transaction ends before the instance is released
+    txn: Transaction,
+    ndb: Arc<Ndb>,
+    /// Shared with objects that borrow from the transaction
+    alive: TxnLiveness,
+}
+
+/// Whether a transaction is still open, shared with the objects that
+/// borrow from it
+///
+/// Reads through such a borrow hold a [`TxnGuard`]; ending the transaction
+/// takes the write lock, so it waits for them to finish.
+#[derive(Clone)]
+pub struct TxnLiveness(Arc<RwLock<bool>>);
+
+/// Keeps a transaction open while it is held
+pub type TxnGuard<'a> = RwLockReadGuard<'a, bool>;
+
+impl Handle for NdbHandle {
+    const KIND: HandleKind = HandleKind::Ndb;
+}
+
+impl Handle for TxnHandle {
+    const KIND: HandleKind = HandleKind::Transaction;
+}
+
+impl Deref for TxnHandle {
+    type Target = Transaction;
+
+    fn deref(&self) -> &Transaction {
+        &self.txn
+    }
+}
+
+impl Drop for TxnHandle {
+    fn drop(&mut self) {
+        // Waits for reads through objects borrowing the transaction
+        let mut open = self.alive.0.write().unwrap_or_else(PoisonError::into_inner);
+        *open = false;
+    }
+}
+
+impl Deref for Writer {
+    type Target = Ndb;
+
+    fn deref(&self) -> &Ndb {
+        &self.ndb
+    }
+}
+
+impl Writer {
+    /// Queue an event or relay message
+    pub fn process_event(&self, json: &str) -> Result<()> {
+        self.write(
+            |ndb| ndb.process_event(json),
+            || Queued::Event(json.to_string()),
+        )
+    }
+
+    /// Queue an event received from a relay
+    pub fn process_relay_event(&self, json: &str, relay: &str) -> Result<()> {
+        self.write(
+            |ndb| ndb.process_event_with(json, IngestMetadata::new().client(false).relay(relay)),
+            || Queued::Relay(json.to_string(), relay.to_string()),
+        )
+    }
+
+    /// Queue a client `EVENT` message
+    pub fn process_client_event(&self, message: &str) -> Result<()> {
+        self.write(
+            |ndb| ndb.process_client_event(message),
+            || Queued::Client(message.to_string()),
+        )
+    }
+
+    /// Hand an event to nostrdb, or hold it back while a resize waits
+    ///
+    /// `event` makes the copy, and is only called when one is kept.
+    fn write(
+        &self,
+        submit: impl FnOnce(&Ndb) -> std::result::Result<(), nostrdb::Error>,
+        event: impl FnOnce() -> Queued,
+    ) -> Result<()> {
+        if self.db.stalled.load(Ordering::Relaxed) {
+            // Handed back if the stall ended before it could be held back
+            let Some(event) = self.db.defer(event())? else {
+                return Ok(());
+            };
+            let result = event.submit(&self.ndb);
+            self.db.retain(&result, || event);
+            return Ok(result?);
+        }
+        let result = submit(&self.ndb);
+        self.db.retain(&result, event);
+        Ok(result?)
+    }
+}
+
+impl Queued {
+    fn submit(&self, ndb: &Ndb) -> std::result::Result<(), nostrdb::Error> {
+        match self {
+            Queued::Event(json) => ndb.process_event(json),
+            Queued::Relay(json, relay) => {
+                ndb.process_event_with(json, IngestMetadata::new().client(false).relay(relay))
+            }
+            Queued::Client(message) => ndb.process_client_event(message),
+        }
+    }
+
+    /// Bytes of JSON the copy holds
+    fn len(&self) -> usize {
+        match self {
+            Queued::Event(json) | Queued::Client(json) => json.len(),
+            Queued::Relay(json, relay) => json.len() + relay.len(),
+        }
+    }
+
+    /// The id the event claims, if it has a readable one
+    fn id(&self) -> Option<[u8; 32]> {
+        let json = match self {
+            Queued::Event(json) | Queued::Relay(json, _) | Queued::Client(json) => json,
+        };
+        let event = verify::parse_wrapped(json).ok()?;
+        verify::event_claimed_id(&event).ok()
+    }
+}
+
+impl DbSource {
+    /// The current instance; None once the database has been closed
+    pub fn ndb(&self) -> Option<Arc<Ndb>> {
+        self.0.upgrade().and_then(|db| db.ndb().ok())
+    }
+
+    /// Poll a subscription made with [`NdbHandle::subscribe`]; None once
+    /// the database has been closed
+    pub fn poll(&self, sub: Subscription, max_notes: u32) -> Option<Result<Vec<NoteKey>>> {
+        Some(self.0.upgrade()?.poll(sub, max_notes))
+    }
+
+    /// End a subscription made with [`NdbHandle::subscribe`]
+    pub fn unsubscribe(&self, sub: Subscription) -> Result<()> {
+        match self.0.upgrade() {
+            Some(db) => db.unsubscribe(sub),
+            None => Ok(()),
+        }
+    }
+}
+
+impl TxnHandle {
+    /// The instance the transaction reads from
+    pub fn ndb(&self) -> &Ndb {
+        &self.ndb
+    }
+
+    /// A token telling whether the transaction is still open
+    pub fn liveness(&self) -> TxnLiveness {
+        self.alive.clone()
+    }
+}
+
+impl TxnLiveness {
+    /// Keep the transaction open while the guard is held
+    ///
+    /// `borrower` names what borrows the transaction, for the error
+    /// thrown once it has ended.
+    pub fn guard(&self, borrower: &str) -> Result<TxnGuard<'_>> {
+        let open = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        if !*open {
+            return Err(Error::InvalidState(format!(
+                "The {}'s transaction has ended",
+                borrower
+            )));
+        }
+        Ok(open)
+    }
+}
+
+impl NdbHandle {
+    /// Open the database at `path` with the given configuration
+    ///
+    /// # Arguments
+    /// * `path` - The database directory
+    /// * `config` - Configuration to open with
+    /// * `create_if_missing` - Create the directory (and parents) if needed
+    pub fn open(path: &str, config: &NdbConfig, create_if_missing: bool) -> Result<Self> {
+        prepare_dir(path, create_if_missing)?;
+        let mut open = lock(registry())?;
+        if find_open(&open, path).is_some() {
+            return Err(open_failed(
+                path,
+                "database is already open in this process (close it first or open it write-protected)",
+            ));
+        }
+        let db = SharedDb::open(path, config.clone())?;
+        let resizer = if config.auto_resize_max() > config.mapsize() {
+            Some(Resizer::start(&db, config.auto_resize_max())?)
+        } else {
+            None
+        };
+        Ok(Self::register(
+            &mut open,
+            path,
+            db,
+            resizer,
+            false,
+            config.utf8_mode(),
+        ))
+    }
+
+    /// Open the database at `path`, rejecting writes through this handle
+    ///
+    /// The directory must already exist. If it is open elsewhere in this
+    /// process, the existing instance is shared; otherwise nostrdb is opened
+    /// with migrations disabled.
+    ///
+    /// This is a write guard, not a read-only open: only the binding refuses
+    /// writes, in `check_writable`. nostrdb has no read-only mode, so LMDB
+    /// still opens the environment read-write and the writer threads run,
+    /// and other processes can still write to it.
+    pub fn open_write_protected(path: &str, config: &NdbConfig) -> Result<Self> {
+        prepare_dir(path, false)?;
+        let mut open = lock(registry())?;
+        let db = match find_open(&open, path) {
+            Some(db) => db,
+            None => {
+                let mut config = config.clone();
+                config.set_flags(config.flags() | config::FLAG_NO_MIGRATE);
+                SharedDb::open(path, config)?
+            }
+        };
+        Ok(Self::register(
+            &mut open,
+            path,
+            db,
+            None,
+            true,
+            config.utf8_mode(),
+        ))
+    }
+
+    fn register(
+        open: &mut HashMap<PathBuf, Vec<Weak<SharedDb>>>,
+        path: &str,
+        db: Arc<SharedDb>,
+        resizer: Option<Resizer>,
+        write_protected: bool,
+        utf8: Utf8Mode,
+    ) -> Self {
+        open.retain(|_, dbs| {
+            dbs.retain(|db| db.strong_count() > 0);
+            !dbs.is_empty()
+        });
+        let path = canonical(path);
+        open.entry(path.clone())
+            .or_default()
+            .push(Arc::downgrade(&db));
+
+        Self {
+            health: Mutex::new(None),
+            _resizer: resizer,
+            thresholds: Mutex::new(Thresholds::default()),
+            db,
+            path,
+            write_protected,
+            utf8,
+            stats: Arc::default(),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// Box the handle for Java and record the pointer as live
+    pub fn into_ptr(self) -> Result<jlong> {
+        let mut live = lock(live_handles())?;
+        let ptr = box_to_ptr(self);
+        live.insert(ptr);
+        Ok(ptr)
+    }
+
+    /// Close a handle returned by [`NdbHandle::into_ptr`]
+    ///
+    /// Closing a pointer that is not live (already closed, or never
+    /// returned by `into_ptr`) does nothing.
+    ///
+    /// # Safety
+    /// No other thread may be using the handle.
+    pub unsafe fn close(ptr: jlong) {
+        let was_live = live_handles()
+            .lock()
+            .map(|mut live| live.remove(&ptr))
+            .unwrap_or(false);
+        if was_live {
+            if let Ok(handle) = take_ptr::<NdbHandle>(ptr, "ndb") {
+                handle.closed.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Whether `ptr` is a live, open handle
+    ///
+    /// Safe to call with stale pointers: they are looked up, not dereferenced.
+    pub fn is_open(ptr: jlong) -> bool {
+        let Ok(live) = live_handles().lock() else {
+            return false;
+        };
+        // SAFETY: live pointers are only freed by `close`, which needs the lock
+        live.contains(&ptr)
+            && unsafe { ptr_to_ref::<NdbHandle>(ptr, "ndb") }
+                .is_ok_and(|handle| !handle.closed.load(Ordering::SeqCst))
+    }
+
+    /// The canonical database directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// How notes with content that isn't valid UTF-8 are serialized
+    pub fn utf8_mode(&self) -> Utf8Mode {
+        self.utf8
+    }
+
+    /// Totals of the events ingested through this handle
+    pub fn ingest_stats(&self) -> &IngestStats {
+        &self.stats
+    }
+
+    /// Whether ingestion skips id and signature verification
+    pub fn skips_validation(&self) -> Result<bool> {
+        Ok(lock(&self.db.config)?.flags() & config::FLAG_SKIP_NOTE_VERIFY != 0)
+    }
+
+    /// The configuration the instance is currently open with
+    ///
+    /// A write-protected handle sharing another handle's instance reports that
+    /// handle's configuration; the map size reflects any auto-resize.
+    pub fn config(&self) -> Result<NdbConfig> {
+        Ok(lock(&self.db.config)?.clone())
+    }
+
+    /// The underlying nostrdb instance
+    pub fn ndb(&self) -> Result<Arc<Ndb>> {
+        self.db.ndb()
+    }
+
+    /// The nostrdb instance, for operations that write to the database
+    pub fn writable_ndb(&self) -> Result<Writer> {
+        self.check_writable()?;
+        Ok(Writer {
+            ndb: self.db.ndb()?,
+            db: Arc::clone(&self.db),
+        })
+    }
+
+    /// The database, looked up each time without keeping it open
+    pub fn db_source(&self) -> DbSource {
+        DbSource(Arc::downgrade(&self.db))
+    }
+
+    /// Begin a read transaction
+    pub fn begin_transaction(&self) -> Result<TxnHandle> {
+        let ndb = self.ndb()?;
+        let txn = Transaction::new(&ndb)?;
+        Ok(TxnHandle {
+            txn,
+            ndb,
+            alive: TxnLiveness(Arc::new(RwLock::new(true))),
+        })
+    }
+
+    /// Subscribe to notes matching any of `filters`
+    ///
+    /// The subscription keeps its id when a resize reopens the instance.
+    pub fn subscribe(&self, filters: &[Filter]) -> Result<Subscription> {
+        self.db.subscribe(filters)
+    }
+
+    /// Take up to `max_notes` keys of notes a subscription has matched
+    ///
+    /// An unknown subscription has no notes.
+    pub fn poll(&self, sub: Subscription, max_notes: u32) -> Result<Vec<NoteKey>> {
+        self.db.poll(sub, max_notes)
+    }
+
+    /// Remove a subscription
+    pub fn unsubscribe(&self, sub: Subscription) -> Result<()> {
+        self.db.unsubscribe(sub)
+    }
+
+    /// Start a health monitor for this database
+    pub fn start_health_monitor(
+        &self,
+        vm: jni::JavaVM,
+        callback: jni::objects::GlobalRef,
+        interval: std::time::Duration,
+    ) -> Result<HealthMonitor> {
+        let thresholds = *self.thresholds()?;
+        let db = Arc::downgrade(&self.db);
+        let stats = Arc::clone(&self.stats);
+        let sample: health::SampleFn = Box::new(move || {
+            let db = db.upgrade()?;
+            let ndb = db.ndb().ok()?;
+            let stored = ingest::stored_note_count(&ndb).ok()?;
+            let map = db.map_stats().ok()?;
+            Some(health::Sample {
+                map_used: map.map(|map| map.used),
+                mapsize: match map {
+                    Some(map) => map.mapsize,
+                    None => lock(&db.config).ok()?.mapsize(),
+                },
+                accepted: stats.snapshot()[0],
+                stored,
+            })
+        });
+        HealthMonitor::start(vm, callback, sample, thresholds, interval)
+    }
+
+    /// Replace the health monitor (None stops monitoring)
+    pub fn set_health_monitor(&self, monitor: Option<HealthMonitor>) -> Result<()> {
+        let previous = std::mem::replace(&mut *lock(&self.health)?, monitor);
+        // Stop the old thread outside the lock
+        drop(previous);
+        Ok(())
+    }
+
+    /// Thresholds used by health monitors started from now on
+    pub fn thresholds(&self) -> Result<MutexGuard<'_, Thresholds>> {
+        lock(&self.thresholds)
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.write_protected {
+            return Err(Error::InvalidState(
+                "Database handle is write-protected".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl SharedDb {
+    fn open(path: &str, config: NdbConfig) -> Result<Arc<Self>> {
+        let ndb = new_ndb(path, &config)?;
+        Ok(Arc::new(Self {
+            ndb: RwLock::new(Some(Arc::new(ndb))),
+            path: canonical(path),
+            config: Mutex::new(config),
+            subs: Mutex::default(),
+            retaining: AtomicBool::new(false),
+            retained: Mutex::default(),
+            stalled: AtomicBool::new(false),
+            deferred: Mutex::default(),
+        }))
+    }
+
+    fn ndb(&self) -> Result<Arc<Ndb>> {
+        let ndb = self
+            .ndb
+            .read()
+            .map_err(|_| Error::InvalidState("Ndb handle lock poisoned".to_string()))?;
+        ndb.clone().ok_or_else(|| {
+            Error::InvalidState("Database is unavailable after a failed map resize".to_string())
+        })
+    }
+
+    fn subscribe(&self, filters: &[Filter]) -> Result<Subscription> {
+        let json = filters
+            .iter()
+            .map(|filter| filter.json())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        // Held while subscribing so that a resize can't miss the subscription
+        let ndb = self.ndb()?;
+        let mut subs = lock(&self.subs)?;
+        let current = ndb.subscribe(filters)?;
+        subs.last_id += 1;
+        let id = subs.last_id;
+        subs.live.insert(
+            id,
+            Sub {
+                filters: json,
+                current: Some(current),
+                pending: VecDeque::new(),
+            },
+        );
+        Ok(Subscription::new(id))
+    }
+
+    fn poll(&self, sub: Subscription, max_notes: u32) -> Result<Vec<NoteKey>> {
+        let ndb = self.ndb()?;
+        let mut subs = lock(&self.subs)?;
+        let Some(sub) = subs.live.get_mut(&sub.id()) else {
+            return Ok(Vec::new());
+        };
+        let Some(current) = sub.current else {
+            return Err(Error::InvalidState(
+                "Subscription was lost when auto-resize reopened the database".to_string(),
+            ));
+        };
+        let from_pending = sub.pending.len().min(max_notes as usize);
+        let mut keys: Vec<NoteKey> = sub.pending.drain(..from_pending).collect();
+        let remaining = max_notes - keys.len() as u32;
+        if remaining > 0 {
+            keys.extend(ndb.poll_for_notes(current, remaining));
+        }
+        Ok(keys)
+    }
+
+    fn unsubscribe(&self, sub: Subscription) -> Result<()> {
+        let ndb = self.ndb()?;
+        let removed = lock(&self.subs)?.live.remove(&sub.id());
+        if let Some(current) = removed.and_then(|sub| sub.current) {
+            // Ndb clones share the underlying database, so a clone can be
+            // borrowed mutably without exclusive access to the handle
+            (*ndb).clone().unsubscribe(current)?;
+        }
+        Ok(())
+    }
+
+    /// Keep a copy of an event that was queued, while the map is nearly full
+    fn retain(
+        &self,
+        result: &std::result::Result<(), nostrdb::Error>,
+        event: impl FnOnce() -> Queued,
+    ) {
+        if result.is_err() || !self.retaining.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Ok(mut retained) = self.retained.lock() {
+            retained.push(event());
+            while retained.bytes > RETAIN_BYTES {
+                retained.pop();
+            }
+        }
+    }
+
+    /// Hold an event back until the waiting resize has happened
+    ///
+    /// Hands the event back if the map is no longer stalled, to be queued
+    /// right away.
+    fn defer(&self, event: Queued) -> Result<Option<Queued>> {
+        let mut deferred = lock(&self.deferred)?;
+        // Checked under the lock: the flag is cleared before the held-back
+        // events are taken, so none can be left behind
+        if !self.stalled.load(Ordering::Relaxed) {
+            return Ok(Some(event));
+        }
+        if deferred.bytes + event.len() > DEFER_BYTES {
+            return Err(Error::InvalidState(
+                "The map is full and auto-resize is waiting for open transactions to end; \
+                 too many writes are already held back"
+                    .to_string(),
+            ));
+        }
+        deferred.push(event);
+        Ok(None)
+    }
+
+    /// Size and usage of the instance's map
+    fn map_stats(&self) -> Result<Option<lmdb::MapStats>> {
+        let ndb = self.ndb()?;
+        let txn = Transaction::new(&ndb)?;
+        Ok(lmdb::map_stats(&txn))
+    }
+
+    /// Measure the map, and grow it (up to `max` bytes) if it is full
+    fn check_map(&self, max: usize) -> Result<()> {
+        let Some(lmdb::MapStats { mapsize, used }) = self.map_stats()? else {
+            return Ok(());
+        };
+        let free = (mapsize as u64).saturating_sub(used);
+        let nearly_full = used.saturating_mul(100)
+            >= (mapsize as u64).saturating_mul(RETAIN_PERCENT)
+            || free < 4 * health::MAP_FULL_HEADROOM;
+        let can_grow = mapsize < max;
+        self.retaining
+            .store(nearly_full && can_grow, Ordering::Relaxed);
+
+        if !health::map_full(used, mapsize) || !can_grow {
+            // A resize that was waiting isn't needed any more
+            if self.stalled.swap(false, Ordering::Relaxed) {
+                self.release_deferred(&*self.ndb()?)?;
+            }
+            let mut retained = lock(&self.retained)?;
+            if nearly_full && can_grow {
+                while retained
+                    .queue
+                    .front()
+                    .is_some_and(|(queued_at, _)| queued_at.elapsed() >= RETAIN_TIME)
+                {
+                    retained.pop();
+                }
+            } else {
+                *retained = Events::default();
+            }
+            return Ok(());
+        }
+
+        let grown = self.grow(mapsize.saturating_mul(2).min(max));
+        if matches!(grown, Ok(false)) {
+            return Ok(());
+        }
+        // Reopened, with the larger map or else the old one
+        let requeued = self.requeue();
+        grown?;
+        requeued
+    }
+
+    /// Reopen the instance with a map of `new_size` bytes, returning false
+    /// if it is still waiting to
+    ///
+    /// Marks writes as stalled instead if operations or transactions stay
+    /// open on the instance for [`RESIZE_WAIT`]. If the larger map fails,
+    /// the instance is reopened with the old one and the error returned.
+    fn grow(&self, new_size: usize) -> Result<bool> {
+        let path = self.path.to_string_lossy().into_owned();
+        let Some(mut slot) = self.exclusive()? else {
+            if !self.stalled.swap(true, Ordering::Relaxed) {
+                tracing::warn!(
+                    "Map at {} is full; holding writes back until open transactions end",
+                    path
+                );
+            }
+            return Ok(false);
+        };
+        let mut config = lock(&self.config)?;
+        let mut subs = lock(&self.subs)?;
+        if let Some(old) = slot.as_ref() {
+            subs.drain(old);
+        }
+        tracing::info!(
+            "Growing nostrdb map at {} from {} to {} bytes",
+            path,
+            config.mapsize(),
+            new_size
+        );
+
+        // LMDB can't open an environment twice in one process, so the old
+        // instance closes first
+        drop(slot.take());
+        let mut grown = config.clone();
+        grown.set_mapsize(new_size);
+        let (ndb, result) = match new_ndb(&path, &grown) {
+            Ok(ndb) => {
+                *config = grown;
+                (ndb, Ok(true))
+            }
+            Err(e) => {
+                tracing::error!("Map resize failed, reopening with the old size: {}", e);
+                (new_ndb(&path, &config)?, Err(e))
+            }
+        };
+        subs.recreate(&ndb);
+        *slot = Some(Arc::new(ndb));
+        self.stalled.store(false, Ordering::Relaxed);
+        result
+    }
+
+    /// Write access to the instance once nothing else holds a clone of it,
+    /// or None if clones are still held after [`RESIZE_WAIT`]
+    ///
+    /// The lock is only held while checking, so operations that need the
+    /// instance to finish aren't blocked while waiting.
+    fn exclusive(&self) -> Result<Option<RwLockWriteGuard<'_, Option<Arc<Ndb>>>>> {
+        let deadline = Instant::now() + RESIZE_WAIT;
+        loop {
+            let slot = self
+                .ndb
+                .write()
+                .map_err(|_| Error::InvalidState("Ndb handle lock poisoned".to_string()))?;
+            if slot.as_ref().is_none_or(|ndb| Arc::strong_count(ndb) == 1) {
+                return Ok(Some(slot));
+            }
+            drop(slot);
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Queue the kept events again, except those stored before the resize,
+    /// then the events held back while it waited
+    fn requeue(&self) -> Result<()> {
+        let retained = std::mem::take(&mut *lock(&self.retained)?);
+        let ndb = self.ndb()?;
+        let mut requeued = 0;
+        if !retained.queue.is_empty() {
+            let txn = Transaction::new(&ndb)?;
+            for (_, event) in &retained.queue {
+                let stored = event
+                    .id()
+                    .is_some_and(|id| ndb.get_notekey_by_id(&txn, &id).is_ok());
+                if !stored && event.submit(&ndb).is_ok() {
+                    requeued += 1;
+                }
+            }
+        }
+        let released = self.release_deferred(&ndb)?;
+        tracing::info!(
+            "Queued {} of {} kept events again and {} held-back events after reopening the map",
+            requeued,
+            retained.queue.len(),
+            released
+        );
+        Ok(())
+    }
+
+    /// Queue the events held back while the map was stalled, returning how
+    /// many nostrdb accepted
+    ///
+    /// The stalled flag must be cleared first.
+    fn release_deferred(&self, ndb: &Ndb) -> Result<usize> {
+        let deferred = std::mem::take(&mut *lock(&self.deferred)?);
+        let mut released = 0;
+        for (_, event) in deferred.queue {
+            let result = event.submit(ndb);
+            if result.is_ok() {
+                released += 1;
+            }
+            self.retain(&result, || event);
+        }
+        Ok(released)
+    }
+}
+
+impl Events {
+    fn push(&mut self, event: Queued) {
+        self.bytes += event.len();
+        self.queue.push_back((Instant::now(), event));
+    }
+
+    /// Drop the oldest event
+    fn pop(&mut self) {
+        if let Some((_, event)) = self.queue.pop_front() {
+            self.bytes -= event.len();
+        }
+    }
+}
+
+impl Subscriptions {
+    /// Move the notes the old instance has matched into each subscription
+    fn drain(&mut self, old: &Ndb) {
+        for sub in self.live.values_mut() {
+            let Some(current) = sub.current else {
+                continue;
+            };
+            loop {
+                let keys = old.poll_for_notes(current, DRAIN_BATCH);
+                let done = keys.len() < DRAIN_BATCH as usize;
+                sub.pending.extend(keys);
+                if done {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Subscribe again on a new instance
+    fn recreate(&mut self, ndb: &Ndb) {
+        for (id, sub) in self.live.iter_mut() {
+            let filters = sub
+                .filters
+                .iter()
+                .map(|json| filter::from_json(json, false))
+                .collect::<Result<Vec<_>>>();
+            sub.current = match filters.and_then(|filters| Ok(ndb.subscribe(&filters)?)) {
+                Ok(current) => Some(current),
+                Err(e) => {
+                    tracing::error!("Subscription {} was lost in a map resize: {}", id, e);
+                    None
+                }
+            };
+        }
+    }
+}
+
+impl Resizer {
+    /// Start checking the map of `db` every [`RESIZE_CHECK_INTERVAL`],
+    /// growing it up to `max` bytes
+    fn start(db: &Arc<SharedDb>, max: usize) -> Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let db = Arc::downgrade(db);
+        let thread = thread::Builder::new()
+            .name("nostrdb-resize".to_string())
+            .spawn(move || {
+                // Any other result means stop was requested or the handle was dropped
+                while let Err(RecvTimeoutError::Timeout) =
+                    stop_rx.recv_timeout(RESIZE_CHECK_INTERVAL)
+                {
+                    let Some(db) = db.upgrade() else {
+                        return;
+                    };
+                    if let Err(e) = db.check_map(max) {
+                        tracing::error!("Auto-resize failed: {}", e);
+                    }
+                }
+            })
+            .map_err(Error::Io)?;
+        Ok(Self {
+            stop: Some(stop_tx),
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for Resizer {
+    fn drop(&mut self) {
+        // Closing the channel wakes the thread up immediately
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Check the database directory before opening, creating it if requested
+fn prepare_dir(path: &str, create_if_missing: bool) -> Result<()> {
+    let dir = Path::new(path);
+    match fs::metadata(dir) {
+        Ok(meta) if meta.is_dir() => Ok(()),
+        Ok(_) => Err(open_failed(path, "path exists but is not a directory")),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            if create_if_missing {
+                return fs::create_dir_all(dir).map_err(|e| {
+                    open_failed(
+                        path,
+                        &format!("cannot create directory: {}", describe_io(&e)),
+                    )
+                });
+            }
+            match dir.parent().filter(|p| !p.as_os_str().is_empty()) {
+                Some(parent) if !parent.exists() => Err(open_failed(
+                    path,
+                    &format!(
+                        "directory does not exist (parent {} is missing)",
+                        parent.display()
+                    ),
+                )),
+                _ => Err(open_failed(path, "directory does not exist")),
+            }
+        }
+        Err(e) => Err(open_failed(path, &describe_io(&e))),
+    }
+}
+
+/// Open nostrdb, replacing its bare DbOpenFailed with a diagnosed cause
+fn new_ndb(path: &str, config: &NdbConfig) -> Result<Ndb> {
+    Ndb::new(path, config.config()).map_err(|e| match e {
+        nostrdb::Error::DbOpenFailed => open_failed(path, &diagnose_open_failure(path)),
+        e => Error::Nostrdb(e),
+    })
+}
+
+/// Best-effort explanation of why nostrdb could not open `path`
+fn diagnose_open_failure(path: &str) -> String {
+    let dir = Path::new(path);
+    if let Err(e) = fs::read_dir(dir) {
+        return describe_io(&e);
+    }
+    if !is_writable(dir) {
+        return "permission denied (directory is not writable)".to_string();
+    }
+    for file in ["data.mdb", "lock.mdb"] {
+        let file = dir.join(file);
+        if file.exists() {
+            if let Err(e) = fs::OpenOptions::new().read(true).write(true).open(&file) {
+                return format!("cannot open {}: {}", file.display(), describe_io(&e));
+            }
+        }
+    }
+    "LMDB could not open the environment (the lock may be held by another \
+     process, or the map size may be too small for the existing data)"
+        .to_string()
+}
+
+#[cfg(unix)]
+fn is_writable(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    let Ok(c_path) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: c_path is a valid NUL-terminated string
+    unsafe { libc::access(c_path.as_ptr(), libc::W_OK | libc::X_OK) == 0 }
+}
+
+#[cfg(not(unix))]
+fn is_writable(dir: &Path) -> bool {
+    fs::metadata(dir)
+        .map(|m| !m.permissions().readonly())
+        .unwrap_or(false)
+}
+
+fn describe_io(e: &io::Error) -> String {
+    match e.kind() {
+        io::ErrorKind::PermissionDenied => "permission denied".to_string(),
+        _ => e.to_string(),
+    }
+}
+
+fn open_failed(path: &str, reason: &str) -> Error {
+    Error::DbOpenFailed {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+fn live_handles() -> &'static Mutex<HashSet<jlong>> {
+    static LIVE: OnceLock<Mutex<HashSet<jlong>>> = OnceLock::new();
+    LIVE.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+fn registry() -> &'static Mutex<HashMap<PathBuf, Vec<Weak<SharedDb>>>> {
+    static OPEN: OnceLock<Mutex<HashMap<PathBuf, Vec<Weak<SharedDb>>>>> = OnceLock::new();
+    OPEN.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Live state for `path` opened elsewhere in this process
+fn find_open(open: &HashMap<PathBuf, Vec<Weak<SharedDb>>>, path: &str) -> Option<Arc<SharedDb>> {
+    open.get(&canonical(path))?
+        .iter()
+        .find_map(|db| db.upgrade())
+}
+
+fn canonical(path: &str) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>> {
+    mutex
+        .lock()
+        .map_err(|_| Error::InvalidState("Ndb handle lock poisoned".to_string()))
+}
@@ -0,0 +1,264 @@
+//! Database health monitoring for nostrdb-jni
+//!
+//! A [`HealthMonitor`] runs a lightweight native thread that periodically
+//! checks LMDB map utilization and the ingest backlog, and reports
+//! threshold crossings to a Java callback. Events are edge-triggered: a
+//! condition is reported once when it is entered and again only after a
+//! recovery has been reported.
+//!
+//! Map utilization comes from LMDB's own page counts, read through a
+//! transaction on nostrdb's environment (see
+//! [`lmdb::map_stats`](crate::lmdb::map_stats)), so pages freed and reused
+//! count as free. The ingest backlog is the number of events nostrdb
+//! accepted that are not stored yet; see [`Backlog`].
+
+use jni::objects::{GlobalRef, JValue};
+use jni::JavaVM;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle, ThreadId};
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+
+/// Map utilization crossed the nearly-full threshold
+pub const MAP_NEARLY_FULL: i32 = 1;
+/// Map is full; writes will fail
+pub const MAP_FULL: i32 = 2;
+/// Map utilization dropped back below the nearly-full threshold
+pub const MAP_RECOVERED: i32 = 3;
+/// The ingest backlog reached its threshold
+pub const INGEST_BACKLOG_HIGH: i32 = 4;
+/// The ingest backlog dropped back below its threshold
+pub const BACKLOG_RECOVERED: i32 = 5;
+
+/// Utilization (in percent) at which the map is considered full
+const MAP_FULL_PERCENT: u64 = 99;
+
+/// Free space below which the map is considered full whatever its size:
+/// a write copies every page it touches, so it needs room beyond its data
+pub const MAP_FULL_HEADROOM: u64 = 256 * 1024;
+
+/// How long neither count may move before the backlog is taken to be
+/// drained
+const BACKLOG_SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// One reading of the database, taken by the monitor thread
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    /// Bytes of the map in use, or None if LMDB's counts couldn't be read
+    pub map_used: Option<u64>,
+    /// Current LMDB map size
+    pub mapsize: usize,
+    /// Events nostrdb has accepted, from the handle's ingest stats
+    pub accepted: u64,
+    /// Notes stored in the database
+    pub stored: u64,
+}
+
+/// Takes a [`Sample`]; None once the database has been closed
+pub type SampleFn = Box<dyn Fn() -> Option<Sample> + Send>;
+
+/// Thresholds evaluated by the monitor
+#[derive(Debug, Clone, Copy)]
+pub struct Thresholds {
+    /// Map utilization percentage that triggers MAP_NEARLY_FULL
+    pub map_nearly_full_percent: u64,
+    /// Backlog (in events) that triggers INGEST_BACKLOG_HIGH
+    pub ingest_backlog_high: u64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            map_nearly_full_percent: 90,
+            ingest_backlog_high: 10_000,
+        }
+    }
+}
+
+/// Current map state, used to make events edge-triggered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MapLevel {
+    Ok,
+    NearlyFull,
+    Full,
+}
+
+/// Estimate of the events accepted but not yet stored
+///
+/// The backlog is the growth in accepted events less the growth in stored
+/// notes since a base reading. Events nostrdb accepts and then drops
+/// (duplicates it finds itself, bad signatures, failed writes) are never
+/// stored, so once neither count has moved for [`BACKLOG_SETTLE_TIME`] the
+/// queue is taken to be drained and the base moves up to the current
+/// reading. Resetting the ingest stats moves it too.
+struct Backlog {
+    base: (u64, u64),
+    last: (u64, u64),
+    changed: Instant,
+}
+
+impl Backlog {
+    fn new(accepted: u64, stored: u64) -> Self {
+        Self {
+            base: (accepted, stored),
+            last: (accepted, stored),
+            changed: Instant::now(),
+        }
+    }
+
+    /// Record a reading and return the backlog
+    fn update(&mut self, accepted: u64, stored: u64) -> u64 {
+        let now = (accepted, stored);
+        if now != self.last {
+            self.last = now;
+            self.changed = Instant::now();
+        }
+        if accepted < self.base.0 || self.changed.elapsed() >= BACKLOG_SETTLE_TIME {
+            self.base = now;
+        }
+        (accepted - self.base.0).saturating_sub(stored.saturating_sub(self.base.1))
+    }
+}
+
+/// Background thread reporting health events to a Java callback
+///
+/// Dropping the monitor stops the thread and waits for it to exit.
+pub struct HealthMonitor {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+    thread_id: ThreadId,
+}
+
+impl HealthMonitor {
+    /// Start monitoring a database
+    ///
+    /// # Arguments
+    /// * `vm` - The JavaVM used to attach the monitor thread
+    /// * `callback` - Global reference to a `HealthCallback` object
+    /// * `sample` - Reads the database's current state
+    /// * `thresholds` - Thresholds to evaluate
+    /// * `interval` - Time between checks
+    pub fn start(
+        vm: JavaVM,
+        callback: GlobalRef,
+        sample: SampleFn,
+        thresholds: Thresholds,
+        interval: Duration,
+    ) -> Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+
+        let thread = thread::Builder::new()
+            .name("nostrdb-health".to_string())
+            .spawn(move || {
+                let mut env = match vm.attach_current_thread_as_daemon() {
+                    Ok(env) => env,
+                    Err(e) => {
+                        tracing::error!("Health monitor failed to attach to JVM: {}", e);
+                        return;
+                    }
+                };
+
+                let mut level = MapLevel::Ok;
+                let mut backlog_high = false;
+                let mut backlog = None;
+                // Any other result means stop was requested or the monitor was dropped
+                while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                    let Some(now) = sample() else {
+                        continue;
+                    };
+                    let mut events = Vec::new();
+
+                    if let Some(used) = now.map_used {
+                        let next = map_level(used, now.mapsize, &thresholds);
+                        if let Some(code) = transition(level, next) {
+                            level = next;
+                            events.push((code, used));
+                        }
+                    }
+
+                    let pending = backlog
+                        .get_or_insert_with(|| Backlog::new(now.accepted, now.stored))
+                        .update(now.accepted, now.stored);
+                    let high = pending >= thresholds.ingest_backlog_high;
+                    if high != backlog_high {
+                        backlog_high = high;
+                        let code = if high {
+                            INGEST_BACKLOG_HIGH
+                        } else {
+                            BACKLOG_RECOVERED
+                        };
+                        events.push((code, pending));
+                    }
+
+                    for (code, value) in events {
+                        let args = [JValue::Int(code), JValue::Long(value as i64)];
+                        if env
+                            .call_method(&callback, "onHealthEvent", "(IJ)V", &args)
+                            .is_err()
+                        {
+                            // Never let a callback exception escape into native code
+                            if env.exception_check().unwrap_or(false) {
+                                let _ = env.exception_describe();
+                                let _ = env.exception_clear();
+                            }
+                            tracing::error!("Health callback threw for event {}", code);
+                        }
+                    }
+                }
+            })
+            .map_err(Error::Io)?;
+
+        Ok(Self {
+            stop: Some(stop_tx),
+            thread_id: thread.thread().id(),
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for HealthMonitor {
+    fn drop(&mut self) {
+        // Closing the channel wakes the thread up immediately
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            // A callback that closes the database runs on the monitor thread
+            // itself; joining there would deadlock.
+            if thread::current().id() != self.thread_id {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+/// Classify map utilization against the thresholds
+fn map_level(used: u64, mapsize: usize, thresholds: &Thresholds) -> MapLevel {
+    let percent = used.saturating_mul(100) / (mapsize as u64).max(1);
+    if map_full(used, mapsize) {
+        MapLevel::Full
+    } else if percent >= thresholds.map_nearly_full_percent {
+        MapLevel::NearlyFull
+    } else {
+        MapLevel::Ok
+    }
+}
+
+/// Whether a map of `mapsize` bytes with `used` bytes in use is full
+pub fn map_full(used: u64, mapsize: usize) -> bool {
+    let mapsize = mapsize as u64;
+    used.saturating_mul(100) >= mapsize.saturating_mul(MAP_FULL_PERCENT)
+        || mapsize.saturating_sub(used) < MAP_FULL_HEADROOM
+}
+
+/// Event to fire when moving between levels, if any
+fn transition(from: MapLevel, to: MapLevel) -> Option<i32> {
+    match (from, to) {
+        (a, b) if a == b => None,
+        (_, MapLevel::Full) => Some(MAP_FULL),
+        (MapLevel::Ok, MapLevel::NearlyFull) => Some(MAP_NEARLY_FULL),
+        // Dropping from full to nearly full is not a recovery
+        (MapLevel::Full, MapLevel::NearlyFull) => None,
+        (_, MapLevel::Ok) => Some(MAP_RECOVERED),
+        _ => None,
+    }
+}
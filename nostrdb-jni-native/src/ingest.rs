@@ -0,0 +1,599 @@
+//! Checked event ingestion for nostrdb-jni
+//!
+//! nostrdb ingests events on its writer threads, so `process_event` only
+//! queues an event. `processEventReturningKey` checks the event first,
+//! then polls for its id until the note is visible or a bounded wait runs
+//! out, so Java gets the note key back instead of looking it up again.
+//! Outcomes without a key are negative sentinels.
+//!
+//! `waitForIngestion` and `waitForIngestionCount` poll the same way after
+//! `processEvent(s)`, for callers that read right after writing.
+//!
+//! `processEvent` only queues the event, as `processEvents` does for each
+//! line; nostrdb's writer drops duplicates and events that don't verify
+//! on its own. `processEventWithResult` checks the event first, so that
+//! an event that is already stored is reported as a duplicate rather than
+//! queued again, and one without an id, or one that doesn't verify on a
+//! database that validates, as rejected, as `processEventsDetailed` does
+//! for each line.
+//!
+//! nostrdb verifies ids and signatures for a whole database or not at all,
+//! so the per-call validation of `processEventWithValidation` and
+//! `processEventsWithValidation` is done here: an event to validate is
+//! verified before it is queued, and skipping validation is only allowed
+//! on a database opened with validation skipped, since nostrdb's writer
+//! would still drop an event that doesn't verify.
+//!
+//! `processEventsDetailed` runs the full check on every line of a batch,
+//! so lines nostrdb's writer would drop are reported by line number, and
+//! counts duplicates, including repeats within the batch, separately.
+//! `processEventsArray` does the same for the elements of a JSON array,
+//! which is read element by element rather than parsed whole.
+//!
+//! `processEvents`, `processEventsBytes`, and `processEventsDirect` share
+//! the batch loop; the byte variants split raw UTF-8 on newlines, so large
+//! imports read from disk never become Java strings, and a direct buffer
+//! such as a memory-mapped dump is read in place.
+//!
+//! `processEventsFromFile` reads a file natively, a line at a time, so an
+//! export larger than the Java heap can be imported; it reports progress
+//! every [`PROGRESS_INTERVAL`] lines and stops once its cancellation token
+//! is set, reporting the offset just past the last line it read so that a
+//! later import can resume there. The cancellable `processEventsBytes`
+//! imports a byte array the same way. A file starting with the gzip magic bytes is decompressed as
+//! it is read, as is the batch given to `processEventsGzip`, so a gzipped
+//! relay dump is never expanded on disk; a truncated or corrupt stream
+//! fails with the number of events ingested before the damage.
+//!
+//! `processClientEvent` checks the `["EVENT", {...}]` envelope of a client
+//! message before handing it to nostrdb's client ingestion, so a malformed
+//! message is reported with what is wrong rather than dropped.
+//!
+//! Each entry point records the outcome of every event in the handle's
+//! [`IngestStats`](crate::stats::IngestStats); [`queue`] hands an event to
+//! nostrdb and records whether it was accepted.
+//!
+//! `processRelayEvent(s)` read only the type of a relay message, so
+//! `EVENT` messages go to nostrdb's relay ingestion whole (the
+//! subscription id is ignored) and other messages, such as `EOSE`,
+//! `NOTICE`, and `OK`, are skipped without parsing the rest.
+
+use flate2::bufread::MultiGzDecoder;
+use jni::sys::{jint, jlong};
+use nostrdb::{Ndb, NoteKey, Transaction};
+use serde::de::{self, Deserializer as _, SeqAccess, Visitor};
+use serde_json::value::RawValue;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cancel::CancelToken;
+use crate::error::{Error, Result};
+use crate::handle::NdbHandle;
+use crate::stats::IngestStats;
+use crate::verify::{self, Failure};
+
+/// `processEvent`: the event is malformed, or nostrdb refused it
+pub const PROCESS_REJECTED: jint = 0;
+/// `processEvent`: the event was queued to be stored
+pub const PROCESS_STORED: jint = 1;
+/// `processEvent`: a note with the event's id is already stored
+pub const PROCESS_DUPLICATE: jint = 2;
+
+/// The event is malformed, or its id or signature doesn't verify
+pub const INGEST_INVALID: jlong = -1;
+/// A note with the event's id is already stored
+pub const INGEST_DUPLICATE: jlong = -2;
+/// The note wasn't visible before the wait ran out
+pub const INGEST_TIMEOUT: jlong = -3;
+/// nostrdb refused to queue the event
+pub const INGEST_REJECTED: jlong = -4;
+
+/// Delay between lookups while waiting for a note
+pub const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Lines read between two progress reports of a file import; matches
+/// `ImportProgressCallback.INTERVAL`
+pub const PROGRESS_INTERVAL: u64 = 1_000;
+
+/// The first bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// What happened to the lines of an imported file
+#[derive(Default)]
+pub struct ImportCounts {
+    /// Lines read, including blank lines
+    pub lines: u64,
+    /// Events nostrdb accepted
+    pub processed: u64,
+    /// Lines that aren't valid UTF-8, or whose event nostrdb refused
+    pub failed: u64,
+    /// The import stopped before the end of the file
+    pub cancelled: bool,
+    /// Offset just past the last line read, from the start of the input
+    /// (of the decompressed stream for gzip)
+    pub offset: u64,
+}
+
+/// Queue an event unless a note with its id is already stored, returning
+/// one of the `PROCESS_*` outcomes
+///
+/// The event is verified here unless the database skips validation, so
+/// that `PROCESS_STORED` is never returned for an event nostrdb's writer
+/// would drop; only on a database that skips validation is the id read
+/// unchecked. The event is parsed once, and its id looked up in a single
+/// read transaction on the instance it is queued to. An event that is
+/// queued but not yet written isn't detected as a duplicate.
+pub fn process_new(handle: &NdbHandle, json: &str) -> Result<jint> {
+    let stats = handle.ingest_stats();
+    let validate = !handle.skips_validation()?;
+    let ndb = handle.writable_ndb()?;
+    let Ok(id) = checked_id(json, validate) else {
+        stats.rejected();
+        return Ok(PROCESS_REJECTED);
+    };
+    let stored = match ndb.get_notekey_by_id(&Transaction::new(&ndb)?, &id) {
+        Ok(_) => true,
+        Err(nostrdb::Error::NotFound) => false,
+        Err(e) => return Err(e.into()),
+    };
+    if stored {
+        stats.duplicate();
+        return Ok(PROCESS_DUPLICATE);
+    }
+    let result = ndb.process_event(json);
+    stats.record(&result, json.len());
+    Ok(match result {
+        Ok(()) => PROCESS_STORED,
+        Err(_) => PROCESS_REJECTED,
+    })
+}
+
+/// Hand an event to nostrdb, recording whether it was accepted in the
+/// handle's statistics
+pub fn queue(handle: &NdbHandle, json: &str) -> Result<Result<()>> {
+    // Fetched per event so that auto-resize can grow the map mid-batch
+    let result = handle.writable_ndb()?.process_event(json);
+    handle.ingest_stats().record(&result, json.len());
+    Ok(result)
+}
+
+/// A batch whose events are checked before they are queued, reported as
+/// by `processEventsDetailed`
+pub struct CheckedBatch<'a> {
+    handle: &'a NdbHandle,
+    validate: bool,
+    /// Events queued earlier in the batch may not be written yet
+    seen: HashSet<[u8; 32]>,
+    succeeded: u32,
+    duplicates: u32,
+    blank: u32,
+    /// Number of the last event or line added
+    read: u32,
+    /// Number and error of each event that failed
+    failures: Vec<(u32, String)>,
+}
+
+impl<'a> CheckedBatch<'a> {
+    pub fn new(handle: &'a NdbHandle) -> Result<Self> {
+        Ok(Self {
+            handle,
+            validate: !handle.skips_validation()?,
+            seen: HashSet::new(),
+            succeeded: 0,
+            duplicates: 0,
+            blank: 0,
+            read: 0,
+            failures: Vec::new(),
+        })
+    }
+
+    /// Check the event numbered `number` and queue it unless it is already
+    /// stored, returning false if it failed
+    pub fn add(&mut self, number: u32, json: &str) -> Result<bool> {
+        self.read = number;
+        if json.trim().is_empty() {
+            self.blank += 1;
+            return Ok(true);
+        }
+        let stats = self.handle.ingest_stats();
+        let result = match checked_id(json, self.validate) {
+            Ok(id) if !self.seen.insert(id) || note_key(self.handle, &id)?.is_some() => {
+                stats.duplicate();
+                self.duplicates += 1;
+                return Ok(true);
+            }
+            Ok(_) => queue(self.handle, json)?.map_err(|e| e.to_string()),
+            Err(failure) => {
+                stats.rejected();
+                Err(failure.message().to_string())
+            }
+        };
+        Ok(match result {
+            Ok(()) => {
+                self.succeeded += 1;
+                true
+            }
+            Err(error) => {
+                self.failures.push((number, error));
+                false
+            }
+        })
+    }
+
+    /// The totals as [succeeded, duplicates, failed, blank, read]
+    pub fn totals(&self) -> [u32; 5] {
+        let failed = self.failures.len() as u32;
+        [
+            self.succeeded,
+            self.duplicates,
+            failed,
+            self.blank,
+            self.read,
+        ]
+    }
+
+    /// The number and error of each event that failed, in order
+    pub fn failures(&self) -> &[(u32, String)] {
+        &self.failures
+    }
+}
+
+/// Call `f` with the JSON of each element of the top-level array `json`,
+/// in order, reading one element at a time
+///
+/// Input that isn't an array fails with [`Error::Json`] saying so; an
+/// error from `f` stops the loop and is returned as is.
+pub fn for_each_array_element<'a>(
+    json: &'a str,
+    mut f: impl FnMut(&'a str) -> Result<()>,
+) -> Result<()> {
+    let mut stopped = None;
+    let mut de = serde_json::Deserializer::from_str(json);
+    let result = (&mut de)
+        .deserialize_seq(ArrayElements {
+            f: &mut f,
+            stopped: &mut stopped,
+        })
+        .and_then(|()| de.end());
+    match stopped {
+        Some(e) => Err(e),
+        None => Ok(result?),
+    }
+}
+
+/// Reads the elements of an array as raw JSON, without parsing them
+struct ArrayElements<'f, F> {
+    f: &'f mut F,
+    /// The error `f` returned, kept since serde can only carry a message
+    stopped: &'f mut Option<Error>,
+}
+
+impl<'de, F: FnMut(&'de str) -> Result<()>> Visitor<'de> for ArrayElements<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON array of events")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while let Some(element) = seq.next_element::<&'de RawValue>()? {
+            if let Err(e) = (self.f)(element.get()) {
+                let message = e.to_string();
+                *self.stopped = Some(e);
+                return Err(de::Error::custom(message));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Process each non-blank line as an event, returning how many nostrdb
+/// accepted
+///
+/// With `verify`, a line whose event doesn't verify is rejected here
+/// rather than queued.
+pub fn process_lines<'a>(
+    handle: &NdbHandle,
+    lines: impl Iterator<Item = &'a str>,
+    verify: bool,
+) -> Result<jint> {
+    let mut count = 0;
+    for line in lines.filter(|line| !line.trim().is_empty()) {
+        if verify && checked_id(line, true).is_err() {
+            handle.ingest_stats().rejected();
+        } else if queue(handle, line)?.is_ok() {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Check that a call may skip validation
+///
+/// nostrdb has no per-call switch: unless the database was opened with
+/// validation skipped, its writer drops every event that doesn't verify.
+pub fn check_skip_validation(handle: &NdbHandle) -> Result<()> {
+    if !handle.skips_validation()? {
+        return Err(Error::InvalidState(
+            "Skipping validation per call requires a database opened with validation skipped"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The lines of newline-delimited UTF-8, like `str::lines`, skipping
+/// lines that aren't valid UTF-8 as nostrdb would skip unparseable events
+/// and recording them as rejected in `stats`
+pub fn byte_lines<'a>(bytes: &'a [u8], stats: &'a IngestStats) -> impl Iterator<Item = &'a str> {
+    bytes
+        .split(|&b| b == b'\n')
+        .map(without_line_ending)
+        .filter_map(|line| match std::str::from_utf8(line) {
+            Ok(line) => Some(line),
+            Err(_) => {
+                stats.rejected();
+                None
+            }
+        })
+}
+
+/// Process each non-blank line read from `reader` as an event, without
+/// holding more than one line in memory
+///
+/// `offset` is the position in the input `reader` starts at, so that the
+/// offset reported is from the start of the input. `progress` is called
+/// with the lines read and failed so far every [`PROGRESS_INTERVAL`] lines
+/// and once at the end; an error it returns stops the import. `token` is
+/// checked before every line.
+pub fn process_reader(
+    handle: &NdbHandle,
+    reader: impl BufRead,
+    offset: u64,
+    token: Option<&CancelToken>,
+    progress: impl FnMut(&ImportCounts) -> Result<()>,
+) -> Result<ImportCounts> {
+    let mut counts = ImportCounts {
+        offset,
+        ..ImportCounts::default()
+    };
+    read_lines(handle, reader, token, progress, &mut counts)?;
+    Ok(counts)
+}
+
+/// Like [`process_reader`], decompressing `reader` as gzip, including
+/// several concatenated members, as it is read
+///
+/// A compressed stream can't be sought, so the first `offset` bytes of the
+/// decompressed stream are read and skipped. A truncated or corrupt stream
+/// fails with [`Error::Gzip`], which counts the events processed before
+/// it; those stay ingested.
+pub fn process_gzip(
+    handle: &NdbHandle,
+    reader: impl BufRead,
+    offset: u64,
+    token: Option<&CancelToken>,
+    progress: impl FnMut(&ImportCounts) -> Result<()>,
+) -> Result<ImportCounts> {
+    let mut counts = ImportCounts::default();
+    let mut reader = BufReader::new(MultiGzDecoder::new(reader));
+    let result = io::copy(&mut (&mut reader).take(offset), &mut io::sink())
+        .map_err(Error::Io)
+        .and_then(|skipped| {
+            counts.offset = skipped;
+            read_lines(handle, reader, token, progress, &mut counts)
+        });
+    match result {
+        Ok(()) => Ok(counts),
+        Err(Error::Io(e)) => Err(Error::Gzip {
+            processed: counts.processed,
+            reason: e.to_string(),
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+/// Whether `reader` starts with the gzip magic bytes, without consuming them
+pub fn is_gzip(reader: &mut impl BufRead) -> Result<bool> {
+    Ok(reader.fill_buf()?.starts_with(&GZIP_MAGIC))
+}
+
+/// The line loop of [`process_reader`], leaving the counts so far in
+/// `counts` when it fails
+fn read_lines(
+    handle: &NdbHandle,
+    mut reader: impl BufRead,
+    token: Option<&CancelToken>,
+    mut progress: impl FnMut(&ImportCounts) -> Result<()>,
+    counts: &mut ImportCounts,
+) -> Result<()> {
+    let mut line = Vec::new();
+    loop {
+        if token.is_some_and(CancelToken::is_cancelled) {
+            counts.cancelled = true;
+            break;
+        }
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 {
+            break;
+        }
+        counts.lines += 1;
+        counts.offset += read as u64;
+
+        match std::str::from_utf8(without_line_ending(&line)) {
+            Ok(json) if json.trim().is_empty() => {}
+            Ok(json) => match queue(handle, json)? {
+                Ok(()) => counts.processed += 1,
+                Err(_) => counts.failed += 1,
+            },
+            Err(_) => {
+                handle.ingest_stats().rejected();
+                counts.failed += 1;
+            }
+        }
+
+        if counts.lines.is_multiple_of(PROGRESS_INTERVAL) {
+            progress(counts)?;
+        }
+    }
+    progress(counts)
+}
+
+/// A line without its trailing `\n` or `\r\n`
+fn without_line_ending(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Check an event JSON as nostrdb's writer would, returning its id
+///
+/// The event may be wrapped in a relay or client `EVENT` message. Its id
+/// and signature are verified unless `validate` is false, when only the
+/// id is read. The JSON is parsed once for both.
+pub fn checked_id(json: &str, validate: bool) -> std::result::Result<[u8; 32], Failure> {
+    let event = verify::parse_wrapped(json)?;
+    if validate {
+        verify::verify_event(&event)?;
+    }
+    verify::event_claimed_id(&event)
+}
+
+/// Check that a client message is an `["EVENT", {...}]` envelope
+///
+/// With `accept_bare`, a bare event object is wrapped in one instead of
+/// being refused.
+pub fn client_message(json: &str, accept_bare: bool) -> Result<Cow<'_, str>> {
+    let message = match serde_json::from_str(json) {
+        Ok(Value::Array(message)) => message,
+        Ok(Value::Object(_)) if accept_bare => {
+            return Ok(Cow::Owned(format!("[\"EVENT\",{json}]")))
+        }
+        Ok(Value::Object(_)) => {
+            return invalid("expected [\"EVENT\", {...}], got a bare event object")
+        }
+        Ok(_) => return invalid("expected a JSON array"),
+        Err(e) => return invalid(format!("not valid JSON: {e}")),
+    };
+    match message.first() {
+        Some(Value::String(label)) if label == "EVENT" => {}
+        Some(Value::String(label)) => {
+            return invalid(format!("expected an EVENT message, got {label}"))
+        }
+        Some(_) => return invalid("the first element must be a string"),
+        None => return invalid("the message is empty"),
+    }
+    if message.len() != 2 {
+        return invalid(format!(
+            "an EVENT message has 2 elements, got {}",
+            message.len()
+        ));
+    }
+    if !message[1].is_object() {
+        return invalid("the event must be a JSON object");
+    }
+    Ok(Cow::Borrowed(json))
+}
+
+/// The type of a relay message `["TYPE", ...]`
+pub fn relay_message_type(json: &str) -> Result<&str> {
+    // Message types are plain ASCII words, so the first quote ends the type
+    json.trim_start()
+        .strip_prefix('[')
+        .map(str::trim_start)
+        .and_then(|rest| rest.strip_prefix('"'))
+        .and_then(|rest| rest.split_once('"'))
+        .map(|(label, _)| label)
+        .ok_or_else(|| {
+            Error::RelayMessage("expected a JSON array starting with the message type".to_string())
+        })
+}
+
+fn invalid<T>(reason: impl Into<String>) -> Result<T> {
+    Err(Error::ClientMessage(reason.into()))
+}
+
+/// The key of the stored note with an id, if there is one
+pub fn note_key(handle: &NdbHandle, id: &[u8; 32]) -> Result<Option<u64>> {
+    let txn = handle.begin_transaction()?;
+    match handle.ndb()?.get_notekey_by_id(&txn, id) {
+        Ok(key) => Ok(Some(key.as_u64())),
+        Err(nostrdb::Error::NotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Wait up to `timeout` for a note to be stored, returning its key
+pub fn await_note_key(handle: &NdbHandle, id: &[u8; 32], timeout: Duration) -> Result<Option<u64>> {
+    poll(timeout, || note_key(handle, id))
+}
+
+/// Wait up to `timeout` for the database to hold at least `count` notes,
+/// returning whether it does
+///
+/// nostrdb numbers notes from 1 in the order its writer stores them, so
+/// this waits for the note with key `count` rather than counting.
+pub fn await_note_count(handle: &NdbHandle, count: u64, timeout: Duration) -> Result<bool> {
+    if count == 0 {
+        return Ok(true);
+    }
+    let stored = poll(timeout, || {
+        let txn = handle.begin_transaction()?;
+        match handle.ndb()?.get_note_by_key(&txn, NoteKey::new(count)) {
+            Ok(_) => Ok(Some(())),
+            Err(nostrdb::Error::NotFound) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    })?;
+    Ok(stored.is_some())
+}
+
+/// Number of notes stored in the database
+///
+/// nostrdb numbers notes from 1 without gaps as it stores them, so this
+/// searches for the highest key that has a note.
+pub fn stored_note_count(ndb: &Ndb) -> Result<u64> {
+    let txn = Transaction::new(ndb)?;
+    let exists = |key: u64| -> Result<bool> {
+        match ndb.get_note_by_key(&txn, NoteKey::new(key)) {
+            Ok(_) => Ok(true),
+            Err(nostrdb::Error::NotFound) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    };
+
+    // Double past the last key, then bisect between the last two probes
+    let mut high = 1;
+    while exists(high)? {
+        high *= 2;
+    }
+    let mut low = high / 2;
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        if exists(mid)? {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+/// Call `f` until it returns a value or `timeout` has passed
+fn poll<T>(timeout: Duration, mut f: impl FnMut() -> Result<Option<T>>) -> Result<Option<T>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(value) = f()? {
+            return Ok(Some(value));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,178 @@
+//! Note JSON serialization for nostrdb-jni
+//!
+//! Notes are serialized straight from nostrdb's in-memory representation
+//! into a caller-supplied buffer, so serializing many notes (as
+//! `queryNotes` does) grows a single allocation instead of building a JSON
+//! value per note.
+//!
+//! The same structure is also written as CBOR, for callers that decode
+//! CBOR anyway: the keys are the same, but id, pubkey, and sig are byte
+//! strings instead of hex.
+//!
+//! nostrdb doesn't check that content is valid UTF-8, and a few events in
+//! the wild aren't. By default invalid sequences are replaced with U+FFFD;
+//! [`Utf8Mode::Strict`] fails with the note's key instead.
+
+use std::borrow::Cow;
+
+use nostrdb::{NdbStrVariant, Note, Tag, Tags};
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::{Serialize, Serializer};
+
+use crate::error::{Error, Result};
+
+/// How note content that isn't valid UTF-8 is handled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Utf8Mode {
+    /// Replace invalid sequences with U+FFFD
+    #[default]
+    Lossy,
+    /// Fail with [`Error::InvalidContent`]
+    Strict,
+}
+
+/// A note's content as valid UTF-8
+///
+/// Borrows the stored content unless it has to be repaired.
+pub fn note_content<'a>(note: &'a Note, utf8: Utf8Mode) -> Result<Cow<'a, str>> {
+    let bytes = content_bytes(note);
+    match std::str::from_utf8(bytes) {
+        Ok(content) => Ok(Cow::Borrowed(content)),
+        Err(_) if utf8 == Utf8Mode::Lossy => Ok(String::from_utf8_lossy(bytes)),
+        Err(_) => Err(Error::InvalidContent {
+            key: note.key().map_or(0, |key| key.as_u64()),
+        }),
+    }
+}
+
+/// A note's content as stored, which may not be valid UTF-8
+///
+/// nostrdb's `Note::content` makes a str without checking it, which is
+/// undefined behavior for invalid UTF-8, so the bytes are read raw.
+pub fn content_bytes<'a>(note: &'a Note) -> &'a [u8] {
+    let len = note.content_len() as usize;
+    if len == 0 {
+        return &[];
+    }
+    // SAFETY: nostrdb's pointer and length describe the note's content,
+    // which lives as long as the note
+    unsafe { std::slice::from_raw_parts(note.content_ptr().cast::<u8>(), len) }
+}
+
+/// Serialize a Note to JSON bytes
+pub fn serialize_note(note: &Note, utf8: Utf8Mode) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    write_note(note, utf8, &mut buf)?;
+    Ok(buf)
+}
+
+/// Append a Note's JSON to `buf`
+pub fn write_note(note: &Note, utf8: Utf8Mode, buf: &mut Vec<u8>) -> Result<()> {
+    let note = NoteJson {
+        note,
+        content: note_content(note, utf8)?,
+        binary: false,
+    };
+    Ok(serde_json::to_writer(buf, &note)?)
+}
+
+/// Append a Note's CBOR to `buf`
+pub fn write_note_cbor(note: &Note, utf8: Utf8Mode, buf: &mut Vec<u8>) -> Result<()> {
+    let note = NoteJson {
+        note,
+        content: note_content(note, utf8)?,
+        binary: true,
+    };
+    Ok(ciborium::into_writer(&note, buf)?)
+}
+
+/// A note in NIP-01 form, keys in alphabetical order
+struct NoteJson<'a, 'n> {
+    note: &'a Note<'n>,
+    content: Cow<'n, str>,
+    /// Write id, pubkey, and sig as bytes rather than hex strings
+    binary: bool,
+}
+
+impl Serialize for NoteJson<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let note = self.note;
+        let mut hex_buf = [0u8; 128];
+        let mut map = serializer.serialize_map(Some(7))?;
+        map.serialize_entry("content", &self.content)?;
+        map.serialize_entry("created_at", &note.created_at())?;
+        self.serialize_bytes_entry(&mut map, "id", note.id(), &mut hex_buf)?;
+        map.serialize_entry("kind", &note.kind())?;
+        self.serialize_bytes_entry(&mut map, "pubkey", note.pubkey(), &mut hex_buf)?;
+        self.serialize_bytes_entry(&mut map, "sig", note.sig(), &mut hex_buf)?;
+        map.serialize_entry("tags", &TagsJson(note.tags()))?;
+        map.end()
+    }
+}
+
+impl NoteJson<'_, '_> {
+    fn serialize_bytes_entry<M: SerializeMap>(
+        &self,
+        map: &mut M,
+        key: &str,
+        bytes: &[u8],
+        hex_buf: &mut [u8],
+    ) -> std::result::Result<(), M::Error> {
+        if self.binary {
+            map.serialize_entry(key, &Bytes(bytes))
+        } else {
+            map.serialize_entry(key, hex_str::<M::Error>(bytes, hex_buf)?)
+        }
+    }
+}
+
+/// Bytes serialized as a byte string rather than a sequence of numbers
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Tags as an array of string arrays
+struct TagsJson<'n>(Tags<'n>);
+
+impl Serialize for TagsJson<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(TagJson))
+    }
+}
+
+/// A tag's elements as strings; ids nostrdb packs into 32 bytes (as in
+/// `e` and `p` tags) are written back as the hex they were parsed from
+struct TagJson<'n>(Tag<'n>);
+
+impl Serialize for TagJson<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut hex_buf = [0u8; 64];
+        let mut seq = serializer.serialize_seq(Some(self.0.count() as usize))?;
+        for i in 0..self.0.count() {
+            let Some(elem) = self.0.get(i) else {
+                continue;
+            };
+            match elem.variant() {
+                NdbStrVariant::Str(value) => seq.serialize_element(value)?,
+                NdbStrVariant::Id(id) => {
+                    seq.serialize_element(hex_str::<S::Error>(id, &mut hex_buf)?)?
+                }
+            }
+        }
+        seq.end()
+    }
+}
+
+/// Hex-encode `bytes` into the front of `buf`
+fn hex_str<'b, E: serde::ser::Error>(
+    bytes: &[u8],
+    buf: &'b mut [u8],
+) -> std::result::Result<&'b str, E> {
+    let buf = &mut buf[..bytes.len() * 2];
+    hex::encode_to_slice(bytes, buf).map_err(E::custom)?;
+    std::str::from_utf8(buf).map_err(E::custom)
+}
@@ -4,21 +4,93 @@
 //! library, enabling Java applications to leverage the high-performance
 //! embedded Nostr event database.
 
-use jni::objects::{JByteArray, JClass, JObjectArray, JString};
-use jni::sys::{jbyteArray, jint, jlong, jobjectArray};
+use jni::objects::{
+    JByteArray, JByteBuffer, JClass, JIntArray, JLongArray, JObject, JObjectArray, JString, JValue,
+};
+use jni::sys::{
+    jboolean, jbyteArray, jint, jintArray, jlong, jlongArray, jobjectArray, jstring, JNI_FALSE,
+    JNI_TRUE,
+};
 use jni::JNIEnv;
-use nostrdb::{Config, Filter, Ndb, NoteKey, Transaction};
-use std::sync::Arc;
+use nostrdb::{Filter, FilterBuilder, NoteKey};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::io::{Seek, SeekFrom};
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
 
+mod blocks;
+mod cancel;
+mod completion;
+mod config;
+mod contacts;
+mod cursor;
+mod draft;
 mod error;
+mod expiry;
+mod filter;
+mod handle;
+mod health;
+mod ingest;
+mod json;
+mod lmdb;
+mod mute;
+mod note;
+mod page;
+mod relay_list;
+mod repost;
+mod search;
+mod stats;
+mod thread;
 mod util;
+mod verify;
+mod zap;
 
+use cancel::CancelToken;
+use config::NdbConfig;
+use cursor::QueryCursor;
+use draft::NoteDraft;
 use error::{Error, Result};
+use filter::{BuilderHandle, FilterHandle};
+use handle::{NdbHandle, TxnHandle};
+use json::{note_content, serialize_note, write_note, write_note_cbor, Utf8Mode};
+use mute::{MuteEntries, MuteList};
+use note::NoteHandle;
+use page::Position;
 use util::{
-    box_to_ptr, catch_panic, catch_panic_void, drop_ptr, java_bytes_to_32, java_bytes_to_rust,
-    java_string_to_rust, rust_bytes_to_java, with_exception,
+    box_to_ptr, catch_panic, catch_panic_void, java_bytes_to_32, java_bytes_to_32_vec,
+    java_bytes_to_rust, java_string_to_rust, rust_bytes_to_java, rust_longs_to_java,
+    rust_strings_to_java, with_exception,
 };
 
+/// Version of the data exchanged with the Java wrapper
+///
+/// Bump this whenever a serialized format changes (e.g. the
+/// `[count:4][key:8]...` result layout or the note/profile JSON shape), so
+/// that a mismatched JAR and native library fail at load time. Raw notes
+/// (`getNoteRawByKey`, `queryNotesRaw`) are nostrdb's own `struct ndb_note`
+/// layout, and raw profiles (`getProfileRawByPubkey`) are flatbuffers of
+/// nostrdb's `NdbProfileRecord` schema, so an upgrade of nostrdb that
+/// changes either must bump this too.
+///
+/// Version 2 added raw notes; version 3 added note sizes to the
+/// `getNotesByKeys`/`getNotesByIds` frames; version 4 added the record
+/// fields (`received_at`, `note_key`, ...) to the profile JSON; version 5
+/// added raw profiles; version 6 added duplicates to `processEvent`
+/// outcomes and the `processEventsDetailed` report; version 7 added the
+/// resume offset to the `processEventsFromFile` counts.
+const ABI_VERSION: jint = 7;
+
+/// `queryOrdered`: the newest `limit` matches, newest first (nostrdb's order)
+const ORDER_NEWEST_FIRST: jint = 0;
+/// `queryOrdered`: the newest `limit` matches, oldest first
+const ORDER_NEWEST_REVERSED: jint = 1;
+/// `queryOrdered`: the oldest `limit` matches, oldest first
+const ORDER_OLDEST_FIRST: jint = 2;
+
+/// `getProfileField`: number of field ids, 0 (name) through 8 (website)
+const PROFILE_FIELD_COUNT: jint = 9;
+
 // ============================================================================
 // Ndb Lifecycle
 // ============================================================================
@@ -28,25 +100,66 @@ use util::{
 /// # Arguments
 /// * `db_path` - Path to the database directory
 /// * `config_ptr` - Pointer to Config, or 0 for defaults
+/// * `create_if_missing` - Create the directory (and parents) if it does not exist
 ///
 /// # Returns
-/// Pointer to Arc<Ndb> as jlong, or 0 on error
+/// Pointer to NdbHandle as jlong, or 0 on error. Failures throw an
+/// IOException whose message names the cause (missing directory or
+/// parent, not a directory, permission denied, already open).
 #[no_mangle]
 pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_ndbOpen(
     mut env: JNIEnv,
     _class: JClass,
     db_path: JString,
-    _config_ptr: jlong, // Reserved for future use
+    config_ptr: jlong,
+    create_if_missing: jboolean,
+) -> jlong {
+    with_exception(&mut env, 0, |env| {
+        let path = java_string_to_rust(env, &db_path)?;
+        let create_if_missing = create_if_missing != JNI_FALSE;
+        let handle = if config_ptr == 0 {
+            NdbHandle::open(&path, &NdbConfig::new(), create_if_missing)?
+        } else {
+            let config = unsafe { util::ptr_to_ref::<NdbConfig>(config_ptr, "config")? };
+            NdbHandle::open(&path, config, create_if_missing)?
+        };
+        handle.into_ptr()
+    })
+}
+
+/// Open an existing database, rejecting writes through the handle
+///
+/// Write operations on the returned handle throw IllegalStateException.
+/// The guard is the binding's: LMDB still opens the environment read-write.
+///
+/// # Arguments
+/// * `db_path` - Path to an existing database directory
+/// * `config_ptr` - Pointer to Config, or 0 for defaults
+///
+/// # Returns
+/// Pointer to NdbHandle as jlong, or 0 on error
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_ndbOpenWriteProtected(
+    mut env: JNIEnv,
+    _class: JClass,
+    db_path: JString,
+    config_ptr: jlong,
 ) -> jlong {
     with_exception(&mut env, 0, |env| {
         let path = java_string_to_rust(env, &db_path)?;
-        let config = Config::new();
-        let ndb = Ndb::new(&path, &config)?;
-        Ok(box_to_ptr(Arc::new(ndb)))
+        let handle = if config_ptr == 0 {
+            NdbHandle::open_write_protected(&path, &NdbConfig::new())?
+        } else {
+            let config = unsafe { util::ptr_to_ref::<NdbConfig>(config_ptr, "config")? };
+            NdbHandle::open_write_protected(&path, config)?
+        };
+        handle.into_ptr()
     })
 }
 
 /// Destroy Ndb instance
+///
+/// Closing an already closed handle does nothing.
 #[no_mangle]
 pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_ndbClose(
     _env: JNIEnv,
@@ -54,439 +167,5433 @@ pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_ndbClose(
     ndb_ptr: jlong,
 ) {
     catch_panic_void(|| unsafe {
-        drop_ptr::<Arc<Ndb>>(ndb_ptr);
+        NdbHandle::close(ndb_ptr);
     });
 }
 
-// ============================================================================
-// Event Ingestion
-// ============================================================================
-
-/// Process a single JSON event
+/// Check whether a handle is open
 ///
-/// # Arguments
-/// * `ndb_ptr` - Pointer to the Ndb instance
-/// * `json` - JSON string of the event
+/// Returns false (rather than crashing) for handles that were closed.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_ndbIsOpen(
+    _env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+) -> jboolean {
+    catch_panic(JNI_FALSE, || {
+        if NdbHandle::is_open(ndb_ptr) {
+            JNI_TRUE
+        } else {
+            JNI_FALSE
+        }
+    })
+}
+
+/// Get the canonical database directory of an open handle
 ///
 /// # Returns
-/// 1 on success, 0 on failure
+/// The directory path, or null (with IllegalStateException) if closed
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEvent(
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_ndbGetPath(
     mut env: JNIEnv,
     _class: JClass,
     ndb_ptr: jlong,
-    json: JString,
-) -> jint {
-    with_exception(&mut env, 0, |env| {
-        let ndb = unsafe { util::ptr_to_ref::<Arc<Ndb>>(ndb_ptr, "ndb")? };
-        let json_str = java_string_to_rust(env, &json)?;
-        ndb.process_event(&json_str)?;
-        Ok(1)
+) -> jstring {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        if !NdbHandle::is_open(ndb_ptr) {
+            return Err(Error::InvalidState("Ndb is closed".to_string()));
+        }
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let path = handle.path().to_string_lossy();
+        Ok(env.new_string(path.as_ref())?.into_raw())
     })
 }
 
-/// Process batch of newline-delimited JSON events
+/// Get the settings an open handle's database is open with
 ///
 /// # Arguments
 /// * `ndb_ptr` - Pointer to the Ndb instance
-/// * `ldjson` - Newline-delimited JSON events
 ///
 /// # Returns
-/// Number of events processed, or -1 on error
+/// Settings as [mapsize, flags, ingester_threads, auto_resize_max], where
+/// ingester_threads is 0 for nostrdb's default and auto_resize_max 0 when
+/// auto-resize is off
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEvents(
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_ndbGetSettings(
     mut env: JNIEnv,
     _class: JClass,
     ndb_ptr: jlong,
-    ldjson: JString,
-) -> jint {
-    with_exception(&mut env, -1, |env| {
-        let ndb = unsafe { util::ptr_to_ref::<Arc<Ndb>>(ndb_ptr, "ndb")? };
-        let json_str = java_string_to_rust(env, &ldjson)?;
-        let mut count = 0;
-        for line in json_str.lines() {
-            if !line.trim().is_empty() {
-                if ndb.process_event(line).is_ok() {
-                    count += 1;
-                }
-            }
-        }
-        Ok(count)
+) -> jlongArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let config = handle.config()?;
+        let settings = [
+            config.mapsize() as jlong,
+            config.flags() as jlong,
+            config.ingester_threads().unwrap_or(0) as jlong,
+            config.auto_resize_max() as jlong,
+        ];
+        rust_longs_to_java(env, &settings)
     })
 }
 
 // ============================================================================
-// Transaction Management
+// Configuration
 // ============================================================================
 
-/// Begin read transaction
-///
-/// IMPORTANT: Only one transaction per thread!
-///
-/// # Arguments
-/// * `ndb_ptr` - Pointer to the Ndb instance
+/// Create a new Config with nostrdb's defaults
 ///
-/// # Returns
-/// Pointer to Transaction as jlong, or 0 on error
+/// The config is not consumed by `ndbOpen`; it must be released with
+/// `configDestroy` and may be reused for several databases.
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_beginTransaction(
-    mut env: JNIEnv,
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_configNew(
+    _env: JNIEnv,
     _class: JClass,
-    ndb_ptr: jlong,
 ) -> jlong {
-    with_exception(&mut env, 0, |_env| {
-        let ndb = unsafe { util::ptr_to_ref::<Arc<Ndb>>(ndb_ptr, "ndb")? };
-        let txn = Transaction::new(ndb)?;
-        Ok(box_to_ptr(txn))
-    })
+    catch_panic(0, || box_to_ptr(NdbConfig::new()))
 }
 
-/// End transaction
+/// Destroy a Config
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_endTransaction(
-    _env: JNIEnv,
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_configDestroy(
+    mut env: JNIEnv,
     _class: JClass,
-    txn_ptr: jlong,
+    config_ptr: jlong,
 ) {
-    catch_panic_void(|| unsafe {
-        drop_ptr::<Transaction>(txn_ptr);
+    with_exception(&mut env, (), |_env| unsafe {
+        util::drop_ptr::<NdbConfig>(config_ptr, "config")
     });
 }
 
-// ============================================================================
-// Note Retrieval
-// ============================================================================
-
-/// Get note by 32-byte event ID
+/// Set the LMDB map size
 ///
-/// # Arguments
-/// * `ndb_ptr` - Pointer to the Ndb instance
-/// * `txn_ptr` - Pointer to the Transaction
-/// * `event_id` - 32-byte event ID
+/// The map size bounds how large the database can grow; writes fail once
+/// it is reached. Reopening with a larger size lets ingestion resume.
 ///
-/// # Returns
-/// Serialized note as byte array (JSON), or null if not found
+/// # Arguments
+/// * `config_ptr` - Pointer to the Config
+/// * `size_bytes` - Map size in bytes (must be positive)
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteById(
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_configSetMapSize(
     mut env: JNIEnv,
     _class: JClass,
-    ndb_ptr: jlong,
-    txn_ptr: jlong,
-    event_id: JByteArray,
-) -> jbyteArray {
-    with_exception(&mut env, std::ptr::null_mut(), |env| {
-        let ndb = unsafe { util::ptr_to_ref::<Arc<Ndb>>(ndb_ptr, "ndb")? };
-        let txn = unsafe { util::ptr_to_ref::<Transaction>(txn_ptr, "transaction")? };
-        let id = java_bytes_to_32(env, &event_id)?;
-
-        match ndb.get_note_by_id(txn, &id) {
-            Ok(note) => {
-                let json = serialize_note(&note)?;
-                Ok(rust_bytes_to_java(env, &json))
-            }
-            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
-            Err(e) => Err(e.into()),
-        }
+    config_ptr: jlong,
+    size_bytes: jlong,
+) {
+    with_exception(&mut env, (), |_env| {
+        let config = unsafe { util::ptr_to_mut::<NdbConfig>(config_ptr, "config")? };
+        let size = usize::try_from(size_bytes)
+            .ok()
+            .filter(|&size| size > 0)
+            .ok_or_else(|| {
+                Error::InvalidArgument(format!("Map size must be positive, got {}", size_bytes))
+            })?;
+        config.set_mapsize(size);
+        Ok(())
     })
 }
 
-/// Get note by internal key (faster for repeated lookups)
+/// Set the number of ingester threads
 ///
 /// # Arguments
-/// * `ndb_ptr` - Pointer to the Ndb instance
-/// * `txn_ptr` - Pointer to the Transaction
-/// * `note_key` - Internal note key
-///
-/// # Returns
-/// Serialized note as byte array (JSON), or null if not found
+/// * `config_ptr` - Pointer to the Config
+/// * `count` - Number of threads (must be positive)
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteByKey(
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_configSetIngesterThreads(
     mut env: JNIEnv,
     _class: JClass,
-    ndb_ptr: jlong,
-    txn_ptr: jlong,
-    note_key: jlong,
-) -> jbyteArray {
-    with_exception(&mut env, std::ptr::null_mut(), |env| {
-        let ndb = unsafe { util::ptr_to_ref::<Arc<Ndb>>(ndb_ptr, "ndb")? };
-        let txn = unsafe { util::ptr_to_ref::<Transaction>(txn_ptr, "transaction")? };
-        let key = NoteKey::new(note_key as u64);
-
-        match ndb.get_note_by_key(txn, key) {
-            Ok(note) => {
-                let json = serialize_note(&note)?;
-                Ok(rust_bytes_to_java(env, &json))
-            }
-            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
-            Err(e) => Err(e.into()),
+    config_ptr: jlong,
+    count: jint,
+) {
+    with_exception(&mut env, (), |_env| {
+        let config = unsafe { util::ptr_to_mut::<NdbConfig>(config_ptr, "config")? };
+        if count <= 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Ingester thread count must be positive, got {}",
+                count
+            )));
         }
+        config.set_ingester_threads(count);
+        Ok(())
     })
 }
 
-// ============================================================================
-// Query Execution
-// ============================================================================
-
-/// Execute query with filter
+/// Enable or disable skip-validation mode
 ///
-/// # Arguments
-/// * `ndb_ptr` - Pointer to the Ndb instance
-/// * `txn_ptr` - Pointer to the Transaction
-/// * `filter_ptr` - Pointer to the Filter
-/// * `limit` - Maximum number of results
+/// When enabled, nostrdb stores events without verifying their ids and
+/// signatures. Only use this for trusted input.
 ///
-/// # Returns
-/// Serialized results: [count:4][key1:8][key2:8]...
+/// # Arguments
+/// * `config_ptr` - Pointer to the Config
+/// * `skip` - Whether to skip validation
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_query(
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_configSkipValidation(
     mut env: JNIEnv,
     _class: JClass,
-    ndb_ptr: jlong,
-    txn_ptr: jlong,
-    filter_ptr: jlong,
-    limit: jint,
-) -> jbyteArray {
-    with_exception(&mut env, std::ptr::null_mut(), |env| {
-        let ndb = unsafe { util::ptr_to_ref::<Arc<Ndb>>(ndb_ptr, "ndb")? };
-        let txn = unsafe { util::ptr_to_ref::<Transaction>(txn_ptr, "transaction")? };
-        let filter = unsafe { util::ptr_to_ref::<Filter>(filter_ptr, "filter")? };
-
-        let results = ndb.query(txn, &[filter.clone()], limit)?;
+    config_ptr: jlong,
+    skip: jboolean,
+) {
+    with_exception(&mut env, (), |_env| {
+        let config = unsafe { util::ptr_to_mut::<NdbConfig>(config_ptr, "config")? };
+        config.set_skip_validation(skip != JNI_FALSE);
+        Ok(())
+    })
+}
 
-        // Serialize results: [count:4][key1:8][key2:8]...
-        let mut buf = Vec::with_capacity(4 + results.len() * 8);
-        buf.extend_from_slice(&(results.len() as u32).to_le_bytes());
-        for result in results {
-            buf.extend_from_slice(&result.note_key.as_u64().to_le_bytes());
+/// Replace the nostrdb flags bitmask
+///
+/// This overwrites every flag, including the one set by
+/// `configSkipValidation`.
+///
+/// # Arguments
+/// * `config_ptr` - Pointer to the Config
+/// * `flags` - Bitmask of `NdbConfig` flag constants
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_configSetFlags(
+    mut env: JNIEnv,
+    _class: JClass,
+    config_ptr: jlong,
+    flags: jint,
+) {
+    with_exception(&mut env, (), |_env| {
+        let config = unsafe { util::ptr_to_mut::<NdbConfig>(config_ptr, "config")? };
+        let unknown = flags & !config::KNOWN_FLAGS;
+        if unknown != 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Unknown config flags: {:#x}",
+                unknown
+            )));
         }
-
-        Ok(rust_bytes_to_java(env, &buf))
+        config.set_flags(flags);
+        Ok(())
     })
 }
 
-// ============================================================================
-// Filter Building
-// ============================================================================
+/// Enable automatic map growth
+///
+/// When enabled, a background thread measures LMDB map utilization; once
+/// the map is full, the database is closed and reopened with twice the
+/// map size, up to `max_size_bytes`. Events nostrdb's writer dropped for
+/// lack of space are queued again after the resize.
+///
+/// # Arguments
+/// * `config_ptr` - Pointer to the Config
+/// * `max_size_bytes` - Largest map size to grow to, or 0 to disable
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_configSetAutoResize(
+    mut env: JNIEnv,
+    _class: JClass,
+    config_ptr: jlong,
+    max_size_bytes: jlong,
+) {
+    with_exception(&mut env, (), |_env| {
+        let config = unsafe { util::ptr_to_mut::<NdbConfig>(config_ptr, "config")? };
+        let max = usize::try_from(max_size_bytes).map_err(|_| {
+            Error::InvalidArgument(format!(
+                "Maximum map size must not be negative, got {}",
+                max_size_bytes
+            ))
+        })?;
+        config.set_auto_resize_max(max);
+        Ok(())
+    })
+}
 
-/// Create new filter builder
+/// Fail on stored content that isn't valid UTF-8
+///
+/// By default, note content that isn't valid UTF-8 is serialized with the
+/// invalid sequences replaced by U+FFFD. In strict mode, serializing such
+/// a note throws InvalidContentException with the note's key instead.
+///
+/// # Arguments
+/// * `config_ptr` - Pointer to the Config
+/// * `strict` - Whether to fail rather than repair the content
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterNew(
-    _env: JNIEnv,
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_configSetStrictUtf8(
+    mut env: JNIEnv,
     _class: JClass,
-) -> jlong {
-    catch_panic(0, || {
-        let filter = Filter::new();
-        box_to_ptr(filter)
+    config_ptr: jlong,
+    strict: jboolean,
+) {
+    with_exception(&mut env, (), |_env| {
+        let config = unsafe { util::ptr_to_mut::<NdbConfig>(config_ptr, "config")? };
+        config.set_strict_utf8(strict != JNI_FALSE);
+        Ok(())
     })
 }
 
-/// Add kinds to filter
+// ============================================================================
+// Event Ingestion
+// ============================================================================
+
+/// Process a single JSON event
+///
+/// The event is only queued, as by `processEvents`; nostrdb's writer
+/// drops it if it is a duplicate or doesn't verify.
 ///
 /// # Arguments
-/// * `filter_ptr` - Pointer to the FilterBuilder
-/// * `kinds` - Serialized kinds: [kind1:4][kind2:4]...
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `json` - JSON string of the event
 ///
 /// # Returns
-/// New filter pointer (old one is consumed)
+/// 1 on success, 0 on failure
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterKinds(
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEvent(
     mut env: JNIEnv,
     _class: JClass,
-    filter_ptr: jlong,
-    kinds: JByteArray,
-) -> jlong {
-    with_exception(&mut env, filter_ptr, |env| {
-        let filter = unsafe { Box::from_raw(filter_ptr as *mut nostrdb::FilterBuilder) };
-        let bytes = java_bytes_to_rust(env, &kinds)?;
-
-        let kinds: Vec<u64> = bytes
-            .chunks_exact(4)
-            .map(|chunk| {
-                let arr: [u8; 4] = chunk.try_into().unwrap();
-                u32::from_le_bytes(arr) as u64
-            })
-            .collect();
-
-        let new_filter = filter.kinds(kinds);
-        Ok(box_to_ptr(new_filter))
+    ndb_ptr: jlong,
+    json: JString,
+) -> jint {
+    with_exception(&mut env, 0, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let json_str = java_string_to_rust(env, &json)?;
+        ingest::queue(handle, &json_str)??;
+        Ok(1)
     })
 }
 
-/// Add authors to filter (array of 32-byte pubkeys)
+/// Process a single JSON event, reporting whether it was new
+///
+/// The event's id is looked up first, so that an event that is already
+/// stored isn't queued again. Unless the database skips validation, its
+/// id and signature are verified before it is queued.
 ///
 /// # Arguments
-/// * `filter_ptr` - Pointer to the FilterBuilder
-/// * `authors` - Serialized authors: [pubkey1:32][pubkey2:32]...
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `json` - JSON string of the event
 ///
 /// # Returns
-/// New filter pointer (old one is consumed)
+/// 1 if queued, 2 if already stored, 0 if malformed, not verified, or
+/// refused by nostrdb
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterAuthors(
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventWithResult(
     mut env: JNIEnv,
     _class: JClass,
-    filter_ptr: jlong,
-    authors: JByteArray,
-) -> jlong {
-    with_exception(&mut env, filter_ptr, |env| {
-        let filter = unsafe { Box::from_raw(filter_ptr as *mut nostrdb::FilterBuilder) };
-        let bytes = java_bytes_to_rust(env, &authors)?;
-
-        let authors: Vec<[u8; 32]> = bytes
-            .chunks_exact(32)
-            .map(|chunk| {
-                let mut arr = [0u8; 32];
-                arr.copy_from_slice(chunk);
-                arr
-            })
-            .collect();
-
-        let author_refs: Vec<&[u8; 32]> = authors.iter().collect();
-        let new_filter = filter.authors(author_refs);
-        Ok(box_to_ptr(new_filter))
+    ndb_ptr: jlong,
+    json: JString,
+) -> jint {
+    with_exception(&mut env, ingest::PROCESS_REJECTED, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let json_str = java_string_to_rust(env, &json)?;
+        ingest::process_new(handle, &json_str)
     })
 }
 
-/// Add tag filter
+/// Process a single JSON event, validating it or not for this call only
+///
+/// With `skip_validation` false, the event's id and signature are
+/// verified before it is queued, even if the database skips validation;
+/// one that doesn't verify is rejected. With it true, the event goes
+/// through unverified, which nostrdb only allows for a database opened
+/// with validation skipped (IllegalStateException otherwise).
 ///
 /// # Arguments
-/// * `filter_ptr` - Pointer to the FilterBuilder
-/// * `tag_name` - Single-character tag name (e.g., "d", "p", "e")
-/// * `tag_values` - Array of tag values
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `json` - JSON string of the event
+/// * `skip_validation` - Whether to skip verification for this call
 ///
 /// # Returns
-/// New filter pointer (old one is consumed)
+/// 1 on success, 0 if the event doesn't verify or nostrdb refuses it
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterTag(
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventWithValidation(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    json: JString,
+    skip_validation: jboolean,
+) -> jint {
+    with_exception(&mut env, 0, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let skip = skip_validation != JNI_FALSE;
+        if skip {
+            ingest::check_skip_validation(handle)?;
+        }
+        let json_str = java_string_to_rust(env, &json)?;
+        if !skip && ingest::checked_id(&json_str, true).is_err() {
+            handle.ingest_stats().rejected();
+            return Ok(0);
+        }
+        ingest::queue(handle, &json_str)??;
+        Ok(1)
+    })
+}
+
+/// Process a single JSON event from UTF-8 bytes
+///
+/// Like `processEvent`, without decoding the event into a Java string
+/// first.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `json` - JSON of the event, UTF-8 encoded
+///
+/// # Returns
+/// 1 on success, 0 on failure (IllegalArgumentException if the bytes
+/// aren't valid UTF-8)
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventBytes(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    json: JByteArray,
+) -> jint {
+    with_exception(&mut env, 0, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let bytes = java_bytes_to_rust(env, &json)?;
+        ingest::queue(handle, std::str::from_utf8(&bytes)?)??;
+        Ok(1)
+    })
+}
+
+/// Process a single JSON event and return the stored note's key
+///
+/// The event may be wrapped in a relay or client `EVENT` message, as for
+/// `processEvent`. Unless the database skips validation, its id and
+/// signature are verified first, so an invalid event is reported instead
+/// of being dropped silently by nostrdb. An event that is already stored
+/// is not processed again. Otherwise the event is processed and its id
+/// polled for, since nostrdb stores notes asynchronously, until the note
+/// is visible or `timeout_ms` has passed.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `json` - JSON string of the event
+/// * `timeout_ms` - How long to wait for the note to be stored (not negative)
+///
+/// # Returns
+/// The note key, or a negative outcome: -1 invalid, -2 duplicate,
+/// -3 not stored in time, -4 rejected by nostrdb
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventReturningKey(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    json: JString,
+    timeout_ms: jlong,
+) -> jlong {
+    with_exception(&mut env, ingest::INGEST_REJECTED, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let json_str = java_string_to_rust(env, &json)?;
+        let timeout = non_negative_timeout(timeout_ms)?;
+        let ndb = handle.writable_ndb()?;
+
+        let stats = handle.ingest_stats();
+        let validate = !handle.skips_validation()?;
+        let Ok(id) = ingest::checked_id(&json_str, validate) else {
+            stats.rejected();
+            return Ok(ingest::INGEST_INVALID);
+        };
+        if ingest::note_key(handle, &id)?.is_some() {
+            stats.duplicate();
+            return Ok(ingest::INGEST_DUPLICATE);
+        }
+        let result = ndb.process_event(&json_str);
+        stats.record(&result, json_str.len());
+        if result.is_err() {
+            return Ok(ingest::INGEST_REJECTED);
+        }
+        // Don't hold the instance while waiting, so that a resize can proceed
+        drop(ndb);
+
+        Ok(match ingest::await_note_key(handle, &id, timeout)? {
+            Some(key) => key as jlong,
+            None => ingest::INGEST_TIMEOUT,
+        })
+    })
+}
+
+/// Wait for an event to be stored
+///
+/// nostrdb stores events on its writer threads after `processEvent`
+/// returns; this polls for the id until the note is visible or
+/// `timeout_ms` has passed.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `id` - Event ID (32 bytes)
+/// * `timeout_ms` - How long to wait (not negative)
+///
+/// # Returns
+/// True if the note is stored, false if the wait ran out
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_waitForIngestion(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    id: JByteArray,
+    timeout_ms: jlong,
+) -> jboolean {
+    with_exception(&mut env, JNI_FALSE, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let id = java_bytes_to_32(env, &id)?;
+        let timeout = non_negative_timeout(timeout_ms)?;
+        Ok(match ingest::await_note_key(handle, &id, timeout)? {
+            Some(_) => JNI_TRUE,
+            None => JNI_FALSE,
+        })
+    })
+}
+
+/// Wait for the database to hold at least a number of notes
+///
+/// For batch imports, where waiting for each id would be slow. nostrdb
+/// numbers notes from 1 as they are stored, so this polls for the note
+/// with key `count`; `count` should include the notes stored before the
+/// import.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `count` - Number of notes to wait for
+/// * `timeout_ms` - How long to wait (not negative)
+///
+/// # Returns
+/// True if the notes are stored, false if the wait ran out
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_waitForIngestionCount(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    count: jlong,
+    timeout_ms: jlong,
+) -> jboolean {
+    with_exception(&mut env, JNI_FALSE, |_env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let timeout = non_negative_timeout(timeout_ms)?;
+        let count = count.max(0) as u64;
+        Ok(if ingest::await_note_count(handle, count, timeout)? {
+            JNI_TRUE
+        } else {
+            JNI_FALSE
+        })
+    })
+}
+
+/// A timeout in milliseconds from Java, which must not be negative
+fn non_negative_timeout(timeout_ms: jlong) -> Result<Duration> {
+    if timeout_ms < 0 {
+        return Err(Error::InvalidArgument(format!(
+            "Timeout must not be negative, got {}",
+            timeout_ms
+        )));
+    }
+    Ok(Duration::from_millis(timeout_ms as u64))
+}
+
+/// Process a single JSON event, calling back once it has been written
+///
+/// The event is checked as by `processEventReturningKey`, and a
+/// subscription on its id is set up before it is queued. The callback's
+/// `onComplete(byte[] id, long noteKey, int status)` is then called
+/// exactly once from a native thread: when the note lands, when
+/// `timeout_ms` has passed, or at once if the event is invalid, already
+/// stored, or refused. Exceptions it throws are logged and cleared.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `json` - JSON string of the event
+/// * `callback` - Object implementing `IngestCallback`
+/// * `timeout_ms` - How long to wait for the note to be stored (not negative)
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventAsync(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    json: JString,
+    callback: JObject,
+    timeout_ms: jlong,
+) {
+    with_exception(&mut env, (), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let json_str = java_string_to_rust(env, &json)?;
+        let timeout = non_negative_timeout(timeout_ms)?;
+        if callback.is_null() {
+            return Err(Error::NullPointer("callback"));
+        }
+        let ndb = handle.writable_ndb()?;
+
+        let stats = handle.ingest_stats();
+        let validate = !handle.skips_validation()?;
+        let outcome = match ingest::checked_id(&json_str, validate) {
+            Err(_) => {
+                stats.rejected();
+                let event = verify::parse_wrapped(&json_str);
+                let id = event
+                    .and_then(|event| verify::event_claimed_id(&event))
+                    .ok();
+                completion::Outcome::Done(id, completion::STATUS_INVALID, 0)
+            }
+            Ok(id) => match ingest::note_key(handle, &id)? {
+                Some(key) => {
+                    stats.duplicate();
+                    completion::Outcome::Done(Some(id), completion::STATUS_DUPLICATE, key)
+                }
+                None => {
+                    let filter = Filter::new().ids([&id]).build();
+                    let sub = handle.subscribe(&[filter])?;
+                    let result = ndb.process_event(&json_str);
+                    stats.record(&result, json_str.len());
+                    if result.is_ok() {
+                        let deadline = Instant::now() + timeout;
+                        completion::Outcome::Waiting(id, sub, deadline)
+                    } else {
+                        handle.unsubscribe(sub)?;
+                        completion::Outcome::Done(Some(id), completion::STATUS_REJECTED, 0)
+                    }
+                }
+            },
+        };
+        drop(ndb);
+
+        let vm = env.get_java_vm()?;
+        let callback = env.new_global_ref(&callback)?;
+        completion::spawn(vm, callback, handle.db_source(), outcome)
+    })
+}
+
+/// Process batch of newline-delimited JSON events
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `ldjson` - Newline-delimited JSON events
+///
+/// # Returns
+/// Number of events processed, or -1 on error
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEvents(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    ldjson: JString,
+) -> jint {
+    with_exception(&mut env, -1, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let json_str = java_string_to_rust(env, &ldjson)?;
+        ingest::process_lines(handle, json_str.lines(), false)
+    })
+}
+
+/// Process batch of newline-delimited JSON events, validating them or not
+/// for this call only
+///
+/// As `processEventWithValidation`, for each line of the batch: with
+/// `skip_validation` false, a line whose event doesn't verify isn't queued
+/// or counted.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `ldjson` - Newline-delimited JSON events
+/// * `skip_validation` - Whether to skip verification for this call
+///
+/// # Returns
+/// Number of events processed, or -1 on error
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventsWithValidation(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    ldjson: JString,
+    skip_validation: jboolean,
+) -> jint {
+    with_exception(&mut env, -1, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let skip = skip_validation != JNI_FALSE;
+        if skip {
+            ingest::check_skip_validation(handle)?;
+        }
+        let json_str = java_string_to_rust(env, &ldjson)?;
+        ingest::process_lines(handle, json_str.lines(), !skip)
+    })
+}
+
+/// Process batch of newline-delimited JSON events from UTF-8 bytes
+///
+/// Like `processEvents`, without decoding the batch into a Java string
+/// first. The bytes are split on newlines in place; a line that isn't
+/// valid UTF-8 is skipped and not counted, like any event nostrdb rejects.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `ldjson` - Newline-delimited JSON events, UTF-8 encoded
+///
+/// # Returns
+/// Number of events processed, or -1 on error
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventsBytes(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    ldjson: JByteArray,
+) -> jint {
+    with_exception(&mut env, -1, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let bytes = java_bytes_to_rust(env, &ldjson)?;
+        let lines = ingest::byte_lines(&bytes, handle.ingest_stats());
+        ingest::process_lines(handle, lines, false)
+    })
+}
+
+/// Process batch of gzip-compressed newline-delimited JSON events
+///
+/// Like `processEventsBytes`, decompressing the batch as it is split into
+/// lines, so the uncompressed events are never held in memory at once.
+/// Concatenated gzip members are read as one stream.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `gzip_data` - Gzip-compressed newline-delimited JSON events
+///
+/// # Returns
+/// Number of events processed, or -1 on error; a truncated or corrupt
+/// stream throws NostrdbException naming the events processed before it
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventsGzip(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    gzip_data: JByteArray,
+) -> jint {
+    with_exception(&mut env, -1, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let bytes = java_bytes_to_rust(env, &gzip_data)?;
+        let counts = ingest::process_gzip(handle, bytes.as_slice(), 0, None, |_| Ok(()))?;
+        Ok(jint::try_from(counts.processed).unwrap_or(jint::MAX))
+    })
+}
+
+/// Process batch of newline-delimited JSON events from a direct ByteBuffer
+///
+/// Like `processEventsBytes`, but the events are read in place from the
+/// window `[offset, offset + length)` of the buffer's memory, so a
+/// memory-mapped dump is ingested without copying it onto the Java heap
+/// or into native memory first. The buffer's position and limit are
+/// ignored.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `buffer` - Direct ByteBuffer holding UTF-8 newline-delimited JSON
+/// * `offset` - Index of the window's first byte
+/// * `length` - Length of the window in bytes
+///
+/// # Returns
+/// Number of events processed, or -1 on error
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventsDirect(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    buffer: JByteBuffer,
+    offset: jint,
+    length: jint,
+) -> jint {
+    with_exception(&mut env, -1, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+
+        if buffer.is_null() {
+            return Err(Error::NullPointer("buffer"));
+        }
+        let (address, capacity) = match env.get_direct_buffer_address(&buffer) {
+            Ok(address) => (address, env.get_direct_buffer_capacity(&buffer)?),
+            Err(_) => {
+                return Err(Error::InvalidArgument(
+                    "Buffer must be a direct ByteBuffer".to_string(),
+                ))
+            }
+        };
+        let (start, len) = (offset as usize, length as usize);
+        if offset < 0 || length < 0 || start + len > capacity {
+            return Err(Error::InvalidArgument(format!(
+                "Window [{}, {} + {}) is outside the buffer's capacity {}",
+                offset, offset, length, capacity
+            )));
+        }
+
+        // SAFETY: the window is within the buffer's capacity, and the
+        // address is valid while the Java buffer is reachable, which it is
+        // for the duration of this call
+        let window = unsafe { std::slice::from_raw_parts(address.add(start), len) };
+        let lines = ingest::byte_lines(window, handle.ingest_stats());
+        ingest::process_lines(handle, lines, false)
+    })
+}
+
+/// Import newline-delimited JSON events from a file, streamed natively
+///
+/// The file is read a line at a time through a buffered reader, so its
+/// size isn't bounded by the Java heap. Lines are processed as by
+/// `processEventsBytes`. If `progress` isn't null, its
+/// `onProgress(long lines, long failed)` is called every
+/// `ingest::PROGRESS_INTERVAL` lines and once at the end; an exception it
+/// throws stops the import and propagates. If `token_ptr` isn't 0, the
+/// token is checked before every line and the import stops once it is
+/// cancelled. A file starting with the gzip magic bytes is decompressed
+/// as it is read, as by `processEventsGzip`.
+///
+/// The import starts at `resume_offset`, which should be 0 or the offset
+/// returned by an earlier import of the same file, so that it falls at the
+/// start of a line. For a gzipped file it is an offset in the decompressed
+/// stream, which is read up to it again.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `path` - Path of the file to import
+/// * `progress` - An `ImportProgressCallback`, or null
+/// * `token_ptr` - Pointer to a cancellation token, or 0
+/// * `resume_offset` - Byte offset to start at (not negative)
+///
+/// # Returns
+/// Counts as [lines, processed, failed, cancelled (1 or 0), offset], where
+/// lines are counted from `resume_offset` and offset is from the start of
+/// the file
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventsFromFile(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    path: JString,
+    progress: JObject,
+    token_ptr: jlong,
+    resume_offset: jlong,
+) -> jlongArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let token = unsafe { optional_cancel_token(token_ptr)? };
+        if resume_offset < 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Resume offset must not be negative, got {}",
+                resume_offset
+            )));
+        }
+        let offset = resume_offset as u64;
+        let path = java_string_to_rust(env, &path)?;
+        let file = std::fs::File::open(&path)
+            .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+        let mut reader = std::io::BufReader::with_capacity(1 << 20, file);
+
+        let report = |counts: &ingest::ImportCounts| report_import_progress(env, &progress, counts);
+        let counts = if ingest::is_gzip(&mut reader)? {
+            ingest::process_gzip(handle, reader, offset, token, report)?
+        } else {
+            reader.seek(SeekFrom::Start(offset))?;
+            ingest::process_reader(handle, reader, offset, token, report)?
+        };
+        import_counts_to_java(env, &counts)
+    })
+}
+
+/// Process batch of newline-delimited JSON events from UTF-8 bytes,
+/// reporting progress and stopping once a token is cancelled
+///
+/// Like `processEventsFromFile`, reading the lines of `ldjson` instead of
+/// a file; a line that isn't valid UTF-8 is counted as failed.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `ldjson` - Newline-delimited JSON events, UTF-8 encoded
+/// * `progress` - An `ImportProgressCallback`, or null
+/// * `token_ptr` - Pointer to a cancellation token, or 0
+///
+/// # Returns
+/// Counts as [lines, processed, failed, cancelled (1 or 0), offset], where
+/// offset is the index just past the last line read
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventsBytesCancellable(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    ldjson: JByteArray,
+    progress: JObject,
+    token_ptr: jlong,
+) -> jlongArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let token = unsafe { optional_cancel_token(token_ptr)? };
+        let bytes = java_bytes_to_rust(env, &ldjson)?;
+        let report = |counts: &ingest::ImportCounts| report_import_progress(env, &progress, counts);
+        let counts = ingest::process_reader(handle, bytes.as_slice(), 0, token, report)?;
+        import_counts_to_java(env, &counts)
+    })
+}
+
+/// The cancellation token behind `token_ptr`, or None if it is 0
+///
+/// # Safety
+/// `token_ptr` must be 0 or a pointer returned by `cancelTokenNew` that
+/// has not been freed.
+unsafe fn optional_cancel_token<'a>(token_ptr: jlong) -> Result<Option<&'a CancelToken>> {
+    if token_ptr == 0 {
+        return Ok(None);
+    }
+    let token = util::ptr_to_ref::<CancelToken>(token_ptr, "cancel token")?;
+    Ok(Some(token))
+}
+
+/// Call an `ImportProgressCallback`'s `onProgress(long lines, long failed)`,
+/// unless the callback is null
+fn report_import_progress(
+    env: &mut JNIEnv,
+    progress: &JObject,
+    counts: &ingest::ImportCounts,
+) -> Result<()> {
+    if !progress.is_null() {
+        let args = [
+            JValue::Long(counts.lines as jlong),
+            JValue::Long(counts.failed as jlong),
+        ];
+        env.call_method(progress, "onProgress", "(JJ)V", &args)?;
+    }
+    Ok(())
+}
+
+/// Import counts as the [lines, processed, failed, cancelled, offset]
+/// array read by `ImportSummary.fromArray`
+fn import_counts_to_java(env: &mut JNIEnv, counts: &ingest::ImportCounts) -> Result<jlongArray> {
+    rust_longs_to_java(
+        env,
+        &[
+            counts.lines as jlong,
+            counts.processed as jlong,
+            counts.failed as jlong,
+            counts.cancelled as jlong,
+            counts.offset as jlong,
+        ],
+    )
+}
+
+/// Process batch of newline-delimited JSON events, reporting failed lines
+///
+/// Each line is checked before it is queued, as by
+/// `processEventReturningKey`, since nostrdb's writer drops invalid events
+/// silently; a line fails if the check or nostrdb's ingestion does. An
+/// event that is already stored, or repeats an earlier line, is counted as
+/// a duplicate and not queued. Successful lines aren't listed. Line
+/// numbers start at 1.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `ldjson` - Newline-delimited JSON events
+/// * `stop_on_error` - Stop at the first failed line
+///
+/// # Returns
+/// Serialized report: [succeeded:4][duplicates:4][failed:4][blank:4]
+/// [lines_read:4] followed by `failed` entries ([line:4][len:2][error:len])*
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventsDetailed(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    ldjson: JString,
+    stop_on_error: jboolean,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let json_str = java_string_to_rust(env, &ldjson)?;
+        let mut batch = ingest::CheckedBatch::new(handle)?;
+        for (line_number, line) in (1u32..).zip(json_str.lines()) {
+            if !batch.add(line_number, line)? && stop_on_error != JNI_FALSE {
+                break;
+            }
+        }
+        Ok(rust_bytes_to_java(env, &checked_batch_report(&batch)))
+    })
+}
+
+/// Process a JSON array of events, reporting failed elements
+///
+/// The array is read an element at a time, so a large one is never parsed
+/// into a whole document. Each element is checked and queued as a line is
+/// by `processEventsDetailed`, and reported in the same format, numbered
+/// from 1. Input that isn't an array throws NostrdbException; so does a
+/// syntax error partway through, after the elements before it have been
+/// processed.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `json` - JSON array of events
+///
+/// # Returns
+/// Serialized report: [succeeded:4][duplicates:4][failed:4][blank:4]
+/// [elements_read:4] followed by `failed` entries
+/// ([element:4][len:2][error:len])*; blank is always 0
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventsArray(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    json: JString,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let json_str = java_string_to_rust(env, &json)?;
+
+        let mut batch = ingest::CheckedBatch::new(handle)?;
+        let mut number = 0u32;
+        ingest::for_each_array_element(&json_str, |event| {
+            number += 1;
+            batch.add(number, event).map(|_| ())
+        })?;
+        Ok(rust_bytes_to_java(env, &checked_batch_report(&batch)))
+    })
+}
+
+/// A checked batch as the report read by `IngestReport.parse`
+fn checked_batch_report(batch: &ingest::CheckedBatch) -> Vec<u8> {
+    let failures = batch.failures();
+    let mut buf = Vec::with_capacity(20 + failures.len() * 64);
+    for total in batch.totals() {
+        buf.extend_from_slice(&total.to_le_bytes());
+    }
+    for (number, error) in failures {
+        buf.extend_from_slice(&number.to_le_bytes());
+        write_short_str(&mut buf, error);
+    }
+    buf
+}
+
+/// Process a single JSON event received from a relay, recording the relay
+///
+/// nostrdb stores the relay as one of the note's origins, whether or not
+/// the note was already stored; `getNoteRelays` lists them.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `json` - JSON string of the event
+/// * `relay_url` - URL of the relay the event came from
+///
+/// # Returns
+/// 1 on success, 0 on failure
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventFromRelay(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    json: JString,
+    relay_url: JString,
+) -> jint {
+    with_exception(&mut env, 0, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.writable_ndb()?;
+        let json_str = java_string_to_rust(env, &json)?;
+        let relay_url = java_string_to_rust(env, &relay_url)?;
+        if relay_url.is_empty() {
+            return Err(Error::InvalidArgument(
+                "Relay URL must not be empty".to_string(),
+            ));
+        }
+        let result = ndb.process_relay_event(&json_str, &relay_url);
+        handle.ingest_stats().record(&result, json_str.len());
+        result?;
+        Ok(1)
+    })
+}
+
+/// Process batch of newline-delimited JSON events received from a relay
+///
+/// Each event is recorded as seen on the relay, as by `processEventFromRelay`.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `ldjson` - Newline-delimited JSON events
+/// * `relay_url` - URL of the relay the events came from
+///
+/// # Returns
+/// Number of events processed, or -1 on error
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processEventsFromRelay(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    ldjson: JString,
+    relay_url: JString,
+) -> jint {
+    with_exception(&mut env, -1, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let json_str = java_string_to_rust(env, &ldjson)?;
+        let relay_url = java_string_to_rust(env, &relay_url)?;
+        if relay_url.is_empty() {
+            return Err(Error::InvalidArgument(
+                "Relay URL must not be empty".to_string(),
+            ));
+        }
+        let mut count = 0;
+        for line in json_str.lines() {
+            if !line.trim().is_empty() {
+                // Fetched per event so that auto-resize can grow the map mid-batch
+                let result = handle.writable_ndb()?.process_relay_event(line, &relay_url);
+                handle.ingest_stats().record(&result, line.len());
+                if result.is_ok() {
+                    count += 1;
+                }
+            }
+        }
+        Ok(count)
+    })
+}
+
+/// Process a client message `["EVENT", {...}]` through nostrdb's client path
+///
+/// The envelope is checked first; a malformed one throws NostrdbException
+/// naming what is wrong.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `json` - JSON of the client message
+/// * `accept_bare` - Accept a bare event object as if it were wrapped
+///
+/// # Returns
+/// 1 on success, 0 on failure
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processClientEvent(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    json: JString,
+    accept_bare: jboolean,
+) -> jint {
+    with_exception(&mut env, 0, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.writable_ndb()?;
+        let json_str = java_string_to_rust(env, &json)?;
+        let message = ingest::client_message(&json_str, accept_bare != JNI_FALSE)
+            .inspect_err(|_| handle.ingest_stats().rejected())?;
+        let result = ndb.process_client_event(&message);
+        handle.ingest_stats().record(&result, message.len());
+        result?;
+        Ok(1)
+    })
+}
+
+/// Process a relay message, ingesting it if it is `["EVENT", subid, {...}]`
+///
+/// An `EVENT` message goes to nostrdb's relay ingestion whole; its
+/// subscription id is ignored. Other relay messages (`EOSE`, `NOTICE`,
+/// `OK`, ...) are skipped.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `json` - JSON of the relay message
+///
+/// # Returns
+/// 1 if an event was processed, 0 if the message was skipped
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processRelayEvent(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    json: JString,
+) -> jint {
+    with_exception(&mut env, 0, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.writable_ndb()?;
+        let json_str = java_string_to_rust(env, &json)?;
+        let message_type = ingest::relay_message_type(&json_str)
+            .inspect_err(|_| handle.ingest_stats().rejected())?;
+        if message_type != "EVENT" {
+            return Ok(0);
+        }
+        let result = ndb.process_event(&json_str);
+        handle.ingest_stats().record(&result, json_str.len());
+        result?;
+        Ok(1)
+    })
+}
+
+/// Process a batch of newline-delimited relay messages
+///
+/// Each line is handled as by `processRelayEvent`; lines that fail are
+/// counted rather than ending the batch.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `ldjson` - Newline-delimited relay messages
+///
+/// # Returns
+/// Counts [events, skipped, failed]: events processed, non-`EVENT`
+/// messages skipped, and lines that are not relay messages or whose
+/// event nostrdb refused
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_processRelayEvents(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    ldjson: JString,
+) -> jintArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let json_str = java_string_to_rust(env, &ldjson)?;
+        let (mut events, mut skipped, mut failed) = (0, 0, 0);
+        for line in json_str.lines().filter(|line| !line.trim().is_empty()) {
+            match ingest::relay_message_type(line) {
+                Ok("EVENT") => match ingest::queue(handle, line)? {
+                    Ok(()) => events += 1,
+                    Err(_) => failed += 1,
+                },
+                Ok(_) => skipped += 1,
+                Err(_) => {
+                    handle.ingest_stats().rejected();
+                    failed += 1;
+                }
+            }
+        }
+
+        let arr = env.new_int_array(3)?;
+        env.set_int_array_region(&arr, 0, &[events, skipped, failed])?;
+        Ok(arr.into_raw())
+    })
+}
+
+/// Get the running ingestion totals of a database handle
+///
+/// Every ingestion native records each event it is handed, from any
+/// thread, since the handle was opened or the totals were last reset.
+/// Blank lines and non-`EVENT` relay messages aren't counted.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+///
+/// # Returns
+/// Totals as [accepted, duplicates, rejected, bytes], where bytes are the
+/// UTF-8 bytes of the accepted events
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getIngestStats(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+) -> jlongArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let totals = handle.ingest_stats().snapshot().map(|total| total as jlong);
+        rust_longs_to_java(env, &totals)
+    })
+}
+
+/// Set the ingestion totals of a database handle back to zero
+///
+/// Each total is reset on its own, so an event ingested concurrently may
+/// be counted in some totals and not others.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_resetIngestStats(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+) {
+    with_exception(&mut env, (), |_env| {
+        unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }
+            .ingest_stats()
+            .reset();
+        Ok(())
+    })
+}
+
+// ============================================================================
+// Transaction Management
+// ============================================================================
+
+/// Begin read transaction
+///
+/// IMPORTANT: Only one transaction per thread!
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+///
+/// # Returns
+/// Pointer to Transaction as jlong, or 0 on error
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_beginTransaction(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, 0, |_env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        Ok(box_to_ptr(handle.begin_transaction()?))
+    })
+}
+
+/// End transaction
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_endTransaction(
+    mut env: JNIEnv,
+    _class: JClass,
+    txn_ptr: jlong,
+) {
+    with_exception(&mut env, (), |_env| unsafe {
+        util::drop_ptr::<TxnHandle>(txn_ptr, "transaction")
+    });
+}
+
+// ============================================================================
+// Note Retrieval
+// ============================================================================
+
+/// Get note by 32-byte event ID
+///
+/// Content that isn't valid UTF-8 is serialized with the invalid sequences
+/// replaced by U+FFFD, unless the database was opened in strict mode (see
+/// `configSetStrictUtf8`), which throws InvalidContentException instead.
+/// The same holds for every native that serializes notes.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `event_id` - 32-byte event ID
+///
+/// # Returns
+/// Serialized note as byte array (JSON), or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteById(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    event_id: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let id = java_bytes_to_32(env, &event_id)?;
+
+        match ndb.get_note_by_id(txn, &id) {
+            Ok(note) => {
+                let json = serialize_note(&note, handle.utf8_mode())?;
+                Ok(rust_bytes_to_java(env, &json))
+            }
+            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Get note by internal key (faster for repeated lookups)
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// Serialized note as byte array (JSON), or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteByKey(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let key = NoteKey::new(note_key as u64);
+
+        match ndb.get_note_by_key(txn, key) {
+            Ok(note) => {
+                let json = serialize_note(&note, handle.utf8_mode())?;
+                Ok(rust_bytes_to_java(env, &json))
+            }
+            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Get note by 32-byte event ID, as CBOR
+///
+/// The CBOR map has the keys of the JSON from `getNoteById`, with id,
+/// pubkey, and sig as byte strings instead of hex.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `event_id` - 32-byte event ID
+///
+/// # Returns
+/// Serialized note as byte array (CBOR), or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteByIdCbor(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    event_id: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let id = java_bytes_to_32(env, &event_id)?;
+
+        match ndb.get_note_by_id(txn, &id) {
+            Ok(note) => {
+                let mut cbor = Vec::new();
+                write_note_cbor(&note, handle.utf8_mode(), &mut cbor)?;
+                Ok(rust_bytes_to_java(env, &cbor))
+            }
+            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Get note by internal key, as CBOR
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// Serialized note as byte array (CBOR, as `getNoteByIdCbor`), or null if
+/// not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteByKeyCbor(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        Ok(match find_note(&ndb, txn, note_key)? {
+            Some(note) => {
+                let mut cbor = Vec::new();
+                write_note_cbor(&note, handle.utf8_mode(), &mut cbor)?;
+                rust_bytes_to_java(env, &cbor)
+            }
+            None => std::ptr::null_mut(),
+        })
+    })
+}
+
+/// Get note by 32-byte event ID, as a Java string
+///
+/// The JSON is that of `getNoteById`, including its handling of content
+/// that isn't valid UTF-8. Any other invalid UTF-8 (in tags) is replaced
+/// with U+FFFD before the string is created.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `event_id` - 32-byte event ID
+///
+/// # Returns
+/// The note's JSON, or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteByIdString(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    event_id: JByteArray,
+) -> jstring {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let id = java_bytes_to_32(env, &event_id)?;
+
+        match ndb.get_note_by_id(txn, &id) {
+            Ok(note) => note_json_to_java(env, &note, handle.utf8_mode()),
+            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Get note by internal key, as a Java string
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// The note's JSON (as `getNoteByIdString`), or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteByKeyString(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jstring {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        match find_note(&ndb, txn, note_key)? {
+            Some(note) => note_json_to_java(env, &note, handle.utf8_mode()),
+            None => Ok(std::ptr::null_mut()),
+        }
+    })
+}
+
+/// Get a note's raw bytes by internal key
+///
+/// The bytes are nostrdb's internal `struct ndb_note`, copied as stored:
+/// a version byte and 3 bytes of padding, then id (32), pubkey (32), sig
+/// (64), created_at (u64), kind (u32), and the packed content and tags.
+/// Numbers are in native byte order. The layout is versioned by
+/// [`ABI_VERSION`].
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// The raw note, or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteRawByKey(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let key = NoteKey::new(note_key as u64);
+
+        match ndb.get_note_by_key(txn, key) {
+            Ok(note) => Ok(rust_bytes_to_java(env, note_bytes(&note))),
+            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Get a note's JSON as nostrdb writes it, by internal key
+///
+/// Unlike `getNoteByKey`, the JSON comes from nostrdb itself rather than
+/// this library's serializer, for relaying stored events back out
+/// verbatim.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// The note's JSON, or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteJsonByKey(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let key = NoteKey::new(note_key as u64);
+
+        match ndb.get_note_by_key(txn, key) {
+            Ok(note) => Ok(rust_bytes_to_java(env, note.json()?.as_bytes())),
+            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Get a note's JSON as nostrdb writes it, by 32-byte event ID
+///
+/// As `getNoteJsonByKey`.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `event_id` - 32-byte event ID
+///
+/// # Returns
+/// The note's JSON, or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteJsonById(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    event_id: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let id = java_bytes_to_32(env, &event_id)?;
+
+        match ndb.get_note_by_id(txn, &id) {
+            Ok(note) => Ok(rust_bytes_to_java(env, note.json()?.as_bytes())),
+            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Get a note's 32-byte event ID by internal key
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// The event ID, or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteIdByKey(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let key = NoteKey::new(note_key as u64);
+
+        match ndb.get_note_by_key(txn, key) {
+            Ok(note) => Ok(rust_bytes_to_java(env, note.id())),
+            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Get a note's internal key by 32-byte event ID
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `event_id` - 32-byte event ID
+///
+/// # Returns
+/// The note key, or -1 if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteKeyById(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    event_id: JByteArray,
+) -> jlong {
+    with_exception(&mut env, -1, |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let id = java_bytes_to_32(env, &event_id)?;
+
+        match ndb.get_notekey_by_id(txn, &id) {
+            Ok(key) => Ok(key.as_u64() as jlong),
+            Err(nostrdb::Error::NotFound) => Ok(-1),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Check whether a note with a 32-byte event ID is stored
+///
+/// Only the id index is read; the note itself isn't fetched.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `event_id` - 32-byte event ID
+///
+/// # Returns
+/// Whether the note is stored
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteExists(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    event_id: JByteArray,
+) -> jboolean {
+    with_exception(&mut env, JNI_FALSE, |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let id = java_bytes_to_32(env, &event_id)?;
+
+        match ndb.get_notekey_by_id(txn, &id) {
+            Ok(_) => Ok(JNI_TRUE),
+            Err(nostrdb::Error::NotFound) => Ok(JNI_FALSE),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Check which of many 32-byte event IDs are stored, in one call
+///
+/// As `noteExists` for each id; bit `i % 8` of byte `i / 8` of the result
+/// is set if `ids[i]` is stored, as in the `getNotesByIds` bitmap.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `ids` - Event IDs [id1:32][id2:32]...
+///
+/// # Returns
+/// Bitmap of the stored ids: [found:(n+7)/8]
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_notesExist(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    ids: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let ids = java_bytes_to_32_vec(env, &ids)?;
+
+        let mut bitmap = vec![0u8; ids.len().div_ceil(8)];
+        for (i, id) in ids.iter().enumerate() {
+            match ndb.get_notekey_by_id(txn, id) {
+                Ok(_) => bitmap[i / 8] |= 1 << (i % 8),
+                Err(nostrdb::Error::NotFound) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(rust_bytes_to_java(env, &bitmap))
+    })
+}
+
+/// Get notes by internal key, in one call
+///
+/// Keys that don't resolve to a note are skipped and counted; the notes
+/// that remain keep the order of `keys`. Each note carries its stored size
+/// (as `getNoteSize`) ahead of its JSON.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `keys` - Internal note keys
+///
+/// # Returns
+/// Serialized notes: [count:4][skipped:4]([key:8][size:4][len:4][noteJson:len])*
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNotesByKeys(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    keys: JLongArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let mut values = vec![0; env.get_array_length(&keys)? as usize];
+        env.get_long_array_region(&keys, 0, &mut values)?;
+
+        let mut count: u32 = 0;
+        let mut skipped: u32 = 0;
+        let mut buf = Vec::with_capacity(8 + values.len() * 512);
+        buf.extend_from_slice(&[0; 8]);
+        for &key in &values {
+            match ndb.get_note_by_key(txn, NoteKey::new(key as u64)) {
+                Ok(note) => {
+                    buf.extend_from_slice(&key.to_le_bytes());
+                    buf.extend_from_slice(&(note.size() as u32).to_le_bytes());
+                    write_framed_note(&note, handle.utf8_mode(), &mut buf)?;
+                    count += 1;
+                }
+                Err(nostrdb::Error::NotFound) => skipped += 1,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        buf[..4].copy_from_slice(&count.to_le_bytes());
+        buf[4..8].copy_from_slice(&skipped.to_le_bytes());
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Get notes by 32-byte event ID, in one call
+///
+/// As `getNotesByKeys`, with a bitmap after the header marking which ids
+/// were found: bit `i % 8` of byte `i / 8` is set if `ids[i]` was.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `ids` - Event IDs [id1:32][id2:32]...
+///
+/// # Returns
+/// Serialized notes: [count:4][skipped:4][found:(n+7)/8]([key:8][size:4][len:4][noteJson:len])*
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNotesByIds(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    ids: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let bytes = java_bytes_to_rust(env, &ids)?;
+        if bytes.len() % 32 != 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Event IDs must be 32 bytes each; the one at index {} has {} bytes",
+                bytes.len() / 32,
+                bytes.len() % 32
+            )));
+        }
+
+        let ids: Vec<&[u8; 32]> = bytes
+            .chunks_exact(32)
+            .map(|chunk| chunk.try_into().unwrap())
+            .collect();
+        let bitmap_len = ids.len().div_ceil(8);
+        let mut count: u32 = 0;
+        let mut buf = Vec::with_capacity(8 + bitmap_len + ids.len() * 512);
+        buf.resize(8 + bitmap_len, 0);
+        for (i, id) in ids.iter().enumerate() {
+            let key = match ndb.get_notekey_by_id(txn, id) {
+                Ok(key) => key,
+                Err(nostrdb::Error::NotFound) => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let note = ndb.get_note_by_key(txn, key)?;
+            buf[8 + i / 8] |= 1 << (i % 8);
+            buf.extend_from_slice(&key.as_u64().to_le_bytes());
+            buf.extend_from_slice(&(note.size() as u32).to_le_bytes());
+            write_framed_note(&note, handle.utf8_mode(), &mut buf)?;
+            count += 1;
+        }
+        let skipped = ids.len() as u32 - count;
+        buf[..4].copy_from_slice(&count.to_le_bytes());
+        buf[4..8].copy_from_slice(&skipped.to_le_bytes());
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+// ============================================================================
+// Note Fields
+// ============================================================================
+
+/// Get a note's kind by internal key
+///
+/// The field accessors read a single field, skipping the JSON
+/// serialization `getNoteByKey` does.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// The kind, or -1 if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteKind(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jlong {
+    with_exception(&mut env, -1, |_env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        Ok(find_note(&ndb, txn, note_key)?.map_or(-1, |note| note.kind() as jlong))
+    })
+}
+
+/// Get a note's creation timestamp by internal key
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// The Unix timestamp in seconds, or -1 if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteCreatedAt(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jlong {
+    with_exception(&mut env, -1, |_env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        Ok(find_note(&ndb, txn, note_key)?.map_or(-1, |note| note.created_at() as jlong))
+    })
+}
+
+/// Get a note's author public key by internal key
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// The 32-byte public key, or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNotePubkey(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        Ok(match find_note(&ndb, txn, note_key)? {
+            Some(note) => rust_bytes_to_java(env, note.pubkey()),
+            None => std::ptr::null_mut(),
+        })
+    })
+}
+
+/// Get a note's content by internal key
+///
+/// The bytes are copied straight from the stored note, with no JSON in
+/// between. Content that isn't valid UTF-8 is repaired with U+FFFD, or
+/// throws InvalidContentException in strict mode, as in `getNoteById`.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// The content as UTF-8 bytes, or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteContent(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        Ok(match find_note(&ndb, txn, note_key)? {
+            Some(note) => {
+                let content = note_content(&note, handle.utf8_mode())?;
+                rust_bytes_to_java(env, content.as_bytes())
+            }
+            None => std::ptr::null_mut(),
+        })
+    })
+}
+
+/// Get the length of a note's content by internal key
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// The content's length in UTF-8 bytes, or -1 if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteContentLength(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jint {
+    with_exception(&mut env, -1, |_env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        Ok(match find_note(&ndb, txn, note_key)? {
+            Some(note) => note_content(&note, handle.utf8_mode())?.len() as jint,
+            None => -1,
+        })
+    })
+}
+
+/// Get a note's signature by internal key
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// The 64-byte signature, or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteSig(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        Ok(match find_note(&ndb, txn, note_key)? {
+            Some(note) => rust_bytes_to_java(env, note.sig()),
+            None => std::ptr::null_mut(),
+        })
+    })
+}
+
+/// Get the size of a note as stored, by internal key
+///
+/// This is the length of nostrdb's note buffer (the bytes
+/// `getNoteRawByKey` returns), not of the note's JSON.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// The size in bytes, or -1 if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteSize(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jlong {
+    with_exception(&mut env, -1, |_env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        Ok(find_note(&ndb, txn, note_key)?.map_or(-1, |note| note.size() as jlong))
+    })
+}
+
+/// Get the number of tags on a note by internal key
+///
+/// With `noteTagLen` and `noteTagGet`, this walks a note's tags natively,
+/// without serializing the note.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// The tag count, or -1 if the note is not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteTagCount(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jint {
+    with_exception(&mut env, -1, |_env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        Ok(find_note(&ndb, txn, note_key)?.map_or(-1, |note| note.tags().count() as jint))
+    })
+}
+
+/// Get the number of elements in one of a note's tags
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+/// * `tag_index` - Index of the tag
+///
+/// # Returns
+/// The element count; throws NoSuchElementException if the note is not
+/// found and IndexOutOfBoundsException if the tag index is out of range
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteTagLen(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+    tag_index: jint,
+) -> jint {
+    with_exception(&mut env, -1, |_env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let note = ndb.get_note_by_key(txn, NoteKey::new(note_key as u64))?;
+
+        Ok(note_tag(&note, tag_index)?.count() as jint)
+    })
+}
+
+/// Get one element of one of a note's tags
+///
+/// nostrdb stores 64-character hex elements as 32-byte ids; these are
+/// hex-encoded again, as in note JSON, unless `raw_ids` is set.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+/// * `tag_index` - Index of the tag
+/// * `elem_index` - Index of the element in the tag
+/// * `raw_ids` - Return id elements as their 32 bytes instead of hex
+///
+/// # Returns
+/// The element as UTF-8 bytes (or 32 id bytes); throws
+/// NoSuchElementException if the note is not found and
+/// IndexOutOfBoundsException if an index is out of range
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteTagGet(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+    tag_index: jint,
+    elem_index: jint,
+    raw_ids: jboolean,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let note = ndb.get_note_by_key(txn, NoteKey::new(note_key as u64))?;
+        let tag = note_tag(&note, tag_index)?;
+
+        tag_elem_to_java(env, &tag, elem_index, raw_ids != JNI_FALSE)
+    })
+}
+
+/// Get the 32-byte values of a note's `e`, `p`, or `q` tags
+///
+/// For timeline code that needs the pubkeys a note mentions or the events
+/// it references without parsing its tags. Values nostrdb stored as ids
+/// are passed through; others are decoded from hex. Values that aren't 32
+/// bytes of hex (such as a `q` tag naming an address, or a tag with no
+/// value) are skipped and counted.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+/// * `tag_name` - "e", "p", or "q"
+///
+/// # Returns
+/// [count:4]([id:32])*[skipped:4] in tag order, or null if the note is not
+/// found; throws IllegalArgumentException for any other tag name
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteTagIds(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+    tag_name: JString,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let tag_name = java_string_to_rust(env, &tag_name)?;
+        if !matches!(tag_name.as_str(), "e" | "p" | "q") {
+            return Err(Error::InvalidArgument(format!(
+                "Tag name must be \"e\", \"p\", or \"q\", got \"{}\"",
+                tag_name
+            )));
+        }
+        let Some(note) = find_note(&ndb, txn, note_key)? else {
+            return Ok(std::ptr::null_mut());
+        };
+
+        let mut count: u32 = 0;
+        let mut skipped: u32 = 0;
+        let mut buf = vec![0; 4];
+        for tag in note.tags().iter() {
+            if tag.get_str(0) != Some(tag_name.as_str()) {
+                continue;
+            }
+            match mute::tag_id(&tag, 1) {
+                Some(id) => {
+                    buf.extend_from_slice(&id);
+                    count += 1;
+                }
+                None => skipped += 1,
+            }
+        }
+        buf[..4].copy_from_slice(&count.to_le_bytes());
+        buf.extend_from_slice(&skipped.to_le_bytes());
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Get the relays a note was seen on by internal key
+///
+/// Relays are recorded by `processEventFromRelay`; a note only ever ingested
+/// without one has none.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// String array of relay URLs, or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteRelays(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jobjectArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        match find_note(&ndb, txn, note_key)? {
+            Some(note) => {
+                let relays: Vec<String> = note.relays(txn).map(str::to_string).collect();
+                rust_strings_to_java(env, &relays)
+            }
+            None => Ok(std::ptr::null_mut()),
+        }
+    })
+}
+
+/// Get a note's content blocks by internal key
+///
+/// The blocks are nostrdb's parse of the content into text, mentions,
+/// hashtags, URLs, and invoices; together they cover the content
+/// contiguously. Mentions of a pubkey or event carry its 32-byte id.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// Serialized blocks: [count:4]([type:1][offset:4][length:4][target:1][id:32]?)*,
+/// where id is present unless target is 0; or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getNoteBlocks(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let Some(note) = find_note(&ndb, txn, note_key)? else {
+            return Ok(std::ptr::null_mut());
+        };
+
+        let key = NoteKey::new(note_key as u64);
+        let spans = blocks::note_blocks(&ndb, txn, &note, key, handle.utf8_mode())?;
+        let mut buf = Vec::with_capacity(4 + spans.len() * 42);
+        buf.extend_from_slice(&(spans.len() as u32).to_le_bytes());
+        for span in &spans {
+            span.write(&mut buf);
+        }
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+// ============================================================================
+// Note Handles
+// ============================================================================
+//
+// A note handle holds a note fetched once, for reading several fields
+// without serializing the note or looking it up again. It reads from the
+// transaction it was opened in and must be freed with `noteClose`; once
+// the transaction ends, its accessors throw IllegalStateException.
+
+/// Open a handle on a note by internal key
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// Pointer to the note handle, or 0 if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteOpen(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jlong {
+    with_exception(&mut env, 0, |_env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn: &'static TxnHandle =
+            unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        Ok(match find_note(&ndb, txn, note_key)? {
+            // SAFETY: `txn` is the handle behind `txn_ptr`
+            Some(note) => box_to_ptr(unsafe { NoteHandle::new(note, txn, handle.utf8_mode()) }),
+            None => 0,
+        })
+    })
+}
+
+/// Open a handle on a note by 32-byte event ID
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `event_id` - 32-byte event ID
+///
+/// # Returns
+/// Pointer to the note handle, or 0 if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteOpenById(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    event_id: JByteArray,
+) -> jlong {
+    with_exception(&mut env, 0, |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn: &'static TxnHandle =
+            unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let id = java_bytes_to_32(env, &event_id)?;
+
+        match ndb.get_note_by_id(txn, &id) {
+            // SAFETY: `txn` is the handle behind `txn_ptr`
+            Ok(note) => Ok(box_to_ptr(unsafe {
+                NoteHandle::new(note, txn, handle.utf8_mode())
+            })),
+            Err(nostrdb::Error::NotFound) => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Get the internal key of a handle's note
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteHandleKey(
+    mut env: JNIEnv,
+    _class: JClass,
+    note_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, 0, |_env| {
+        let note = unsafe { util::ptr_to_ref::<NoteHandle>(note_ptr, "note")? }.note()?;
+        Ok(note.key().map_or(0, |key| key.as_u64() as jlong))
+    })
+}
+
+/// Get the 32-byte event ID of a handle's note
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteHandleId(
+    mut env: JNIEnv,
+    _class: JClass,
+    note_ptr: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let note = unsafe { util::ptr_to_ref::<NoteHandle>(note_ptr, "note")? }.note()?;
+        Ok(rust_bytes_to_java(env, note.id()))
+    })
+}
+
+/// Get the 32-byte author public key of a handle's note
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteHandlePubkey(
+    mut env: JNIEnv,
+    _class: JClass,
+    note_ptr: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let note = unsafe { util::ptr_to_ref::<NoteHandle>(note_ptr, "note")? }.note()?;
+        Ok(rust_bytes_to_java(env, note.pubkey()))
+    })
+}
+
+/// Get the 64-byte signature of a handle's note
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteHandleSig(
+    mut env: JNIEnv,
+    _class: JClass,
+    note_ptr: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let note = unsafe { util::ptr_to_ref::<NoteHandle>(note_ptr, "note")? }.note()?;
+        Ok(rust_bytes_to_java(env, note.sig()))
+    })
+}
+
+/// Get the kind of a handle's note
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteHandleKind(
+    mut env: JNIEnv,
+    _class: JClass,
+    note_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, -1, |_env| {
+        let note = unsafe { util::ptr_to_ref::<NoteHandle>(note_ptr, "note")? }.note()?;
+        Ok(note.kind() as jlong)
+    })
+}
+
+/// Get the creation timestamp of a handle's note
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteHandleCreatedAt(
+    mut env: JNIEnv,
+    _class: JClass,
+    note_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, -1, |_env| {
+        let note = unsafe { util::ptr_to_ref::<NoteHandle>(note_ptr, "note")? }.note()?;
+        Ok(note.created_at() as jlong)
+    })
+}
+
+/// Get the content of a handle's note as UTF-8 bytes
+///
+/// Content that isn't valid UTF-8 is handled as in `getNoteContent`.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteHandleContent(
+    mut env: JNIEnv,
+    _class: JClass,
+    note_ptr: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NoteHandle>(note_ptr, "note")? };
+        let note = handle.note()?;
+        let content = note_content(&note, handle.utf8_mode())?;
+        Ok(rust_bytes_to_java(env, content.as_bytes()))
+    })
+}
+
+/// Get the number of tags on a handle's note
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteHandleTagCount(
+    mut env: JNIEnv,
+    _class: JClass,
+    note_ptr: jlong,
+) -> jint {
+    with_exception(&mut env, -1, |_env| {
+        let note = unsafe { util::ptr_to_ref::<NoteHandle>(note_ptr, "note")? }.note()?;
+        Ok(note.tags().count() as jint)
+    })
+}
+
+/// Get the number of elements in one of a handle's note's tags
+///
+/// Throws IndexOutOfBoundsException if the tag index is out of range.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteHandleTagLen(
+    mut env: JNIEnv,
+    _class: JClass,
+    note_ptr: jlong,
+    tag_index: jint,
+) -> jint {
+    with_exception(&mut env, -1, |_env| {
+        let note = unsafe { util::ptr_to_ref::<NoteHandle>(note_ptr, "note")? }.note()?;
+        Ok(note_tag(&note, tag_index)?.count() as jint)
+    })
+}
+
+/// Get one element of one of a handle's note's tags
+///
+/// As `noteTagGet`: id elements are hex-encoded unless `raw_ids` is set,
+/// and out-of-range indices throw IndexOutOfBoundsException.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteHandleTagGet(
+    mut env: JNIEnv,
+    _class: JClass,
+    note_ptr: jlong,
+    tag_index: jint,
+    elem_index: jint,
+    raw_ids: jboolean,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let note = unsafe { util::ptr_to_ref::<NoteHandle>(note_ptr, "note")? }.note()?;
+        let tag = note_tag(&note, tag_index)?;
+
+        tag_elem_to_java(env, &tag, elem_index, raw_ids != JNI_FALSE)
+    })
+}
+
+/// Free a note handle
+///
+/// May be called after the handle's transaction has ended.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteClose(
+    mut env: JNIEnv,
+    _class: JClass,
+    note_ptr: jlong,
+) {
+    with_exception(&mut env, (), |_env| unsafe {
+        util::drop_ptr::<NoteHandle>(note_ptr, "note")
+    });
+}
+
+// ============================================================================
+// Query Execution
+// ============================================================================
+
+/// Execute query with filter
+///
+/// The filter's own limit (set with `filterLimit`) also applies: a
+/// non-negative `limit` is capped by it, and a negative `limit` defers to it
+/// entirely, returning every match if the filter has no limit.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of results, or negative for the filter's limit
+///
+/// # Returns
+/// Serialized results: [count:4][key1:8][key2:8]...
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_query(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        let results = query_filter(&ndb, txn, filter, limit)?;
+        Ok(rust_bytes_to_java(env, &serialize_query_results(&results)))
+    })
+}
+
+/// Execute query with filter, returning the note keys as a long[]
+///
+/// Same results, in the same order, as `query`, without the byte framing.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of results, or negative for the filter's limit
+///
+/// # Returns
+/// The note keys of the results
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryKeys(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+) -> jlongArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        let keys: Vec<jlong> = query_filter(&ndb, txn, filter, limit)?
+            .iter()
+            .map(|result| result.note_key.as_u64() as jlong)
+            .collect();
+        rust_longs_to_java(env, &keys)
+    })
+}
+
+/// Execute query with filter, writing the results into a direct ByteBuffer
+///
+/// Writes the same `[count:4][key1:8]...` framing as `query`, starting at
+/// the beginning of the buffer regardless of its position, so timelines can
+/// reuse one buffer instead of allocating a byte[] per query. Nothing is
+/// written if the buffer is too small.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of results, or negative for the filter's limit
+/// * `buffer` - Direct ByteBuffer to write into
+///
+/// # Returns
+/// The number of bytes written, or the negated required size if the
+/// buffer's capacity is too small
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryInto(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+    buffer: JByteBuffer,
+) -> jint {
+    with_exception(&mut env, 0, |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        if buffer.is_null() {
+            return Err(Error::NullPointer("buffer"));
+        }
+        let (address, capacity) = match env.get_direct_buffer_address(&buffer) {
+            Ok(address) => (address, env.get_direct_buffer_capacity(&buffer)?),
+            Err(_) => {
+                return Err(Error::InvalidArgument(
+                    "Buffer must be a direct ByteBuffer".to_string(),
+                ))
+            }
+        };
+
+        let results = query_filter(&ndb, txn, filter, limit)?;
+        let required = 4 + results.len() * 8;
+        if required > capacity {
+            return Ok(-(required as jint));
+        }
+
+        // SAFETY: the address is valid for `capacity` bytes while the Java
+        // buffer is reachable, which it is for the duration of this call
+        let out = unsafe { std::slice::from_raw_parts_mut(address, required) };
+        out[..4].copy_from_slice(&(results.len() as u32).to_le_bytes());
+        for (chunk, result) in out[4..].chunks_exact_mut(8).zip(&results) {
+            chunk.copy_from_slice(&result.note_key.as_u64().to_le_bytes());
+        }
+        Ok(required as jint)
+    })
+}
+
+/// Execute query with filter, in a chosen order
+///
+/// `order` selects both which matches are returned and their order:
+/// `ORDER_NEWEST_FIRST` (0) and `ORDER_NEWEST_REVERSED` (1) return the
+/// newest `limit` matches, newest or oldest first, while
+/// `ORDER_OLDEST_FIRST` (2) returns the oldest `limit` matches, oldest
+/// first. Ascending results are reversed natively; notes with the same
+/// `created_at` keep a consistent relative order. `limit` is combined with
+/// the filter's own limit as in `query`.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of results, or negative for the filter's limit
+/// * `order` - One of the `ORDER_*` values
+///
+/// # Returns
+/// The note keys of the results. Throws IllegalArgumentException for an
+/// unknown order.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryOrdered(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+    order: jint,
+) -> jlongArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        let mut results = match order {
+            ORDER_NEWEST_FIRST | ORDER_NEWEST_REVERSED => query_filter(&ndb, txn, filter, limit)?,
+            ORDER_OLDEST_FIRST => {
+                let mut all = query_accepted(&ndb, txn, filter, None, |result| {
+                    Ok(filter.accepts_kind(result.note.kind() as u64))
+                })?;
+                if let Some(limit) = effective_limit(limit, filter) {
+                    all.drain(..all.len().saturating_sub(limit as usize));
+                }
+                all
+            }
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "Unknown query order {}",
+                    order
+                )))
+            }
+        };
+        if order != ORDER_NEWEST_FIRST {
+            results.reverse();
+        }
+
+        let keys: Vec<jlong> = results
+            .iter()
+            .map(|result| result.note_key.as_u64() as jlong)
+            .collect();
+        rust_longs_to_java(env, &keys)
+    })
+}
+
+/// Execute query with filter, returning event ids instead of note keys
+///
+/// The ids are read from the notes the query loaded within the
+/// transaction, so no per-result lookup is needed and a result cannot
+/// vanish between the query and reading its id. `limit` is applied as in
+/// `query`.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of results, or negative for the filter's limit
+///
+/// # Returns
+/// Serialized ids: [count:4][id1:32][id2:32]...
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryIds(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        let results = query_filter(&ndb, txn, filter, limit)?;
+        let mut buf = Vec::with_capacity(4 + results.len() * 32);
+        buf.extend_from_slice(&(results.len() as u32).to_le_bytes());
+        for result in &results {
+            buf.extend_from_slice(result.note.id());
+        }
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Find the newest note matching a filter for each of a list of authors
+///
+/// Runs one query per author, restricted to that author, all within the
+/// caller's transaction. Authors without a match, including those excluded
+/// by the filter's own authors, are omitted; repeated authors are reported
+/// once. The filter's own limit is ignored. Results are ordered newest
+/// first, with ties in the order the authors were given.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `authors` - Concatenated 32-byte author pubkeys
+///
+/// # Returns
+/// Serialized results: [count:4]([pubkey:32][key:8][created_at:8])*
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryLatestPerAuthor(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    authors: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        let authors = java_bytes_to_32_vec(env, &authors)?;
+
+        let mut seen = HashSet::new();
+        let mut latest = Vec::new();
+        for author in &authors {
+            if !seen.insert(author) {
+                continue;
+            }
+            let Some(author_filter) = filter.for_author(author)? else {
+                continue;
+            };
+            if let Some(result) = query_filter(&ndb, txn, &author_filter, 1)?.first() {
+                latest.push((author, result.note_key.as_u64(), result.note.created_at()));
+            }
+        }
+        latest.sort_by_key(|&(_, _, created_at)| Reverse(created_at));
+
+        let mut buf = Vec::with_capacity(4 + latest.len() * 48);
+        buf.extend_from_slice(&(latest.len() as u32).to_le_bytes());
+        for (author, key, created_at) in &latest {
+            buf.extend_from_slice(*author);
+            buf.extend_from_slice(&key.to_le_bytes());
+            buf.extend_from_slice(&created_at.to_le_bytes());
+        }
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Execute query with filter, returning the matching notes themselves
+///
+/// Saves a `getNoteByKey` call per result: every note is serialized in
+/// this call, into one buffer. `limit` is applied as in `query`.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of results, or negative for the filter's limit
+///
+/// # Returns
+/// Serialized notes: [count:4]([len:4][noteJson:len])*
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryNotes(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        let results = query_filter(&ndb, txn, filter, limit)?;
+        let notes = serialize_notes(&results, handle.utf8_mode())?;
+        Ok(rust_bytes_to_java(env, &notes))
+    })
+}
+
+/// Execute query with filter, returning the matching notes as CBOR
+///
+/// As `queryNotes`, with each note serialized as in `getNoteByIdCbor`.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of results, or negative for the filter's limit
+///
+/// # Returns
+/// Serialized notes: [count:4]([len:4][noteCbor:len])*
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryNotesCbor(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        let results = query_filter(&ndb, txn, filter, limit)?;
+        let mut buf = Vec::with_capacity(4 + results.len() * 384);
+        buf.extend_from_slice(&(results.len() as u32).to_le_bytes());
+        for result in &results {
+            write_framed(&result.note, handle.utf8_mode(), &mut buf, write_note_cbor)?;
+        }
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Execute query with filter, returning the raw bytes of the matching notes
+///
+/// As `queryNotes`, with each note in the layout described at
+/// `getNoteRawByKey` instead of JSON.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of results, or negative for the filter's limit
+///
+/// # Returns
+/// Serialized notes: [count:4]([len:4][rawNote:len])*
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryNotesRaw(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        let results = query_filter(&ndb, txn, filter, limit)?;
+        let mut buf = Vec::with_capacity(4 + results.len() * 512);
+        buf.extend_from_slice(&(results.len() as u32).to_le_bytes());
+        for result in &results {
+            let note = note_bytes(&result.note);
+            buf.extend_from_slice(&(note.len() as u32).to_le_bytes());
+            buf.extend_from_slice(note);
+        }
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Count notes matching a filter
+///
+/// Matches are counted natively, so no note keys cross into Java. The
+/// filter's own limit caps the count; without one every match is counted.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+///
+/// # Returns
+/// The number of matching notes
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryCount(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, 0, |_env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        Ok(query_filter(&ndb, txn, filter, -1)?.len() as jlong)
+    })
+}
+
+/// Execute one query with several filters (OR'd together, as in a REQ)
+///
+/// Results are merged and limited natively. If a filter has a kind range
+/// checked against results, each filter is queried on its own and the
+/// merged results are ordered newest first.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptrs` - Pointers to the Filters
+/// * `limit` - Maximum number of results across all filters
+///
+/// # Returns
+/// Serialized results: [count:4][key1:8][key2:8]...
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryMulti(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptrs: JLongArray,
+    limit: jint,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filters = unsafe { java_filters(env, &filter_ptrs)? };
+
+        if filters.iter().all(|filter| filter.kind_range().is_none()) {
+            let filters: Vec<Filter> = filters.iter().map(|f| f.filter().clone()).collect();
+            let results = ndb.query(txn, &filters, limit)?;
+            return Ok(rust_bytes_to_java(env, &serialize_query_results(&results)));
+        }
+
+        let limit = limit.max(0);
+        let mut seen = HashSet::new();
+        let mut merged = Vec::new();
+        for filter in filters {
+            let results = query_accepted(&ndb, txn, filter, Some(limit), |result| {
+                Ok(filter.accepts_kind(result.note.kind() as u64))
+            })?;
+            merged.extend(
+                results
+                    .into_iter()
+                    .filter(|result| seen.insert(result.note_key.as_u64())),
+            );
+        }
+        merged.sort_by_key(|result| Reverse(result.note.created_at()));
+        merged.truncate(limit as usize);
+        Ok(rust_bytes_to_java(env, &serialize_query_results(&merged)))
+    })
+}
+
+/// Execute several filters, merging their results without duplicates
+///
+/// Unlike `queryMulti`, each filter is queried on its own, honouring its
+/// own limit and kind range. A note matched by several filters is
+/// returned once, and the merged results are ordered by `created_at`
+/// descending, ties by note key descending, before `limit` is applied.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptrs` - Pointers to the Filters
+/// * `limit` - Maximum number of merged results, or negative for no limit
+///   beyond the filters' own
+///
+/// # Returns
+/// Serialized results: [count:4][key1:8][key2:8]...
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryMergedUnique(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptrs: JLongArray,
+    limit: jint,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filters = unsafe { java_filters(env, &filter_ptrs)? };
+
+        // The newest `limit` merged results are among each filter's newest `limit`
+        let mut seen = HashSet::new();
+        let mut merged = Vec::new();
+        for filter in filters {
+            for result in query_filter(&ndb, txn, filter, limit)? {
+                let key = result.note_key.as_u64();
+                if seen.insert(key) {
+                    merged.push((result.note.created_at(), key));
+                }
+            }
+        }
+        merged.sort_unstable_by(|a, b| b.cmp(a));
+        if limit >= 0 {
+            merged.truncate(limit as usize);
+        }
+        let keys: Vec<u64> = merged.iter().map(|&(_, key)| key).collect();
+        Ok(rust_bytes_to_java(env, &serialize_note_keys(&keys)))
+    })
+}
+
+/// Execute query, keeping only notes accepted by a Java predicate
+///
+/// Candidates are fetched in batches that double until `limit` notes are
+/// accepted or the filter has no more matches; each candidate is passed to
+/// `predicate.test(byte[] noteJson)` on the calling thread. An exception
+/// thrown by the predicate aborts the query and propagates to the caller.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of accepted results
+/// * `predicate` - A `NotePredicate`
+///
+/// # Returns
+/// Serialized accepted results: [count:4][key1:8][key2:8]...
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryWithPredicate(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+    predicate: JObject,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        if predicate.is_null() {
+            return Err(Error::NullPointer("predicate"));
+        }
+        if limit <= 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Limit must be positive, got {}",
+                limit
+            )));
+        }
+
+        let accepted = query_accepted(&ndb, txn, filter, Some(limit), |result| {
+            if !filter.accepts_kind(result.note.kind() as u64) {
+                return Ok(false);
+            }
+            let json = serialize_note(&result.note, handle.utf8_mode())?;
+            let json = env.byte_array_from_slice(&json)?;
+            let keep = env
+                .call_method(&predicate, "test", "([B)Z", &[JValue::Object(&json)])?
+                .z()?;
+            env.delete_local_ref(json)?;
+            Ok(keep)
+        })?;
+        Ok(rust_bytes_to_java(env, &serialize_query_results(&accepted)))
+    })
+}
+
+/// Execute query, excluding notes hidden by a mute list
+///
+/// Muted notes are skipped while results are collected, so `limit` counts
+/// only visible notes; as in `query`, it is combined with the filter's own
+/// limit and a negative value defers to it.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of visible results, or negative for the filter's limit
+/// * `muted_authors` - Muted pubkeys [pubkey1:32][pubkey2:32]..., or null
+/// * `muted_ids` - Muted event ids [id1:32][id2:32]..., or null
+/// * `mute_threads` - Also exclude notes whose `p` / `e` tags reference a
+///   muted author / event
+///
+/// # Returns
+/// Serialized results: [count:4][key1:8][key2:8]...
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryExcluding(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+    muted_authors: JByteArray,
+    muted_ids: JByteArray,
+    mute_threads: jboolean,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        let mutes = MuteList::new(
+            java_optional_32_vec(env, &muted_authors)?,
+            java_optional_32_vec(env, &muted_ids)?,
+            mute_threads != JNI_FALSE,
+        );
+
+        let limit = effective_limit(limit, filter);
+        let results = query_accepted(&ndb, txn, filter, limit, |result| {
+            Ok(filter.accepts_kind(result.note.kind() as u64) && !mutes.hides(&result.note))
+        })?;
+        Ok(rust_bytes_to_java(env, &serialize_query_results(&results)))
+    })
+}
+
+/// Execute query, excluding notes hidden by an author's stored mute list
+///
+/// Reads the author's newest mute list (NIP-51, kind 10000) and excludes
+/// its muted authors, events, and hashtags, and notes whose content
+/// contains a muted word, as `queryExcluding` does. Without a stored mute
+/// list nothing is excluded.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of visible results, or negative for the filter's limit
+/// * `pubkey` - 32-byte public key of the mute list's author
+///
+/// # Returns
+/// Serialized results: [count:4][key1:8][key2:8]...
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryExcludingMuteList(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+    pubkey: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        let pk = java_bytes_to_32(env, &pubkey)?;
+
+        let list_filter = mute::mute_list_filter(&pk);
+        let lists = ndb.query(txn, std::slice::from_ref(&list_filter), 1)?;
+        let mutes = match lists.first() {
+            Some(list) => MuteList::from_entries(&MuteEntries::parse(&list.note), false),
+            None => MuteList::new(Vec::new(), Vec::new(), false),
+        };
+
+        let limit = effective_limit(limit, filter);
+        let results = query_accepted(&ndb, txn, filter, limit, |result| {
+            Ok(filter.accepts_kind(result.note.kind() as u64) && !mutes.hides(&result.note))
+        })?;
+        Ok(rust_bytes_to_java(env, &serialize_query_results(&results)))
+    })
+}
+
+/// Execute query, excluding notes that have expired (NIP-40)
+///
+/// Expired notes are skipped while results are collected, so `limit`
+/// counts only live notes; as in `query`, it is combined with the filter's
+/// own limit and a negative value defers to it.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of live results, or negative for the filter's limit
+/// * `now` - Current time in Unix seconds (not negative)
+///
+/// # Returns
+/// Serialized results: [count:4][key1:8][key2:8]...
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryUnexpired(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+    now: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        let results = query_unexpired(&ndb, txn, filter, limit, now)?;
+        Ok(rust_bytes_to_java(env, &serialize_query_results(&results)))
+    })
+}
+
+/// Execute query, returning the notes that have not expired (NIP-40)
+///
+/// As `queryUnexpired`, with the notes serialized as in `queryNotes`.
+///
+/// # Returns
+/// Serialized notes: [count:4]([len:4][noteJson:len])*
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryNotesUnexpired(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+    now: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        let results = query_unexpired(&ndb, txn, filter, limit, now)?;
+        let notes = serialize_notes(&results, handle.utf8_mode())?;
+        Ok(rust_bytes_to_java(env, &notes))
+    })
+}
+
+/// Find the replies in a thread, split into direct and nested replies
+///
+/// Fetches the newest `limit` text notes whose `e` tags reference the
+/// root, then groups them by the event each replies to (see the `thread`
+/// module): the root itself, or anything else. Each group keeps query
+/// order, newest first.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `root_id` - 32-byte id of the root event
+/// * `limit` - Maximum number of replies in total (positive)
+///
+/// # Returns
+/// Serialized keys: [directCount:4][key:8]*[otherCount:4][key:8]*
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryThread(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    root_id: JByteArray,
+    limit: jint,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let root = java_bytes_to_32(env, &root_id)?;
+        if limit <= 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Limit must be positive, got {}",
+                limit
+            )));
+        }
+
+        let filter = thread::replies_filter(&root);
+        let results = ndb.query(txn, std::slice::from_ref(&filter), limit)?;
+        let (direct, nested): (Vec<_>, Vec<_>) = results
+            .iter()
+            .partition(|result| thread::parent(&result.note) == Some(root));
+
+        let mut buf = Vec::with_capacity(8 + results.len() * 8);
+        for group in [direct, nested] {
+            buf.extend_from_slice(&(group.len() as u32).to_le_bytes());
+            for result in group {
+                buf.extend_from_slice(&result.note_key.as_u64().to_le_bytes());
+            }
+        }
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Total the zaps a note has received
+///
+/// Tallies every zap receipt (kind 9735) referencing the note, reading
+/// each amount as described in the `zap` module.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_id` - 32-byte id of the zapped note
+///
+/// # Returns
+/// Serialized total: [millisats:8][count:4][unparsable:4]
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_sumZaps(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_id: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let id = java_bytes_to_32(env, &note_id)?;
+
+        let filter = zap::receipts_filter(&id);
+        let receipts = query_accepted(&ndb, txn, &filter, None, |_| Ok(true))?;
+        let mut total = zap::ZapTotal::default();
+        for receipt in &receipts {
+            total.add(&receipt.note);
+        }
+
+        let mut buf = Vec::with_capacity(16);
+        total.write(&mut buf);
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Count the reposts and quotes of a note
+///
+/// Reposts are kind 6 notes referencing it; quotes are text notes that
+/// reference it in a `q` tag or a "mention" `e` tag (see the `repost`
+/// module). Replies are not counted.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_id` - 32-byte id of the reposted note
+///
+/// # Returns
+/// Serialized counts: [reposts:4][quotes:4]
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_countReposts(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_id: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let id = java_bytes_to_32(env, &note_id)?;
+
+        let reposts = query_accepted(&ndb, txn, &repost::reposts_filter(&id), None, |_| Ok(true))?;
+        // A quote can match both candidate filters; count it once
+        let mut quotes = HashSet::new();
+        for filter in &repost::quote_candidate_filters(&id) {
+            let found = query_accepted(&ndb, txn, filter, None, |result| {
+                Ok(repost::quotes(&result.note, &id))
+            })?;
+            quotes.extend(found.iter().map(|result| result.note_key.as_u64()));
+        }
+
+        let mut buf = Vec::with_capacity(8);
+        buf.extend_from_slice(&(reposts.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&(quotes.len() as u32).to_le_bytes());
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Execute query with filter, one page at a time
+///
+/// Pages are ordered newest first, with ties on `created_at` broken by note
+/// key, and never overlap or leave gaps. The filter's own limit is ignored.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `page_size` - Maximum number of results in the page (positive)
+/// * `cursor` - Cursor returned with the previous page, or null for the
+///   first page
+///
+/// # Returns
+/// Serialized page: [count:4][key1:8][key2:8]...[cursor:16], where the
+/// cursor is left out after the last page
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryPage(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    page_size: jint,
+    cursor: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        if page_size <= 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Page size must be positive, got {}",
+                page_size
+            )));
+        }
+        let after = if cursor.is_null() {
+            None
+        } else {
+            Some(Position::decode(&java_bytes_to_rust(env, &cursor)?)?)
+        };
+
+        let page = page::query_page(&ndb, txn, filter, page_size, after)?;
+        let mut buf = serialize_note_keys(&page.keys);
+        if let Some(next) = page.next {
+            buf.extend_from_slice(&next.encode());
+        }
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Start a cursor over the results of a query
+///
+/// The cursor reads from the given transaction and must be freed with
+/// `queryEnd`; once the transaction ends, `queryNext` throws
+/// IllegalStateException. The filter's own limit caps the total number of
+/// results.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter (copied; it may be closed afterwards)
+///
+/// # Returns
+/// Pointer to the cursor
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryBegin(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, 0, |_env| {
+        unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        Ok(box_to_ptr(QueryCursor::new(txn_ptr, txn, filter)))
+    })
+}
+
+/// Fetch the next page of a cursor's results
+///
+/// # Arguments
+/// * `cursor_ptr` - Pointer to the cursor
+/// * `batch_size` - Maximum number of results to return (positive)
+///
+/// # Returns
+/// Serialized results: [count:4][key1:8][key2:8]..., with a count of 0
+/// once every result was returned
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryNext(
+    mut env: JNIEnv,
+    _class: JClass,
+    cursor_ptr: jlong,
+    batch_size: jint,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let cursor = unsafe { util::ptr_to_mut::<QueryCursor>(cursor_ptr, "cursor")? };
+        if batch_size <= 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Batch size must be positive, got {}",
+                batch_size
+            )));
+        }
+
+        let keys = cursor.next(batch_size as usize)?;
+        Ok(rust_bytes_to_java(env, &serialize_note_keys(&keys)))
+    })
+}
+
+/// Free a cursor
+///
+/// May be called after the cursor's transaction has ended.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryEnd(
+    mut env: JNIEnv,
+    _class: JClass,
+    cursor_ptr: jlong,
+) {
+    with_exception(&mut env, (), |_env| unsafe {
+        util::drop_ptr::<QueryCursor>(cursor_ptr, "cursor")
+    });
+}
+
+/// Create a cancellation token for `queryCancellable`,
+/// `queryNotesCancellable`, and `processEventsFromFile`
+///
+/// # Returns
+/// Pointer to the token, to be freed with `cancelTokenFree`
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_cancelTokenNew(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jlong {
+    catch_panic(0, || box_to_ptr(CancelToken::new()))
+}
+
+/// Cancel every query using a token
+///
+/// Safe to call from any thread, including while a query using the token
+/// runs on another.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_cancelTokenCancel(
+    mut env: JNIEnv,
+    _class: JClass,
+    token_ptr: jlong,
+) {
+    with_exception(&mut env, (), |_env| {
+        unsafe { util::ptr_to_ref::<CancelToken>(token_ptr, "cancel token")? }.cancel();
+        Ok(())
+    });
+}
+
+/// Check if a token has been cancelled
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_cancelTokenIsCancelled(
+    mut env: JNIEnv,
+    _class: JClass,
+    token_ptr: jlong,
+) -> jboolean {
+    with_exception(&mut env, JNI_FALSE, |_env| {
+        let token = unsafe { util::ptr_to_ref::<CancelToken>(token_ptr, "cancel token")? };
+        Ok(if token.is_cancelled() {
+            JNI_TRUE
+        } else {
+            JNI_FALSE
+        })
+    })
+}
+
+/// Free a cancellation token
+///
+/// Must not be called while a query is using the token.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_cancelTokenFree(
+    mut env: JNIEnv,
+    _class: JClass,
+    token_ptr: jlong,
+) {
+    with_exception(&mut env, (), |_env| unsafe {
+        util::drop_ptr::<CancelToken>(token_ptr, "cancel token")
+    });
+}
+
+/// Execute query with filter, stopping early if a token is cancelled
+///
+/// Matches are fetched in pages, newest first, and the token is checked
+/// between pages and between results. `limit` is applied as in `query`.
+/// Notes sharing a `created_at` may come out in a different order than
+/// from `query`.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of results, or negative for the filter's limit
+/// * `token_ptr` - Pointer to the cancellation token
+///
+/// # Returns
+/// Serialized results: [count:4][key1:8][key2:8]...[cancelled:1], where the
+/// last byte is 1 if the query was cancelled and the results are partial
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryCancellable(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+    token_ptr: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        let token = unsafe { util::ptr_to_ref::<CancelToken>(token_ptr, "cancel token")? };
+
+        let limit = effective_limit(limit, filter).map(|limit| limit as usize);
+        let mut keys = Vec::new();
+        let cancelled = cancel::for_each_key(&ndb, txn, filter, limit, token, |key| {
+            keys.push(key);
+            Ok(())
+        })?;
+        let mut buf = serialize_note_keys(&keys);
+        buf.push(cancelled as u8);
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Execute query with filter, returning the matching notes themselves and
+/// stopping early if a token is cancelled
+///
+/// The token is also checked between serialized notes. Otherwise as
+/// `queryCancellable`.
+///
+/// # Returns
+/// Serialized notes: [count:4]([len:4][noteJson:len])*[cancelled:1]
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryNotesCancellable(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    filter_ptr: jlong,
+    limit: jint,
+    token_ptr: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        let token = unsafe { util::ptr_to_ref::<CancelToken>(token_ptr, "cancel token")? };
+
+        let limit = effective_limit(limit, filter).map(|limit| limit as usize);
+        let mut buf = Vec::new();
+        let utf8 = handle.utf8_mode();
+        let cancelled = cancel::write_notes(&ndb, txn, filter, limit, token, utf8, &mut buf)?;
+        buf.push(cancelled as u8);
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+// ============================================================================
+// Filter Building
+// ============================================================================
+
+/// Consume a builder handle and return a new one for `f(builder)`
+fn consume_builder(
+    filter_ptr: jlong,
+    f: impl FnOnce(FilterBuilder) -> FilterBuilder,
+) -> Result<jlong> {
+    let handle = unsafe { util::take_ptr::<BuilderHandle>(filter_ptr, "filter builder")? };
+    Ok(box_to_ptr(handle.map(f)))
+}
+
+/// Create new filter builder
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterNew(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jlong {
+    catch_panic(0, || box_to_ptr(BuilderHandle::new()))
+}
+
+/// Add kinds to filter
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the FilterBuilder
+/// * `kinds` - Serialized kinds: [kind1:4][kind2:4]...
+///
+/// # Returns
+/// New filter pointer (old one is consumed). Throws
+/// IllegalArgumentException if the length is not a multiple of 4.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterKinds(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    kinds: JByteArray,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |env| {
+        let kinds = java_kinds(env, &kinds)?;
+        consume_builder(filter_ptr, |filter| filter.kinds(kinds))
+    })
+}
+
+/// Add kinds to filter from an int[]
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the FilterBuilder
+/// * `kinds` - The kinds to match
+///
+/// # Returns
+/// New filter pointer (old one is consumed). Throws
+/// IllegalArgumentException if a kind is negative.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterKindsInt(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    kinds: JIntArray,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |env| {
+        let kinds = java_int_kinds(env, &kinds)?;
+        consume_builder(filter_ptr, |filter| filter.kinds(kinds))
+    })
+}
+
+/// Add authors to filter (array of 32-byte pubkeys)
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the FilterBuilder
+/// * `authors` - Serialized authors: [pubkey1:32][pubkey2:32]...
+///
+/// # Returns
+/// New filter pointer (old one is consumed). Throws
+/// IllegalArgumentException if the length is not a multiple of 32.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterAuthors(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    authors: JByteArray,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |env| {
+        let authors = java_bytes_to_32_vec(env, &authors)?;
+        consume_builder(filter_ptr, |filter| filter.authors(authors.iter()))
+    })
+}
+
+/// Add authors to filter from 64-character hex pubkeys
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the FilterBuilder
+/// * `hex_pubkeys` - Hex-encoded pubkeys (either case)
+///
+/// # Returns
+/// New filter pointer (old one is consumed). Throws
+/// IllegalArgumentException naming the index of a malformed pubkey.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterAuthorsHex(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    hex_pubkeys: JObjectArray,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |env| {
+        let authors = java_hex_32_vec(env, &hex_pubkeys, "pubkey")?;
+        consume_builder(filter_ptr, |filter| filter.authors(authors.iter()))
+    })
+}
+
+/// Add event IDs to filter (array of 32-byte ids)
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the FilterBuilder
+/// * `ids` - Serialized ids: [id1:32][id2:32]...
+///
+/// # Returns
+/// New filter pointer (old one is consumed). Throws
+/// IllegalArgumentException if the length is not a multiple of 32.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterIds(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    ids: JByteArray,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |env| {
+        // Validate before taking ownership so the builder survives an error
+        let ids = java_bytes_to_32_vec(env, &ids)?;
+        consume_builder(filter_ptr, |filter| filter.ids(ids.iter()))
+    })
+}
+
+/// Add referenced event IDs to filter (`#e` tag, array of 32-byte ids)
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the FilterBuilder
+/// * `ids` - Serialized ids: [id1:32][id2:32]...
+///
+/// # Returns
+/// New filter pointer (old one is consumed). Throws
+/// IllegalArgumentException if the length is not a multiple of 32.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterEvents(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    ids: JByteArray,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |env| {
+        let ids = java_bytes_to_32_vec(env, &ids)?;
+        consume_builder(filter_ptr, |filter| filter.events(ids.iter()))
+    })
+}
+
+/// Add referenced pubkeys to filter (`#p` tag, array of 32-byte pubkeys)
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the FilterBuilder
+/// * `pubkeys` - Serialized pubkeys: [pubkey1:32][pubkey2:32]...
+///
+/// # Returns
+/// New filter pointer (old one is consumed). Throws
+/// IllegalArgumentException if the length is not a multiple of 32.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterPubkeys(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    pubkeys: JByteArray,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |env| {
+        let pubkeys = java_bytes_to_32_vec(env, &pubkeys)?;
+        consume_builder(filter_ptr, |filter| filter.pubkeys(pubkeys.iter()))
+    })
+}
+
+/// Add tag filter
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the FilterBuilder
+/// * `tag_name` - Single-character tag name (e.g., "d", "p", "e")
+/// * `tag_values` - Array of tag values
+///
+/// # Returns
+/// New filter pointer (old one is consumed)
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterTag(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    tag_name: JString,
+    tag_values: JObjectArray,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |env| {
+        let (tag_char, values) = java_tag_filter(env, &tag_name, &tag_values)?;
+        let value_refs: Vec<&str> = values.iter().map(|s| s.as_str()).collect();
+        consume_builder(filter_ptr, |filter| filter.tags(value_refs, tag_char))
+    })
+}
+
+/// Add hashtags to filter (`#t` tag, normalized per NIP-24)
+///
+/// Each value has a leading '#' removed and is lowercased; empty values and
+/// duplicates are skipped.
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the FilterBuilder
+/// * `tags` - Hashtags as typed, e.g. "#Bitcoin"
+///
+/// # Returns
+/// New filter pointer (old one is consumed). Throws
+/// IllegalArgumentException if every value is empty after normalization.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterHashtags(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    tags: JObjectArray,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |env| {
+        let tags = filter::normalize_hashtags(&java_strings(env, &tags)?)?;
+        let tag_refs: Vec<&str> = tags.iter().map(|s| s.as_str()).collect();
+        consume_builder(filter_ptr, |filter| filter.tags(tag_refs, 't'))
+    })
+}
+
+/// Match one parameterized replaceable event (kind, author, and `#d` tag)
+///
+/// Also sets limit 1, so a query returns only the latest version.
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the FilterBuilder
+/// * `kind` - Event kind, e.g. 30023
+/// * `author` - 32-byte author pubkey
+/// * `d_tag` - The `d` tag value; may be empty
+///
+/// # Returns
+/// New filter pointer (old one is consumed). Throws
+/// IllegalArgumentException for a negative kind or a pubkey that is not
+/// 32 bytes.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterReplaceable(
     mut env: JNIEnv,
     _class: JClass,
     filter_ptr: jlong,
+    kind: jint,
+    author: JByteArray,
+    d_tag: JString,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |env| {
+        let (kind, author, d_tag) = java_replaceable(env, kind, &author, &d_tag)?;
+        consume_builder(filter_ptr, |filter| {
+            replaceable(filter, kind, &author, &d_tag)
+        })
+    })
+}
+
+/// Add tag filter with 32-byte binary values (e.g. `#e`, `#p`, `#q`)
+///
+/// Matches the same events as [`filterTag`] with the hex-encoded values,
+/// without round-tripping them through Java Strings.
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the FilterBuilder
+/// * `tag_name` - Single-character tag name
+/// * `values` - Serialized values: [value1:32][value2:32]...
+///
+/// # Returns
+/// New filter pointer (old one is consumed). Throws
+/// IllegalArgumentException for a malformed tag name or length.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterTagBinary(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    tag_name: JString,
+    values: JByteArray,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |env| {
+        let tag_char = java_tag_name(env, &tag_name)?;
+        let values = java_bytes_to_32_vec(env, &values)?;
+        consume_builder(filter_ptr, |filter| filter.id_tags(tag_char, values.iter()))
+    })
+}
+
+/// Set since timestamp
+///
+/// Throws IllegalArgumentException, leaving the builder valid, if the
+/// timestamp is negative or too far in the future (see
+/// `filterSetTimestampSlack`).
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterSince(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    since: jlong,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |_env| {
+        let since = filter::timestamp("since", since)?;
+        consume_builder(filter_ptr, |filter| filter.since(since))
+    })
+}
+
+/// Set until timestamp
+///
+/// Throws IllegalArgumentException, leaving the builder valid, if the
+/// timestamp is negative or too far in the future (see
+/// `filterSetTimestampSlack`).
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterUntil(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    until: jlong,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |_env| {
+        let until = filter::timestamp("until", until)?;
+        consume_builder(filter_ptr, |filter| filter.until(until))
+    })
+}
+
+/// Set how far in the future since/until timestamps may lie
+///
+/// Applies to every filter built afterwards. The default is 10 years.
+///
+/// # Arguments
+/// * `slack_secs` - Seconds past the current time (must be non-negative)
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterSetTimestampSlack(
+    mut env: JNIEnv,
+    _class: JClass,
+    slack_secs: jlong,
+) {
+    with_exception(&mut env, (), |_env| {
+        let slack_secs = u64::try_from(slack_secs).map_err(|_| {
+            Error::InvalidArgument(format!("Slack must not be negative, got {}", slack_secs))
+        })?;
+        filter::set_timestamp_slack(slack_secs);
+        Ok(())
+    })
+}
+
+/// Set limit
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterLimit(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    limit: jlong,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |_env| {
+        consume_builder(filter_ptr, |filter| filter.limit(limit as u64))
+    })
+}
+
+/// Full-text search
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterSearch(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    search: JString,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |env| {
+        let search_str = java_string_to_rust(env, &search)?;
+        consume_builder(filter_ptr, |filter| filter.search(&search_str))
+    })
+}
+
+/// Match only kinds in an inclusive range
+///
+/// Ranges of up to 256 kinds are added as an explicit kinds list. Larger
+/// ones (e.g. 30000-39999) are checked natively against query results,
+/// as nostrdb can only match kinds by list; such a filter cannot be used
+/// for a subscription.
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the FilterBuilder
+/// * `min_kind` - Lowest kind to match
+/// * `max_kind` - Highest kind to match
+///
+/// # Returns
+/// New filter pointer (old one is consumed). Throws
+/// IllegalArgumentException for a negative or inverted range.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterKindRange(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    min_kind: jint,
+    max_kind: jint,
+) -> jlong {
+    with_exception(&mut env, filter_ptr, |_env| {
+        let range = java_kind_range(min_kind, max_kind)?;
+        let handle = unsafe { util::take_ptr::<BuilderHandle>(filter_ptr, "filter builder")? };
+        Ok(box_to_ptr(handle.kind_range(range)))
+    })
+}
+
+/// Build filter (finalize)
+///
+/// The builder is consumed even if validation fails. Throws
+/// NostrdbException for since after until or too many elements.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuild(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, 0, |_env| {
+        let builder = unsafe { util::take_ptr::<BuilderHandle>(filter_ptr, "filter builder")? };
+        Ok(box_to_ptr(builder.build(false)?))
+    })
+}
+
+/// Build filter (finalize), also rejecting an empty filter
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuildStrict(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, 0, |_env| {
+        let builder = unsafe { util::take_ptr::<BuilderHandle>(filter_ptr, "filter builder")? };
+        Ok(box_to_ptr(builder.build(true)?))
+    })
+}
+
+/// Parse a NIP-01 filter JSON object into a built filter
+///
+/// # Arguments
+/// * `json` - The filter object, e.g. `{"kinds":[1],"#e":["..."],"limit":10}`
+///
+/// # Returns
+/// Pointer to the built Filter (same as `filterBuild`), or 0 with
+/// NostrdbException on malformed input or a filter `filterBuild` rejects
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterFromJson(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: JString,
+) -> jlong {
+    with_exception(&mut env, 0, |env| {
+        let json = java_string_to_rust(env, &json)?;
+        let filter = filter::from_json(&json, false)?;
+        Ok(box_to_ptr(FilterHandle::from(filter)))
+    })
+}
+
+/// Parse a NIP-01 filter JSON object, also rejecting an empty filter
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterFromJsonStrict(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: JString,
+) -> jlong {
+    with_exception(&mut env, 0, |env| {
+        let json = java_string_to_rust(env, &json)?;
+        let filter = filter::from_json(&json, true)?;
+        Ok(box_to_ptr(FilterHandle::from(filter)))
+    })
+}
+
+/// Serialize a built filter to its NIP-01 JSON object
+///
+/// Ids and pubkeys are hex-encoded and tags appear under `#x` keys, so
+/// the result can be sent to a relay or parsed back with `filterFromJson`.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterToJson(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+) -> jstring {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        let json = filter.to_json()?;
+        Ok(env.new_string(json)?.into_raw())
+    })
+}
+
+/// Check whether a stored note matches a filter, without running a query
+///
+/// A search term matches as a case-insensitive substring of the content.
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the Filter
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `note_key` - Internal note key
+///
+/// # Returns
+/// Whether the note matches; throws NoSuchElementException if the note
+/// does not exist
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterMatches(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    note_key: jlong,
+) -> jboolean {
+    with_exception(&mut env, JNI_FALSE, |_env| {
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+
+        let note = ndb.get_note_by_key(txn, NoteKey::new(note_key as u64))?;
+        let utf8 = handle.utf8_mode();
+        Ok(if filter::matches_note(filter, &note, utf8)? {
+            JNI_TRUE
+        } else {
+            JNI_FALSE
+        })
+    })
+}
+
+/// Check whether an event that is not stored matches a filter
+///
+/// # Arguments
+/// * `filter_ptr` - Pointer to the Filter
+/// * `event_json` - The event as a JSON object (not validated)
+///
+/// # Returns
+/// Whether the event matches; throws IllegalArgumentException if a field
+/// the filter refers to is missing or malformed
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterMatchesJson(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+    event_json: JString,
+) -> jboolean {
+    with_exception(&mut env, JNI_FALSE, |env| {
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        let json = java_string_to_rust(env, &event_json)?;
+        Ok(if filter::matches_event_json(filter, &json)? {
+            JNI_TRUE
+        } else {
+            JNI_FALSE
+        })
+    })
+}
+
+/// Get the kinds of a built filter
+///
+/// # Returns
+/// The kinds, or null if the filter has no kinds element
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterGetKinds(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+) -> jintArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        let Some(kinds) = filter::kinds(filter)? else {
+            return Ok(std::ptr::null_mut());
+        };
+        let kinds = kinds
+            .into_iter()
+            .map(|kind| {
+                jint::try_from(kind)
+                    .map_err(|_| Error::Filter(format!("Kind {} does not fit in an int", kind)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let arr = env.new_int_array(kinds.len() as jint)?;
+        env.set_int_array_region(&arr, 0, &kinds)?;
+        Ok(arr.into_raw())
+    })
+}
+
+/// Get the authors of a built filter
+///
+/// # Returns
+/// Serialized pubkeys [pubkey1:32][pubkey2:32]..., or null if the filter
+/// has no authors element
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterGetAuthors(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        Ok(match filter::authors(filter)? {
+            Some(authors) => rust_bytes_to_java(env, &authors.concat()),
+            None => std::ptr::null_mut(),
+        })
+    })
+}
+
+/// Get the since timestamp of a built filter, or -1 if unset
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterGetSince(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, -1, |_env| {
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        Ok(filter.since().map_or(-1, |since| since as jlong))
+    })
+}
+
+/// Get the until timestamp of a built filter, or -1 if unset
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterGetUntil(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, -1, |_env| {
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        Ok(filter.until().map_or(-1, |until| until as jlong))
+    })
+}
+
+/// Get the limit of a built filter, or -1 if unset
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterGetLimit(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, -1, |_env| {
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        Ok(filter.limit().map_or(-1, |limit| limit as jlong))
+    })
+}
+
+/// Clone a built filter
+///
+/// # Returns
+/// Pointer to a new, independently owned Filter; it outlives the original
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterClone(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, 0, |_env| {
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+        Ok(box_to_ptr(filter.clone()))
+    })
+}
+
+/// Destroy filter
+///
+/// Throws IllegalStateException if the handle is not a built Filter
+/// (e.g. a FilterBuilder, which is freed by `filterBuild`).
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterDestroy(
+    mut env: JNIEnv,
+    _class: JClass,
+    filter_ptr: jlong,
+) {
+    with_exception(&mut env, (), |_env| unsafe {
+        util::drop_ptr::<FilterHandle>(filter_ptr, "filter")
+    });
+}
+
+// ============================================================================
+// Filter Building (stable pointer)
+// ============================================================================
+//
+// Unlike the `filter*` natives above, these keep the builder pointer stable:
+// each call mutates the builder in place, so callers never replace their
+// stored pointer. The pointer must be freed with `filterBuilderDestroy`,
+// also after `filterBuilderBuild`.
+
+/// Replace the builder behind a stable pointer with `f(builder)`
+///
+/// Throws IllegalStateException if the builder has already been built.
+fn update_builder(
+    builder_ptr: jlong,
+    f: impl FnOnce(FilterBuilder) -> FilterBuilder,
+) -> Result<()> {
+    update_handle(builder_ptr, |handle| handle.map(f))
+}
+
+/// Replace the builder handle behind a stable pointer with `f(handle)`
+fn update_handle(builder_ptr: jlong, f: impl FnOnce(BuilderHandle) -> BuilderHandle) -> Result<()> {
+    let slot = unsafe { util::ptr_to_mut::<Option<BuilderHandle>>(builder_ptr, "filter builder")? };
+    let handle = slot
+        .take()
+        .ok_or_else(|| Error::InvalidState("Filter has already been built".to_string()))?;
+    *slot = Some(f(handle));
+    Ok(())
+}
+
+/// Create new filter builder with a stable pointer
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderNew(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jlong {
+    catch_panic(0, || box_to_ptr(Some(BuilderHandle::new())))
+}
+
+/// Add kinds to a stable builder
+///
+/// # Arguments
+/// * `builder_ptr` - Pointer from `filterBuilderNew`
+/// * `kinds` - Serialized kinds: [kind1:4][kind2:4]...
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderAddKinds(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    kinds: JByteArray,
+) {
+    with_exception(&mut env, (), |env| {
+        let kinds = java_kinds(env, &kinds)?;
+        update_builder(builder_ptr, |builder| builder.kinds(kinds))
+    })
+}
+
+/// Add kinds to a stable builder from an int[]
+///
+/// Throws IllegalArgumentException if a kind is negative.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderAddKindsInt(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    kinds: JIntArray,
+) {
+    with_exception(&mut env, (), |env| {
+        let kinds = java_int_kinds(env, &kinds)?;
+        update_builder(builder_ptr, |builder| builder.kinds(kinds))
+    })
+}
+
+/// Add authors to a stable builder (array of 32-byte pubkeys)
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderAddAuthors(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    authors: JByteArray,
+) {
+    with_exception(&mut env, (), |env| {
+        let authors = java_bytes_to_32_vec(env, &authors)?;
+        update_builder(builder_ptr, |builder| builder.authors(authors.iter()))
+    })
+}
+
+/// Add authors to a stable builder from 64-character hex pubkeys
+///
+/// Throws IllegalArgumentException naming the index of a malformed pubkey.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderAddAuthorsHex(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    hex_pubkeys: JObjectArray,
+) {
+    with_exception(&mut env, (), |env| {
+        let authors = java_hex_32_vec(env, &hex_pubkeys, "pubkey")?;
+        update_builder(builder_ptr, |builder| builder.authors(authors.iter()))
+    })
+}
+
+/// Add event IDs to a stable builder (array of 32-byte ids)
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderAddIds(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    ids: JByteArray,
+) {
+    with_exception(&mut env, (), |env| {
+        let ids = java_bytes_to_32_vec(env, &ids)?;
+        update_builder(builder_ptr, |builder| builder.ids(ids.iter()))
+    })
+}
+
+/// Add referenced event IDs (`#e` tag) to a stable builder
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderAddEvents(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    ids: JByteArray,
+) {
+    with_exception(&mut env, (), |env| {
+        let ids = java_bytes_to_32_vec(env, &ids)?;
+        update_builder(builder_ptr, |builder| builder.events(ids.iter()))
+    })
+}
+
+/// Add referenced pubkeys (`#p` tag) to a stable builder
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderAddPubkeys(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    pubkeys: JByteArray,
+) {
+    with_exception(&mut env, (), |env| {
+        let pubkeys = java_bytes_to_32_vec(env, &pubkeys)?;
+        update_builder(builder_ptr, |builder| builder.pubkeys(pubkeys.iter()))
+    })
+}
+
+/// Add a tag filter to a stable builder
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderAddTag(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    tag_name: JString,
+    tag_values: JObjectArray,
+) {
+    with_exception(&mut env, (), |env| {
+        let (tag_char, values) = java_tag_filter(env, &tag_name, &tag_values)?;
+        let value_refs: Vec<&str> = values.iter().map(|s| s.as_str()).collect();
+        update_builder(builder_ptr, |builder| builder.tags(value_refs, tag_char))
+    })
+}
+
+/// Add hashtags (`#t` tag, normalized per NIP-24) to a stable builder
+///
+/// # Returns
+/// The number of distinct hashtags applied. Throws
+/// IllegalArgumentException if every value is empty after normalization.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderAddHashtags(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    tags: JObjectArray,
+) -> jint {
+    with_exception(&mut env, 0, |env| {
+        let tags = filter::normalize_hashtags(&java_strings(env, &tags)?)?;
+        let tag_refs: Vec<&str> = tags.iter().map(|s| s.as_str()).collect();
+        update_builder(builder_ptr, |builder| builder.tags(tag_refs, 't'))?;
+        Ok(tags.len() as jint)
+    })
+}
+
+/// Match one parameterized replaceable event on a stable builder
+///
+/// Same as `filterReplaceable`, including limit 1.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderReplaceable(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    kind: jint,
+    author: JByteArray,
+    d_tag: JString,
+) {
+    with_exception(&mut env, (), |env| {
+        let (kind, author, d_tag) = java_replaceable(env, kind, &author, &d_tag)?;
+        update_builder(builder_ptr, |builder| {
+            replaceable(builder, kind, &author, &d_tag)
+        })
+    })
+}
+
+/// Add a tag filter with 32-byte binary values to a stable builder
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderAddTagBinary(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
     tag_name: JString,
-    tag_values: jobjectArray,
+    values: JByteArray,
+) {
+    with_exception(&mut env, (), |env| {
+        let tag_char = java_tag_name(env, &tag_name)?;
+        let values = java_bytes_to_32_vec(env, &values)?;
+        update_builder(builder_ptr, |builder| {
+            builder.id_tags(tag_char, values.iter())
+        })
+    })
+}
+
+/// Set since timestamp on a stable builder
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderSetSince(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    since: jlong,
+) {
+    with_exception(&mut env, (), |_env| {
+        let since = filter::timestamp("since", since)?;
+        update_builder(builder_ptr, |builder| builder.since(since))
+    })
+}
+
+/// Set until timestamp on a stable builder
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderSetUntil(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    until: jlong,
+) {
+    with_exception(&mut env, (), |_env| {
+        let until = filter::timestamp("until", until)?;
+        update_builder(builder_ptr, |builder| builder.until(until))
+    })
+}
+
+/// Set limit on a stable builder
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderSetLimit(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    limit: jlong,
+) {
+    with_exception(&mut env, (), |_env| {
+        update_builder(builder_ptr, |builder| builder.limit(limit as u64))
+    })
+}
+
+/// Set full-text search on a stable builder
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderSetSearch(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    search: JString,
+) {
+    with_exception(&mut env, (), |env| {
+        let search = java_string_to_rust(env, &search)?;
+        update_builder(builder_ptr, |builder| builder.search(&search))
+    })
+}
+
+/// Match only kinds in an inclusive range on a stable builder
+///
+/// See `filterKindRange`.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderKindRange(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    min_kind: jint,
+    max_kind: jint,
+) {
+    with_exception(&mut env, (), |_env| {
+        let range = java_kind_range(min_kind, max_kind)?;
+        update_handle(builder_ptr, |handle| handle.kind_range(range))
+    })
+}
+
+/// Build the filter from a stable builder
+///
+/// The builder pointer stays valid (and must still be destroyed), but
+/// further calls on it throw IllegalStateException, also if validation
+/// fails as in `filterBuild`.
+///
+/// # Returns
+/// Pointer to the built Filter
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderBuild(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
 ) -> jlong {
-    with_exception(&mut env, filter_ptr, |env| {
-        let filter = unsafe { Box::from_raw(filter_ptr as *mut nostrdb::FilterBuilder) };
-        let tag = java_string_to_rust(env, &tag_name)?;
-        let tag_char = tag.chars().next().ok_or(Error::Filter("Empty tag name".to_string()))?;
+    with_exception(&mut env, 0, |_env| {
+        Ok(box_to_ptr(build_from_slot(builder_ptr, false)?))
+    })
+}
+
+/// Build the filter from a stable builder, also rejecting an empty filter
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderBuildStrict(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+) -> jlong {
+    with_exception(&mut env, 0, |_env| {
+        Ok(box_to_ptr(build_from_slot(builder_ptr, true)?))
+    })
+}
+
+/// Take the builder out of a stable pointer and build it
+fn build_from_slot(builder_ptr: jlong, strict: bool) -> Result<FilterHandle> {
+    let slot = unsafe { util::ptr_to_mut::<Option<BuilderHandle>>(builder_ptr, "filter builder")? };
+    let builder = slot
+        .take()
+        .ok_or_else(|| Error::InvalidState("Filter has already been built".to_string()))?;
+    builder.build(strict)
+}
+
+/// Destroy a stable builder, built or not
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuilderDestroy(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+) {
+    with_exception(&mut env, (), |_env| unsafe {
+        util::drop_ptr::<Option<BuilderHandle>>(builder_ptr, "filter builder")
+    });
+}
+
+// ============================================================================
+// Note Building
+// ============================================================================
+//
+// A note builder keeps a stable pointer, like the `filterBuilder*` natives:
+// each call mutates it in place. `noteBuilderSign` consumes the note; the
+// pointer must still be freed with `noteBuilderFree`.
+
+/// Apply `f` to the draft behind a builder pointer
+///
+/// Throws IllegalStateException if the note has already been signed.
+fn update_draft(builder_ptr: jlong, f: impl FnOnce(&mut NoteDraft) -> Result<()>) -> Result<()> {
+    let slot = unsafe { util::ptr_to_mut::<Option<NoteDraft>>(builder_ptr, "note builder")? };
+    let draft = slot
+        .as_mut()
+        .ok_or_else(|| Error::InvalidState("Note has already been signed".to_string()))?;
+    f(draft)
+}
+
+/// Create a note builder for an empty kind 1 note
+///
+/// # Returns
+/// Pointer to the builder, to be freed with `noteBuilderFree`
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteBuilderNew(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jlong {
+    catch_panic(0, || box_to_ptr(Some(NoteDraft::new())))
+}
+
+/// Set the kind of the note being built
+///
+/// Throws IllegalArgumentException if the kind is negative.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteBuilderKind(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    kind: jint,
+) {
+    with_exception(&mut env, (), |_env| {
+        let kind = u32::try_from(kind).map_err(|_| {
+            Error::InvalidArgument(format!("Kind must not be negative, got {}", kind))
+        })?;
+        update_draft(builder_ptr, |draft| {
+            draft.set_kind(kind);
+            Ok(())
+        })
+    })
+}
+
+/// Set the content of the note being built
+///
+/// # Arguments
+/// * `builder_ptr` - Pointer from `noteBuilderNew`
+/// * `content` - Content as UTF-8 bytes
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteBuilderContent(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    content: JByteArray,
+) {
+    with_exception(&mut env, (), |env| {
+        let content = std::str::from_utf8(&java_bytes_to_rust(env, &content)?)?.to_string();
+        update_draft(builder_ptr, |draft| {
+            draft.set_content(content);
+            Ok(())
+        })
+    })
+}
+
+/// Set the creation timestamp of the note being built
+///
+/// Without one, the note is stamped with the time it is signed. Throws
+/// IllegalArgumentException if the timestamp is negative.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteBuilderCreatedAt(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    created_at: jlong,
+) {
+    with_exception(&mut env, (), |_env| {
+        let created_at = u64::try_from(created_at).map_err(|_| {
+            Error::InvalidArgument(format!(
+                "Timestamp must not be negative, got {}",
+                created_at
+            ))
+        })?;
+        update_draft(builder_ptr, |draft| {
+            draft.set_created_at(created_at);
+            Ok(())
+        })
+    })
+}
+
+/// Start a new tag on the note being built
+///
+/// `noteBuilderTagStr` and `noteBuilderTagId` add elements to the tag
+/// started last.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteBuilderStartTag(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+) {
+    with_exception(&mut env, (), |_env| {
+        update_draft(builder_ptr, |draft| {
+            draft.start_tag();
+            Ok(())
+        })
+    })
+}
+
+/// Add a string element to the current tag
+///
+/// Throws IllegalStateException if no tag has been started.
+///
+/// # Arguments
+/// * `builder_ptr` - Pointer from `noteBuilderNew`
+/// * `value` - Element as UTF-8 bytes
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteBuilderTagStr(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    value: JByteArray,
+) {
+    with_exception(&mut env, (), |env| {
+        let value = std::str::from_utf8(&java_bytes_to_rust(env, &value)?)?.to_string();
+        update_draft(builder_ptr, |draft| draft.push_str(value))
+    })
+}
+
+/// Add a 32-byte id element (event id or pubkey) to the current tag
+///
+/// The element appears in the note's JSON as 64 hex characters. Throws
+/// IllegalStateException if no tag has been started.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteBuilderTagId(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    id: JByteArray,
+) {
+    with_exception(&mut env, (), |env| {
+        let id = java_bytes_to_32(env, &id)?;
+        update_draft(builder_ptr, |draft| draft.push_id(id))
+    })
+}
+
+/// Finalize and sign the note being built
+///
+/// The secret key is copied out of Java only for the duration of the call
+/// and wiped before it returns. Afterwards the builder rejects further
+/// calls with IllegalStateException.
+///
+/// # Arguments
+/// * `builder_ptr` - Pointer from `noteBuilderNew`
+/// * `ndb_ptr` - Pointer to an Ndb instance to ingest the note into, or 0
+/// * `seckey` - 32-byte secret key
+///
+/// # Returns
+/// The signed event as JSON bytes
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteBuilderSign(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+    ndb_ptr: jlong,
+    seckey: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let slot = unsafe { util::ptr_to_mut::<Option<NoteDraft>>(builder_ptr, "note builder")? };
+        let len = env.get_array_length(&seckey)? as usize;
+        if len != 32 {
+            return Err(Error::InvalidIdLength(len));
+        }
+        let draft = slot
+            .take()
+            .ok_or_else(|| Error::InvalidState("Note has already been signed".to_string()))?;
+
+        // Read into fixed buffers rather than a Vec, so that every copy of
+        // the key is wiped below
+        let mut raw = [0i8; 32];
+        let mut key = [0u8; 32];
+        let signed = env
+            .get_byte_array_region(&seckey, 0, &mut raw)
+            .map_err(Error::from)
+            .and_then(|()| {
+                for (byte, &value) in key.iter_mut().zip(raw.iter()) {
+                    *byte = value as u8;
+                }
+                draft.sign(&key)
+            });
+        draft::wipe(&mut raw);
+        draft::wipe(&mut key);
+        let json = signed?;
+
+        if ndb_ptr != 0 {
+            let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+            ingest::queue(handle, &json)??;
+        }
+        Ok(rust_bytes_to_java(env, json.as_bytes()))
+    })
+}
 
-        // Get array length - use JObjectArray for proper type
-        let arr_obj = unsafe { JObjectArray::from_raw(tag_values) };
-        let len = env.get_array_length(&arr_obj)?;
+/// Free a note builder, signed or not
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_noteBuilderFree(
+    mut env: JNIEnv,
+    _class: JClass,
+    builder_ptr: jlong,
+) {
+    with_exception(&mut env, (), |_env| unsafe {
+        util::drop_ptr::<Option<NoteDraft>>(builder_ptr, "note builder")
+    });
+}
+
+// ============================================================================
+// Verification
+// ============================================================================
 
-        let mut values: Vec<String> = Vec::with_capacity(len as usize);
-        for i in 0..len {
-            let obj = env.get_object_array_element(&arr_obj, i)?;
-            let s = java_string_to_rust(env, &JString::from(obj))?;
-            values.push(s);
+/// Verify an event's id and signature without ingesting it
+///
+/// # Arguments
+/// * `json` - The event as a JSON object
+/// * `strict` - Throw IllegalArgumentException describing the failure
+///   instead of returning its code
+///
+/// # Returns
+/// 0 if valid, 1 if the id doesn't match the content, 2 if the signature
+/// doesn't verify, 3 if the event is malformed
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_verifyEvent(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: JString,
+    strict: jboolean,
+) -> jint {
+    with_exception(&mut env, verify::VERIFY_MALFORMED, |env| {
+        let json = java_string_to_rust(env, &json)?;
+        match verify::verify_json(&json) {
+            Ok(()) => Ok(verify::VERIFY_OK),
+            Err(failure) if strict != JNI_FALSE => Err(failure.into()),
+            Err(failure) => Ok(failure.code()),
         }
+    })
+}
 
-        let value_refs: Vec<&str> = values.iter().map(|s| s.as_str()).collect();
-        let new_filter = filter.tags(value_refs, tag_char);
-        Ok(box_to_ptr(new_filter))
+/// Compute the NIP-01 id of an event
+///
+/// The id is the SHA-256 of `[0,pubkey,created_at,kind,tags,content]`,
+/// serialized with NIP-01's escaping. Any `id` and `sig` fields are
+/// ignored, so this works for unsigned events. Throws
+/// IllegalArgumentException if a field is missing or mistyped.
+///
+/// # Arguments
+/// * `json` - The event as a JSON object
+///
+/// # Returns
+/// The 32-byte event id
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_computeEventId(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: JString,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let json = java_string_to_rust(env, &json)?;
+        let id = verify::compute_id(&json)?;
+        Ok(rust_bytes_to_java(env, &id))
     })
 }
 
-/// Set since timestamp
+/// Verify a stored note's id and signature
+///
+/// Notes are verified when ingested unless validation is skipped; this
+/// checks them again, e.g. after importing with `setSkipValidation`.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `id` - 32-byte event ID
+///
+/// # Returns
+/// The code as for `verifyEvent`, or -1 if the note is not found
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterSince(
-    _env: JNIEnv,
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_verifyNoteById(
+    mut env: JNIEnv,
     _class: JClass,
-    filter_ptr: jlong,
-    since: jlong,
-) -> jlong {
-    catch_panic(filter_ptr, || {
-        let filter = unsafe { Box::from_raw(filter_ptr as *mut nostrdb::FilterBuilder) };
-        let new_filter = filter.since(since as u64);
-        box_to_ptr(new_filter)
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    id: JByteArray,
+) -> jint {
+    with_exception(&mut env, -1, |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let id = java_bytes_to_32(env, &id)?;
+
+        let note = match ndb.get_note_by_id(txn, &id) {
+            Ok(note) => note,
+            Err(nostrdb::Error::NotFound) => return Ok(-1),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(match verify::verify_json(&note.json()?) {
+            Ok(()) => verify::VERIFY_OK,
+            Err(failure) => failure.code(),
+        })
     })
 }
 
-/// Set until timestamp
+// ============================================================================
+// Profile Operations
+// ============================================================================
+
+/// Get profile by 32-byte pubkey
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `pubkey` - 32-byte public key
+///
+/// # Returns
+/// Serialized profile as byte array (JSON), or null if not found
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterUntil(
-    _env: JNIEnv,
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getProfileByPubkey(
+    mut env: JNIEnv,
     _class: JClass,
-    filter_ptr: jlong,
-    until: jlong,
-) -> jlong {
-    catch_panic(filter_ptr, || {
-        let filter = unsafe { Box::from_raw(filter_ptr as *mut nostrdb::FilterBuilder) };
-        let new_filter = filter.until(until as u64);
-        box_to_ptr(new_filter)
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    pubkey: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let pk = java_bytes_to_32(env, &pubkey)?;
+
+        match ndb.get_profile_by_pubkey(txn, &pk) {
+            Ok(profile) => {
+                let json = serialize_profile(&profile)?;
+                Ok(rust_bytes_to_java(env, &json))
+            }
+            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
+            Err(e) => Err(e.into()),
+        }
     })
 }
 
-/// Set limit
+/// Get one field of a profile by pubkey
+///
+/// Reads a single string from the stored profile, without building the
+/// whole profile's JSON, for lookups as frequent as autocomplete.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `pubkey` - 32-byte public key
+/// * `field_id` - 0 name, 1 display_name, 2 about, 3 picture, 4 banner,
+///   5 nip05, 6 lud06, 7 lud16, 8 website
+///
+/// # Returns
+/// The field's value, or null if there is no profile or it lacks the field
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterLimit(
-    _env: JNIEnv,
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getProfileField(
+    mut env: JNIEnv,
     _class: JClass,
-    filter_ptr: jlong,
-    limit: jlong,
-) -> jlong {
-    catch_panic(filter_ptr, || {
-        let filter = unsafe { Box::from_raw(filter_ptr as *mut nostrdb::FilterBuilder) };
-        let new_filter = filter.limit(limit as u64);
-        box_to_ptr(new_filter)
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    pubkey: JByteArray,
+    field_id: jint,
+) -> jstring {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let pk = java_bytes_to_32(env, &pubkey)?;
+        if !(0..PROFILE_FIELD_COUNT).contains(&field_id) {
+            return Err(Error::InvalidArgument(format!(
+                "Unknown profile field id: {}",
+                field_id
+            )));
+        }
+
+        let profile = match ndb.get_profile_by_pubkey(txn, &pk) {
+            Ok(record) => record.record().profile(),
+            Err(nostrdb::Error::NotFound) => None,
+            Err(e) => return Err(e.into()),
+        };
+        match profile.and_then(|p| profile_field(p, field_id)) {
+            Some(value) => Ok(env.new_string(value)?.into_raw()),
+            None => Ok(std::ptr::null_mut()),
+        }
     })
 }
 
-/// Full-text search
+/// Get the kind-0 event behind a pubkey's profile, as nostrdb writes it
+///
+/// Follows the profile record's note key to the stored note and returns
+/// its JSON from nostrdb, as `getNoteJsonByKey` does, so the signed event
+/// can be rebroadcast unchanged.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `pubkey` - 32-byte public key
+///
+/// # Returns
+/// The event JSON, or null if the pubkey has no stored profile
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterSearch(
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getProfileNote(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    pubkey: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let pk = java_bytes_to_32(env, &pubkey)?;
+
+        let note_key = match ndb.get_profile_by_pubkey(txn, &pk) {
+            Ok(profile) => profile.record().note_key(),
+            Err(nostrdb::Error::NotFound) => return Ok(std::ptr::null_mut()),
+            Err(e) => return Err(e.into()),
+        };
+        // A record without a note key (0) has no event to return
+        match ndb.get_note_by_key(txn, NoteKey::new(note_key)) {
+            Ok(note) if note_key != 0 => Ok(rust_bytes_to_java(env, note.json()?.as_bytes())),
+            Ok(_) | Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Get a profile's raw bytes by 32-byte public key
+///
+/// The bytes are the profile record as nostrdb stores it: a flatbuffer
+/// whose root is an `NdbProfileRecord` (nostrdb's `profile.fbs`), holding
+/// the `NdbProfile` and the record fields. The schema is versioned by
+/// [`ABI_VERSION`].
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `pubkey` - 32-byte public key
+///
+/// # Returns
+/// The raw profile record, or null if not found
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getProfileRawByPubkey(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    pubkey: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let pk = java_bytes_to_32(env, &pubkey)?;
+
+        match ndb.get_profile_by_pubkey(txn, &pk) {
+            // The record's table reads from the whole stored buffer
+            Ok(profile) => Ok(rust_bytes_to_java(env, profile.record()._tab.buf())),
+            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Get profiles by 32-byte public key, in one call
+///
+/// Every pubkey gets an entry, in input order; a pubkey without a stored
+/// profile has an empty (len 0) profile.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `pubkeys` - Public keys [pubkey1:32][pubkey2:32]...
+///
+/// # Returns
+/// Serialized profiles: [count:4]([pubkey:32][len:4][profileJson:len])*
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getProfilesByPubkeys(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    pubkeys: JByteArray,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let bytes = java_bytes_to_rust(env, &pubkeys)?;
+        if bytes.len() % 32 != 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Pubkeys must be 32 bytes each; the one at index {} has {} bytes",
+                bytes.len() / 32,
+                bytes.len() % 32
+            )));
+        }
+
+        let count = bytes.len() / 32;
+        let mut buf = Vec::with_capacity(4 + count * 512);
+        buf.extend_from_slice(&(count as u32).to_le_bytes());
+        for chunk in bytes.chunks_exact(32) {
+            let pubkey: &[u8; 32] = chunk.try_into().unwrap();
+            let json = match ndb.get_profile_by_pubkey(txn, pubkey) {
+                Ok(profile) => serialize_profile(&profile)?,
+                Err(nostrdb::Error::NotFound) => Vec::new(),
+                Err(e) => return Err(e.into()),
+            };
+            buf.extend_from_slice(pubkey);
+            buf.extend_from_slice(&(json.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&json);
+        }
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Get the profiles of the authors of the notes matching a filter
+///
+/// Runs the filter, collects its distinct authors in result order
+/// (newest note first), and looks each profile up in the same
+/// transaction. `limit` bounds the notes queried, as in `query`, not the
+/// number of profiles.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `filter_ptr` - Pointer to the Filter
+/// * `limit` - Maximum number of notes, or negative for the filter's limit
+/// * `include_missing` - Report authors without a stored profile with an
+///   empty JSON object instead of leaving them out
+///
+/// # Returns
+/// Serialized profiles: [count:4]([pubkey:32][len:4][profileJson:len])*
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_queryProfiles(
     mut env: JNIEnv,
     _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
     filter_ptr: jlong,
-    search: JString,
-) -> jlong {
-    with_exception(&mut env, filter_ptr, |env| {
-        let filter = unsafe { Box::from_raw(filter_ptr as *mut nostrdb::FilterBuilder) };
-        let search_str = java_string_to_rust(env, &search)?;
-        let new_filter = filter.search(&search_str);
-        Ok(box_to_ptr(new_filter))
+    limit: jint,
+    include_missing: jboolean,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
+
+        let results = query_filter(&ndb, txn, filter, limit)?;
+        let mut seen = HashSet::new();
+        let mut count: u32 = 0;
+        let mut buf = vec![0; 4];
+        for result in &results {
+            let pubkey = result.note.pubkey();
+            if !seen.insert(*pubkey) {
+                continue;
+            }
+            let json = match ndb.get_profile_by_pubkey(txn, pubkey) {
+                Ok(profile) => serialize_profile(&profile)?,
+                Err(nostrdb::Error::NotFound) if include_missing != JNI_FALSE => b"{}".to_vec(),
+                Err(nostrdb::Error::NotFound) => continue,
+                Err(e) => return Err(e.into()),
+            };
+            buf.extend_from_slice(pubkey);
+            buf.extend_from_slice(&(json.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&json);
+            count += 1;
+        }
+        buf[..4].copy_from_slice(&count.to_le_bytes());
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Search note contents with nostrdb's full-text index
+///
+/// Each hit reports where the query matched in the content (see the
+/// `search` module), so callers can highlight it directly.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `query` - Search query (not blank)
+/// * `limit` - Maximum number of hits (positive)
+/// * `order` - `SEARCH_ORDER_NEWEST` (0) or `SEARCH_ORDER_RELEVANCE` (1)
+///
+/// # Returns
+/// Serialized hits: [count:4]([key:8][created_at:8][offset:4][length:4])*,
+/// where offset and length locate the first match in the content's UTF-8
+/// bytes
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_searchNotes(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    query: JString,
+    limit: jint,
+    order: jint,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let ndb = handle.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let query = java_string_to_rust(env, &query)?;
+        if limit <= 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Limit must be positive, got {}",
+                limit
+            )));
+        }
+
+        let utf8 = handle.utf8_mode();
+        let hits = search::search_notes(&ndb, txn, &query, limit as usize, order, utf8)?;
+        let mut buf = Vec::with_capacity(4 + hits.len() * 24);
+        buf.extend_from_slice(&(hits.len() as u32).to_le_bytes());
+        for hit in &hits {
+            hit.write(&mut buf);
+        }
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Search profiles by name
+///
+/// By default this is nostrdb's profile index, which matches names by
+/// prefix as stored. With `PROFILE_SEARCH_FOLD` (1), names and query are
+/// folded for case and accents first ("jose" finds "José"), by scanning
+/// the stored profiles newest first.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `query` - Search query string
+/// * `limit` - Maximum number of results
+/// * `flags` - 0 for exact search, or `PROFILE_SEARCH_FOLD`
+///
+/// # Returns
+/// Array of 32-byte pubkeys
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_searchProfiles(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    query: JString,
+    limit: jint,
+    flags: jint,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let search_str = java_string_to_rust(env, &query)?;
+
+        let results = search_profile_pubkeys(&ndb, txn, &search_str, limit, flags)?;
+
+        // Serialize as concatenated pubkeys
+        let mut buf = Vec::with_capacity(4 + results.len() * 32);
+        buf.extend_from_slice(&(results.len() as u32).to_le_bytes());
+        for pubkey in &results {
+            buf.extend_from_slice(pubkey);
+        }
+
+        Ok(rust_bytes_to_java(env, &buf))
     })
 }
 
-/// Build filter (finalize)
+/// Search profiles by name, with each hit's names
+///
+/// As `searchProfiles`, but each pubkey comes with its profile's name and
+/// display name, read in the same transaction, for autocomplete. A hit
+/// whose profile can't be read back, or that lacks a name, has an empty
+/// one. Names longer than 65535 bytes are cut at a character boundary.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `query` - Search query string
+/// * `limit` - Maximum number of results
+/// * `flags` - As in `searchProfiles`
+///
+/// # Returns
+/// Serialized hits: [count:4]([pubkey:32][nameLen:2][name][displayNameLen:2][displayName])*
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterBuild(
-    _env: JNIEnv,
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_searchProfilesNamed(
+    mut env: JNIEnv,
     _class: JClass,
-    filter_ptr: jlong,
-) -> jlong {
-    catch_panic(0, || {
-        let mut filter = unsafe { Box::from_raw(filter_ptr as *mut nostrdb::FilterBuilder) };
-        let built = filter.build();
-        box_to_ptr(built)
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    query: JString,
+    limit: jint,
+    flags: jint,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let search_str = java_string_to_rust(env, &query)?;
+
+        let results = search_profile_pubkeys(&ndb, txn, &search_str, limit, flags)?;
+
+        let mut buf = Vec::with_capacity(4 + results.len() * 64);
+        buf.extend_from_slice(&(results.len() as u32).to_le_bytes());
+        for pubkey in &results {
+            let profile = match ndb.get_profile_by_pubkey(txn, pubkey) {
+                Ok(record) => record.record().profile(),
+                Err(nostrdb::Error::NotFound) => None,
+                Err(e) => return Err(e.into()),
+            };
+            let name = profile.and_then(|p| p.name());
+            let display_name = profile.and_then(|p| p.display_name());
+            buf.extend_from_slice(pubkey);
+            write_short_str(&mut buf, name.unwrap_or(""));
+            write_short_str(&mut buf, display_name.unwrap_or(""));
+        }
+
+        Ok(rust_bytes_to_java(env, &buf))
     })
 }
 
-/// Destroy filter
+/// Get the pubkeys an author follows, from their newest contact list
+///
+/// Reads the author's newest kind-3 note as the `contacts` module
+/// describes: `p` tag pubkeys, each once, in list order, skipping
+/// malformed ones.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `txn_ptr` - Pointer to the Transaction
+/// * `pubkey` - 32-byte public key of the author
+/// * `relay_hints` - Also return each contact's relay hint
+///
+/// # Returns
+/// Serialized contacts: [count:4]([pubkey:32])*, followed with
+/// `relay_hints` by ([len:2][relay:len])* in the same order (len 0 where
+/// the tag has none), or null if the author has no contact list
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_filterDestroy(
-    _env: JNIEnv,
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getContacts(
+    mut env: JNIEnv,
     _class: JClass,
-    filter_ptr: jlong,
-) {
-    catch_panic_void(|| unsafe {
-        drop_ptr::<Filter>(filter_ptr);
-    });
-}
+    ndb_ptr: jlong,
+    txn_ptr: jlong,
+    pubkey: JByteArray,
+    relay_hints: jboolean,
+) -> jbyteArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let pk = java_bytes_to_32(env, &pubkey)?;
 
-// ============================================================================
-// Profile Operations
-// ============================================================================
+        let filter = contacts::contact_list_filter(&pk);
+        let results = ndb.query(txn, std::slice::from_ref(&filter), 1)?;
+        let Some(list) = results.first() else {
+            return Ok(std::ptr::null_mut());
+        };
+        let contacts = contacts::Contacts::parse(&list.note);
 
-/// Get profile by 32-byte pubkey
+        let mut buf = Vec::with_capacity(4 + contacts.pubkeys.len() * 64);
+        buf.extend_from_slice(&(contacts.pubkeys.len() as u32).to_le_bytes());
+        for contact in &contacts.pubkeys {
+            buf.extend_from_slice(contact);
+        }
+        if relay_hints != JNI_FALSE {
+            for relay in &contacts.relays {
+                write_short_str(&mut buf, relay);
+            }
+        }
+        Ok(rust_bytes_to_java(env, &buf))
+    })
+}
+
+/// Get the relays an author reads from and writes to, from their newest
+/// relay list (NIP-65)
 ///
 /// # Arguments
 /// * `ndb_ptr` - Pointer to the Ndb instance
 /// * `txn_ptr` - Pointer to the Transaction
-/// * `pubkey` - 32-byte public key
+/// * `pubkey` - 32-byte public key of the author
 ///
 /// # Returns
-/// Serialized profile as byte array (JSON), or null if not found
+/// Serialized relays: [count:4]([mode:1][len:2][url:len])*, where mode bit 0
+/// is read and bit 1 is write; or null if the author has no relay list
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getProfileByPubkey(
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getRelayList(
     mut env: JNIEnv,
     _class: JClass,
     ndb_ptr: jlong,
@@ -494,54 +5601,74 @@ pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getProfileByPubke
     pubkey: JByteArray,
 ) -> jbyteArray {
     with_exception(&mut env, std::ptr::null_mut(), |env| {
-        let ndb = unsafe { util::ptr_to_ref::<Arc<Ndb>>(ndb_ptr, "ndb")? };
-        let txn = unsafe { util::ptr_to_ref::<Transaction>(txn_ptr, "transaction")? };
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
         let pk = java_bytes_to_32(env, &pubkey)?;
 
-        match ndb.get_profile_by_pubkey(txn, &pk) {
-            Ok(profile) => {
-                let json = serialize_profile(&profile)?;
-                Ok(rust_bytes_to_java(env, &json))
-            }
-            Err(nostrdb::Error::NotFound) => Ok(std::ptr::null_mut()),
-            Err(e) => Err(e.into()),
+        let filter = relay_list::relay_list_filter(&pk);
+        let results = ndb.query(txn, std::slice::from_ref(&filter), 1)?;
+        let Some(list) = results.first() else {
+            return Ok(std::ptr::null_mut());
+        };
+        let relays = relay_list::RelayList::parse(&list.note).relays;
+
+        let mut buf = Vec::with_capacity(4 + relays.len() * 32);
+        buf.extend_from_slice(&(relays.len() as u32).to_le_bytes());
+        for (url, mode) in &relays {
+            buf.push(*mode);
+            write_short_str(&mut buf, url);
         }
+        Ok(rust_bytes_to_java(env, &buf))
     })
 }
 
-/// Search profiles by name
+/// Get the public entries of an author's newest mute list (NIP-51)
+///
+/// Private entries, encrypted in the list's content, are skipped; flag
+/// bit 0 reports that the list has some.
 ///
 /// # Arguments
 /// * `ndb_ptr` - Pointer to the Ndb instance
 /// * `txn_ptr` - Pointer to the Transaction
-/// * `query` - Search query string
-/// * `limit` - Maximum number of results
+/// * `pubkey` - 32-byte public key of the author
 ///
 /// # Returns
-/// Array of 32-byte pubkeys
+/// Serialized mute list: [flags:1][count:4]([pubkey:32])*[count:4]([id:32])*
+/// [count:4]([len:2][hashtag:len])*[count:4]([len:2][word:len])*,
+/// or null if the author has no mute list
 #[no_mangle]
-pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_searchProfiles(
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_getMuteList(
     mut env: JNIEnv,
     _class: JClass,
     ndb_ptr: jlong,
     txn_ptr: jlong,
-    query: JString,
-    limit: jint,
+    pubkey: JByteArray,
 ) -> jbyteArray {
     with_exception(&mut env, std::ptr::null_mut(), |env| {
-        let ndb = unsafe { util::ptr_to_ref::<Arc<Ndb>>(ndb_ptr, "ndb")? };
-        let txn = unsafe { util::ptr_to_ref::<Transaction>(txn_ptr, "transaction")? };
-        let search_str = java_string_to_rust(env, &query)?;
+        let ndb = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? }.ndb()?;
+        let txn = unsafe { util::ptr_to_ref::<TxnHandle>(txn_ptr, "transaction")? };
+        let pk = java_bytes_to_32(env, &pubkey)?;
 
-        let results = ndb.search_profile(txn, &search_str, limit as u32)?;
+        let filter = mute::mute_list_filter(&pk);
+        let results = ndb.query(txn, std::slice::from_ref(&filter), 1)?;
+        let Some(list) = results.first() else {
+            return Ok(std::ptr::null_mut());
+        };
+        let entries = MuteEntries::parse(&list.note);
 
-        // Serialize as concatenated pubkeys
-        let mut buf = Vec::with_capacity(4 + results.len() * 32);
-        buf.extend_from_slice(&(results.len() as u32).to_le_bytes());
-        for pubkey in results {
-            buf.extend_from_slice(pubkey);
+        let mut buf = vec![entries.private as u8];
+        for ids in [&entries.authors, &entries.ids] {
+            buf.extend_from_slice(&(ids.len() as u32).to_le_bytes());
+            for id in ids {
+                buf.extend_from_slice(id);
+            }
+        }
+        for values in [&entries.hashtags, &entries.words] {
+            buf.extend_from_slice(&(values.len() as u32).to_le_bytes());
+            for value in values {
+                write_short_str(&mut buf, value);
+            }
         }
-
         Ok(rust_bytes_to_java(env, &buf))
     })
 }
@@ -559,10 +5686,15 @@ pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_subscribe(
     filter_ptr: jlong,
 ) -> jlong {
     with_exception(&mut env, 0, |_env| {
-        let ndb = unsafe { util::ptr_to_ref::<Arc<Ndb>>(ndb_ptr, "ndb")? };
-        let filter = unsafe { util::ptr_to_ref::<Filter>(filter_ptr, "filter")? };
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let filter = unsafe { util::ptr_to_ref::<FilterHandle>(filter_ptr, "filter")? };
 
-        let sub = ndb.subscribe(&[filter.clone()])?;
+        if filter.kind_range().is_some() {
+            return Err(Error::InvalidArgument(
+                "Filters with a kind range over 256 kinds cannot be subscribed to".to_string(),
+            ));
+        }
+        let sub = handle.subscribe(&[filter.filter().clone()])?;
         Ok(sub.id() as jlong)
     })
 }
@@ -577,10 +5709,10 @@ pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_pollForNotes(
     max_notes: jint,
 ) -> jbyteArray {
     with_exception(&mut env, std::ptr::null_mut(), |env| {
-        let ndb = unsafe { util::ptr_to_ref::<Arc<Ndb>>(ndb_ptr, "ndb")? };
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
         let sub = nostrdb::Subscription::new(sub_id as u64);
 
-        let note_keys = ndb.poll_for_notes(sub, max_notes as u32);
+        let note_keys = handle.poll(sub, max_notes as u32)?;
 
         // Serialize as [count:4][key1:8][key2:8]...
         let mut buf = Vec::with_capacity(4 + note_keys.len() * 8);
@@ -593,6 +5725,30 @@ pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_pollForNotes(
     })
 }
 
+/// Poll for new notes on subscription, returning the note keys as a long[]
+///
+/// Same keys, in the same order, as `pollForNotes`, without the byte framing.
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_pollForNoteKeys(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    sub_id: jlong,
+    max_notes: jint,
+) -> jlongArray {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        let sub = nostrdb::Subscription::new(sub_id as u64);
+
+        let keys: Vec<jlong> = handle
+            .poll(sub, max_notes as u32)?
+            .iter()
+            .map(|key| key.as_u64() as jlong)
+            .collect();
+        rust_longs_to_java(env, &keys)
+    })
+}
+
 /// Unsubscribe from a subscription
 #[no_mangle]
 pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_unsubscribe(
@@ -602,72 +5758,598 @@ pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_unsubscribe(
     sub_id: jlong,
 ) {
     let _ = with_exception(&mut env, (), |_env| {
-        let ndb = unsafe { util::ptr_to_mut::<Arc<Ndb>>(ndb_ptr, "ndb")? };
-        let sub = nostrdb::Subscription::new(sub_id as u64);
-        // Note: unsubscribe requires &mut self
-        let ndb_mut = Arc::get_mut(ndb).ok_or(Error::InvalidState(
-            "Cannot unsubscribe: Ndb has multiple references".to_string(),
-        ))?;
-        ndb_mut.unsubscribe(sub)?;
-        Ok(())
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        handle.unsubscribe(nostrdb::Subscription::new(sub_id as u64))
     });
 }
 
+// ============================================================================
+// Health Monitoring
+// ============================================================================
+
+/// Start (or replace) the health monitor for a database
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `callback` - Object implementing `HealthCallback`, or null to stop monitoring
+/// * `check_interval_ms` - Milliseconds between checks (must be positive)
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_setHealthCallback(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    callback: JObject,
+    check_interval_ms: jlong,
+) {
+    with_exception(&mut env, (), |env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        if callback.is_null() {
+            return handle.set_health_monitor(None);
+        }
+        if check_interval_ms <= 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Check interval must be positive, got {}",
+                check_interval_ms
+            )));
+        }
+
+        let monitor = handle.start_health_monitor(
+            env.get_java_vm()?,
+            env.new_global_ref(&callback)?,
+            Duration::from_millis(check_interval_ms as u64),
+        )?;
+        handle.set_health_monitor(Some(monitor))
+    })
+}
+
+/// Set health monitor thresholds
+///
+/// Takes effect the next time a health callback is registered.
+///
+/// # Arguments
+/// * `ndb_ptr` - Pointer to the Ndb instance
+/// * `map_nearly_full_percent` - Map utilization (0-100) that triggers MAP_NEARLY_FULL
+/// * `ingest_backlog_high` - Events accepted but not yet stored (at least 1)
+///   that trigger INGEST_BACKLOG_HIGH
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_setHealthThresholds(
+    mut env: JNIEnv,
+    _class: JClass,
+    ndb_ptr: jlong,
+    map_nearly_full_percent: jint,
+    ingest_backlog_high: jlong,
+) {
+    with_exception(&mut env, (), |_env| {
+        let handle = unsafe { util::ptr_to_ref::<NdbHandle>(ndb_ptr, "ndb")? };
+        if !(0..=100).contains(&map_nearly_full_percent) {
+            return Err(Error::InvalidArgument(format!(
+                "Map threshold must be between 0 and 100, got {}",
+                map_nearly_full_percent
+            )));
+        }
+        if ingest_backlog_high < 1 {
+            return Err(Error::InvalidArgument(format!(
+                "Backlog threshold must be positive, got {}",
+                ingest_backlog_high
+            )));
+        }
+        let mut thresholds = handle.thresholds()?;
+        thresholds.map_nearly_full_percent = map_nearly_full_percent as u64;
+        thresholds.ingest_backlog_high = ingest_backlog_high as u64;
+        Ok(())
+    })
+}
+
+// ============================================================================
+// Version Information
+// ============================================================================
+
+/// Describe the loaded native library
+///
+/// # Returns
+/// "nostrdb-jni <version> (nostrdb <version>, git <hash>)"
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_nativeVersion(
+    mut env: JNIEnv,
+    _class: JClass,
+) -> jstring {
+    with_exception(&mut env, std::ptr::null_mut(), |env| {
+        let version = format!(
+            "nostrdb-jni {} (nostrdb {}, git {})",
+            env!("CARGO_PKG_VERSION"),
+            env!("NOSTRDB_JNI_NOSTRDB_VERSION"),
+            env!("NOSTRDB_JNI_GIT_HASH"),
+        );
+        Ok(env.new_string(version)?.into_raw())
+    })
+}
+
+/// Get the ABI version of the native library (see [`ABI_VERSION`])
+#[no_mangle]
+pub extern "system" fn Java_xyz_tcheeric_nostrdb_NostrdbNative_nativeAbiVersion(
+    _env: JNIEnv,
+    _class: JClass,
+) -> jint {
+    ABI_VERSION
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
-/// Serialize a Note to JSON bytes
-fn serialize_note(note: &nostrdb::Note) -> Result<Vec<u8>> {
-    let tags: Vec<Vec<String>> = note
-        .tags()
-        .iter()
-        .map(|tag| {
-            let mut tag_vec = Vec::new();
-            for i in 0..tag.count() {
-                if let Some(s) = tag.get_str(i as u16) {
-                    tag_vec.push(s.to_string());
-                }
-            }
-            tag_vec
-        })
-        .collect();
+/// Serialize a ProfileRecord to JSON bytes
+///
+/// Besides the kind-0 fields, this carries what nostrdb records about the
+/// profile: when it was received, the key of the kind-0 note it came from,
+/// the LNURL nostrdb resolved, and damus' extensions. Absent values are
+/// null, as are the kind-0 fields of a record without a parsed profile.
+fn serialize_profile(profile_record: &nostrdb::ProfileRecord) -> Result<Vec<u8>> {
+    let record = profile_record.record();
+    let profile = record.profile();
 
+    // nostrdb leaves unset record fields at 0, which no note key or
+    // timestamp of a received profile can be
     let json = serde_json::json!({
-        "id": hex::encode(note.id()),
-        "pubkey": hex::encode(note.pubkey()),
-        "kind": note.kind(),
-        "created_at": note.created_at(),
-        "content": note.content(),
-        "sig": hex::encode(note.sig()),
-        "tags": tags,
+        "name": profile.and_then(|p| p.name()),
+        "display_name": profile.and_then(|p| p.display_name()),
+        "about": profile.and_then(|p| p.about()),
+        "picture": profile.and_then(|p| p.picture()),
+        "banner": profile.and_then(|p| p.banner()),
+        "website": profile.and_then(|p| p.website()),
+        "lud06": profile.and_then(|p| p.lud06()),
+        "lud16": profile.and_then(|p| p.lud16()),
+        "nip05": profile.and_then(|p| p.nip05()),
+        "reactions": profile.map(|p| p.reactions()),
+        "damus_donation": profile.map(|p| p.damus_donation()),
+        "damus_donation_v2": profile.map(|p| p.damus_donation_v2()),
+        "received_at": Some(record.received_at()).filter(|&at| at != 0),
+        "note_key": Some(record.note_key()).filter(|&key| key != 0),
+        "lnurl": record.lnurl(),
     });
 
     Ok(serde_json::to_vec(&json)?)
 }
 
-/// Serialize a ProfileRecord to JSON bytes
-fn serialize_profile(profile_record: &nostrdb::ProfileRecord) -> Result<Vec<u8>> {
-    let record = profile_record.record();
+/// The pubkeys of the profiles matching a name search, as `searchProfiles`
+/// describes
+fn search_profile_pubkeys(
+    ndb: &nostrdb::Ndb,
+    txn: &nostrdb::Transaction,
+    query: &str,
+    limit: jint,
+    flags: jint,
+) -> Result<Vec<[u8; 32]>> {
+    if flags & !search::PROFILE_SEARCH_FOLD != 0 {
+        return Err(Error::InvalidArgument(format!(
+            "Unknown profile search flags: {:#x}",
+            flags
+        )));
+    }
+    if flags & search::PROFILE_SEARCH_FOLD == 0 {
+        let results = ndb.search_profile(txn, query, limit as u32)?;
+        return Ok(results.into_iter().copied().collect());
+    }
 
-    // Get the profile from the record
-    let profile = record.profile();
+    let folded = search::fold(query.trim());
+    if folded.is_empty() {
+        return Err(Error::InvalidArgument(
+            "Search query must not be blank".to_string(),
+        ));
+    }
+    // Every kind-0 note is a candidate, but only the one each profile
+    // record was built from is matched, so each author comes up once; a
+    // record without a note key is matched on its author's newest note
+    let filter = Filter::new().kinds([0]).build();
+    let mut seen = HashSet::new();
+    let results = query_accepted(ndb, txn, &filter, Some(limit), |result| {
+        let pubkey = result.note.pubkey();
+        let record = match ndb.get_profile_by_pubkey(txn, pubkey) {
+            Ok(profile) => profile.record(),
+            Err(nostrdb::Error::NotFound) => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        let current = match record.note_key() {
+            0 => seen.insert(*pubkey),
+            key => key == result.note_key.as_u64(),
+        };
+        if !current {
+            return Ok(false);
+        }
+        let profile = record.profile();
+        let names = [
+            profile.and_then(|p| p.name()),
+            profile.and_then(|p| p.display_name()),
+        ];
+        Ok(search::profile_matches(&folded, &names))
+    })?;
+    Ok(results.iter().map(|result| *result.note.pubkey()).collect())
+}
+
+/// One string field of a profile by `getProfileField` id
+fn profile_field<'a>(profile: nostrdb::NdbProfile<'a>, field_id: jint) -> Option<&'a str> {
+    match field_id {
+        0 => profile.name(),
+        1 => profile.display_name(),
+        2 => profile.about(),
+        3 => profile.picture(),
+        4 => profile.banner(),
+        5 => profile.nip05(),
+        6 => profile.lud06(),
+        7 => profile.lud16(),
+        8 => profile.website(),
+        _ => None,
+    }
+}
 
-    let json = if let Some(p) = profile {
-        serde_json::json!({
-            "name": p.name(),
-            "display_name": p.display_name(),
-            "about": p.about(),
-            "picture": p.picture(),
-            "banner": p.banner(),
-            "website": p.website(),
-            "lud06": p.lud06(),
-            "lud16": p.lud16(),
-            "nip05": p.nip05(),
+/// Append `[len:2][utf8]`, cutting `value` at a character boundary to fit
+fn write_short_str(buf: &mut Vec<u8>, value: &str) {
+    let mut len = value.len().min(u16::MAX as usize);
+    while !value.is_char_boundary(len) {
+        len -= 1;
+    }
+    buf.extend_from_slice(&(len as u16).to_le_bytes());
+    buf.extend_from_slice(&value.as_bytes()[..len]);
+}
+
+/// Convert serialized kinds ([kind1:4][kind2:4]...) for a filter
+fn java_kinds(env: &mut JNIEnv, kinds: &JByteArray) -> Result<Vec<u64>> {
+    let bytes = java_bytes_to_rust(env, kinds)?;
+    if bytes.len() % 4 != 0 {
+        return Err(Error::InvalidArgument(format!(
+            "Kinds must be 4 bytes each, got {} bytes",
+            bytes.len()
+        )));
+    }
+
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|chunk| {
+            let arr: [u8; 4] = chunk.try_into().unwrap();
+            u32::from_le_bytes(arr) as u64
         })
-    } else {
-        serde_json::json!({})
-    };
+        .collect())
+}
 
-    Ok(serde_json::to_vec(&json)?)
+/// Convert an int[] of kinds for a filter, rejecting negative kinds
+fn java_int_kinds(env: &mut JNIEnv, kinds: &JIntArray) -> Result<Vec<u64>> {
+    let len = env.get_array_length(kinds)?;
+    let mut buf = vec![0; len as usize];
+    env.get_int_array_region(kinds, 0, &mut buf)?;
+
+    buf.into_iter()
+        .map(|kind| {
+            u64::try_from(kind).map_err(|_| {
+                Error::InvalidArgument(format!("Kind must not be negative, got {}", kind))
+            })
+        })
+        .collect()
+}
+
+/// Convert a packed array of 32-byte values, treating null as empty
+fn java_optional_32_vec(env: &mut JNIEnv, arr: &JByteArray) -> Result<Vec<[u8; 32]>> {
+    if arr.is_null() {
+        return Ok(Vec::new());
+    }
+    java_bytes_to_32_vec(env, arr)
+}
+
+/// Decode a String[] of 64-character hex values (ids or pubkeys)
+///
+/// The error names the index of the first malformed entry.
+fn java_hex_32_vec(env: &mut JNIEnv, arr: &JObjectArray, what: &str) -> Result<Vec<[u8; 32]>> {
+    let len = env.get_array_length(arr)?;
+    let mut values = Vec::with_capacity(len as usize);
+    for i in 0..len {
+        let obj = env.get_object_array_element(arr, i)?;
+        let invalid = |reason: String| {
+            Error::InvalidArgument(format!("Invalid {} at index {}: {}", what, i, reason))
+        };
+        if obj.is_null() {
+            return Err(invalid("null".to_string()));
+        }
+        let hex_str = java_string_to_rust(env, &JString::from(obj))?;
+        let mut value = [0u8; 32];
+        hex::decode_to_slice(&hex_str, &mut value)
+            .map_err(|_| invalid(format!("expected 64 hex characters, got {:?}", hex_str)))?;
+        values.push(value);
+    }
+    Ok(values)
+}
+
+/// Convert the arguments of a replaceable event filter
+fn java_replaceable(
+    env: &mut JNIEnv,
+    kind: jint,
+    author: &JByteArray,
+    d_tag: &JString,
+) -> Result<(u64, [u8; 32], String)> {
+    let kind = u64::try_from(kind)
+        .map_err(|_| Error::InvalidArgument(format!("Kind must not be negative, got {}", kind)))?;
+    let author = java_bytes_to_32(env, author)?;
+    let d_tag = java_string_to_rust(env, d_tag)?;
+    Ok((kind, author, d_tag))
+}
+
+/// Kind range from Java ints, rejecting negative or inverted bounds
+fn java_kind_range(min_kind: jint, max_kind: jint) -> Result<RangeInclusive<u64>> {
+    if min_kind < 0 {
+        return Err(Error::InvalidArgument(format!(
+            "Kind must not be negative, got {}",
+            min_kind
+        )));
+    }
+    if min_kind > max_kind {
+        return Err(Error::InvalidArgument(format!(
+            "Invalid kind range: min {} is greater than max {}",
+            min_kind, max_kind
+        )));
+    }
+    Ok(min_kind as u64..=max_kind as u64)
+}
+
+/// Kind, author, and `#d` tag of one replaceable event, latest version only
+fn replaceable(builder: FilterBuilder, kind: u64, author: &[u8; 32], d_tag: &str) -> FilterBuilder {
+    builder
+        .kinds([kind])
+        .authors([author])
+        .tags([d_tag], 'd')
+        .limit(1)
+}
+
+/// Convert a tag name and String[] of values for a string tag filter
+fn java_tag_filter(
+    env: &mut JNIEnv,
+    tag_name: &JString,
+    tag_values: &JObjectArray,
+) -> Result<(char, Vec<String>)> {
+    let tag = java_string_to_rust(env, tag_name)?;
+    let tag_char = tag.chars().next().ok_or(Error::Filter("Empty tag name".to_string()))?;
+
+    Ok((tag_char, java_strings(env, tag_values)?))
+}
+
+/// Convert a String[] to Rust Strings
+fn java_strings(env: &mut JNIEnv, arr: &JObjectArray) -> Result<Vec<String>> {
+    let len = env.get_array_length(arr)?;
+    let mut values: Vec<String> = Vec::with_capacity(len as usize);
+    for i in 0..len {
+        let obj = env.get_object_array_element(arr, i)?;
+        let s = java_string_to_rust(env, &JString::from(obj))?;
+        values.push(s);
+    }
+    Ok(values)
+}
+
+/// Resolve a Java long[] of filter pointers, requiring at least one
+///
+/// # Safety
+/// Every pointer must be a handle created by `box_to_ptr`, as for
+/// `util::ptr_to_ref`
+unsafe fn java_filters<'a>(env: &mut JNIEnv, ptrs: &JLongArray) -> Result<Vec<&'a FilterHandle>> {
+    let mut values = vec![0; env.get_array_length(ptrs)? as usize];
+    env.get_long_array_region(ptrs, 0, &mut values)?;
+    if values.is_empty() {
+        return Err(Error::InvalidArgument(
+            "At least one filter is required".to_string(),
+        ));
+    }
+    values
+        .iter()
+        .map(|&ptr| util::ptr_to_ref::<FilterHandle>(ptr, "filter"))
+        .collect()
+}
+
+/// Convert a tag name that must be exactly one character
+fn java_tag_name(env: &mut JNIEnv, tag_name: &JString) -> Result<char> {
+    let tag = java_string_to_rust(env, tag_name)?;
+    let mut chars = tag.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(Error::InvalidArgument(format!(
+            "Tag name must be a single character, got {:?}",
+            tag
+        ))),
+    }
+}
+
+/// Result limit of a single-filter query, or None for every match
+///
+/// A negative `limit` defers to the filter's own limit; otherwise the
+/// smaller of the two applies.
+fn effective_limit(limit: jint, filter: &Filter) -> Option<jint> {
+    let own = filter.limit().map(|own| own.min(jint::MAX as u64) as jint);
+    match own {
+        Some(own) if limit < 0 || own < limit => Some(own),
+        _ if limit < 0 => None,
+        _ => Some(limit),
+    }
+}
+
+/// Query a single filter, honouring its own limit and kind range
+fn query_filter<'a>(
+    ndb: &nostrdb::Ndb,
+    txn: &'a nostrdb::Transaction,
+    filter: &FilterHandle,
+    limit: jint,
+) -> Result<Vec<nostrdb::QueryResult<'a>>> {
+    match effective_limit(limit, filter) {
+        Some(limit) if filter.kind_range().is_none() => {
+            Ok(ndb.query(txn, std::slice::from_ref(filter.filter()), limit)?)
+        }
+        limit => query_accepted(ndb, txn, filter, limit, |result| {
+            Ok(filter.accepts_kind(result.note.kind() as u64))
+        }),
+    }
+}
+
+/// Query a single filter as `query_filter` does, skipping notes expired at `now`
+fn query_unexpired<'a>(
+    ndb: &nostrdb::Ndb,
+    txn: &'a nostrdb::Transaction,
+    filter: &FilterHandle,
+    limit: jint,
+    now: jlong,
+) -> Result<Vec<nostrdb::QueryResult<'a>>> {
+    if now < 0 {
+        return Err(Error::InvalidArgument(format!(
+            "Current time must not be negative, got {}",
+            now
+        )));
+    }
+    let limit = effective_limit(limit, filter);
+    query_accepted(ndb, txn, filter, limit, |result| {
+        Ok(filter.accepts_kind(result.note.kind() as u64)
+            && !expiry::is_expired(&result.note, now as u64))
+    })
+}
+
+/// Query a filter, keeping only the results accepted by `keep`
+///
+/// Candidates are fetched in batches that double until `limit` results
+/// are kept (every match if None) or the filter has no more matches.
+/// nostrdb allocates the result buffer up front, so batches start small
+/// rather than sized for the worst case.
+fn query_accepted<'a>(
+    ndb: &nostrdb::Ndb,
+    txn: &'a nostrdb::Transaction,
+    filter: &Filter,
+    limit: Option<jint>,
+    mut keep: impl FnMut(&nostrdb::QueryResult<'a>) -> Result<bool>,
+) -> Result<Vec<nostrdb::QueryResult<'a>>> {
+    let mut accepted = Vec::new();
+    if limit == Some(0) {
+        return Ok(accepted);
+    }
+    let mut evaluated = 0;
+    let mut batch = limit.map_or(1024, |limit| limit.min(1024));
+    loop {
+        let results = ndb.query(txn, std::slice::from_ref(filter), batch)?;
+        let fetched = results.len();
+        // Each batch repeats the previous one, in the same order
+        for result in results.into_iter().skip(evaluated) {
+            if keep(&result)? {
+                accepted.push(result);
+                if limit == Some(accepted.len() as jint) {
+                    return Ok(accepted);
+                }
+            }
+        }
+        if fetched < batch as usize || batch == jint::MAX {
+            return Ok(accepted);
+        }
+        evaluated = fetched;
+        batch = batch.saturating_mul(2);
+    }
+}
+
+/// Serialize query results as [count:4][key1:8][key2:8]...
+fn serialize_query_results(results: &[nostrdb::QueryResult]) -> Vec<u8> {
+    let keys: Vec<u64> = results.iter().map(|r| r.note_key.as_u64()).collect();
+    serialize_note_keys(&keys)
+}
+
+/// Serialize the notes of query results as [count:4]([len:4][noteJson:len])*
+fn serialize_notes(results: &[nostrdb::QueryResult], utf8: Utf8Mode) -> Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(4 + results.len() * 512);
+    buf.extend_from_slice(&(results.len() as u32).to_le_bytes());
+    for result in results {
+        write_framed_note(&result.note, utf8, &mut buf)?;
+    }
+    Ok(buf)
+}
+
+/// Append a note as [len:4][noteJson:len]
+fn write_framed_note(note: &nostrdb::Note, utf8: Utf8Mode, buf: &mut Vec<u8>) -> Result<()> {
+    write_framed(note, utf8, buf, write_note)
+}
+
+/// Append a note as [len:4][note:len], serialized by `write`
+fn write_framed(
+    note: &nostrdb::Note,
+    utf8: Utf8Mode,
+    buf: &mut Vec<u8>,
+    write: fn(&nostrdb::Note, Utf8Mode, &mut Vec<u8>) -> Result<()>,
+) -> Result<()> {
+    let start = buf.len();
+    buf.extend_from_slice(&[0; 4]);
+    write(note, utf8, buf)?;
+    let len = (buf.len() - start - 4) as u32;
+    buf[start..start + 4].copy_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+/// A note's JSON as a Java string
+fn note_json_to_java(env: &mut JNIEnv, note: &nostrdb::Note, utf8: Utf8Mode) -> Result<jstring> {
+    let json = serialize_note(note, utf8)?;
+    Ok(env.new_string(String::from_utf8_lossy(&json))?.into_raw())
+}
+
+/// Look up a note by internal key, or None if there is no such note
+fn find_note<'a>(
+    ndb: &nostrdb::Ndb,
+    txn: &'a nostrdb::Transaction,
+    note_key: jlong,
+) -> Result<Option<nostrdb::Note<'a>>> {
+    match ndb.get_note_by_key(txn, NoteKey::new(note_key as u64)) {
+        Ok(note) => Ok(Some(note)),
+        Err(nostrdb::Error::NotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The tag of a note at `index`, or IndexOutOfBounds
+fn note_tag<'a>(note: &nostrdb::Note<'a>, index: jint) -> Result<nostrdb::Tag<'a>> {
+    let tags = note.tags();
+    usize::try_from(index)
+        .ok()
+        .and_then(|index| tags.iter().nth(index))
+        .ok_or_else(|| {
+            Error::IndexOutOfBounds(format!(
+                "Tag index {} out of range for {} tags",
+                index,
+                tags.count()
+            ))
+        })
+}
+
+/// One element of a tag as UTF-8 bytes, with id elements hex-encoded
+/// unless `raw_ids` is set, or IndexOutOfBounds
+fn tag_elem_to_java(
+    env: &mut JNIEnv,
+    tag: &nostrdb::Tag,
+    elem_index: jint,
+    raw_ids: bool,
+) -> Result<jbyteArray> {
+    let elem = u16::try_from(elem_index)
+        .ok()
+        .and_then(|index| tag.get(index))
+        .ok_or_else(|| {
+            Error::IndexOutOfBounds(format!(
+                "Element index {} out of range for a tag of {} elements",
+                elem_index,
+                tag.count()
+            ))
+        })?;
+    Ok(match elem.variant() {
+        nostrdb::NdbStrVariant::Str(value) => rust_bytes_to_java(env, value.as_bytes()),
+        nostrdb::NdbStrVariant::Id(id) if raw_ids => rust_bytes_to_java(env, id),
+        nostrdb::NdbStrVariant::Id(id) => rust_bytes_to_java(env, hex::encode(id).as_bytes()),
+    })
+}
+
+/// The bytes of a note as nostrdb stores them
+fn note_bytes<'a>(note: &'a nostrdb::Note) -> &'a [u8] {
+    // SAFETY: a note points at `size()` bytes that stay valid for as long
+    // as the note (and the transaction it was read in)
+    unsafe { std::slice::from_raw_parts(note.as_ptr() as *const u8, note.size()) }
+}
+
+/// Serialize note keys in the query result format [count:4][key1:8]...
+fn serialize_note_keys(keys: &[u64]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(4 + keys.len() * 8);
+    buf.extend_from_slice(&(keys.len() as u32).to_le_bytes());
+    for key in keys {
+        buf.extend_from_slice(&key.to_le_bytes());
+    }
+    buf
 }
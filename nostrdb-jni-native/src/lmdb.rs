@@ -0,0 +1,170 @@
+//! LMDB map statistics for nostrdb-jni
+//!
+//! nostrdb-rs does not wrap LMDB's statistics calls, but a nostrdb read
+//! transaction is an `ndb_txn` holding the `MDB_txn` it reads through,
+//! and LMDB is linked into nostrdb's static library. The figures are read
+//! through that transaction, so they describe the environment nostrdb has
+//! open, from a reader slot nostrdb already holds.
+//!
+//! The page counts come from the last committed meta page while the free
+//! list is read from the transaction's snapshot, so they are estimates
+//! for monitoring only.
+
+use nostrdb::Transaction;
+use std::ffi::{c_int, c_uint, c_void};
+use std::ptr;
+
+const MDB_SUCCESS: c_int = 0;
+/// `MDB_cursor_op` value of `MDB_NEXT`
+const MDB_NEXT: c_int = 8;
+/// The database LMDB records free pages in
+const FREE_DBI: c_uint = 0;
+
+#[repr(C)]
+struct MdbEnv {
+    _opaque: [u8; 0],
+}
+
+#[repr(C)]
+struct MdbTxn {
+    _opaque: [u8; 0],
+}
+
+#[repr(C)]
+struct MdbCursor {
+    _opaque: [u8; 0],
+}
+
+#[repr(C)]
+struct MdbVal {
+    mv_size: usize,
+    mv_data: *mut c_void,
+}
+
+/// Mirrors nostrdb's public `struct ndb_txn`
+#[repr(C)]
+struct NdbTxn {
+    lmdb: *mut c_void,
+    mdb_txn: *mut MdbTxn,
+}
+
+// These mirror the C layouts, so fields that aren't read are still needed.
+// nostrdb builds LMDB without MDB_VL32, so mdb_size_t is size_t.
+#[allow(dead_code)]
+#[repr(C)]
+struct MdbEnvInfo {
+    me_mapaddr: *mut c_void,
+    me_mapsize: usize,
+    me_last_pgno: usize,
+    me_last_txnid: usize,
+    me_maxreaders: c_uint,
+    me_numreaders: c_uint,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct MdbStat {
+    ms_psize: c_uint,
+    ms_depth: c_uint,
+    ms_branch_pages: usize,
+    ms_leaf_pages: usize,
+    ms_overflow_pages: usize,
+    ms_entries: usize,
+}
+
+extern "C" {
+    fn mdb_txn_env(txn: *mut MdbTxn) -> *mut MdbEnv;
+    fn mdb_env_info(env: *mut MdbEnv, info: *mut MdbEnvInfo) -> c_int;
+    fn mdb_env_stat(env: *mut MdbEnv, stat: *mut MdbStat) -> c_int;
+    fn mdb_cursor_open(txn: *mut MdbTxn, dbi: c_uint, cursor: *mut *mut MdbCursor) -> c_int;
+    fn mdb_cursor_get(
+        cursor: *mut MdbCursor,
+        key: *mut MdbVal,
+        data: *mut MdbVal,
+        op: c_int,
+    ) -> c_int;
+    fn mdb_cursor_close(cursor: *mut MdbCursor);
+}
+
+/// A cursor, closed on drop
+struct Cursor(*mut MdbCursor);
+
+impl Drop for Cursor {
+    fn drop(&mut self) {
+        // SAFETY: the cursor came from mdb_cursor_open and is closed once
+        unsafe { mdb_cursor_close(self.0) }
+    }
+}
+
+/// Size and usage of a map
+#[derive(Debug, Clone, Copy)]
+pub struct MapStats {
+    /// Current map size in bytes
+    pub mapsize: usize,
+    /// Bytes of the map holding live data
+    pub used: u64,
+}
+
+/// Size and usage of the map of the environment `txn` reads from
+///
+/// Usage counts the pages LMDB has ever allocated, as recorded in the last
+/// committed meta page, less those on its free list, which later writes
+/// reuse before growing into the rest of the map. Returns None if LMDB's
+/// counts can't be read.
+pub fn map_stats(txn: &Transaction) -> Option<MapStats> {
+    // SAFETY: a nostrdb transaction is an ndb_txn whose mdb_txn stays a
+    // live read transaction until `txn` is dropped; out-pointers point at
+    // locals
+    unsafe {
+        let mdb_txn = (*(txn.as_ptr() as *const NdbTxn)).mdb_txn;
+        if mdb_txn.is_null() {
+            return None;
+        }
+        let env = mdb_txn_env(mdb_txn);
+        let mut info = std::mem::zeroed::<MdbEnvInfo>();
+        let mut stat = std::mem::zeroed::<MdbStat>();
+        if env.is_null()
+            || mdb_env_info(env, &mut info) != MDB_SUCCESS
+            || mdb_env_stat(env, &mut stat) != MDB_SUCCESS
+        {
+            return None;
+        }
+        let allocated = info.me_last_pgno as u64 + 1;
+        let free = free_pages(mdb_txn).unwrap_or(0);
+        Some(MapStats {
+            mapsize: info.me_mapsize,
+            used: allocated.saturating_sub(free) * stat.ms_psize as u64,
+        })
+    }
+}
+
+/// Pages on the environment's free list, as of `txn`'s snapshot
+///
+/// Each free list record holds a page number list whose first element is
+/// its length, as `mdb_stat -f` reads it.
+///
+/// # Safety
+/// `txn` must be a live transaction.
+unsafe fn free_pages(txn: *mut MdbTxn) -> Option<u64> {
+    let mut cursor = ptr::null_mut();
+    if mdb_cursor_open(txn, FREE_DBI, &mut cursor) != MDB_SUCCESS {
+        return None;
+    }
+    let cursor = Cursor(cursor);
+
+    let mut key = MdbVal {
+        mv_size: 0,
+        mv_data: ptr::null_mut(),
+    };
+    let mut data = MdbVal {
+        mv_size: 0,
+        mv_data: ptr::null_mut(),
+    };
+    let mut free = 0u64;
+    while mdb_cursor_get(cursor.0, &mut key, &mut data, MDB_NEXT) == MDB_SUCCESS {
+        if data.mv_size >= std::mem::size_of::<usize>() {
+            free += ptr::read_unaligned(data.mv_data as *const usize) as u64;
+        }
+    }
+    Some(free)
+}
@@ -0,0 +1,182 @@
+//! Mute list filtering for nostrdb-jni
+//!
+//! `queryExcluding` drops notes hidden by a mute list while it collects
+//! query results, so muted notes never cross into Java and the query limit
+//! counts only visible notes. `getMuteList` reads an author's stored mute
+//! list (NIP-51, kind 10000), and `queryExcludingMuteList` queries with it
+//! directly.
+//!
+//! Only a mute list's public tags are read. Its private entries are
+//! encrypted to the author in its content, which needs their secret key,
+//! so they are skipped and only reported as present.
+
+use nostrdb::{Filter, NdbStrVariant, Note, Tag};
+use std::collections::HashSet;
+
+use crate::json::{note_content, Utf8Mode};
+
+/// Kind of a mute list
+pub const MUTE_LIST_KIND: u64 = 10000;
+
+/// Filter for an author's newest mute list
+pub fn mute_list_filter(pubkey: &[u8; 32]) -> Filter {
+    Filter::new()
+        .authors([pubkey])
+        .kinds([MUTE_LIST_KIND])
+        .limit(1)
+        .build()
+}
+
+/// The public entries of a mute list, each once and in list order
+pub struct MuteEntries<'a> {
+    pub authors: Vec<[u8; 32]>,
+    pub ids: Vec<[u8; 32]>,
+    pub hashtags: Vec<&'a str>,
+    pub words: Vec<&'a str>,
+    /// The list also has encrypted private entries, which were skipped
+    pub private: bool,
+}
+
+impl<'a> MuteEntries<'a> {
+    /// Read the `p`, `e`, `t`, and `word` tags of a mute list
+    ///
+    /// Ids that aren't 64 hex characters and empty hashtags or words are
+    /// skipped.
+    pub fn parse(note: &Note<'a>) -> Self {
+        let mut entries = MuteEntries {
+            authors: Vec::new(),
+            ids: Vec::new(),
+            hashtags: Vec::new(),
+            words: Vec::new(),
+            private: note.content_len() != 0,
+        };
+        let mut seen_authors = HashSet::new();
+        let mut seen_ids = HashSet::new();
+        for tag in note.tags().iter() {
+            match tag.get_str(0) {
+                Some("p") => {
+                    if let Some(pubkey) = tag_id(&tag, 1).filter(|id| seen_authors.insert(*id)) {
+                        entries.authors.push(pubkey);
+                    }
+                }
+                Some("e") => {
+                    if let Some(id) = tag_id(&tag, 1).filter(|id| seen_ids.insert(*id)) {
+                        entries.ids.push(id);
+                    }
+                }
+                Some("t") => push_unique(&mut entries.hashtags, tag.get_str(1)),
+                Some("word") => push_unique(&mut entries.words, tag.get_str(1)),
+                _ => {}
+            }
+        }
+        entries
+    }
+}
+
+fn push_unique<'a>(values: &mut Vec<&'a str>, value: Option<&'a str>) {
+    if let Some(value) = value.filter(|value| !value.is_empty() && !values.contains(value)) {
+        values.push(value);
+    }
+}
+
+/// Muted authors, events, hashtags, and words
+pub struct MuteList {
+    authors: HashSet<[u8; 32]>,
+    ids: HashSet<[u8; 32]>,
+    /// Lowercased
+    hashtags: HashSet<String>,
+    /// Lowercased
+    words: Vec<String>,
+    /// Also hide notes that reference a muted author or event
+    threads: bool,
+}
+
+impl MuteList {
+    pub fn new(authors: Vec<[u8; 32]>, ids: Vec<[u8; 32]>, threads: bool) -> Self {
+        Self {
+            authors: authors.into_iter().collect(),
+            ids: ids.into_iter().collect(),
+            hashtags: HashSet::new(),
+            words: Vec::new(),
+            threads,
+        }
+    }
+
+    /// Mute the entries of a stored mute list
+    pub fn from_entries(entries: &MuteEntries, threads: bool) -> Self {
+        Self {
+            authors: entries.authors.iter().copied().collect(),
+            ids: entries.ids.iter().copied().collect(),
+            hashtags: entries
+                .hashtags
+                .iter()
+                .map(|tag| tag.to_lowercase())
+                .collect(),
+            words: entries
+                .words
+                .iter()
+                .map(|word| word.to_lowercase())
+                .collect(),
+            threads,
+        }
+    }
+
+    /// Whether a note is hidden
+    ///
+    /// A note is hidden if its author or id is muted, if it has a `t` tag
+    /// naming a muted hashtag, or if its content contains a muted word,
+    /// ignoring case. With thread muting, so is a note with a `p` tag
+    /// naming a muted author or an `e` tag naming a muted event.
+    pub fn hides(&self, note: &Note) -> bool {
+        if self.authors.contains(note.pubkey()) || self.ids.contains(note.id()) {
+            return true;
+        }
+        if !self.hashtags.is_empty() && note.tags().iter().any(|tag| self.hides_hashtag(&tag)) {
+            return true;
+        }
+        if !self.words.is_empty() {
+            // Muted words are matched against repaired content whatever the
+            // handle's UTF-8 mode, so a bad byte can't get a note past a mute;
+            // lossy repair never fails
+            let content = note_content(note, Utf8Mode::Lossy)
+                .unwrap_or_default()
+                .to_lowercase();
+            if self
+                .words
+                .iter()
+                .any(|word| content.contains(word.as_str()))
+            {
+                return true;
+            }
+        }
+        self.threads && note.tags().iter().any(|tag| self.hides_reference(&tag))
+    }
+
+    fn hides_hashtag(&self, tag: &Tag) -> bool {
+        tag.get_str(0) == Some("t")
+            && tag
+                .get_str(1)
+                .is_some_and(|hashtag| self.hashtags.contains(&hashtag.to_lowercase()))
+    }
+
+    fn hides_reference(&self, tag: &Tag) -> bool {
+        let muted = match tag.get_str(0) {
+            Some("p") => &self.authors,
+            Some("e") => &self.ids,
+            _ => return false,
+        };
+        tag_id(tag, 1).is_some_and(|id| muted.contains(&id))
+    }
+}
+
+/// A 32-byte tag value, stored either in binary or as hex
+pub fn tag_id(tag: &Tag, index: u16) -> Option<[u8; 32]> {
+    match tag.get(index)?.variant() {
+        NdbStrVariant::Id(id) => Some(*id),
+        NdbStrVariant::Str(hex_str) => {
+            let mut id = [0u8; 32];
+            hex::decode_to_slice(hex_str, &mut id).ok()?;
+            Some(id)
+        }
+    }
+}
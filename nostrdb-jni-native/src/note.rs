@@ -0,0 +1,72 @@
+//! Note handles for nostrdb-jni
+//!
+//! A [`NoteHandle`] holds a note fetched once, so Java can read several of
+//! its fields without serializing the note or looking it up again. The
+//! note points into its transaction's snapshot of the database; like a
+//! query cursor, the handle stops working (with IllegalStateException)
+//! once the transaction has ended, and each read keeps the transaction
+//! open until it is done.
+
+use nostrdb::Note;
+use std::ops::Deref;
+
+use crate::error::Result;
+use crate::handle::{TxnGuard, TxnHandle, TxnLiveness};
+use crate::json::Utf8Mode;
+use crate::util::{Handle, HandleKind};
+
+/// A note read from a transaction, with its fields read on demand
+pub struct NoteHandle {
+    /// Borrows the transaction; only read through a [`NoteRef`]
+    note: Note<'static>,
+    txn_alive: TxnLiveness,
+    /// From the database the note was read from
+    utf8: Utf8Mode,
+}
+
+impl Handle for NoteHandle {
+    const KIND: HandleKind = HandleKind::Note;
+}
+
+impl NoteHandle {
+    /// Hold `note`, read from `txn`
+    ///
+    /// # Safety
+    /// `txn` must be the transaction handle Java holds, which stays at the
+    /// same address until it is freed; every read of the note keeps it
+    /// open.
+    pub unsafe fn new(note: Note<'static>, txn: &TxnHandle, utf8: Utf8Mode) -> Self {
+        Self {
+            note,
+            txn_alive: txn.liveness(),
+            utf8,
+        }
+    }
+
+    /// The note, keeping its transaction open until the borrow is dropped
+    pub fn note(&self) -> Result<NoteRef<'_>> {
+        Ok(NoteRef {
+            _open: self.txn_alive.guard("note")?,
+            note: &self.note,
+        })
+    }
+
+    /// How content that isn't valid UTF-8 is returned
+    pub fn utf8_mode(&self) -> Utf8Mode {
+        self.utf8
+    }
+}
+
+/// A handle's note, borrowed while its transaction is held open
+pub struct NoteRef<'a> {
+    _open: TxnGuard<'a>,
+    note: &'a Note<'a>,
+}
+
+impl<'a> Deref for NoteRef<'a> {
+    type Target = Note<'a>;
+
+    fn deref(&self) -> &Note<'a> {
+        self.note
+    }
+}
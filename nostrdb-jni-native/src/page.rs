@@ -0,0 +1,118 @@
+//! Keyset pagination for nostrdb-jni
+//!
+//! `queryPage` returns results in pages, newest first, with ties on
+//! `created_at` broken by note key (higher first). Each page comes with an
+//! opaque cursor holding the position of its last note. The next page
+//! lowers the filter's `until` to that note's `created_at` and skips every
+//! note at or before the position, so pages neither overlap nor leave gaps,
+//! even when many notes share a timestamp. Unlike a
+//! [`QueryCursor`](crate::cursor::QueryCursor), a page cursor is plain data
+//! and can be used with a later transaction.
+
+use jni::sys::jint;
+use nostrdb::{Ndb, Transaction};
+
+use crate::error::{Error, Result};
+use crate::filter::FilterHandle;
+
+/// Length of a serialized page cursor
+pub const CURSOR_LEN: usize = 16;
+
+/// Position of a note in page order; later pages hold smaller positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    created_at: u64,
+    key: u64,
+}
+
+impl Position {
+    /// Parse a cursor returned with a previous page
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != CURSOR_LEN {
+            return Err(Error::InvalidArgument(format!(
+                "Page cursor must be {} bytes, got {}",
+                CURSOR_LEN,
+                bytes.len()
+            )));
+        }
+        let (created_at, key) = bytes.split_at(8);
+        Ok(Self {
+            created_at: u64::from_le_bytes(created_at.try_into().unwrap()),
+            key: u64::from_le_bytes(key.try_into().unwrap()),
+        })
+    }
+
+    /// Serialize as [created_at:8][key:8]
+    pub fn encode(&self) -> [u8; CURSOR_LEN] {
+        let mut bytes = [0u8; CURSOR_LEN];
+        bytes[..8].copy_from_slice(&self.created_at.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.key.to_le_bytes());
+        bytes
+    }
+}
+
+/// One page of results
+pub struct Page {
+    pub keys: Vec<u64>,
+    /// Cursor for the following page, or None if this is the last one
+    pub next: Option<Position>,
+}
+
+/// Query the page of at most `size` results following `after`, or the
+/// first page if `after` is None
+///
+/// The filter's own limit is ignored. Matches are fetched in windows that
+/// double until every note sharing the page's last timestamp is known, as
+/// nostrdb orders ties arbitrarily.
+pub fn query_page(
+    ndb: &Ndb,
+    txn: &Transaction,
+    filter: &FilterHandle,
+    size: jint,
+    after: Option<Position>,
+) -> Result<Page> {
+    let page_filter = match filter.for_page(after.map(|after| after.created_at))? {
+        Some(page_filter) => page_filter,
+        None => {
+            return Ok(Page {
+                keys: Vec::new(),
+                next: None,
+            })
+        }
+    };
+    let filters = std::slice::from_ref(page_filter.filter());
+    let mut window = size.saturating_add(1);
+    let size = size as usize;
+    loop {
+        let results = ndb.query(txn, filters, window)?;
+        let complete = results.len() < window as usize || window == jint::MAX;
+        let oldest = results.last().map(|result| result.note.created_at());
+
+        let mut matches: Vec<Position> = results
+            .iter()
+            .filter(|result| filter.accepts_kind(result.note.kind() as u64))
+            .map(|result| Position {
+                created_at: result.note.created_at(),
+                key: result.note_key.as_u64(),
+            })
+            .filter(|position| match after {
+                Some(after) => *position < after,
+                None => true,
+            })
+            .collect();
+        matches.sort_unstable_by(|a, b| b.cmp(a));
+
+        // Fetching past the last included timestamp means its ties are all in
+        let settled =
+            complete || (matches.len() >= size && oldest < Some(matches[size - 1].created_at));
+        if settled {
+            let more = matches.len() > size || !complete;
+            matches.truncate(size);
+            return Ok(Page {
+                keys: matches.iter().map(|position| position.key).collect(),
+                next: if more { matches.last().copied() } else { None },
+            });
+        }
+        window = window.saturating_mul(2);
+    }
+}
@@ -0,0 +1,58 @@
+//! Relay lists for nostrdb-jni
+//!
+//! `getRelayList` reads an author's newest relay list (NIP-65, kind 10002)
+//! and extracts its `r` tags, so callers can pick the relays to read an
+//! author from or publish to. A tag without a marker is both read and
+//! write; a URL listed more than once gets the union of its markers, at
+//! the position it was first listed. Tags with an empty URL or an unknown
+//! marker are skipped.
+
+use nostrdb::{Filter, Note};
+
+/// Kind of a relay list
+pub const RELAY_LIST_KIND: u64 = 10002;
+
+/// Mode bit of a relay the author reads from
+pub const RELAY_READ: u8 = 1 << 0;
+/// Mode bit of a relay the author writes to
+pub const RELAY_WRITE: u8 = 1 << 1;
+
+/// Filter for an author's newest relay list
+pub fn relay_list_filter(pubkey: &[u8; 32]) -> Filter {
+    Filter::new()
+        .authors([pubkey])
+        .kinds([RELAY_LIST_KIND])
+        .limit(1)
+        .build()
+}
+
+/// The relays of a relay list, with their modes
+pub struct RelayList<'a> {
+    pub relays: Vec<(&'a str, u8)>,
+}
+
+impl<'a> RelayList<'a> {
+    /// Read the `r` tags of a relay list
+    pub fn parse(note: &Note<'a>) -> Self {
+        let mut relays: Vec<(&'a str, u8)> = Vec::new();
+        for tag in note.tags().iter() {
+            if tag.get_str(0) != Some("r") {
+                continue;
+            }
+            let Some(url) = tag.get_str(1).filter(|url| !url.is_empty()) else {
+                continue;
+            };
+            let mode = match tag.get_str(2) {
+                None => RELAY_READ | RELAY_WRITE,
+                Some("read") => RELAY_READ,
+                Some("write") => RELAY_WRITE,
+                Some(_) => continue,
+            };
+            match relays.iter_mut().find(|(listed, _)| *listed == url) {
+                Some((_, listed_mode)) => *listed_mode |= mode,
+                None => relays.push((url, mode)),
+            }
+        }
+        RelayList { relays }
+    }
+}
@@ -0,0 +1,39 @@
+//! Repost counts for nostrdb-jni
+//!
+//! `countReposts` counts the reposts (kind 6) and quotes of a note
+//! natively. A quote is a text note that references the note in a `q` tag
+//! (NIP-18) or in an `e` tag marked "mention" (NIP-10); a reply references
+//! it too, but is not a quote, so candidates' tags are checked here.
+
+use nostrdb::{Filter, Note};
+
+use crate::mute::tag_id;
+
+/// Kind of a repost of a text note
+pub const REPOST_KIND: u64 = 6;
+
+/// Filter for the reposts of `id`
+pub fn reposts_filter(id: &[u8; 32]) -> Filter {
+    Filter::new().kinds([REPOST_KIND]).event(id).build()
+}
+
+/// Filters for the text notes that may quote `id`: those with a `q` tag
+/// naming it, and those with an `e` tag naming it
+pub fn quote_candidate_filters(id: &[u8; 32]) -> [Filter; 2] {
+    [
+        Filter::new().kinds([1]).id_tags('q', [id]).build(),
+        Filter::new().kinds([1]).event(id).build(),
+    ]
+}
+
+/// Whether a note quotes `id`
+pub fn quotes(note: &Note, id: &[u8; 32]) -> bool {
+    note.tags().iter().any(|tag| {
+        let quoting = match tag.get_str(0) {
+            Some("q") => true,
+            Some("e") => tag.get_str(3) == Some("mention"),
+            _ => false,
+        };
+        quoting && tag_id(&tag, 1).as_ref() == Some(id)
+    })
+}
@@ -0,0 +1,184 @@
+//! Full-text note search for nostrdb-jni
+//!
+//! Runs a search filter through nostrdb's text index and reports, for
+//! each hit, where the query matched in the note's content, so Java can
+//! highlight hits without searching the content again.
+//!
+//! Matches are located as case-insensitive substrings: the whole query if
+//! it appears, otherwise the earliest of its words. Hits whose content
+//! contains none of them are dropped, so every hit has a match to report.
+//!
+//! Profile search can also fold names (see [`fold`]) so that "jose"
+//! finds "José"; nostrdb's profile index only matches names as stored.
+
+use std::cmp::Reverse;
+
+use jni::sys::jint;
+use nostrdb::{Filter, Ndb, Transaction};
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::{Error, Result};
+use crate::json::{note_content, Utf8Mode};
+
+/// `searchNotes`: newest hits first
+pub const SEARCH_ORDER_NEWEST: jint = 0;
+/// `searchNotes`: most relevant hits first
+pub const SEARCH_ORDER_RELEVANCE: jint = 1;
+
+/// `searchProfiles` flag: match folded names (see [`fold`]) instead of
+/// nostrdb's index
+pub const PROFILE_SEARCH_FOLD: jint = 1 << 0;
+
+/// One search hit
+pub struct Hit {
+    pub key: u64,
+    pub created_at: u64,
+    /// Byte offset of the first match in the content
+    pub offset: u32,
+    /// Byte length of the first match in the content
+    pub len: u32,
+    /// Number of times the query's words occur in the content
+    score: usize,
+}
+
+impl Hit {
+    /// Serialize as [key:8][created_at:8][offset:4][length:4]
+    pub fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.key.to_le_bytes());
+        buf.extend_from_slice(&self.created_at.to_le_bytes());
+        buf.extend_from_slice(&self.offset.to_le_bytes());
+        buf.extend_from_slice(&self.len.to_le_bytes());
+    }
+}
+
+/// Search note contents for `query`, returning at most `limit` hits
+///
+/// Relevance ranks every match by how often the query's words occur in
+/// it, ties newest first; it has to see every match, so it is slower than
+/// time order for common terms. Offsets are into the content as
+/// [`note_content`] returns it under `utf8`.
+pub fn search_notes(
+    ndb: &Ndb,
+    txn: &Transaction,
+    query: &str,
+    limit: usize,
+    order: jint,
+    utf8: Utf8Mode,
+) -> Result<Vec<Hit>> {
+    let words: Vec<&str> = query.split_whitespace().collect();
+    if words.is_empty() {
+        return Err(Error::InvalidArgument(
+            "Search query must not be blank".to_string(),
+        ));
+    }
+    if order != SEARCH_ORDER_NEWEST && order != SEARCH_ORDER_RELEVANCE {
+        return Err(Error::InvalidArgument(format!(
+            "Unknown search order: {}",
+            order
+        )));
+    }
+
+    let filter = Filter::new().search(query).build();
+    let filters = std::slice::from_ref(&filter);
+    let mut hits = Vec::new();
+    let mut window = limit.clamp(1, 1024) as jint;
+    loop {
+        // Each window repeats the previous one, in the same order
+        let results = ndb.query(txn, filters, window)?;
+        let complete = results.len() < window as usize || window == jint::MAX;
+        hits.clear();
+        for result in &results {
+            let content = note_content(&result.note, utf8)?;
+            let Some((offset, len)) = locate(&content, query.trim(), &words) else {
+                continue;
+            };
+            hits.push(Hit {
+                key: result.note_key.as_u64(),
+                created_at: result.note.created_at(),
+                offset: offset as u32,
+                len: len as u32,
+                score: words.iter().map(|word| count(&content, word)).sum(),
+            });
+        }
+        if complete || (order == SEARCH_ORDER_NEWEST && hits.len() >= limit) {
+            break;
+        }
+        window = window.saturating_mul(2);
+    }
+
+    if order == SEARCH_ORDER_RELEVANCE {
+        hits.sort_by_key(|hit| (Reverse(hit.score), Reverse(hit.created_at)));
+    }
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+/// Byte offset and length of the whole query in `content`, or else of the
+/// earliest of its words
+fn locate(content: &str, query: &str, words: &[&str]) -> Option<(usize, usize)> {
+    find_ignore_case(content, query).or_else(|| {
+        words
+            .iter()
+            .filter_map(|word| find_ignore_case(content, word))
+            .min()
+    })
+}
+
+/// Number of non-overlapping case-insensitive occurrences of `needle`
+fn count(haystack: &str, needle: &str) -> usize {
+    let mut count = 0;
+    let mut start = 0;
+    while let Some((offset, len)) = find_ignore_case(&haystack[start..], needle) {
+        count += 1;
+        start += offset + len;
+    }
+    count
+}
+
+/// Byte offset and length in `haystack` of the first case-insensitive
+/// occurrence of `needle`
+///
+/// Characters are compared by their lowercase forms one by one, so the
+/// returned span is measured in `haystack`'s own bytes even where case
+/// mapping changes a character's encoded length.
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<(usize, usize)> {
+    if needle.is_empty() {
+        return None;
+    }
+    haystack.char_indices().find_map(|(start, _)| {
+        let mut rest = haystack[start..].char_indices();
+        for expected in needle.chars() {
+            let (_, actual) = rest.next()?;
+            if !actual.to_lowercase().eq(expected.to_lowercase()) {
+                return None;
+            }
+        }
+        let end = rest.next().map_or(haystack.len(), |(i, _)| start + i);
+        Some((start, end - start))
+    })
+}
+
+/// Fold `text` for matching regardless of case and accents
+///
+/// Applies compatibility decomposition (NFKD), lowercases, and drops
+/// combining marks, so "José", "JOSE", and "ｊｏｓｅ" all fold to "jose".
+/// Turkish dotted capital I decomposes to I plus a mark and so folds to
+/// "i"; dotless ı, which has no decomposition, is folded to "i" as well.
+pub fn fold(text: &str) -> String {
+    text.nfkd()
+        .flat_map(char::to_lowercase)
+        .filter(|&c| !is_combining_mark(c))
+        .map(|c| if c == 'ı' { 'i' } else { c })
+        .collect()
+}
+
+/// Whether either of a profile's names starts with `folded_query`, once
+/// folded
+pub fn profile_matches(folded_query: &str, names: &[Option<&str>]) -> bool {
+    names
+        .iter()
+        .flatten()
+        .any(|name| fold(name.trim_start()).starts_with(folded_query))
+}
@@ -0,0 +1,69 @@
+//! Ingestion statistics for nostrdb-jni
+//!
+//! Every ingestion entry point records what happened to each event it is
+//! handed in the [`IngestStats`] of its database handle, so operators can
+//! read running totals without wrapping every call. Blank lines and relay
+//! messages other than `EVENT` are not events and aren't counted.
+//!
+//! The counters are independent atomics, updated from whichever thread
+//! ingests: each total is exact, but a snapshot taken while other threads
+//! ingest may count an event in one total and not yet in another.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Running ingestion totals for one database handle
+#[derive(Default)]
+pub struct IngestStats {
+    /// Events nostrdb queued
+    accepted: AtomicU64,
+    /// Events found to be stored already, or repeated within a batch
+    duplicates: AtomicU64,
+    /// Events that failed a check or that nostrdb refused
+    rejected: AtomicU64,
+    /// UTF-8 bytes of the accepted events
+    bytes: AtomicU64,
+}
+
+impl IngestStats {
+    /// Record an event of `bytes` bytes that nostrdb queued
+    pub fn accepted(&self, bytes: usize) {
+        self.accepted.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Record an event that was already stored
+    pub fn duplicate(&self) {
+        self.duplicates.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record an event that failed a check or that nostrdb refused
+    pub fn rejected(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record the result of handing an event of `bytes` bytes to nostrdb
+    pub fn record<T, E>(&self, result: &std::result::Result<T, E>, bytes: usize) {
+        match result {
+            Ok(_) => self.accepted(bytes),
+            Err(_) => self.rejected(),
+        }
+    }
+
+    /// The totals as [accepted, duplicates, rejected, bytes]
+    pub fn snapshot(&self) -> [u64; 4] {
+        [
+            self.accepted.load(Ordering::Relaxed),
+            self.duplicates.load(Ordering::Relaxed),
+            self.rejected.load(Ordering::Relaxed),
+            self.bytes.load(Ordering::Relaxed),
+        ]
+    }
+
+    /// Set every total back to zero
+    pub fn reset(&self) {
+        self.accepted.store(0, Ordering::Relaxed);
+        self.duplicates.store(0, Ordering::Relaxed);
+        self.rejected.store(0, Ordering::Relaxed);
+        self.bytes.store(0, Ordering::Relaxed);
+    }
+}
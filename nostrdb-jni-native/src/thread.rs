@@ -0,0 +1,52 @@
+//! NIP-10 threads for nostrdb-jni
+//!
+//! `queryThread` fetches the text notes whose `e` tags reference a root
+//! event and splits them into direct replies to the root and replies
+//! further down the thread, by the event each note replies to.
+
+use nostrdb::{Filter, Note};
+
+use crate::mute::tag_id;
+
+/// Filter for the text notes that reference `root` in an `e` tag
+pub fn replies_filter(root: &[u8; 32]) -> Filter {
+    Filter::new().kinds([1]).event(root).build()
+}
+
+/// The event a note replies to, per NIP-10
+///
+/// If any `e` tag has a marker, the note's "reply" tag names its parent,
+/// or its "root" tag for a top-level reply; unmarked tags are ignored.
+/// Otherwise the deprecated positional convention applies and the last
+/// `e` tag is the parent. "mention" tags never name the parent.
+pub fn parent(note: &Note) -> Option<[u8; 32]> {
+    let mut marked = false;
+    let mut root = None;
+    let mut reply = None;
+    let mut last = None;
+    for tag in note.tags().iter() {
+        if tag.get_str(0) != Some("e") {
+            continue;
+        }
+        let Some(id) = tag_id(&tag, 1) else {
+            continue;
+        };
+        match tag.get_str(3) {
+            Some("root") => {
+                marked = true;
+                root.get_or_insert(id);
+            }
+            Some("reply") => {
+                marked = true;
+                reply.get_or_insert(id);
+            }
+            Some("mention") => marked = true,
+            _ => last = Some(id),
+        }
+    }
+    if marked {
+        reply.or(root)
+    } else {
+        last
+    }
+}
@@ -4,8 +4,8 @@
 //! including exception throwing, type conversions, pointer handling,
 //! and panic safety for FFI boundaries.
 
-use jni::objects::{JByteArray, JString};
-use jni::sys::{jbyteArray, jlong};
+use jni::objects::{JByteArray, JObject, JString, JThrowable, JValue};
+use jni::sys::{jbyteArray, jlong, jlongArray, jobjectArray};
 use jni::JNIEnv;
 use std::panic::{catch_unwind, AssertUnwindSafe};
 
@@ -17,16 +17,42 @@ use crate::error::{Error, Result};
 /// * `env` - The JNI environment
 /// * `error` - The error to throw as an exception
 pub fn throw_exception(env: &mut JNIEnv, error: &Error) {
+    // An exception thrown by a Java callback is already pending; let it
+    // propagate instead of replacing it
+    if env.exception_check().unwrap_or(false) {
+        return;
+    }
+
     let class = error.exception_class();
     let message = error.to_string();
 
-    if let Err(e) = env.throw_new(class, &message) {
+    let thrown = match error.note_key() {
+        Some(key) => throw_with_key(env, class, &message, key),
+        None => env.throw_new(class, &message),
+    };
+    if let Err(e) = thrown {
         // If we can't throw the specific exception, try a generic RuntimeException
         tracing::error!("Failed to throw {}: {}. Attempting RuntimeException", class, e);
         let _ = env.throw_new("java/lang/RuntimeException", &message);
     }
 }
 
+/// Throw an exception constructed with a message and a note key
+fn throw_with_key(
+    env: &mut JNIEnv,
+    class: &str,
+    message: &str,
+    key: u64,
+) -> jni::errors::Result<()> {
+    let message = env.new_string(message)?;
+    let exception = env.new_object(
+        class,
+        "(Ljava/lang/String;J)V",
+        &[JValue::Object(&message), JValue::Long(key as jlong)],
+    )?;
+    env.throw(JThrowable::from(exception))
+}
+
 /// Convert a Java string to a Rust String
 ///
 /// # Arguments
@@ -71,6 +97,38 @@ pub fn rust_bytes_to_java(env: &mut JNIEnv, bytes: &[u8]) -> jbyteArray {
     }
 }
 
+/// Convert a slice of Rust longs to a Java long array
+///
+/// # Arguments
+/// * `env` - The JNI environment
+/// * `longs` - The values to convert
+///
+/// # Returns
+/// The Java long[] as a raw pointer, or an error if allocation fails
+pub fn rust_longs_to_java(env: &mut JNIEnv, longs: &[jlong]) -> Result<jlongArray> {
+    let arr = env.new_long_array(longs.len() as i32)?;
+    env.set_long_array_region(&arr, 0, longs)?;
+    Ok(arr.into_raw())
+}
+
+/// Convert a slice of Rust strings to a Java String array
+///
+/// # Arguments
+/// * `env` - The JNI environment
+/// * `strings` - The strings to convert
+///
+/// # Returns
+/// The Java String[] as a raw pointer, or an error if allocation fails
+pub fn rust_strings_to_java(env: &mut JNIEnv, strings: &[String]) -> Result<jobjectArray> {
+    let arr = env.new_object_array(strings.len() as i32, "java/lang/String", JObject::null())?;
+    for (i, s) in strings.iter().enumerate() {
+        let js = env.new_string(s)?;
+        env.set_object_array_element(&arr, i as i32, &js)?;
+        env.delete_local_ref(js)?;
+    }
+    Ok(arr.into_raw())
+}
+
 /// Convert a Java byte array to a 32-byte array (for event IDs and pubkeys)
 ///
 /// # Arguments
@@ -89,84 +147,199 @@ pub fn java_bytes_to_32(env: &mut JNIEnv, arr: &JByteArray) -> Result<[u8; 32]>
     Ok(result)
 }
 
-/// Safely cast a jlong to a pointer type
+/// Convert a Java byte array of concatenated 32-byte values (event IDs or
+/// pubkeys) to a Vec of 32-byte arrays
 ///
 /// # Arguments
-/// * `ptr` - The jlong pointer value
-/// * `name` - Name of the pointer for error messages
+/// * `env` - The JNI environment
+/// * `arr` - The Java byte array: [value1:32][value2:32]...
 ///
 /// # Returns
-/// A reference to the pointed value, or an error if null
+/// The 32-byte values, or an error if the length is not a multiple of 32
+pub fn java_bytes_to_32_vec(env: &mut JNIEnv, arr: &JByteArray) -> Result<Vec<[u8; 32]>> {
+    let bytes = java_bytes_to_rust(env, arr)?;
+    if bytes.len() % 32 != 0 {
+        return Err(Error::InvalidIdLength(bytes.len()));
+    }
+    Ok(bytes
+        .chunks_exact(32)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect())
+}
+
+/// Kind of object behind a native handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandleKind {
+    Ndb,
+    Transaction,
+    Config,
+    FilterBuilder,
+    Filter,
+    StableFilterBuilder,
+    QueryCursor,
+    CancelToken,
+    NoteBuilder,
+    Note,
+}
+
+impl HandleKind {
+    const ALL: [HandleKind; 10] = [
+        HandleKind::Ndb,
+        HandleKind::Transaction,
+        HandleKind::Config,
+        HandleKind::FilterBuilder,
+        HandleKind::Filter,
+        HandleKind::StableFilterBuilder,
+        HandleKind::QueryCursor,
+        HandleKind::CancelToken,
+        HandleKind::NoteBuilder,
+        HandleKind::Note,
+    ];
+
+    fn tag(self) -> u64 {
+        HANDLE_MAGIC | (self as u64 + 1)
+    }
+}
+
+impl std::fmt::Display for HandleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            HandleKind::Ndb => "Ndb",
+            HandleKind::Transaction => "Transaction",
+            HandleKind::Config => "NdbConfig",
+            HandleKind::FilterBuilder => "FilterBuilder",
+            HandleKind::Filter => "Filter",
+            HandleKind::StableFilterBuilder => "stable FilterBuilder",
+            HandleKind::QueryCursor => "QueryCursor",
+            HandleKind::CancelToken => "CancelToken",
+            HandleKind::NoteBuilder => "NoteBuilder",
+            HandleKind::Note => "NoteHandle",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Types handed to Java as native handles
+///
+/// Every handle is allocated with a tag identifying its kind, so passing
+/// one kind where another is expected (e.g. a built Filter to a builder
+/// function) throws IllegalStateException instead of reinterpreting memory.
+/// The tag is only read from live handles: a freed handle's memory may
+/// already be reused, so using one after it is freed is not detected.
+pub trait Handle {
+    const KIND: HandleKind;
+}
+
+/// Marks a word as a handle tag ("NDBJ")
+const HANDLE_MAGIC: u64 = 0x4e44_424a_0000_0000;
+
+/// Heap layout of a handle; the tag comes first so it can be read
+/// without knowing the payload type
+#[repr(C)]
+struct Tagged<T> {
+    tag: u64,
+    value: T,
+}
+
+/// Check that `ptr` is a non-null handle of kind `T::KIND`
 ///
 /// # Safety
-/// The caller must ensure the pointer is valid and properly aligned
-pub unsafe fn ptr_to_ref<'a, T>(ptr: jlong, name: &'static str) -> Result<&'a T> {
+/// A non-null `ptr` must have been created by `box_to_ptr` (of any kind)
+unsafe fn checked<T: Handle>(ptr: jlong, name: &'static str) -> Result<*mut Tagged<T>> {
     if ptr == 0 {
         return Err(Error::NullPointer(name));
     }
-    Ok(&*(ptr as *const T))
+    let tag = *(ptr as *const u64);
+    if tag != T::KIND.tag() {
+        let actual = match HandleKind::ALL.iter().find(|kind| kind.tag() == tag) {
+            Some(kind) => format!("a {} handle", kind),
+            None => "an unknown pointer".to_string(),
+        };
+        return Err(Error::InvalidState(format!(
+            "Expected a {} handle for {}, got {}",
+            T::KIND,
+            name,
+            actual
+        )));
+    }
+    Ok(ptr as *mut Tagged<T>)
 }
 
-/// Safely cast a jlong to a mutable pointer type
+/// Safely cast a jlong handle to a reference
 ///
 /// # Arguments
 /// * `ptr` - The jlong pointer value
 /// * `name` - Name of the pointer for error messages
 ///
 /// # Returns
-/// A mutable reference to the pointed value, or an error if null
+/// A reference to the pointed value, or an error if null or of the wrong kind
 ///
 /// # Safety
-/// The caller must ensure the pointer is valid and properly aligned
-#[allow(dead_code)]
-pub unsafe fn ptr_to_mut<'a, T>(ptr: jlong, name: &'static str) -> Result<&'a mut T> {
-    if ptr == 0 {
-        return Err(Error::NullPointer(name));
-    }
-    Ok(&mut *(ptr as *mut T))
+/// The caller must ensure the pointer was created by `box_to_ptr` and hasn't been freed
+pub unsafe fn ptr_to_ref<'a, T: Handle>(ptr: jlong, name: &'static str) -> Result<&'a T> {
+    Ok(&(*checked::<T>(ptr, name)?).value)
 }
 
-/// Box a value and return it as a jlong pointer
+/// Safely cast a jlong handle to a mutable reference
+///
+/// # Arguments
+/// * `ptr` - The jlong pointer value
+/// * `name` - Name of the pointer for error messages
+///
+/// # Returns
+/// A mutable reference to the pointed value, or an error if null or of the wrong kind
+///
+/// # Safety
+/// The caller must ensure the pointer was created by `box_to_ptr` and hasn't been freed
+pub unsafe fn ptr_to_mut<'a, T: Handle>(ptr: jlong, name: &'static str) -> Result<&'a mut T> {
+    Ok(&mut (*checked::<T>(ptr, name)?).value)
+}
+
+/// Box a value and return it as a tagged jlong handle
 ///
 /// # Arguments
 /// * `value` - The value to box
 ///
 /// # Returns
 /// A jlong representing the pointer to the boxed value
-pub fn box_to_ptr<T>(value: T) -> jlong {
-    Box::into_raw(Box::new(value)) as jlong
+pub fn box_to_ptr<T: Handle>(value: T) -> jlong {
+    Box::into_raw(Box::new(Tagged {
+        tag: T::KIND.tag(),
+        value,
+    })) as jlong
 }
 
-/// Unbox a pointer and return the owned value
+/// Free a handle and return the owned value
 ///
 /// # Arguments
 /// * `ptr` - The jlong pointer value
 /// * `name` - Name of the pointer for error messages
 ///
 /// # Returns
-/// The owned boxed value, or an error if null
+/// The owned value, or an error if null or of the wrong kind (in which
+/// case nothing is freed)
 ///
 /// # Safety
 /// The caller must ensure the pointer was created by `box_to_ptr` and hasn't been freed
-#[allow(dead_code)]
-pub unsafe fn ptr_to_box<T>(ptr: jlong, name: &'static str) -> Result<Box<T>> {
-    if ptr == 0 {
-        return Err(Error::NullPointer(name));
-    }
-    Ok(Box::from_raw(ptr as *mut T))
+pub unsafe fn take_ptr<T: Handle>(ptr: jlong, name: &'static str) -> Result<T> {
+    Ok(Box::from_raw(checked::<T>(ptr, name)?).value)
 }
 
-/// Drop a boxed value by pointer
+/// Drop a handle by pointer
+///
+/// Dropping a null handle does nothing.
 ///
 /// # Arguments
 /// * `ptr` - The jlong pointer value
+/// * `name` - Name of the pointer for error messages
 ///
 /// # Safety
 /// The caller must ensure the pointer was created by `box_to_ptr` and hasn't been freed
-pub unsafe fn drop_ptr<T>(ptr: jlong) {
+pub unsafe fn drop_ptr<T: Handle>(ptr: jlong, name: &'static str) -> Result<()> {
     if ptr != 0 {
-        let _ = Box::from_raw(ptr as *mut T);
+        drop(take_ptr::<T>(ptr, name)?);
     }
+    Ok(())
 }
 
 /// Execute a closure and handle errors by throwing Java exceptions
@@ -0,0 +1,196 @@
+//! Event verification for nostrdb-jni
+//!
+//! `verifyEvent` checks an event received outside of a relay subscription
+//! without ingesting it: the id is recomputed over the NIP-01 serialization
+//! `[0,pubkey,created_at,kind,tags,content]` and compared with the event's
+//! `id`, then the BIP-340 signature is checked over that id.
+//! `verifyNoteById` runs the same checks on a stored note's JSON.
+//!
+//! `computeEventId` exposes the id computation on its own, for events
+//! assembled in Java that still need an id before they are signed.
+
+use jni::sys::jint;
+use secp256k1::{schnorr::Signature, Message, XOnlyPublicKey, SECP256K1};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::error::Error;
+
+/// The event is valid
+pub const VERIFY_OK: jint = 0;
+/// The `id` field doesn't match the event's content
+pub const VERIFY_BAD_ID: jint = 1;
+/// The signature doesn't verify for the id and pubkey
+pub const VERIFY_BAD_SIG: jint = 2;
+/// The event isn't a well-formed NIP-01 event
+pub const VERIFY_MALFORMED: jint = 3;
+
+/// Why an event failed verification, with a message for strict mode
+#[derive(Debug)]
+pub enum Failure {
+    BadId(String),
+    BadSig(String),
+    Malformed(String),
+}
+
+impl Failure {
+    /// The code returned by the non-strict natives
+    pub fn code(&self) -> jint {
+        match self {
+            Failure::BadId(_) => VERIFY_BAD_ID,
+            Failure::BadSig(_) => VERIFY_BAD_SIG,
+            Failure::Malformed(_) => VERIFY_MALFORMED,
+        }
+    }
+
+    /// What is wrong with the event
+    pub fn message(&self) -> &str {
+        match self {
+            Failure::BadId(message) | Failure::BadSig(message) | Failure::Malformed(message) => {
+                message
+            }
+        }
+    }
+}
+
+/// Thrown in strict mode as IllegalArgumentException
+impl From<Failure> for Error {
+    fn from(failure: Failure) -> Self {
+        match failure {
+            Failure::BadId(message) | Failure::BadSig(message) | Failure::Malformed(message) => {
+                Error::InvalidArgument(message)
+            }
+        }
+    }
+}
+
+/// Verify an event's id and signature
+pub fn verify_json(json: &str) -> Result<(), Failure> {
+    verify_event(&parse(json)?)
+}
+
+/// Verify the id and signature of a parsed event object
+pub fn verify_event(event: &Map<String, Value>) -> Result<(), Failure> {
+    let id: [u8; 32] = hex_field(event, "id")?;
+    let pubkey: [u8; 32] = hex_field(event, "pubkey")?;
+    let sig: [u8; 64] = hex_field(event, "sig")?;
+    let computed = event_id(event)?;
+    if computed != id {
+        return Err(Failure::BadId(format!(
+            "Event id {} does not match its content, which hashes to {}",
+            hex::encode(id),
+            hex::encode(computed)
+        )));
+    }
+
+    let pubkey = XOnlyPublicKey::from_slice(&pubkey).map_err(|_| {
+        Failure::BadSig(format!(
+            "Pubkey {} is not a valid public key",
+            hex::encode(pubkey)
+        ))
+    })?;
+    let sig = Signature::from_slice(&sig)
+        .map_err(|_| Failure::BadSig("Signature is not a valid BIP-340 signature".to_string()))?;
+    SECP256K1
+        .verify_schnorr(&sig, &Message::from_digest(id), &pubkey)
+        .map_err(|_| {
+            Failure::BadSig(format!(
+                "Signature does not verify for event {}",
+                hex::encode(id)
+            ))
+        })
+}
+
+/// The `id` a parsed event object claims, without checking it
+pub fn event_claimed_id(event: &Map<String, Value>) -> Result<[u8; 32], Failure> {
+    hex_field(event, "id")
+}
+
+/// Compute the id of an event JSON, ignoring its `id` and `sig` if present
+pub fn compute_id(json: &str) -> Result<[u8; 32], Failure> {
+    event_id(&parse(json)?)
+}
+
+/// SHA-256 of the event's NIP-01 serialization
+fn event_id(event: &Map<String, Value>) -> Result<[u8; 32], Failure> {
+    let pubkey: [u8; 32] = hex_field(event, "pubkey")?;
+    let created_at = event
+        .get("created_at")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| malformed("created_at must be a non-negative integer"))?;
+    let kind = event
+        .get("kind")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| malformed("kind must be a non-negative integer"))?;
+    let tags = event
+        .get("tags")
+        .filter(|tags| is_tag_list(tags))
+        .ok_or_else(|| malformed("tags must be an array of arrays of strings"))?;
+    let content = event
+        .get("content")
+        .and_then(Value::as_str)
+        .ok_or_else(|| malformed("content must be a string"))?;
+
+    // serde_json escapes strings as NIP-01 prescribes
+    let serialized =
+        serde_json::to_string(&(0, hex::encode(pubkey), created_at, kind, tags, content))
+            .map_err(|e| Failure::Malformed(e.to_string()))?;
+    Ok(Sha256::digest(serialized.as_bytes()).into())
+}
+
+/// Parse an event JSON object
+fn parse(json: &str) -> Result<Map<String, Value>, Failure> {
+    match serde_json::from_str(json) {
+        Ok(Value::Object(event)) => Ok(event),
+        Ok(_) => Err(malformed("Event must be a JSON object")),
+        Err(e) => Err(Failure::Malformed(format!(
+            "Event is not valid JSON: {}",
+            e
+        ))),
+    }
+}
+
+/// Parse an event JSON object, unwrapping it from a relay
+/// (`["EVENT", subid, {...}]`) or client (`["EVENT", {...}]`) message
+pub fn parse_wrapped(json: &str) -> Result<Map<String, Value>, Failure> {
+    match serde_json::from_str(json) {
+        Ok(Value::Object(event)) => Ok(event),
+        Ok(Value::Array(mut message))
+            if message.first().and_then(Value::as_str) == Some("EVENT") =>
+        {
+            match message.pop() {
+                Some(Value::Object(event)) => Ok(event),
+                _ => Err(malformed("Event must be a JSON object")),
+            }
+        }
+        Ok(_) => Err(malformed("Event must be a JSON object")),
+        Err(e) => Err(Failure::Malformed(format!(
+            "Event is not valid JSON: {}",
+            e
+        ))),
+    }
+}
+
+fn malformed(message: &str) -> Failure {
+    Failure::Malformed(message.to_string())
+}
+
+/// Decode a fixed-length hex string field
+fn hex_field<const N: usize>(event: &Map<String, Value>, name: &str) -> Result<[u8; N], Failure> {
+    let mut bytes = [0u8; N];
+    event
+        .get(name)
+        .and_then(Value::as_str)
+        .and_then(|value| hex::decode_to_slice(value, &mut bytes).ok())
+        .ok_or_else(|| Failure::Malformed(format!("{} must be {} hex characters", name, N * 2)))?;
+    Ok(bytes)
+}
+
+fn is_tag_list(tags: &Value) -> bool {
+    tags.as_array().is_some_and(|tags| {
+        tags.iter().all(|tag| {
+            tag.as_array()
+                .is_some_and(|elements| elements.iter().all(Value::is_string))
+        })
+    })
+}
@@ -0,0 +1,110 @@
+//! Zap totals for nostrdb-jni
+//!
+//! `sumZaps` adds up a note's NIP-57 zap receipts natively, so Java gets
+//! the total without fetching or parsing any receipt.
+//!
+//! A receipt's amount comes from the `amount` tag of the zap request in
+//! its `description` tag, falling back to the amount of its `bolt11`
+//! invoice. Receipts with neither are counted as unparsable.
+
+use nostrdb::{Filter, Note};
+use serde_json::Value;
+
+/// Kind of a zap receipt
+pub const ZAP_RECEIPT_KIND: u64 = 9735;
+
+/// Millisats per bitcoin, the unit of an invoice amount without multiplier
+const MSATS_PER_BTC: u64 = 100_000_000_000;
+
+/// Running total of zap receipts
+#[derive(Default)]
+pub struct ZapTotal {
+    pub millisats: u64,
+    /// Receipts whose amount is included
+    pub counted: u32,
+    /// Receipts whose amount couldn't be parsed
+    pub unparsable: u32,
+}
+
+impl ZapTotal {
+    /// Add a receipt to the total
+    pub fn add(&mut self, receipt: &Note) {
+        match amount(receipt) {
+            Some(millisats) => {
+                self.millisats = self.millisats.saturating_add(millisats);
+                self.counted += 1;
+            }
+            None => self.unparsable += 1,
+        }
+    }
+
+    /// Serialize as [millisats:8][count:4][unparsable:4]
+    pub fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.millisats.to_le_bytes());
+        buf.extend_from_slice(&self.counted.to_le_bytes());
+        buf.extend_from_slice(&self.unparsable.to_le_bytes());
+    }
+}
+
+/// Filter for the zap receipts that reference `id` in an `e` tag
+pub fn receipts_filter(id: &[u8; 32]) -> Filter {
+    Filter::new().kinds([ZAP_RECEIPT_KIND]).event(id).build()
+}
+
+/// Amount of a zap receipt in millisats
+fn amount(receipt: &Note) -> Option<u64> {
+    let mut description = None;
+    let mut bolt11 = None;
+    for tag in receipt.tags().iter() {
+        match tag.get_str(0) {
+            Some("description") => description = description.or(tag.get_str(1)),
+            Some("bolt11") => bolt11 = bolt11.or(tag.get_str(1)),
+            _ => {}
+        }
+    }
+    description
+        .and_then(requested_amount)
+        .or_else(|| bolt11.and_then(invoice_amount))
+}
+
+/// The `amount` tag of a zap request, given as event JSON
+fn requested_amount(request: &str) -> Option<u64> {
+    let request: Value = serde_json::from_str(request).ok()?;
+    request.get("tags")?.as_array()?.iter().find_map(|tag| {
+        let tag = tag.as_array()?;
+        if tag.first()?.as_str()? != "amount" {
+            return None;
+        }
+        tag.get(1)?.as_str()?.parse().ok()
+    })
+}
+
+/// The amount of a BOLT 11 invoice in millisats
+///
+/// Read from the human-readable part: `ln`, a currency prefix, then the
+/// amount with an optional multiplier, up to the last `1`. Invoices
+/// without an amount have none to report.
+fn invoice_amount(invoice: &str) -> Option<u64> {
+    let invoice = invoice.to_ascii_lowercase();
+    let invoice = invoice.strip_prefix("lightning:").unwrap_or(&invoice);
+    let hrp = invoice[..invoice.rfind('1')?].strip_prefix("ln")?;
+    let amount = hrp.trim_start_matches(|c: char| c.is_ascii_lowercase());
+    let (digits, multiplier) = match amount.strip_suffix(|c: char| c.is_ascii_lowercase()) {
+        Some(digits) => (digits, amount.chars().last()),
+        None => (amount, None),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let value: u64 = digits.parse().ok()?;
+    match multiplier {
+        None => value.checked_mul(MSATS_PER_BTC),
+        Some('m') => value.checked_mul(MSATS_PER_BTC / 1_000),
+        Some('u') => value.checked_mul(MSATS_PER_BTC / 1_000_000),
+        Some('n') => value.checked_mul(MSATS_PER_BTC / 1_000_000_000),
+        // A pico-bitcoin is a tenth of a millisat
+        Some('p') if value.is_multiple_of(10) => Some(value / 10),
+        _ => None,
+    }
+}